@@ -40,6 +40,15 @@ pub enum QuestionsCommands {
         /// Path to a file containing the answers in JSON format.
         path: String,
     },
+    /// Save the answers given during this session.
+    ///
+    /// The answers (except the ones for questions with password) are written in the same format
+    /// used by the "answers" command. The ones saved to the default location are loaded
+    /// automatically by the next installations.
+    SaveAnswers {
+        /// File to write, relative to /etc/agama.d (default: answers.json).
+        path: Option<String>,
+    },
     /// Prints the list of questions that are waiting for an answer in JSON format
     List,
     /// Reads a question definition in JSON from stdin and prints the response when it is answered.
@@ -74,6 +83,15 @@ async fn set_answers(proxy: Questions1Proxy<'_>, path: String) -> Result<(), Ser
         .map_err(|e| e.into())
 }
 
+async fn save_answers(
+    proxy: Questions1Proxy<'_>,
+    path: Option<String>,
+) -> Result<(), ServiceError> {
+    let count = proxy.save_answers(&path.unwrap_or_default()).await?;
+    println!("{} answers saved", count);
+    Ok(())
+}
+
 async fn list_questions(client: BaseHTTPClient) -> Result<(), ServiceError> {
     let client = HTTPClient::new(client)?;
     let questions = client.list_questions().await?;
@@ -114,6 +132,7 @@ pub async fn run(
     match subcommand {
         QuestionsCommands::Mode(value) => set_mode(proxy, value.value).await,
         QuestionsCommands::Answers { path } => set_answers(proxy, path).await,
+        QuestionsCommands::SaveAnswers { path } => save_answers(proxy, path).await,
         QuestionsCommands::List => list_questions(client).await,
         QuestionsCommands::Ask => ask_question(client).await,
     }
@@ -0,0 +1,714 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the bootloader settings model.
+
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+/// Bootloader settings for the installed system.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BootloaderSettings {
+    /// Bootloader to install. If it is not set, the product default is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bootloader: Option<BootloaderType>,
+    /// Seconds to wait in the boot menu before booting the default entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u32>,
+    /// Whether to show the boot menu.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_menu: Option<bool>,
+    /// Kernel parameters to append to the ones set by Agama.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_kernel_params: Vec<String>,
+    /// Kernel crash dump settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kdump: Option<KdumpSettings>,
+    /// Kernel modules to load or blacklist in the installed system.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kernel_modules: Option<KernelModulesSettings>,
+    /// Kernel tunables (e.g., "vm.swappiness" => "10") written to the sysctl.d directory of the
+    /// installed system. Numeric values are accepted too.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "crate::sysctl::deserialize"
+    )]
+    pub sysctl: BTreeMap<String, String>,
+    /// Password to protect the boot entries from being edited (GRUB only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<BootloaderPassword>,
+    /// Whether to install a Secure Boot capable setup (shim and signed kernel). If it is not set,
+    /// it follows the Secure Boot state of the firmware.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secure_boot: Option<bool>,
+}
+
+/// Prefix of the GRUB PBKDF2 hashes.
+pub const GRUB_PBKDF2_PREFIX: &str = "grub.pbkdf2.sha512.";
+
+fn default_superuser() -> String {
+    "root".to_string()
+}
+
+/// GRUB superuser and password.
+///
+/// The password can be given in plain text (it is hashed by Agama) or already hashed. Neither of
+/// them is reported back: only the user and whether the protection is enabled.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BootloaderPassword {
+    /// GRUB superuser.
+    #[serde(default = "default_superuser")]
+    pub user: String,
+    /// Password in plain text. It is never serialized.
+    #[serde(default, skip_serializing)]
+    #[schema(write_only)]
+    pub password: Option<String>,
+    /// PBKDF2 hash, as generated by `grub2-mkpasswd-pbkdf2`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(write_only)]
+    pub hashed_password: Option<String>,
+    /// Whether the protection is enabled (read only).
+    #[serde(
+        default,
+        skip_deserializing,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    #[schema(read_only)]
+    pub enabled: bool,
+}
+
+// Do not leak the password in the logs.
+impl fmt::Debug for BootloaderPassword {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BootloaderPassword")
+            .field("user", &self.user)
+            .field("password", &self.password.as_ref().map(|_| "<hidden>"))
+            .field(
+                "hashed_password",
+                &self.hashed_password.as_ref().map(|_| "<hidden>"),
+            )
+            .finish()
+    }
+}
+
+impl BootloaderPassword {
+    /// Returns the list of problems found in the password settings.
+    pub fn validate(&self) -> Vec<String> {
+        let mut issues = vec![];
+        if self.user.is_empty()
+            || !self
+                .user
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            issues.push(format!("Invalid bootloader superuser: '{}'", self.user));
+        }
+        match (&self.password, &self.hashed_password) {
+            (Some(_), Some(_)) => issues.push(
+                "The bootloader password must be given either in plain text or hashed, not both"
+                    .to_string(),
+            ),
+            (Some(password), None) if password.is_empty() => {
+                issues.push("The bootloader password cannot be empty".to_string())
+            }
+            (None, Some(hash)) if !is_valid_grub_hash(hash) => {
+                issues.push("The bootloader password hash is not a GRUB PBKDF2 hash".to_string())
+            }
+            (None, None) => issues.push("Missing bootloader password".to_string()),
+            _ => {}
+        }
+        issues
+    }
+
+    /// Hides the password and the hash, keeping the user.
+    pub fn redact(&mut self) {
+        self.enabled = self.password.is_some() || self.hashed_password.is_some();
+        self.password = None;
+        self.hashed_password = None;
+    }
+}
+
+/// Whether the given string looks like a GRUB PBKDF2 hash (grub.pbkdf2.sha512.ITERATIONS.SALT.HASH).
+fn is_valid_grub_hash(hash: &str) -> bool {
+    let Some(rest) = hash.strip_prefix(GRUB_PBKDF2_PREFIX) else {
+        return false;
+    };
+    let parts: Vec<_> = rest.split('.').collect();
+    let [iterations, salt, hash] = parts.as_slice() else {
+        return false;
+    };
+    iterations.parse::<u32>().is_ok_and(|i| i > 0)
+        && [salt, hash]
+            .iter()
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Memory reserved for the crash kernel when it is not specified, in MiB.
+pub const DEFAULT_CRASHKERNEL_MIB: u64 = 256;
+/// Reservations below this size may not be enough to save a crash dump, in MiB.
+const MIN_CRASHKERNEL_MIB: u64 = 192;
+const CRASHKERNEL_PARAM: &str = "crashkernel=";
+
+/// Kernel crash dump (kdump) settings.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct KdumpSettings {
+    /// Whether to enable the kdump service.
+    pub enabled: bool,
+    /// Memory reserved for the crash kernel, in MiB.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reserved_memory: Option<u64>,
+}
+
+impl KdumpSettings {
+    /// Returns the memory to reserve for the crash kernel, in MiB.
+    pub fn reserved_memory(&self) -> u64 {
+        self.reserved_memory.unwrap_or(DEFAULT_CRASHKERNEL_MIB)
+    }
+}
+
+/// Kernel modules configuration for the installed system.
+///
+/// It is written to the `modules-load.d` and `modprobe.d` directories of the target system.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct KernelModulesSettings {
+    /// Modules to load on boot.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub load: Vec<KernelModule>,
+    /// Modules that must not be loaded.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blacklist: Vec<String>,
+}
+
+/// Kernel module to load on boot.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+pub struct KernelModule {
+    /// Module name (e.g., "br_netfilter").
+    pub name: String,
+    /// modprobe options for the module (e.g., "nf_conntrack_helper=1").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<String>,
+}
+
+impl KernelModulesSettings {
+    /// Returns the list of problems found in the kernel modules settings.
+    pub fn validate(&self) -> Vec<String> {
+        let mut issues = vec![];
+        for module in &self.load {
+            if !is_valid_module_name(&module.name) {
+                issues.push(format!("Invalid kernel module name: '{}'", module.name));
+            }
+            let options = module.options.as_deref().unwrap_or_default();
+            if options
+                .split_whitespace()
+                .any(|o| !is_valid_kernel_param(o))
+            {
+                issues.push(format!(
+                    "Invalid options for kernel module '{}': '{}'",
+                    module.name, options
+                ));
+            }
+            if self.blacklist.contains(&module.name) {
+                issues.push(format!(
+                    "Kernel module '{}' cannot be loaded and blacklisted",
+                    module.name
+                ));
+            }
+        }
+
+        issues.extend(
+            self.blacklist
+                .iter()
+                .filter(|n| !is_valid_module_name(n))
+                .map(|n| format!("Invalid kernel module name: '{}'", n)),
+        );
+        issues
+    }
+}
+
+impl BootloaderSettings {
+    /// Returns the list of problems found in the settings.
+    ///
+    /// An empty list means that the settings are valid.
+    ///
+    /// * `supported`: bootloaders supported by the product.
+    /// * `efi`: whether the system boots using EFI.
+    pub fn validate(&self, supported: &[BootloaderType], efi: bool) -> Vec<String> {
+        let mut issues: Vec<String> = self
+            .extra_kernel_params
+            .iter()
+            .filter(|p| !is_valid_kernel_param(p))
+            .map(|p| format!("Invalid kernel parameter: '{}'", p))
+            .collect();
+
+        if let Some(kernel_modules) = &self.kernel_modules {
+            issues.extend(kernel_modules.validate());
+        }
+        issues.extend(crate::sysctl::validate(&self.sysctl));
+
+        if let Some(password) = &self.password {
+            issues.extend(password.validate());
+            if self.bootloader == Some(BootloaderType::SystemdBoot) {
+                issues.push("The bootloader password is only supported by GRUB".to_string());
+            }
+        }
+
+        if self.secure_boot == Some(true) && !efi {
+            issues.push("Secure Boot requires EFI".to_string());
+        }
+
+        if let Some(bootloader) = self.bootloader {
+            if !supported.contains(&bootloader) {
+                issues.push(format!(
+                    "Bootloader '{}' is not supported by the product",
+                    bootloader
+                ));
+            } else if bootloader.requires_efi() && !efi {
+                issues.push(format!("Bootloader '{}' requires EFI", bootloader));
+            }
+        }
+
+        issues
+    }
+
+    /// Returns the list of problems found in the kdump settings.
+    ///
+    /// The reservation must be smaller than half of the memory.
+    ///
+    /// * `memory`: total memory in bytes (0 if unknown).
+    pub fn validate_kdump(&self, memory: u64) -> Vec<String> {
+        let Some(kdump) = self.kdump.as_ref().filter(|k| k.enabled) else {
+            return vec![];
+        };
+
+        let reserved = kdump.reserved_memory();
+        let available = memory / (1024 * 1024);
+        if reserved == 0 {
+            vec!["The crash kernel reservation cannot be 0".to_string()]
+        } else if available > 0 && reserved >= available / 2 {
+            vec![format!(
+                "The crash kernel reservation ({} MiB) must be smaller than half of the memory ({} MiB)",
+                reserved, available
+            )]
+        } else {
+            vec![]
+        }
+    }
+
+    /// Returns the warnings about the kdump settings (e.g., a too small reservation).
+    pub fn kdump_warnings(&self) -> Vec<String> {
+        match self.kdump.as_ref().filter(|k| k.enabled) {
+            Some(kdump) if kdump.reserved_memory() < MIN_CRASHKERNEL_MIB => vec![format!(
+                "The crash kernel reservation ({} MiB) might be too small (recommended: {} MiB)",
+                kdump.reserved_memory(),
+                DEFAULT_CRASHKERNEL_MIB
+            )],
+            _ => vec![],
+        }
+    }
+
+    /// Sets the `crashkernel` kernel parameter according to the kdump settings.
+    ///
+    /// Any previous `crashkernel` parameter is removed.
+    pub fn apply_kdump_params(&mut self) {
+        let Some(kdump) = &self.kdump else {
+            return;
+        };
+
+        self.extra_kernel_params
+            .retain(|p| !p.starts_with(CRASHKERNEL_PARAM));
+        if kdump.enabled {
+            self.extra_kernel_params.push(format!(
+                "{}{}M",
+                CRASHKERNEL_PARAM,
+                kdump.reserved_memory()
+            ));
+        }
+    }
+
+    /// Whether the installed system uses a Secure Boot capable setup (shim and signed kernel).
+    ///
+    /// * `firmware`: whether Secure Boot is enabled in the firmware.
+    pub fn uses_secure_boot(&self, firmware: bool) -> bool {
+        self.secure_boot.unwrap_or(firmware)
+    }
+
+    /// Returns the warnings about the choices which would not boot with Secure Boot.
+    ///
+    /// * `firmware`: whether Secure Boot is enabled in the firmware.
+    pub fn secure_boot_warnings(&self, firmware: bool) -> Vec<String> {
+        if !firmware {
+            return vec![];
+        }
+
+        let mut warnings = vec![];
+        if !self.uses_secure_boot(firmware) {
+            warnings.push(
+                "Secure Boot is enabled, but shim and the signed kernel will not be installed, so \
+                 the system will not boot unless Secure Boot is disabled"
+                    .to_string(),
+            );
+        }
+        if self.bootloader == Some(BootloaderType::SystemdBoot) {
+            warnings.push(
+                "Secure Boot is enabled and systemd-boot might not be signed, so the system might \
+                 not boot unless Secure Boot is disabled"
+                    .to_string(),
+            );
+        }
+        warnings
+    }
+
+    /// Removes the repeated kernel parameters, keeping the first occurrence.
+    pub fn dedup_kernel_params(&mut self) {
+        let mut seen = vec![];
+        self.extra_kernel_params.retain(|p| {
+            if seen.contains(p) {
+                false
+            } else {
+                seen.push(p.clone());
+                true
+            }
+        });
+    }
+}
+
+/// Bootloader types.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub enum BootloaderType {
+    /// GRUB 2.
+    #[serde(rename = "grub2")]
+    Grub2,
+    /// systemd-boot (EFI only).
+    #[serde(rename = "systemd-boot")]
+    SystemdBoot,
+}
+
+impl BootloaderType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Grub2 => "grub2",
+            Self::SystemdBoot => "systemd-boot",
+        }
+    }
+
+    /// Whether the bootloader can only be used on EFI systems.
+    pub fn requires_efi(&self) -> bool {
+        matches!(self, Self::SystemdBoot)
+    }
+}
+
+impl fmt::Display for BootloaderType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown bootloader: '{0}'")]
+pub struct UnknownBootloader(String);
+
+impl FromStr for BootloaderType {
+    type Err = UnknownBootloader;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "grub2" => Ok(Self::Grub2),
+            "systemd-boot" => Ok(Self::SystemdBoot),
+            _ => Err(UnknownBootloader(s.to_string())),
+        }
+    }
+}
+
+/// Whether the given string looks like a single kernel command-line parameter.
+///
+/// It should be a `name` or a `name=value` pair. The name must not be empty and it can only
+/// contain alphanumeric characters, dots, dashes and underscores. Spaces are only allowed in
+/// quoted values.
+fn is_valid_kernel_param(param: &str) -> bool {
+    let (name, value) = param.split_once('=').unwrap_or((param, ""));
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+    {
+        return false;
+    }
+
+    if value.chars().any(|c| c.is_control()) {
+        return false;
+    }
+
+    match value.strip_prefix('"') {
+        Some(quoted) => quoted.len() == quoted.trim_end_matches('"').len() + 1,
+        None => !value.contains(['"', ' ']),
+    }
+}
+
+/// Whether the given string looks like a kernel module name.
+///
+/// Module names can only contain alphanumeric characters, dashes and underscores.
+fn is_valid_module_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BootloaderPassword, BootloaderSettings, BootloaderType, KdumpSettings, KernelModule,
+        KernelModulesSettings,
+    };
+
+    fn settings_with(params: &[&str]) -> BootloaderSettings {
+        BootloaderSettings {
+            extra_kernel_params: params.iter().map(|p| p.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_kernel_params() {
+        let settings = settings_with(&[
+            "console=ttyS0,115200",
+            "mitigations=off",
+            "quiet",
+            "rd.driver.blacklist=nouveau",
+            "acpi_osi=\"Windows 2015\"",
+        ]);
+        assert!(settings.validate(&[], false).is_empty());
+
+        let settings = settings_with(&["", "=off", "console ttyS0", "foo=\"bar", "a$b=1"]);
+        assert_eq!(settings.validate(&[], false).len(), 5);
+    }
+
+    #[test]
+    fn test_validate_bootloader() {
+        let supported = [BootloaderType::Grub2, BootloaderType::SystemdBoot];
+        let settings = BootloaderSettings {
+            bootloader: Some(BootloaderType::SystemdBoot),
+            timeout: Some(5),
+            show_menu: Some(false),
+            ..Default::default()
+        };
+        assert!(settings.validate(&supported, true).is_empty());
+        assert_eq!(
+            settings.validate(&supported, false),
+            vec!["Bootloader 'systemd-boot' requires EFI"]
+        );
+        assert_eq!(
+            settings.validate(&[BootloaderType::Grub2], true),
+            vec!["Bootloader 'systemd-boot' is not supported by the product"]
+        );
+    }
+
+    #[test]
+    fn test_validate_kernel_modules() {
+        let mut modules = KernelModulesSettings {
+            load: vec![
+                KernelModule {
+                    name: "br_netfilter".to_string(),
+                    options: None,
+                },
+                KernelModule {
+                    name: "nf_conntrack".to_string(),
+                    options: Some("nf_conntrack_helper=1 hashsize=4096".to_string()),
+                },
+            ],
+            blacklist: vec!["nouveau".to_string()],
+        };
+        assert!(modules.validate().is_empty());
+
+        modules.load.push(KernelModule {
+            name: "../evil".to_string(),
+            options: Some("foo=\"bar".to_string()),
+        });
+        modules.load.push(KernelModule {
+            name: "nouveau".to_string(),
+            options: None,
+        });
+        modules.blacklist.push("".to_string());
+        let settings = BootloaderSettings {
+            kernel_modules: Some(modules),
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.validate(&[], false),
+            vec![
+                "Invalid kernel module name: '../evil'",
+                "Invalid options for kernel module '../evil': 'foo=\"bar'",
+                "Kernel module 'nouveau' cannot be loaded and blacklisted",
+                "Invalid kernel module name: ''",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_sysctl() {
+        let settings: BootloaderSettings = serde_json::from_str(
+            r#"{ "sysctl": { "vm.swappiness": 10, "net.ipv4.conf.eth0.rp_filter": "2" } }"#,
+        )
+        .unwrap();
+        assert_eq!(settings.sysctl["vm.swappiness"], "10");
+        assert!(settings.validate(&[], false).is_empty());
+
+        let settings: BootloaderSettings = serde_json::from_str(
+            r#"{
+                "sysctl": {
+                    "vm.swappiness": 300,
+                    "fs.file-max": 0,
+                    "net.ipv4.ip_forward": "yes",
+                    "kernel..pid": "1",
+                    "swappiness": "1",
+                    "kernel.modules_disabled": "1",
+                    "kernel.hostname": "foo\nvm.swappiness=0"
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            settings.validate(&[], false),
+            vec![
+                "The value of sysctl key 'fs.file-max' must be at least 1",
+                "Invalid sysctl key: 'kernel..pid'",
+                "Invalid value for sysctl key 'kernel.hostname': 'foo\nvm.swappiness=0'",
+                "The sysctl key 'kernel.modules_disabled' is not allowed: no kernel module could be loaded",
+                "The value of sysctl key 'net.ipv4.ip_forward' must be a number, not 'yes'",
+                "Invalid sysctl key: 'swappiness'",
+                "The value of sysctl key 'vm.swappiness' must be between 0 and 200",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dedup_kernel_params() {
+        let mut settings = settings_with(&["quiet", "console=ttyS0", "quiet", "console=tty0"]);
+        settings.dedup_kernel_params();
+        assert_eq!(
+            settings.extra_kernel_params,
+            vec!["quiet", "console=ttyS0", "console=tty0"]
+        );
+    }
+
+    #[test]
+    fn test_validate_kdump() {
+        let gib = 1024 * 1024 * 1024;
+        let mut settings = BootloaderSettings {
+            kdump: Some(KdumpSettings {
+                enabled: true,
+                reserved_memory: None,
+            }),
+            ..Default::default()
+        };
+        assert!(settings.validate_kdump(4 * gib).is_empty());
+        assert!(settings.validate_kdump(0).is_empty());
+        assert_eq!(settings.validate_kdump(gib / 2).len(), 1);
+        assert!(settings.kdump_warnings().is_empty());
+
+        settings.kdump = Some(KdumpSettings {
+            enabled: true,
+            reserved_memory: Some(128),
+        });
+        assert!(settings.validate_kdump(4 * gib).is_empty());
+        assert_eq!(settings.kdump_warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_secure_boot() {
+        let mut settings = BootloaderSettings::default();
+        assert!(settings.uses_secure_boot(true));
+        assert!(!settings.uses_secure_boot(false));
+        assert!(settings.secure_boot_warnings(true).is_empty());
+
+        settings.secure_boot = Some(false);
+        assert!(!settings.uses_secure_boot(true));
+        assert_eq!(settings.secure_boot_warnings(true).len(), 1);
+        assert!(settings.secure_boot_warnings(false).is_empty());
+
+        settings.secure_boot = Some(true);
+        settings.bootloader = Some(BootloaderType::SystemdBoot);
+        assert_eq!(settings.secure_boot_warnings(true).len(), 1);
+        assert_eq!(
+            settings.validate(&[BootloaderType::SystemdBoot], false),
+            vec![
+                "Secure Boot requires EFI",
+                "Bootloader 'systemd-boot' requires EFI"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_kdump_params() {
+        let mut settings = settings_with(&["quiet", "crashkernel=128M"]);
+        settings.kdump = Some(KdumpSettings {
+            enabled: true,
+            reserved_memory: Some(512),
+        });
+        settings.apply_kdump_params();
+        assert_eq!(
+            settings.extra_kernel_params,
+            vec!["quiet", "crashkernel=512M"]
+        );
+
+        settings.kdump = Some(KdumpSettings::default());
+        settings.apply_kdump_params();
+        assert_eq!(settings.extra_kernel_params, vec!["quiet"]);
+    }
+
+    #[test]
+    fn test_validate_password() {
+        let mut password: BootloaderPassword =
+            serde_json::from_str(r#"{ "hashedPassword": "grub.pbkdf2.sha512.10000.AB12.CD34" }"#)
+                .unwrap();
+        assert_eq!(password.user, "root");
+        assert!(password.validate().is_empty());
+
+        password.hashed_password = Some("$6$salt$hash".to_string());
+        assert_eq!(password.validate().len(), 1);
+        password.password = Some("secret".to_string());
+        assert_eq!(password.validate().len(), 1);
+        password.hashed_password = None;
+        assert!(password.validate().is_empty());
+        assert!(!format!("{:?}", password).contains("secret"));
+
+        let settings = BootloaderSettings {
+            bootloader: Some(BootloaderType::SystemdBoot),
+            password: Some(password.clone()),
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.validate(&[BootloaderType::SystemdBoot], true),
+            vec!["The bootloader password is only supported by GRUB"]
+        );
+
+        password.redact();
+        assert!(password.enabled);
+        assert_eq!(
+            serde_json::to_string(&password).unwrap(),
+            r#"{"user":"root","enabled":true}"#
+        );
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the store for the bootloader settings.
+
+use crate::base_http_client::BaseHTTPClient;
+use crate::error::ServiceError;
+
+use super::http_client::BootloaderHTTPClient;
+use super::model::BootloaderSettings;
+
+/// Loads and stores the bootloader settings from/to the HTTP service.
+pub struct BootloaderStore {
+    bootloader_client: BootloaderHTTPClient,
+}
+
+impl BootloaderStore {
+    pub fn new(client: BaseHTTPClient) -> Result<Self, ServiceError> {
+        Ok(Self {
+            bootloader_client: BootloaderHTTPClient::new(client),
+        })
+    }
+
+    pub async fn load(&self) -> Result<BootloaderSettings, ServiceError> {
+        self.bootloader_client.get_config().await
+    }
+
+    pub async fn store(&self, settings: &BootloaderSettings) -> Result<(), ServiceError> {
+        self.bootloader_client.set_config(settings).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::base_http_client::BaseHTTPClient;
+    use httpmock::prelude::*;
+    use std::error::Error;
+    use tokio::test; // without this, "error: async functions cannot be used for tests"
+
+    fn bootloader_store(mock_server_url: String) -> BootloaderStore {
+        let mut bhc = BaseHTTPClient::default();
+        bhc.base_url = mock_server_url;
+        let client = BootloaderHTTPClient::new(bhc);
+        BootloaderStore {
+            bootloader_client: client,
+        }
+    }
+
+    #[test]
+    async fn test_getting_bootloader() -> Result<(), Box<dyn Error>> {
+        let server = MockServer::start();
+        let bootloader_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/bootloader/config");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{ "extraKernelParams": ["console=ttyS0"] }"#);
+        });
+        let url = server.url("/api");
+
+        let store = bootloader_store(url);
+        let settings = store.load().await?;
+
+        let expected = BootloaderSettings {
+            extra_kernel_params: vec!["console=ttyS0".to_string()],
+            ..Default::default()
+        };
+        // main assertion
+        assert_eq!(settings, expected);
+
+        // Ensure the specified mock was called exactly one time (or fail with a detailed error description).
+        bootloader_mock.assert();
+        Ok(())
+    }
+
+    #[test]
+    async fn test_setting_bootloader() -> Result<(), Box<dyn Error>> {
+        let server = MockServer::start();
+        let bootloader_mock = server.mock(|when, then| {
+            when.method(PUT)
+                .path("/api/bootloader/config")
+                .header("content-type", "application/json")
+                .body(r#"{"timeout":5,"extraKernelParams":["mitigations=off"]}"#);
+            then.status(200);
+        });
+        let url = server.url("/api");
+
+        let store = bootloader_store(url);
+        let settings = BootloaderSettings {
+            timeout: Some(5),
+            extra_kernel_params: vec!["mitigations=off".to_string()],
+            ..Default::default()
+        };
+
+        let result = store.store(&settings).await;
+
+        // main assertion
+        result?;
+
+        // Ensure the specified mock was called exactly one time (or fail with a detailed error description).
+        bootloader_mock.assert();
+        Ok(())
+    }
+}
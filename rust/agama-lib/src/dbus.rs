@@ -73,6 +73,26 @@ macro_rules! property_from_dbus {
     };
 }
 
+/// Turns the error of reading a property which the D-Bus service does not implement into `None`.
+///
+/// It allows talking to services which do not offer some of the newest properties yet.
+///
+/// * `result`: result of reading the property.
+pub fn optional_property<T>(result: zbus::Result<T>) -> zbus::Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(zbus::Error::FDO(error)) if is_unknown_property(&error) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+fn is_unknown_property(error: &zbus::fdo::Error) -> bool {
+    matches!(
+        error,
+        zbus::fdo::Error::UnknownProperty(_) | zbus::fdo::Error::UnknownInterface(_)
+    )
+}
+
 /// Converts a hash map containing zbus non-owned values to hash map with owned ones.
 ///
 /// NOTE: we could follow a different approach like building our own type (e.g.
@@ -105,7 +125,7 @@ mod tests {
 
     use zbus::zvariant::{self, OwnedValue, Str};
 
-    use crate::dbus::{get_optional_property, get_property};
+    use crate::dbus::{get_optional_property, get_property, optional_property};
 
     #[test]
     fn test_get_property() {
@@ -136,4 +156,23 @@ mod tests {
         let device: Option<String> = get_optional_property(&data, "Device").unwrap();
         assert_eq!(device, None);
     }
+
+    #[test]
+    fn test_optional_property() {
+        let value = optional_property(Ok(1)).unwrap();
+        assert_eq!(value, Some(1));
+
+        let missing: zbus::Result<u8> = Err(zbus::fdo::Error::UnknownProperty(
+            "Property 'Sessions' not found".to_string(),
+        )
+        .into());
+        assert_eq!(optional_property(missing).unwrap(), None);
+
+        let failed: zbus::Result<u8> = Err(zbus::fdo::Error::Failed("Failed".to_string()).into());
+        assert!(optional_property(failed).is_err());
+
+        let invalid: zbus::Result<u8> =
+            Err(zbus::fdo::Error::InvalidArgs("Invalid".to_string()).into());
+        assert!(optional_property(invalid).is_err());
+    }
 }
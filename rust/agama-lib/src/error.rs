@@ -77,6 +77,10 @@ pub enum ProfileError {
     Unreachable(#[from] TransferError),
     #[error("Jsonnet evaluation failed:\n{0}")]
     EvaluationError(String),
+    #[error("AutoYaST conversion failed:\n{0}")]
+    ConversionError(String),
+    #[error("The AutoYaST profile is not a valid XML file: {0}")]
+    XmlError(String),
     #[error("I/O error")]
     InputOutputError(#[from] io::Error),
     #[error("The profile is not a valid JSON file")]
@@ -0,0 +1,67 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements a client to access Agama's D-Bus API related to the firewall configuration.
+
+use zbus::Connection;
+
+use crate::{error::ServiceError, firewall::model::FirewallSettings};
+
+use super::proxies::FirewallProxy;
+
+/// Client to connect to Agama's D-Bus API for the firewall configuration.
+#[derive(Clone)]
+pub struct FirewallClient<'a> {
+    firewall_proxy: FirewallProxy<'a>,
+}
+
+impl<'a> FirewallClient<'a> {
+    pub async fn new(connection: Connection) -> Result<FirewallClient<'a>, ServiceError> {
+        let firewall_proxy = FirewallProxy::new(&connection).await?;
+
+        Ok(Self { firewall_proxy })
+    }
+
+    /// Returns the planned firewall settings.
+    pub async fn get_config(&self) -> Result<FirewallSettings, ServiceError> {
+        let serialized_string = self.firewall_proxy.get_config().await?;
+        let settings = serde_json::from_str(serialized_string.as_str())?;
+        Ok(settings)
+    }
+
+    /// Sets the firewall settings.
+    ///
+    /// * `config`: firewall settings.
+    pub async fn set_config(&self, config: &FirewallSettings) -> Result<(), ServiceError> {
+        let serialized_string = serde_json::to_string(config)?;
+        let result = self.firewall_proxy.set_config(&serialized_string).await?;
+        if result != 0 {
+            return Err(ServiceError::UnsuccessfulAction(
+                "Set firewall config".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the firewalld services available in the target system.
+    pub async fn known_services(&self) -> Result<Vec<String>, ServiceError> {
+        Ok(self.firewall_proxy.known_services().await?)
+    }
+}
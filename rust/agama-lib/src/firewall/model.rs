@@ -0,0 +1,195 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the firewall settings model.
+
+use serde::{Deserialize, Serialize};
+
+/// Zones predefined by firewalld.
+pub const PREDEFINED_ZONES: [&str; 9] = [
+    "block", "dmz", "drop", "external", "home", "internal", "public", "trusted", "work",
+];
+
+/// Protocols that can be used when opening a port.
+pub const PROTOCOLS: [&str; 4] = ["tcp", "udp", "sctp", "dccp"];
+
+/// Firewall settings for the installed system.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FirewallSettings {
+    /// Whether the firewall is enabled. If it is not set, the product default is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Zone for the interfaces that are not assigned to any zone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_zone: Option<String>,
+    /// Services and ports to open in each zone.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub zones: Vec<FirewallZone>,
+}
+
+/// Services and ports to open in a firewall zone.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+pub struct FirewallZone {
+    /// Zone name (e.g., "public").
+    pub name: String,
+    /// Firewalld services to open (e.g., "ssh").
+    #[serde(default)]
+    pub services: Vec<String>,
+    /// Ports to open, using the "port/protocol" or "from-to/protocol" syntax (e.g., "8080/tcp").
+    #[serde(default)]
+    pub ports: Vec<String>,
+}
+
+impl FirewallSettings {
+    /// Returns the list of problems found in the settings.
+    ///
+    /// An empty list means that the settings are valid.
+    ///
+    /// * `known_services`: firewalld services available in the target system.
+    pub fn validate(&self, known_services: &[String]) -> Vec<String> {
+        let mut issues = vec![];
+
+        if let Some(zone) = &self.default_zone {
+            if !PREDEFINED_ZONES.contains(&zone.as_str())
+                && !self.zones.iter().any(|z| &z.name == zone)
+            {
+                issues.push(format!("Unknown default zone: '{}'", zone));
+            }
+        }
+
+        for zone in &self.zones {
+            if !is_valid_zone_name(&zone.name) {
+                issues.push(format!("Invalid zone name: '{}'", zone.name));
+            }
+            for service in &zone.services {
+                if !known_services.contains(service) {
+                    issues.push(format!(
+                        "Unknown service in zone {}: '{}'",
+                        zone.name, service
+                    ));
+                }
+            }
+            for port in &zone.ports {
+                if !is_valid_port(port) {
+                    issues.push(format!("Invalid port in zone {}: '{}'", zone.name, port));
+                }
+            }
+        }
+
+        issues
+    }
+
+    /// Adds the given zone, replacing the existing one with the same name.
+    pub fn set_zone(&mut self, zone: FirewallZone) {
+        match self.zones.iter_mut().find(|z| z.name == zone.name) {
+            Some(existing) => *existing = zone,
+            None => self.zones.push(zone),
+        }
+    }
+}
+
+/// Whether the zone name is valid for firewalld (up to 17 alphanumeric chars, '-' or '_').
+fn is_valid_zone_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 17
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Whether the port follows the "port/protocol" or "from-to/protocol" syntax.
+fn is_valid_port(port: &str) -> bool {
+    let Some((range, protocol)) = port.split_once('/') else {
+        return false;
+    };
+    if !PROTOCOLS.contains(&protocol) {
+        return false;
+    }
+
+    let parse = |p: &str| p.parse::<u16>().ok().filter(|p| *p > 0);
+    match range.split_once('-') {
+        Some((from, to)) => matches!((parse(from), parse(to)), (Some(f), Some(t)) if f <= t),
+        None => parse(range).is_some(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn known_services() -> Vec<String> {
+        vec!["ssh".to_string(), "http".to_string()]
+    }
+
+    #[test]
+    fn test_validate() {
+        let settings = FirewallSettings {
+            enabled: Some(true),
+            default_zone: Some("public".to_string()),
+            zones: vec![FirewallZone {
+                name: "public".to_string(),
+                services: vec!["ssh".to_string()],
+                ports: vec!["8080/tcp".to_string(), "6000-6010/udp".to_string()],
+            }],
+        };
+        assert!(settings.validate(&known_services()).is_empty());
+    }
+
+    #[test]
+    fn test_validate_wrong_settings() {
+        let settings = FirewallSettings {
+            default_zone: Some("unknown".to_string()),
+            zones: vec![FirewallZone {
+                name: "public".to_string(),
+                services: vec!["telnet".to_string()],
+                ports: vec![
+                    "8080".to_string(),
+                    "0/tcp".to_string(),
+                    "10-5/tcp".to_string(),
+                    "22/icmp".to_string(),
+                ],
+            }],
+            ..Default::default()
+        };
+        assert_eq!(settings.validate(&known_services()).len(), 6);
+    }
+
+    #[test]
+    fn test_custom_default_zone() {
+        let mut settings = FirewallSettings {
+            default_zone: Some("servers".to_string()),
+            ..Default::default()
+        };
+        settings.set_zone(FirewallZone {
+            name: "servers".to_string(),
+            ..Default::default()
+        });
+        assert!(settings.validate(&known_services()).is_empty());
+
+        settings.set_zone(FirewallZone {
+            name: "servers".to_string(),
+            services: vec!["http".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(settings.zones.len(), 1);
+        assert_eq!(settings.zones[0].services, vec!["http".to_string()]);
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! D-Bus interface proxies for: `org.opensuse.Agama.Manager1.Firewall`
+use zbus::dbus_proxy;
+
+#[dbus_proxy(
+    interface = "org.opensuse.Agama.Manager1.Firewall",
+    default_service = "org.opensuse.Agama.Manager1",
+    default_path = "/org/opensuse/Agama/Manager1"
+)]
+trait Firewall {
+    /// Set the firewall configuration (as JSON)
+    fn set_config(&self, settings: &str) -> zbus::Result<u32>;
+
+    /// Get the current firewall configuration (as JSON)
+    fn get_config(&self) -> zbus::Result<String>;
+
+    /// KnownServices property
+    #[dbus_proxy(property)]
+    fn known_services(&self) -> zbus::Result<Vec<String>>;
+}
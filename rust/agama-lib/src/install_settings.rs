@@ -22,8 +22,9 @@
 //!
 //! This module implements the mechanisms to load and store the installation settings.
 use crate::{
-    localization::LocalizationSettings, network::NetworkSettings, product::ProductSettings,
-    software::SoftwareSettings, users::UserSettings,
+    bootloader::model::BootloaderSettings, localization::LocalizationSettings,
+    network::NetworkSettings, product::ProductSettings, software::SoftwareSettings,
+    users::UserSettings,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::value::RawValue;
@@ -42,6 +43,8 @@ pub struct InstallSettings {
     #[serde(default, flatten)]
     pub user: Option<UserSettings>,
     #[serde(default)]
+    pub bootloader: Option<BootloaderSettings>,
+    #[serde(default)]
     pub software: Option<SoftwareSettings>,
     #[serde(default)]
     pub product: Option<ProductSettings>,
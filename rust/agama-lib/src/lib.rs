@@ -45,7 +45,9 @@
 
 pub mod auth;
 pub mod base_http_client;
+pub mod bootloader;
 pub mod error;
+pub mod firewall;
 pub mod install_settings;
 pub mod jobs;
 pub mod localization;
@@ -53,8 +55,10 @@ pub mod manager;
 pub mod network;
 pub mod product;
 pub mod profile;
+pub mod scripts;
 pub mod software;
 pub mod storage;
+pub mod sysctl;
 pub mod users;
 // TODO: maybe expose only clients when we have it?
 pub mod dbus;
@@ -63,6 +67,7 @@ pub mod proxies;
 mod store;
 pub use store::Store;
 pub mod questions;
+pub mod retry;
 pub mod transfer;
 use crate::error::ServiceError;
 use reqwest::{header, Client};
@@ -19,6 +19,23 @@
 // find current contact information at www.suse.com.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Locale categories which can use a different locale than the primary one.
+pub const LOCALE_CATEGORIES: [&str; 12] = [
+    "LC_CTYPE",
+    "LC_NUMERIC",
+    "LC_TIME",
+    "LC_COLLATE",
+    "LC_MONETARY",
+    "LC_MESSAGES",
+    "LC_PAPER",
+    "LC_NAME",
+    "LC_ADDRESS",
+    "LC_TELEPHONE",
+    "LC_MEASUREMENT",
+    "LC_IDENTIFICATION",
+];
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -29,6 +46,9 @@ pub struct LocaleConfig {
     pub keymap: Option<String>,
     /// Timezone for the target system
     pub timezone: Option<String>,
+    /// Locale of each category (e.g., `{ "LC_TIME": "de_DE.UTF-8" }`). When reading, all the
+    /// categories are included, using the primary locale for the ones which are not set.
+    pub categories: Option<BTreeMap<String, String>>,
     /// User-interface locale. It is actually not related to the `locales` property.
     pub ui_locale: Option<String>,
     /// User-interface locale. It is relevant only on local installations.
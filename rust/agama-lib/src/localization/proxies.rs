@@ -56,6 +56,45 @@ trait Locale {
     #[dbus_proxy(property)]
     fn set_timezone(&self, value: &str) -> zbus::Result<()>;
 
+    /// LocaleCategories property
+    ///
+    /// Locale of the categories (e.g., "LC_TIME") which do not use the primary locale.
+    #[dbus_proxy(property)]
+    fn locale_categories(&self) -> zbus::Result<std::collections::HashMap<String, String>>;
+    #[dbus_proxy(property)]
+    fn set_locale_categories(
+        &self,
+        value: std::collections::HashMap<&str, &str>,
+    ) -> zbus::Result<()>;
+
+    /// NtpServers property
+    #[dbus_proxy(property)]
+    fn ntp_servers(&self) -> zbus::Result<Vec<String>>;
+    #[dbus_proxy(property)]
+    fn set_ntp_servers(&self, value: &[&str]) -> zbus::Result<()>;
+
+    /// NtpDHCP property
+    #[dbus_proxy(property, name = "NtpDHCP")]
+    fn ntp_dhcp(&self) -> zbus::Result<String>;
+    #[dbus_proxy(property, name = "NtpDHCP")]
+    fn set_ntp_dhcp(&self, value: &str) -> zbus::Result<()>;
+
+    /// DHCPNtpServers property
+    #[dbus_proxy(property, name = "DHCPNtpServers")]
+    fn dhcp_ntp_servers(&self) -> zbus::Result<Vec<String>>;
+    #[dbus_proxy(property, name = "DHCPNtpServers")]
+    fn set_dhcp_ntp_servers(&self, value: &[&str]) -> zbus::Result<()>;
+
+    /// EffectiveNtpServers property
+    #[dbus_proxy(property)]
+    fn effective_ntp_servers(&self) -> zbus::Result<Vec<String>>;
+
+    /// LocalRTC property
+    #[dbus_proxy(property, name = "LocalRTC")]
+    fn local_rtc(&self) -> zbus::Result<bool>;
+    #[dbus_proxy(property, name = "LocalRTC")]
+    fn set_local_rtc(&self, value: bool) -> zbus::Result<()>;
+
     /// UILocale property
     #[dbus_proxy(property, name = "UILocale")]
     fn uilocale(&self) -> zbus::Result<String>;
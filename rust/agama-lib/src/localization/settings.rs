@@ -21,6 +21,7 @@
 //! Representation of the localization settings
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Localization settings for the system being installed (not the UI)
 /// FIXME: this one is close to CLI. A possible duplicate close to HTTP is LocaleConfig
@@ -33,4 +34,7 @@ pub struct LocalizationSettings {
     pub keyboard: Option<String>,
     /// like "Europe/Berlin"
     pub timezone: Option<String>,
+    /// Locales for individual categories, like { "LC_TIME": "de_DE.UTF-8" }
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub categories: Option<BTreeMap<String, String>>,
 }
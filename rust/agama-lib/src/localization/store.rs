@@ -25,6 +25,7 @@ use super::{LocalizationHTTPClient, LocalizationSettings};
 use crate::base_http_client::BaseHTTPClient;
 use crate::error::ServiceError;
 use crate::localization::model::LocaleConfig;
+use std::collections::BTreeMap;
 
 /// Loads and stores the storage settings from/to the D-Bus service.
 pub struct LocalizationStore {
@@ -62,11 +63,22 @@ impl LocalizationStore {
         let opt_language = config.locales.and_then(Self::chestburster);
         let opt_keyboard = config.keymap;
         let opt_timezone = config.timezone;
+        // only the categories which do not use the primary locale
+        let opt_categories = config
+            .categories
+            .map(|categories| {
+                categories
+                    .into_iter()
+                    .filter(|(_, locale)| Some(locale) != opt_language.as_ref())
+                    .collect::<BTreeMap<_, _>>()
+            })
+            .filter(|categories| !categories.is_empty());
 
         Ok(LocalizationSettings {
             language: opt_language,
             keyboard: opt_keyboard,
             timezone: opt_timezone,
+            categories: opt_categories,
         })
     }
 
@@ -75,11 +87,13 @@ impl LocalizationStore {
         let opt_language = settings.language.clone();
         let opt_keymap = settings.keyboard.clone();
         let opt_timezone = settings.timezone.clone();
+        let opt_categories = settings.categories.clone();
 
         let config = LocaleConfig {
             locales: opt_language.map(|s| vec![s]),
             keymap: opt_keymap,
             timezone: opt_timezone,
+            categories: opt_categories,
             ui_locale: None,
             ui_keymap: None,
         };
@@ -116,7 +130,8 @@ mod test {
                     r#"{
                     "locales": ["fr_FR.UTF-8"],
                     "keymap": "fr(dvorak)",
-                    "timezone": "Europe/Paris"
+                    "timezone": "Europe/Paris",
+                    "categories": { "LC_MESSAGES": "fr_FR.UTF-8", "LC_TIME": "de_DE.UTF-8" }
                 }"#,
                 );
         });
@@ -129,6 +144,10 @@ mod test {
             language: Some("fr_FR.UTF-8".to_owned()),
             keyboard: Some("fr(dvorak)".to_owned()),
             timezone: Some("Europe/Paris".to_owned()),
+            categories: Some(BTreeMap::from([(
+                "LC_TIME".to_owned(),
+                "de_DE.UTF-8".to_owned(),
+            )])),
         };
         // main assertion
         assert_eq!(settings, expected);
@@ -146,7 +165,7 @@ mod test {
                 .path("/api/l10n/config")
                 .header("content-type", "application/json")
                 .body(
-                    r#"{"locales":["fr_FR.UTF-8"],"keymap":"fr(dvorak)","timezone":"Europe/Paris","uiLocale":null,"uiKeymap":null}"#
+                    r#"{"locales":["fr_FR.UTF-8"],"keymap":"fr(dvorak)","timezone":"Europe/Paris","categories":null,"uiLocale":null,"uiKeymap":null}"#
                 );
             then.status(204);
         });
@@ -158,6 +177,7 @@ mod test {
             language: Some("fr_FR.UTF-8".to_owned()),
             keyboard: Some("fr(dvorak)".to_owned()),
             timezone: Some("Europe/Paris".to_owned()),
+            categories: None,
         };
         let result = store.store(&settings).await;
 
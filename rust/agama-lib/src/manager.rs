@@ -23,15 +23,18 @@
 pub mod http_client;
 pub use http_client::ManagerHTTPClient;
 
+use crate::dbus::{get_optional_property, get_property, optional_property};
 use crate::error::ServiceError;
 use crate::proxies::ServiceStatusProxy;
 use crate::{
     progress::Progress,
     proxies::{Manager1Proxy, ProgressProxy},
 };
+use serde::{Deserialize, Serialize};
 use serde_repr::Serialize_repr;
+use std::collections::HashMap;
 use tokio_stream::StreamExt;
-use zbus::Connection;
+use zbus::{zvariant::OwnedValue, Connection};
 
 /// D-Bus client for the manager service
 #[derive(Clone)]
@@ -67,6 +70,109 @@ impl TryFrom<u32> for InstallationPhase {
     }
 }
 
+/// State of the installer self-update.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfUpdateInfo {
+    /// Whether an update of the installer is available.
+    pub available: bool,
+    /// Whether the update was already applied.
+    pub applied: bool,
+    /// Whether the user opted out of the self-update for this session.
+    pub skipped: bool,
+    /// Version of the running installer.
+    pub from_version: Option<String>,
+    /// Version of the available update.
+    pub to_version: Option<String>,
+}
+
+impl TryFrom<HashMap<String, OwnedValue>> for SelfUpdateInfo {
+    type Error = zbus::zvariant::Error;
+
+    fn try_from(hash: HashMap<String, OwnedValue>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            available: get_property(&hash, "Available")?,
+            applied: get_property(&hash, "Applied")?,
+            skipped: get_property(&hash, "Skipped")?,
+            from_version: get_optional_property(&hash, "FromVersion")?,
+            to_version: get_optional_property(&hash, "ToVersion")?,
+        })
+    }
+}
+
+/// How the machine-id of the installed system is determined.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "policy", rename_all = "camelCase")]
+pub enum MachineIdPolicy {
+    /// Generate a fresh machine-id on the first boot.
+    #[default]
+    Generate,
+    /// Keep the machine-id of the installation media.
+    Keep,
+    /// Use the given machine-id.
+    Explicit {
+        /// Machine-id (32 hexadecimal characters).
+        id: String,
+    },
+}
+
+impl MachineIdPolicy {
+    /// Returns the list of problems found in the policy.
+    pub fn validate(&self) -> Vec<String> {
+        let Self::Explicit { id } = self else {
+            return vec![];
+        };
+
+        if id.len() != 32 || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return vec![format!(
+                "'{id}' is not a valid machine-id: 32 hexadecimal characters are expected"
+            )];
+        }
+
+        if id.chars().all(|c| c == '0') {
+            return vec!["The machine-id cannot be all zeros".to_string()];
+        }
+
+        vec![]
+    }
+
+    /// Returns the policy with the machine-id in lowercase, as systemd writes it.
+    pub fn normalize(self) -> Self {
+        match self {
+            Self::Explicit { id } => Self::Explicit {
+                id: id.to_ascii_lowercase(),
+            },
+            other => other,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Generate => "generate",
+            Self::Keep => "keep",
+            Self::Explicit { .. } => "explicit",
+        }
+    }
+}
+
+impl TryFrom<HashMap<String, OwnedValue>> for MachineIdPolicy {
+    type Error = zbus::zvariant::Error;
+
+    fn try_from(hash: HashMap<String, OwnedValue>) -> Result<Self, Self::Error> {
+        let policy: String = get_property(&hash, "Policy")?;
+        match policy.as_str() {
+            "generate" => Ok(Self::Generate),
+            "keep" => Ok(Self::Keep),
+            "explicit" => Ok(Self::Explicit {
+                id: get_property(&hash, "Id")?,
+            }),
+            _ => Err(zbus::zvariant::Error::Message(format!(
+                "Unknown machine-id policy: {policy}"
+            ))),
+        }
+    }
+}
+
 impl<'a> ManagerClient<'a> {
     pub async fn new(connection: Connection) -> zbus::Result<ManagerClient<'a>> {
         Ok(Self {
@@ -113,6 +219,75 @@ impl<'a> ManagerClient<'a> {
         Ok(self.manager_proxy.iguana_backend().await?)
     }
 
+    /// Returns the state of the installer self-update.
+    pub async fn self_update(&self) -> Result<SelfUpdateInfo, ServiceError> {
+        let hash = self.manager_proxy.self_update().await?;
+        Ok(hash.try_into()?)
+    }
+
+    /// Opts out of the installer self-update for the rest of the session.
+    pub async fn skip_self_update(&self) -> Result<(), ServiceError> {
+        Ok(self.manager_proxy.skip_self_update().await?)
+    }
+
+    /// Returns the machine-id policy for the installed system.
+    pub async fn machine_id_policy(&self) -> Result<MachineIdPolicy, ServiceError> {
+        let hash = self.manager_proxy.machine_id_policy().await?;
+        Ok(hash.try_into()?)
+    }
+
+    /// Sets the machine-id policy for the installed system.
+    ///
+    /// The policy is expected to be valid (see [MachineIdPolicy::validate]).
+    pub async fn set_machine_id_policy(
+        &self,
+        policy: &MachineIdPolicy,
+    ) -> Result<(), ServiceError> {
+        let id = match policy {
+            MachineIdPolicy::Explicit { id } => id.as_str(),
+            _ => "",
+        };
+        let result = self
+            .manager_proxy
+            .set_machine_id_policy(policy.name(), id)
+            .await?;
+        if result != 0 {
+            return Err(ServiceError::UnsuccessfulAction(
+                "Could not set the machine-id policy".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Waits until the installation finishes.
+    ///
+    /// It waits for the installation phase to start, if needed, and then until the manager is
+    /// idle.
+    pub async fn wait_for_installation(&self) -> Result<(), ServiceError> {
+        let mut stream = self
+            .manager_proxy
+            .receive_current_installation_phase_changed()
+            .await;
+        if self.current_installation_phase().await? != InstallationPhase::Install {
+            while let Some(change) = stream.next().await {
+                let phase = InstallationPhase::try_from(change.get().await?)?;
+                if phase == InstallationPhase::Install {
+                    break;
+                }
+            }
+        }
+        self.wait().await
+    }
+
+    /// Returns whether the last installation failed.
+    ///
+    /// It returns `None` if the manager service does not report it.
+    pub async fn installation_failed(&self) -> Result<Option<bool>, ServiceError> {
+        Ok(optional_property(
+            self.manager_proxy.installation_failed().await,
+        )?)
+    }
+
     /// Returns the current progress.
     pub async fn progress(&self) -> zbus::Result<Progress> {
         Progress::from_proxy(&self.progress_proxy).await
@@ -143,3 +318,90 @@ impl<'a> ManagerClient<'a> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zbus::zvariant::Value;
+
+    #[test]
+    fn test_self_update_info_from_dbus() {
+        let hash = HashMap::from([
+            ("Available".to_string(), Value::new(true).to_owned()),
+            ("Applied".to_string(), Value::new(false).to_owned()),
+            ("Skipped".to_string(), Value::new(false).to_owned()),
+            ("FromVersion".to_string(), Value::new("10").to_owned()),
+            ("ToVersion".to_string(), Value::new("11").to_owned()),
+        ]);
+        let info = SelfUpdateInfo::try_from(hash).unwrap();
+        assert!(info.available);
+        assert_eq!(info.from_version.as_deref(), Some("10"));
+        assert_eq!(info.to_version.as_deref(), Some("11"));
+
+        let hash = HashMap::from([("Available".to_string(), Value::new(false).to_owned())]);
+        assert!(SelfUpdateInfo::try_from(hash).is_err());
+    }
+
+    #[test]
+    fn test_validate_machine_id_policy() {
+        assert!(MachineIdPolicy::Generate.validate().is_empty());
+        assert!(MachineIdPolicy::Keep.validate().is_empty());
+
+        let policy = MachineIdPolicy::Explicit {
+            id: "0123456789ABCDEF0123456789abcdef".to_string(),
+        };
+        assert!(policy.validate().is_empty());
+        assert_eq!(
+            policy.normalize(),
+            MachineIdPolicy::Explicit {
+                id: "0123456789abcdef0123456789abcdef".to_string()
+            }
+        );
+
+        for id in ["0123", "0123456789abcdef0123456789abcdeg", &"0".repeat(32)] {
+            let policy = MachineIdPolicy::Explicit { id: id.to_string() };
+            assert_eq!(policy.validate().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_machine_id_policy_from_dbus() {
+        let hash = HashMap::from([
+            ("Policy".to_string(), Value::new("explicit").to_owned()),
+            (
+                "Id".to_string(),
+                Value::new("0123456789abcdef0123456789abcdef").to_owned(),
+            ),
+        ]);
+        let policy = MachineIdPolicy::try_from(hash).unwrap();
+        assert_eq!(
+            policy,
+            MachineIdPolicy::Explicit {
+                id: "0123456789abcdef0123456789abcdef".to_string()
+            }
+        );
+
+        let hash = HashMap::from([("Policy".to_string(), Value::new("keep").to_owned())]);
+        assert_eq!(
+            MachineIdPolicy::try_from(hash).unwrap(),
+            MachineIdPolicy::Keep
+        );
+
+        let hash = HashMap::from([("Policy".to_string(), Value::new("other").to_owned())]);
+        assert!(MachineIdPolicy::try_from(hash).is_err());
+    }
+
+    #[test]
+    fn test_machine_id_policy_json() {
+        let policy: MachineIdPolicy =
+            serde_json::from_str(r#"{ "policy": "explicit", "id": "abc" }"#).unwrap();
+        assert_eq!(
+            policy,
+            MachineIdPolicy::Explicit {
+                id: "abc".to_string()
+            }
+        );
+        let json = serde_json::to_string(&MachineIdPolicy::Generate).unwrap();
+        assert_eq!(json, r#"{"policy":"generate"}"#);
+    }
+}
@@ -79,6 +79,15 @@ impl NetworkClient {
         Ok(())
     }
 
+    /// Copies the connections which are active in the live system into the configuration
+    ///
+    /// It returns the IDs of the copied connections.
+    pub async fn copy_live_connections(&self) -> Result<Vec<String>, ServiceError> {
+        self.client
+            .post::<Vec<String>>("/network/connections/copy_live", &())
+            .await
+    }
+
     /// Returns an array of network connections
     pub async fn apply(&self) -> Result<(), ServiceError> {
         // trying to be tricky here. If something breaks then we need a put method on
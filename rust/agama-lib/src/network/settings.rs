@@ -20,7 +20,7 @@
 
 //! Representation of the network settings
 
-use super::types::{DeviceState, DeviceType, Status};
+use super::types::{DeviceState, DeviceType, DnsOverTls, Ipv6Privacy, Metered, Status};
 use cidr::IpInet;
 use serde::{Deserialize, Serialize};
 use std::default::Default;
@@ -32,6 +32,10 @@ use std::net::IpAddr;
 pub struct NetworkSettings {
     /// Connections to use in the installation
     pub connections: Vec<NetworkConnection>,
+    /// Whether to copy the connections which are active in the live system. The connections in
+    /// the list take precedence over the copied ones.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub copy_live: Option<bool>,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -113,6 +117,26 @@ impl Default for BondSettings {
     }
 }
 
+/// Bridge settings. The ports are the connections (IDs or interface names) attached to the bridge.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BridgeSettings {
+    /// Whether the Spanning Tree Protocol is enabled (NetworkManager enables it by default).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stp: Option<bool>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub ports: Vec<String>,
+}
+
+/// VLAN settings. The lower layer is set in the `parent` of the connection.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct VlanSettings {
+    /// VLAN ID (1-4094).
+    pub id: u32,
+    /// VLAN protocol ("802.1Q" or "802.1ad"). "802.1Q" is used if it is not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+}
+
 /// IEEE 802.1x (EAP) settings
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -185,20 +209,52 @@ pub struct NetworkConnection {
     pub dns_searchlist: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ignore_auto_dns: Option<bool>,
+    /// Priority of the IPv4 DNS servers (lower values first, negative ones exclude the servers
+    /// of other connections). Zero means the default priority.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dns_priority4: Option<i32>,
+    /// Priority of the IPv6 DNS servers, like `dns_priority4`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dns_priority6: Option<i32>,
+    /// DNS-over-TLS mode ("default", "no", "opportunistic" or "yes").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dns_over_tls: Option<DnsOverTls>,
+    /// IPv6 privacy extensions ("disabled", "enabled" or "prefer-temporary"). NetworkManager's
+    /// default is used if it is not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipv6_privacy: Option<Ipv6Privacy>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub wireless: Option<WirelessSettings>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub interface: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub match_settings: Option<MatchSettings>,
+    /// Lower layer of a VLAN (e.g., a bond), as a connection ID or an interface name.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parent: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bond: Option<BondSettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bridge: Option<BridgeSettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vlan: Option<VlanSettings>,
     #[serde(rename = "mac-address", skip_serializing_if = "Option::is_none")]
     pub mac_address: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<Status>,
+    /// Whether the connection is activated automatically.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub autoconnect: Option<bool>,
+    /// Whether the connection is metered ("yes", "no" or "auto").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metered: Option<Metered>,
+    /// Free-text description of the connection.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Seconds to wait for the gateway to answer a ping after activating the connection. The
+    /// check is disabled if it is not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gateway_check_timeout: Option<u32>,
     #[serde(skip_serializing_if = "is_zero", default)]
     pub mtu: u32,
     #[serde(rename = "ieee-8021x", skip_serializing_if = "Option::is_none")]
@@ -219,8 +275,35 @@ impl NetworkConnection {
             DeviceType::Wireless
         } else if self.bond.is_some() {
             DeviceType::Bond
+        } else if self.bridge.is_some() {
+            DeviceType::Bridge
+        } else if self.vlan.is_some() {
+            DeviceType::Vlan
         } else {
             DeviceType::Ethernet
         }
     }
+
+    /// Ports of a bond or a bridge (connection IDs or interface names).
+    pub fn ports(&self) -> Option<&[String]> {
+        self.bond
+            .as_ref()
+            .map(|b| b.ports.as_slice())
+            .or_else(|| self.bridge.as_ref().map(|b| b.ports.as_slice()))
+    }
+
+    /// Connections (IDs or interface names) this one is stacked on: the ports of a bond or a
+    /// bridge and the parent of a VLAN.
+    pub fn lower_layers(&self) -> Vec<&str> {
+        let mut layers: Vec<&str> = self
+            .ports()
+            .unwrap_or_default()
+            .iter()
+            .map(String::as_str)
+            .collect();
+        if self.vlan.is_some() {
+            layers.extend(self.parent.as_deref());
+        }
+        layers
+    }
 }
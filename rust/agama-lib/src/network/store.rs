@@ -39,10 +39,17 @@ impl NetworkStore {
     pub async fn load(&self) -> Result<NetworkSettings, ServiceError> {
         let connections = self.network_client.connections().await?;
 
-        Ok(NetworkSettings { connections })
+        Ok(NetworkSettings {
+            connections,
+            ..Default::default()
+        })
     }
 
     pub async fn store(&self, settings: &NetworkSettings) -> Result<(), ServiceError> {
+        if settings.copy_live == Some(true) {
+            let ids = self.network_client.copy_live_connections().await?;
+            log::info!("Copied the live network connections: {:?}", ids);
+        }
         for id in ordered_connections(&settings.connections) {
             let id = id.as_str();
             let fallback = default_connection(id);
@@ -63,34 +70,44 @@ impl NetworkStore {
 fn ordered_connections(conns: &Vec<NetworkConnection>) -> Vec<String> {
     let mut ordered: Vec<String> = Vec::with_capacity(conns.len());
     for conn in conns {
-        add_ordered_connection(conn, conns, &mut ordered);
+        add_ordered_connection(conn, conns, &mut ordered, &mut vec![]);
     }
     ordered
 }
 
 /// Adds a connections and its dependencies to the list.
 ///
+/// The lower layers (the ports of a bond or a bridge and the parent of a VLAN) go first, so a
+/// stack like bond ports → bond → VLAN is written from the bottom up. A VLAN parent which is not
+/// in the list is expected to exist already.
+///
 /// * `conn`: connection to add.
 /// * `conns`: existing connections.
 /// * `ordered`: ordered list of connections.
-fn add_ordered_connection(
-    conn: &NetworkConnection,
-    conns: &Vec<NetworkConnection>,
+/// * `visiting`: connections whose lower layers are being added, to stop at loops (the service
+///   rejects them anyway).
+fn add_ordered_connection<'a>(
+    conn: &'a NetworkConnection,
+    conns: &'a Vec<NetworkConnection>,
     ordered: &mut Vec<String>,
+    visiting: &mut Vec<&'a str>,
 ) {
-    if let Some(bond) = &conn.bond {
-        for port in &bond.ports {
-            if let Some(conn) = find_connection(port, conns) {
-                add_ordered_connection(conn, conns, ordered);
-            } else if !ordered.contains(&conn.id) {
-                ordered.push(port.clone());
-            }
+    if ordered.contains(&conn.id) || visiting.contains(&conn.id.as_str()) {
+        return;
+    }
+    visiting.push(&conn.id);
+
+    let ports = conn.ports().unwrap_or_default();
+    for layer in conn.lower_layers() {
+        if let Some(lower) = find_connection(layer, conns) {
+            add_ordered_connection(lower, conns, ordered, visiting);
+        } else if ports.iter().any(|p| p == layer) && !ordered.iter().any(|o| o == layer) {
+            ordered.push(layer.to_string());
         }
     }
 
-    if !ordered.contains(&conn.id) {
-        ordered.push(conn.id.to_owned())
-    }
+    visiting.pop();
+    ordered.push(conn.id.to_owned())
 }
 
 /// Finds a connection by id in the list.
@@ -113,7 +130,7 @@ fn default_connection(id: &str) -> NetworkConnection {
 #[cfg(test)]
 mod tests {
     use super::ordered_connections;
-    use crate::network::settings::{BondSettings, NetworkConnection};
+    use crate::network::settings::{BondSettings, NetworkConnection, VlanSettings};
 
     #[test]
     fn test_ordered_connections() {
@@ -153,4 +170,71 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn test_ordered_vlan_on_bond() {
+        let vlan = NetworkConnection {
+            id: "bond0.100".to_string(),
+            parent: Some("bond0".to_string()),
+            vlan: Some(VlanSettings {
+                id: 100,
+                protocol: None,
+            }),
+            ..Default::default()
+        };
+        let bond = NetworkConnection {
+            id: "bond0".to_string(),
+            bond: Some(BondSettings {
+                ports: vec!["eth0".to_string(), "eth1".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        // VLAN on an existing connection
+        let other = NetworkConnection {
+            id: "eth2.200".to_string(),
+            parent: Some("eth2".to_string()),
+            vlan: Some(VlanSettings {
+                id: 200,
+                protocol: None,
+            }),
+            ..Default::default()
+        };
+
+        let ordered = ordered_connections(&vec![vlan, bond, other]);
+        assert_eq!(
+            ordered,
+            vec![
+                "eth0".to_string(),
+                "eth1".to_string(),
+                "bond0".to_string(),
+                "bond0.100".to_string(),
+                "eth2.200".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ordered_connections_loop() {
+        let bond = NetworkConnection {
+            id: "bond0".to_string(),
+            bond: Some(BondSettings {
+                ports: vec!["bond0.100".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let vlan = NetworkConnection {
+            id: "bond0.100".to_string(),
+            parent: Some("bond0".to_string()),
+            vlan: Some(VlanSettings {
+                id: 100,
+                protocol: None,
+            }),
+            ..Default::default()
+        };
+
+        let ordered = ordered_connections(&vec![bond, vlan]);
+        assert_eq!(ordered.len(), 2);
+    }
 }
@@ -182,6 +182,121 @@ impl TryFrom<&str> for Status {
     }
 }
 
+/// Whether the connection is metered (e.g., mobile broadband), limiting the background traffic.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Metered {
+    Yes,
+    No,
+    /// Let NetworkManager guess it.
+    #[default]
+    Auto,
+}
+
+impl Metered {
+    /// Value of the NetworkManager "metered" property (NMMetered).
+    pub fn to_nm(self) -> i32 {
+        match self {
+            Metered::Auto => 0,
+            Metered::Yes => 1,
+            Metered::No => 2,
+        }
+    }
+
+    /// Converts the NetworkManager "metered" property. Unknown values are considered "auto".
+    pub fn from_nm(value: i32) -> Self {
+        match value {
+            1 => Metered::Yes,
+            2 => Metered::No,
+            _ => Metered::Auto,
+        }
+    }
+}
+
+impl fmt::Display for Metered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match &self {
+            Metered::Yes => "yes",
+            Metered::No => "no",
+            Metered::Auto => "auto",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// DNS-over-TLS mode, as supported by systemd-resolved.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum DnsOverTls {
+    /// Use the global setting.
+    #[default]
+    Default,
+    No,
+    /// Use TLS if the server supports it, falling back to plain DNS otherwise.
+    Opportunistic,
+    Yes,
+}
+
+impl DnsOverTls {
+    /// Value of the NetworkManager "dns-over-tls" property (NMSettingConnectionDnsOverTls).
+    pub fn to_nm(self) -> i32 {
+        match self {
+            DnsOverTls::Default => -1,
+            DnsOverTls::No => 0,
+            DnsOverTls::Opportunistic => 1,
+            DnsOverTls::Yes => 2,
+        }
+    }
+
+    /// Converts the NetworkManager "dns-over-tls" property. Unknown values are considered
+    /// "default".
+    pub fn from_nm(value: i32) -> Self {
+        match value {
+            0 => DnsOverTls::No,
+            1 => DnsOverTls::Opportunistic,
+            2 => DnsOverTls::Yes,
+            _ => DnsOverTls::Default,
+        }
+    }
+}
+
+/// IPv6 privacy extensions (RFC 4941) mode.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum Ipv6Privacy {
+    /// Use the NetworkManager default.
+    #[default]
+    Default,
+    Disabled,
+    /// Generate temporary addresses, but prefer the public ones.
+    Enabled,
+    /// Generate temporary addresses and prefer them over the public ones.
+    PreferTemporary,
+}
+
+impl Ipv6Privacy {
+    /// Value of the NetworkManager "ip6-privacy" property (NMSettingIP6ConfigPrivacy).
+    pub fn to_nm(self) -> i32 {
+        match self {
+            Ipv6Privacy::Default => -1,
+            Ipv6Privacy::Disabled => 0,
+            Ipv6Privacy::Enabled => 1,
+            Ipv6Privacy::PreferTemporary => 2,
+        }
+    }
+
+    /// Converts the NetworkManager "ip6-privacy" property. Unknown values are considered
+    /// "default".
+    pub fn from_nm(value: i32) -> Self {
+        match value {
+            0 => Ipv6Privacy::Disabled,
+            1 => Ipv6Privacy::Enabled,
+            2 => Ipv6Privacy::PreferTemporary,
+            _ => Ipv6Privacy::Default,
+        }
+    }
+}
+
 /// Bond mode
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
 pub enum BondMode {
@@ -320,6 +435,14 @@ mod tests {
         assert_eq!(dtype, Err(InvalidDeviceType(128)));
     }
 
+    #[test]
+    fn test_metered_nm_values() {
+        for metered in [Metered::Yes, Metered::No, Metered::Auto] {
+            assert_eq!(Metered::from_nm(metered.to_nm()), metered);
+        }
+        assert_eq!(Metered::from_nm(3), Metered::Auto);
+    }
+
     #[test]
     fn test_display_bond_mode() {
         let mode = BondMode::try_from(1).unwrap();
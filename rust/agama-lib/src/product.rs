@@ -23,11 +23,16 @@
 mod client;
 mod http_client;
 pub mod proxies;
+mod requirements;
 mod settings;
 mod store;
 
 pub use crate::software::model::RegistrationRequirement;
 pub use client::{Product, ProductClient};
 pub use http_client::ProductHTTPClient;
+pub use requirements::{
+    check_requirements, ProductRequirements, RequirementCheck, RequirementStatus,
+    RequirementsReport, SystemResources,
+};
 pub use settings::ProductSettings;
 pub use store::ProductStore;
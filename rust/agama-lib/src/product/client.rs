@@ -21,12 +21,14 @@
 use std::collections::HashMap;
 
 use crate::error::ServiceError;
-use crate::software::model::RegistrationRequirement;
+use crate::software::model::{RegistrationRequirement, SubscriptionStatus};
 use crate::software::proxies::SoftwareProductProxy;
 use serde::Serialize;
+use zbus::zvariant::{OwnedValue, Value};
 use zbus::Connection;
 
 use super::proxies::RegistrationProxy;
+use super::ProductRequirements;
 
 /// Represents a software product
 #[derive(Default, Debug, Serialize, utoipa::ToSchema)]
@@ -39,6 +41,37 @@ pub struct Product {
     pub description: String,
     /// Product icon (e.g., "default.svg")
     pub icon: String,
+    /// Product version (e.g., "16.0"), if the product defines it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Whether the product requires registration
+    pub registration: RegistrationRequirement,
+    /// Hardware requirements
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requirements: Option<ProductRequirements>,
+    /// Location of the release notes, indexed by language (e.g., "en", "pt_BR")
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub release_notes: HashMap<String, String>,
+}
+
+/// Converts the release notes (an `a{sv}` dictionary) to a language-location map.
+///
+/// The entries which are not strings are ignored.
+fn release_notes_from_dbus(value: &OwnedValue) -> HashMap<String, String> {
+    let value: Value = value.into();
+    let Ok(notes) = HashMap::<String, Value>::try_from(value) else {
+        return HashMap::new();
+    };
+    notes
+        .into_iter()
+        .filter_map(|(language, url)| {
+            let url = match url {
+                Value::Value(inner) => *inner,
+                url => url,
+            };
+            String::try_from(url).ok().map(|url| (language, url))
+        })
+        .collect()
 }
 
 /// D-Bus client for the software service
@@ -72,11 +105,31 @@ impl<'a> ProductClient<'a> {
                     Some(value) => value.try_into().unwrap(),
                     None => "default.svg",
                 };
+                let version = data
+                    .get("version")
+                    .and_then(|value| <&str>::try_from(value).ok())
+                    .map(str::to_string);
+                let registration = data
+                    .get("registration")
+                    .and_then(|value| u32::try_from(value).ok())
+                    .and_then(|value| RegistrationRequirement::try_from(value).ok())
+                    .unwrap_or_default();
+                let requirements = data
+                    .get("requirements")
+                    .and_then(|value| ProductRequirements::try_from(value).ok());
+                let release_notes = data
+                    .get("release_notes")
+                    .map(release_notes_from_dbus)
+                    .unwrap_or_default();
                 Product {
                     id,
                     name,
                     description: description.to_string(),
                     icon: icon.to_string(),
+                    version,
+                    registration,
+                    requirements,
+                    release_notes,
                 }
             })
             .collect();
@@ -121,6 +174,18 @@ impl<'a> ProductClient<'a> {
         Ok(result)
     }
 
+    /// Subscription status of the registered product
+    pub async fn subscription_status(&self) -> Result<SubscriptionStatus, ServiceError> {
+        let subscription = self.registration_proxy.subscription().await?;
+        let extensions = self.registration_proxy.extensions().await?;
+        let today = chrono::Local::now().date_naive();
+        Ok(SubscriptionStatus::from_dbus(
+            &subscription,
+            &extensions,
+            today,
+        )?)
+    }
+
     /// register product
     pub async fn register(&self, code: &str, email: &str) -> Result<(u32, String), ServiceError> {
         let mut options: HashMap<&str, zbus::zvariant::Value> = HashMap::new();
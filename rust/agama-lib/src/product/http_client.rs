@@ -55,6 +55,10 @@ impl ProductHTTPClient {
         let config = SoftwareConfig {
             product: Some(product_id.to_owned()),
             patterns: None,
+            default_target: None,
+            extra_repositories: None,
+            only_required: None,
+            services: None,
         };
         self.set_software(&config).await
     }
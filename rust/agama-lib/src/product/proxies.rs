@@ -50,4 +50,16 @@ trait Registration {
     /// Requirement property
     #[dbus_proxy(property)]
     fn requirement(&self) -> zbus::Result<u32>;
+
+    /// Subscription property
+    #[dbus_proxy(property)]
+    fn subscription(
+        &self,
+    ) -> zbus::Result<std::collections::HashMap<String, zbus::zvariant::OwnedValue>>;
+
+    /// Extensions property
+    #[dbus_proxy(property)]
+    fn extensions(
+        &self,
+    ) -> zbus::Result<Vec<std::collections::HashMap<String, zbus::zvariant::OwnedValue>>>;
 }
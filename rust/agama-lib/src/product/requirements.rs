@@ -0,0 +1,344 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Checks the system resources against the product requirements.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use zbus::zvariant::{self, OwnedValue, Value};
+
+/// Hardware requirements of a product.
+///
+/// All the limits are optional: a missing limit is always satisfied.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductRequirements {
+    /// Minimum amount of memory, in bytes.
+    pub min_memory: Option<u64>,
+    /// Recommended amount of memory, in bytes.
+    pub recommended_memory: Option<u64>,
+    /// Minimum disk size, in bytes.
+    pub min_disk: Option<u64>,
+    /// Recommended disk size, in bytes.
+    pub recommended_disk: Option<u64>,
+    /// Supported architectures (e.g., "x86_64"). An empty list means any architecture.
+    pub archs: Vec<String>,
+}
+
+impl TryFrom<HashMap<String, OwnedValue>> for ProductRequirements {
+    type Error = zbus::zvariant::Error;
+
+    fn try_from(hash: HashMap<String, OwnedValue>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            min_memory: get_optional_size(&hash, "min_memory")?,
+            recommended_memory: get_optional_size(&hash, "recommended_memory")?,
+            min_disk: get_optional_size(&hash, "min_disk")?,
+            recommended_disk: get_optional_size(&hash, "recommended_disk")?,
+            archs: get_archs(&hash)?,
+        })
+    }
+}
+
+/// Returns the value, removing the variant wrapping which the software service adds to the
+/// values of a dictionary.
+fn unwrap_variant(value: &OwnedValue) -> Value<'_> {
+    match Value::from(value) {
+        Value::Value(inner) => *inner,
+        value => value,
+    }
+}
+
+/// Reads a size from the requirements.
+///
+/// The software service sends the sizes as 32-bit or 64-bit signed integers, depending on their
+/// values.
+fn get_optional_size(
+    hash: &HashMap<String, OwnedValue>,
+    name: &str,
+) -> Result<Option<u64>, zvariant::Error> {
+    let Some(value) = hash.get(name) else {
+        return Ok(None);
+    };
+    let size = match unwrap_variant(value) {
+        Value::U64(size) => Some(size),
+        Value::U32(size) => Some(size.into()),
+        Value::I64(size) => u64::try_from(size).ok(),
+        Value::I32(size) => u64::try_from(size).ok(),
+        _ => return Err(zvariant::Error::IncorrectType),
+    };
+    size.map(Some)
+        .ok_or_else(|| zvariant::Error::Message(format!("Invalid {}", name)))
+}
+
+/// Reads the list of supported architectures from the requirements.
+fn get_archs(hash: &HashMap<String, OwnedValue>) -> Result<Vec<String>, zvariant::Error> {
+    let Some(value) = hash.get("archs") else {
+        return Ok(vec![]);
+    };
+    let Value::Array(archs) = unwrap_variant(value) else {
+        return Err(zvariant::Error::IncorrectType);
+    };
+    archs
+        .get()
+        .iter()
+        .map(|arch| match arch {
+            Value::Value(inner) => String::try_from(inner.as_ref().clone()),
+            arch => String::try_from(arch.clone()),
+        })
+        .collect()
+}
+
+impl TryFrom<&OwnedValue> for ProductRequirements {
+    type Error = zbus::zvariant::Error;
+
+    fn try_from(value: &OwnedValue) -> Result<Self, Self::Error> {
+        let value: Value = value.into();
+        let hash: HashMap<String, OwnedValue> = value.try_into()?;
+        hash.try_into()
+    }
+}
+
+/// Resources of the system to check against the requirements.
+#[derive(Clone, Debug, Default)]
+pub struct SystemResources {
+    /// Total memory, in bytes (0 if unknown).
+    pub memory: u64,
+    /// Size of the biggest disk, in bytes (`None` if there are no disks).
+    pub disk: Option<u64>,
+    /// Architecture of the system.
+    pub arch: String,
+}
+
+/// Status of a requirement, from the best to the worst.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum RequirementStatus {
+    /// The requirement is satisfied.
+    Pass,
+    /// The installation is possible but not recommended.
+    Warn,
+    /// The installation is not possible.
+    Fail,
+}
+
+/// Result of checking a single requirement.
+#[derive(Clone, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RequirementCheck {
+    pub status: RequirementStatus,
+    /// Human-readable explanation.
+    pub message: String,
+}
+
+impl RequirementCheck {
+    fn new(status: RequirementStatus, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+/// Results of checking the product requirements.
+#[derive(Clone, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RequirementsReport {
+    pub memory: RequirementCheck,
+    pub disk: RequirementCheck,
+    pub architecture: RequirementCheck,
+}
+
+impl RequirementsReport {
+    /// Overall status (the worst of all the checks).
+    pub fn status(&self) -> RequirementStatus {
+        [&self.memory, &self.disk, &self.architecture]
+            .iter()
+            .map(|c| c.status)
+            .max()
+            .unwrap_or(RequirementStatus::Pass)
+    }
+}
+
+/// Checks the given resources against the product requirements.
+///
+/// * `requirements`: product requirements.
+/// * `resources`: system resources.
+pub fn check_requirements(
+    requirements: &ProductRequirements,
+    resources: &SystemResources,
+) -> RequirementsReport {
+    let memory = if resources.memory == 0 {
+        RequirementCheck::new(
+            RequirementStatus::Warn,
+            "The amount of memory could not be determined",
+        )
+    } else {
+        check_size(
+            "memory",
+            resources.memory,
+            requirements.min_memory,
+            requirements.recommended_memory,
+        )
+    };
+
+    let disk = match resources.disk {
+        Some(size) => check_size(
+            "disk size",
+            size,
+            requirements.min_disk,
+            requirements.recommended_disk,
+        ),
+        None => RequirementCheck::new(RequirementStatus::Fail, "No disk was found"),
+    };
+
+    let architecture =
+        if requirements.archs.is_empty() || requirements.archs.contains(&resources.arch) {
+            RequirementCheck::new(
+                RequirementStatus::Pass,
+                format!("The {} architecture is supported", resources.arch),
+            )
+        } else {
+            RequirementCheck::new(
+                RequirementStatus::Fail,
+                format!(
+                    "The {} architecture is not supported (supported: {})",
+                    resources.arch,
+                    requirements.archs.join(", ")
+                ),
+            )
+        };
+
+    RequirementsReport {
+        memory,
+        disk,
+        architecture,
+    }
+}
+
+fn check_size(
+    what: &str,
+    value: u64,
+    min: Option<u64>,
+    recommended: Option<u64>,
+) -> RequirementCheck {
+    if let Some(min) = min.filter(|m| value < *m) {
+        return RequirementCheck::new(
+            RequirementStatus::Fail,
+            format!("The {what} ({value} bytes) is below the minimum ({min} bytes)"),
+        );
+    }
+    if let Some(recommended) = recommended.filter(|r| value < *r) {
+        return RequirementCheck::new(
+            RequirementStatus::Warn,
+            format!("The {what} ({value} bytes) is below the recommended ({recommended} bytes)"),
+        );
+    }
+    RequirementCheck::new(
+        RequirementStatus::Pass,
+        format!("The {what} ({value} bytes) is enough"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    fn requirements() -> ProductRequirements {
+        ProductRequirements {
+            min_memory: Some(GIB),
+            recommended_memory: Some(2 * GIB),
+            min_disk: Some(10 * GIB),
+            recommended_disk: Some(20 * GIB),
+            archs: vec!["x86_64".to_string(), "aarch64".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_check_requirements_pass() {
+        let resources = SystemResources {
+            memory: 4 * GIB,
+            disk: Some(40 * GIB),
+            arch: "x86_64".to_string(),
+        };
+        let report = check_requirements(&requirements(), &resources);
+        assert_eq!(report.memory.status, RequirementStatus::Pass);
+        assert_eq!(report.disk.status, RequirementStatus::Pass);
+        assert_eq!(report.architecture.status, RequirementStatus::Pass);
+        assert_eq!(report.status(), RequirementStatus::Pass);
+    }
+
+    #[test]
+    fn test_check_requirements_warn_and_fail() {
+        let resources = SystemResources {
+            memory: GIB + 1,
+            disk: Some(5 * GIB),
+            arch: "s390".to_string(),
+        };
+        let report = check_requirements(&requirements(), &resources);
+        assert_eq!(report.memory.status, RequirementStatus::Warn);
+        assert_eq!(report.disk.status, RequirementStatus::Fail);
+        assert_eq!(report.architecture.status, RequirementStatus::Fail);
+        assert_eq!(report.status(), RequirementStatus::Fail);
+    }
+
+    #[test]
+    fn test_requirements_from_dbus() {
+        // the software service wraps the values and uses signed integers
+        let archs = Value::new(vec![
+            Value::new(Value::new("x86_64")),
+            Value::new(Value::new("aarch64")),
+        ]);
+        let hash: HashMap<String, OwnedValue> = HashMap::from([
+            (
+                "min_memory".to_string(),
+                Value::new(Value::new(GIB as i32)).into(),
+            ),
+            (
+                "min_disk".to_string(),
+                Value::new(Value::new(10 * GIB as i64)).into(),
+            ),
+            ("archs".to_string(), Value::new(archs).into()),
+        ]);
+        let requirements = ProductRequirements::try_from(hash).unwrap();
+        assert_eq!(requirements.min_memory, Some(GIB));
+        assert_eq!(requirements.min_disk, Some(10 * GIB));
+        assert_eq!(requirements.recommended_disk, None);
+        assert_eq!(requirements.archs, vec!["x86_64", "aarch64"]);
+
+        let hash: HashMap<String, OwnedValue> =
+            HashMap::from([("min_disk".to_string(), Value::new(-1_i32).into())]);
+        assert!(ProductRequirements::try_from(hash).is_err());
+    }
+
+    #[test]
+    fn test_check_requirements_unknown() {
+        let resources = SystemResources {
+            memory: 0,
+            disk: None,
+            arch: "ppc".to_string(),
+        };
+        let report = check_requirements(&ProductRequirements::default(), &resources);
+        assert_eq!(report.memory.status, RequirementStatus::Warn);
+        assert_eq!(report.disk.status, RequirementStatus::Fail);
+        assert_eq!(report.architecture.status, RequirementStatus::Pass);
+    }
+}
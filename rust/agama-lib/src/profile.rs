@@ -22,6 +22,8 @@ use crate::error::ProfileError;
 use anyhow::Context;
 use jsonschema::JSONSchema;
 use log::info;
+use quick_xml::events::Event;
+use serde::Serialize;
 use serde_json;
 use std::{fs, io::Write, path::Path, process::Command};
 use tempfile::{tempdir, TempDir};
@@ -63,6 +65,100 @@ impl AutoyastProfile {
     }
 }
 
+/// AutoYaST sections which are translated into the Agama profile.
+const CONVERTIBLE_SECTIONS: [&str; 8] = [
+    "keyboard",
+    "language",
+    "networking",
+    "partitioning",
+    "software",
+    "suse_register",
+    "timezone",
+    "users",
+];
+
+/// Result of converting an AutoYaST profile.
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoyastConversion {
+    /// Agama profile.
+    #[schema(value_type = Object)]
+    pub profile: serde_json::Value,
+    /// AutoYaST sections which could not be converted.
+    pub unsupported: Vec<String>,
+}
+
+impl AutoyastConversion {
+    /// Converts the content of an AutoYaST XML profile.
+    ///
+    /// It uses the `agama-autoyast` command with the `--no-scripts` option, so the pre-scripts
+    /// of the profile are not executed and the profile is converted as it is.
+    ///
+    /// * `xml`: AutoYaST profile.
+    pub fn from_xml(xml: &str) -> Result<Self, ProfileError> {
+        const TMP_DIR_PREFIX: &str = "autoyast";
+        const AUTOINST_XML: &str = "autoinst.xml";
+        const AUTOINST_JSON: &str = "autoinst.json";
+
+        let unsupported = Self::unsupported_sections(xml)?;
+        let tmp_dir = TempDir::with_prefix(TMP_DIR_PREFIX)?;
+        let xml_path = tmp_dir.path().join(AUTOINST_XML);
+        fs::write(&xml_path, xml)?;
+        let url = format!("file://{}", xml_path.display());
+        let output_dir = tmp_dir.path().join("agama");
+        let result = Command::new("agama-autoyast")
+            .arg("--no-scripts")
+            .arg(&url)
+            .arg(&output_dir)
+            .output()
+            .context("Failed to run agama-autoyast")?;
+        if !result.status.success() {
+            let message = String::from_utf8_lossy(&result.stderr).to_string();
+            return Err(ProfileError::ConversionError(message));
+        }
+
+        let content = fs::read_to_string(output_dir.join(AUTOINST_JSON))?;
+        Ok(Self {
+            profile: serde_json::from_str(&content)?,
+            unsupported,
+        })
+    }
+
+    /// Returns the top-level sections of an AutoYaST profile which cannot be converted.
+    ///
+    /// * `xml`: AutoYaST profile.
+    pub fn unsupported_sections(xml: &str) -> Result<Vec<String>, ProfileError> {
+        let mut reader = quick_xml::Reader::from_str(xml);
+        let mut depth = 0;
+        let mut sections = vec![];
+        loop {
+            let event = reader
+                .read_event()
+                .map_err(|e| ProfileError::XmlError(e.to_string()))?;
+            let (start, name) = match &event {
+                Event::Start(e) => (true, e.local_name()),
+                Event::Empty(e) => (false, e.local_name()),
+                Event::End(_) => {
+                    depth -= 1;
+                    continue;
+                }
+                Event::Eof => break,
+                _ => continue,
+            };
+            if depth == 1 {
+                let name = String::from_utf8_lossy(name.as_ref()).to_string();
+                if !CONVERTIBLE_SECTIONS.contains(&name.as_str()) && !sections.contains(&name) {
+                    sections.push(name);
+                }
+            }
+            if start {
+                depth += 1;
+            }
+        }
+        Ok(sections)
+    }
+}
+
 #[derive(Debug)]
 pub enum ValidationResult {
     Valid,
@@ -120,12 +216,16 @@ impl ProfileValidator {
 
     pub fn validate_str(&self, profile: &str) -> Result<ValidationResult, ProfileError> {
         let contents = serde_json::from_str(profile)?;
-        let result = self.schema.validate(&contents);
+        Ok(self.validate_value(&contents))
+    }
+
+    pub fn validate_value(&self, profile: &serde_json::Value) -> ValidationResult {
+        let result = self.schema.validate(profile);
         if let Err(errors) = result {
             let messages: Vec<String> = errors.map(|e| format!("{e}. {e:?}")).collect();
-            return Ok(ValidationResult::NotValid(messages));
+            return ValidationResult::NotValid(messages);
         }
-        Ok(ValidationResult::Valid)
+        ValidationResult::Valid
     }
 }
 
@@ -182,3 +282,25 @@ impl ProfileEvaluator {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::AutoyastConversion;
+
+    #[test]
+    fn test_unsupported_sections() {
+        let xml = r#"<?xml version="1.0"?>
+<!DOCTYPE profile>
+<profile xmlns="http://www.suse.com/1.0/yast2ns" xmlns:config="http://www.suse.com/1.0/configns">
+  <software><patterns config:type="list"><pattern>base</pattern></patterns></software>
+  <networking><keep_install_network config:type="boolean">true</keep_install_network></networking>
+  <users config:type="list"><user><username>root</username></user></users>
+  <services-manager/>
+  <language><language>en_US</language></language>
+</profile>"#;
+        let sections = AutoyastConversion::unsupported_sections(xml).unwrap();
+        assert_eq!(sections, vec!["services-manager"]);
+
+        assert!(AutoyastConversion::unsupported_sections("<profile><users></profile>").is_err());
+    }
+}
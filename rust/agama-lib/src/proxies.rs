@@ -87,6 +87,12 @@ trait Manager1 {
     /// Probe method
     fn probe(&self) -> zbus::Result<()>;
 
+    /// SetMachineIdPolicy method
+    fn set_machine_id_policy(&self, policy: &str, id: &str) -> zbus::Result<u32>;
+
+    /// SkipSelfUpdate method
+    fn skip_self_update(&self) -> zbus::Result<()>;
+
     /// BusyServices property
     #[dbus_proxy(property)]
     fn busy_services(&self) -> zbus::Result<Vec<String>>;
@@ -99,6 +105,22 @@ trait Manager1 {
     #[dbus_proxy(property)]
     fn iguana_backend(&self) -> zbus::Result<bool>;
 
+    /// InstallationFailed property
+    #[dbus_proxy(property)]
+    fn installation_failed(&self) -> zbus::Result<bool>;
+
+    /// MachineIdPolicy property
+    #[dbus_proxy(property)]
+    fn machine_id_policy(
+        &self,
+    ) -> zbus::Result<std::collections::HashMap<String, zbus::zvariant::OwnedValue>>;
+
+    /// SelfUpdate property
+    #[dbus_proxy(property)]
+    fn self_update(
+        &self,
+    ) -> zbus::Result<std::collections::HashMap<String, zbus::zvariant::OwnedValue>>;
+
     /// InstallationPhases property
     #[dbus_proxy(property)]
     fn installation_phases(
@@ -139,11 +161,43 @@ trait Questions1 {
         data: std::collections::HashMap<&str, &str>,
     ) -> zbus::Result<zbus::zvariant::OwnedObjectPath>;
 
+    /// NewWithTextInput method
+    ///
+    /// An empty `pattern` and zero lengths mean that there is no such restriction.
+    #[allow(clippy::too_many_arguments)]
+    fn new_with_text_input(
+        &self,
+        class: &str,
+        text: &str,
+        options: &[&str],
+        default_option: &str,
+        data: std::collections::HashMap<&str, &str>,
+        pattern: &str,
+        min_length: u32,
+        max_length: u32,
+    ) -> zbus::Result<zbus::zvariant::OwnedObjectPath>;
+
+    /// RememberAnswer method
+    fn remember_answer(&self, class: &str, answer: &str) -> zbus::Result<()>;
+
+    /// ForgetAnswers method
+    fn forget_answers(&self) -> zbus::Result<()>;
+
+    /// History method
+    fn history(&self) -> zbus::Result<Vec<crate::questions::model::AnsweredQuestionTuple>>;
+
+    /// SaveAnswers method
+    fn save_answers(&self, path: &str) -> zbus::Result<u32>;
+
     /// Interactive property
     #[dbus_proxy(property)]
     fn interactive(&self) -> zbus::Result<bool>;
     #[dbus_proxy(property)]
     fn set_interactive(&self, value: bool) -> zbus::Result<()>;
+
+    /// RememberedAnswers property
+    #[dbus_proxy(property)]
+    fn remembered_answers(&self) -> zbus::Result<std::collections::HashMap<String, String>>;
 }
 
 #[dbus_proxy(
@@ -196,11 +250,41 @@ trait QuestionWithPassword {
     fn set_password(&self, value: &str) -> zbus::Result<()>;
 }
 
+#[dbus_proxy(
+    interface = "org.opensuse.Agama1.Questions.WithTextInput",
+    default_service = "org.opensuse.Agama1",
+    default_path = "/org/opensuse/Agama1/Questions"
+)]
+trait QuestionWithTextInput {
+    /// Text property
+    #[dbus_proxy(property)]
+    fn text(&self) -> zbus::Result<String>;
+    #[dbus_proxy(property)]
+    fn set_text(&self, value: &str) -> zbus::Result<()>;
+
+    /// Pattern property
+    #[dbus_proxy(property)]
+    fn pattern(&self) -> zbus::Result<String>;
+
+    /// MinLength property
+    #[dbus_proxy(property)]
+    fn min_length(&self) -> zbus::Result<u32>;
+
+    /// MaxLength property
+    #[dbus_proxy(property)]
+    fn max_length(&self) -> zbus::Result<u32>;
+}
+
 #[dbus_proxy(interface = "org.opensuse.Agama1.Issues", assume_defaults = true)]
 trait Issues {
     /// All property
     #[dbus_proxy(property)]
     fn all(&self) -> zbus::Result<Vec<(String, String, u32, u32)>>;
+
+    /// AllWithKind property: the same values as All, followed by the kind of each issue (e.g.,
+    /// "missing_product").
+    #[dbus_proxy(property)]
+    fn all_with_kind(&self) -> zbus::Result<Vec<(String, String, u32, u32, String)>>;
 }
 
 #[dbus_proxy(
@@ -125,3 +125,35 @@ impl WithPassword {
         }
     }
 }
+
+/// Composition for questions which expect a free-form text answer.
+#[derive(Clone, Debug)]
+pub struct WithTextInput {
+    /// Text answer. Empty means no text set.
+    pub text: String,
+    /// Regular expression the whole text must match.
+    pub pattern: Option<String>,
+    /// Minimum number of characters.
+    pub min_length: Option<u32>,
+    /// Maximum number of characters.
+    pub max_length: Option<u32>,
+    /// rest of question data that is same as for other questions
+    pub base: GenericQuestion,
+}
+
+impl WithTextInput {
+    pub fn new(
+        base: GenericQuestion,
+        pattern: Option<String>,
+        min_length: Option<u32>,
+        max_length: Option<u32>,
+    ) -> Self {
+        Self {
+            text: "".to_string(),
+            pattern,
+            min_length,
+            max_length,
+            base,
+        }
+    }
+}
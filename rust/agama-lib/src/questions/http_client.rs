@@ -134,6 +134,7 @@ mod test {
                 data: HashMap::from([("a".to_owned(), "A".to_owned())]),
             },
             with_password: None,
+            with_text_input: None,
         }];
         let actual = client.list_questions().await?;
         assert_eq!(actual, expected);
@@ -180,6 +181,7 @@ mod test {
                 data: HashMap::from([("a".to_owned(), "A".to_owned())]),
             },
             with_password: None,
+            with_text_input: None,
         };
         let mut expected_question = posted_question.clone();
         expected_question.generic.id = Some(7);
@@ -216,6 +218,8 @@ mod test {
                 answer: "maybe".to_owned(),
             },
             with_password: None,
+            with_text_input: None,
+            remember: false,
         });
         let actual = client.try_answer(42).await?;
         assert_eq!(actual, expected);
@@ -27,6 +27,8 @@ use serde::{Deserialize, Serialize};
 pub struct Question {
     pub generic: GenericQuestion,
     pub with_password: Option<QuestionWithPassword>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub with_text_input: Option<QuestionWithTextInput>,
 }
 
 /// Facade of agama_lib::questions::GenericQuestion
@@ -62,11 +64,34 @@ pub struct GenericQuestion {
 #[serde(rename_all = "camelCase")]
 pub struct QuestionWithPassword {}
 
+/// Facade of agama_lib::questions::WithTextInput
+///
+/// Questions that expect a free-form text answer (e.g., a path) instead of
+/// choosing one of the options. The fields are hints to validate the text.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionWithTextInput {
+    /// Regular expression the whole text must match.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Minimum number of characters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<u32>,
+    /// Maximum number of characters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u32>,
+}
+
 #[derive(Default, Clone, Debug, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Answer {
     pub generic: GenericAnswer,
     pub with_password: Option<PasswordAnswer>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub with_text_input: Option<TextInputAnswer>,
+    /// Whether to use the same answer for the subsequent questions of the same class.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub remember: bool,
 }
 
 /// Answer needed for GenericQuestion
@@ -82,3 +107,70 @@ pub struct GenericAnswer {
 pub struct PasswordAnswer {
     pub password: String,
 }
+
+/// Answer needed for text input questions.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TextInputAnswer {
+    pub text: String,
+}
+
+/// Answer remembered for all the (generic) questions of a class.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RememberedAnswer {
+    pub class: String,
+    pub answer: String,
+}
+
+/// Where to save the answers of the session.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveAnswersParams {
+    /// File to write, relative to the directory of the default location (e.g.,
+    /// "answers-backup.json"). If missing, the default location (loaded at startup) is used.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+/// Result of saving the answers of the session.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedAnswers {
+    /// Number of saved answers.
+    pub count: u32,
+}
+
+/// D-Bus representation of an [AnsweredQuestion]: id, class, text, answer, whether it was answered
+/// automatically and the timestamp.
+pub type AnsweredQuestionTuple = (u32, String, String, String, bool, i64);
+
+/// Question answered during the session.
+///
+/// The passwords are never included.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AnsweredQuestion {
+    pub id: u32,
+    pub class: String,
+    pub text: String,
+    pub answer: String,
+    /// Whether the answer was given automatically (default answers, answers file or a
+    /// remembered answer).
+    pub auto_answered: bool,
+    /// When the question was resolved, in seconds since the Unix epoch.
+    pub timestamp: i64,
+}
+
+impl From<AnsweredQuestionTuple> for AnsweredQuestion {
+    fn from((id, class, text, answer, auto_answered, timestamp): AnsweredQuestionTuple) -> Self {
+        Self {
+            id,
+            class,
+            text,
+            answer,
+            auto_answered,
+            timestamp,
+        }
+    }
+}
@@ -0,0 +1,222 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Retries D-Bus calls which failed because of a transient problem.
+//!
+//! A D-Bus call might fail when the service is momentarily busy (e.g., while probing). The
+//! [`retry`] function repeats the call, waiting a bit longer after each attempt, as long as the
+//! error looks transient.
+//!
+//! Only idempotent calls (e.g., reading a property) should be retried. Actions like logging into
+//! an iSCSI target or committing the changes must not be wrapped.
+//!
+//! The policy can be adjusted through the `AGAMA_DBUS_RETRIES` (number of attempts) and
+//! `AGAMA_DBUS_RETRY_DELAY` (initial delay in milliseconds) environment variables.
+
+use std::{future::Future, time::Duration};
+
+/// Retry policy for D-Bus calls.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts (including the first one).
+    pub attempts: u32,
+    /// Delay before the first retry. It is doubled after each attempt.
+    pub initial_delay: Duration,
+    /// Maximum delay between attempts.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    const ATTEMPTS_VAR: &'static str = "AGAMA_DBUS_RETRIES";
+    const DELAY_VAR: &'static str = "AGAMA_DBUS_RETRY_DELAY";
+
+    /// Returns a policy which does not retry at all.
+    pub fn no_retry() -> Self {
+        Self {
+            attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the default policy adjusted with the values from the environment.
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Some(attempts) = read_env_var::<u32>(Self::ATTEMPTS_VAR) {
+            policy.attempts = attempts.max(1);
+        }
+        if let Some(delay) = read_env_var::<u64>(Self::DELAY_VAR) {
+            policy.initial_delay = Duration::from_millis(delay);
+        }
+        policy
+    }
+
+    /// Returns the delay to wait after the given attempt (starting at 1).
+    fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2_u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay)
+    }
+}
+
+fn read_env_var<T: std::str::FromStr>(name: &str) -> Option<T> {
+    let value = std::env::var(name).ok()?;
+    match value.parse::<T>() {
+        Ok(value) => Some(value),
+        Err(_) => {
+            log::warn!("Ignoring invalid value for {}: '{}'", name, value);
+            None
+        }
+    }
+}
+
+/// Determines whether the error is a transient one, so it makes sense to retry the call.
+pub fn is_transient(error: &zbus::Error) -> bool {
+    match error {
+        zbus::Error::InputOutput(_) => true,
+        zbus::Error::FDO(error) => matches!(
+            **error,
+            zbus::fdo::Error::NoReply(_)
+                | zbus::fdo::Error::ServiceUnknown(_)
+                | zbus::fdo::Error::LimitsExceeded(_)
+                | zbus::fdo::Error::Timeout(_)
+                | zbus::fdo::Error::TimedOut(_)
+        ),
+        zbus::Error::MethodError(name, _, _) => matches!(
+            name.as_str(),
+            "org.freedesktop.DBus.Error.NoReply"
+                | "org.freedesktop.DBus.Error.ServiceUnknown"
+                | "org.freedesktop.DBus.Error.LimitsExceeded"
+                | "org.freedesktop.DBus.Error.Timeout"
+                | "org.freedesktop.DBus.Error.TimedOut"
+        ),
+        _ => false,
+    }
+}
+
+/// Runs the given D-Bus call, retrying it according to the policy on transient errors.
+///
+/// * `policy`: retry policy.
+/// * `name`: name of the call, used for logging.
+/// * `call`: function which performs the call.
+pub async fn retry<T, F, Fut>(policy: &RetryPolicy, name: &str, call: F) -> zbus::Result<T>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = zbus::Result<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match call().await {
+            Err(error) if attempt < policy.attempts && is_transient(&error) => {
+                let delay = policy.delay(attempt);
+                log::warn!(
+                    "D-Bus call {} failed (attempt {}/{}), retrying in {:?}: {}",
+                    name,
+                    attempt,
+                    policy.attempts,
+                    delay,
+                    error
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{retry, RetryPolicy};
+    use std::{
+        sync::atomic::{AtomicU32, Ordering},
+        time::Duration,
+    };
+    use tokio::test;
+
+    fn test_policy() -> RetryPolicy {
+        RetryPolicy {
+            attempts: 3,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(2),
+        }
+    }
+
+    fn transient_error() -> zbus::Error {
+        zbus::Error::FDO(Box::new(zbus::fdo::Error::NoReply("busy".to_string())))
+    }
+
+    #[test]
+    async fn test_retry_transient_error() {
+        let calls = AtomicU32::new(0);
+        let result = retry(&test_policy(), "Test", || async {
+            if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(transient_error())
+            } else {
+                Ok(42)
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    async fn test_retry_gives_up() {
+        let calls = AtomicU32::new(0);
+        let result: zbus::Result<()> = retry(&test_policy(), "Test", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(transient_error())
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    async fn test_no_retry_on_other_errors() {
+        let calls = AtomicU32::new(0);
+        let result: zbus::Result<()> = retry(&test_policy(), "Test", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(zbus::Error::InvalidReply)
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    async fn test_delay() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(10), Duration::from_secs(2));
+    }
+}
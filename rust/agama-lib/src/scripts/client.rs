@@ -0,0 +1,62 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements a client to access Agama's D-Bus API related to the post-installation scripts.
+
+use zbus::Connection;
+
+use crate::{error::ServiceError, scripts::model::ScriptsSettings};
+
+use super::proxies::ScriptsProxy;
+
+/// Client to connect to Agama's D-Bus API for the post-installation scripts.
+#[derive(Clone)]
+pub struct ScriptsClient<'a> {
+    scripts_proxy: ScriptsProxy<'a>,
+}
+
+impl<'a> ScriptsClient<'a> {
+    pub async fn new(connection: Connection) -> Result<ScriptsClient<'a>, ServiceError> {
+        let scripts_proxy = ScriptsProxy::new(&connection).await?;
+
+        Ok(Self { scripts_proxy })
+    }
+
+    /// Returns the registered scripts.
+    pub async fn get_config(&self) -> Result<ScriptsSettings, ServiceError> {
+        let serialized_string = self.scripts_proxy.get_config().await?;
+        let settings = serde_json::from_str(serialized_string.as_str())?;
+        Ok(settings)
+    }
+
+    /// Sets the scripts to register, replacing the previous ones.
+    ///
+    /// * `config`: scripts settings.
+    pub async fn set_config(&self, config: &ScriptsSettings) -> Result<(), ServiceError> {
+        let serialized_string = serde_json::to_string(config)?;
+        let result = self.scripts_proxy.set_config(&serialized_string).await?;
+        if result != 0 {
+            return Err(ServiceError::UnsuccessfulAction(
+                "Set scripts config".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the model of the scripts to run in the installed system.
+
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+
+/// Shell used when the script does not set one.
+pub const DEFAULT_SHELL: &str = "/bin/sh";
+
+/// When a script runs in the installed system.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ScriptTrigger {
+    /// Only on the first boot.
+    #[default]
+    FirstBoot,
+    /// On every boot.
+    EveryBoot,
+}
+
+/// Script to run in the installed system.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Script {
+    /// Unique name of the script (alphanumeric chars, '-', '_' or '.').
+    pub name: String,
+    /// Content of the script.
+    pub body: String,
+    /// Shell to run the script with.
+    #[serde(default = "default_shell")]
+    pub shell: String,
+    /// Environment variables to set when running the script.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Locale to run the script with (e.g., "en_US.UTF-8"). It sets the `LANG` variable.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(default)]
+    pub trigger: ScriptTrigger,
+}
+
+fn default_shell() -> String {
+    DEFAULT_SHELL.to_string()
+}
+
+impl Script {
+    /// Returns the list of problems found in the script.
+    ///
+    /// The shell is searched in the installation system, which is built from the same packages
+    /// as the installed one.
+    pub fn validate(&self) -> Vec<String> {
+        let mut issues = vec![];
+        if !is_valid_name(&self.name) {
+            issues.push(format!("Invalid script name: '{}'", self.name));
+        }
+        if self.body.trim().is_empty() {
+            issues.push(format!("The script {} is empty", self.name));
+        }
+        if !self.shell.starts_with('/') || !Path::new(&self.shell).is_file() {
+            issues.push(format!(
+                "The shell of the script {} does not exist: '{}'",
+                self.name, self.shell
+            ));
+        }
+        for key in self.env.keys() {
+            if !is_valid_variable(key) {
+                issues.push(format!(
+                    "Invalid environment variable in script {}: '{}'",
+                    self.name, key
+                ));
+            }
+        }
+        if let Some(locale) = &self.locale {
+            if locale.is_empty() || !locale.chars().all(|c| c.is_ascii_graphic()) {
+                issues.push(format!(
+                    "Invalid locale in script {}: '{}'",
+                    self.name, locale
+                ));
+            }
+        }
+        issues
+    }
+
+    /// Returns the environment to run the script with, including the locale variables.
+    pub fn environment(&self) -> BTreeMap<String, String> {
+        let mut env = self.env.clone();
+        if let Some(locale) = &self.locale {
+            env.insert("LANG".to_string(), locale.clone());
+        }
+        env
+    }
+}
+
+/// Scripts to register in the installed system.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+pub struct ScriptsSettings {
+    #[serde(default)]
+    pub scripts: Vec<Script>,
+}
+
+impl ScriptsSettings {
+    /// Returns the list of problems found in the scripts.
+    ///
+    /// An empty list means that the settings are valid.
+    pub fn validate(&self) -> Vec<String> {
+        let mut issues = vec![];
+        for (index, script) in self.scripts.iter().enumerate() {
+            issues.extend(script.validate());
+            if self.scripts[..index].iter().any(|s| s.name == script.name) {
+                issues.push(format!("Duplicated script name: '{}'", script.name));
+            }
+        }
+        issues
+    }
+
+    /// Adds the given script, replacing the existing one with the same name.
+    pub fn set_script(&mut self, script: Script) {
+        match self.scripts.iter_mut().find(|s| s.name == script.name) {
+            Some(existing) => *existing = script,
+            None => self.scripts.push(script),
+        }
+    }
+
+    /// Removes the script with the given name, returning whether it existed.
+    pub fn remove_script(&mut self, name: &str) -> bool {
+        let len = self.scripts.len();
+        self.scripts.retain(|s| s.name != name);
+        self.scripts.len() != len
+    }
+}
+
+/// Whether the name can be used for the script files (alphanumeric chars, '-', '_' or '.').
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+/// Whether the name is a valid environment variable name.
+fn is_valid_variable(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(name: &str, body: &str) -> Script {
+        Script {
+            name: name.to_string(),
+            body: body.to_string(),
+            shell: DEFAULT_SHELL.to_string(),
+            env: BTreeMap::new(),
+            locale: None,
+            trigger: ScriptTrigger::FirstBoot,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut register = script("register", "echo hello");
+        register
+            .env
+            .insert("PROXY_URL".to_string(), "http://proxy".to_string());
+        register.locale = Some("en_US.UTF-8".to_string());
+        let settings = ScriptsSettings {
+            scripts: vec![register, script("cleanup", "rm -rf /tmp/setup")],
+        };
+        assert!(settings.validate().is_empty());
+    }
+
+    #[test]
+    fn test_validate_wrong_settings() {
+        let mut wrong = script("../wrong", "  \n");
+        wrong.shell = "/usr/bin/no-such-shell".to_string();
+        wrong.env.insert("1VAR".to_string(), "value".to_string());
+        wrong.locale = Some("en US".to_string());
+        let settings = ScriptsSettings {
+            scripts: vec![wrong, script("setup", "true"), script("setup", "false")],
+        };
+        assert_eq!(settings.validate().len(), 6);
+    }
+
+    #[test]
+    fn test_environment() {
+        let mut setup = script("setup", "true");
+        setup.env.insert("MODE".to_string(), "server".to_string());
+        setup.locale = Some("de_DE.UTF-8".to_string());
+        let env = setup.environment();
+        assert_eq!(env.get("LANG"), Some(&"de_DE.UTF-8".to_string()));
+        assert_eq!(env.get("MODE"), Some(&"server".to_string()));
+    }
+
+    #[test]
+    fn test_deserialize_defaults() {
+        let setup: Script = serde_json::from_str(r#"{"name": "setup", "body": "true"}"#).unwrap();
+        assert_eq!(setup.shell, DEFAULT_SHELL);
+        assert_eq!(setup.trigger, ScriptTrigger::FirstBoot);
+    }
+
+    #[test]
+    fn test_set_and_remove_script() {
+        let mut settings = ScriptsSettings::default();
+        settings.set_script(script("setup", "true"));
+        settings.set_script(script("setup", "false"));
+        assert_eq!(settings.scripts.len(), 1);
+        assert_eq!(settings.scripts[0].body, "false");
+        assert!(settings.remove_script("setup"));
+        assert!(!settings.remove_script("setup"));
+    }
+}
@@ -27,7 +27,7 @@ pub mod proxies;
 mod settings;
 mod store;
 
-pub use client::{Pattern, SelectedBy, SoftwareClient, UnknownSelectedBy};
+pub use client::{only_required_warnings, Pattern, SelectedBy, SoftwareClient, UnknownSelectedBy};
 pub use http_client::SoftwareHTTPClient;
 pub use settings::SoftwareSettings;
 pub use store::SoftwareStore;
@@ -18,8 +18,14 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use super::proxies::Software1Proxy;
-use crate::error::ServiceError;
+use super::{
+    model::{PackageChange, RepositoryConfig, ServiceAction, ServiceConfig, SystemdTarget},
+    proxies::Software1Proxy,
+};
+use crate::{
+    error::ServiceError,
+    retry::{retry, RetryPolicy},
+};
 use serde::Serialize;
 use serde_repr::Serialize_repr;
 use std::collections::HashMap;
@@ -54,6 +60,38 @@ pub enum SelectedBy {
     None = 2,
 }
 
+/// Returns the warnings about disabling the recommended packages.
+///
+/// The patterns selected by the user only install their required packages, and the ones which
+/// were only recommended by other patterns are dropped.
+///
+/// * `before`: patterns selected before disabling the recommended packages.
+/// * `after`: patterns selected after disabling them.
+pub fn only_required_warnings(
+    before: &HashMap<String, SelectedBy>,
+    after: &HashMap<String, SelectedBy>,
+) -> Vec<String> {
+    let mut names: Vec<&String> = before.keys().collect();
+    names.sort();
+
+    let mut warnings = vec![];
+    for name in names {
+        let now = after.get(name).copied().unwrap_or(SelectedBy::None);
+        match (before[name], now) {
+            (SelectedBy::User, SelectedBy::User) => warnings.push(format!(
+                "Pattern '{}' will only install its required packages",
+                name
+            )),
+            (SelectedBy::User | SelectedBy::Auto, SelectedBy::None) => warnings.push(format!(
+                "Pattern '{}' is not selected anymore because recommended packages are disabled",
+                name
+            )),
+            _ => {}
+        }
+    }
+    warnings
+}
+
 #[derive(Debug, thiserror::Error)]
 #[error("Unknown selected by value: '{0}'")]
 pub struct UnknownSelectedBy(u8);
@@ -74,58 +112,71 @@ impl TryFrom<u8> for SelectedBy {
 #[derive(Clone)]
 pub struct SoftwareClient<'a> {
     software_proxy: Software1Proxy<'a>,
+    retry_policy: RetryPolicy,
 }
 
 impl<'a> SoftwareClient<'a> {
     pub async fn new(connection: Connection) -> Result<SoftwareClient<'a>, ServiceError> {
         Ok(Self {
             software_proxy: Software1Proxy::new(&connection).await?,
+            retry_policy: RetryPolicy::from_env(),
         })
     }
 
+    /// Sets the policy to retry the read calls on transient errors.
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            retry_policy,
+            ..self
+        }
+    }
+
     /// Returns the available patterns
     pub async fn patterns(&self, filtered: bool) -> Result<Vec<Pattern>, ServiceError> {
-        let patterns: Vec<Pattern> = self
-            .software_proxy
-            .list_patterns(filtered)
-            .await?
-            .into_iter()
-            .map(
-                |(name, (category, description, icon, summary, order))| Pattern {
-                    name,
-                    category,
-                    icon,
-                    description,
-                    summary,
-                    order,
-                },
-            )
-            .collect();
+        let patterns: Vec<Pattern> = retry(&self.retry_policy, "ListPatterns", || {
+            self.software_proxy.list_patterns(filtered)
+        })
+        .await?
+        .into_iter()
+        .map(
+            |(name, (category, description, icon, summary, order))| Pattern {
+                name,
+                category,
+                icon,
+                description,
+                summary,
+                order,
+            },
+        )
+        .collect();
         Ok(patterns)
     }
 
     /// Returns the ids of patterns selected by user
     pub async fn user_selected_patterns(&self) -> Result<Vec<String>, ServiceError> {
-        let patterns: Vec<String> = self
-            .software_proxy
-            .selected_patterns()
-            .await?
-            .into_iter()
-            .filter_map(|(id, reason)| match SelectedBy::try_from(reason) {
-                Ok(SelectedBy::User) => Some(id),
-                Ok(_reason) => None,
-                Err(e) => {
-                    log::warn!("Ignoring pattern {}. Error: {}", &id, e);
-                    None
-                }
-            })
-            .collect();
+        let patterns: Vec<String> = retry(&self.retry_policy, "SelectedPatterns", || {
+            self.software_proxy.selected_patterns()
+        })
+        .await?
+        .into_iter()
+        .filter_map(|(id, reason)| match SelectedBy::try_from(reason) {
+            Ok(SelectedBy::User) => Some(id),
+            Ok(_reason) => None,
+            Err(e) => {
+                log::warn!("Ignoring pattern {}. Error: {}", &id, e);
+                None
+            }
+        })
+        .collect();
         Ok(patterns)
     }
 
     /// Returns the selected pattern and the reason each one selected.
     pub async fn selected_patterns(&self) -> Result<HashMap<String, SelectedBy>, ServiceError> {
-        let patterns = self.software_proxy.selected_patterns().await?;
+        let patterns = retry(&self.retry_policy, "SelectedPatterns", || {
+            self.software_proxy.selected_patterns()
+        })
+        .await?;
         let patterns = patterns
             .into_iter()
             .filter_map(|(id, reason)| match SelectedBy::try_from(reason) {
@@ -161,11 +212,186 @@ impl<'a> SoftwareClient<'a> {
         }
     }
 
+    /// Returns whether each provision (e.g., a package name or a file) is selected to install.
+    ///
+    /// * `provisions`: provisions to check.
+    pub async fn provisions_selected(
+        &self,
+        provisions: &[&str],
+    ) -> Result<Vec<bool>, ServiceError> {
+        Ok(self.software_proxy.provisions_selected(provisions).await?)
+    }
+
     /// Returns the required space for installing the selected patterns.
     ///
     /// It returns a formatted string including the size and the unit.
     pub async fn used_disk_space(&self) -> Result<String, ServiceError> {
-        Ok(self.software_proxy.used_disk_space().await?)
+        Ok(retry(&self.retry_policy, "UsedDiskSpace", || {
+            self.software_proxy.used_disk_space()
+        })
+        .await?)
+    }
+
+    /// Returns the packages whose version changes, sorted by name.
+    ///
+    /// The solver compares the installed system with the selected packages, so it is meant for
+    /// upgrades. On a fresh installation all the packages are reported as added.
+    pub async fn package_changes(&self) -> Result<Vec<PackageChange>, ServiceError> {
+        let changes = retry(&self.retry_policy, "PackageChanges", || {
+            self.software_proxy.package_changes()
+        })
+        .await?;
+        let mut changes: Vec<_> = changes
+            .iter()
+            .filter_map(|(name, current, target)| PackageChange::new(name, current, target))
+            .collect();
+        changes.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(changes)
+    }
+
+    /// Returns the systemd default target for the installed system.
+    ///
+    /// It returns `None` if the product default is used.
+    pub async fn default_target(&self) -> Result<Option<SystemdTarget>, ServiceError> {
+        let target = retry(&self.retry_policy, "DefaultTarget", || {
+            self.software_proxy.default_target()
+        })
+        .await?;
+        if target.is_empty() {
+            return Ok(None);
+        }
+        let target = target
+            .parse::<SystemdTarget>()
+            .map_err(|e| ServiceError::InternalError(e.to_string()))?;
+        Ok(Some(target))
+    }
+
+    /// Sets the systemd default target for the installed system.
+    ///
+    /// The target is applied during the finish phase of the installation.
+    pub async fn set_default_target(&self, target: SystemdTarget) -> Result<(), ServiceError> {
+        Ok(self
+            .software_proxy
+            .set_default_target(target.as_str())
+            .await?)
+    }
+
+    /// Returns the systemd units to enable, disable or mask in the installed system.
+    pub async fn services(&self) -> Result<Vec<ServiceConfig>, ServiceError> {
+        let services = retry(&self.retry_policy, "Services", || {
+            self.software_proxy.services()
+        })
+        .await?;
+        services
+            .into_iter()
+            .map(|(name, action)| {
+                let action = action
+                    .parse::<ServiceAction>()
+                    .map_err(|e| ServiceError::InternalError(e.to_string()))?;
+                Ok(ServiceConfig { name, action })
+            })
+            .collect()
+    }
+
+    /// Replaces the systemd units to enable, disable or mask in the installed system.
+    ///
+    /// The actions are applied during the finish phase of the installation.
+    ///
+    /// * `services`: units and their actions.
+    pub async fn set_services(&self, services: &[ServiceConfig]) -> Result<(), ServiceError> {
+        let units: Vec<String> = services.iter().map(ServiceConfig::unit).collect();
+        let services: Vec<_> = units
+            .iter()
+            .zip(services)
+            .map(|(unit, s)| (unit.as_str(), s.action.as_str()))
+            .collect();
+        Ok(self.software_proxy.set_services(&services).await?)
+    }
+
+    /// Returns the architecture of the system to install.
+    pub async fn architecture(&self) -> Result<String, ServiceError> {
+        Ok(retry(&self.retry_policy, "Architecture", || {
+            self.software_proxy.architecture()
+        })
+        .await?)
+    }
+
+    /// Whether the solver is allowed to select multilib (e.g., 32-bit on 64-bit) packages.
+    pub async fn multilib(&self) -> Result<bool, ServiceError> {
+        Ok(retry(&self.retry_policy, "Multilib", || {
+            self.software_proxy.multilib()
+        })
+        .await?)
+    }
+
+    /// Allows or forbids the selection of multilib packages.
+    ///
+    /// * `enabled`: whether to allow multilib packages.
+    pub async fn set_multilib(&self, enabled: bool) -> Result<(), ServiceError> {
+        Ok(self.software_proxy.set_multilib(enabled).await?)
+    }
+
+    /// Whether the solver only selects the hard dependencies (no recommended packages).
+    pub async fn only_required(&self) -> Result<bool, ServiceError> {
+        Ok(retry(&self.retry_policy, "OnlyRequired", || {
+            self.software_proxy.only_required()
+        })
+        .await?)
+    }
+
+    /// Enables or disables the installation of recommended packages.
+    ///
+    /// The software proposal is recalculated.
+    ///
+    /// * `only_required`: whether to install only the hard dependencies.
+    pub async fn set_only_required(&self, only_required: bool) -> Result<(), ServiceError> {
+        Ok(self.software_proxy.set_only_required(only_required).await?)
+    }
+
+    /// Returns the additional repositories.
+    pub async fn extra_repositories(&self) -> Result<Vec<RepositoryConfig>, ServiceError> {
+        let repositories = retry(&self.retry_policy, "ExtraRepositories", || {
+            self.software_proxy.extra_repositories()
+        })
+        .await?;
+        let repositories = repositories
+            .into_iter()
+            .map(|(alias, url, name, persistent)| RepositoryConfig {
+                name: if name.is_empty() { None } else { Some(name) },
+                alias,
+                url,
+                persistent,
+                repo_type: None,
+                source: None,
+            })
+            .collect();
+        Ok(repositories)
+    }
+
+    /// Replaces the additional repositories.
+    ///
+    /// The persistent ones are written to the zypp configuration of the installed system.
+    ///
+    /// * `repositories`: repositories to use.
+    pub async fn set_extra_repositories(
+        &self,
+        repositories: &[RepositoryConfig],
+    ) -> Result<(), ServiceError> {
+        let repositories: Vec<_> = repositories
+            .iter()
+            .map(|r| {
+                (
+                    r.alias.as_str(),
+                    r.url.as_str(),
+                    r.name.as_deref().unwrap_or_default(),
+                    r.persistent,
+                )
+            })
+            .collect();
+        Ok(self
+            .software_proxy
+            .set_extra_repositories(&repositories)
+            .await?)
     }
 
     /// Starts the process to read the repositories data.
@@ -173,3 +399,34 @@ impl<'a> SoftwareClient<'a> {
         Ok(self.software_proxy.probe().await?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{only_required_warnings, SelectedBy};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_only_required_warnings() {
+        let before = HashMap::from([
+            ("base".to_string(), SelectedBy::User),
+            ("x11".to_string(), SelectedBy::Auto),
+            ("kde".to_string(), SelectedBy::None),
+        ]);
+        let after = HashMap::from([
+            ("base".to_string(), SelectedBy::User),
+            ("x11".to_string(), SelectedBy::None),
+            ("kde".to_string(), SelectedBy::None),
+        ]);
+        assert_eq!(
+            only_required_warnings(&before, &after),
+            vec![
+                "Pattern 'base' will only install its required packages".to_string(),
+                "Pattern 'x11' is not selected anymore because recommended packages are disabled"
+                    .to_string()
+            ]
+        );
+        assert!(only_required_warnings(&after, &after)
+            .iter()
+            .all(|w| w.contains("'base'")));
+    }
+}
@@ -49,17 +49,7 @@ impl SoftwareHTTPClient {
     pub async fn user_selected_patterns(&self) -> Result<Vec<String>, ServiceError> {
         // TODO: this way we unnecessarily ask D-Bus (via web.rs) also for the product and then ignore it
         let config = self.get_config().await?;
-
-        let Some(patterns_map) = config.patterns else {
-            return Ok(vec![]);
-        };
-
-        let patterns: Vec<String> = patterns_map
-            .into_iter()
-            .filter_map(|(name, is_selected)| if is_selected { Some(name) } else { None })
-            .collect();
-
-        Ok(patterns)
+        Ok(config.selected_patterns())
     }
 
     /// Selects patterns by user
@@ -71,6 +61,10 @@ impl SoftwareHTTPClient {
             product: None,
             // TODO: SoftwareStore only passes true bools, false branch is untested
             patterns: Some(patterns),
+            default_target: None,
+            extra_repositories: None,
+            only_required: None,
+            services: None,
         };
         self.set_config(&config).await
     }
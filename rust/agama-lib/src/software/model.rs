@@ -18,16 +18,669 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
+use crate::dbus::{get_optional_property, get_property};
+use chrono::{DateTime, NaiveDate};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use zbus::zvariant;
 
 /// Software service configuration (product, patterns, etc.).
 #[derive(Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct SoftwareConfig {
     /// A map where the keys are the pattern names and the values whether to install them or not.
     pub patterns: Option<HashMap<String, bool>>,
     /// Name of the product to install.
     pub product: Option<String>,
+    /// systemd default target of the installed system.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_target: Option<SystemdTarget>,
+    /// Additional repositories on top of the product ones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_repositories: Option<Vec<RepositoryConfig>>,
+    /// Whether to install only the hard dependencies, skipping the recommended packages
+    /// (minimal installation).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub only_required: Option<bool>,
+    /// systemd units to enable, disable or mask in the installed system.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub services: Option<Vec<ServiceConfig>>,
+}
+
+impl SoftwareConfig {
+    /// Returns the names of the patterns which should be installed.
+    pub fn selected_patterns(&self) -> Vec<String> {
+        let Some(patterns) = &self.patterns else {
+            return vec![];
+        };
+
+        patterns
+            .iter()
+            .filter_map(|(name, is_selected)| {
+                if *is_selected {
+                    Some(name.to_owned())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// URL schemes supported by libzypp for the additional repositories.
+pub const REPOSITORY_SCHEMES: [&str; 11] = [
+    "http", "https", "ftp", "tftp", "nfs", "smb", "cifs", "dir", "file", "iso", "cd",
+];
+
+/// Directory, relative to the target root, holding the zypp repositories.
+pub const ZYPP_REPOS_DIR: &str = "etc/zypp/repos.d";
+
+/// Additional repository to use during the installation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositoryConfig {
+    /// Unique identifier of the repository (e.g., "my-tools").
+    pub alias: String,
+    /// URL of the repository.
+    pub url: String,
+    /// Human readable name. The alias is used if it is not given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Whether the repository is kept in the installed system. Otherwise, it is only used during
+    /// the installation.
+    #[serde(default)]
+    pub persistent: bool,
+    /// Kind of repository. It is deduced from the URL and only reported when reading the
+    /// configuration.
+    #[serde(
+        rename = "type",
+        default,
+        skip_deserializing,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub repo_type: Option<RepositoryType>,
+    /// Local directory or ISO image backing the repository, if any. Like `repo_type`, it is only
+    /// reported when reading the configuration.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// Kind of additional repository.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum RepositoryType {
+    /// Remote repository (HTTP, NFS, etc.).
+    Online,
+    /// Local directory (`dir://` or `file://` URLs).
+    Directory,
+    /// Local ISO image (`iso:///path/to/image.iso` or `iso:/?iso=/path/to/image.iso`).
+    Iso,
+}
+
+/// Files or directories, relative to the repository root, that identify a repository.
+const REPOSITORY_METADATA: [&str; 3] = ["repodata/repomd.xml", "content", "media.1"];
+
+/// Offset and value of the ISO 9660 signature ("CD001" in the primary volume descriptor).
+const ISO9660_SIGNATURE: (u64, &[u8; 5]) = (32769, b"CD001");
+
+/// Whether the given directory contains repository metadata.
+///
+/// * `path`: directory to check.
+pub fn has_repository_metadata(path: &Path) -> bool {
+    REPOSITORY_METADATA
+        .iter()
+        .any(|entry| path.join(entry).exists())
+}
+
+/// Whether the given file is a readable ISO 9660 image.
+///
+/// * `path`: file to check.
+pub fn is_iso_image(path: &Path) -> bool {
+    let Ok(mut file) = File::open(path) else {
+        return false;
+    };
+    let (offset, signature) = ISO9660_SIGNATURE;
+    let mut buffer = [0u8; 5];
+    file.seek(SeekFrom::Start(offset)).is_ok()
+        && file.read_exact(&mut buffer).is_ok()
+        && &buffer == signature
+}
+
+impl RepositoryConfig {
+    /// Checks the alias and the URL of the repository.
+    ///
+    /// It returns the list of problems, if any.
+    pub fn validate(&self) -> Vec<String> {
+        let mut issues = vec![];
+
+        let valid_alias = !self.alias.is_empty()
+            && !self.alias.starts_with('.')
+            && self
+                .alias
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_.:".contains(c));
+        if !valid_alias {
+            issues.push(format!("Invalid repository alias: '{}'", self.alias));
+        }
+
+        // the name and the URL are written as is to the repository file
+        if self
+            .url
+            .chars()
+            .any(|c| c.is_control() || c.is_whitespace())
+        {
+            issues.push(format!(
+                "Invalid characters in the URL of repository '{}'",
+                self.alias
+            ));
+        }
+        if self
+            .name
+            .as_ref()
+            .is_some_and(|name| name.chars().any(char::is_control))
+        {
+            issues.push(format!(
+                "Invalid characters in the name of repository '{}'",
+                self.alias
+            ));
+        }
+
+        match url::Url::parse(&self.url) {
+            Ok(url) if !REPOSITORY_SCHEMES.contains(&url.scheme()) => issues.push(format!(
+                "Unsupported URL scheme for repository '{}': '{}'",
+                self.alias,
+                url.scheme()
+            )),
+            Ok(_) => {}
+            Err(_) => issues.push(format!(
+                "Invalid URL for repository '{}': '{}'",
+                self.alias, self.url
+            )),
+        }
+
+        // the mount point of the image is not available in the installed system
+        if self.persistent && self.repository_type() == RepositoryType::Iso {
+            issues.push(format!(
+                "ISO-backed repository '{}' cannot be persistent",
+                self.alias
+            ));
+        }
+
+        issues
+    }
+
+    /// Returns the kind of repository according to its URL.
+    pub fn repository_type(&self) -> RepositoryType {
+        match url::Url::parse(&self.url).as_ref().map(url::Url::scheme) {
+            Ok("dir" | "file") => RepositoryType::Directory,
+            Ok("iso") => RepositoryType::Iso,
+            _ => RepositoryType::Online,
+        }
+    }
+
+    /// Returns the local directory or ISO image backing the repository, if any.
+    pub fn local_path(&self) -> Option<PathBuf> {
+        let url = url::Url::parse(&self.url).ok()?;
+        let path = match self.repository_type() {
+            RepositoryType::Online => return None,
+            RepositoryType::Directory => url.path().to_string(),
+            RepositoryType::Iso => url
+                .query_pairs()
+                .find(|(key, _)| key == "iso")
+                .map(|(_, value)| value.to_string())
+                .unwrap_or_else(|| url.path().to_string()),
+        };
+        if path.is_empty() || path == "/" {
+            None
+        } else {
+            Some(PathBuf::from(path))
+        }
+    }
+
+    /// Returns a copy including the repository type and its local source.
+    pub fn with_source(&self) -> Self {
+        Self {
+            repo_type: Some(self.repository_type()),
+            source: self
+                .local_path()
+                .map(|path| path.to_string_lossy().to_string()),
+            ..self.clone()
+        }
+    }
+
+    /// Checks that the local directory or ISO image backing the repository is usable.
+    ///
+    /// Online repositories are not checked. It returns the problem, if any.
+    pub fn validate_source(&self) -> Option<String> {
+        let repo_type = self.repository_type();
+        if repo_type == RepositoryType::Online {
+            return None;
+        }
+
+        let Some(path) = self.local_path() else {
+            return Some(format!(
+                "Missing local path for repository '{}'",
+                self.alias
+            ));
+        };
+        if !path.exists() {
+            return Some(format!(
+                "Path for repository '{}' does not exist: '{}'",
+                self.alias,
+                path.display()
+            ));
+        }
+
+        match repo_type {
+            RepositoryType::Directory if !path.is_dir() || !has_repository_metadata(&path) => {
+                Some(format!(
+                    "No repository metadata found for repository '{}' at '{}'",
+                    self.alias,
+                    path.display()
+                ))
+            }
+            RepositoryType::Iso if !path.is_file() || !is_iso_image(&path) => Some(format!(
+                "Not a readable ISO image for repository '{}': '{}'",
+                self.alias,
+                path.display()
+            )),
+            _ => None,
+        }
+    }
+
+    /// Returns the path of the repository file, relative to the target root.
+    pub fn repo_file_path(&self) -> String {
+        format!("{}/{}.repo", ZYPP_REPOS_DIR, self.alias)
+    }
+
+    /// Renders the repository as a zypp `.repo` file.
+    ///
+    /// The repository is expected to be valid (see [RepositoryConfig::validate]).
+    pub fn to_repo_file(&self) -> String {
+        let name = self.name.as_deref().unwrap_or(&self.alias);
+        format!(
+            "[{}]\nname={}\nenabled=1\nautorefresh=1\nbaseurl={}\n",
+            self.alias, name, self.url
+        )
+    }
+}
+
+/// Checks a list of repositories, including that the aliases are unique.
+pub fn validate_repositories(repositories: &[RepositoryConfig]) -> Vec<String> {
+    let mut issues: Vec<String> = repositories.iter().flat_map(|r| r.validate()).collect();
+    for (index, repo) in repositories.iter().enumerate() {
+        if repositories[..index].iter().any(|r| r.alias == repo.alias) {
+            issues.push(format!("Duplicated repository alias: '{}'", repo.alias));
+        }
+    }
+    issues
+}
+
+/// Architecture constraints for the packages to install.
+#[derive(Clone, Debug, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchitectureSettings {
+    /// Architecture of the system to install (e.g., "x86_64").
+    pub architecture: String,
+    /// Whether multilib (e.g., 32-bit on 64-bit) packages are allowed.
+    pub multilib: bool,
+}
+
+/// Architecture settings that can be changed.
+#[derive(Clone, Debug, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchitectureParams {
+    /// Whether multilib (e.g., 32-bit on 64-bit) packages are allowed.
+    pub multilib: bool,
+}
+
+/// How a package changes between the installed system and the selected packages.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum PackageChangeKind {
+    Added,
+    Removed,
+    Upgraded,
+    Downgraded,
+}
+
+/// Package whose version changes during an upgrade.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageChange {
+    pub name: String,
+    /// Version in the installed system (`None` if the package is added).
+    pub current_version: Option<String>,
+    /// Selected version (`None` if the package is removed).
+    pub target_version: Option<String>,
+    pub kind: PackageChangeKind,
+}
+
+impl PackageChange {
+    /// Builds the change of a package, returning `None` if the version does not change.
+    ///
+    /// * `name`: package name.
+    /// * `current`: installed version, or an empty string if it is not installed.
+    /// * `target`: selected version, or an empty string if it is removed.
+    pub fn new(name: &str, current: &str, target: &str) -> Option<Self> {
+        let kind = match (current.is_empty(), target.is_empty()) {
+            (true, true) => return None,
+            (true, false) => PackageChangeKind::Added,
+            (false, true) => PackageChangeKind::Removed,
+            (false, false) => match compare_versions(current, target) {
+                Ordering::Less => PackageChangeKind::Upgraded,
+                Ordering::Greater => PackageChangeKind::Downgraded,
+                Ordering::Equal => return None,
+            },
+        };
+        let version = |v: &str| (!v.is_empty()).then(|| v.to_string());
+        Some(Self {
+            name: name.to_string(),
+            current_version: version(current),
+            target_version: version(target),
+            kind,
+        })
+    }
+}
+
+/// Compares two package versions in the `[epoch:]version[-release]` form, as RPM does.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn split(evr: &str) -> (u64, &str, &str) {
+        let (epoch, rest) = match evr.split_once(':') {
+            Some((epoch, rest)) if epoch.chars().all(|c| c.is_ascii_digit()) => {
+                (epoch.parse().unwrap_or(0), rest)
+            }
+            _ => (0, evr),
+        };
+        let (version, release) = rest.rsplit_once('-').unwrap_or((rest, ""));
+        (epoch, version, release)
+    }
+
+    let (a_epoch, a_version, a_release) = split(a);
+    let (b_epoch, b_version, b_release) = split(b);
+    a_epoch
+        .cmp(&b_epoch)
+        .then_with(|| rpmvercmp(a_version, b_version))
+        .then_with(|| rpmvercmp(a_release, b_release))
+}
+
+/// Implements the RPM algorithm to compare a version or release string.
+///
+/// The strings are split into numeric and alphabetic segments which are compared one by one. A
+/// tilde sorts before anything (even the end of the string) and a caret sorts after the end of
+/// the string but before anything else.
+fn rpmvercmp(a: &str, b: &str) -> Ordering {
+    let is_separator = |c: char| !c.is_ascii_alphanumeric() && c != '~' && c != '^';
+    let mut a = a;
+    let mut b = b;
+    loop {
+        a = a.trim_start_matches(is_separator);
+        b = b.trim_start_matches(is_separator);
+
+        if a.starts_with('~') || b.starts_with('~') {
+            match (a.strip_prefix('~'), b.strip_prefix('~')) {
+                (Some(a_rest), Some(b_rest)) => {
+                    a = a_rest;
+                    b = b_rest;
+                    continue;
+                }
+                (Some(_), None) => return Ordering::Less,
+                _ => return Ordering::Greater,
+            }
+        }
+
+        if a.starts_with('^') || b.starts_with('^') {
+            if a.is_empty() {
+                return Ordering::Less;
+            }
+            if b.is_empty() {
+                return Ordering::Greater;
+            }
+            match (a.strip_prefix('^'), b.strip_prefix('^')) {
+                (Some(a_rest), Some(b_rest)) => {
+                    a = a_rest;
+                    b = b_rest;
+                    continue;
+                }
+                (Some(_), None) => return Ordering::Less,
+                _ => return Ordering::Greater,
+            }
+        }
+
+        if a.is_empty() || b.is_empty() {
+            break;
+        }
+
+        let numeric = a.starts_with(|c: char| c.is_ascii_digit());
+        let in_segment = |c: char| {
+            if numeric {
+                c.is_ascii_digit()
+            } else {
+                c.is_ascii_alphabetic()
+            }
+        };
+        let a_len = a.find(|c: char| !in_segment(c)).unwrap_or(a.len());
+        let b_len = b.find(|c: char| !in_segment(c)).unwrap_or(b.len());
+        let (a_segment, a_rest) = a.split_at(a_len);
+        let (b_segment, b_rest) = b.split_at(b_len);
+
+        // segments of different types: numeric ones are newer
+        if b_segment.is_empty() {
+            return if numeric {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+
+        let order = if numeric {
+            let a_segment = a_segment.trim_start_matches('0');
+            let b_segment = b_segment.trim_start_matches('0');
+            a_segment
+                .len()
+                .cmp(&b_segment.len())
+                .then_with(|| a_segment.cmp(b_segment))
+        } else {
+            a_segment.cmp(b_segment)
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+        a = a_rest;
+        b = b_rest;
+    }
+
+    // the string with remaining characters is newer
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => Ordering::Equal,
+        (false, _) => Ordering::Greater,
+        _ => Ordering::Less,
+    }
+}
+
+/// systemd target to boot the installed system into.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub enum SystemdTarget {
+    /// Graphical session (display manager).
+    #[serde(rename = "graphical.target")]
+    Graphical,
+    /// Multi-user text mode.
+    #[serde(rename = "multi-user.target")]
+    MultiUser,
+}
+
+impl SystemdTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Graphical => "graphical.target",
+            Self::MultiUser => "multi-user.target",
+        }
+    }
+}
+
+impl fmt::Display for SystemdTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown systemd target: '{0}'")]
+pub struct UnknownSystemdTarget(String);
+
+impl FromStr for SystemdTarget {
+    type Err = UnknownSystemdTarget;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "graphical.target" => Ok(Self::Graphical),
+            "multi-user.target" => Ok(Self::MultiUser),
+            _ => Err(UnknownSystemdTarget(s.to_string())),
+        }
+    }
+}
+
+/// What to do with a systemd unit in the installed system.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ServiceAction {
+    /// Start the unit on boot.
+    Enable,
+    /// Do not start the unit on boot, although it can be started manually or as a dependency.
+    Disable,
+    /// Do not start the unit at all.
+    Mask,
+}
+
+impl ServiceAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Enable => "enable",
+            Self::Disable => "disable",
+            Self::Mask => "mask",
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown service action: '{0}'")]
+pub struct UnknownServiceAction(String);
+
+impl FromStr for ServiceAction {
+    type Err = UnknownServiceAction;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "enable" => Ok(Self::Enable),
+            "disable" => Ok(Self::Disable),
+            "mask" => Ok(Self::Mask),
+            _ => Err(UnknownServiceAction(s.to_string())),
+        }
+    }
+}
+
+/// Suffixes of the systemd units which can be enabled, disabled or masked.
+const UNIT_SUFFIXES: [&str; 7] = [
+    ".service",
+    ".socket",
+    ".timer",
+    ".target",
+    ".path",
+    ".mount",
+    ".automount",
+];
+
+/// Packages providing some well-known units: (unit, package).
+const UNIT_PACKAGES: [(&str, &str); 10] = [
+    ("sshd.service", "openssh-server"),
+    ("cockpit.socket", "cockpit"),
+    ("cockpit.service", "cockpit"),
+    ("firewalld.service", "firewalld"),
+    ("chronyd.service", "chrony"),
+    ("cups.service", "cups"),
+    ("libvirtd.service", "libvirt-daemon"),
+    ("podman.socket", "podman"),
+    ("docker.service", "docker"),
+    ("nfs-server.service", "nfs-kernel-server"),
+];
+
+/// systemd unit to enable, disable or mask in the installed system.
+///
+/// The actions are applied during the finish phase of the installation, after setting the
+/// default target.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceConfig {
+    /// Unit name (e.g., "sshd.service"). The ".service" suffix can be omitted.
+    pub name: String,
+    /// Action to apply.
+    pub action: ServiceAction,
+}
+
+impl ServiceConfig {
+    /// Returns the full unit name, adding the ".service" suffix if there is no suffix.
+    pub fn unit(&self) -> String {
+        if UNIT_SUFFIXES.iter().any(|s| self.name.ends_with(s)) {
+            self.name.clone()
+        } else {
+            format!("{}.service", self.name)
+        }
+    }
+
+    /// Checks whether the unit name is valid.
+    ///
+    /// It returns the list of problems, if any.
+    pub fn validate(&self) -> Vec<String> {
+        let unit = self.unit();
+        let stem = UNIT_SUFFIXES
+            .iter()
+            .find_map(|s| unit.strip_suffix(s))
+            .unwrap_or_default();
+        let valid_chars = stem
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ":-_.\\@".contains(c));
+        if stem.is_empty() || !valid_chars || unit.len() > 255 {
+            return vec![format!("Invalid systemd unit name: '{}'", self.name)];
+        }
+        if stem.ends_with('@') && self.action == ServiceAction::Enable {
+            return vec![format!(
+                "The template unit '{}' cannot be enabled without an instance",
+                self.name
+            )];
+        }
+        vec![]
+    }
+
+    /// Returns the package known to provide the unit, if any.
+    pub fn package(&self) -> Option<&'static str> {
+        let unit = self.unit();
+        UNIT_PACKAGES
+            .iter()
+            .find(|(u, _)| *u == unit)
+            .map(|(_, p)| *p)
+    }
+}
+
+/// Checks whether the list of units is valid, including repeated units.
+///
+/// It returns the list of problems, if any.
+pub fn validate_services(services: &[ServiceConfig]) -> Vec<String> {
+    let mut issues: Vec<String> = services.iter().flat_map(|s| s.validate()).collect();
+    for (index, service) in services.iter().enumerate() {
+        let unit = service.unit();
+        if services[..index].iter().any(|s| s.unit() == unit) {
+            issues.push(format!("Duplicated systemd unit: '{}'", unit));
+        }
+    }
+    issues
 }
 
 /// Software service configuration (product, patterns, etc.).
@@ -52,9 +705,10 @@ pub struct RegistrationInfo {
     pub requirement: RegistrationRequirement,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, utoipa::ToSchema)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, utoipa::ToSchema)]
 pub enum RegistrationRequirement {
     /// Product does not require registration
+    #[default]
     NotRequired = 0,
     /// Product has optional registration
     Optional = 1,
@@ -80,3 +734,311 @@ impl TryFrom<u32> for RegistrationRequirement {
         }
     }
 }
+
+/// Subscription status of the registered product, as reported by the registration server.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionStatus {
+    /// Whether the product is registered. The rest of the fields are empty if it is not.
+    pub registered: bool,
+    /// Registered product (e.g., "SLES/16.0/x86_64").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product: Option<String>,
+    /// Expiration date of the subscription (e.g., "2027-01-31T00:00:00Z"), if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    /// Whether the subscription already expired.
+    pub expired: bool,
+    /// Number of remaining entitlements, if the registration server reports it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining_entitlements: Option<u32>,
+    /// Activated extensions and modules.
+    pub extensions: Vec<ActivatedExtension>,
+}
+
+/// Extension (or module) activated for the registered product.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivatedExtension {
+    /// Extension ID (e.g., "sle-module-basesystem").
+    pub id: String,
+    /// Extension version (e.g., "16.0").
+    pub version: String,
+    /// Expiration date of the extension subscription, if it differs from the product one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+}
+
+impl SubscriptionStatus {
+    /// Builds the status from the `Subscription` and `Extensions` properties of the registration
+    /// D-Bus interface.
+    ///
+    /// * `subscription`: subscription data. It is empty if the product is not registered.
+    /// * `extensions`: data of each activated extension.
+    /// * `today`: date to decide whether the subscription expired.
+    pub fn from_dbus(
+        subscription: &HashMap<String, zvariant::OwnedValue>,
+        extensions: &[HashMap<String, zvariant::OwnedValue>],
+        today: NaiveDate,
+    ) -> Result<Self, zvariant::Error> {
+        let product: Option<String> = get_optional_property(subscription, "Product")?;
+        let Some(product) = product.filter(|p| !p.is_empty()) else {
+            return Ok(Self::default());
+        };
+
+        let expires_at: Option<String> = get_optional_property(subscription, "ExpiresAt")?;
+        let expired = expires_at
+            .as_deref()
+            .and_then(parse_expiration_date)
+            .is_some_and(|date| date < today);
+        let extensions = extensions
+            .iter()
+            .map(|extension| {
+                Ok(ActivatedExtension {
+                    id: get_property(extension, "Id")?,
+                    version: get_optional_property(extension, "Version")?.unwrap_or_default(),
+                    expires_at: get_optional_property(extension, "ExpiresAt")?,
+                })
+            })
+            .collect::<Result<_, zvariant::Error>>()?;
+
+        Ok(Self {
+            registered: true,
+            product: Some(product),
+            expires_at,
+            expired,
+            remaining_entitlements: get_optional_property(subscription, "RemainingEntitlements")?,
+            extensions,
+        })
+    }
+}
+
+/// Parses an expiration date, either as a RFC 3339 timestamp or as a plain date (YYYY-MM-DD).
+fn parse_expiration_date(date: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(date)
+        .map(|d| d.date_naive())
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repository(alias: &str, url: &str) -> RepositoryConfig {
+        RepositoryConfig {
+            alias: alias.to_string(),
+            url: url.to_string(),
+            name: None,
+            persistent: true,
+            repo_type: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_repository() {
+        assert!(repository("tools", "https://example.net/repo")
+            .validate()
+            .is_empty());
+        assert!(repository("local", "dir:///srv/repo").validate().is_empty());
+
+        assert_eq!(repository("", "https://example.net").validate().len(), 1);
+        assert_eq!(
+            repository("my tools", "https://example.net")
+                .validate()
+                .len(),
+            1
+        );
+        assert_eq!(
+            repository(".hidden", "https://example.net")
+                .validate()
+                .len(),
+            1
+        );
+        assert_eq!(
+            repository("tools", "gopher://example.net").validate().len(),
+            1
+        );
+        assert_eq!(repository("tools", "example.net/repo").validate().len(), 1);
+
+        assert_eq!(
+            repository("tools", "https://example.net/repo\n[evil]")
+                .validate()
+                .len(),
+            1
+        );
+        let mut named = repository("tools", "https://example.net/repo");
+        named.name = Some("Tools\nbaseurl=https://evil.example.net".to_string());
+        assert_eq!(
+            named.validate(),
+            vec!["Invalid characters in the name of repository 'tools'".to_string()]
+        );
+
+        let mut media = repository("media", "iso:///srv/SLES.iso");
+        assert_eq!(media.validate().len(), 1);
+        media.persistent = false;
+        assert!(media.validate().is_empty());
+    }
+
+    #[test]
+    fn test_validate_duplicated_aliases() {
+        let repos = vec![
+            repository("tools", "https://example.net/a"),
+            repository("tools", "https://example.net/b"),
+        ];
+        assert_eq!(
+            validate_repositories(&repos),
+            vec!["Duplicated repository alias: 'tools'".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_repository_source() {
+        let repo = repository("tools", "https://example.net/repo").with_source();
+        assert_eq!(repo.repo_type, Some(RepositoryType::Online));
+        assert_eq!(repo.source, None);
+
+        let repo = repository("local", "file:///srv/repo").with_source();
+        assert_eq!(repo.repo_type, Some(RepositoryType::Directory));
+        assert_eq!(repo.source.as_deref(), Some("/srv/repo"));
+
+        let repo = repository("media", "iso:///srv/SLES.iso").with_source();
+        assert_eq!(repo.repo_type, Some(RepositoryType::Iso));
+        assert_eq!(repo.source.as_deref(), Some("/srv/SLES.iso"));
+
+        let repo = repository("media", "iso:/?iso=/srv/SLES.iso");
+        assert_eq!(repo.local_path(), Some(PathBuf::from("/srv/SLES.iso")));
+    }
+
+    #[test]
+    fn test_validate_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("dir://{}", dir.path().display());
+        assert!(repository("local", &url).validate_source().is_some());
+
+        std::fs::create_dir(dir.path().join("repodata")).unwrap();
+        std::fs::write(dir.path().join("repodata/repomd.xml"), "<repomd/>").unwrap();
+        assert!(repository("local", &url).validate_source().is_none());
+
+        let iso = dir.path().join("media.iso");
+        std::fs::write(&iso, "not an ISO").unwrap();
+        let url = format!("iso://{}", iso.display());
+        assert!(repository("media", &url).validate_source().is_some());
+
+        let mut content = vec![0u8; 32769];
+        content.extend_from_slice(b"CD001");
+        std::fs::write(&iso, content).unwrap();
+        assert!(repository("media", &url).validate_source().is_none());
+
+        assert!(repository("media", "iso:///does/not/exist.iso")
+            .validate_source()
+            .is_some());
+        assert!(repository("tools", "https://example.net/repo")
+            .validate_source()
+            .is_none());
+    }
+
+    #[test]
+    fn test_repo_file() {
+        let mut repo = repository("tools", "https://example.net/repo");
+        assert_eq!(repo.repo_file_path(), "etc/zypp/repos.d/tools.repo");
+        repo.name = Some("My tools".to_string());
+        assert_eq!(
+            repo.to_repo_file(),
+            "[tools]\nname=My tools\nenabled=1\nautorefresh=1\nbaseurl=https://example.net/repo\n"
+        );
+    }
+
+    #[test]
+    fn test_subscription_status() {
+        let today = NaiveDate::from_ymd_opt(2026, 6, 1).unwrap();
+        let status = SubscriptionStatus::from_dbus(&HashMap::new(), &[], today).unwrap();
+        assert!(!status.registered);
+
+        let value =
+            |v: &'static str| -> zvariant::OwnedValue { zvariant::Str::from_static(v).into() };
+        let subscription = HashMap::from([
+            ("Product".to_string(), value("SLES/16.0/x86_64")),
+            ("ExpiresAt".to_string(), value("2026-05-31T00:00:00Z")),
+            ("RemainingEntitlements".to_string(), 3_u32.into()),
+        ]);
+        let extensions = vec![HashMap::from([
+            ("Id".to_string(), value("sle-module-basesystem")),
+            ("Version".to_string(), value("16.0")),
+        ])];
+        let status = SubscriptionStatus::from_dbus(&subscription, &extensions, today).unwrap();
+        assert!(status.registered);
+        assert!(status.expired);
+        assert_eq!(status.remaining_entitlements, Some(3));
+        assert_eq!(status.extensions[0].id, "sle-module-basesystem");
+        assert_eq!(status.extensions[0].expires_at, None);
+
+        assert_eq!(
+            parse_expiration_date("2027-01-31"),
+            NaiveDate::from_ymd_opt(2027, 1, 31)
+        );
+        assert_eq!(parse_expiration_date("never"), None);
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.0-1", "1.0-1"), Ordering::Equal);
+        assert_eq!(compare_versions("1.10-1", "1.9-1"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0-1", "1.0-2"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.1", "1.0"), Ordering::Greater);
+        assert_eq!(compare_versions("1:1.0-1", "2.0-1"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0~rc1", "1.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0^git1", "1.0"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0^git1", "1.0.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.0a", "1.0.1"), Ordering::Less);
+        assert_eq!(compare_versions("2.01", "2.1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_package_change() {
+        let change = PackageChange::new("vim", "9.0-1.1", "9.1-2.1").unwrap();
+        assert_eq!(change.kind, PackageChangeKind::Upgraded);
+        assert_eq!(change.current_version, Some("9.0-1.1".to_string()));
+
+        let change = PackageChange::new("vim", "9.1-2.1", "9.0-1.1").unwrap();
+        assert_eq!(change.kind, PackageChangeKind::Downgraded);
+
+        let change = PackageChange::new("vim", "", "9.1-2.1").unwrap();
+        assert_eq!(change.kind, PackageChangeKind::Added);
+        assert_eq!(change.current_version, None);
+
+        let change = PackageChange::new("vim", "9.1-2.1", "").unwrap();
+        assert_eq!(change.kind, PackageChangeKind::Removed);
+
+        assert!(PackageChange::new("vim", "9.1-2.1", "9.1-2.1").is_none());
+    }
+
+    #[test]
+    fn test_validate_services() {
+        let service = |name: &str, action: ServiceAction| ServiceConfig {
+            name: name.to_string(),
+            action,
+        };
+        let services = vec![
+            service("sshd", ServiceAction::Enable),
+            service("cockpit.socket", ServiceAction::Enable),
+            service("sshd.service", ServiceAction::Mask),
+            service("bad unit", ServiceAction::Disable),
+            service("getty@.service", ServiceAction::Enable),
+            service("getty@tty2", ServiceAction::Enable),
+        ];
+        assert_eq!(services[0].unit(), "sshd.service");
+        assert_eq!(services[0].package(), Some("openssh-server"));
+        assert_eq!(services[5].package(), None);
+        assert_eq!(
+            validate_services(&services),
+            vec![
+                "Invalid systemd unit name: 'bad unit'".to_string(),
+                "The template unit 'getty@.service' cannot be enabled without an instance"
+                    .to_string(),
+                "Duplicated systemd unit: 'sshd.service'".to_string(),
+            ]
+        );
+    }
+}
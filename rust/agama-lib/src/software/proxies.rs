@@ -55,6 +55,12 @@ trait Software1 {
     /// ListPatterns method
     fn list_patterns(&self, filtered: bool) -> zbus::Result<PatternsMap>;
 
+    /// PackageChanges method
+    ///
+    /// It returns the name, the installed version and the selected version of each package. The
+    /// installed version is empty for new packages and the selected one for removed packages.
+    fn package_changes(&self) -> zbus::Result<Vec<(String, String, String)>>;
+
     /// Probe method
     fn probe(&self) -> zbus::Result<()>;
 
@@ -70,12 +76,66 @@ trait Software1 {
     /// SetUserPatterns method
     fn set_user_patterns(&self, add: &[&str], remove: &[&str]) -> zbus::Result<Vec<String>>;
 
+    /// SetExtraRepositories method
+    fn set_extra_repositories(&self, repositories: &[(&str, &str, &str, bool)])
+        -> zbus::Result<()>;
+
     /// UsedDiskSpace method
     fn used_disk_space(&self) -> zbus::Result<String>;
 
     /// SelectedPatterns property
     #[dbus_proxy(property)]
     fn selected_patterns(&self) -> zbus::Result<std::collections::HashMap<String, u8>>;
+
+    /// DefaultTarget property
+    #[dbus_proxy(property)]
+    fn default_target(&self) -> zbus::Result<String>;
+    #[dbus_proxy(property)]
+    fn set_default_target(&self, value: &str) -> zbus::Result<()>;
+
+    /// Architecture property
+    #[dbus_proxy(property)]
+    fn architecture(&self) -> zbus::Result<String>;
+
+    /// Multilib property
+    #[dbus_proxy(property)]
+    fn multilib(&self) -> zbus::Result<bool>;
+    #[dbus_proxy(property)]
+    fn set_multilib(&self, value: bool) -> zbus::Result<()>;
+
+    /// OnlyRequired property
+    ///
+    /// Whether the solver only selects the hard dependencies, skipping the recommended packages.
+    #[dbus_proxy(property)]
+    fn only_required(&self) -> zbus::Result<bool>;
+    #[dbus_proxy(property)]
+    fn set_only_required(&self, value: bool) -> zbus::Result<()>;
+
+    /// ExtraRepositories property
+    ///
+    /// Each repository is represented by its alias, URL, name and whether it is persistent.
+    #[dbus_proxy(property)]
+    fn extra_repositories(&self) -> zbus::Result<Vec<(String, String, String, bool)>>;
+
+    /// Services property
+    ///
+    /// Each systemd unit is represented by its name and the action to apply ("enable", "disable"
+    /// or "mask").
+    #[dbus_proxy(property)]
+    fn services(&self) -> zbus::Result<Vec<(String, String)>>;
+    #[dbus_proxy(property)]
+    fn set_services(&self, value: &[(&str, &str)]) -> zbus::Result<()>;
+
+    /// PackageProgress signal
+    #[dbus_proxy(signal)]
+    fn package_progress(
+        &self,
+        current_package: &str,
+        installed: u32,
+        total: u32,
+        bytes_done: u64,
+        bytes_total: u64,
+    ) -> zbus::Result<()>;
 }
 
 /// Product definition.
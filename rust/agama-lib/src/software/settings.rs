@@ -20,6 +20,7 @@
 
 //! Representation of the software settings
 
+use super::model::{RepositoryConfig, ServiceConfig, SystemdTarget};
 use serde::{Deserialize, Serialize};
 
 /// Software settings for installation
@@ -28,4 +29,16 @@ use serde::{Deserialize, Serialize};
 pub struct SoftwareSettings {
     /// List of patterns to install. If empty use default.
     pub patterns: Vec<String>,
+    /// systemd default target of the installed system. If empty use the product default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_target: Option<SystemdTarget>,
+    /// Additional repositories to use during the installation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_repositories: Vec<RepositoryConfig>,
+    /// Whether to install only the hard dependencies, skipping the recommended packages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub only_required: Option<bool>,
+    /// systemd units to enable, disable or mask in the installed system.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub services: Vec<ServiceConfig>,
 }
@@ -22,7 +22,7 @@
 
 use std::collections::HashMap;
 
-use super::{SoftwareHTTPClient, SoftwareSettings};
+use super::{model::SoftwareConfig, SoftwareHTTPClient, SoftwareSettings};
 use crate::base_http_client::BaseHTTPClient;
 use crate::error::ServiceError;
 
@@ -39,8 +39,14 @@ impl SoftwareStore {
     }
 
     pub async fn load(&self) -> Result<SoftwareSettings, ServiceError> {
-        let patterns = self.software_client.user_selected_patterns().await?;
-        Ok(SoftwareSettings { patterns })
+        let config = self.software_client.get_config().await?;
+        Ok(SoftwareSettings {
+            patterns: config.selected_patterns(),
+            default_target: config.default_target,
+            extra_repositories: config.extra_repositories.unwrap_or_default(),
+            only_required: config.only_required,
+            services: config.services.unwrap_or_default(),
+        })
     }
 
     pub async fn store(&self, settings: &SoftwareSettings) -> Result<(), ServiceError> {
@@ -49,7 +55,23 @@ impl SoftwareStore {
             .iter()
             .map(|name| (name.to_owned(), true))
             .collect();
-        self.software_client.select_patterns(patterns).await?;
+        let config = SoftwareConfig {
+            product: None,
+            patterns: Some(patterns),
+            default_target: settings.default_target,
+            extra_repositories: if settings.extra_repositories.is_empty() {
+                None
+            } else {
+                Some(settings.extra_repositories.clone())
+            },
+            only_required: settings.only_required,
+            services: if settings.services.is_empty() {
+                None
+            } else {
+                Some(settings.services.clone())
+            },
+        };
+        self.software_client.set_config(&config).await?;
 
         Ok(())
     }
@@ -93,6 +115,8 @@ mod test {
 
         let expected = SoftwareSettings {
             patterns: vec!["xfce".to_owned()],
+            default_target: None,
+            ..Default::default()
         };
         // main assertion
         assert_eq!(settings, expected);
@@ -117,6 +141,8 @@ mod test {
         let store = software_store(url);
         let settings = SoftwareSettings {
             patterns: vec!["xfce".to_owned()],
+            default_target: None,
+            ..Default::default()
         };
 
         let result = store.store(&settings).await;
@@ -145,6 +171,8 @@ mod test {
         let store = software_store(url);
         let settings = SoftwareSettings {
             patterns: vec!["no_such_pattern".to_owned()],
+            default_target: None,
+            ..Default::default()
         };
 
         let result = store.store(&settings).await;
@@ -28,7 +28,8 @@ mod settings;
 mod store;
 
 pub use client::{
-    iscsi::{ISCSIAuth, ISCSIClient, ISCSIInitiator, ISCSINode},
+    iscsi::{ISCSIAuth, ISCSIAuthMode, ISCSIClient, ISCSIInitiator, ISCSINode},
+    nvmeof::NVMeoFClient,
     zfcp::ZFCPClient,
     StorageClient,
 };
@@ -21,14 +21,16 @@
 //! Implements a client to access Agama's storage service.
 
 use super::model::{
-    Action, BlockDevice, Component, Device, DeviceInfo, DeviceSid, Drive, Filesystem, LvmLv, LvmVg,
-    Md, Multipath, Partition, PartitionTable, ProposalSettings, ProposalSettingsPatch, Raid,
-    Volume,
+    Action, BlockDevice, Cache, Component, Device, DeviceInfo, DeviceSid, Drive, Filesystem, LvmLv,
+    LvmVg, Md, Multipath, Partition, PartitionTable, ProposalSettings, ProposalSettingsPatch, Raid,
+    SupportedFilesystem, Volume,
 };
 use super::proxies::{ProposalCalculatorProxy, ProposalProxy, Storage1Proxy};
 use super::StorageSettings;
-use crate::dbus::get_property;
+use crate::dbus::{get_optional_property, get_property};
 use crate::error::ServiceError;
+use crate::retry::{retry, RetryPolicy};
+use serde::Serialize;
 use std::collections::HashMap;
 use zbus::fdo::ObjectManagerProxy;
 use zbus::names::{InterfaceName, OwnedInterfaceName};
@@ -36,6 +38,7 @@ use zbus::zvariant::{OwnedObjectPath, OwnedValue};
 use zbus::Connection;
 pub mod dasd;
 pub mod iscsi;
+pub mod nvmeof;
 pub mod zfcp;
 
 type DBusObject = (
@@ -43,6 +46,37 @@ type DBusObject = (
     HashMap<OwnedInterfaceName, HashMap<std::string::String, OwnedValue>>,
 );
 
+/// Result of unlocking an encrypted device.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+pub enum UnlockResult {
+    /// The device was unlocked.
+    Success = 0,
+    /// The device does not exist or it is not encrypted.
+    DeviceNotFound = 1,
+    /// Wrong passphrase.
+    WrongPassphrase = 2,
+    /// The device could not be unlocked for another reason.
+    Failed = 3,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+#[error("Invalid unlock result: {0}")]
+pub struct InvalidUnlockResult(u32);
+
+impl TryFrom<u32> for UnlockResult {
+    type Error = InvalidUnlockResult;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            v if v == Self::Success as u32 => Ok(Self::Success),
+            v if v == Self::DeviceNotFound as u32 => Ok(Self::DeviceNotFound),
+            v if v == Self::WrongPassphrase as u32 => Ok(Self::WrongPassphrase),
+            v if v == Self::Failed as u32 => Ok(Self::Failed),
+            _ => Err(InvalidUnlockResult(value)),
+        }
+    }
+}
+
 /// D-Bus client for the storage service
 #[derive(Clone)]
 pub struct StorageClient<'a> {
@@ -51,6 +85,7 @@ pub struct StorageClient<'a> {
     storage_proxy: Storage1Proxy<'a>,
     object_manager_proxy: ObjectManagerProxy<'a>,
     proposal_proxy: ProposalProxy<'a>,
+    retry_policy: RetryPolicy,
 }
 
 impl<'a> StorageClient<'a> {
@@ -69,10 +104,19 @@ impl<'a> StorageClient<'a> {
                 .cache_properties(zbus::CacheProperties::No)
                 .build()
                 .await?,
+            retry_policy: RetryPolicy::from_env(),
             connection,
         })
     }
 
+    /// Sets the policy to retry the read calls on transient errors.
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            retry_policy,
+            ..self
+        }
+    }
+
     /// Whether the devices have changed.
     pub async fn devices_dirty_bit(&self) -> Result<bool, ServiceError> {
         Ok(self.storage_proxy.deprecated_system().await?)
@@ -117,6 +161,18 @@ impl<'a> StorageClient<'a> {
         Ok(self.calculator_proxy.product_mount_points().await?)
     }
 
+    /// File systems which can be created, according to the volume templates of the product.
+    pub async fn supported_filesystems(&self) -> Result<Vec<SupportedFilesystem>, ServiceError> {
+        let mut mount_points = self.product_mount_points().await?;
+        // the generic template, used for any other mount point
+        mount_points.push(String::new());
+        let mut volumes = Vec::with_capacity(mount_points.len());
+        for mount_point in mount_points {
+            volumes.push(self.volume_for(&mount_point).await?);
+        }
+        Ok(SupportedFilesystem::from_volumes(&volumes))
+    }
+
     /// Encryption methods allowed by the product.
     pub async fn encryption_methods(&self) -> Result<Vec<String>, ServiceError> {
         Ok(self.calculator_proxy.encryption_methods().await?)
@@ -132,6 +188,26 @@ impl<'a> StorageClient<'a> {
         Ok(self.storage_proxy.probe().await?)
     }
 
+    /// Commits the storage changes to the system
+    pub async fn install(&self) -> Result<(), ServiceError> {
+        Ok(self.storage_proxy.install().await?)
+    }
+
+    /// Unlocks an existing encrypted (LUKS) device
+    ///
+    /// Once unlocked, the device and its content are included in the probed devices.
+    ///
+    /// * `device`: name of the encrypted device (e.g., "/dev/sda2").
+    /// * `passphrase`: passphrase to open the device.
+    pub async fn unlock_device(
+        &self,
+        device: &str,
+        passphrase: &str,
+    ) -> Result<UnlockResult, ServiceError> {
+        let result = self.storage_proxy.unlock_device(device, passphrase).await?;
+        UnlockResult::try_from(result).map_err(|e| ServiceError::InternalError(e.to_string()))
+    }
+
     /// Set the storage config according to the JSON schema
     pub async fn set_config(&self, settings: StorageSettings) -> Result<u32, ServiceError> {
         Ok(self
@@ -142,7 +218,10 @@ impl<'a> StorageClient<'a> {
 
     /// Get the storage config according to the JSON schema
     pub async fn get_config(&self) -> Result<StorageSettings, ServiceError> {
-        let serialized_settings = self.storage_proxy.get_config().await?;
+        let serialized_settings = retry(&self.retry_policy, "GetConfig", || {
+            self.storage_proxy.get_config()
+        })
+        .await?;
         let settings = serde_json::from_str(serialized_settings.as_str()).unwrap();
         Ok(settings)
     }
@@ -151,9 +230,23 @@ impl<'a> StorageClient<'a> {
         Ok(self.calculator_proxy.calculate(settings.into()).await?)
     }
 
+    /// Returns the objects exported by the storage service.
+    async fn managed_objects(
+        &self,
+    ) -> Result<
+        HashMap<OwnedObjectPath, HashMap<OwnedInterfaceName, HashMap<String, OwnedValue>>>,
+        ServiceError,
+    > {
+        let objects = retry(&self.retry_policy, "GetManagedObjects", || async {
+            Ok(self.object_manager_proxy.get_managed_objects().await?)
+        })
+        .await?;
+        Ok(objects)
+    }
+
     /// Probed devices.
     pub async fn system_devices(&self) -> Result<Vec<Device>, ServiceError> {
-        let objects = self.object_manager_proxy.get_managed_objects().await?;
+        let objects = self.managed_objects().await?;
         let mut result = vec![];
         for object in objects {
             let path = &object.0;
@@ -164,12 +257,20 @@ impl<'a> StorageClient<'a> {
             result.push(self.build_device(&object).await?)
         }
 
+        let contents: Vec<_> = result
+            .iter()
+            .map(|device| device.detect_contents(&result))
+            .collect();
+        for (device, contents) in result.iter_mut().zip(contents) {
+            device.contents = contents;
+        }
+
         Ok(result)
     }
 
     /// Resulting devices after calculating a proposal.
     pub async fn staging_devices(&self) -> Result<Vec<Device>, ServiceError> {
-        let objects = self.object_manager_proxy.get_managed_objects().await?;
+        let objects = self.managed_objects().await?;
         let mut result = vec![];
         for object in objects {
             let path = &object.0;
@@ -207,6 +308,8 @@ impl<'a> StorageClient<'a> {
             partition: self.build_partition(object).await?,
             partition_table: self.build_partition_table(object).await?,
             raid: self.build_raid(object).await?,
+            cache: self.build_cache(object).await?,
+            contents: None,
         })
     }
 
@@ -237,15 +340,21 @@ impl<'a> StorageClient<'a> {
             return Ok(None);
         };
 
+        let start: u64 = get_property(properties, "Start")?;
+        let logical_sector_size: Option<u64> =
+            get_optional_property(properties, "LogicalSectorSize")?;
         Ok(Some(BlockDevice {
             active: get_property(properties, "Active")?,
             encrypted: get_property(properties, "Encrypted")?,
             size: get_property(properties, "Size")?,
             shrinking: get_property(properties, "Shrinking")?,
-            start: get_property(properties, "Start")?,
+            start,
             systems: get_property(properties, "Systems")?,
             udev_ids: get_property(properties, "UdevIds")?,
             udev_paths: get_property(properties, "UdevPaths")?,
+            logical_sector_size,
+            physical_sector_size: get_optional_property(properties, "PhysicalSectorSize")?,
+            offset: logical_sector_size.map(|size| start * size),
         }))
     }
 
@@ -280,6 +389,7 @@ impl<'a> StorageClient<'a> {
             driver: get_property(properties, "Driver")?,
             transport: get_property(properties, "Transport")?,
             info: get_property(properties, "Info")?,
+            smart: Default::default(),
         }))
     }
 
@@ -297,6 +407,8 @@ impl<'a> StorageClient<'a> {
             fs_type: get_property(properties, "Type")?,
             mount_path: get_property(properties, "MountPath")?,
             label: get_property(properties, "Label")?,
+            free_space: get_optional_property(properties, "FreeSpace")?,
+            uuid: get_optional_property::<String>(properties, "UUID")?.filter(|u| !u.is_empty()),
         }))
     }
 
@@ -392,4 +504,18 @@ impl<'a> StorageClient<'a> {
             devices: get_property(properties, "Devices")?,
         }))
     }
+
+    async fn build_cache(&self, object: &DBusObject) -> Result<Option<Cache>, ServiceError> {
+        let iface = self.get_interface(object, "org.opensuse.Agama.Storage1.Cache");
+        let Some(properties) = iface else {
+            return Ok(None);
+        };
+
+        Ok(Some(Cache {
+            method: get_property(properties, "Method")?,
+            mode: get_property(properties, "Mode")?,
+            cache_device: get_property(properties, "CacheDevice")?,
+            backing_device: get_property(properties, "BackingDevice")?,
+        }))
+    }
 }
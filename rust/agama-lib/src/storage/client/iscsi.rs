@@ -22,7 +22,7 @@ use core::fmt;
 use std::collections::HashMap;
 
 use crate::{
-    dbus::{extract_id_from_path, get_property},
+    dbus::{extract_id_from_path, get_optional_property, get_property, optional_property},
     error::ServiceError,
     storage::proxies::{InitiatorProxy, NodeProxy},
 };
@@ -61,6 +61,221 @@ pub struct ISCSINode {
     pub startup: String,
 }
 
+impl ISCSINode {
+    /// Whether both nodes refer to the same target through the same portal and interface.
+    pub fn same_target(&self, other: &ISCSINode) -> bool {
+        self.target == other.target
+            && self.address == other.address
+            && self.port == other.port
+            && self.interface == other.interface
+    }
+}
+
+/// Path of the initiator name provided by the iBFT (iSCSI Boot Firmware Table).
+const IBFT_INITIATOR_NAME_PATH: &str = "/sys/firmware/ibft/initiator/initiator-name";
+
+/// Path of the initiator name configured in the system.
+const INITIATOR_NAME_PATH: &str = "/etc/iscsi/initiatorname.iscsi";
+
+/// Where the initiator name restored by [ISCSIClient::reset_initiator_name] comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum InitiatorNameSource {
+    /// iSCSI Boot Firmware Table.
+    Ibft,
+    /// Name configured in the system (`/etc/iscsi/initiatorname.iscsi`).
+    Default,
+}
+
+/// Result of restoring the initiator name.
+#[derive(Clone, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InitiatorNameReset {
+    /// Resulting initiator name.
+    pub name: String,
+    pub source: InitiatorNameSource,
+    /// Whether the name was changed. It is false if it already had the restored value.
+    pub changed: bool,
+}
+
+/// Name of the interface of the default software initiator (iSCSI over TCP).
+pub const DEFAULT_ISCSI_INTERFACE: &str = "default";
+
+/// iSCSI interface (iscsiadm iface), which can be bound to a software transport or to an
+/// offload engine.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ISCSIInterface {
+    /// Interface name (e.g., "bnx2i.00:10:18:aa:bb:cc").
+    pub name: String,
+    /// Transport (e.g., "tcp", "iser", "bnx2i").
+    pub transport: String,
+    /// Hardware address of the network interface, if it is bound to one.
+    pub mac_address: Option<String>,
+    /// IP address of the interface, if it is set.
+    pub ip_address: Option<String>,
+}
+
+impl TryFrom<&HashMap<String, OwnedValue>> for ISCSIInterface {
+    type Error = ServiceError;
+
+    fn try_from(value: &HashMap<String, OwnedValue>) -> Result<Self, Self::Error> {
+        let optional = |name| -> Result<Option<String>, zvariant::Error> {
+            Ok(get_optional_property::<String>(value, name)?.filter(|v| !v.is_empty()))
+        };
+        Ok(ISCSIInterface {
+            name: get_property(value, "Name")?,
+            transport: get_property(value, "Transport")?,
+            mac_address: optional("HWAddress")?,
+            ip_address: optional("IPAddress")?,
+        })
+    }
+}
+
+/// Live session of a logged-in iSCSI node.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct ISCSISession {
+    /// Session state (e.g., "LOGGED_IN" or "FAILED").
+    pub state: String,
+    /// Number of connections of the session.
+    pub connections: u32,
+    /// Negotiated header digest (e.g., "None" or "CRC32C").
+    pub header_digest: String,
+    /// Negotiated data digest (e.g., "None" or "CRC32C").
+    pub data_digest: String,
+    /// Other negotiated parameters (e.g., "MaxRecvDataSegmentLength").
+    pub parameters: HashMap<String, String>,
+}
+
+impl TryFrom<&HashMap<String, OwnedValue>> for ISCSISession {
+    type Error = ServiceError;
+
+    fn try_from(value: &HashMap<String, OwnedValue>) -> Result<Self, Self::Error> {
+        Ok(ISCSISession {
+            state: get_property(value, "State")?,
+            connections: get_property(value, "Connections")?,
+            header_digest: get_property(value, "HeaderDigest")?,
+            data_digest: get_property(value, "DataDigest")?,
+            parameters: get_optional_property(value, "Parameters")?.unwrap_or_default(),
+        })
+    }
+}
+
+/// Session currently held by the system, no matter whether it belongs to a configured node.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ISCSIActiveSession {
+    /// Target name.
+    pub target: String,
+    /// Portal address.
+    pub address: String,
+    /// Portal port.
+    pub port: u32,
+    /// iSCSI interface used by the session.
+    pub interface: String,
+    /// Whether the session was established by the firmware (iBFT) at boot time.
+    pub ibft: bool,
+    /// ID of the configured node for the same target, if any.
+    pub node: Option<u32>,
+    #[serde(flatten)]
+    pub session: ISCSISession,
+}
+
+impl TryFrom<&HashMap<String, OwnedValue>> for ISCSIActiveSession {
+    type Error = ServiceError;
+
+    fn try_from(value: &HashMap<String, OwnedValue>) -> Result<Self, Self::Error> {
+        Ok(ISCSIActiveSession {
+            target: get_property(value, "Target")?,
+            address: get_property(value, "Address")?,
+            port: get_property(value, "Port")?,
+            interface: get_optional_property(value, "Interface")?
+                .unwrap_or_else(|| DEFAULT_ISCSI_INTERFACE.to_string()),
+            ibft: get_optional_property(value, "IBFT")?.unwrap_or_default(),
+            node: None,
+            session: ISCSISession::try_from(value)?,
+        })
+    }
+}
+
+impl ISCSIActiveSession {
+    /// Links the session to the configured node for the same target, if any.
+    ///
+    /// * `nodes`: configured nodes.
+    pub fn link_node(&mut self, nodes: &[ISCSINode]) {
+        self.node = nodes
+            .iter()
+            .find(|n| {
+                n.target == self.target
+                    && n.address == self.address
+                    && n.port == self.port
+                    && n.interface == self.interface
+            })
+            .map(|n| n.id);
+    }
+}
+
+/// Nodes found by an iSCSI discovery.
+#[derive(Clone, Debug, Default, Serialize, utoipa::ToSchema)]
+pub struct DiscoveryResult {
+    /// Nodes which did not exist before the discovery.
+    pub added: Vec<ISCSINode>,
+    /// Nodes which already existed (kept as they were).
+    pub present: Vec<ISCSINode>,
+}
+
+/// Changes to apply after a discovery to keep the existing nodes untouched.
+#[derive(Debug, Default, PartialEq)]
+struct DiscoveryReconciliation {
+    /// IDs of the new nodes which duplicate an existing one.
+    duplicates: Vec<u32>,
+    /// Startup modes to restore on the existing nodes.
+    startups: Vec<(u32, String)>,
+}
+
+/// Compares the nodes before and after a discovery.
+///
+/// * `before`: nodes before the discovery.
+/// * `after`: nodes after the discovery.
+fn reconcile_discovery(
+    before: &[ISCSINode],
+    after: &[ISCSINode],
+) -> (DiscoveryResult, DiscoveryReconciliation) {
+    let mut result = DiscoveryResult::default();
+    let mut reconciliation = DiscoveryReconciliation::default();
+
+    for node in after {
+        let previous = before.iter().find(|n| n.same_target(node));
+        match previous {
+            None => {
+                if result.added.iter().any(|n| n.same_target(node)) {
+                    reconciliation.duplicates.push(node.id);
+                } else {
+                    result.added.push(node.clone());
+                }
+            }
+            Some(previous) => {
+                let kept = after.iter().any(|n| n.id == previous.id);
+                if (kept && previous.id != node.id)
+                    || result.present.iter().any(|n| n.same_target(node))
+                {
+                    reconciliation.duplicates.push(node.id);
+                    continue;
+                }
+                if previous.startup != node.startup {
+                    reconciliation
+                        .startups
+                        .push((node.id, previous.startup.clone()));
+                }
+                let mut node = node.clone();
+                node.startup = previous.startup.clone();
+                result.present.push(node);
+            }
+        }
+    }
+    (result, reconciliation)
+}
+
 impl TryFrom<&HashMap<String, OwnedValue>> for ISCSINode {
     type Error = ServiceError;
 
@@ -78,8 +293,34 @@ impl TryFrom<&HashMap<String, OwnedValue>> for ISCSINode {
     }
 }
 
+/// Authentication mode for the iSCSI targets.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ISCSIAuthMode {
+    /// No authentication at all. Any previously negotiated CHAP credentials are cleared.
+    None,
+    /// CHAP authentication, optionally mutual (using the reverse credentials).
+    Chap,
+}
+
+impl ISCSIAuthMode {
+    pub fn as_dbus_string(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Chap => "chap",
+        }
+    }
+}
+
+/// Authentication options.
+///
+/// If the `auth_mode` is not set, only the given credentials are sent and the backend decides
+/// (e.g., reusing the discovery ones).
 #[derive(Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct ISCSIAuth {
+    /// Authentication mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_mode: Option<ISCSIAuthMode>,
     /// Username for authentication by target.
     pub username: Option<String>,
     /// Password for authentication by target.
@@ -90,10 +331,51 @@ pub struct ISCSIAuth {
     pub reverse_password: Option<String>,
 }
 
+impl ISCSIAuth {
+    /// Checks whether the credentials are consistent with the authentication mode.
+    ///
+    /// It returns the list of problems, if any.
+    pub fn validate(&self) -> Vec<String> {
+        let mut issues = vec![];
+        let has_credentials = self.username.is_some()
+            || self.password.is_some()
+            || self.reverse_username.is_some()
+            || self.reverse_password.is_some();
+        match self.auth_mode {
+            Some(ISCSIAuthMode::None) if has_credentials => {
+                issues.push("No credentials are allowed without authentication".to_string());
+            }
+            Some(ISCSIAuthMode::Chap) if self.username.is_none() || self.password.is_none() => {
+                issues.push("CHAP authentication requires a username and a password".to_string());
+            }
+            _ => {}
+        }
+        if self.reverse_username.is_some() != self.reverse_password.is_some() {
+            issues.push("The reverse username and password must be given together".to_string());
+        }
+        issues
+    }
+}
+
 impl From<ISCSIAuth> for HashMap<String, OwnedValue> {
     fn from(value: ISCSIAuth) -> Self {
         let mut hash = HashMap::new();
 
+        if let Some(mode) = value.auth_mode {
+            hash.insert(
+                "AuthMode".to_string(),
+                Value::new(mode.as_dbus_string()).to_owned(),
+            );
+        }
+
+        if value.auth_mode == Some(ISCSIAuthMode::None) {
+            // empty credentials so the backend clears the previous ones
+            for key in ["Username", "Password", "ReverseUsername", "ReversePassword"] {
+                hash.insert(key.to_string(), Value::new("").to_owned());
+            }
+            return hash;
+        }
+
         if let Some(username) = value.username {
             hash.insert("Username".to_string(), Value::new(username).to_owned());
         }
@@ -120,6 +402,9 @@ impl From<ISCSIAuth> for HashMap<String, OwnedValue> {
     }
 }
 
+/// Allowed values for the startup of an ISCSI node.
+pub const STARTUP_MODES: [&str; 3] = ["onboot", "automatic", "manual"];
+
 /// D-Bus client for the ISCSI part of the storage service.
 #[derive(Clone)]
 pub struct ISCSIClient<'a> {
@@ -156,14 +441,20 @@ impl<'a> ISCSIClient<'a> {
     /// * `address`: target address in string-like form.
     /// * `port`: target port.
     /// * `auth`: authentication options.
+    /// * `interface`: iSCSI interface to bind the discovery to (default interface if `None`).
     pub async fn discover<'b>(
         &self,
         address: &str,
         port: u32,
         auth: ISCSIAuth,
+        interface: Option<&str>,
     ) -> Result<bool, ServiceError> {
         let mut options_hash: HashMap<&str, zvariant::Value> = HashMap::new();
 
+        if let Some(interface) = interface {
+            options_hash.insert("Interface", interface.to_string().into());
+        }
+
         if let (Some(ref username), Some(ref password)) = (auth.username, auth.password) {
             options_hash.insert("Username", username.to_string().into());
             options_hash.insert("Password", password.to_string().into());
@@ -188,6 +479,63 @@ impl<'a> ISCSIClient<'a> {
         Ok(result == 0)
     }
 
+    /// Performs an iSCSI discovery without altering the already known nodes.
+    ///
+    /// The nodes duplicating an existing one are removed and the startup mode of the existing
+    /// nodes is restored. It returns `None` if the discovery failed.
+    ///
+    /// * `address`: iSCSI server address.
+    /// * `port`: iSCSI service port.
+    /// * `auth`: authentication options.
+    /// * `interface`: iSCSI interface to bind the discovery to.
+    pub async fn discover_nodes(
+        &self,
+        address: &str,
+        port: u32,
+        auth: ISCSIAuth,
+        interface: Option<&str>,
+    ) -> Result<Option<DiscoveryResult>, ServiceError> {
+        let before = self.get_nodes().await?;
+        if !self.discover(address, port, auth, interface).await? {
+            return Ok(None);
+        }
+        let after = self.get_nodes().await?;
+
+        let (result, reconciliation) = reconcile_discovery(&before, &after);
+        for id in reconciliation.duplicates {
+            log::info!("Removing duplicated iSCSI node {}", id);
+            self.delete_node(id).await?;
+        }
+        for (id, startup) in reconciliation.startups {
+            self.set_startup(id, &startup).await?;
+        }
+        Ok(Some(result))
+    }
+
+    /// Whether iSCSI is supported (i.e., the storage service found the iSCSI initiator tools).
+    pub async fn supported(&self) -> Result<bool, ServiceError> {
+        Ok(optional_property(self.initiator_proxy.supported().await)?.unwrap_or(false))
+    }
+
+    /// Returns the names of the iSCSI interfaces (iscsiadm ifaces), including offload engines.
+    pub async fn interfaces(&self) -> Result<Vec<String>, ServiceError> {
+        Ok(self.initiator_proxy.interfaces().await?)
+    }
+
+    /// Returns the details of the iSCSI interfaces.
+    ///
+    /// The interface of the default software initiator is not included, so the list is empty
+    /// when there are no other interfaces.
+    pub async fn interfaces_details(&self) -> Result<Vec<ISCSIInterface>, ServiceError> {
+        self.initiator_proxy
+            .interfaces_details()
+            .await?
+            .iter()
+            .map(ISCSIInterface::try_from)
+            .filter(|i| !matches!(i, Ok(i) if i.name == DEFAULT_ISCSI_INTERFACE))
+            .collect()
+    }
+
     /// Returns the initiator data.
     pub async fn get_initiator(&self) -> Result<ISCSIInitiator, ServiceError> {
         let ibft = self.initiator_proxy.ibft().await?;
@@ -202,6 +550,53 @@ impl<'a> ISCSIClient<'a> {
         Ok(self.initiator_proxy.set_initiator_name(name).await?)
     }
 
+    /// Returns the initiator name.
+    pub async fn initiator_name(&self) -> Result<String, ServiceError> {
+        Ok(self.initiator_proxy.initiator_name().await?)
+    }
+
+    /// Returns the initiator name provided by the iBFT, if any.
+    pub fn ibft_initiator_name() -> Option<String> {
+        std::fs::read_to_string(IBFT_INITIATOR_NAME_PATH)
+            .ok()
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+    }
+
+    /// Returns the initiator name configured in the system, if any.
+    pub fn default_initiator_name() -> Option<String> {
+        std::fs::read_to_string(INITIATOR_NAME_PATH)
+            .ok()
+            .and_then(|content| parse_initiator_name(&content))
+    }
+
+    /// Restores the initiator name to the one provided by the iBFT or, if there is none, to the
+    /// one configured in the system. It does nothing if the initiator already has that name.
+    ///
+    /// The names are read each time, so they are up to date.
+    pub async fn reset_initiator_name(&self) -> Result<InitiatorNameReset, ServiceError> {
+        let (name, source) = match Self::ibft_initiator_name() {
+            Some(name) => (name, InitiatorNameSource::Ibft),
+            None => {
+                let name =
+                    Self::default_initiator_name().ok_or(ServiceError::UnsuccessfulAction(
+                        "restore the initiator name (the default one is unknown)".to_string(),
+                    ))?;
+                (name, InitiatorNameSource::Default)
+            }
+        };
+
+        let changed = self.initiator_name().await? != name;
+        if changed {
+            self.set_initiator_name(&name).await?;
+        }
+        Ok(InitiatorNameReset {
+            name,
+            source,
+            changed,
+        })
+    }
+
     /// Returns the iSCSI nodes.
     pub async fn get_nodes(&self) -> Result<Vec<ISCSINode>, ServiceError> {
         let managed_objects = self.object_manager_proxy.get_managed_objects().await?;
@@ -233,16 +628,74 @@ impl<'a> ISCSIClient<'a> {
         Ok(proxy.set_startup(startup).await?)
     }
 
+    /// Logs into an ISCSI node.
+    ///
+    /// * `id`: node ID.
+    /// * `auth`: authentication options.
+    /// * `startup`: startup value.
+    /// * `interface`: iSCSI interface to bind the session to (default interface if `None`).
+    ///
+    /// It returns a [LoginError] if the login failed for a known reason.
     pub async fn login(
         &self,
         id: u32,
         auth: ISCSIAuth,
         startup: String,
-    ) -> Result<LoginResult, ServiceError> {
+        interface: Option<String>,
+    ) -> Result<Result<(), LoginError>, ServiceError> {
         let proxy = self.get_node_proxy(id).await?;
+        if proxy.connected().await? {
+            return Ok(Err(LoginError {
+                code: LoginResult::AlreadyLoggedIn,
+                message: None,
+            }));
+        }
 
-        let mut options: HashMap<String, OwnedValue> = auth.into();
-        options.insert("Startup".to_string(), Value::new(startup).to_owned());
+        Self::login_node(&proxy, auth, startup, interface).await
+    }
+
+    /// Checks whether the target accepts the given credentials, without keeping the session.
+    ///
+    /// The storage service logs into the node and, if it succeeds, logs out right away. The
+    /// authentication settings of the node are restored afterwards. A connected node cannot be
+    /// checked, as logging out would close its session.
+    ///
+    /// * `id`: node ID.
+    /// * `auth`: authentication options.
+    /// * `interface`: iSCSI interface to bind the session to (default interface if `None`).
+    ///
+    /// It returns a [LoginError] if the authentication failed for a known reason and an error if
+    /// the session could not be closed.
+    pub async fn test_auth(
+        &self,
+        id: u32,
+        auth: ISCSIAuth,
+        interface: Option<String>,
+    ) -> Result<Result<(), LoginError>, ServiceError> {
+        let proxy = self.get_node_proxy(id).await?;
+        if proxy.connected().await? {
+            return Ok(Err(LoginError {
+                code: LoginResult::AlreadyLoggedIn,
+                message: None,
+            }));
+        }
+
+        let options = Self::login_options(auth, None, interface);
+        let mut options_ref: HashMap<&str, &zvariant::Value<'_>> = HashMap::new();
+        for (key, value) in options.iter() {
+            options_ref.insert(key, value);
+        }
+        let result = proxy.test_auth(options_ref).await?;
+        Self::login_result(&proxy, result).await
+    }
+
+    async fn login_node(
+        proxy: &NodeProxy<'_>,
+        auth: ISCSIAuth,
+        startup: String,
+        interface: Option<String>,
+    ) -> Result<Result<(), LoginError>, ServiceError> {
+        let options = Self::login_options(auth, Some(startup), interface);
 
         // FIXME: duplicated code (see discover)
         let mut options_ref: HashMap<&str, &zvariant::Value<'_>> = HashMap::new();
@@ -250,9 +703,76 @@ impl<'a> ISCSIClient<'a> {
             options_ref.insert(key, value);
         }
         let result = proxy.login(options_ref).await?;
+        Self::login_result(proxy, result).await
+    }
+
+    fn login_options(
+        auth: ISCSIAuth,
+        startup: Option<String>,
+        interface: Option<String>,
+    ) -> HashMap<String, OwnedValue> {
+        let mut options: HashMap<String, OwnedValue> = auth.into();
+        if let Some(startup) = startup {
+            options.insert("Startup".to_string(), Value::new(startup).to_owned());
+        }
+        if let Some(interface) = interface {
+            options.insert("Interface".to_string(), Value::new(interface).to_owned());
+        }
+        options
+    }
+
+    /// Converts the result of a login attempt, including the details of the failure reported by
+    /// the backend.
+    async fn login_result(
+        proxy: &NodeProxy<'_>,
+        result: u32,
+    ) -> Result<Result<(), LoginError>, ServiceError> {
         let result =
             LoginResult::try_from(result).map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
-        Ok(result)
+        if let LoginResult::Success = result {
+            return Ok(Ok(()));
+        }
+
+        let message = proxy.login_error().await?;
+        Ok(Err(LoginError {
+            code: result,
+            message: Some(message).filter(|m| !m.is_empty()),
+        }))
+    }
+
+    /// Returns the live session of an iSCSI node.
+    ///
+    /// It returns `None` if the node is not connected.
+    ///
+    /// * `id`: node ID.
+    pub async fn session(&self, id: u32) -> Result<Option<ISCSISession>, ServiceError> {
+        let proxy = self.get_node_proxy(id).await?;
+        if !proxy.connected().await? {
+            return Ok(None);
+        }
+        let session = proxy.session().await?;
+        if session.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(ISCSISession::try_from(&session)?))
+    }
+
+    /// Returns the sessions currently held by the system.
+    ///
+    /// Unlike the nodes, they are read from the live sessions, so they include the ones
+    /// established at boot time (e.g., through iBFT). Each session is linked to the configured
+    /// node for the same target, if any.
+    pub async fn active_sessions(&self) -> Result<Vec<ISCSIActiveSession>, ServiceError> {
+        let sessions = self.initiator_proxy.sessions().await?;
+        let nodes = self.get_nodes().await?;
+        sessions
+            .iter()
+            .map(|s| {
+                let mut session = ISCSIActiveSession::try_from(s)?;
+                session.link_node(&nodes);
+                Ok(session)
+            })
+            .collect()
     }
 
     pub async fn logout(&self, id: u32) -> Result<bool, ServiceError> {
@@ -269,22 +789,41 @@ impl<'a> ISCSIClient<'a> {
     }
 
     pub async fn get_node_proxy(&self, id: u32) -> Result<NodeProxy, ServiceError> {
+        // LoginError is updated by the login call itself, so it must not be read from the cache
         let proxy = NodeProxy::builder(&self.connection)
             .path(format!("/org/opensuse/Agama/Storage1/iscsi_nodes/{}", id))?
+            .cache_properties(zbus::CacheProperties::No)
             .build()
             .await?;
         Ok(proxy)
     }
 }
 
-#[derive(Serialize, utoipa::ToSchema)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, utoipa::ToSchema)]
 pub enum LoginResult {
     /// Successful login.
     Success = 0,
     /// Invalid startup value.
     InvalidStartup = 1,
-    /// Failed login.
+    /// Failed login (generic error).
     Failed = 2,
+    /// The target rejected the credentials.
+    AuthenticationFailed = 3,
+    /// The target does not exist in the portal.
+    TargetNotFound = 4,
+    /// The portal could not be reached.
+    NetworkUnreachable = 5,
+    /// There is already a session for the node.
+    AlreadyLoggedIn = 6,
+}
+
+/// Failed iSCSI login.
+#[derive(Clone, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct LoginError {
+    pub code: LoginResult,
+    /// Details of the failure, if the backend reports them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -299,6 +838,10 @@ impl TryFrom<u32> for LoginResult {
             v if v == Self::Success as u32 => Ok(Self::Success),
             v if v == Self::InvalidStartup as u32 => Ok(Self::InvalidStartup),
             v if v == Self::Failed as u32 => Ok(Self::Failed),
+            v if v == Self::AuthenticationFailed as u32 => Ok(Self::AuthenticationFailed),
+            v if v == Self::TargetNotFound as u32 => Ok(Self::TargetNotFound),
+            v if v == Self::NetworkUnreachable as u32 => Ok(Self::NetworkUnreachable),
+            v if v == Self::AlreadyLoggedIn as u32 => Ok(Self::AlreadyLoggedIn),
             _ => Err(InvalidLoginResult(value)),
         }
     }
@@ -311,6 +854,205 @@ impl fmt::Display for LoginResult {
             Self::Success => write!(f, "Success"),
             Self::InvalidStartup => write!(f, "Invalid startup value"),
             Self::Failed => write!(f, "Could not login into the iSCSI node"),
+            Self::AuthenticationFailed => write!(f, "The iSCSI authentication failed"),
+            Self::TargetNotFound => write!(f, "The iSCSI target was not found"),
+            Self::NetworkUnreachable => write!(f, "The iSCSI portal is not reachable"),
+            Self::AlreadyLoggedIn => write!(f, "Already logged into the iSCSI node"),
+        }
+    }
+}
+
+/// Extracts the initiator name from the content of an `initiatorname.iscsi` file.
+///
+/// * `content`: lines like `InitiatorName=iqn.1996-04.de.suse:01:abcdef`. Comments are ignored.
+fn parse_initiator_name(content: &str) -> Option<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .find_map(|line| line.strip_prefix("InitiatorName="))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: u32, target: &str, startup: &str) -> ISCSINode {
+        ISCSINode {
+            id,
+            target: target.to_string(),
+            address: "192.168.100.1".to_string(),
+            port: 3260,
+            interface: "default".to_string(),
+            startup: startup.to_string(),
+            ..Default::default()
         }
     }
+
+    #[test]
+    fn test_parse_initiator_name() {
+        let content = "## Generated by iscsi-gen-initiatorname\n\
+            #InitiatorName=iqn.1996-04.de.suse:01:old\n\
+            InitiatorName=iqn.1996-04.de.suse:01:6f0b24d4e288\n";
+        assert_eq!(
+            parse_initiator_name(content),
+            Some("iqn.1996-04.de.suse:01:6f0b24d4e288".to_string())
+        );
+        assert_eq!(parse_initiator_name("InitiatorName=\n"), None);
+        assert_eq!(parse_initiator_name(""), None);
+    }
+
+    #[test]
+    fn test_session_from_dbus() {
+        let session = HashMap::from([
+            ("State".to_string(), Value::new("LOGGED_IN").to_owned()),
+            ("Connections".to_string(), Value::new(1_u32).to_owned()),
+            ("HeaderDigest".to_string(), Value::new("CRC32C").to_owned()),
+            ("DataDigest".to_string(), Value::new("None").to_owned()),
+        ]);
+        let session = ISCSISession::try_from(&session).unwrap();
+        assert_eq!(session.state, "LOGGED_IN");
+        assert_eq!(session.connections, 1);
+        assert_eq!(session.header_digest, "CRC32C");
+        assert!(session.parameters.is_empty());
+    }
+
+    #[test]
+    fn test_active_session_from_dbus() {
+        let session = HashMap::from([
+            (
+                "Target".to_string(),
+                Value::new("iqn.2024-01.test:a").to_owned(),
+            ),
+            (
+                "Address".to_string(),
+                Value::new("192.168.100.1").to_owned(),
+            ),
+            ("Port".to_string(), Value::new(3260_u32).to_owned()),
+            ("IBFT".to_string(), Value::new(true).to_owned()),
+            ("State".to_string(), Value::new("LOGGED_IN").to_owned()),
+            ("Connections".to_string(), Value::new(1_u32).to_owned()),
+            ("HeaderDigest".to_string(), Value::new("None").to_owned()),
+            ("DataDigest".to_string(), Value::new("None").to_owned()),
+        ]);
+        let mut session = ISCSIActiveSession::try_from(&session).unwrap();
+        assert_eq!(session.interface, "default");
+        assert!(session.ibft);
+
+        session.link_node(&[node(1, "iqn.2024-01.test:b", "onboot")]);
+        assert_eq!(session.node, None);
+        session.link_node(&[
+            node(1, "iqn.2024-01.test:b", "onboot"),
+            node(2, "iqn.2024-01.test:a", "onboot"),
+        ]);
+        assert_eq!(session.node, Some(2));
+    }
+
+    #[test]
+    fn test_discover_twice() {
+        let discovered = vec![
+            node(1, "iqn.2024-01.test:a", "manual"),
+            node(2, "iqn.2024-01.test:b", "manual"),
+        ];
+        let (result, reconciliation) = reconcile_discovery(&[], &discovered);
+        assert_eq!(result.added.len(), 2);
+        assert!(result.present.is_empty());
+        assert_eq!(reconciliation, DiscoveryReconciliation::default());
+
+        // the user changes the startup of the first node and discovers again
+        let mut before = discovered.clone();
+        before[0].startup = "onboot".to_string();
+        let after = vec![
+            node(1, "iqn.2024-01.test:a", "manual"),
+            node(2, "iqn.2024-01.test:b", "manual"),
+            node(3, "iqn.2024-01.test:b", "manual"),
+        ];
+        let (result, reconciliation) = reconcile_discovery(&before, &after);
+        assert!(result.added.is_empty());
+        let ids: Vec<u32> = result.present.iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(result.present[0].startup, "onboot");
+        assert_eq!(reconciliation.duplicates, vec![3]);
+        assert_eq!(reconciliation.startups, vec![(1, "onboot".to_string())]);
+    }
+
+    #[test]
+    fn test_auth_none_clears_credentials() {
+        let auth = ISCSIAuth {
+            auth_mode: Some(ISCSIAuthMode::None),
+            ..Default::default()
+        };
+        assert!(auth.validate().is_empty());
+        let hash: HashMap<String, OwnedValue> = auth.into();
+        let mode: String = get_property(&hash, "AuthMode").unwrap();
+        assert_eq!(mode, "none");
+        let username: String = get_property(&hash, "Username").unwrap();
+        assert!(username.is_empty());
+        let password: String = get_property(&hash, "ReversePassword").unwrap();
+        assert!(password.is_empty());
+
+        let hash: HashMap<String, OwnedValue> = ISCSIAuth::default().into();
+        assert!(hash.is_empty());
+    }
+
+    #[test]
+    fn test_validate_auth() {
+        let auth = ISCSIAuth {
+            auth_mode: Some(ISCSIAuthMode::None),
+            username: Some("admin".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(auth.validate().len(), 1);
+
+        let auth = ISCSIAuth {
+            auth_mode: Some(ISCSIAuthMode::Chap),
+            username: Some("admin".to_string()),
+            reverse_username: Some("target".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(auth.validate().len(), 2);
+
+        let auth = ISCSIAuth {
+            auth_mode: Some(ISCSIAuthMode::Chap),
+            username: Some("admin".to_string()),
+            password: Some("secret".to_string()),
+            ..Default::default()
+        };
+        assert!(auth.validate().is_empty());
+    }
+
+    #[test]
+    fn test_login_result_from_dbus() {
+        assert_eq!(
+            LoginResult::try_from(3),
+            Ok(LoginResult::AuthenticationFailed)
+        );
+        assert_eq!(
+            LoginResult::try_from(5),
+            Ok(LoginResult::NetworkUnreachable)
+        );
+    }
+
+    #[test]
+    fn test_interface_from_dbus() {
+        let data = HashMap::from([
+            (
+                "Name".to_string(),
+                Value::from("bnx2i.00:10:18:aa:bb:cc").into(),
+            ),
+            ("Transport".to_string(), Value::from("bnx2i").into()),
+            (
+                "HWAddress".to_string(),
+                Value::from("00:10:18:aa:bb:cc").into(),
+            ),
+            ("IPAddress".to_string(), Value::from("").into()),
+        ]);
+        let interface = ISCSIInterface::try_from(&data).unwrap();
+        assert_eq!(interface.name, "bnx2i.00:10:18:aa:bb:cc");
+        assert_eq!(interface.transport, "bnx2i");
+        assert_eq!(interface.mac_address.as_deref(), Some("00:10:18:aa:bb:cc"));
+        assert_eq!(interface.ip_address, None);
+    }
 }
@@ -0,0 +1,128 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements a client to access Agama's D-Bus API related to NVMe-oF management.
+
+use std::collections::HashMap;
+
+use zbus::{zvariant::Value, Connection};
+
+use crate::{
+    error::ServiceError,
+    storage::{
+        model::nvmeof::{
+            NVMeoFController, NVMeoFError, NVMeoFResult, NVMeoFTarget, DEFAULT_DISCOVERY_PORT,
+            DEFAULT_PORT,
+        },
+        proxies::NVMeoFManagerProxy,
+    },
+};
+
+/// Client to connect to Agama's D-Bus API for NVMe-oF management.
+#[derive(Clone)]
+pub struct NVMeoFClient<'a> {
+    manager_proxy: NVMeoFManagerProxy<'a>,
+}
+
+impl<'a> NVMeoFClient<'a> {
+    pub async fn new(connection: Connection) -> Result<Self, ServiceError> {
+        let manager_proxy = NVMeoFManagerProxy::new(&connection).await?;
+        Ok(Self { manager_proxy })
+    }
+
+    pub async fn supported(&self) -> Result<bool, ServiceError> {
+        let introspect = self.manager_proxy.introspect().await?;
+        Ok(introspect.contains("org.opensuse.Agama.Storage1.NVMeoF.Manager"))
+    }
+
+    /// Returns the connected controllers.
+    pub async fn controllers(&self) -> Result<Vec<NVMeoFController>, ServiceError> {
+        let controllers = self.manager_proxy.controllers().await?;
+        controllers.iter().map(NVMeoFController::try_from).collect()
+    }
+
+    /// Asks a discovery controller for the subsystems it exports.
+    ///
+    /// * `target`: address of the discovery controller (the subsystem NQN is ignored).
+    pub async fn discover(
+        &self,
+        target: &NVMeoFTarget,
+    ) -> Result<Result<Vec<NVMeoFTarget>, NVMeoFError>, ServiceError> {
+        let target = NVMeoFTarget {
+            trsvcid: Some(target.service_id(DEFAULT_DISCOVERY_PORT)),
+            subnqn: String::new(),
+            ..target.clone()
+        };
+        let options: HashMap<&str, Value<'_>> = (&target).into();
+        let (code, message, records) = self.manager_proxy.discover(options).await?;
+        if let Err(error) = Self::check(code, &target, message) {
+            return Ok(Err(error));
+        }
+        let targets = records
+            .iter()
+            .map(NVMeoFTarget::try_from)
+            .collect::<Result<_, _>>()?;
+        Ok(Ok(targets))
+    }
+
+    /// Connects to an NVMe-oF subsystem.
+    ///
+    /// * `target`: target to connect to.
+    pub async fn connect(
+        &self,
+        target: &NVMeoFTarget,
+    ) -> Result<Result<(), NVMeoFError>, ServiceError> {
+        let target = NVMeoFTarget {
+            trsvcid: Some(target.service_id(DEFAULT_PORT)),
+            ..target.clone()
+        };
+        let options: HashMap<&str, Value<'_>> = (&target).into();
+        let (code, message) = self.manager_proxy.connect(options).await?;
+        Ok(Self::check(code, &target, message))
+    }
+
+    /// Disconnects a controller.
+    ///
+    /// * `name`: controller name (e.g., "nvme1").
+    pub async fn disconnect(&self, name: &str) -> Result<Result<(), NVMeoFError>, ServiceError> {
+        let controllers = self.controllers().await?;
+        let Some(controller) = controllers.iter().find(|c| c.name == name) else {
+            return Ok(Err(NVMeoFError::new(
+                NVMeoFResult::UnknownController,
+                Default::default(),
+                String::new(),
+            )));
+        };
+        let (code, message) = self.manager_proxy.disconnect(name).await?;
+        Ok(Self::check(code, &controller.target, message))
+    }
+
+    fn check(code: u32, target: &NVMeoFTarget, message: String) -> Result<(), NVMeoFError> {
+        match NVMeoFResult::try_from(code) {
+            Ok(NVMeoFResult::Success) => Ok(()),
+            Ok(code) => Err(NVMeoFError::new(code, target.transport, message)),
+            Err(error) => Err(NVMeoFError::new(
+                NVMeoFResult::Failed,
+                target.transport,
+                error.to_string(),
+            )),
+        }
+    }
+}
@@ -18,7 +18,7 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use serde::{Deserialize, Serialize};
 use zbus::zvariant::{OwnedValue, Value};
@@ -26,9 +26,10 @@ use zbus::zvariant::{OwnedValue, Value};
 use crate::dbus::{get_optional_property, get_property};
 
 pub mod dasd;
+pub mod nvmeof;
 pub mod zfcp;
 
-#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct DeviceSid(u32);
 
 impl From<u32> for DeviceSid {
@@ -78,6 +79,13 @@ impl TryFrom<zbus::zvariant::ObjectPath<'_>> for DeviceSid {
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct DeviceSize(u64);
 
+impl DeviceSize {
+    /// Size in bytes.
+    pub fn bytes(&self) -> u64 {
+        self.0
+    }
+}
+
 impl From<u64> for DeviceSize {
     fn from(value: u64) -> Self {
         DeviceSize(value)
@@ -185,10 +193,61 @@ impl TryFrom<zbus::zvariant::Value<'_>> for SpaceAction {
     }
 }
 
+/// How a file system is referenced in the fstab.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum MountBy {
+    /// File system UUID (`UUID=`).
+    Uuid,
+    /// File system label (`LABEL=`).
+    Label,
+    /// Hardware path (`/dev/disk/by-path`).
+    Path,
+    /// Kernel device name (e.g., `/dev/sda1`).
+    Device,
+    /// Hardware identifier (`/dev/disk/by-id`).
+    Id,
+}
+
+impl MountBy {
+    pub fn as_dbus_string(&self) -> String {
+        match &self {
+            Self::Uuid => "uuid",
+            Self::Label => "label",
+            Self::Path => "path",
+            Self::Device => "device",
+            Self::Id => "id",
+        }
+        .to_string()
+    }
+}
+
+impl TryFrom<zbus::zvariant::Value<'_>> for MountBy {
+    type Error = zbus::zvariant::Error;
+
+    fn try_from(value: zbus::zvariant::Value) -> Result<Self, zbus::zvariant::Error> {
+        let svalue: String = value.try_into()?;
+        match svalue.as_str() {
+            "uuid" => Ok(Self::Uuid),
+            "label" => Ok(Self::Label),
+            "path" => Ok(Self::Path),
+            "device" => Ok(Self::Device),
+            "id" => Ok(Self::Id),
+            _ => Err(zbus::zvariant::Error::Message(format!(
+                "Wrong value for MountBy: {}",
+                svalue
+            ))),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct SpaceActionSettings {
     pub device: String,
     pub action: SpaceAction,
+    /// Target size in bytes when resizing (the minimum possible size if not set).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
 }
 
 impl TryFrom<zbus::zvariant::Value<'_>> for SpaceActionSettings {
@@ -199,6 +258,7 @@ impl TryFrom<zbus::zvariant::Value<'_>> for SpaceActionSettings {
         let res = SpaceActionSettings {
             device: get_property(&mvalue, "Device")?,
             action: get_property(&mvalue, "Action")?,
+            size: get_optional_property(&mvalue, "Size")?,
         };
 
         Ok(res)
@@ -207,201 +267,2219 @@ impl TryFrom<zbus::zvariant::Value<'_>> for SpaceActionSettings {
 
 impl<'a> From<SpaceActionSettings> for zbus::zvariant::Value<'a> {
     fn from(val: SpaceActionSettings) -> Self {
-        let result: HashMap<&str, Value> = HashMap::from([
+        let mut result: HashMap<&str, Value> = HashMap::from([
             ("Device", Value::new(val.device)),
             ("Action", Value::new(val.action.as_dbus_string())),
         ]);
+        if let Some(size) = val.size {
+            result.insert("Size", Value::new(size));
+        }
 
         Value::new(result)
     }
 }
 
-/// Represents a proposal patch -> change of proposal configuration that can be partial
-#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+/// File systems which can be shrunk while they are mounted.
+const ONLINE_SHRINK_FILESYSTEMS: [&str; 1] = ["btrfs"];
+
+/// Reasons why an existing partition cannot be resized.
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum ResizeError {
+    #[error("Unknown device {0}")]
+    UnknownDevice(String),
+    #[error("{0} is not a partition")]
+    NotPartition(String),
+    #[error("{0} cannot be resized: {1}")]
+    Unsupported(String, String),
+    #[error("The file system of {0} must be unmounted to be shrunk")]
+    Mounted(String),
+    #[error("{device} cannot be grown to {size} bytes (current size: {current_size} bytes)")]
+    CannotGrow {
+        device: String,
+        size: u64,
+        current_size: u64,
+    },
+    #[error("{device} cannot be shrunk below {min_size} bytes without losing data")]
+    BelowMinimum { device: String, min_size: u64 },
+}
+
+/// Planned resizing of an existing partition.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
-pub struct ProposalSettingsPatch {
-    pub target: Option<ProposalTarget>,
-    pub target_device: Option<String>,
-    #[serde(rename = "targetPVDevices")]
-    pub target_pv_devices: Option<Vec<String>>,
-    pub configure_boot: Option<bool>,
-    pub boot_device: Option<String>,
-    pub encryption_password: Option<String>,
-    pub encryption_method: Option<String>,
-    #[serde(rename = "encryptionPBKDFunction")]
-    pub encryption_pbkd_function: Option<String>,
-    pub space_policy: Option<String>,
-    pub space_actions: Option<Vec<SpaceActionSettings>>,
-    pub volumes: Option<Vec<Volume>>,
+pub struct ResizeSummary {
+    pub device: String,
+    /// Current size in bytes.
+    pub current_size: u64,
+    /// Size in bytes after resizing.
+    pub size: u64,
+    /// Space freed in bytes.
+    pub freed: u64,
 }
 
-impl<'a> From<ProposalSettingsPatch> for HashMap<&'static str, Value<'a>> {
-    fn from(val: ProposalSettingsPatch) -> Self {
-        let mut result = HashMap::new();
-        if let Some(target) = val.target {
-            result.insert("Target", Value::new(target.as_dbus_string()));
+impl SpaceActionSettings {
+    /// Checks whether the partition and its file system can be shrunk to the requested size.
+    ///
+    /// The minimum size reported by the probing (which keeps the existing data) is used when no
+    /// size is given. Growing a partition is not supported.
+    ///
+    /// * `devices`: probed devices.
+    pub fn validate_resize(&self, devices: &[Device]) -> Result<ResizeSummary, ResizeError> {
+        let device = devices
+            .iter()
+            .find(|d| d.device_info.name == self.device)
+            .ok_or_else(|| ResizeError::UnknownDevice(self.device.clone()))?;
+        let block = device
+            .block_device
+            .as_ref()
+            .filter(|_| device.partition.is_some())
+            .ok_or_else(|| ResizeError::NotPartition(self.device.clone()))?;
+        let current_size = block.size.bytes();
+
+        let min_size = match &block.shrinking {
+            ShrinkingInfo::Supported(min_size) => min_size.bytes(),
+            ShrinkingInfo::Unsupported(reasons) => {
+                return Err(ResizeError::Unsupported(
+                    self.device.clone(),
+                    reasons.join(", "),
+                ))
+            }
+        };
+
+        let size = self.size.unwrap_or(min_size);
+        if size > current_size {
+            return Err(ResizeError::CannotGrow {
+                device: self.device.clone(),
+                size,
+                current_size,
+            });
         }
-        if let Some(dev) = val.target_device {
-            result.insert("TargetDevice", Value::new(dev));
+        if size < min_size {
+            return Err(ResizeError::BelowMinimum {
+                device: self.device.clone(),
+                min_size,
+            });
         }
-        if let Some(devs) = val.target_pv_devices {
-            result.insert("TargetPVDevices", Value::new(devs));
+
+        if let Some(fs) = device.filesystem.as_ref() {
+            let online = ONLINE_SHRINK_FILESYSTEMS.contains(&fs.fs_type.as_str());
+            if !fs.mount_path.is_empty() && !online && size < current_size {
+                return Err(ResizeError::Mounted(self.device.clone()));
+            }
         }
-        if let Some(value) = val.configure_boot {
-            result.insert("ConfigureBoot", Value::new(value));
+
+        Ok(ResizeSummary {
+            device: self.device.clone(),
+            current_size,
+            size,
+            freed: current_size - size,
+        })
+    }
+}
+
+/// Encryption method which enrolls the LUKS devices against the TPM 2.0, so the installed system
+/// boots without asking for the passphrase.
+pub const TPM_FDE_METHOD: &str = "tpm_fde";
+
+/// Key file to unlock the encrypted devices instead of typing a passphrase (e.g., a file on a USB
+/// token).
+///
+/// The storage service adds the key to the encrypted devices, which keep the encryption password
+/// as a fallback. The key material is never serialized nor shown when debugging. An empty path
+/// disables the key file.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionKeyFile {
+    /// Path of the key file, as written to the crypttab (e.g., "/luks.key").
+    pub path: String,
+    /// Device containing the key file (e.g., "LABEL=USBKEY"). If it is not set, the key file is
+    /// looked up in the root file system.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// Base64-encoded contents of the key file. They are required, as the key file is usually
+    /// not available in the installation system.
+    #[serde(default, skip_serializing)]
+    #[schema(value_type = Option<String>)]
+    pub contents: Option<Passphrase>,
+    /// Local file holding the key material to format the devices.
+    #[serde(skip)]
+    source: Option<String>,
+}
+
+impl std::fmt::Debug for EncryptionKeyFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptionKeyFile")
+            .field("path", &self.path)
+            .field("device", &self.device)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EncryptionKeyFile {
+    /// Name of the file keeping the uploaded key material.
+    const KEY_FILE_NAME: &'static str = "luks.key";
+
+    /// Whether the key file is used.
+    pub fn is_enabled(&self) -> bool {
+        !self.path.is_empty()
+    }
+
+    /// Key file reference as written to the crypttab (e.g., "/luks.key:LABEL=USBKEY").
+    pub fn crypttab_key(&self) -> String {
+        match &self.device {
+            Some(device) => format!("{}:{}", self.path, device),
+            None => self.path.clone(),
         }
-        if let Some(value) = val.boot_device {
-            result.insert("BootDevice", Value::new(value));
+    }
+
+    /// Returns the list of problems found in the key file settings.
+    ///
+    /// The path must be absolute and the key cannot be empty. The key file requires an encryption
+    /// password and it cannot be combined with the TPM-based encryption.
+    ///
+    /// * `encryption_method`: encryption method of the proposal.
+    /// * `encrypted`: whether the proposal sets an encryption password.
+    pub fn validate(&self, encryption_method: Option<&str>, encrypted: bool) -> Vec<String> {
+        if !self.is_enabled() {
+            return vec![];
         }
-        if let Some(value) = val.encryption_password {
-            result.insert("EncryptionPassword", Value::new(value));
+
+        let mut issues = vec![];
+        if !self.path.starts_with('/') || self.path.contains(char::is_whitespace) {
+            issues.push(format!(
+                "The key file path must be an absolute path, not '{}'",
+                self.path
+            ));
         }
-        if let Some(value) = val.encryption_method {
-            result.insert("EncryptionMethod", Value::new(value));
+        if self.device.as_deref().is_some_and(|d| d.is_empty()) {
+            issues.push("The key file device cannot be empty".to_string());
         }
-        if let Some(value) = val.encryption_pbkd_function {
-            result.insert("EncryptionPBKDFunction", Value::new(value));
+        match self.key() {
+            Ok(key) if key.is_empty() => issues.push("The encryption key is empty".to_string()),
+            Ok(_) => {}
+            Err(error) => issues.push(error),
         }
-        if let Some(value) = val.space_policy {
-            result.insert("SpacePolicy", Value::new(value));
+        if !encrypted {
+            issues.push("A key file requires an encryption password".to_string());
         }
-        if let Some(value) = val.space_actions {
-            let list: Vec<Value> = value.into_iter().map(|a| a.into()).collect();
-            result.insert("SpaceActions", Value::new(list));
+        if encryption_method == Some(TPM_FDE_METHOD) {
+            issues.push("A key file cannot be used with TPM-based encryption".to_string());
         }
-        if let Some(value) = val.volumes {
-            let list: Vec<Value> = value.into_iter().map(|a| a.into()).collect();
-            result.insert("Volumes", Value::new(list));
+        issues
+    }
+
+    /// Makes the key material available to the storage service.
+    ///
+    /// The contents are written to the given directory, readable only by the owner. The storage
+    /// service removes the file once the key is added to the devices.
+    ///
+    /// * `dir`: directory to write the key material.
+    pub fn write_key(&mut self, dir: &std::path::Path) -> std::io::Result<()> {
+        use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+
+        let key = self
+            .key()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)?;
+        let path = dir.join(Self::KEY_FILE_NAME);
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&path)?;
+        std::io::Write::write_all(&mut file, &key)?;
+        self.source = Some(path.to_string_lossy().to_string());
+        Ok(())
+    }
+
+    /// Removes the key material written by [Self::write_key], if any.
+    ///
+    /// * `dir`: directory where the key material was written.
+    pub fn remove_key(dir: &std::path::Path) -> std::io::Result<()> {
+        match std::fs::remove_file(dir.join(Self::KEY_FILE_NAME)) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
         }
-        result
+    }
+
+    /// Returns the key material, decoding the contents.
+    fn key(&self) -> Result<Vec<u8>, String> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        match &self.contents {
+            Some(Passphrase(contents)) => STANDARD
+                .decode(contents.trim())
+                .map_err(|_| "The key file contents are not valid base64".to_string()),
+            None => Err("The key file contents are required".to_string()),
+        }
+    }
+
+    fn to_dbus(&self, result: &mut HashMap<&'static str, Value>) {
+        result.insert("EncryptionKeyFile", Value::new(self.path.clone()));
+        result.insert(
+            "EncryptionKeyDevice",
+            Value::new(self.device.clone().unwrap_or_default()),
+        );
+        if self.is_enabled() {
+            result.insert("EncryptionKeyCrypttab", Value::new(self.crypttab_key()));
+        }
+        if let Some(source) = &self.source {
+            result.insert("EncryptionKeySource", Value::new(source.clone()));
+        }
+    }
+
+    fn from_dbus(
+        hash: &HashMap<String, OwnedValue>,
+    ) -> Result<Option<Self>, zbus::zvariant::Error> {
+        let path: String = get_optional_property(hash, "EncryptionKeyFile")?.unwrap_or_default();
+        if path.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            path,
+            device: get_optional_property::<String>(hash, "EncryptionKeyDevice")?
+                .filter(|d| !d.is_empty()),
+            ..Default::default()
+        }))
     }
 }
 
-/// Represents a proposal configuration
-#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+/// Size of the logical sectors assumed when validating an explicit alignment.
+const SECTOR_SIZE: u64 = 512;
+
+/// Partition alignment policy
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
-pub struct ProposalSettings {
-    pub target: ProposalTarget,
-    pub target_device: Option<String>,
-    #[serde(rename = "targetPVDevices")]
-    pub target_pv_devices: Option<Vec<String>>,
-    pub configure_boot: bool,
-    pub boot_device: String,
-    pub default_boot_device: String,
-    pub encryption_password: String,
-    pub encryption_method: String,
-    #[serde(rename = "encryptionPBKDFunction")]
-    pub encryption_pbkd_function: String,
-    pub space_policy: String,
-    pub space_actions: Vec<SpaceActionSettings>,
-    pub volumes: Vec<Volume>,
+pub enum PartitionAlignment {
+    /// Align to the optimal I/O size of the device (1 MiB).
+    #[default]
+    Optimal,
+    /// Align to the classic cylinder boundaries.
+    Cylinder,
+    /// Align to the given amount of bytes.
+    Explicit(u64),
 }
 
-impl TryFrom<HashMap<String, OwnedValue>> for ProposalSettings {
-    type Error = zbus::zvariant::Error;
+impl PartitionAlignment {
+    /// Returns the alignment grain in bytes.
+    pub fn grain(&self) -> u64 {
+        match self {
+            Self::Optimal => 1024 * 1024,
+            Self::Cylinder => 255 * 63 * SECTOR_SIZE,
+            Self::Explicit(grain) => *grain,
+        }
+    }
 
-    fn try_from(hash: HashMap<String, OwnedValue>) -> Result<Self, zbus::zvariant::Error> {
-        let res = ProposalSettings {
-            target: get_property(&hash, "Target")?,
-            target_device: get_optional_property(&hash, "TargetDevice")?,
-            target_pv_devices: get_optional_property(&hash, "TargetPVDevices")?,
-            configure_boot: get_property(&hash, "ConfigureBoot")?,
-            boot_device: get_property(&hash, "BootDevice")?,
-            default_boot_device: get_property(&hash, "DefaultBootDevice")?,
-            encryption_password: get_property(&hash, "EncryptionPassword")?,
-            encryption_method: get_property(&hash, "EncryptionMethod")?,
-            encryption_pbkd_function: get_property(&hash, "EncryptionPBKDFunction")?,
-            space_policy: get_property(&hash, "SpacePolicy")?,
-            space_actions: get_property(&hash, "SpaceActions")?,
-            volumes: get_property(&hash, "Volumes")?,
-        };
+    fn as_dbus_string(&self) -> &'static str {
+        match self {
+            Self::Optimal => "optimal",
+            Self::Cylinder => "cylinder",
+            Self::Explicit(_) => "explicit",
+        }
+    }
 
-        Ok(res)
+    fn from_dbus(
+        hash: &HashMap<String, OwnedValue>,
+    ) -> Result<PartitionAlignment, zbus::zvariant::Error> {
+        let alignment: Option<String> = get_optional_property(hash, "Alignment")?;
+        match alignment.as_deref() {
+            None | Some("optimal") => Ok(Self::Optimal),
+            Some("cylinder") => Ok(Self::Cylinder),
+            Some("explicit") => Ok(Self::Explicit(get_property(hash, "AlignmentGrain")?)),
+            Some(other) => Err(zbus::zvariant::Error::Message(format!(
+                "Unknown partition alignment: {}",
+                other
+            ))),
+        }
     }
 }
 
-/// Represents a single change action done to storage
-#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+/// Minimum size of an EFI System Partition.
+pub const ESP_MIN_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Mount points allowed for the EFI System Partition.
+pub const ESP_MOUNT_PATHS: [&str; 3] = ["/boot/efi", "/efi", "/boot"];
+
+fn default_esp_mount_path() -> String {
+    ESP_MOUNT_PATHS[0].to_string()
+}
+
+/// How to get the EFI System Partition (ESP) for UEFI systems.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
-pub struct Action {
-    device: DeviceSid,
-    text: String,
-    subvol: bool,
-    delete: bool,
-    resize: bool,
+pub enum EspSettings {
+    /// Let the proposal decide.
+    #[default]
+    Auto,
+    /// Reuse an existing ESP, keeping its content (e.g., for dual boot).
+    #[serde(rename_all = "camelCase")]
+    Reuse {
+        /// Name of the partition (e.g., "/dev/sda1").
+        device: String,
+        #[serde(default = "default_esp_mount_path")]
+        mount_path: String,
+    },
+    /// Create a new ESP.
+    #[serde(rename_all = "camelCase")]
+    Create {
+        /// Size in bytes.
+        size: u64,
+        #[serde(default = "default_esp_mount_path")]
+        mount_path: String,
+    },
 }
 
-impl TryFrom<HashMap<String, OwnedValue>> for Action {
-    type Error = zbus::zvariant::Error;
+impl EspSettings {
+    /// Mount point of the ESP, unless the proposal decides it.
+    pub fn mount_path(&self) -> Option<&str> {
+        match self {
+            Self::Auto => None,
+            Self::Reuse { mount_path, .. } | Self::Create { mount_path, .. } => Some(mount_path),
+        }
+    }
 
-    fn try_from(hash: HashMap<String, OwnedValue>) -> Result<Self, zbus::zvariant::Error> {
-        let res = Action {
-            device: get_property(&hash, "Device")?,
-            text: get_property(&hash, "Text")?,
-            subvol: get_property(&hash, "Subvol")?,
-            delete: get_property(&hash, "Delete")?,
-            resize: get_property(&hash, "Resize")?,
+    fn to_dbus(&self, result: &mut HashMap<&'static str, Value>) {
+        let mode = match self {
+            Self::Auto => "auto",
+            Self::Reuse { device, .. } => {
+                result.insert("EspDevice", Value::new(device.clone()));
+                "reuse"
+            }
+            Self::Create { size, .. } => {
+                result.insert("EspSize", Value::new(*size));
+                "create"
+            }
         };
+        result.insert("EspMode", Value::new(mode));
+        if let Some(path) = self.mount_path() {
+            result.insert("EspMountPath", Value::new(path.to_string()));
+        }
+    }
 
-        Ok(res)
+    fn from_dbus(hash: &HashMap<String, OwnedValue>) -> Result<Self, zbus::zvariant::Error> {
+        let mode: Option<String> = get_optional_property(hash, "EspMode")?;
+        let mount_path = || -> Result<String, zbus::zvariant::Error> {
+            Ok(get_optional_property(hash, "EspMountPath")?.unwrap_or_else(default_esp_mount_path))
+        };
+        match mode.as_deref() {
+            None | Some("auto") => Ok(Self::Auto),
+            Some("reuse") => Ok(Self::Reuse {
+                device: get_property(hash, "EspDevice")?,
+                mount_path: mount_path()?,
+            }),
+            Some("create") => Ok(Self::Create {
+                size: get_property(hash, "EspSize")?,
+                mount_path: mount_path()?,
+            }),
+            Some(other) => Err(zbus::zvariant::Error::Message(format!(
+                "Unknown ESP mode: {}",
+                other
+            ))),
+        }
+    }
+
+    /// Checks the ESP settings, returning the errors and the warnings.
+    ///
+    /// * `efi`: whether the system boots using UEFI.
+    /// * `devices`: probed devices, to check the ESP to reuse.
+    pub fn validate(&self, efi: bool, devices: &[Device]) -> (Vec<String>, Vec<String>) {
+        let (mut errors, mut warnings) = (vec![], vec![]);
+        if let Some(path) = self.mount_path() {
+            if !efi {
+                warnings.push(
+                    "The ESP settings are ignored because the system does not use UEFI".to_string(),
+                );
+            }
+            if !ESP_MOUNT_PATHS.contains(&path) {
+                errors.push(format!(
+                    "The ESP cannot be mounted at {} (allowed: {})",
+                    path,
+                    ESP_MOUNT_PATHS.join(", ")
+                ));
+            }
+        }
+
+        match self {
+            Self::Auto => {}
+            Self::Create { size, .. } => {
+                if *size < ESP_MIN_SIZE {
+                    errors.push(format!("The ESP must be at least {} bytes", ESP_MIN_SIZE));
+                }
+            }
+            Self::Reuse { device, .. } => {
+                let Some(esp) = devices.iter().find(|d| &d.device_info.name == device) else {
+                    errors.push(format!("Unknown device {}", device));
+                    return (errors, warnings);
+                };
+                let filesystem = esp.filesystem.as_ref();
+                if filesystem.map(|fs| fs.fs_type.as_str()) != Some("vfat") {
+                    errors.push(format!(
+                        "The ESP {} does not contain a FAT file system",
+                        device
+                    ));
+                }
+                let size = esp
+                    .block_device
+                    .as_ref()
+                    .map(|b| b.size.bytes())
+                    .unwrap_or_default();
+                if size < ESP_MIN_SIZE {
+                    errors.push(format!(
+                        "The ESP {} is smaller than {} bytes",
+                        device, ESP_MIN_SIZE
+                    ));
+                }
+                let free = filesystem
+                    .and_then(|fs| fs.free_space.as_ref())
+                    .map(|s| s.bytes());
+                if free.is_some_and(|free| free < size / 10) {
+                    warnings.push(format!(
+                        "The ESP {} is nearly full, the boot loader might not fit",
+                        device
+                    ));
+                }
+            }
+        }
+        (errors, warnings)
     }
 }
 
-/// Represents value for target key of Volume
-/// It is snake cased when serializing to be compatible with yast2-storage-ng.
-#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum VolumeTarget {
-    Default,
-    NewPartition,
-    NewVg,
-    Device,
-    Filesystem,
+/// File systems which can hold the writable layer of an overlay.
+pub const OVERLAY_FILESYSTEMS: [&str; 3] = ["btrfs", "ext4", "xfs"];
+
+/// Read-only root file system with a writable overlay (e.g., for appliances).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadOnlyRootSettings {
+    /// Whether the root file system is mounted read-only.
+    pub enabled: bool,
+    /// Directory keeping the changes done on top of the root file system (e.g.,
+    /// "/var/lib/overlay"). It must be on a writable volume.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlay: Option<String>,
 }
 
-impl<'a> From<VolumeTarget> for zbus::zvariant::Value<'a> {
-    fn from(val: VolumeTarget) -> Self {
-        let str = match val {
-            VolumeTarget::Default => "default",
-            VolumeTarget::NewPartition => "new_partition",
-            VolumeTarget::NewVg => "new_vg",
-            VolumeTarget::Device => "device",
-            VolumeTarget::Filesystem => "filesystem",
+impl ReadOnlyRootSettings {
+    /// Overlay directory, if the root file system is read-only.
+    pub fn overlay(&self) -> Option<&str> {
+        self.overlay.as_deref().filter(|_| self.enabled)
+    }
+
+    fn to_dbus(&self, result: &mut HashMap<&'static str, Value>) {
+        result.insert("ReadOnlyRoot", Value::new(self.enabled));
+        result.insert(
+            "RootOverlay",
+            Value::new(self.overlay.clone().unwrap_or_default()),
+        );
+    }
+
+    fn from_dbus(hash: &HashMap<String, OwnedValue>) -> Result<Self, zbus::zvariant::Error> {
+        Ok(Self {
+            enabled: get_optional_property(hash, "ReadOnlyRoot")?.unwrap_or_default(),
+            overlay: get_optional_property::<String>(hash, "RootOverlay")?
+                .filter(|o| !o.is_empty()),
+        })
+    }
+
+    /// Returns the list of problems found in the settings.
+    ///
+    /// The overlay directory must be on a writable volume whose file system supports overlays,
+    /// and a transactional root file system is already read-only.
+    ///
+    /// * `volumes`: volumes of the proposal.
+    pub fn validate(&self, volumes: &[Volume]) -> Vec<String> {
+        if !self.enabled {
+            return vec![];
+        }
+        let Some(overlay) = self.overlay.as_deref() else {
+            return vec!["A read-only root file system requires an overlay directory".to_string()];
         };
+        let overlay = overlay.trim_end_matches('/');
+        if !overlay.starts_with('/') {
+            return vec![format!(
+                "The overlay directory must be an absolute path, not '{}'",
+                overlay
+            )];
+        }
 
-        Value::new(str)
+        let mut issues = vec![];
+        if volumes
+            .iter()
+            .any(|v| v.mount_path == "/" && v.transactional == Some(true))
+        {
+            issues.push(
+                "The root file system is transactional, so it is already read-only".to_string(),
+            );
+        }
+
+        // the volume with the longest mount path containing the overlay
+        let writable = volumes
+            .iter()
+            .filter(|v| v.mount_path != "/" && !v.is_swap())
+            .filter(|v| {
+                overlay
+                    .strip_prefix(&v.mount_path)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .max_by_key(|v| v.mount_path.len());
+        match writable {
+            None => issues.push(format!(
+                "The overlay directory {} must be on a writable volume, not on the read-only root",
+                overlay
+            )),
+            Some(volume) if !OVERLAY_FILESYSTEMS.contains(&volume.fs_type.as_str()) => {
+                issues.push(format!(
+                    "The overlay directory {} cannot be on {} because {} does not support overlays (supported: {})",
+                    overlay,
+                    volume.mount_path,
+                    volume.fs_type,
+                    OVERLAY_FILESYSTEMS.join(", ")
+                ))
+            }
+            Some(_) => {}
+        }
+        issues
     }
 }
 
-impl TryFrom<zbus::zvariant::Value<'_>> for VolumeTarget {
+/// Technology used to put a cache device in front of a backing device.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum CacheMethod {
+    /// Kernel block layer cache (bcache).
+    Bcache,
+    /// LVM cache (dm-cache).
+    Lvm,
+}
+
+impl CacheMethod {
+    pub fn as_dbus_string(&self) -> String {
+        match &self {
+            Self::Bcache => "bcache",
+            Self::Lvm => "lvm",
+        }
+        .to_string()
+    }
+
+    /// Kernel module implementing the cache.
+    pub fn kernel_module(&self) -> &'static str {
+        match &self {
+            Self::Bcache => "bcache",
+            Self::Lvm => "dm-cache",
+        }
+    }
+}
+
+impl TryFrom<zbus::zvariant::Value<'_>> for CacheMethod {
     type Error = zbus::zvariant::Error;
 
     fn try_from(value: zbus::zvariant::Value) -> Result<Self, zbus::zvariant::Error> {
         let svalue: String = value.try_into()?;
         match svalue.as_str() {
-            "default" => Ok(VolumeTarget::Default),
-            "new_partition" => Ok(VolumeTarget::NewPartition),
-            "new_vg" => Ok(VolumeTarget::NewVg),
-            "device" => Ok(VolumeTarget::Device),
-            "filesystem" => Ok(VolumeTarget::Filesystem),
-            _ => Err(zbus::zvariant::Error::Message(
-                format!("Wrong value for Target: {}", svalue).to_string(),
-            )),
+            "bcache" => Ok(Self::Bcache),
+            "lvm" => Ok(Self::Lvm),
+            _ => Err(zbus::zvariant::Error::Message(format!(
+                "Wrong value for CacheMethod: {}",
+                svalue
+            ))),
         }
     }
 }
 
-/// Represents volume outline aka requirements for volume
-#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+/// When the writes reach the backing device.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
-pub struct VolumeOutline {
-    required: bool,
-    fs_types: Vec<String>,
-    support_auto_size: bool,
-    adjust_by_ram: bool,
-    snapshots_configurable: bool,
-    snapshots_affect_sizes: bool,
-    size_relevant_volumes: Vec<String>,
+pub enum CacheMode {
+    /// The writes are done to both devices (safe if the cache device fails).
+    #[default]
+    Writethrough,
+    /// The writes are done to the cache device and copied to the backing one later.
+    Writeback,
+}
+
+impl CacheMode {
+    pub fn as_dbus_string(&self) -> String {
+        match &self {
+            Self::Writethrough => "writethrough",
+            Self::Writeback => "writeback",
+        }
+        .to_string()
+    }
+}
+
+impl TryFrom<zbus::zvariant::Value<'_>> for CacheMode {
+    type Error = zbus::zvariant::Error;
+
+    fn try_from(value: zbus::zvariant::Value) -> Result<Self, zbus::zvariant::Error> {
+        let svalue: String = value.try_into()?;
+        match svalue.as_str() {
+            "writethrough" => Ok(Self::Writethrough),
+            "writeback" => Ok(Self::Writeback),
+            _ => Err(zbus::zvariant::Error::Message(format!(
+                "Wrong value for CacheMode: {}",
+                svalue
+            ))),
+        }
+    }
+}
+
+/// Fast device (e.g., NVMe) caching a slower backing device (e.g., HDD).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheSettings {
+    /// Name of the cache device (e.g., "/dev/nvme0n1").
+    pub cache_device: String,
+    /// Name of the backing device (e.g., "/dev/sda").
+    pub backing_device: String,
+    pub method: CacheMethod,
+    #[serde(default)]
+    pub mode: CacheMode,
+}
+
+impl TryFrom<zbus::zvariant::Value<'_>> for CacheSettings {
+    type Error = zbus::zvariant::Error;
+
+    fn try_from(value: zbus::zvariant::Value) -> Result<Self, zbus::zvariant::Error> {
+        let mvalue: HashMap<String, OwnedValue> = value.try_into()?;
+        Ok(CacheSettings {
+            cache_device: get_property(&mvalue, "CacheDevice")?,
+            backing_device: get_property(&mvalue, "BackingDevice")?,
+            method: get_property(&mvalue, "Method")?,
+            mode: get_optional_property(&mvalue, "Mode")?.unwrap_or_default(),
+        })
+    }
+}
+
+impl<'a> From<CacheSettings> for zbus::zvariant::Value<'a> {
+    fn from(val: CacheSettings) -> Self {
+        let result: HashMap<&str, Value> = HashMap::from([
+            ("CacheDevice", Value::new(val.cache_device)),
+            ("BackingDevice", Value::new(val.backing_device)),
+            ("Method", Value::new(val.method.as_dbus_string())),
+            ("Mode", Value::new(val.mode.as_dbus_string())),
+        ]);
+
+        Value::new(result)
+    }
+}
+
+/// Name prefixes of the devices considered fast enough to act as a cache.
+const FAST_DEVICE_PREFIXES: [&str; 2] = ["/dev/nvme", "/dev/pmem"];
+
+impl CacheSettings {
+    /// Returns the errors and the warnings found in the cache settings.
+    ///
+    /// Both devices must exist and be unused, and the cache device must be smaller than the
+    /// backing one. Checking that the cache device is faster is a best effort based on its name,
+    /// so it only produces warnings.
+    ///
+    /// * `caches`: all the requested caches, to detect devices used twice.
+    /// * `devices`: probed devices.
+    pub fn validate(caches: &[CacheSettings], devices: &[Device]) -> (Vec<String>, Vec<String>) {
+        let mut errors = vec![];
+        let mut warnings = vec![];
+        let mut used: Vec<&str> = vec![];
+
+        for cache in caches {
+            if cache.cache_device == cache.backing_device {
+                errors.push(format!(
+                    "{} cannot be the cache of itself",
+                    cache.cache_device
+                ));
+                continue;
+            }
+
+            let mut sizes = vec![];
+            for name in [&cache.cache_device, &cache.backing_device] {
+                if used.contains(&name.as_str()) {
+                    errors.push(format!("{} is used by more than one cache", name));
+                }
+                used.push(name);
+
+                let Some(device) = devices.iter().find(|d| &d.device_info.name == name) else {
+                    errors.push(format!("Unknown device {}", name));
+                    continue;
+                };
+                if device.detect_contents(devices).is_some_and(|c| c.in_use()) {
+                    errors.push(format!("{} is in use", name));
+                }
+                if let Some(block_device) = &device.block_device {
+                    sizes.push(block_device.size.bytes());
+                }
+            }
+
+            if let [cache_size, backing_size] = sizes[..] {
+                if cache_size >= backing_size {
+                    errors.push(format!(
+                        "The cache device {} must be smaller than the backing device {}",
+                        cache.cache_device, cache.backing_device
+                    ));
+                }
+            }
+
+            let is_fast = |name: &str| FAST_DEVICE_PREFIXES.iter().any(|p| name.starts_with(p));
+            if !is_fast(&cache.cache_device) && is_fast(&cache.backing_device) {
+                warnings.push(format!(
+                    "The cache device {} seems slower than the backing device {}",
+                    cache.cache_device, cache.backing_device
+                ));
+            }
+        }
+        (errors, warnings)
+    }
+}
+
+/// Position of a partition in a disk.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(untagged)]
+pub enum PartitionRegion {
+    /// First and last sectors (both included).
+    Sectors { start: u64, end: u64 },
+    /// Offset from the beginning of the disk and size, in bytes.
+    Bytes { offset: u64, size: u64 },
+}
+
+impl PartitionRegion {
+    /// Returns the first and last sectors of the region.
+    ///
+    /// * `sector_size`: logical sector size of the disk in bytes.
+    pub fn sectors(&self, sector_size: u64) -> Result<(u64, u64), String> {
+        if sector_size == 0 {
+            return Err("The sector size of the disk is unknown".to_string());
+        }
+        let (start, end) = match *self {
+            Self::Sectors { start, end } => (start, end),
+            Self::Bytes { offset, size } => {
+                if offset % sector_size != 0 || size % sector_size != 0 {
+                    return Err(format!(
+                        "The offset and the size must be multiples of the sector size ({} bytes)",
+                        sector_size
+                    ));
+                }
+                if size == 0 {
+                    return Err("The partition size cannot be zero".to_string());
+                }
+                let start = offset / sector_size;
+                let Some(end) = start.checked_add(size / sector_size - 1) else {
+                    return Err("The partition ends beyond the last possible sector".to_string());
+                };
+                (start, end)
+            }
+        };
+        if end < start {
+            return Err(format!(
+                "The end sector ({}) is before the start sector ({})",
+                end, start
+            ));
+        }
+        if end
+            .checked_add(1)
+            .and_then(|e| e.checked_mul(sector_size))
+            .is_none()
+        {
+            return Err(format!("The end sector ({}) is out of range", end));
+        }
+        Ok((start, end))
+    }
+}
+
+/// Partition created at an explicit position, bypassing the automatic placement of the proposal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplicitPartition {
+    /// Name of the disk (e.g., "/dev/sda").
+    pub device: String,
+    #[serde(flatten)]
+    pub region: PartitionRegion,
+    /// File system to create in the partition, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fs_type: Option<String>,
+    /// Mount point of the file system, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mount_path: Option<String>,
+}
+
+impl TryFrom<zbus::zvariant::Value<'_>> for ExplicitPartition {
+    type Error = zbus::zvariant::Error;
+
+    fn try_from(value: zbus::zvariant::Value) -> Result<Self, zbus::zvariant::Error> {
+        let mvalue: HashMap<String, OwnedValue> = value.try_into()?;
+        Ok(ExplicitPartition {
+            device: get_property(&mvalue, "Device")?,
+            region: PartitionRegion::Sectors {
+                start: get_property(&mvalue, "Start")?,
+                end: get_property(&mvalue, "End")?,
+            },
+            fs_type: get_optional_property(&mvalue, "FsType")?,
+            mount_path: get_optional_property(&mvalue, "MountPath")?,
+        })
+    }
+}
+
+impl<'a> From<ExplicitPartition> for zbus::zvariant::Value<'a> {
+    fn from(val: ExplicitPartition) -> Self {
+        let mut result: HashMap<&str, Value> = HashMap::from([("Device", Value::new(val.device))]);
+        match val.region {
+            PartitionRegion::Sectors { start, end } => {
+                result.insert("Start", Value::new(start));
+                result.insert("End", Value::new(end));
+            }
+            PartitionRegion::Bytes { offset, size } => {
+                result.insert("Offset", Value::new(offset));
+                result.insert("Size", Value::new(size));
+            }
+        }
+        if let Some(fs_type) = val.fs_type {
+            result.insert("FsType", Value::new(fs_type));
+        }
+        if let Some(mount_path) = val.mount_path {
+            result.insert("MountPath", Value::new(mount_path));
+        }
+
+        Value::new(result)
+    }
+}
+
+impl ExplicitPartition {
+    /// Checks the requested partitions and converts their regions to sectors.
+    ///
+    /// Each region must be inside the free space of a partition table and must not overlap with
+    /// other requested partitions. Misaligned regions are allowed, but they produce a warning.
+    ///
+    /// Returns the errors and the warnings.
+    ///
+    /// * `partitions`: all the requested partitions.
+    /// * `devices`: probed devices.
+    /// * `alignment`: partition alignment used to check the regions.
+    pub fn resolve(
+        partitions: &mut [ExplicitPartition],
+        devices: &[Device],
+        alignment: &PartitionAlignment,
+    ) -> (Vec<String>, Vec<String>) {
+        let mut errors = vec![];
+        let mut warnings = vec![];
+        let mut planned: Vec<(&str, u64, u64)> = vec![];
+        let grain = alignment.grain();
+
+        for partition in partitions.iter_mut() {
+            let name = partition.device.as_str();
+            let Some(device) = devices.iter().find(|d| d.device_info.name == name) else {
+                errors.push(format!("Unknown disk {}", name));
+                continue;
+            };
+            let (Some(block_device), Some(ptable)) =
+                (&device.block_device, &device.partition_table)
+            else {
+                errors.push(format!("{} has no partition table", name));
+                continue;
+            };
+            let sector_size = block_device.logical_sector_size.unwrap_or(SECTOR_SIZE);
+            let (start, end) = match partition.region.sectors(sector_size) {
+                Ok(sectors) => sectors,
+                Err(error) => {
+                    errors.push(format!(
+                        "Wrong region for a partition in {}: {}",
+                        name, error
+                    ));
+                    continue;
+                }
+            };
+
+            let is_free = ptable.unused_slots.iter().any(|slot| {
+                let slot_end = slot.start.saturating_add(slot.size.bytes() / sector_size);
+                slot.start <= start && end < slot_end
+            });
+            if !is_free {
+                errors.push(format!(
+                    "The sectors {}-{} of {} are not free",
+                    start, end, name
+                ));
+                continue;
+            }
+            if planned
+                .iter()
+                .any(|(other, s, e)| *other == name && start <= *e && *s <= end)
+            {
+                errors.push(format!(
+                    "The sectors {}-{} of {} overlap with another partition",
+                    start, end, name
+                ));
+                continue;
+            }
+
+            // sectors() ensures that the end of the region (in bytes) does not overflow
+            if grain != 0
+                && ((start * sector_size) % grain != 0 || ((end + 1) * sector_size) % grain != 0)
+            {
+                warnings.push(format!(
+                    "The partition at sectors {}-{} of {} is not aligned to {} bytes",
+                    start, end, name, grain
+                ));
+            }
+
+            planned.push((name, start, end));
+            partition.region = PartitionRegion::Sectors { start, end };
+        }
+        (errors, warnings)
+    }
+}
+
+/// Maximum size of a disk using an MS-DOS partition table (2 TiB with 512-byte sectors).
+pub const MSDOS_MAX_DISK_SIZE: u64 = 2 * 1024 * 1024 * 1024 * 1024;
+
+/// Maximum number of primary partitions in an MS-DOS partition table.
+pub const MSDOS_MAX_PRIMARY_PARTITIONS: usize = 4;
+
+/// Maximum number of logical partitions in an MS-DOS partition table. They are numbered from 5
+/// to 256, which is the limit of the Linux kernel.
+pub const MSDOS_MAX_LOGICAL_PARTITIONS: usize = 252;
+
+/// Type of partition table.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PartitionTableType {
+    Gpt,
+    /// MS-DOS (MBR) partition table.
+    Msdos,
+}
+
+impl PartitionTableType {
+    /// Partition table type to use by default.
+    ///
+    /// GPT is used on UEFI systems and for disks too big for MS-DOS, and MS-DOS otherwise.
+    ///
+    /// * `efi`: whether the system boots using UEFI.
+    /// * `disk_size`: size of the disk in bytes.
+    pub fn default_for(efi: bool, disk_size: u64) -> Self {
+        if efi || disk_size > MSDOS_MAX_DISK_SIZE {
+            Self::Gpt
+        } else {
+            Self::Msdos
+        }
+    }
+
+    pub fn as_dbus_string(&self) -> String {
+        match &self {
+            Self::Gpt => "gpt",
+            Self::Msdos => "msdos",
+        }
+        .to_string()
+    }
+}
+
+/// Partition table to create in a disk.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PartitionTableSettings {
+    /// Name of the disk (e.g., "/dev/sda").
+    pub device: String,
+    /// Partition table type. If it is not set, the type is chosen depending on the firmware.
+    #[serde(rename = "type")]
+    pub ptable_type: Option<PartitionTableType>,
+}
+
+/// Represents a proposal patch -> change of proposal configuration that can be partial
+#[derive(Debug, Clone, Default, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalSettingsPatch {
+    pub target: Option<ProposalTarget>,
+    pub target_device: Option<String>,
+    #[serde(rename = "targetPVDevices")]
+    pub target_pv_devices: Option<Vec<String>>,
+    pub configure_boot: Option<bool>,
+    pub boot_device: Option<String>,
+    pub encryption_password: Option<String>,
+    pub encryption_method: Option<String>,
+    #[serde(rename = "encryptionPBKDFunction")]
+    pub encryption_pbkd_function: Option<String>,
+    /// Key file to unlock the encrypted devices instead of a passphrase.
+    pub encryption_key_file: Option<EncryptionKeyFile>,
+    pub space_policy: Option<String>,
+    pub space_actions: Option<Vec<SpaceActionSettings>>,
+    pub volumes: Option<Vec<Volume>>,
+    pub alignment: Option<PartitionAlignment>,
+    /// File system type for the root volume. It is applied to the volumes.
+    pub root_fs_type: Option<String>,
+    /// File system type for the /home volume. It is applied to the volumes.
+    pub home_fs_type: Option<String>,
+    /// Partition table type for each disk.
+    pub partition_tables: Option<Vec<PartitionTableSettings>>,
+    /// How to get the EFI System Partition.
+    pub esp: Option<EspSettings>,
+    /// How the file systems are referenced in the fstab, unless a volume sets its own.
+    pub mount_by: Option<MountBy>,
+    /// Whether the root file system is read-only, with a writable overlay.
+    pub read_only_root: Option<ReadOnlyRootSettings>,
+    /// Cache devices in front of backing devices.
+    pub caches: Option<Vec<CacheSettings>>,
+    /// Whether removable devices (e.g., USB sticks) can be automatically chosen as target. An
+    /// explicit target device is always used.
+    pub include_removable: Option<bool>,
+    /// Partitions to create at explicit positions.
+    pub partitions: Option<Vec<ExplicitPartition>>,
+    /// Disks to use as target device, in order of preference. The first one which can hold the
+    /// proposal is used, unless the target device is set.
+    pub preferred_devices: Option<Vec<String>>,
+}
+
+impl ProposalSettingsPatch {
+    /// Returns the list of problems found in the alignment settings.
+    ///
+    /// An explicit alignment must be a positive multiple of the sector size.
+    pub fn validate_alignment(&self) -> Vec<String> {
+        match self.alignment {
+            Some(PartitionAlignment::Explicit(grain)) if grain == 0 || grain % SECTOR_SIZE != 0 => {
+                vec![format!(
+                    "The partition alignment must be a multiple of {} bytes",
+                    SECTOR_SIZE
+                )]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Sets the requested file system types for the root and /home volumes.
+    ///
+    /// Returns the list of problems found (e.g., a file system type not supported for the
+    /// mount point). The volumes from the patch are used, if any.
+    ///
+    /// * `current`: volumes to use if the patch does not include them.
+    pub fn apply_fs_types(&mut self, current: Vec<Volume>) -> Vec<String> {
+        let requested: Vec<_> = [("/", &self.root_fs_type), ("/home", &self.home_fs_type)]
+            .into_iter()
+            .filter_map(|(path, fs_type)| Some((path, fs_type.clone()?)))
+            .collect();
+        if requested.is_empty() {
+            return vec![];
+        }
+
+        let volumes = self.volumes.get_or_insert(current);
+        let mut issues = vec![];
+        for (path, fs_type) in requested {
+            match volumes.iter_mut().find(|v| v.mount_path == path) {
+                Some(volume) => {
+                    if let Err(issue) = volume.set_fs_type(&fs_type) {
+                        issues.push(issue);
+                    }
+                }
+                None => issues.push(format!("There is no {} volume in the proposal", path)),
+            }
+        }
+        issues
+    }
+
+    /// Rounds up the volume sizes to the partition alignment.
+    ///
+    /// Returns a warning for each rounded size.
+    ///
+    /// * `current`: alignment already set, used if the patch does not set it.
+    pub fn align_volumes(&mut self, current: &PartitionAlignment) -> Vec<String> {
+        let grain = self.alignment.as_ref().unwrap_or(current).grain();
+        let Some(volumes) = self.volumes.as_mut() else {
+            return vec![];
+        };
+        volumes
+            .iter_mut()
+            .flat_map(|v| v.align_sizes(grain))
+            .collect()
+    }
+
+    /// Returns the list of problems found in the quotas of the volumes.
+    pub fn validate_quotas(&self) -> Vec<String> {
+        self.volumes
+            .iter()
+            .flatten()
+            .flat_map(Volume::validate_quota)
+            .collect()
+    }
+
+    /// Returns the list of problems found in the size ranges of the volumes.
+    ///
+    /// Besides checking each range, the minimum sizes of the volumes created in the target disk
+    /// must fit in it.
+    ///
+    /// * `disk`: name and size (in bytes) of the target disk, if known.
+    pub fn validate_size_ranges(&self, disk: Option<(&str, u64)>) -> Vec<String> {
+        let volumes = self.volumes.as_deref().unwrap_or_default();
+        let mut issues: Vec<String> = volumes
+            .iter()
+            .flat_map(Volume::validate_size_range)
+            .collect();
+        if let Some((name, size)) = disk {
+            let needed: u64 = volumes.iter().map(|v| v.min_size_in(name)).sum();
+            if needed > size {
+                issues.push(format!(
+                    "The minimum sizes of the volumes ({} bytes) do not fit in {} ({} bytes)",
+                    needed, name, size
+                ));
+            }
+        }
+        issues
+    }
+
+    /// Returns the list of problems found in the encryption settings.
+    ///
+    /// Using the TPM requires a TPM 2.0 device and a fallback passphrase.
+    ///
+    /// * `tpm2`: whether a TPM 2.0 device is available.
+    /// * `current_password`: encryption password already set, used if the patch does not set it.
+    pub fn validate_encryption(&self, tpm2: bool, current_password: &str) -> Vec<String> {
+        let mut issues = vec![];
+        if self.encryption_method.as_deref() != Some(TPM_FDE_METHOD) {
+            return issues;
+        }
+
+        if !tpm2 {
+            issues.push("TPM-based encryption requires a TPM 2.0 device".to_string());
+        }
+
+        let password = self
+            .encryption_password
+            .as_deref()
+            .unwrap_or(current_password);
+        if password.is_empty() {
+            issues.push("TPM-based encryption requires a fallback passphrase".to_string());
+        }
+        issues
+    }
+
+    /// Chooses the partition table type of each disk and checks the combinations.
+    ///
+    /// Disks without an explicit type get the default for the firmware. It returns the errors
+    /// (impossible combinations) and the warnings. When there are more partitions than primary
+    /// ones in an MS-DOS partition table, the proposal creates an extended partition holding the
+    /// rest as logical partitions.
+    ///
+    /// * `efi`: whether the system boots using UEFI.
+    /// * `disks`: sizes of the known disks, indexed by name.
+    /// * `current`: settings already set, used for the values the patch does not set.
+    pub fn resolve_partition_tables(
+        &mut self,
+        efi: bool,
+        disks: &HashMap<String, u64>,
+        current: Option<&ProposalSettings>,
+    ) -> (Vec<String>, Vec<String>) {
+        let (mut errors, mut warnings) = (vec![], vec![]);
+        let Some(mut tables) = self.partition_tables.take() else {
+            return (errors, warnings);
+        };
+
+        let target_device = self
+            .target_device
+            .clone()
+            .or_else(|| current.and_then(|c| c.target_device.clone()));
+        let configure_boot = self
+            .configure_boot
+            .or(current.map(|c| c.configure_boot))
+            .unwrap_or_default();
+        let boot_device = self
+            .boot_device
+            .clone()
+            .or_else(|| current.map(|c| c.boot_device.clone()))
+            .filter(|d| !d.is_empty())
+            .or_else(|| current.map(|c| c.default_boot_device.clone()))
+            .or(target_device.clone());
+        let volumes = self
+            .volumes
+            .as_deref()
+            .or(current.map(|c| c.volumes.as_slice()))
+            .unwrap_or_default();
+
+        for table in tables.iter_mut() {
+            let device = &table.device;
+            let Some(size) = disks.get(device) else {
+                errors.push(format!("Unknown disk {}", device));
+                continue;
+            };
+            let ptable_type = *table
+                .ptable_type
+                .get_or_insert(PartitionTableType::default_for(efi, *size));
+            let is_boot = configure_boot && boot_device.as_ref() == Some(device);
+
+            match ptable_type {
+                PartitionTableType::Msdos => {
+                    if *size > MSDOS_MAX_DISK_SIZE {
+                        errors.push(format!(
+                            "An MS-DOS partition table cannot be used for {}, which is bigger than 2 TiB",
+                            device
+                        ));
+                    }
+                    let mut partitions = volumes
+                        .iter()
+                        .filter(|v| {
+                            matches!(v.target, VolumeTarget::Default | VolumeTarget::NewPartition)
+                                && v.target_device.as_ref().or(target_device.as_ref())
+                                    == Some(device)
+                        })
+                        .count();
+                    if is_boot && efi {
+                        partitions += 1;
+                    }
+                    if partitions > MSDOS_MAX_PRIMARY_PARTITIONS {
+                        // the extended partition takes one of the primary slots
+                        let logical = partitions - (MSDOS_MAX_PRIMARY_PARTITIONS - 1);
+                        if logical > MSDOS_MAX_LOGICAL_PARTITIONS {
+                            errors.push(format!(
+                                "{} partitions are planned for {}, but an MS-DOS partition table supports at most {} partitions",
+                                partitions,
+                                device,
+                                MSDOS_MAX_PRIMARY_PARTITIONS - 1 + MSDOS_MAX_LOGICAL_PARTITIONS
+                            ));
+                        } else {
+                            warnings.push(format!(
+                                "{} partitions are planned for {}, so {} of them will be logical partitions inside an extended partition",
+                                partitions, device, logical
+                            ));
+                        }
+                    }
+                }
+                PartitionTableType::Gpt => {
+                    if is_boot && !efi {
+                        warnings.push(format!(
+                            "Booting from the GPT partition table of {} without UEFI requires a BIOS boot partition, and some legacy firmware cannot do it",
+                            device
+                        ));
+                    }
+                }
+            }
+        }
+
+        self.partition_tables = Some(tables);
+        (errors, warnings)
+    }
+
+    /// Returns the list of problems found in the encryption of the volumes.
+    ///
+    /// A random key can only be used for swap, as the content is lost on each boot, and it cannot
+    /// be combined with a passphrase. The passphrase of a volume cannot be empty.
+    pub fn validate_volume_encryption(&self) -> Vec<String> {
+        let Some(volumes) = &self.volumes else {
+            return vec![];
+        };
+        let mut issues = vec![];
+        for volume in volumes {
+            let random_key = volume.encryption == Some(VolumeEncryption::RandomKey);
+            if random_key && !volume.is_swap() {
+                issues.push(format!(
+                    "A random encryption key can only be used for swap, not for {}",
+                    volume.mount_path
+                ));
+            }
+            match &volume.encryption_password {
+                Some(_) if random_key => issues.push(format!(
+                    "A random encryption key and a passphrase cannot be used together for {}",
+                    volume.mount_path
+                )),
+                Some(passphrase) if passphrase.is_empty() => issues.push(format!(
+                    "The encryption passphrase for {} cannot be empty",
+                    volume.mount_path
+                )),
+                _ => {}
+            }
+        }
+        issues
+    }
+
+    /// Checks whether the default encryption passphrase is used by some volume.
+    ///
+    /// * `current`: settings already set, used for the values the patch does not set.
+    pub fn validate_default_passphrase(&self, current: Option<&ProposalSettings>) -> Vec<String> {
+        let password = self
+            .encryption_password
+            .as_deref()
+            .or(current.map(|c| c.encryption_password.as_str()))
+            .unwrap_or_default();
+        let volumes = self
+            .volumes
+            .as_deref()
+            .or(current.map(|c| c.volumes.as_slice()))
+            .unwrap_or_default();
+        if password.is_empty() || volumes.is_empty() {
+            return vec![];
+        }
+
+        if volumes.iter().any(Volume::uses_default_passphrase) {
+            vec![]
+        } else {
+            vec!["The default encryption passphrase is not used by any volume".to_string()]
+        }
+    }
+
+    /// Whether the patch sets how some file system is referenced in the fstab.
+    pub fn sets_mount_by(&self) -> bool {
+        self.mount_by.is_some() || self.volumes.iter().flatten().any(|v| v.mount_by.is_some())
+    }
+
+    /// Returns the list of problems found in the fstab references of the volumes.
+    ///
+    /// Mounting by label requires a unique label, and the hardware paths and identifiers must
+    /// be known for the target disk. Paths are not available for logical volumes.
+    ///
+    /// * `current`: settings already set, used for the values the patch does not set.
+    /// * `devices`: system devices.
+    pub fn validate_mount_by(
+        &self,
+        current: Option<&ProposalSettings>,
+        devices: &[Device],
+    ) -> Vec<String> {
+        let default = self.mount_by.or(current.and_then(|c| c.mount_by));
+        let volumes = self
+            .volumes
+            .as_deref()
+            .or(current.map(|c| c.volumes.as_slice()))
+            .unwrap_or_default();
+        let target = self.target.as_ref().or(current.map(|c| &c.target));
+        let target_device = self
+            .target_device
+            .as_deref()
+            .or(current.and_then(|c| c.target_device.as_deref()));
+        let find_device = |name: &str| devices.iter().find(|d| d.device_info.name == name);
+
+        let mut issues = vec![];
+        let mut labels = vec![];
+        for volume in volumes {
+            let Some(mount_by) = volume.mount_by.or(default) else {
+                continue;
+            };
+            let device = volume.target_device().or(target_device);
+            match mount_by {
+                MountBy::Label => {
+                    let label = match &volume.label {
+                        Some(label) => Some(label.as_str()),
+                        None if !volume.formats() => device
+                            .and_then(find_device)
+                            .and_then(|d| d.filesystem.as_ref())
+                            .map(|fs| fs.label.as_str()),
+                        None => None,
+                    }
+                    .unwrap_or_default();
+                    if label.is_empty() {
+                        issues.push(format!(
+                            "{} cannot be mounted by label because it has no label",
+                            volume.mount_path
+                        ));
+                    } else if labels.contains(&label) {
+                        issues.push(format!(
+                            "The label '{}' of {} is not unique",
+                            label, volume.mount_path
+                        ));
+                    } else {
+                        labels.push(label);
+                    }
+                }
+                MountBy::Path if !matches!(target, None | Some(ProposalTarget::Disk)) => {
+                    issues.push(format!(
+                        "{} cannot be mounted by path because it is a logical volume",
+                        volume.mount_path
+                    ));
+                }
+                MountBy::Path | MountBy::Id => {
+                    let Some(block_device) = device
+                        .and_then(find_device)
+                        .and_then(|d| d.block_device.as_ref())
+                    else {
+                        continue;
+                    };
+                    let (known, kind) = if mount_by == MountBy::Path {
+                        (&block_device.udev_paths, "path")
+                    } else {
+                        (&block_device.udev_ids, "id")
+                    };
+                    if known.is_empty() {
+                        issues.push(format!(
+                            "{} cannot be mounted by {} because the device {} has none",
+                            volume.mount_path,
+                            kind,
+                            device.unwrap_or_default()
+                        ));
+                    }
+                }
+                MountBy::Uuid | MountBy::Device => {}
+            }
+        }
+        issues
+    }
+}
+
+impl<'a> From<ProposalSettingsPatch> for HashMap<&'static str, Value<'a>> {
+    fn from(val: ProposalSettingsPatch) -> Self {
+        let mut result = HashMap::new();
+        if let Some(target) = val.target {
+            result.insert("Target", Value::new(target.as_dbus_string()));
+        }
+        if let Some(dev) = val.target_device {
+            result.insert("TargetDevice", Value::new(dev));
+        }
+        if let Some(devs) = val.target_pv_devices {
+            result.insert("TargetPVDevices", Value::new(devs));
+        }
+        if let Some(value) = val.configure_boot {
+            result.insert("ConfigureBoot", Value::new(value));
+        }
+        if let Some(value) = val.boot_device {
+            result.insert("BootDevice", Value::new(value));
+        }
+        if let Some(value) = val.encryption_password {
+            result.insert("EncryptionPassword", Value::new(value));
+        }
+        if let Some(value) = val.encryption_method {
+            result.insert("EncryptionMethod", Value::new(value));
+        }
+        if let Some(value) = val.encryption_pbkd_function {
+            result.insert("EncryptionPBKDFunction", Value::new(value));
+        }
+        if let Some(value) = val.encryption_key_file {
+            value.to_dbus(&mut result);
+        }
+        if let Some(value) = val.space_policy {
+            result.insert("SpacePolicy", Value::new(value));
+        }
+        if let Some(value) = val.space_actions {
+            let list: Vec<Value> = value.into_iter().map(|a| a.into()).collect();
+            result.insert("SpaceActions", Value::new(list));
+        }
+        if let Some(value) = val.volumes {
+            // the passphrases are sent apart, so they are not included in the volume hashes
+            let passphrases: HashMap<String, String> = value
+                .iter()
+                .filter_map(|v| {
+                    let Passphrase(passphrase) = v.encryption_password.as_ref()?;
+                    Some((v.mount_path.clone(), passphrase.clone()))
+                })
+                .collect();
+            if !passphrases.is_empty() {
+                result.insert("VolumeEncryptionPasswords", Value::new(passphrases));
+            }
+            let list: Vec<Value> = value.into_iter().map(|a| a.into()).collect();
+            result.insert("Volumes", Value::new(list));
+        }
+        if let Some(value) = val.partition_tables {
+            let tables: HashMap<String, String> = value
+                .into_iter()
+                .filter_map(|t| Some((t.device, t.ptable_type?.as_dbus_string())))
+                .collect();
+            result.insert("PartitionTables", Value::new(tables));
+        }
+        if let Some(value) = val.esp {
+            value.to_dbus(&mut result);
+        }
+        if let Some(value) = val.mount_by {
+            result.insert("MountBy", Value::new(value.as_dbus_string()));
+        }
+        if let Some(value) = val.read_only_root {
+            value.to_dbus(&mut result);
+        }
+        if let Some(value) = val.caches {
+            let list: Vec<Value> = value.into_iter().map(|c| c.into()).collect();
+            result.insert("Caches", Value::new(list));
+        }
+        if let Some(value) = val.include_removable {
+            result.insert("IncludeRemovable", Value::new(value));
+        }
+        if let Some(value) = val.partitions {
+            let list: Vec<Value> = value.into_iter().map(|p| p.into()).collect();
+            result.insert("Partitions", Value::new(list));
+        }
+        if let Some(value) = val.alignment {
+            result.insert("Alignment", Value::new(value.as_dbus_string()));
+            if let PartitionAlignment::Explicit(grain) = value {
+                result.insert("AlignmentGrain", Value::new(grain));
+            }
+        }
+        result
+    }
+}
+
+/// Represents a proposal configuration
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalSettings {
+    pub target: ProposalTarget,
+    pub target_device: Option<String>,
+    #[serde(rename = "targetPVDevices")]
+    pub target_pv_devices: Option<Vec<String>>,
+    pub configure_boot: bool,
+    pub boot_device: String,
+    pub default_boot_device: String,
+    /// Default encryption passphrase. It is write-only, so it is never sent to the clients.
+    #[serde(skip_serializing)]
+    pub encryption_password: String,
+    /// Whether a default encryption passphrase is set.
+    pub encryption_password_set: bool,
+    pub encryption_method: String,
+    #[serde(rename = "encryptionPBKDFunction")]
+    pub encryption_pbkd_function: String,
+    /// Key file to unlock the encrypted devices, if any. The key material is not included.
+    pub encryption_key_file: Option<EncryptionKeyFile>,
+    pub space_policy: String,
+    pub space_actions: Vec<SpaceActionSettings>,
+    pub volumes: Vec<Volume>,
+    pub alignment: PartitionAlignment,
+    pub esp: EspSettings,
+    /// How the file systems are referenced in the fstab if it is not set by the volume.
+    pub mount_by: Option<MountBy>,
+    pub read_only_root: ReadOnlyRootSettings,
+    pub caches: Vec<CacheSettings>,
+    /// Whether removable devices (e.g., USB sticks) can be automatically chosen as target. They
+    /// are excluded by default.
+    pub include_removable: bool,
+    /// Removable devices which were not considered when choosing the target device.
+    pub excluded_devices: Vec<String>,
+    /// Partitions created at explicit positions.
+    pub partitions: Vec<ExplicitPartition>,
+    /// Disks tried as target device, in order of preference. They are not kept by the storage
+    /// service, see [DiskSelection].
+    pub preferred_devices: Vec<String>,
+}
+
+impl ProposalSettings {
+    /// Returns how the file system mounted at the given path is referenced in the fstab.
+    ///
+    /// * `mount_path`: mount path of the volume (e.g., "/home").
+    pub fn mount_by_for(&self, mount_path: &str) -> Option<MountBy> {
+        self.volumes
+            .iter()
+            .find(|v| v.mount_path == mount_path)
+            .and_then(|v| v.mount_by)
+            .or(self.mount_by)
+    }
+}
+
+/// Device which is not considered as a target for the installation.
+#[derive(Clone, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcludedDevice {
+    pub sid: DeviceSid,
+    /// Device name (e.g., "/dev/sdb").
+    pub name: String,
+    /// Why the device is excluded.
+    pub reason: String,
+}
+
+impl ExcludedDevice {
+    /// Returns the removable devices which were not considered when choosing the target device.
+    ///
+    /// * `devices`: probed devices.
+    /// * `names`: names of the devices excluded by the proposal.
+    pub fn removable(devices: &[Device], names: &[String]) -> Vec<Self> {
+        devices
+            .iter()
+            .filter(|d| names.contains(&d.device_info.name))
+            .filter(|d| d.drive.as_ref().is_some_and(|d| d.is_removable()))
+            .map(|d| Self {
+                sid: d.device_info.sid.clone(),
+                name: d.device_info.name.clone(),
+                reason: "it is a removable device".to_string(),
+            })
+            .collect()
+    }
+}
+
+/// Minimum recommended size of a separate `/boot` file system.
+pub const BOOT_MIN_SIZE: u64 = 512 * 1024 * 1024;
+
+/// Problem found in the storage proposal.
+#[derive(Clone, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalIssue {
+    /// Stable identifier of the problem (e.g., "no_swap").
+    pub code: String,
+    /// Human-readable description.
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+impl ProposalIssue {
+    pub fn new(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: code.to_string(),
+            message: message.into(),
+            details: None,
+        }
+    }
+}
+
+/// Problems of the storage proposal, split by severity.
+#[derive(Clone, Debug, Default, Serialize, utoipa::ToSchema)]
+pub struct ProposalIssues {
+    /// Problems which prevent the installation.
+    pub errors: Vec<ProposalIssue>,
+    /// Problems which do not prevent the installation, but might lead to an unexpected result.
+    pub warnings: Vec<ProposalIssue>,
+}
+
+impl ProposalIssues {
+    /// Checks the planned volumes for layouts which work but are not recommended.
+    ///
+    /// * `volumes`: volumes of the proposal settings.
+    pub fn layout_warnings(volumes: &[Volume]) -> Vec<ProposalIssue> {
+        let mut warnings = vec![];
+        if !volumes.iter().any(|v| v.mount_path == "swap") {
+            warnings.push(ProposalIssue::new(
+                "no_swap",
+                "No swap volume is planned, so hibernation is not possible",
+            ));
+        }
+
+        let boot = volumes
+            .iter()
+            .find(|v| v.mount_path == "/boot" && !v.auto_size);
+        if let Some(max_size) = boot.and_then(|v| v.max_size.as_ref()) {
+            if max_size.bytes() < BOOT_MIN_SIZE {
+                let mut issue = ProposalIssue::new(
+                    "small_boot",
+                    "The /boot file system might be too small to keep several kernels",
+                );
+                issue.details = Some(format!(
+                    "At most {} bytes are planned, but {} bytes are recommended",
+                    max_size.bytes(),
+                    BOOT_MIN_SIZE
+                ));
+                warnings.push(issue);
+            }
+        }
+        warnings
+    }
+
+    /// Reports the drives whose SMART status reports that they are failing.
+    ///
+    /// Installing on a failing drive is an error. The rest of failing drives are only reported as
+    /// warnings.
+    ///
+    /// * `devices`: probed devices, including their SMART health.
+    /// * `used`: names of the devices used by the proposal.
+    pub fn add_smart_issues(&mut self, devices: &[Device], used: &[String]) {
+        let failing = devices
+            .iter()
+            .filter(|d| d.drive.as_ref().is_some_and(|d| d.smart.is_failing()));
+        for device in failing {
+            let name = &device.device_info.name;
+            let issue = ProposalIssue::new(
+                "smart_failing",
+                format!("The SMART status of {} reports that it is failing", name),
+            );
+            if used.contains(name) {
+                self.errors.push(issue);
+            } else {
+                self.warnings.push(issue);
+            }
+        }
+    }
+}
+
+impl TryFrom<HashMap<String, OwnedValue>> for ProposalSettings {
+    type Error = zbus::zvariant::Error;
+
+    fn try_from(hash: HashMap<String, OwnedValue>) -> Result<Self, zbus::zvariant::Error> {
+        let mut res = ProposalSettings {
+            target: get_property(&hash, "Target")?,
+            target_device: get_optional_property(&hash, "TargetDevice")?,
+            target_pv_devices: get_optional_property(&hash, "TargetPVDevices")?,
+            configure_boot: get_property(&hash, "ConfigureBoot")?,
+            boot_device: get_property(&hash, "BootDevice")?,
+            default_boot_device: get_property(&hash, "DefaultBootDevice")?,
+            encryption_password: get_property(&hash, "EncryptionPassword")?,
+            encryption_password_set: false,
+            encryption_method: get_property(&hash, "EncryptionMethod")?,
+            encryption_pbkd_function: get_property(&hash, "EncryptionPBKDFunction")?,
+            encryption_key_file: EncryptionKeyFile::from_dbus(&hash)?,
+            space_policy: get_property(&hash, "SpacePolicy")?,
+            space_actions: get_property(&hash, "SpaceActions")?,
+            volumes: get_property(&hash, "Volumes")?,
+            alignment: PartitionAlignment::from_dbus(&hash)?,
+            esp: EspSettings::from_dbus(&hash)?,
+            mount_by: get_optional_property(&hash, "MountBy")?,
+            read_only_root: ReadOnlyRootSettings::from_dbus(&hash)?,
+            caches: get_optional_property(&hash, "Caches")?.unwrap_or_default(),
+            partitions: get_optional_property(&hash, "Partitions")?.unwrap_or_default(),
+            include_removable: get_optional_property(&hash, "IncludeRemovable")?
+                .unwrap_or_default(),
+            excluded_devices: get_optional_property(&hash, "ExcludedDevices")?.unwrap_or_default(),
+            preferred_devices: vec![],
+        };
+        res.encryption_password_set = !res.encryption_password.is_empty();
+        let passphrases: HashMap<String, String> =
+            get_optional_property(&hash, "VolumeEncryptionPasswords")?.unwrap_or_default();
+        for volume in res.volumes.iter_mut() {
+            volume.encryption_password = passphrases
+                .get(&volume.mount_path)
+                .filter(|p| !p.is_empty())
+                .map(|p| Passphrase::new(p));
+        }
+        // volumes without an explicit encryption follow the global settings
+        if res.encryption_password_set {
+            for volume in res.volumes.iter_mut().filter(|v| v.encryption.is_none()) {
+                volume.encryption = Some(VolumeEncryption::Passphrase);
+            }
+        }
+
+        Ok(res)
+    }
+}
+
+/// Represents a single change action done to storage
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Action {
+    device: DeviceSid,
+    text: String,
+    subvol: bool,
+    delete: bool,
+    resize: bool,
+}
+
+impl TryFrom<HashMap<String, OwnedValue>> for Action {
+    type Error = zbus::zvariant::Error;
+
+    fn try_from(hash: HashMap<String, OwnedValue>) -> Result<Self, zbus::zvariant::Error> {
+        let res = Action {
+            device: get_property(&hash, "Device")?,
+            text: get_property(&hash, "Text")?,
+            subvol: get_property(&hash, "Subvol")?,
+            delete: get_property(&hash, "Delete")?,
+            resize: get_property(&hash, "Resize")?,
+        };
+
+        Ok(res)
+    }
+}
+
+/// How harmful the storage changes are for the existing data.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, utoipa::ToSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum ImpactSeverity {
+    /// No existing device is changed (e.g., only new partitions are created).
+    #[default]
+    None,
+    /// Existing devices are changed but their data is kept (e.g., a partition is shrunk).
+    Low,
+    /// Existing devices are deleted, but nothing was detected in them.
+    Medium,
+    /// Devices containing data (file systems, operating systems, RAID or LVM members) are
+    /// deleted.
+    High,
+}
+
+/// Existing device whose contents are going to be lost.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ErasedDevice {
+    pub sid: DeviceSid,
+    pub name: String,
+    pub description: String,
+    /// Size of the device.
+    pub size: DeviceSize,
+    /// Estimated size of the lost data: the used space of the file system if it is known, the
+    /// whole device if it contains data of unknown size and zero if nothing was detected.
+    pub data: DeviceSize,
+    /// Operating systems installed in the device.
+    pub systems: Vec<String>,
+    pub severity: ImpactSeverity,
+}
+
+/// Action which cannot be undone once the storage changes are committed.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IrreversibleAction {
+    pub device: DeviceSid,
+    pub text: String,
+    pub severity: ImpactSeverity,
+}
+
+/// Destructive impact of the storage proposal, computed from its list of actions.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageImpact {
+    /// Existing devices to erase, excluding the partitions of erased disks.
+    pub erased: Vec<ErasedDevice>,
+    /// Estimated size of the data to lose.
+    pub lost_data: DeviceSize,
+    pub irreversible: Vec<IrreversibleAction>,
+    /// Highest severity of the actions.
+    pub severity: ImpactSeverity,
+    /// Random token bound to the list of actions. It must be sent to commit the storage, which
+    /// is refused if the actions changed since the impact was reviewed.
+    pub token: String,
+}
+
+impl StorageImpact {
+    /// Summarizes the impact of the actions.
+    ///
+    /// * `actions`: actions of the proposal.
+    /// * `devices`: probed devices.
+    /// * `token`: confirmation token issued for the actions.
+    pub fn new(actions: &[Action], devices: &[Device], token: String) -> Self {
+        let find = |sid: &DeviceSid| devices.iter().find(|d| &d.device_info.sid == sid);
+        let deleted: Vec<&DeviceSid> = actions
+            .iter()
+            .filter(|a| a.delete && !a.subvol)
+            .map(|a| &a.device)
+            .collect();
+
+        let mut erased = vec![];
+        let mut irreversible = vec![];
+        for action in actions {
+            let severity = if action.delete {
+                match find(&action.device) {
+                    Some(device) if Self::has_data(device, devices) => ImpactSeverity::High,
+                    // the contents of the subvolumes are not probed
+                    None if action.subvol => ImpactSeverity::High,
+                    _ => ImpactSeverity::Medium,
+                }
+            } else if action.resize {
+                ImpactSeverity::Low
+            } else {
+                continue;
+            };
+            irreversible.push(IrreversibleAction {
+                device: action.device.clone(),
+                text: action.text.clone(),
+                severity,
+            });
+
+            if !action.delete || action.subvol {
+                continue;
+            }
+            let Some(device) = find(&action.device) else {
+                continue;
+            };
+            let in_erased_disk = device
+                .partition
+                .as_ref()
+                .is_some_and(|p| deleted.contains(&&p.device));
+            if in_erased_disk || erased.iter().any(|e: &ErasedDevice| e.sid == action.device) {
+                continue;
+            }
+            let contents = device.detect_contents(devices).unwrap_or_default();
+            erased.push(ErasedDevice {
+                sid: action.device.clone(),
+                name: device.device_info.name.clone(),
+                description: device.device_info.description.clone(),
+                size: device
+                    .block_device
+                    .as_ref()
+                    .map(|b| b.size.clone())
+                    .unwrap_or(0.into()),
+                data: Self::estimated_data(device, devices).into(),
+                systems: contents.systems,
+                severity,
+            });
+        }
+
+        let lost_data = erased.iter().map(|e| e.data.bytes()).sum::<u64>();
+        let severity = irreversible
+            .iter()
+            .map(|a| a.severity)
+            .max()
+            .unwrap_or_default();
+        Self {
+            erased,
+            lost_data: lost_data.into(),
+            irreversible,
+            severity,
+            token,
+        }
+    }
+
+    fn has_data(device: &Device, devices: &[Device]) -> bool {
+        device.detect_contents(devices).is_some_and(|c| c.in_use())
+    }
+
+    /// Best estimate of the data stored in the device and its partitions, in bytes.
+    fn estimated_data(device: &Device, devices: &[Device]) -> u64 {
+        let mut parts = vec![device];
+        if let Some(ptable) = &device.partition_table {
+            parts.extend(
+                devices
+                    .iter()
+                    .filter(|d| ptable.partitions.contains(&d.device_info.sid)),
+            );
+        }
+
+        parts
+            .into_iter()
+            .map(|part| {
+                let size = part
+                    .block_device
+                    .as_ref()
+                    .map(|b| b.size.bytes())
+                    .unwrap_or_default();
+                match (&part.filesystem, &part.component) {
+                    (Some(fs), _) => fs
+                        .free_space
+                        .as_ref()
+                        .map_or(size, |free| size.saturating_sub(free.bytes())),
+                    (None, Some(c)) if MEMBERSHIP_TYPES.contains(&c.component_type.as_str()) => {
+                        size
+                    }
+                    _ => 0,
+                }
+            })
+            .sum()
+    }
+}
+
+/// Represents value for target key of Volume
+/// It is snake cased when serializing to be compatible with yast2-storage-ng.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeTarget {
+    Default,
+    NewPartition,
+    NewVg,
+    Device,
+    Filesystem,
+}
+
+impl<'a> From<VolumeTarget> for zbus::zvariant::Value<'a> {
+    fn from(val: VolumeTarget) -> Self {
+        let str = match val {
+            VolumeTarget::Default => "default",
+            VolumeTarget::NewPartition => "new_partition",
+            VolumeTarget::NewVg => "new_vg",
+            VolumeTarget::Device => "device",
+            VolumeTarget::Filesystem => "filesystem",
+        };
+
+        Value::new(str)
+    }
+}
+
+impl TryFrom<zbus::zvariant::Value<'_>> for VolumeTarget {
+    type Error = zbus::zvariant::Error;
+
+    fn try_from(value: zbus::zvariant::Value) -> Result<Self, zbus::zvariant::Error> {
+        let svalue: String = value.try_into()?;
+        match svalue.as_str() {
+            "default" => Ok(VolumeTarget::Default),
+            "new_partition" => Ok(VolumeTarget::NewPartition),
+            "new_vg" => Ok(VolumeTarget::NewVg),
+            "device" => Ok(VolumeTarget::Device),
+            "filesystem" => Ok(VolumeTarget::Filesystem),
+            _ => Err(zbus::zvariant::Error::Message(
+                format!("Wrong value for Target: {}", svalue).to_string(),
+            )),
+        }
+    }
+}
+
+/// How a volume is encrypted.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum VolumeEncryption {
+    /// Encrypted according to the global encryption settings (passphrase, TPM, etc.).
+    Passphrase,
+    /// Encrypted with a random key regenerated on each boot. Only valid for swap.
+    RandomKey,
+}
+
+/// Encryption passphrase of a volume.
+///
+/// It is kept in memory only: it is not serialized and it is hidden when debugging.
+#[derive(Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct Passphrase(String);
+
+impl Passphrase {
+    pub fn new(passphrase: &str) -> Self {
+        Self(passphrase.to_string())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl std::fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("<hidden>")
+    }
+}
+
+impl<'a> From<VolumeEncryption> for zbus::zvariant::Value<'a> {
+    fn from(val: VolumeEncryption) -> Self {
+        let str = match val {
+            VolumeEncryption::Passphrase => "passphrase",
+            VolumeEncryption::RandomKey => "random_key",
+        };
+
+        Value::new(str)
+    }
+}
+
+impl TryFrom<zbus::zvariant::Value<'_>> for VolumeEncryption {
+    type Error = zbus::zvariant::Error;
+
+    fn try_from(value: zbus::zvariant::Value) -> Result<Self, zbus::zvariant::Error> {
+        let svalue: String = value.try_into()?;
+        match svalue.as_str() {
+            "passphrase" => Ok(VolumeEncryption::Passphrase),
+            "random_key" => Ok(VolumeEncryption::RandomKey),
+            _ => Err(zbus::zvariant::Error::Message(format!(
+                "Wrong value for Encryption: {}",
+                svalue
+            ))),
+        }
+    }
+}
+
+/// Type of the quotas of a file system.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum QuotaType {
+    /// Btrfs quota groups, one per subvolume.
+    Qgroups,
+    /// XFS project quotas, one project per directory tree.
+    Project,
+}
+
+impl QuotaType {
+    /// Returns the type of quotas supported by the given file system, if any.
+    ///
+    /// * `fs_type`: file system type (e.g., "btrfs").
+    pub fn for_filesystem(fs_type: &str) -> Option<Self> {
+        match normalize_fs_type(fs_type)?.as_str() {
+            "btrfs" => Some(Self::Qgroups),
+            "xfs" => Some(Self::Project),
+            _ => None,
+        }
+    }
+
+    fn as_dbus_string(&self) -> &'static str {
+        match self {
+            Self::Qgroups => "qgroups",
+            Self::Project => "project",
+        }
+    }
+}
+
+impl TryFrom<zbus::zvariant::Value<'_>> for QuotaType {
+    type Error = zbus::zvariant::Error;
+
+    fn try_from(value: zbus::zvariant::Value) -> Result<Self, zbus::zvariant::Error> {
+        let svalue: String = value.try_into()?;
+        match svalue.as_str() {
+            "qgroups" => Ok(QuotaType::Qgroups),
+            "project" => Ok(QuotaType::Project),
+            _ => Err(zbus::zvariant::Error::Message(format!(
+                "Wrong value for quota type: {}",
+                svalue
+            ))),
+        }
+    }
+}
+
+/// Limit of the space used by a subvolume (Btrfs) or a directory tree (XFS).
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaLimit {
+    /// Path of the subvolume or directory, relative to the mount point (e.g., "var/log").
+    pub path: String,
+    /// Maximum space, in bytes.
+    pub size: DeviceSize,
+}
+
+/// Quotas of the file system of a volume.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeQuota {
+    /// Type of the quotas. It must be supported by the file system of the volume.
+    #[serde(rename = "type")]
+    pub quota_type: QuotaType,
+    /// Limits of the subvolumes or directories. Quotas are enabled even if there are no limits,
+    /// so the space usage can be checked.
+    #[serde(default)]
+    pub limits: Vec<QuotaLimit>,
+}
+
+/// Mount option enabling the XFS project quotas.
+pub const PROJECT_QUOTA_OPTION: &str = "prjquota";
+
+impl<'a> From<VolumeQuota> for zbus::zvariant::Value<'a> {
+    fn from(val: VolumeQuota) -> Self {
+        let limits: Vec<(String, u64)> = val
+            .limits
+            .into_iter()
+            .map(|l| (l.path, l.size.bytes()))
+            .collect();
+        let result: HashMap<&str, Value> = HashMap::from([
+            ("Type", Value::new(val.quota_type.as_dbus_string())),
+            ("Limits", Value::new(limits)),
+        ]);
+        Value::new(result)
+    }
+}
+
+impl TryFrom<zbus::zvariant::Value<'_>> for VolumeQuota {
+    type Error = zbus::zvariant::Error;
+
+    fn try_from(value: zbus::zvariant::Value) -> Result<Self, zbus::zvariant::Error> {
+        let mvalue: HashMap<String, OwnedValue> = value.try_into()?;
+        let limits = match mvalue.get("Limits") {
+            Some(limits) => quota_limits_from_dbus(limits.into())?,
+            None => vec![],
+        };
+        let res = VolumeQuota {
+            quota_type: get_property(&mvalue, "Type")?,
+            limits,
+        };
+
+        Ok(res)
+    }
+}
+
+/// Converts the quota limits from D-Bus.
+///
+/// They are sent as an array of (path, size) structs, but the storage service replies with an
+/// array of variant arrays.
+fn quota_limits_from_dbus(value: Value) -> Result<Vec<QuotaLimit>, zbus::zvariant::Error> {
+    let Value::Array(limits) = unwrap_variant(value) else {
+        return Err(zbus::zvariant::Error::IncorrectType);
+    };
+    limits
+        .get()
+        .iter()
+        .map(|limit| {
+            let fields: Vec<Value> = match unwrap_variant(limit.clone()) {
+                Value::Structure(fields) => fields.into_fields(),
+                Value::Array(fields) => fields.get().to_vec(),
+                _ => return Err(zbus::zvariant::Error::IncorrectType),
+            };
+            let [path, size]: [Value; 2] = fields
+                .try_into()
+                .map_err(|_| zbus::zvariant::Error::IncorrectType)?;
+            Ok(QuotaLimit {
+                path: unwrap_variant(path).try_into()?,
+                size: unwrap_variant(size).try_into()?,
+            })
+        })
+        .collect()
+}
+
+fn unwrap_variant(value: Value) -> Value {
+    match value {
+        Value::Value(inner) => *inner,
+        value => value,
+    }
+}
+
+/// Represents volume outline aka requirements for volume
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeOutline {
+    required: bool,
+    fs_types: Vec<String>,
+    support_auto_size: bool,
+    adjust_by_ram: bool,
+    snapshots_configurable: bool,
+    snapshots_affect_sizes: bool,
+    size_relevant_volumes: Vec<String>,
 }
 
 impl TryFrom<zbus::zvariant::Value<'_>> for VolumeOutline {
@@ -438,13 +2516,441 @@ pub struct Volume {
     snapshots: bool,
     transactional: Option<bool>,
     outline: Option<VolumeOutline>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encryption: Option<VolumeEncryption>,
+    /// Passphrase overriding the default one (`encryptionPassword`) for this volume.
+    #[serde(default, skip_serializing)]
+    #[schema(value_type = Option<String>, write_only)]
+    encryption_password: Option<Passphrase>,
+    /// Free-text description of the volume.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    /// File system label.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    /// How the file system is referenced in the fstab, overriding the global setting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mount_by: Option<MountBy>,
+    /// Relative share of the free space the volume gets on top of its minimum size, up to its
+    /// maximum size (the volumes without a weight use 100). Together with `minSize` and `maxSize`
+    /// it defines a flexible size range.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    weight: Option<u32>,
+    /// Quotas of the file system (only Btrfs and XFS).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    quota: Option<VolumeQuota>,
+}
+
+/// Size of a volume in the calculated proposal.
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeSize {
+    /// Mount path of the volume (e.g., "/home").
+    pub mount_path: String,
+    /// Size of the device holding the volume.
+    pub size: DeviceSize,
+}
+
+impl VolumeSize {
+    /// Returns the sizes of the volumes, as planned in the given (staging) devices.
+    ///
+    /// Volumes which are not found in the devices are skipped.
+    ///
+    /// * `volumes`: volumes of the proposal.
+    /// * `devices`: staging devices.
+    pub fn from_devices(volumes: &[Volume], devices: &[Device]) -> Vec<Self> {
+        volumes
+            .iter()
+            .filter_map(|volume| {
+                let device = devices.iter().find(|d| {
+                    d.filesystem
+                        .as_ref()
+                        .is_some_and(|fs| fs.mount_path == volume.mount_path)
+                })?;
+                Some(Self {
+                    mount_path: volume.mount_path.clone(),
+                    size: device.block_device.as_ref()?.size.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// File systems that can be mounted without formatting them.
+pub const MOUNTABLE_FILESYSTEMS: [&str; 8] = [
+    "btrfs", "exfat", "ext2", "ext3", "ext4", "ntfs", "vfat", "xfs",
+];
+
+/// Capabilities of a file system type known by Agama.
+struct FilesystemCapabilities {
+    fs_type: &'static str,
+    shrink: bool,
+    grow: bool,
+    labels: bool,
+    subvolumes: bool,
+}
+
+/// Capabilities of the known file systems.
+///
+/// The storage service only reports the file system types, so the capabilities follow what
+/// libstorage-ng supports for each of them.
+const FILESYSTEM_CAPABILITIES: [FilesystemCapabilities; 9] = [
+    FilesystemCapabilities {
+        fs_type: "btrfs",
+        shrink: true,
+        grow: true,
+        labels: true,
+        subvolumes: true,
+    },
+    FilesystemCapabilities {
+        fs_type: "exfat",
+        shrink: false,
+        grow: false,
+        labels: true,
+        subvolumes: false,
+    },
+    FilesystemCapabilities {
+        fs_type: "ext2",
+        shrink: true,
+        grow: true,
+        labels: true,
+        subvolumes: false,
+    },
+    FilesystemCapabilities {
+        fs_type: "ext3",
+        shrink: true,
+        grow: true,
+        labels: true,
+        subvolumes: false,
+    },
+    FilesystemCapabilities {
+        fs_type: "ext4",
+        shrink: true,
+        grow: true,
+        labels: true,
+        subvolumes: false,
+    },
+    FilesystemCapabilities {
+        fs_type: "ntfs",
+        shrink: true,
+        grow: true,
+        labels: true,
+        subvolumes: false,
+    },
+    FilesystemCapabilities {
+        fs_type: "swap",
+        shrink: true,
+        grow: true,
+        labels: true,
+        subvolumes: false,
+    },
+    FilesystemCapabilities {
+        fs_type: "vfat",
+        shrink: true,
+        grow: true,
+        labels: true,
+        subvolumes: false,
+    },
+    FilesystemCapabilities {
+        fs_type: "xfs",
+        shrink: false,
+        grow: true,
+        labels: true,
+        subvolumes: false,
+    },
+];
+
+/// File system type which can be created in the installation.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SupportedFilesystem {
+    /// File system type (e.g., "btrfs").
+    pub fs_type: String,
+    pub supports_shrink: bool,
+    pub supports_grow: bool,
+    pub supports_labels: bool,
+    /// Whether the product default subvolumes are created (only btrfs).
+    pub default_subvolumes: bool,
+    /// Type of quotas the file system supports, if any.
+    pub quota_type: Option<QuotaType>,
+    /// Mount points of the product volumes offering the file system.
+    pub mount_points: Vec<String>,
+}
+
+impl SupportedFilesystem {
+    fn new(fs_type: &str) -> Self {
+        let capabilities = FILESYSTEM_CAPABILITIES
+            .iter()
+            .find(|c| c.fs_type == fs_type);
+        Self {
+            fs_type: fs_type.to_string(),
+            supports_shrink: capabilities.is_some_and(|c| c.shrink),
+            supports_grow: capabilities.is_some_and(|c| c.grow),
+            supports_labels: capabilities.is_some_and(|c| c.labels),
+            default_subvolumes: capabilities.is_some_and(|c| c.subvolumes),
+            quota_type: QuotaType::for_filesystem(fs_type),
+            mount_points: vec![],
+        }
+    }
+
+    /// Returns the file systems offered by the given volume templates, sorted by type.
+    ///
+    /// The storage service reports the types in a human readable form (e.g., "Btrfs" or "FAT"),
+    /// so they are normalized.
+    ///
+    /// * `volumes`: volume templates of the product. The generic one has no mount path.
+    pub fn from_volumes(volumes: &[Volume]) -> Vec<Self> {
+        let mut filesystems: BTreeMap<String, Self> = BTreeMap::new();
+        for volume in volumes {
+            let fs_types = volume
+                .outline
+                .as_ref()
+                .map(|o| o.fs_types.as_slice())
+                .unwrap_or_default();
+            let default = std::slice::from_ref(&volume.fs_type);
+            for fs_type in fs_types.iter().chain(default) {
+                let Some(fs_type) = normalize_fs_type(fs_type) else {
+                    continue;
+                };
+                let fs = filesystems
+                    .entry(fs_type.clone())
+                    .or_insert_with(|| Self::new(&fs_type));
+                if !volume.mount_path.is_empty() && !fs.mount_points.contains(&volume.mount_path) {
+                    fs.mount_points.push(volume.mount_path.clone());
+                }
+            }
+        }
+        filesystems.into_values().collect()
+    }
+}
+
+/// Converts a file system type as reported by the storage service to its identifier.
+fn normalize_fs_type(fs_type: &str) -> Option<String> {
+    match fs_type.to_lowercase().as_str() {
+        "" => None,
+        "fat" => Some("vfat".to_string()),
+        other => Some(other.to_string()),
+    }
+}
+
+impl Volume {
+    pub fn mount_path(&self) -> &str {
+        &self.mount_path
+    }
+
+    /// Minimum space needed in the given device to create the volume, in bytes.
+    ///
+    /// Volumes reusing an existing device or placed in a different one do not need any space.
+    ///
+    /// * `device`: name of the device (e.g., "/dev/sda").
+    pub fn min_size_in(&self, device: &str) -> u64 {
+        if !self.creates_in(device) {
+            return 0;
+        }
+        self.min_size
+            .as_ref()
+            .map(|s| s.bytes())
+            .unwrap_or_default()
+    }
+
+    /// Whether the volume is created in the given device.
+    ///
+    /// * `device`: name of the device (e.g., "/dev/sda").
+    fn creates_in(&self, device: &str) -> bool {
+        let creates = matches!(
+            self.target,
+            VolumeTarget::Default | VolumeTarget::NewPartition | VolumeTarget::NewVg
+        );
+        creates && self.target_device.as_deref().is_none_or(|d| d == device)
+    }
+
+    /// Returns the problems found in the size range of the volume.
+    ///
+    /// The minimum size cannot be bigger than the maximum one and the weight only applies to
+    /// explicit (not automatic) sizes.
+    pub fn validate_size_range(&self) -> Vec<String> {
+        let mut issues = vec![];
+        if self.auto_size {
+            if self.weight.is_some() {
+                issues.push(format!(
+                    "A weight cannot be used with the automatic sizes of {}",
+                    self.mount_path
+                ));
+            }
+            return issues;
+        }
+
+        if let (Some(min), Some(max)) = (&self.min_size, &self.max_size) {
+            if min.bytes() > max.bytes() {
+                issues.push(format!(
+                    "The minimum size of {} ({} bytes) is bigger than its maximum size ({} bytes)",
+                    self.mount_path,
+                    min.bytes(),
+                    max.bytes()
+                ));
+            }
+        }
+        issues
+    }
+
+    pub fn fs_type(&self) -> &str {
+        &self.fs_type
+    }
+
+    pub fn target_device(&self) -> Option<&str> {
+        self.target_device.as_deref()
+    }
+
+    pub fn encryption(&self) -> Option<VolumeEncryption> {
+        self.encryption
+    }
+
+    /// Whether the volume is encrypted with the default passphrase.
+    pub fn uses_default_passphrase(&self) -> bool {
+        self.encryption_password.is_none()
+            && matches!(self.encryption, None | Some(VolumeEncryption::Passphrase))
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn mount_by(&self) -> Option<MountBy> {
+        self.mount_by
+    }
+
+    pub fn quota(&self) -> Option<&VolumeQuota> {
+        self.quota.as_ref()
+    }
+
+    /// Returns the problems found in the quotas of the volume.
+    ///
+    /// The file system must support the type of quotas and each limit must refer to a different
+    /// relative path inside the volume.
+    pub fn validate_quota(&self) -> Vec<String> {
+        let Some(quota) = &self.quota else {
+            return vec![];
+        };
+        let mut issues = vec![];
+        if QuotaType::for_filesystem(&self.fs_type) != Some(quota.quota_type) {
+            let quota_type = match quota.quota_type {
+                QuotaType::Qgroups => "Btrfs quota groups",
+                QuotaType::Project => "XFS project quotas",
+            };
+            issues.push(format!(
+                "The file system '{}' of {} does not support {}",
+                self.fs_type, self.mount_path, quota_type
+            ));
+        }
+
+        let mut paths = vec![];
+        for limit in &quota.limits {
+            let path = limit.path.trim_matches('/');
+            if path.is_empty() || limit.path.starts_with('/') || path.split('/').any(|c| c == "..")
+            {
+                issues.push(format!(
+                    "The quota path '{}' of {} must be relative to its mount point",
+                    limit.path, self.mount_path
+                ));
+            } else if paths.contains(&path) {
+                issues.push(format!(
+                    "The quota path '{}' of {} is duplicated",
+                    limit.path, self.mount_path
+                ));
+            } else {
+                paths.push(path);
+            }
+            if limit.size.bytes() == 0 {
+                issues.push(format!(
+                    "The quota limit of '{}' in {} cannot be zero",
+                    limit.path, self.mount_path
+                ));
+            }
+        }
+        issues
+    }
+
+    pub fn is_swap(&self) -> bool {
+        self.mount_path == "swap"
+    }
+
+    /// Sets the file system type, if it is supported for the volume.
+    pub fn set_fs_type(&mut self, fs_type: &str) -> Result<(), String> {
+        let supported = self
+            .outline
+            .as_ref()
+            .map(|o| o.fs_types.as_slice())
+            .unwrap_or_default();
+        let is_swap = self.is_swap();
+        if (fs_type == "swap") != is_swap
+            || (!supported.is_empty() && !supported.iter().any(|t| t == fs_type))
+        {
+            return Err(format!(
+                "File system '{}' is not supported for {} (supported: {})",
+                fs_type,
+                self.mount_path,
+                supported.join(", ")
+            ));
+        }
+
+        self.fs_type = fs_type.to_string();
+        Ok(())
+    }
+
+    /// Whether the volume is formatted, instead of reusing an existing file system.
+    pub fn formats(&self) -> bool {
+        !matches!(self.target, VolumeTarget::Filesystem)
+    }
+
+    /// Mounts the existing file system of the given device, keeping its data.
+    ///
+    /// * `device`: name of the device containing the file system.
+    /// * `fs_type`: type of the existing file system.
+    pub fn keep_filesystem(&mut self, device: &str, fs_type: &str) {
+        self.target = VolumeTarget::Filesystem;
+        self.target_device = Some(device.to_string());
+        self.fs_type = fs_type.to_string();
+    }
+
+    /// Rounds up the explicit sizes to a multiple of the given grain.
+    ///
+    /// Returns a warning for each rounded size.
+    fn align_sizes(&mut self, grain: u64) -> Vec<String> {
+        let mut warnings = vec![];
+        for (name, size) in [
+            ("minimum", &mut self.min_size),
+            ("maximum", &mut self.max_size),
+        ] {
+            let Some(DeviceSize(bytes)) = size else {
+                continue;
+            };
+            let aligned = bytes.div_ceil(grain) * grain;
+            if aligned != *bytes {
+                warnings.push(format!(
+                    "The {} size of {} was rounded from {} to {} bytes to match the partition alignment",
+                    name, self.mount_path, bytes, aligned
+                ));
+                *bytes = aligned;
+            }
+        }
+        warnings
+    }
 }
 
 impl<'a> From<Volume> for zbus::zvariant::Value<'a> {
     fn from(val: Volume) -> Self {
+        let mut mount_options = val.mount_options;
+        // XFS does not enforce the project quotas unless the file system is mounted with them
+        let project_quota = val
+            .quota
+            .as_ref()
+            .is_some_and(|q| q.quota_type == QuotaType::Project);
+        if project_quota && !mount_options.iter().any(|o| o == PROJECT_QUOTA_OPTION) {
+            mount_options.push(PROJECT_QUOTA_OPTION.to_string());
+        }
         let mut result: HashMap<&str, Value> = HashMap::from([
             ("MountPath", Value::new(val.mount_path)),
-            ("MountOptions", Value::new(val.mount_options)),
+            ("MountOptions", Value::new(mount_options)),
             ("Target", val.target.into()),
             ("FsType", Value::new(val.fs_type)),
             ("AutoSize", Value::new(val.auto_size)),
@@ -459,6 +2965,29 @@ impl<'a> From<Volume> for zbus::zvariant::Value<'a> {
         if let Some(value) = val.max_size {
             result.insert("MaxSize", value.into());
         }
+        // a volume with its own passphrase is encrypted even if the default one is not set
+        let encryption = val.encryption.or(val
+            .encryption_password
+            .as_ref()
+            .map(|_| VolumeEncryption::Passphrase));
+        if let Some(value) = encryption {
+            result.insert("Encryption", value.into());
+        }
+        if let Some(value) = val.description {
+            result.insert("Description", Value::new(value));
+        }
+        if let Some(value) = val.label {
+            result.insert("Label", Value::new(value));
+        }
+        if let Some(value) = val.mount_by {
+            result.insert("MountBy", Value::new(value.as_dbus_string()));
+        }
+        if let Some(value) = val.weight {
+            result.insert("Weight", Value::new(value));
+        }
+        if let Some(value) = val.quota {
+            result.insert("Quota", value.into());
+        }
         // intentionally skip outline as it is not send to dbus and act as read only parameter
         Value::new(result)
     }
@@ -490,6 +3019,14 @@ impl TryFrom<HashMap<String, OwnedValue>> for Volume {
             snapshots: get_property(&volume_hash, "Snapshots")?,
             transactional: get_optional_property(&volume_hash, "Transactional")?,
             outline: get_optional_property(&volume_hash, "Outline")?,
+            encryption: get_optional_property(&volume_hash, "Encryption")?,
+            encryption_password: None,
+            description: get_optional_property(&volume_hash, "Description")?,
+            label: get_optional_property::<String>(&volume_hash, "Label")?
+                .filter(|l| !l.is_empty()),
+            mount_by: get_optional_property(&volume_hash, "MountBy")?,
+            weight: get_optional_property(&volume_hash, "Weight")?,
+            quota: get_optional_property(&volume_hash, "Quota")?,
         };
 
         Ok(res)
@@ -513,6 +3050,105 @@ pub struct Device {
     pub partition: Option<Partition>,
     pub partition_table: Option<PartitionTable>,
     pub raid: Option<Raid>,
+    pub cache: Option<Cache>,
+    /// Detected contents, only set for the probed block devices.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contents: Option<DeviceContents>,
+}
+
+/// Component types which make a device part of an existing RAID or LVM.
+const MEMBERSHIP_TYPES: [&str; 3] = ["physical_volume", "md_device", "raid_device"];
+
+impl Device {
+    /// Summarizes what the probe found in the device and its partitions.
+    ///
+    /// It returns `None` for devices which are not block devices.
+    ///
+    /// * `devices`: all the probed devices, used to look up the partitions.
+    pub fn detect_contents(&self, devices: &[Device]) -> Option<DeviceContents> {
+        let block_device = self.block_device.as_ref()?;
+        let mut parts = vec![self];
+        if let Some(ptable) = &self.partition_table {
+            parts.extend(
+                devices
+                    .iter()
+                    .filter(|d| ptable.partitions.contains(&d.device_info.sid)),
+            );
+        }
+
+        let mut contents = DeviceContents {
+            systems: block_device.systems.clone(),
+            ..Default::default()
+        };
+        for part in parts {
+            let name = &part.device_info.name;
+            if let Some(systems) = part.block_device.as_ref().map(|b| &b.systems) {
+                for system in systems {
+                    if !contents.systems.contains(system) {
+                        contents.systems.push(system.clone());
+                    }
+                }
+            }
+            if let Some(filesystem) = &part.filesystem {
+                contents.filesystems.push(DetectedFilesystem {
+                    device: name.clone(),
+                    fs_type: filesystem.fs_type.clone(),
+                    label: filesystem.label.clone(),
+                });
+            }
+            if let Some(component) = &part.component {
+                if MEMBERSHIP_TYPES.contains(&component.component_type.as_str()) {
+                    contents.memberships.push(DeviceMembership {
+                        device: name.clone(),
+                        component_type: component.component_type.clone(),
+                        device_names: component.device_names.clone(),
+                    });
+                }
+            }
+        }
+        Some(contents)
+    }
+}
+
+/// Existing contents of a block device, useful to warn before wiping it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceContents {
+    /// Names of the installed operating systems.
+    pub systems: Vec<String>,
+    /// Filesystems in the device or in its partitions.
+    pub filesystems: Vec<DetectedFilesystem>,
+    /// Devices (the device itself or its partitions) which belong to a RAID or an LVM volume group.
+    pub memberships: Vec<DeviceMembership>,
+}
+
+impl DeviceContents {
+    /// Whether the device contains anything that would be lost when wiping it.
+    pub fn in_use(&self) -> bool {
+        !self.systems.is_empty() || !self.filesystems.is_empty() || !self.memberships.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DetectedFilesystem {
+    /// Name of the device containing the filesystem (e.g., "/dev/sda1").
+    pub device: String,
+    #[serde(rename = "type")]
+    pub fs_type: String,
+    pub label: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceMembership {
+    /// Name of the member device (e.g., "/dev/sda2").
+    pub device: String,
+    /// Component type ("physical_volume", "md_device" or "raid_device").
+    #[serde(rename = "type")]
+    pub component_type: String,
+    /// Names of the RAID or LVM devices it belongs to.
+    pub device_names: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
@@ -533,6 +3169,10 @@ pub struct BlockDevice {
     pub systems: Vec<String>,
     pub udev_ids: Vec<String>,
     pub udev_paths: Vec<String>,
+    pub logical_sector_size: Option<u64>,
+    pub physical_sector_size: Option<u64>,
+    /// Offset of the region in bytes.
+    pub offset: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
@@ -584,6 +3224,284 @@ pub struct Drive {
     pub driver: Vec<String>,
     pub transport: String,
     pub info: DriveInfo,
+    /// SMART health, read from the system (not from the storage service).
+    #[serde(default)]
+    pub smart: SmartInfo,
+}
+
+impl Drive {
+    /// Whether the drive is removable (e.g., a USB stick or an SD card).
+    pub fn is_removable(&self) -> bool {
+        self.transport.eq_ignore_ascii_case("usb")
+            || self.bus.eq_ignore_ascii_case("usb")
+            || self.info.sd_card
+    }
+}
+
+/// Result of checking whether a device is usable as installation target.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, utoipa::ToSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum DeviceValidationStatus {
+    Pass,
+    /// The device can be used, but something could go wrong (e.g., its data would be lost).
+    Warn,
+    /// The device cannot be used.
+    Fail,
+}
+
+/// Reason of a warning or a failure when checking a device.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceValidationReason {
+    /// Machine readable reason (e.g., "tooSmall").
+    pub code: String,
+    pub status: DeviceValidationStatus,
+    pub message: String,
+}
+
+impl DeviceValidationReason {
+    fn new(code: &str, status: DeviceValidationStatus, message: String) -> Self {
+        Self {
+            code: code.to_string(),
+            status,
+            message,
+        }
+    }
+}
+
+/// Facts about a device which are not reported by the storage service.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TargetDeviceFacts {
+    /// Whether the device contains the running installation medium.
+    pub live_medium: bool,
+    /// Whether the device is read-only.
+    pub read_only: bool,
+    /// Minimum size needed by the proposal, in bytes.
+    pub min_size: u64,
+}
+
+/// Whether a device is usable as installation target, and why.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceValidation {
+    pub device: String,
+    /// The worst status of the reasons (`pass` if there are none).
+    pub status: DeviceValidationStatus,
+    pub reasons: Vec<DeviceValidationReason>,
+}
+
+/// Target disk chosen from a list of preferred ones.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskSelection {
+    /// Preferred disks, in the order they are tried.
+    pub preferred: Vec<String>,
+    /// Disk used as target device, if any of the preferred ones can be used.
+    pub selected: Option<String>,
+    /// Checks of the disks which were tried first and could not be used.
+    pub skipped: Vec<DeviceValidation>,
+}
+
+impl DiskSelection {
+    /// Starts the selection, skipping the disks which fail the checks.
+    ///
+    /// The rest of disks, including the ones with warnings (e.g., with existing data), must be
+    /// tried with the proposal. See [DiskSelection::candidates].
+    ///
+    /// * `preferred`: disks in order of preference.
+    /// * `validations`: checks of the disks, in the same order.
+    pub fn new(preferred: &[String], validations: Vec<DeviceValidation>) -> Self {
+        Self {
+            preferred: preferred.to_vec(),
+            selected: None,
+            skipped: validations
+                .into_iter()
+                .filter(|v| v.status == DeviceValidationStatus::Fail)
+                .collect(),
+        }
+    }
+
+    /// Returns the disks which were not skipped yet, in order of preference.
+    pub fn candidates(&self) -> Vec<String> {
+        self.preferred
+            .iter()
+            .filter(|d| !self.skipped.iter().any(|v| &v.device == *d))
+            .cloned()
+            .collect()
+    }
+
+    /// Skips a disk because the proposal could not be calculated on it.
+    pub fn reject(&mut self, device: &str) {
+        self.skipped.push(DeviceValidation {
+            device: device.to_string(),
+            status: DeviceValidationStatus::Fail,
+            reasons: vec![DeviceValidationReason::new(
+                "proposalFailed",
+                DeviceValidationStatus::Fail,
+                format!("The proposal cannot be calculated on {}", device),
+            )],
+        });
+        let preferred = &self.preferred;
+        self.skipped
+            .sort_by_key(|v| preferred.iter().position(|d| d == &v.device));
+    }
+
+    /// Describes why the skipped disks could not be used.
+    pub fn failures(&self) -> Vec<String> {
+        self.skipped
+            .iter()
+            .flat_map(|v| &v.reasons)
+            .filter(|r| r.status == DeviceValidationStatus::Fail)
+            .map(|r| r.message.clone())
+            .collect()
+    }
+
+    /// Describes why the disks before the selected one were skipped.
+    pub fn warnings(&self) -> Vec<String> {
+        let Some(selected) = &self.selected else {
+            return vec![];
+        };
+        self.skipped
+            .iter()
+            .map(|v| {
+                let reasons: Vec<_> = v
+                    .reasons
+                    .iter()
+                    .filter(|r| r.status == DeviceValidationStatus::Fail)
+                    .map(|r| r.message.as_str())
+                    .collect();
+                format!(
+                    "Using {} instead of {}: {}",
+                    selected,
+                    v.device,
+                    reasons.join(", ")
+                )
+            })
+            .collect()
+    }
+}
+
+impl DeviceValidation {
+    /// Checks whether the device is usable as installation target.
+    ///
+    /// * `device`: device to check.
+    /// * `devices`: probed devices, to detect the contents of the device.
+    /// * `facts`: facts about the device not reported by the storage service.
+    pub fn check(device: &Device, devices: &[Device], facts: &TargetDeviceFacts) -> Self {
+        use DeviceValidationStatus::{Fail, Warn};
+
+        let name = &device.device_info.name;
+        let mut reasons = vec![];
+        match &device.block_device {
+            Some(block_device) => {
+                let size = block_device.size.bytes();
+                if size < facts.min_size {
+                    reasons.push(DeviceValidationReason::new(
+                        "tooSmall",
+                        Fail,
+                        format!(
+                            "{} is too small: {} bytes are needed, but it has {} bytes",
+                            name, facts.min_size, size
+                        ),
+                    ));
+                }
+            }
+            None => reasons.push(DeviceValidationReason::new(
+                "notBlockDevice",
+                Fail,
+                format!("{} is not a block device", name),
+            )),
+        }
+        if facts.live_medium {
+            reasons.push(DeviceValidationReason::new(
+                "liveMedium",
+                Fail,
+                format!("{} contains the installation medium", name),
+            ));
+        }
+        if facts.read_only {
+            reasons.push(DeviceValidationReason::new(
+                "readOnly",
+                Fail,
+                format!("{} is read-only", name),
+            ));
+        }
+        if let Some(drive) = &device.drive {
+            if drive.smart.is_failing() {
+                reasons.push(DeviceValidationReason::new(
+                    "smartFailing",
+                    Fail,
+                    format!("The SMART status of {} reports that it is failing", name),
+                ));
+            } else if drive.smart.reallocated_sectors.is_some_and(|s| s > 0) {
+                reasons.push(DeviceValidationReason::new(
+                    "reallocatedSectors",
+                    Warn,
+                    format!("{} has reallocated sectors", name),
+                ));
+            }
+            if drive.is_removable() {
+                reasons.push(DeviceValidationReason::new(
+                    "removable",
+                    Warn,
+                    format!(
+                        "{} is removable, so it is not used by the proposal unless removable devices are included",
+                        name
+                    ),
+                ));
+            }
+        }
+        if device.detect_contents(devices).is_some_and(|c| c.in_use()) {
+            reasons.push(DeviceValidationReason::new(
+                "inUse",
+                Warn,
+                format!("The existing data of {} might be lost", name),
+            ));
+        }
+
+        let status = reasons
+            .iter()
+            .map(|r| r.status)
+            .max()
+            .unwrap_or(DeviceValidationStatus::Pass);
+        Self {
+            device: name.clone(),
+            status,
+            reasons,
+        }
+    }
+}
+
+/// Overall SMART health of a drive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SmartHealth {
+    Passed,
+    /// The drive reports that it is failing, so it should not be used for the installation.
+    Failing,
+    /// The drive does not support SMART or its status could not be read.
+    #[default]
+    Unknown,
+}
+
+/// SMART data of a drive.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SmartInfo {
+    pub health: SmartHealth,
+    /// Number of reallocated sectors, if known.
+    pub reallocated_sectors: Option<u64>,
+    /// Power-on time, in hours, if known.
+    pub power_on_hours: Option<u64>,
+}
+
+impl SmartInfo {
+    /// Whether the drive reports that it is failing.
+    pub fn is_failing(&self) -> bool {
+        self.health == SmartHealth::Failing
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
@@ -625,6 +3543,10 @@ pub struct Filesystem {
     pub fs_type: String,
     pub mount_path: String,
     pub label: String,
+    /// Free space in the file system, if known.
+    pub free_space: Option<DeviceSize>,
+    /// File system UUID. New file systems do not have one until they are formatted.
+    pub uuid: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
@@ -696,3 +3618,1316 @@ impl TryFrom<zbus::zvariant::Value<'_>> for UnusedSlot {
 pub struct Raid {
     pub devices: Vec<String>,
 }
+
+/// Constraints of a RAID level known by Agama.
+struct KnownRaidLevel {
+    level: &'static str,
+    min_members: u32,
+    redundancy: &'static str,
+    capacity: &'static str,
+}
+
+/// RAID levels which can be created.
+///
+/// The storage service does not report them, so the list follows the software RAID levels
+/// supported by libstorage-ng.
+const RAID_LEVELS: [KnownRaidLevel; 6] = [
+    KnownRaidLevel {
+        level: "raid0",
+        min_members: 2,
+        redundancy: "None: the data is lost if any member fails",
+        capacity: "n * s",
+    },
+    KnownRaidLevel {
+        level: "raid1",
+        min_members: 2,
+        redundancy: "All the members but one can fail",
+        capacity: "s",
+    },
+    KnownRaidLevel {
+        level: "raid4",
+        min_members: 3,
+        redundancy: "One member can fail (dedicated parity member)",
+        capacity: "(n - 1) * s",
+    },
+    KnownRaidLevel {
+        level: "raid5",
+        min_members: 3,
+        redundancy: "One member can fail (distributed parity)",
+        capacity: "(n - 1) * s",
+    },
+    KnownRaidLevel {
+        level: "raid6",
+        min_members: 4,
+        redundancy: "Two members can fail (double distributed parity)",
+        capacity: "(n - 2) * s",
+    },
+    KnownRaidLevel {
+        level: "raid10",
+        min_members: 2,
+        redundancy: "One member of each mirrored pair can fail",
+        capacity: "n * s / 2",
+    },
+];
+
+/// RAID level which can be created, and its constraints.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RaidLevel {
+    /// Level name (e.g., "raid5").
+    pub level: String,
+    pub min_members: u32,
+    /// Maximum number of members, if the level limits it.
+    pub max_members: Option<u32>,
+    /// Which members can fail without losing data.
+    pub redundancy: String,
+    /// Usable capacity, where `n` is the number of members and `s` the size of the smallest one.
+    pub capacity: String,
+}
+
+impl RaidLevel {
+    /// Returns the levels which can be created.
+    pub fn supported() -> Vec<Self> {
+        RAID_LEVELS
+            .iter()
+            .map(|known| Self {
+                level: known.level.to_string(),
+                min_members: known.min_members,
+                max_members: None,
+                redundancy: known.redundancy.to_string(),
+                capacity: known.capacity.to_string(),
+            })
+            .collect()
+    }
+
+    /// Finds a supported level by its name (e.g., "raid5" or "RAID5").
+    pub fn find(level: &str) -> Option<Self> {
+        let level = level.to_lowercase();
+        Self::supported().into_iter().find(|l| l.level == level)
+    }
+
+    /// Whether a RAID of this level can be built with the given number of members.
+    pub fn accepts(&self, members: u32) -> bool {
+        members >= self.min_members && self.max_members.map_or(true, |max| members <= max)
+    }
+
+    /// Usable capacity of a RAID of this level, in bytes.
+    ///
+    /// * `members`: sizes of the members, in bytes.
+    pub fn usable_size(&self, members: &[u64]) -> Result<u64, String> {
+        let count = members.len() as u64;
+        if !self.accepts(count as u32) {
+            return Err(format!(
+                "A {} needs at least {} members, but {} were given",
+                self.level.to_uppercase(),
+                self.min_members,
+                count
+            ));
+        }
+        // all the members are used as if they had the size of the smallest one
+        let smallest = members.iter().min().copied().unwrap_or_default();
+        let size = match self.level.as_str() {
+            "raid0" => count * smallest,
+            "raid1" => smallest,
+            "raid4" | "raid5" => (count - 1) * smallest,
+            "raid6" => (count - 2) * smallest,
+            _ => count * smallest / 2,
+        };
+        Ok(size)
+    }
+}
+
+/// Cached device (e.g., "/dev/bcache0") combining a cache and a backing device.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Cache {
+    pub method: CacheMethod,
+    pub mode: CacheMode,
+    pub cache_device: DeviceSid,
+    pub backing_device: DeviceSid,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_device(sid: u32, name: &str, systems: Vec<&str>) -> Device {
+        Device {
+            device_info: DeviceInfo {
+                sid: sid.into(),
+                name: name.to_string(),
+                description: String::new(),
+            },
+            block_device: Some(BlockDevice {
+                active: true,
+                encrypted: false,
+                size: 1024.into(),
+                shrinking: ShrinkingInfo::Unsupported(vec![]),
+                start: 0,
+                systems: systems.into_iter().map(String::from).collect(),
+                udev_ids: vec![],
+                udev_paths: vec![],
+                logical_sector_size: None,
+                physical_sector_size: None,
+                offset: None,
+            }),
+            component: None,
+            drive: None,
+            filesystem: None,
+            lvm_lv: None,
+            lvm_vg: None,
+            md: None,
+            multipath: None,
+            partition: None,
+            partition_table: None,
+            raid: None,
+            cache: None,
+            contents: None,
+        }
+    }
+
+    fn partition(name: &str, size: u64, shrinking: ShrinkingInfo) -> Device {
+        let mut device = block_device(5, name, vec![]);
+        if let Some(block) = device.block_device.as_mut() {
+            block.size = size.into();
+            block.shrinking = shrinking;
+        }
+        device.partition = Some(Partition {
+            device: 1.into(),
+            efi: false,
+        });
+        device
+    }
+
+    #[test]
+    fn test_validate_resize() {
+        let mut windows = partition("/dev/sda2", 1000, ShrinkingInfo::Supported(400.into()));
+        windows.filesystem = Some(Filesystem {
+            sid: 5.into(),
+            fs_type: "ntfs".to_string(),
+            mount_path: String::new(),
+            label: String::new(),
+            free_space: None,
+            uuid: None,
+        });
+        let xfs = partition(
+            "/dev/sda3",
+            1000,
+            ShrinkingInfo::Unsupported(vec!["XFS cannot be shrunk".to_string()]),
+        );
+        let devices = vec![windows, xfs];
+
+        let resize = |device: &str, size: Option<u64>| SpaceActionSettings {
+            device: device.to_string(),
+            action: SpaceAction::Resize,
+            size,
+        };
+
+        let summary = resize("/dev/sda2", Some(600))
+            .validate_resize(&devices)
+            .unwrap();
+        assert_eq!(summary.size, 600);
+        assert_eq!(summary.freed, 400);
+        let summary = resize("/dev/sda2", None).validate_resize(&devices).unwrap();
+        assert_eq!(summary.freed, 600);
+
+        assert_eq!(
+            resize("/dev/sda2", Some(300)).validate_resize(&devices),
+            Err(ResizeError::BelowMinimum {
+                device: "/dev/sda2".to_string(),
+                min_size: 400
+            })
+        );
+        assert!(matches!(
+            resize("/dev/sda2", Some(2000)).validate_resize(&devices),
+            Err(ResizeError::CannotGrow { .. })
+        ));
+        assert!(matches!(
+            resize("/dev/sda3", Some(600)).validate_resize(&devices),
+            Err(ResizeError::Unsupported(..))
+        ));
+        assert!(matches!(
+            resize("/dev/sdb", Some(600)).validate_resize(&devices),
+            Err(ResizeError::UnknownDevice(_))
+        ));
+
+        let mut devices = devices;
+        if let Some(fs) = devices[0].filesystem.as_mut() {
+            fs.mount_path = "/mnt".to_string();
+        }
+        assert_eq!(
+            resize("/dev/sda2", Some(600)).validate_resize(&devices),
+            Err(ResizeError::Mounted("/dev/sda2".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_detect_contents() {
+        let mut disk = block_device(1, "/dev/sda", vec!["openSUSE Leap"]);
+        disk.partition_table = Some(PartitionTable {
+            ptable_type: "gpt".to_string(),
+            partitions: vec![2.into(), 3.into()],
+            unused_slots: vec![],
+        });
+        let mut root = block_device(2, "/dev/sda1", vec!["openSUSE Leap"]);
+        root.filesystem = Some(Filesystem {
+            sid: 2.into(),
+            fs_type: "btrfs".to_string(),
+            mount_path: String::new(),
+            label: "ROOT".to_string(),
+            free_space: None,
+            uuid: None,
+        });
+        let mut pv = block_device(3, "/dev/sda2", vec![]);
+        pv.component = Some(Component {
+            component_type: "physical_volume".to_string(),
+            device_names: vec!["/dev/system".to_string()],
+            devices: vec![10.into()],
+        });
+        let empty = block_device(4, "/dev/sdb", vec![]);
+        let devices = vec![disk, root, pv, empty];
+
+        let contents = devices[0].detect_contents(&devices).unwrap();
+        assert!(contents.in_use());
+        assert_eq!(contents.systems, vec!["openSUSE Leap".to_string()]);
+        assert_eq!(
+            contents.filesystems,
+            vec![DetectedFilesystem {
+                device: "/dev/sda1".to_string(),
+                fs_type: "btrfs".to_string(),
+                label: "ROOT".to_string(),
+            }]
+        );
+        assert_eq!(
+            contents.memberships,
+            vec![DeviceMembership {
+                device: "/dev/sda2".to_string(),
+                component_type: "physical_volume".to_string(),
+                device_names: vec!["/dev/system".to_string()],
+            }]
+        );
+
+        let contents = devices[3].detect_contents(&devices).unwrap();
+        assert!(!contents.in_use());
+    }
+
+    fn action(sid: u32, text: &str, delete: bool, resize: bool) -> Action {
+        Action {
+            device: sid.into(),
+            text: text.to_string(),
+            subvol: false,
+            delete,
+            resize,
+        }
+    }
+
+    #[test]
+    fn test_storage_impact() {
+        let mut disk = block_device(1, "/dev/sda", vec![]);
+        disk.partition_table = Some(PartitionTable {
+            ptable_type: "gpt".to_string(),
+            partitions: vec![2.into(), 3.into()],
+            unused_slots: vec![],
+        });
+        let mut root = partition("/dev/sda1", 1000, ShrinkingInfo::Unsupported(vec![]));
+        root.device_info.sid = 2.into();
+        root.block_device.as_mut().unwrap().systems = vec!["openSUSE Leap".to_string()];
+        root.filesystem = Some(Filesystem {
+            sid: 2.into(),
+            fs_type: "btrfs".to_string(),
+            mount_path: String::new(),
+            label: String::new(),
+            free_space: Some(400.into()),
+            uuid: None,
+        });
+        let mut swap = partition("/dev/sda2", 200, ShrinkingInfo::Unsupported(vec![]));
+        swap.device_info.sid = 3.into();
+        let windows = block_device(4, "/dev/sdb1", vec![]);
+        let devices = vec![disk, root, swap, windows];
+
+        let actions = vec![
+            action(2, "Delete partition /dev/sda1", true, false),
+            action(3, "Delete partition /dev/sda2", true, false),
+            action(4, "Shrink partition /dev/sdb1", false, true),
+            action(5, "Create partition /dev/sda1", false, false),
+        ];
+        let impact = StorageImpact::new(&actions, &devices, "token".to_string());
+        assert_eq!(impact.severity, ImpactSeverity::High);
+        assert_eq!(impact.lost_data.bytes(), 600);
+        assert_eq!(impact.erased.len(), 2);
+        assert_eq!(impact.erased[0].severity, ImpactSeverity::High);
+        assert_eq!(impact.erased[0].systems, vec!["openSUSE Leap".to_string()]);
+        assert_eq!(impact.erased[1].severity, ImpactSeverity::Medium);
+        assert_eq!(impact.erased[1].data.bytes(), 0);
+        let severities: Vec<_> = impact.irreversible.iter().map(|a| a.severity).collect();
+        assert_eq!(
+            severities,
+            vec![
+                ImpactSeverity::High,
+                ImpactSeverity::Medium,
+                ImpactSeverity::Low
+            ]
+        );
+
+        // the partitions are not counted twice when the whole disk is erased
+        let mut erase_disk = actions.clone();
+        erase_disk.insert(
+            0,
+            action(1, "Delete partition table on /dev/sda", true, false),
+        );
+        let impact = StorageImpact::new(&erase_disk, &devices, "token".to_string());
+        assert_eq!(impact.erased.len(), 1);
+        assert_eq!(impact.erased[0].name, "/dev/sda");
+        assert_eq!(impact.lost_data.bytes(), 600);
+        assert_eq!(impact.token, "token");
+
+        let impact = StorageImpact::new(&actions[3..], &devices, "token".to_string());
+        assert_eq!(impact.severity, ImpactSeverity::None);
+        assert!(impact.irreversible.is_empty());
+    }
+
+    fn volume(mount_path: &str, min_size: u64, max_size: Option<u64>) -> Volume {
+        Volume {
+            mount_path: mount_path.to_string(),
+            mount_options: vec![],
+            target: VolumeTarget::Default,
+            target_device: None,
+            fs_type: "xfs".to_string(),
+            min_size: Some(min_size.into()),
+            max_size: max_size.map(|s| s.into()),
+            auto_size: false,
+            snapshots: false,
+            transactional: None,
+            outline: None,
+            encryption: None,
+            encryption_password: None,
+            description: None,
+            label: None,
+            mount_by: None,
+            weight: None,
+            quota: None,
+        }
+    }
+
+    fn drive(sid: u32, name: &str, transport: &str) -> Device {
+        let mut device = block_device(sid, name, vec![]);
+        device.drive = Some(Drive {
+            drive_type: "disk".to_string(),
+            vendor: String::new(),
+            model: String::new(),
+            bus: String::new(),
+            bus_id: String::new(),
+            driver: vec![],
+            transport: transport.to_string(),
+            info: DriveInfo {
+                sd_card: false,
+                dell_boss: false,
+            },
+            smart: SmartInfo::default(),
+        });
+        device
+    }
+
+    #[test]
+    fn test_excluded_devices() {
+        let devices = vec![
+            drive(1, "/dev/sda", "sata"),
+            drive(2, "/dev/sdb", "usb"),
+            block_device(3, "/dev/md0", vec![]),
+        ];
+        let names = vec!["/dev/sda".to_string(), "/dev/sdb".to_string()];
+        let excluded = ExcludedDevice::removable(&devices, &names);
+        assert_eq!(excluded.len(), 1);
+        assert_eq!(excluded[0].name, "/dev/sdb");
+
+        assert!(ExcludedDevice::removable(&devices, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_layout_warnings() {
+        let volumes = vec![volume("/", 0, None), volume("swap", 0, Some(1024))];
+        assert!(ProposalIssues::layout_warnings(&volumes).is_empty());
+
+        let volumes = vec![
+            volume("/", 0, None),
+            volume("/boot", 0, Some(256 * 1024 * 1024)),
+        ];
+        let codes: Vec<_> = ProposalIssues::layout_warnings(&volumes)
+            .into_iter()
+            .map(|i| i.code)
+            .collect();
+        assert_eq!(codes, vec!["no_swap", "small_boot"]);
+
+        let mut boot = volume("/boot", 0, Some(256 * 1024 * 1024));
+        boot.auto_size = true;
+        let volumes = vec![volume("swap", 0, None), boot];
+        assert!(ProposalIssues::layout_warnings(&volumes).is_empty());
+    }
+
+    #[test]
+    fn test_add_smart_issues() {
+        let mut devices = vec![
+            drive(1, "/dev/sda", "sata"),
+            drive(2, "/dev/sdb", "sata"),
+            drive(3, "/dev/sdc", "sata"),
+        ];
+        for device in &mut devices[1..] {
+            device.drive.as_mut().unwrap().smart.health = SmartHealth::Failing;
+        }
+
+        let mut issues = ProposalIssues::default();
+        issues.add_smart_issues(&devices, &["/dev/sdb".to_string()]);
+        assert_eq!(issues.errors.len(), 1);
+        assert!(issues.errors[0].message.contains("/dev/sdb"));
+        assert_eq!(issues.warnings.len(), 1);
+        assert_eq!(issues.warnings[0].code, "smart_failing");
+        assert!(issues.warnings[0].message.contains("/dev/sdc"));
+    }
+
+    #[test]
+    fn test_raid_levels() {
+        let levels = RaidLevel::supported();
+        assert_eq!(levels.len(), 6);
+
+        let raid5 = RaidLevel::find("RAID5").unwrap();
+        assert!(!raid5.accepts(2));
+        assert!(raid5.accepts(3));
+        assert_eq!(raid5.usable_size(&[100, 100, 80]), Ok(160));
+        assert_eq!(
+            raid5.usable_size(&[100, 100]),
+            Err("A RAID5 needs at least 3 members, but 2 were given".to_string())
+        );
+
+        let size = |level: &str, members: &[u64]| RaidLevel::find(level)?.usable_size(members).ok();
+        assert_eq!(size("raid0", &[100, 100]), Some(200));
+        assert_eq!(size("raid1", &[100, 100, 100]), Some(100));
+        assert_eq!(size("raid6", &[100, 100, 100, 100]), Some(200));
+        assert_eq!(size("raid10", &[100, 100, 100, 100]), Some(200));
+        assert!(RaidLevel::find("raid7").is_none());
+    }
+
+    #[test]
+    fn test_supported_filesystems() {
+        let mut root = volume("/", 0, None);
+        root.fs_type = "Btrfs".to_string();
+        root.outline = Some(VolumeOutline {
+            required: true,
+            fs_types: vec!["Btrfs".to_string(), "XFS".to_string()],
+            support_auto_size: true,
+            adjust_by_ram: false,
+            snapshots_configurable: true,
+            snapshots_affect_sizes: true,
+            size_relevant_volumes: vec![],
+        });
+        let mut generic = volume("", 0, None);
+        generic.fs_type = "FAT".to_string();
+
+        let filesystems = SupportedFilesystem::from_volumes(&[root, generic]);
+        let types: Vec<_> = filesystems.iter().map(|f| f.fs_type.as_str()).collect();
+        assert_eq!(types, vec!["btrfs", "vfat", "xfs"]);
+
+        let btrfs = &filesystems[0];
+        assert!(btrfs.supports_shrink && btrfs.default_subvolumes);
+        assert_eq!(btrfs.mount_points, vec!["/".to_string()]);
+        assert!(filesystems[1].mount_points.is_empty());
+        assert!(!filesystems[2].supports_shrink);
+        assert_eq!(btrfs.quota_type, Some(QuotaType::Qgroups));
+        assert_eq!(filesystems[1].quota_type, None);
+    }
+
+    #[test]
+    fn test_resolve_partition_tables() {
+        let tib = 1024 * 1024 * 1024 * 1024;
+        let disks = HashMap::from([
+            ("/dev/sda".to_string(), 100 * 1024 * 1024 * 1024),
+            ("/dev/sdb".to_string(), 4 * tib),
+        ]);
+        let tables = vec![
+            PartitionTableSettings {
+                device: "/dev/sda".to_string(),
+                ptable_type: None,
+            },
+            PartitionTableSettings {
+                device: "/dev/sdb".to_string(),
+                ptable_type: None,
+            },
+        ];
+        let mut settings = ProposalSettingsPatch {
+            target_device: Some("/dev/sda".to_string()),
+            partition_tables: Some(tables.clone()),
+            ..Default::default()
+        };
+
+        let (errors, warnings) = settings.resolve_partition_tables(false, &disks, None);
+        assert!(errors.is_empty());
+        assert!(warnings.is_empty());
+        let types: Vec<_> = settings
+            .partition_tables
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|t| t.ptable_type)
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                Some(PartitionTableType::Msdos),
+                Some(PartitionTableType::Gpt)
+            ]
+        );
+
+        let mut settings = ProposalSettingsPatch {
+            partition_tables: Some(tables.clone()),
+            ..Default::default()
+        };
+        settings.resolve_partition_tables(true, &disks, None);
+        assert_eq!(
+            settings.partition_tables.unwrap()[0].ptable_type,
+            Some(PartitionTableType::Gpt)
+        );
+    }
+
+    #[test]
+    fn test_resolve_partition_tables_errors() {
+        let disks = HashMap::from([("/dev/sda".to_string(), 4 * MSDOS_MAX_DISK_SIZE)]);
+        let volumes = ["/", "/home", "/var", "/srv", "swap"]
+            .into_iter()
+            .map(|p| volume(p, 1024, None))
+            .collect();
+        let mut settings = ProposalSettingsPatch {
+            target_device: Some("/dev/sda".to_string()),
+            configure_boot: Some(true),
+            volumes: Some(volumes),
+            partition_tables: Some(vec![
+                PartitionTableSettings {
+                    device: "/dev/sda".to_string(),
+                    ptable_type: Some(PartitionTableType::Msdos),
+                },
+                PartitionTableSettings {
+                    device: "/dev/sdz".to_string(),
+                    ptable_type: None,
+                },
+            ]),
+            ..Default::default()
+        };
+        let (errors, warnings) = settings.resolve_partition_tables(false, &disks, None);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("bigger than 2 TiB"));
+        assert_eq!(errors[1], "Unknown disk /dev/sdz");
+        assert_eq!(
+            warnings,
+            vec!["5 partitions are planned for /dev/sda, so 2 of them will be logical partitions inside an extended partition"]
+        );
+
+        let volumes = (0..256)
+            .map(|i| volume(&format!("/srv/{}", i), 1024, None))
+            .collect();
+        let mut settings = ProposalSettingsPatch {
+            target_device: Some("/dev/sda".to_string()),
+            volumes: Some(volumes),
+            partition_tables: Some(vec![PartitionTableSettings {
+                device: "/dev/sda".to_string(),
+                ptable_type: Some(PartitionTableType::Msdos),
+            }]),
+            ..Default::default()
+        };
+        let disks = HashMap::from([("/dev/sda".to_string(), MSDOS_MAX_DISK_SIZE)]);
+        let (errors, _) = settings.resolve_partition_tables(false, &disks, None);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("256 partitions are planned for /dev/sda"));
+
+        let mut settings = ProposalSettingsPatch {
+            target_device: Some("/dev/sda".to_string()),
+            configure_boot: Some(true),
+            partition_tables: Some(vec![PartitionTableSettings {
+                device: "/dev/sda".to_string(),
+                ptable_type: Some(PartitionTableType::Gpt),
+            }]),
+            ..Default::default()
+        };
+        let (errors, warnings) = settings.resolve_partition_tables(false, &disks, None);
+        assert!(errors.is_empty());
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_validate_esp() {
+        let mib = 1024 * 1024;
+        let mut esp = block_device(2, "/dev/sda1", vec![]);
+        esp.block_device.as_mut().unwrap().size = (512 * mib).into();
+        esp.filesystem = Some(Filesystem {
+            sid: 2.into(),
+            fs_type: "vfat".to_string(),
+            mount_path: String::new(),
+            label: String::new(),
+            free_space: Some((10 * mib).into()),
+            uuid: None,
+        });
+        let devices = vec![esp];
+
+        let reuse = EspSettings::Reuse {
+            device: "/dev/sda1".to_string(),
+            mount_path: "/boot/efi".to_string(),
+        };
+        let (errors, warnings) = reuse.validate(true, &devices);
+        assert!(errors.is_empty());
+        assert_eq!(
+            warnings,
+            vec!["The ESP /dev/sda1 is nearly full, the boot loader might not fit".to_string()]
+        );
+
+        let create = EspSettings::Create {
+            size: 100 * mib,
+            mount_path: "/boot/grub".to_string(),
+        };
+        let (errors, _) = create.validate(true, &devices);
+        assert_eq!(errors.len(), 2);
+
+        let reuse = EspSettings::Reuse {
+            device: "/dev/sdb1".to_string(),
+            mount_path: "/efi".to_string(),
+        };
+        let (errors, _) = reuse.validate(true, &devices);
+        assert_eq!(errors, vec!["Unknown device /dev/sdb1".to_string()]);
+        assert!(EspSettings::Auto.validate(false, &devices).1.is_empty());
+    }
+
+    #[test]
+    fn test_validate_volume_encryption() {
+        let mut swap = volume("swap", 1024, None);
+        swap.encryption = Some(VolumeEncryption::RandomKey);
+        let mut home = volume("/home", 1024, None);
+        home.encryption = Some(VolumeEncryption::RandomKey);
+        let mut root = volume("/", 1024, None);
+        root.encryption = Some(VolumeEncryption::Passphrase);
+
+        assert!(patch(None, vec![swap.clone(), root.clone()])
+            .validate_volume_encryption()
+            .is_empty());
+        assert_eq!(
+            patch(None, vec![swap, home, root]).validate_volume_encryption(),
+            vec!["A random encryption key can only be used for swap, not for /home".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_validate_volume_passphrase() {
+        let mut swap = volume("swap", 1024, None);
+        swap.encryption = Some(VolumeEncryption::RandomKey);
+        swap.encryption_password = Some(Passphrase::new("secret"));
+        let mut home = volume("/home", 1024, None);
+        home.encryption_password = Some(Passphrase::new(""));
+
+        assert_eq!(
+            patch(None, vec![swap, home]).validate_volume_encryption(),
+            vec![
+                "A random encryption key and a passphrase cannot be used together for swap"
+                    .to_string(),
+                "The encryption passphrase for /home cannot be empty".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_default_passphrase() {
+        let mut root = volume("/", 1024, None);
+        root.encryption_password = Some(Passphrase::new("root-secret"));
+        let home = volume("/home", 1024, None);
+
+        let mut settings = patch(None, vec![root.clone(), home]);
+        settings.encryption_password = Some("secret".to_string());
+        assert!(settings.validate_default_passphrase(None).is_empty());
+
+        let mut settings = patch(None, vec![root.clone()]);
+        assert!(settings.validate_default_passphrase(None).is_empty());
+        settings.encryption_password = Some("secret".to_string());
+        assert_eq!(
+            settings.validate_default_passphrase(None),
+            vec!["The default encryption passphrase is not used by any volume".to_string()]
+        );
+
+        // the passphrase is never serialized nor included in the volume hash
+        let json = serde_json::to_string(&root).unwrap();
+        assert!(!json.contains("root-secret"));
+        assert!(!format!("{:?}", root).contains("root-secret"));
+        let dbus: HashMap<String, OwnedValue> = Value::from(root.clone()).try_into().unwrap();
+        assert!(!dbus.contains_key("EncryptionPassword"));
+        let volume = Volume::try_from(dbus).unwrap();
+        assert!(volume.encryption_password.is_none());
+        assert_eq!(volume.encryption, Some(VolumeEncryption::Passphrase));
+
+        // but it is sent apart, indexed by mount path
+        let dbus: HashMap<&str, Value> = patch(None, vec![root]).into();
+        let passphrases: HashMap<String, String> = dbus["VolumeEncryptionPasswords"]
+            .clone()
+            .try_into()
+            .unwrap();
+        assert_eq!(passphrases.get("/"), Some(&"root-secret".to_string()));
+    }
+
+    #[test]
+    fn test_encryption_key_file() {
+        let mut key_file = EncryptionKeyFile {
+            path: "/luks.key".to_string(),
+            device: Some("LABEL=USBKEY".to_string()),
+            contents: Some(Passphrase::new("c2VjcmV0LWtleQ==")),
+            ..Default::default()
+        };
+        assert!(key_file.validate(Some("luks2"), true).is_empty());
+        assert_eq!(key_file.crypttab_key(), "/luks.key:LABEL=USBKEY");
+        assert_eq!(
+            key_file.validate(Some(TPM_FDE_METHOD), false),
+            vec![
+                "A key file requires an encryption password".to_string(),
+                "A key file cannot be used with TPM-based encryption".to_string()
+            ]
+        );
+
+        // the key material is never serialized nor shown
+        let json = serde_json::to_string(&key_file).unwrap();
+        assert!(!json.contains("c2VjcmV0LWtleQ=="));
+        assert!(!format!("{:?}", key_file).contains("c2VjcmV0LWtleQ=="));
+
+        let dir = tempfile::tempdir().unwrap();
+        let keys = dir.path().join("keys");
+        key_file.write_key(&keys).unwrap();
+        let source = key_file.source.clone().unwrap();
+        assert_eq!(std::fs::read_to_string(&source).unwrap(), "secret-key");
+
+        let mut dbus = HashMap::new();
+        key_file.to_dbus(&mut dbus);
+        let dbus: HashMap<String, OwnedValue> = dbus
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.into()))
+            .collect();
+        assert!(!dbus.contains_key("EncryptionKeyContents"));
+        let read = EncryptionKeyFile::from_dbus(&dbus).unwrap().unwrap();
+        assert_eq!(read.crypttab_key(), "/luks.key:LABEL=USBKEY");
+        assert!(read.contents.is_none());
+
+        key_file.path = "luks.key".to_string();
+        key_file.contents = Some(Passphrase::new("not base64!"));
+        assert_eq!(
+            key_file.validate(None, true),
+            vec![
+                "The key file path must be an absolute path, not 'luks.key'".to_string(),
+                "The key file contents are not valid base64".to_string()
+            ]
+        );
+
+        // the key is not read from the path, which refers to the target system
+        key_file.path = source.clone();
+        key_file.contents = None;
+        assert_eq!(
+            key_file.validate(None, true),
+            vec!["The key file contents are required".to_string()]
+        );
+        assert!(key_file.write_key(&keys).is_err());
+
+        key_file.contents = Some(Passphrase::new(""));
+        assert_eq!(
+            key_file.validate(None, true),
+            vec!["The encryption key is empty".to_string()]
+        );
+
+        EncryptionKeyFile::remove_key(&keys).unwrap();
+        assert!(!std::path::Path::new(&source).exists());
+        EncryptionKeyFile::remove_key(&keys).unwrap();
+    }
+
+    fn patch(alignment: Option<PartitionAlignment>, volumes: Vec<Volume>) -> ProposalSettingsPatch {
+        ProposalSettingsPatch {
+            volumes: Some(volumes),
+            alignment,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_align_volumes() {
+        let mib = 1024 * 1024;
+        let mut settings = patch(None, vec![volume("/home", 10 * mib + 1, Some(20 * mib))]);
+        let warnings = settings.align_volumes(&PartitionAlignment::Optimal);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("minimum size of /home"));
+
+        let volumes = settings.volumes.unwrap();
+        assert_eq!(volumes[0].min_size.as_ref().unwrap().0, 11 * mib);
+        assert_eq!(volumes[0].max_size.as_ref().unwrap().0, 20 * mib);
+    }
+
+    #[test]
+    fn test_align_volumes_explicit() {
+        let alignment = PartitionAlignment::Explicit(4096);
+        let mut settings = patch(Some(alignment), vec![volume("/", 8192, None)]);
+        assert!(settings.validate_alignment().is_empty());
+        assert!(settings
+            .align_volumes(&PartitionAlignment::Cylinder)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_apply_fs_types() {
+        let mut root = volume("/", 1024, None);
+        root.outline = Some(VolumeOutline {
+            required: true,
+            fs_types: vec!["btrfs".to_string(), "ext4".to_string(), "xfs".to_string()],
+            support_auto_size: false,
+            adjust_by_ram: false,
+            snapshots_configurable: false,
+            snapshots_affect_sizes: false,
+            size_relevant_volumes: vec![],
+        });
+
+        let mut settings = ProposalSettingsPatch {
+            root_fs_type: Some("ext4".to_string()),
+            ..Default::default()
+        };
+        assert!(settings.apply_fs_types(vec![root.clone()]).is_empty());
+        assert_eq!(settings.volumes.unwrap()[0].fs_type, "ext4");
+
+        let mut settings = ProposalSettingsPatch {
+            root_fs_type: Some("swap".to_string()),
+            home_fs_type: Some("xfs".to_string()),
+            ..Default::default()
+        };
+        let issues = settings.apply_fs_types(vec![root]);
+        assert_eq!(issues.len(), 2);
+        assert!(issues[1].contains("no /home volume"));
+    }
+
+    #[test]
+    fn test_keep_filesystem() {
+        let mut home = volume("/home", 1024, None);
+        assert!(home.formats());
+
+        home.keep_filesystem("/dev/sda3", "ext4");
+        assert!(!home.formats());
+        assert_eq!(home.target_device(), Some("/dev/sda3"));
+        assert_eq!(home.fs_type(), "ext4");
+    }
+
+    #[test]
+    fn test_validate_alignment() {
+        let settings = patch(Some(PartitionAlignment::Explicit(1000)), vec![]);
+        assert_eq!(settings.validate_alignment().len(), 1);
+    }
+
+    #[test]
+    fn test_validate_mount_by() {
+        let mut sda = block_device(1, "/dev/sda", vec![]);
+        sda.block_device.as_mut().unwrap().udev_ids = vec!["ata-disk1".to_string()];
+        let devices = vec![sda];
+
+        let mut root = volume("/", 1024, None);
+        root.label = Some("root".to_string());
+        let mut home = volume("/home", 1024, None);
+        home.label = Some("root".to_string());
+        let swap = volume("swap", 1024, None);
+        let mut settings = patch(None, vec![root, home, swap]);
+        settings.target_device = Some("/dev/sda".to_string());
+        assert!(!settings.sets_mount_by());
+        assert!(settings.validate_mount_by(None, &devices).is_empty());
+
+        settings.mount_by = Some(MountBy::Label);
+        assert_eq!(
+            settings.validate_mount_by(None, &devices),
+            vec![
+                "The label 'root' of /home is not unique",
+                "swap cannot be mounted by label because it has no label",
+            ]
+        );
+
+        let volumes = settings.volumes.as_mut().unwrap();
+        volumes[1].mount_by = Some(MountBy::Id);
+        volumes[2].mount_by = Some(MountBy::Path);
+        assert_eq!(
+            settings.validate_mount_by(None, &devices),
+            vec!["swap cannot be mounted by path because the device /dev/sda has none"]
+        );
+
+        settings.target = Some(ProposalTarget::NewLvmVg);
+        assert_eq!(
+            settings.validate_mount_by(None, &devices),
+            vec!["swap cannot be mounted by path because it is a logical volume"]
+        );
+    }
+
+    #[test]
+    fn test_validate_read_only_root() {
+        let mut root = volume("/", 1024, None);
+        let mut var = volume("/var", 1024, None);
+        let volumes = vec![root.clone(), var.clone(), volume("swap", 1024, None)];
+        let mut settings = ReadOnlyRootSettings {
+            enabled: true,
+            overlay: None,
+        };
+        assert_eq!(
+            settings.validate(&volumes),
+            vec!["A read-only root file system requires an overlay directory"]
+        );
+
+        settings.overlay = Some("/var/lib/overlay".to_string());
+        assert!(settings.validate(&volumes).is_empty());
+        assert_eq!(settings.overlay(), Some("/var/lib/overlay"));
+
+        settings.overlay = Some("/variable".to_string());
+        assert_eq!(
+            settings.validate(&volumes),
+            vec!["The overlay directory /variable must be on a writable volume, not on the read-only root"]
+        );
+
+        root.transactional = Some(true);
+        var.fs_type = "vfat".to_string();
+        settings.overlay = Some("/var/".to_string());
+        assert_eq!(
+            settings.validate(&[root, var]),
+            vec![
+                "The root file system is transactional, so it is already read-only",
+                "The overlay directory /var cannot be on /var because vfat does not support overlays (supported: btrfs, ext4, xfs)",
+            ]
+        );
+
+        settings.enabled = false;
+        assert!(settings.validate(&[]).is_empty());
+        assert_eq!(settings.overlay(), None);
+    }
+
+    #[test]
+    fn test_validate_caches() {
+        let mut nvme = block_device(2, "/dev/nvme0n1", vec![]);
+        nvme.block_device.as_mut().unwrap().size = 512.into();
+        let mut sda = block_device(3, "/dev/sda", vec![]);
+        sda.block_device.as_mut().unwrap().size = 4096.into();
+        let windows = block_device(4, "/dev/sdb", vec!["Windows"]);
+        let devices = vec![nvme, sda, windows];
+
+        let cache = |cache: &str, backing: &str| CacheSettings {
+            cache_device: cache.to_string(),
+            backing_device: backing.to_string(),
+            method: CacheMethod::Bcache,
+            mode: CacheMode::Writeback,
+        };
+        let (errors, warnings) =
+            CacheSettings::validate(&[cache("/dev/nvme0n1", "/dev/sda")], &devices);
+        assert!(errors.is_empty());
+        assert!(warnings.is_empty());
+
+        let (errors, warnings) =
+            CacheSettings::validate(&[cache("/dev/sda", "/dev/nvme0n1")], &devices);
+        assert_eq!(
+            errors,
+            vec!["The cache device /dev/sda must be smaller than the backing device /dev/nvme0n1"]
+        );
+        assert_eq!(
+            warnings,
+            vec!["The cache device /dev/sda seems slower than the backing device /dev/nvme0n1"]
+        );
+
+        let (errors, _) = CacheSettings::validate(
+            &[
+                cache("/dev/nvme0n1", "/dev/sdb"),
+                cache("/dev/nvme0n1", "/dev/sdc"),
+                cache("/dev/sda", "/dev/sda"),
+            ],
+            &devices,
+        );
+        assert_eq!(
+            errors,
+            vec![
+                "/dev/sdb is in use",
+                "/dev/nvme0n1 is used by more than one cache",
+                "Unknown device /dev/sdc",
+                "/dev/sda cannot be the cache of itself",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_explicit_partitions() {
+        let mib = 1024 * 1024;
+        let mut disk = block_device(1, "/dev/sda", vec![]);
+        disk.partition_table = Some(PartitionTable {
+            ptable_type: "gpt".to_string(),
+            partitions: vec![],
+            unused_slots: vec![UnusedSlot {
+                start: 2048,
+                size: (100 * mib).into(),
+            }],
+        });
+        let devices = vec![disk, block_device(2, "/dev/sdb", vec![])];
+
+        let partition = |device: &str, region: PartitionRegion| ExplicitPartition {
+            device: device.to_string(),
+            region,
+            fs_type: None,
+            mount_path: None,
+        };
+        let mut partitions = vec![
+            partition(
+                "/dev/sda",
+                PartitionRegion::Bytes {
+                    offset: mib,
+                    size: 10 * mib,
+                },
+            ),
+            partition(
+                "/dev/sda",
+                PartitionRegion::Sectors {
+                    start: 22529,
+                    end: 40000,
+                },
+            ),
+        ];
+        let (errors, warnings) =
+            ExplicitPartition::resolve(&mut partitions, &devices, &PartitionAlignment::Optimal);
+        assert!(errors.is_empty());
+        assert_eq!(
+            warnings,
+            vec![
+                "The partition at sectors 22529-40000 of /dev/sda is not aligned to 1048576 bytes"
+            ]
+        );
+        assert_eq!(
+            partitions[0].region,
+            PartitionRegion::Sectors {
+                start: 2048,
+                end: 22527
+            }
+        );
+
+        let mut partitions = vec![
+            partition(
+                "/dev/sda",
+                PartitionRegion::Sectors {
+                    start: 0,
+                    end: 2047,
+                },
+            ),
+            partition(
+                "/dev/sda",
+                PartitionRegion::Sectors {
+                    start: 4096,
+                    end: 8191,
+                },
+            ),
+            partition(
+                "/dev/sda",
+                PartitionRegion::Sectors {
+                    start: 6144,
+                    end: 10239,
+                },
+            ),
+            partition("/dev/sda", PartitionRegion::Sectors { start: 10, end: 5 }),
+            partition("/dev/sdb", PartitionRegion::Sectors { start: 0, end: 10 }),
+            partition("/dev/sdc", PartitionRegion::Sectors { start: 0, end: 10 }),
+        ];
+        let (errors, _) =
+            ExplicitPartition::resolve(&mut partitions, &devices, &PartitionAlignment::Optimal);
+        assert_eq!(
+            errors,
+            vec![
+                "The sectors 0-2047 of /dev/sda are not free",
+                "The sectors 6144-10239 of /dev/sda overlap with another partition",
+                "Wrong region for a partition in /dev/sda: The end sector (5) is before the start sector (10)",
+                "/dev/sdb has no partition table",
+                "Unknown disk /dev/sdc",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_partition_region_json() {
+        let partition: ExplicitPartition =
+            serde_json::from_str(r#"{ "device": "/dev/sda", "offset": 1024, "size": 512 }"#)
+                .unwrap();
+        assert_eq!(
+            partition.region,
+            PartitionRegion::Bytes {
+                offset: 1024,
+                size: 512
+            }
+        );
+        assert_eq!(partition.region.sectors(512), Ok((2, 2)));
+        assert!(partition.region.sectors(4096).is_err());
+        assert!(partition.region.sectors(0).is_err());
+
+        let region = PartitionRegion::Sectors {
+            start: 0,
+            end: u64::MAX,
+        };
+        assert!(region.sectors(512).is_err());
+    }
+
+    #[test]
+    fn test_validate_target_device() {
+        let sda = drive(1, "/dev/sda", "sata");
+        let devices = vec![sda.clone(), block_device(2, "/dev/sdb", vec!["Windows"])];
+        let mut facts = TargetDeviceFacts {
+            min_size: 512,
+            ..Default::default()
+        };
+
+        let validation = DeviceValidation::check(&sda, &devices, &facts);
+        assert_eq!(validation.status, DeviceValidationStatus::Pass);
+        assert!(validation.reasons.is_empty());
+
+        let validation = DeviceValidation::check(&devices[1], &devices, &facts);
+        assert_eq!(validation.status, DeviceValidationStatus::Warn);
+        assert_eq!(validation.reasons[0].code, "inUse");
+
+        facts.min_size = 2048;
+        facts.read_only = true;
+        let mut failing = sda.clone();
+        if let Some(drive) = failing.drive.as_mut() {
+            drive.smart.health = SmartHealth::Failing;
+            drive.transport = "usb".to_string();
+        }
+        let validation = DeviceValidation::check(&failing, &devices, &facts);
+        assert_eq!(validation.status, DeviceValidationStatus::Fail);
+        let codes: Vec<_> = validation.reasons.iter().map(|r| r.code.as_str()).collect();
+        assert_eq!(
+            codes,
+            vec!["tooSmall", "readOnly", "smartFailing", "removable"]
+        );
+    }
+
+    #[test]
+    fn test_volume_min_size_in() {
+        let mut root = volume("/", 1024, None);
+        assert_eq!(root.min_size_in("/dev/sda"), 1024);
+        root.target_device = Some("/dev/sdb".to_string());
+        assert_eq!(root.min_size_in("/dev/sda"), 0);
+        root.target = VolumeTarget::Filesystem;
+        assert_eq!(root.min_size_in("/dev/sdb"), 0);
+    }
+
+    #[test]
+    fn test_validate_size_ranges() {
+        let root = volume("/", 2048, Some(1024));
+        let mut home = volume("/home", 4096, None);
+        home.weight = Some(1);
+        let settings = patch(None, vec![root, home.clone()]);
+        assert_eq!(
+            settings.validate_size_ranges(Some(("/dev/sda", 4096))),
+            vec![
+                "The minimum size of / (2048 bytes) is bigger than its maximum size (1024 bytes)"
+                    .to_string(),
+                "The minimum sizes of the volumes (6144 bytes) do not fit in /dev/sda (4096 bytes)"
+                    .to_string()
+            ]
+        );
+
+        home.auto_size = true;
+        let settings = patch(None, vec![home]);
+        assert_eq!(
+            settings.validate_size_ranges(None),
+            vec!["A weight cannot be used with the automatic sizes of /home".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_validate_quotas() {
+        let limit = |path: &str, size: u64| QuotaLimit {
+            path: path.to_string(),
+            size: size.into(),
+        };
+        let mut home = volume("/home", 4096, None);
+        home.quota = Some(VolumeQuota {
+            quota_type: QuotaType::Project,
+            limits: vec![
+                limit("alice", 1024),
+                limit("alice/", 2048),
+                limit("../etc", 0),
+            ],
+        });
+        let mut root = volume("/", 4096, None);
+        root.quota = Some(VolumeQuota {
+            quota_type: QuotaType::Project,
+            limits: vec![],
+        });
+        root.fs_type = "btrfs".to_string();
+        let settings = patch(None, vec![home.clone(), root]);
+        assert_eq!(
+            settings.validate_quotas(),
+            vec![
+                "The quota path 'alice/' of /home is duplicated".to_string(),
+                "The quota path '../etc' of /home must be relative to its mount point".to_string(),
+                "The quota limit of '../etc' in /home cannot be zero".to_string(),
+                "The file system 'btrfs' of / does not support XFS project quotas".to_string(),
+            ]
+        );
+
+        let value: Value = home.into();
+        let volume = Volume::try_from(value).unwrap();
+        assert_eq!(volume.mount_options, vec![PROJECT_QUOTA_OPTION.to_string()]);
+        assert_eq!(volume.quota.unwrap().limits.len(), 3);
+
+        // the storage service replies with variant arrays instead of structs
+        let limits = vec![Value::new(vec![
+            Value::new("var/log"),
+            Value::new(1024_i32),
+        ])];
+        let reply = HashMap::from([
+            ("Type", Value::new("qgroups")),
+            ("Limits", Value::new(limits)),
+        ]);
+        let quota = VolumeQuota::try_from(Value::new(reply)).unwrap();
+        assert_eq!(quota.quota_type, QuotaType::Qgroups);
+        assert_eq!(quota.limits[0].path, "var/log");
+        assert_eq!(quota.limits[0].size.bytes(), 1024);
+    }
+
+    #[test]
+    fn test_disk_selection() {
+        let validation = |device: &str, status: DeviceValidationStatus| DeviceValidation {
+            device: device.to_string(),
+            status,
+            reasons: vec![DeviceValidationReason::new(
+                "tooSmall",
+                status,
+                format!("{} is too small", device),
+            )],
+        };
+        let preferred = vec![
+            "/dev/sda".to_string(),
+            "/dev/sdb".to_string(),
+            "/dev/sdc".to_string(),
+        ];
+
+        let mut selection = DiskSelection::new(
+            &preferred,
+            vec![
+                validation("/dev/sda", DeviceValidationStatus::Fail),
+                validation("/dev/sdb", DeviceValidationStatus::Warn),
+                validation("/dev/sdc", DeviceValidationStatus::Pass),
+            ],
+        );
+        assert_eq!(selection.preferred, preferred);
+        assert_eq!(selection.candidates(), preferred[1..].to_vec());
+
+        selection.reject("/dev/sdb");
+        selection.selected = Some("/dev/sdc".to_string());
+        assert_eq!(selection.candidates(), preferred[2..].to_vec());
+        assert_eq!(
+            selection.warnings(),
+            vec![
+                "Using /dev/sdc instead of /dev/sda: /dev/sda is too small".to_string(),
+                "Using /dev/sdc instead of /dev/sdb: The proposal cannot be calculated on /dev/sdb"
+                    .to_string()
+            ]
+        );
+
+        let selection = DiskSelection::new(
+            &preferred[..1],
+            vec![validation("/dev/sda", DeviceValidationStatus::Fail)],
+        );
+        assert_eq!(selection.selected, None);
+        assert!(selection.candidates().is_empty());
+        assert!(selection.warnings().is_empty());
+        assert_eq!(
+            selection.failures(),
+            vec!["/dev/sda is too small".to_string()]
+        );
+    }
+}
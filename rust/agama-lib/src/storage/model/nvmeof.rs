@@ -0,0 +1,345 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements a data model for NVMe over Fabrics (NVMe-oF) targets management.
+use std::{collections::HashMap, net::IpAddr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use zbus::zvariant::{OwnedValue, Value};
+
+use crate::{dbus::get_property, error::ServiceError};
+
+/// Port of the discovery controllers (`trsvcid`) when it is not set.
+pub const DEFAULT_DISCOVERY_PORT: u16 = 8009;
+/// Port of the I/O controllers (`trsvcid`) when it is not set.
+pub const DEFAULT_PORT: u16 = 4420;
+/// Maximum length of an NVMe Qualified Name.
+const MAX_NQN_LENGTH: usize = 223;
+
+/// Transport to reach an NVMe-oF target.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum NVMeoFTransport {
+    #[default]
+    Tcp,
+    Rdma,
+}
+
+impl NVMeoFTransport {
+    /// Transport name, as used by nvme-cli.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Tcp => "tcp",
+            Self::Rdma => "rdma",
+        }
+    }
+}
+
+impl TryFrom<&str> for NVMeoFTransport {
+    type Error = ServiceError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "tcp" => Ok(Self::Tcp),
+            "rdma" => Ok(Self::Rdma),
+            _ => Err(ServiceError::InternalError(format!(
+                "Unknown NVMe-oF transport: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// NVMe-oF target (a subsystem exported through a transport address).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct NVMeoFTarget {
+    /// Transport (TCP by default).
+    #[serde(default)]
+    pub transport: NVMeoFTransport,
+    /// Transport address (IP address of the target).
+    pub traddr: String,
+    /// Transport service ID (port). The default one for the operation is used if it is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trsvcid: Option<String>,
+    /// NVMe Qualified Name of the subsystem.
+    #[serde(default)]
+    pub subnqn: String,
+}
+
+impl NVMeoFTarget {
+    /// Returns the list of problems found in the address of the target.
+    ///
+    /// The address must be an IP address and the service ID a port number.
+    pub fn validate_address(&self) -> Vec<String> {
+        let mut issues = vec![];
+        if self.traddr.parse::<IpAddr>().is_err() {
+            issues.push(format!(
+                "Invalid {} transport address: '{}'",
+                self.transport.name(),
+                self.traddr
+            ));
+        }
+        if let Some(trsvcid) = &self.trsvcid {
+            if trsvcid.parse::<u16>().map_or(true, |p| p == 0) {
+                issues.push(format!("Invalid transport service ID: '{}'", trsvcid));
+            }
+        }
+        issues
+    }
+
+    /// Returns the list of problems found in the target to connect to.
+    ///
+    /// Apart from the address, the subsystem NQN must be valid.
+    pub fn validate(&self) -> Vec<String> {
+        let mut issues = self.validate_address();
+        if !self.subnqn.starts_with("nqn.") || self.subnqn.len() > MAX_NQN_LENGTH {
+            issues.push(format!("Invalid subsystem NQN: '{}'", self.subnqn));
+        }
+        issues
+    }
+
+    /// Returns the transport service ID, using the given port if it is not set.
+    ///
+    /// * `default`: default port.
+    pub fn service_id(&self, default: u16) -> String {
+        self.trsvcid.clone().unwrap_or_else(|| default.to_string())
+    }
+}
+
+impl TryFrom<&HashMap<String, OwnedValue>> for NVMeoFTarget {
+    type Error = ServiceError;
+
+    fn try_from(value: &HashMap<String, OwnedValue>) -> Result<Self, Self::Error> {
+        let transport: String = get_property(value, "Transport")?;
+        Ok(NVMeoFTarget {
+            transport: transport.as_str().try_into()?,
+            traddr: get_property(value, "TrAddr")?,
+            trsvcid: Some(get_property(value, "TrSvcId")?),
+            subnqn: get_property(value, "SubNQN")?,
+        })
+    }
+}
+
+/// NVMe-oF controller connected to a target.
+#[derive(Clone, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct NVMeoFController {
+    /// Controller name (e.g., "nvme1").
+    pub name: String,
+    #[serde(flatten)]
+    pub target: NVMeoFTarget,
+    /// Controller state (e.g., "live" or "connecting").
+    pub state: String,
+}
+
+impl TryFrom<&HashMap<String, OwnedValue>> for NVMeoFController {
+    type Error = ServiceError;
+
+    fn try_from(value: &HashMap<String, OwnedValue>) -> Result<Self, Self::Error> {
+        Ok(NVMeoFController {
+            name: get_property(value, "Name")?,
+            target: value.try_into()?,
+            state: get_property(value, "State")?,
+        })
+    }
+}
+
+/// Outcome of an NVMe-oF operation (discovery, connection or disconnection).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+pub enum NVMeoFResult {
+    /// Successful operation.
+    Success = 0,
+    /// Failed operation (generic error).
+    Failed = 1,
+    /// The target could not be reached.
+    Unreachable = 2,
+    /// The subsystem is not exported by the target.
+    SubsystemNotFound = 3,
+    /// There is already a controller connected to the subsystem.
+    AlreadyConnected = 4,
+    /// The transport is not available in the system (e.g., there is no RDMA device).
+    TransportUnavailable = 5,
+    /// There is no controller with the given name.
+    UnknownController = 6,
+}
+
+impl NVMeoFResult {
+    /// Classifies a generic failure according to the details reported by the backend (usually,
+    /// the nvme-cli output).
+    ///
+    /// * `detail`: description of the failure.
+    pub fn from_detail(detail: &str) -> Self {
+        let detail = detail.to_lowercase();
+        let matches = |patterns: &[&str]| patterns.iter().any(|p| detail.contains(p));
+        if matches(&["already connected", "operation already in progress"]) {
+            Self::AlreadyConnected
+        } else if matches(&[
+            "no such device",
+            "failed to load",
+            "module",
+            "not supported",
+        ]) {
+            Self::TransportUnavailable
+        } else if matches(&[
+            "no route to host",
+            "network is unreachable",
+            "connection refused",
+            "timed out",
+        ]) {
+            Self::Unreachable
+        } else if matches(&["invalid subsystem", "subsystem not found", "not found"]) {
+            Self::SubsystemNotFound
+        } else {
+            Self::Failed
+        }
+    }
+}
+
+/// Failed NVMe-oF operation.
+#[derive(Clone, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct NVMeoFError {
+    pub code: NVMeoFResult,
+    /// Transport used to reach the target.
+    pub transport: NVMeoFTransport,
+    /// Details of the failure, if the backend reports them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl NVMeoFError {
+    /// Builds the error from the result reported by the backend.
+    ///
+    /// * `code`: result code.
+    /// * `transport`: transport used to reach the target.
+    /// * `message`: details of the failure.
+    pub fn new(code: NVMeoFResult, transport: NVMeoFTransport, message: String) -> Self {
+        let message = Some(message).filter(|m| !m.is_empty());
+        let code = match (&code, &message) {
+            (NVMeoFResult::Failed, Some(message)) => NVMeoFResult::from_detail(message),
+            _ => code,
+        };
+        Self {
+            code,
+            transport,
+            message,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+#[error("Invalid NVMe-oF result: {0}")]
+pub struct InvalidNVMeoFResult(u32);
+
+impl TryFrom<u32> for NVMeoFResult {
+    type Error = InvalidNVMeoFResult;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            v if v == Self::Success as u32 => Ok(Self::Success),
+            v if v == Self::Failed as u32 => Ok(Self::Failed),
+            v if v == Self::Unreachable as u32 => Ok(Self::Unreachable),
+            v if v == Self::SubsystemNotFound as u32 => Ok(Self::SubsystemNotFound),
+            v if v == Self::AlreadyConnected as u32 => Ok(Self::AlreadyConnected),
+            v if v == Self::TransportUnavailable as u32 => Ok(Self::TransportUnavailable),
+            v if v == Self::UnknownController as u32 => Ok(Self::UnknownController),
+            _ => Err(InvalidNVMeoFResult(value)),
+        }
+    }
+}
+
+/// Converts a target into the arguments to send to D-Bus.
+impl<'a> From<&'a NVMeoFTarget> for HashMap<&'static str, Value<'a>> {
+    fn from(target: &'a NVMeoFTarget) -> Self {
+        let mut data = HashMap::new();
+        data.insert("Transport", target.transport.name().into());
+        data.insert("TrAddr", target.traddr.as_str().into());
+        if let Some(trsvcid) = &target.trsvcid {
+            data.insert("TrSvcId", trsvcid.as_str().into());
+        }
+        if !target.subnqn.is_empty() {
+            data.insert("SubNQN", target.subnqn.as_str().into());
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(transport: NVMeoFTransport, traddr: &str, trsvcid: Option<&str>) -> NVMeoFTarget {
+        NVMeoFTarget {
+            transport,
+            traddr: traddr.to_string(),
+            trsvcid: trsvcid.map(|t| t.to_string()),
+            subnqn: "nqn.2014-08.org.nvmexpress:uuid:1b4e28ba-2fa1-11d2-883f-0016d3cca427"
+                .to_string(),
+        }
+    }
+
+    #[test]
+    fn test_validate_target() {
+        assert!(target(NVMeoFTransport::Tcp, "192.168.1.10", Some("4420"))
+            .validate()
+            .is_empty());
+        assert!(target(NVMeoFTransport::Rdma, "fd00::10", None)
+            .validate()
+            .is_empty());
+
+        let wrong = NVMeoFTarget {
+            subnqn: "iqn.2024-01.com.example".to_string(),
+            ..target(NVMeoFTransport::Rdma, "storage.example.com", Some("0"))
+        };
+        assert_eq!(
+            wrong.validate(),
+            vec![
+                "Invalid rdma transport address: 'storage.example.com'",
+                "Invalid transport service ID: '0'",
+                "Invalid subsystem NQN: 'iqn.2024-01.com.example'",
+            ]
+        );
+        assert_eq!(wrong.validate_address().len(), 2);
+    }
+
+    #[test]
+    fn test_error_from_detail() {
+        let error = NVMeoFError::new(
+            NVMeoFResult::Failed,
+            NVMeoFTransport::Tcp,
+            "Failed to write to /dev/nvme-fabrics: Connection refused".to_string(),
+        );
+        assert_eq!(error.code, NVMeoFResult::Unreachable);
+
+        let error = NVMeoFError::new(
+            NVMeoFResult::Failed,
+            NVMeoFTransport::Rdma,
+            "Failed to write to /dev/nvme-fabrics: No such device".to_string(),
+        );
+        assert_eq!(error.code, NVMeoFResult::TransportUnavailable);
+
+        let error = NVMeoFError::new(
+            NVMeoFResult::AlreadyConnected,
+            NVMeoFTransport::Tcp,
+            "".to_string(),
+        );
+        assert_eq!(error.code, NVMeoFResult::AlreadyConnected);
+        assert_eq!(error.message, None);
+    }
+}
@@ -44,6 +44,9 @@ trait Storage1 {
     /// Get the current storage config according to the JSON schema
     fn get_config(&self) -> zbus::Result<String>;
 
+    /// Unlock an existing encrypted device
+    fn unlock_device(&self, device: &str, passphrase: &str) -> zbus::Result<u32>;
+
     /// DeprecatedSystem property
     #[dbus_proxy(property)]
     fn deprecated_system(&self) -> zbus::Result<bool>;
@@ -124,11 +127,31 @@ trait Initiator {
     #[dbus_proxy(property, name = "IBFT")]
     fn ibft(&self) -> zbus::Result<bool>;
 
+    /// Supported property
+    #[dbus_proxy(property)]
+    fn supported(&self) -> zbus::Result<bool>;
+
     /// InitiatorName property
     #[dbus_proxy(property)]
     fn initiator_name(&self) -> zbus::Result<String>;
+
+    /// Sessions property (live sessions held by the system, including the iBFT ones)
+    #[dbus_proxy(property)]
+    fn sessions(
+        &self,
+    ) -> zbus::Result<Vec<std::collections::HashMap<String, zbus::zvariant::OwnedValue>>>;
     #[dbus_proxy(property)]
     fn set_initiator_name(&self, value: &str) -> zbus::Result<()>;
+
+    /// Interfaces property
+    #[dbus_proxy(property)]
+    fn interfaces(&self) -> zbus::Result<Vec<String>>;
+
+    /// InterfacesDetails property
+    #[dbus_proxy(property)]
+    fn interfaces_details(
+        &self,
+    ) -> zbus::Result<Vec<std::collections::HashMap<String, zbus::zvariant::OwnedValue>>>;
 }
 
 #[dbus_proxy(
@@ -146,6 +169,12 @@ trait Node {
     /// Logout method
     fn logout(&self) -> zbus::Result<u32>;
 
+    /// TestAuth method
+    fn test_auth(
+        &self,
+        options: std::collections::HashMap<&str, &zbus::zvariant::Value<'_>>,
+    ) -> zbus::Result<u32>;
+
     /// Address property
     #[dbus_proxy(property)]
     fn address(&self) -> zbus::Result<String>;
@@ -162,6 +191,16 @@ trait Node {
     #[dbus_proxy(property)]
     fn interface(&self) -> zbus::Result<String>;
 
+    /// LoginError property (details of the last failed login)
+    #[dbus_proxy(property)]
+    fn login_error(&self) -> zbus::Result<String>;
+
+    /// Session property (details of the active session, empty if not connected)
+    #[dbus_proxy(property)]
+    fn session(
+        &self,
+    ) -> zbus::Result<std::collections::HashMap<String, zbus::zvariant::OwnedValue>>;
+
     /// Port property
     #[dbus_proxy(property)]
     fn port(&self) -> zbus::Result<u32>;
@@ -321,3 +360,34 @@ trait Disk {
     #[dbus_proxy(property, name = "WWPN")]
     fn wwpn(&self) -> zbus::Result<String>;
 }
+
+/// NVMe-oF targets or controllers as they come from D-Bus.
+pub type NVMeoFRecords = Vec<std::collections::HashMap<String, zbus::zvariant::OwnedValue>>;
+
+#[dbus_proxy(
+    interface = "org.opensuse.Agama.Storage1.NVMeoF.Manager",
+    default_service = "org.opensuse.Agama.Storage1",
+    default_path = "/org/opensuse/Agama/Storage1"
+)]
+trait NVMeoFManager {
+    /// Discover method
+    ///
+    /// It returns the result code, the details of the failure and the discovered targets.
+    fn discover(
+        &self,
+        options: std::collections::HashMap<&str, zbus::zvariant::Value<'_>>,
+    ) -> zbus::Result<(u32, String, NVMeoFRecords)>;
+
+    /// Connect method
+    fn connect(
+        &self,
+        options: std::collections::HashMap<&str, zbus::zvariant::Value<'_>>,
+    ) -> zbus::Result<(u32, String)>;
+
+    /// Disconnect method
+    fn disconnect(&self, name: &str) -> zbus::Result<(u32, String)>;
+
+    /// Controllers property
+    #[dbus_proxy(property)]
+    fn controllers(&self) -> zbus::Result<NVMeoFRecords>;
+}
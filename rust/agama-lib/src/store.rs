@@ -25,8 +25,8 @@ use crate::base_http_client::BaseHTTPClient;
 use crate::error::ServiceError;
 use crate::install_settings::InstallSettings;
 use crate::{
-    localization::LocalizationStore, network::NetworkStore, product::ProductStore,
-    software::SoftwareStore, storage::StorageStore, users::UsersStore,
+    bootloader::store::BootloaderStore, localization::LocalizationStore, network::NetworkStore,
+    product::ProductStore, software::SoftwareStore, storage::StorageStore, users::UsersStore,
 };
 
 /// Struct that loads/stores the settings from/to the D-Bus services.
@@ -36,6 +36,7 @@ use crate::{
 ///
 /// This struct uses the default connection built by [connection function](super::connection).
 pub struct Store {
+    bootloader: BootloaderStore,
     users: UsersStore,
     network: NetworkStore,
     product: ProductStore,
@@ -47,6 +48,7 @@ pub struct Store {
 impl Store {
     pub async fn new(http_client: BaseHTTPClient) -> Result<Store, ServiceError> {
         Ok(Self {
+            bootloader: BootloaderStore::new(http_client.clone())?,
             localization: LocalizationStore::new(http_client.clone())?,
             users: UsersStore::new(http_client.clone())?,
             network: NetworkStore::new(http_client.clone()).await?,
@@ -59,6 +61,7 @@ impl Store {
     /// Loads the installation settings from the HTTP interface.
     pub async fn load(&self) -> Result<InstallSettings, ServiceError> {
         let mut settings = InstallSettings {
+            bootloader: Some(self.bootloader.load().await?),
             network: Some(self.network.load().await?),
             software: Some(self.software.load().await?),
             user: Some(self.users.load().await?),
@@ -98,6 +101,9 @@ impl Store {
         if settings.storage.is_some() || settings.storage_autoyast.is_some() {
             self.storage.store(&settings.into()).await?
         }
+        if let Some(bootloader) = &settings.bootloader {
+            self.bootloader.store(bootloader).await?;
+        }
         Ok(())
     }
 }
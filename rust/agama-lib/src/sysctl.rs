@@ -0,0 +1,136 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements support for the kernel tunables (sysctl) of the installed system.
+//!
+//! The tunables are written to the `sysctl.d` directory of the target system.
+
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+
+/// Deserializes the sysctl values, which can be strings or numbers.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<BTreeMap<String, String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SysctlValue {
+        String(String),
+        Number(serde_json::Number),
+    }
+
+    let values = BTreeMap::<String, SysctlValue>::deserialize(deserializer)?;
+    Ok(values
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                SysctlValue::String(value) => value,
+                SysctlValue::Number(value) => value.to_string(),
+            };
+            (key, value)
+        })
+        .collect())
+}
+
+/// Numeric sysctl keys and their valid range.
+const NUMERIC_SYSCTL_KEYS: [(&str, i64, i64); 12] = [
+    ("vm.swappiness", 0, 200),
+    ("vm.dirty_ratio", 0, 100),
+    ("vm.dirty_background_ratio", 0, 100),
+    ("vm.overcommit_memory", 0, 2),
+    ("vm.overcommit_ratio", 0, i64::MAX),
+    ("vm.max_map_count", 1, i32::MAX as i64),
+    ("vm.vfs_cache_pressure", 0, i64::MAX),
+    ("fs.file-max", 1, i64::MAX),
+    ("kernel.pid_max", 301, 4_194_304),
+    ("net.core.somaxconn", 1, i32::MAX as i64),
+    ("net.ipv4.ip_forward", 0, 1),
+    ("net.ipv6.conf.all.forwarding", 0, 1),
+];
+
+/// sysctl keys which are rejected because they could break or weaken the installed system.
+const DANGEROUS_SYSCTL_KEYS: [(&str, &str); 3] = [
+    (
+        "kernel.modules_disabled",
+        "no kernel module could be loaded",
+    ),
+    ("vm.drop_caches", "it is an action, not a tunable"),
+    (
+        "kernel.core_pattern",
+        "it could pipe the core dumps to any program",
+    ),
+];
+
+/// Whether the given string looks like a sysctl key (e.g., "net.ipv4.conf.eth0.rp_filter").
+///
+/// It is a list of non-empty components separated by dots. The components can only contain
+/// alphanumeric characters, dashes and underscores.
+fn is_valid_sysctl_key(key: &str) -> bool {
+    key.contains('.')
+        && key.split('.').all(|c| {
+            !c.is_empty()
+                && c.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+        })
+}
+
+/// Returns the list of problems found in the sysctl tunables.
+pub fn validate(sysctl: &BTreeMap<String, String>) -> Vec<String> {
+    let mut issues = vec![];
+    for (key, value) in sysctl {
+        if !is_valid_sysctl_key(key) {
+            issues.push(format!("Invalid sysctl key: '{}'", key));
+            continue;
+        }
+        if let Some((_, reason)) = DANGEROUS_SYSCTL_KEYS.iter().find(|(k, _)| k == key) {
+            issues.push(format!(
+                "The sysctl key '{}' is not allowed: {}",
+                key, reason
+            ));
+            continue;
+        }
+        if value.trim().is_empty() || value.chars().any(|c| c.is_control()) {
+            issues.push(format!(
+                "Invalid value for sysctl key '{}': '{}'",
+                key, value
+            ));
+            continue;
+        }
+        if let Some((_, min, max)) = NUMERIC_SYSCTL_KEYS.iter().find(|(k, _, _)| k == key) {
+            match value.trim().parse::<i64>() {
+                Ok(number) if (*min..=*max).contains(&number) => {}
+                Ok(_) if *max == i64::MAX => issues.push(format!(
+                    "The value of sysctl key '{}' must be at least {}",
+                    key, min
+                )),
+                Ok(_) => issues.push(format!(
+                    "The value of sysctl key '{}' must be between {} and {}",
+                    key, min, max
+                )),
+                Err(_) => issues.push(format!(
+                    "The value of sysctl key '{}' must be a number, not '{}'",
+                    key, value
+                )),
+            }
+        }
+    }
+    issues
+}
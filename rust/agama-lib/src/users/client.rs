@@ -20,6 +20,7 @@
 
 //! Implements a client to access Agama's users service.
 
+use super::model::{UserAccount, UserExpiration, UserGroup};
 use super::proxies::{FirstUser as FirstUserFromDBus, Users1Proxy};
 use crate::error::ServiceError;
 use serde::{Deserialize, Serialize};
@@ -37,6 +38,12 @@ pub struct FirstUser {
     pub password: String,
     /// Whether auto-login should enabled or not
     pub autologin: bool,
+    /// Password and account expiration policy
+    #[serde(default, skip_serializing_if = "UserExpiration::is_default")]
+    pub expiration: UserExpiration,
+    /// User and group IDs and home directory (assigned by the system if they are not set)
+    #[serde(flatten)]
+    pub account: UserAccount,
     /// Additional data coming from the D-Bus service
     pub data: std::collections::HashMap<String, zbus::zvariant::OwnedValue>,
 }
@@ -44,11 +51,15 @@ pub struct FirstUser {
 impl FirstUser {
     pub fn from_dbus(dbus_data: zbus::Result<FirstUserFromDBus>) -> zbus::Result<Self> {
         let data = dbus_data?;
+        let expiration = UserExpiration::from_dbus(&data.4)?;
+        let account = UserAccount::from_dbus(&data.4)?;
         Ok(Self {
             full_name: data.0,
             user_name: data.1,
             password: data.2,
             autologin: data.3,
+            expiration,
+            account,
             data: data.4,
         })
     }
@@ -105,13 +116,15 @@ impl<'a> UsersClient<'a> {
         &self,
         first_user: &FirstUser,
     ) -> zbus::Result<(bool, Vec<String>)> {
+        let mut data = first_user.expiration.to_dbus();
+        data.extend(first_user.account.to_dbus());
         self.users_proxy
             .set_first_user(
                 &first_user.full_name,
                 &first_user.user_name,
                 &first_user.password,
                 first_user.autologin,
-                std::collections::HashMap::new(),
+                data,
             )
             .await
     }
@@ -119,4 +132,41 @@ impl<'a> UsersClient<'a> {
     pub async fn remove_first_user(&self) -> zbus::Result<bool> {
         Ok(self.users_proxy.remove_first_user().await? == 0)
     }
+
+    /// Returns the login shell for the users without an explicit one.
+    ///
+    /// It falls back to the system default when no default shell is configured or the service
+    /// does not support it.
+    pub async fn default_shell(&self) -> Result<String, ServiceError> {
+        let shell = self.users_proxy.default_shell().await.unwrap_or_default();
+        if shell.is_empty() {
+            return Ok(UserAccount::DEFAULT_SHELL.to_string());
+        }
+        Ok(shell)
+    }
+
+    /// Sets the login shell for the users without an explicit one.
+    ///
+    /// * `shell`: path to the shell.
+    pub async fn set_default_shell(&self, shell: &str) -> Result<(), ServiceError> {
+        Ok(self.users_proxy.set_default_shell(shell).await?)
+    }
+
+    /// Returns the groups to create in the target system.
+    pub async fn groups(&self) -> Result<Vec<UserGroup>, ServiceError> {
+        let serialized_string = self.users_proxy.get_groups().await?;
+        Ok(serde_json::from_str(&serialized_string)?)
+    }
+
+    /// Sets the groups to create in the target system, replacing the previous ones.
+    ///
+    /// * `groups`: groups to create.
+    pub async fn set_groups(&self, groups: &[UserGroup]) -> Result<(), ServiceError> {
+        let serialized_string = serde_json::to_string(groups)?;
+        let result = self.users_proxy.set_groups(&serialized_string).await?;
+        if result != 0 {
+            return Err(ServiceError::UnsuccessfulAction("Set groups".to_string()));
+        }
+        Ok(())
+    }
 }
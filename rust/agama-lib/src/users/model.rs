@@ -18,7 +18,33 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
+use crate::dbus::get_optional_property;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use zbus::zvariant::{self, OwnedValue, Value};
+
+/// Reads an unsigned number from the additional data of a user.
+///
+/// The users service sends the numbers as signed integers.
+///
+/// * `data`: additional user data.
+/// * `name`: name of the value.
+fn get_optional_u32(
+    data: &HashMap<String, OwnedValue>,
+    name: &str,
+) -> Result<Option<u32>, zvariant::Error> {
+    let Some(value) = data.get(name) else {
+        return Ok(None);
+    };
+    match Value::from(value) {
+        Value::U32(number) => Ok(Some(number)),
+        Value::I32(number) => u32::try_from(number)
+            .map(Some)
+            .map_err(|_| zvariant::Error::Message(format!("Invalid {}: {}", name, number))),
+        _ => Err(zvariant::Error::IncorrectType),
+    }
+}
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct RootConfig {
@@ -38,3 +64,419 @@ pub struct RootPatchSettings {
     /// specify if patched password is provided in encrypted form
     pub password_encrypted: Option<bool>,
 }
+
+/// Password and account expiration policy for a user.
+///
+/// The values map to the corresponding fields of the shadow(5) file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserExpiration {
+    /// Maximum number of days the password is valid
+    pub password_expire_days: Option<u32>,
+    /// Date when the account expires (in YYYY-MM-DD format)
+    pub account_expire_date: Option<String>,
+    /// Whether the account is locked
+    #[serde(default)]
+    pub locked: bool,
+}
+
+impl UserExpiration {
+    /// Maximum value for the password expiration (the shadow convention for "never").
+    pub const MAX_PASSWORD_EXPIRE_DAYS: u32 = 99999;
+    const DATE_FORMAT: &'static str = "%Y-%m-%d";
+
+    /// Whether no expiration policy is set.
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Returns the list of problems found in the expiration policy.
+    ///
+    /// An empty list means that the policy is valid.
+    pub fn validate(&self) -> Vec<String> {
+        let mut issues = vec![];
+
+        if let Some(days) = self.password_expire_days {
+            if days == 0 || days > Self::MAX_PASSWORD_EXPIRE_DAYS {
+                issues.push(format!(
+                    "Password expiration must be between 1 and {} days",
+                    Self::MAX_PASSWORD_EXPIRE_DAYS
+                ));
+            }
+        }
+
+        if let Some(date) = &self.account_expire_date {
+            match NaiveDate::parse_from_str(date, Self::DATE_FORMAT) {
+                Ok(date) if date > NaiveDate::default() => {}
+                Ok(_) => issues.push(format!("Account expiration date '{}' is too old", date)),
+                Err(_) => issues.push(format!(
+                    "Invalid account expiration date '{}' (expected YYYY-MM-DD)",
+                    date
+                )),
+            }
+        }
+
+        issues
+    }
+
+    /// Reads the expiration policy from the additional data of a user coming from D-Bus.
+    ///
+    /// * `data`: additional user data.
+    pub fn from_dbus(data: &HashMap<String, OwnedValue>) -> Result<Self, zvariant::Error> {
+        Ok(Self {
+            password_expire_days: get_optional_u32(data, "PasswordExpireDays")?,
+            account_expire_date: get_optional_property(data, "AccountExpireDate")?,
+            locked: get_optional_property(data, "Locked")?.unwrap_or_default(),
+        })
+    }
+
+    /// Converts the expiration policy into the additional user data to send to D-Bus.
+    pub fn to_dbus(&self) -> HashMap<&str, Value<'_>> {
+        let mut data: HashMap<&str, Value<'_>> = HashMap::new();
+        if let Some(days) = self.password_expire_days {
+            data.insert("PasswordExpireDays", days.into());
+        }
+        if let Some(date) = &self.account_expire_date {
+            data.insert("AccountExpireDate", date.as_str().into());
+        }
+        data.insert("Locked", self.locked.into());
+        data
+    }
+}
+
+/// Account identifiers, home directory and login shell for a user.
+///
+/// The values that are not set are assigned by the system.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+pub struct UserAccount {
+    /// User ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uid: Option<u32>,
+    /// ID of the primary group
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gid: Option<u32>,
+    /// Home directory (an absolute path)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub home: Option<String>,
+    /// Login shell (an absolute path); the default shell is used when it is not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shell: Option<String>,
+}
+
+impl UserAccount {
+    /// Lowest ID for regular users and groups (`UID_MIN` and `GID_MIN` in login.defs).
+    pub const MIN_ID: u32 = 1000;
+    /// Highest ID for regular users and groups (`UID_MAX` and `GID_MAX` in login.defs).
+    pub const MAX_ID: u32 = 60000;
+    /// Login shell used by the system when no default shell is configured.
+    pub const DEFAULT_SHELL: &'static str = "/bin/bash";
+    /// Directories which cannot be used as home.
+    const SYSTEM_DIRS: [&'static str; 11] = [
+        "/", "/bin", "/boot", "/dev", "/etc", "/proc", "/root", "/run", "/sys", "/usr", "/var",
+    ];
+
+    /// Returns the list of problems found in the account settings.
+    ///
+    /// The IDs must be in the range for regular accounts, so they do not collide with the system
+    /// accounts. The home directory must be an absolute path out of the system directories and
+    /// the shell must be an absolute path.
+    pub fn validate(&self) -> Vec<String> {
+        let mut issues: Vec<String> = [("UID", self.uid), ("GID", self.gid)]
+            .into_iter()
+            .filter_map(|(name, id)| Some((name, id?)))
+            .filter(|(_, id)| !(Self::MIN_ID..=Self::MAX_ID).contains(id))
+            .map(|(name, id)| {
+                format!(
+                    "{} {} is reserved, it must be between {} and {}",
+                    name,
+                    id,
+                    Self::MIN_ID,
+                    Self::MAX_ID
+                )
+            })
+            .collect();
+
+        if let Some(home) = &self.home {
+            let normalized = match home.trim_end_matches('/') {
+                "" => "/",
+                path => path,
+            };
+            if !home.starts_with('/') || home.split('/').any(|c| c == "." || c == "..") {
+                issues.push(format!(
+                    "Home directory '{}' must be an absolute path",
+                    home
+                ));
+            } else if Self::SYSTEM_DIRS.contains(&normalized) {
+                issues.push(format!("Home directory '{}' is a system directory", home));
+            }
+        }
+
+        if let Some(issue) = self.shell.as_deref().and_then(Self::validate_shell) {
+            issues.push(issue);
+        }
+
+        issues
+    }
+
+    /// Returns the problem found in a login shell, if any.
+    ///
+    /// It only checks the path; whether the shell exists in the target system depends on the
+    /// selected software.
+    ///
+    /// * `shell`: path to the shell.
+    pub fn validate_shell(shell: &str) -> Option<String> {
+        let valid = shell.starts_with('/')
+            && !shell.ends_with('/')
+            && !shell.split('/').any(|c| c == "." || c == "..")
+            && !shell.contains(char::is_whitespace);
+        (!valid).then(|| format!("Shell '{}' must be an absolute path", shell))
+    }
+
+    /// Reads the account settings from the additional data of a user coming from D-Bus.
+    ///
+    /// * `data`: additional user data.
+    pub fn from_dbus(data: &HashMap<String, OwnedValue>) -> Result<Self, zvariant::Error> {
+        Ok(Self {
+            uid: get_optional_u32(data, "UID")?,
+            gid: get_optional_u32(data, "GID")?,
+            home: get_optional_property::<String>(data, "Home")?.filter(|h| !h.is_empty()),
+            shell: get_optional_property::<String>(data, "Shell")?.filter(|s| !s.is_empty()),
+        })
+    }
+
+    /// Converts the account settings into the additional user data to send to D-Bus.
+    pub fn to_dbus(&self) -> HashMap<&str, Value<'_>> {
+        let mut data: HashMap<&str, Value<'_>> = HashMap::new();
+        if let Some(uid) = self.uid {
+            data.insert("UID", uid.into());
+        }
+        if let Some(gid) = self.gid {
+            data.insert("GID", gid.into());
+        }
+        if let Some(home) = &self.home {
+            data.insert("Home", home.as_str().into());
+        }
+        if let Some(shell) = &self.shell {
+            data.insert("Shell", shell.as_str().into());
+        }
+        data
+    }
+}
+
+/// Group to create in the target system.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+pub struct UserGroup {
+    /// Group name
+    pub name: String,
+    /// Group ID (assigned by the system if it is not set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gid: Option<u32>,
+    /// Names of the users belonging to the group
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+impl UserGroup {
+    /// Maximum length of a group name.
+    pub const MAX_NAME_LENGTH: usize = 32;
+    /// Groups which already exist in the installed system.
+    const SYSTEM_GROUPS: [&'static str; 14] = [
+        "root", "bin", "daemon", "sys", "adm", "tty", "disk", "lp", "mail", "wheel", "audio",
+        "video", "users", "nogroup",
+    ];
+
+    /// Returns the list of problems found in the groups.
+    ///
+    /// The names must be valid and not used by a system group, and the group IDs must be in the
+    /// range for regular groups. Names, IDs and members must not be repeated, and the members
+    /// must be known users.
+    ///
+    /// * `groups`: groups to check.
+    /// * `users`: names of the configured users.
+    pub fn validate_all(groups: &[UserGroup], users: &[&str]) -> Vec<String> {
+        let mut issues = vec![];
+        let mut names = vec![];
+        let mut gids = vec![];
+        for group in groups {
+            let name = group.name.as_str();
+            if !is_valid_group_name(name) {
+                issues.push(format!("Invalid group name: '{}'", name));
+            } else if Self::SYSTEM_GROUPS.contains(&name) {
+                issues.push(format!("The group '{}' is a system group", name));
+            } else if names.contains(&name) {
+                issues.push(format!("The group '{}' is repeated", name));
+            }
+            names.push(name);
+
+            if let Some(gid) = group.gid {
+                if !(UserAccount::MIN_ID..=UserAccount::MAX_ID).contains(&gid) {
+                    issues.push(format!(
+                        "GID {} of '{}' is reserved, it must be between {} and {}",
+                        gid,
+                        name,
+                        UserAccount::MIN_ID,
+                        UserAccount::MAX_ID
+                    ));
+                } else if gids.contains(&gid) {
+                    issues.push(format!("GID {} of '{}' is already used", gid, name));
+                }
+                gids.push(gid);
+            }
+
+            let mut members = vec![];
+            for member in &group.members {
+                if !users.contains(&member.as_str()) {
+                    issues.push(format!("Unknown member of '{}': '{}'", name, member));
+                } else if members.contains(&member) {
+                    issues.push(format!("Repeated member of '{}': '{}'", name, member));
+                }
+                members.push(member);
+            }
+        }
+        issues
+    }
+}
+
+/// Whether the given string is a valid group name.
+///
+/// It follows the default `NAME_REGEX` of groupadd: lowercase letters, digits, dashes and
+/// underscores, not starting with a digit or a dash.
+fn is_valid_group_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    name.len() <= UserGroup::MAX_NAME_LENGTH
+        && (first.is_ascii_lowercase() || first == '_')
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UserAccount, UserExpiration, UserGroup};
+    use std::collections::HashMap;
+    use zbus::zvariant::{OwnedValue, Value};
+
+    #[test]
+    fn test_validate_expiration() {
+        let expiration = UserExpiration {
+            password_expire_days: Some(90),
+            account_expire_date: Some("2030-12-31".to_string()),
+            locked: true,
+        };
+        assert!(expiration.validate().is_empty());
+        assert!(UserExpiration::default().validate().is_empty());
+    }
+
+    #[test]
+    fn test_validate_wrong_expiration() {
+        let expiration = UserExpiration {
+            password_expire_days: Some(0),
+            account_expire_date: Some("31/12/2030".to_string()),
+            locked: false,
+        };
+        assert_eq!(expiration.validate().len(), 2);
+
+        let expiration = UserExpiration {
+            password_expire_days: Some(UserExpiration::MAX_PASSWORD_EXPIRE_DAYS + 1),
+            account_expire_date: Some("1970-01-01".to_string()),
+            locked: false,
+        };
+        assert_eq!(expiration.validate().len(), 2);
+    }
+
+    #[test]
+    fn test_validate_account() {
+        let account = UserAccount {
+            uid: Some(1500),
+            gid: Some(1500),
+            home: Some("/data/home/tux".to_string()),
+            shell: Some("/usr/bin/zsh".to_string()),
+        };
+        assert!(account.validate().is_empty());
+        assert!(UserAccount::default().validate().is_empty());
+
+        let account = UserAccount {
+            uid: Some(0),
+            gid: Some(65534),
+            home: Some("home/tux".to_string()),
+            shell: Some("zsh".to_string()),
+        };
+        assert_eq!(
+            account.validate(),
+            vec![
+                "UID 0 is reserved, it must be between 1000 and 60000",
+                "GID 65534 is reserved, it must be between 1000 and 60000",
+                "Home directory 'home/tux' must be an absolute path",
+                "Shell 'zsh' must be an absolute path",
+            ]
+        );
+
+        // the users service sends the IDs as signed integers
+        let data: HashMap<String, OwnedValue> = HashMap::from([
+            ("UID".to_string(), Value::new(1001_i32).into()),
+            ("GID".to_string(), Value::new(2000_u32).into()),
+            ("Home".to_string(), Value::new("/srv/tux").into()),
+        ]);
+        let account = UserAccount::from_dbus(&data).unwrap();
+        assert_eq!(account.uid, Some(1001));
+        assert_eq!(account.gid, Some(2000));
+        assert_eq!(account.home.as_deref(), Some("/srv/tux"));
+        let data = HashMap::from([("UID".to_string(), Value::new(-1_i32).into())]);
+        assert!(UserAccount::from_dbus(&data).is_err());
+
+        for home in ["/etc/", "/", "/home/../root"] {
+            let account = UserAccount {
+                home: Some(home.to_string()),
+                ..Default::default()
+            };
+            assert_eq!(account.validate().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_validate_shell() {
+        assert_eq!(UserAccount::validate_shell("/bin/bash"), None);
+        assert_eq!(UserAccount::validate_shell("/usr/sbin/nologin"), None);
+        for shell in ["", "bash", "/usr/bin/", "/usr/../bin/sh", "/bin/my shell"] {
+            assert!(UserAccount::validate_shell(shell).is_some(), "{}", shell);
+        }
+    }
+
+    fn group(name: &str, gid: Option<u32>, members: &[&str]) -> UserGroup {
+        UserGroup {
+            name: name.to_string(),
+            gid,
+            members: members.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_validate_groups() {
+        let users = ["root", "tux"];
+        let groups = vec![
+            group("developers", Some(2000), &["tux"]),
+            group("_backup", None, &["root", "tux"]),
+        ];
+        assert!(UserGroup::validate_all(&groups, &users).is_empty());
+
+        let groups = vec![
+            group("Devs", Some(2000), &[]),
+            group("wheel", None, &[]),
+            group("ops", Some(2000), &["tux", "tux"]),
+            group("ops", Some(10), &["geeko"]),
+        ];
+        assert_eq!(
+            UserGroup::validate_all(&groups, &users),
+            vec![
+                "Invalid group name: 'Devs'",
+                "The group 'wheel' is a system group",
+                "GID 2000 of 'ops' is already used",
+                "Repeated member of 'ops': 'tux'",
+                "The group 'ops' is repeated",
+                "GID 10 of 'ops' is reserved, it must be between 1000 and 60000",
+                "Unknown member of 'ops': 'geeko'",
+            ]
+        );
+    }
+}
@@ -72,6 +72,18 @@ trait Users1 {
     /// Write method
     fn write(&self) -> zbus::Result<u32>;
 
+    /// Get the groups to create (as JSON)
+    fn get_groups(&self) -> zbus::Result<String>;
+
+    /// Set the groups to create (as JSON)
+    fn set_groups(&self, groups: &str) -> zbus::Result<u32>;
+
+    /// DefaultShell property
+    #[dbus_proxy(property)]
+    fn default_shell(&self) -> zbus::Result<String>;
+    #[dbus_proxy(property)]
+    fn set_default_shell(&self, value: &str) -> zbus::Result<()>;
+
     /// FirstUser property
     #[dbus_proxy(property)]
     fn first_user(&self) -> zbus::Result<FirstUser>;
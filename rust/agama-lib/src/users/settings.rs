@@ -18,6 +18,7 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
+use super::model::{UserAccount, UserExpiration};
 use serde::{Deserialize, Serialize};
 
 /// User settings
@@ -45,6 +46,12 @@ pub struct FirstUserSettings {
     pub password: Option<String>,
     /// Whether auto-login should enabled or not
     pub autologin: Option<bool>,
+    /// Password and account expiration policy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration: Option<UserExpiration>,
+    /// User and group IDs and home directory
+    #[serde(flatten)]
+    pub account: UserAccount,
 }
 
 /// Root user settings
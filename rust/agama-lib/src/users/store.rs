@@ -47,6 +47,8 @@ impl UsersStore {
             autologin: Some(first_user.autologin),
             full_name: Some(first_user.full_name),
             password: Some(first_user.password),
+            expiration: Some(first_user.expiration).filter(|e| !e.is_default()),
+            account: first_user.account,
         };
         let mut root_user = RootUserSettings::default();
         let ssh_public_key = self.users_client.root_ssh_key().await?;
@@ -77,6 +79,8 @@ impl UsersStore {
             full_name: settings.full_name.clone().unwrap_or_default(),
             autologin: settings.autologin.unwrap_or_default(),
             password: settings.password.clone().unwrap_or_default(),
+            expiration: settings.expiration.clone().unwrap_or_default(),
+            account: settings.account.clone(),
             ..Default::default()
         };
         self.users_client.set_first_user(&first_user).await?;
@@ -152,6 +156,8 @@ mod test {
             user_name: Some("tux".to_owned()),
             password: Some("fish".to_owned()),
             autologin: Some(true),
+            expiration: None,
+            account: Default::default(),
         };
         let root_user = RootUserSettings {
             // FIXME this is weird: no matter what HTTP reports, we end up with None
@@ -209,6 +215,8 @@ mod test {
             user_name: Some("tux".to_owned()),
             password: Some("fish".to_owned()),
             autologin: Some(true),
+            expiration: None,
+            account: Default::default(),
         };
         let root_user = RootUserSettings {
             password: Some("1234".to_owned()),
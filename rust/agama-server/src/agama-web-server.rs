@@ -19,6 +19,8 @@
 // find current contact information at www.suse.com.
 
 use std::{
+    fs::Permissions,
+    os::unix::fs::{FileTypeExt, PermissionsExt},
     path::{Path, PathBuf},
     pin::Pin,
     process::{ExitCode, Termination},
@@ -42,8 +44,9 @@ use futures_util::pin_mut;
 use hyper::body::Incoming;
 use hyper_util::rt::{TokioExecutor, TokioIo};
 use hyper_util::server::conn::auto::Builder;
+use nix::unistd::{access, AccessFlags};
 use openssl::ssl::{Ssl, SslAcceptor, SslMethod};
-use tokio::sync::broadcast::channel;
+use tokio::{net::UnixListener, sync::broadcast::channel};
 use tokio_openssl::SslStream;
 use tower::Service;
 use utoipa::OpenApi;
@@ -56,7 +59,8 @@ enum Commands {
     /// Starts the API server.
     ///
     /// This command starts the server in the given ports. The secondary port, if enabled, uses SSL.
-    /// If no certificate is specified, agama-web-server generates a self-signed one.
+    /// If no certificate is specified, agama-web-server generates a self-signed one. Additionally,
+    /// it can listen on a Unix domain socket, which only serves plain HTTP to local clients.
     Serve(ServeArgs),
     /// Generates the API documentation in OpenAPI format.
     Openapi,
@@ -92,7 +96,7 @@ fn find_web_ui_dir() -> PathBuf {
 struct ServeArgs {
     // Address/port to listen on. ":::80" listens for both IPv6 and IPv4
     // connections unless manually disabled in /proc/sys/net/ipv6/bindv6only.
-    /// Primary port to listen on
+    /// Primary address to listen on (e.g., "127.0.0.1:80" to bind only to the loopback interface)
     #[arg(long, default_value = ":::80")]
     address: String,
 
@@ -100,6 +104,14 @@ struct ServeArgs {
     #[arg(long, default_value = None)]
     address2: Option<String>,
 
+    /// Optional Unix domain socket to listen on
+    #[arg(long)]
+    socket: Option<PathBuf>,
+
+    /// Do not listen on TCP addresses, only on the Unix domain socket
+    #[arg(long, requires = "socket")]
+    no_tcp: bool,
+
     #[arg(long, default_value = "/etc/agama.d/ssl/key.pem")]
     key: Option<PathBuf>,
 
@@ -113,6 +125,10 @@ struct ServeArgs {
     // Directory containing the web UI code
     #[arg(long)]
     web_ui_dir: Option<PathBuf>,
+
+    /// Reject the requests which could change the installer state (inspection mode)
+    #[arg(long)]
+    read_only: bool,
 }
 
 impl ServeArgs {
@@ -286,6 +302,69 @@ async fn handle_http_stream(
     }
 }
 
+/// handle a connection on the Unix domain socket
+///
+/// The connections are always local, so the API is served over plain HTTP.
+async fn handle_unix_stream(unix_stream: tokio::net::UnixStream, service: axum::Router) {
+    let stream = TokioIo::new(unix_stream);
+    let hyper_service =
+        hyper::service::service_fn(move |request: Request<Incoming>| service.clone().call(request));
+
+    let ret = Builder::new(TokioExecutor::new())
+        .serve_connection_with_upgrades(stream, hyper_service)
+        .await;
+
+    if let Err(err) = ret {
+        tracing::error!("Error serving connection on the Unix socket: {}", err);
+    }
+}
+
+/// Permissions of the Unix domain socket. The API is served over plain HTTP, so only the user
+/// running the server can connect.
+const SOCKET_MODE: u32 = 0o600;
+
+/// Checks whether the server can create the Unix domain socket at the given path
+///
+/// A stale socket from a previous run is removed.
+fn prepare_socket_path(path: &Path) -> anyhow::Result<()> {
+    let parent = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let metadata = std::fs::metadata(parent)
+        .with_context(|| format!("Cannot access the socket directory {}", parent.display()))?;
+    if !metadata.is_dir() {
+        anyhow::bail!("{} is not a directory", parent.display());
+    }
+    if access(parent, AccessFlags::W_OK | AccessFlags::X_OK).is_err() {
+        anyhow::bail!("The socket directory {} is not writable", parent.display());
+    }
+
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            anyhow::bail!("{} exists and it is not a socket", path.display());
+        }
+        std::fs::remove_file(path)
+            .with_context(|| format!("Cannot remove the stale socket {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Starts the web server on a Unix domain socket
+async fn start_unix_server(listener: UnixListener, service: Router) {
+    loop {
+        let tower_service = service.clone();
+        let (unix_stream, _) = listener
+            .accept()
+            .await
+            .expect("Failed to accept a connection on the Unix socket");
+
+        tokio::spawn(async move {
+            handle_unix_stream(unix_stream, tower_service).await;
+        });
+    }
+}
+
 /// Starts the web server
 async fn start_server(address: String, service: Router, ssl_acceptor: SslAcceptor) {
     tracing::info!("Starting Agama web server at {}", address);
@@ -336,7 +415,10 @@ async fn serve_command(args: ServeArgs) -> anyhow::Result<()> {
     let (tx, _) = channel(16);
     run_monitor(tx.clone()).await?;
 
-    let config = web::ServiceConfig::load()?;
+    let mut config = web::ServiceConfig::load()?;
+    if args.read_only {
+        config.read_only = true;
+    }
 
     write_token(TOKEN_FILE, &config.jwt_secret).context("could not create the token file")?;
 
@@ -351,13 +433,15 @@ async fn serve_command(args: ServeArgs) -> anyhow::Result<()> {
         return Err(anyhow::anyhow!("SSL initialization failed"));
     };
 
-    let mut addresses = vec![args.address];
-
-    if let Some(a) = args.address2 {
-        addresses.push(a)
+    let mut addresses = vec![];
+    if !args.no_tcp {
+        addresses.push(args.address);
+        if let Some(a) = args.address2 {
+            addresses.push(a)
+        }
     }
 
-    let servers: Vec<_> = addresses
+    let mut servers: Vec<_> = addresses
         .iter()
         .map(|a| {
             tokio::spawn(start_server(
@@ -368,6 +452,16 @@ async fn serve_command(args: ServeArgs) -> anyhow::Result<()> {
         })
         .collect();
 
+    if let Some(path) = &args.socket {
+        prepare_socket_path(path)?;
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("Could not listen on {}", path.display()))?;
+        std::fs::set_permissions(path, Permissions::from_mode(SOCKET_MODE))
+            .with_context(|| format!("Could not set the permissions of {}", path.display()))?;
+        tracing::info!("Starting Agama web server at {}", path.display());
+        servers.push(tokio::spawn(start_unix_server(listener, service.clone())));
+    }
+
     // notify systemd that web server start serving
     if let Ok(true) = sd_notify::booted() {
         sd_notify::notify(true, &[sd_notify::NotifyState::Ready])
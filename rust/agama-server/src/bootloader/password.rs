@@ -0,0 +1,80 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Hashes the bootloader passwords in the format used by GRUB.
+
+use agama_lib::bootloader::model::GRUB_PBKDF2_PREFIX;
+use openssl::{error::ErrorStack, hash::MessageDigest, pkcs5::pbkdf2_hmac, rand::rand_bytes};
+
+/// Same values used by `grub2-mkpasswd-pbkdf2`.
+const ITERATIONS: usize = 10000;
+const SALT_LENGTH: usize = 64;
+const HASH_LENGTH: usize = 64;
+
+/// Returns the GRUB PBKDF2 hash of the given password, using a random salt.
+///
+/// * `password`: password in plain text.
+pub fn hash_password(password: &str) -> Result<String, ErrorStack> {
+    let mut salt = [0; SALT_LENGTH];
+    rand_bytes(&mut salt)?;
+    hash_password_with_salt(password, &salt)
+}
+
+fn hash_password_with_salt(password: &str, salt: &[u8]) -> Result<String, ErrorStack> {
+    let mut hash = [0; HASH_LENGTH];
+    pbkdf2_hmac(
+        password.as_bytes(),
+        salt,
+        ITERATIONS,
+        MessageDigest::sha512(),
+        &mut hash,
+    )?;
+    Ok(format!(
+        "{}{}.{}.{}",
+        GRUB_PBKDF2_PREFIX,
+        ITERATIONS,
+        to_hex(salt),
+        to_hex(&hash)
+    ))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash_password, hash_password_with_salt};
+
+    #[test]
+    fn test_hash_password() {
+        let hash = hash_password_with_salt("linux", &[0xab; 4]).unwrap();
+        let parts: Vec<_> = hash.split('.').collect();
+        assert_eq!(&parts[..4], ["grub", "pbkdf2", "sha512", "10000"]);
+        assert_eq!(parts[4], "ABABABAB");
+        assert_eq!(parts[5].len(), 128);
+        assert_eq!(hash, hash_password_with_salt("linux", &[0xab; 4]).unwrap());
+
+        assert_ne!(
+            hash_password("linux").unwrap(),
+            hash_password("linux").unwrap()
+        );
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! This module implements the web API for the handling of bootloader settings.
+//!
+//! The module offers one public function:
+//!
+//! * `bootloader_service` which returns the Axum service.
+
+use agama_lib::{
+    bootloader::{
+        client::BootloaderClient,
+        model::{BootloaderSettings, BootloaderType},
+    },
+    error::ServiceError,
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, put},
+    Json, Router,
+};
+
+use super::password::hash_password;
+use crate::{error::Error, manager::facts::HardwareFacts};
+
+#[derive(Clone)]
+struct BootloaderState<'a> {
+    client: BootloaderClient<'a>,
+}
+
+/// Sets up and returns the axum service for the bootloader settings.
+///
+/// * `dbus`: D-Bus connection.
+pub async fn bootloader_service(dbus: zbus::Connection) -> Result<Router, ServiceError> {
+    let client = BootloaderClient::new(dbus).await?;
+    let state = BootloaderState { client };
+    let router = Router::new()
+        .route("/config", put(set_config).get(get_config))
+        .route("/supported", get(supported_bootloaders))
+        .route("/warnings", get(warnings))
+        .with_state(state);
+    Ok(router)
+}
+
+/// Returns the bootloader configuration.
+///
+/// The extra kernel parameters are reported on their own, without the ones set by Agama. The
+/// bootloader password is not included, only whether it is set.
+///
+/// * `state` : service state.
+#[utoipa::path(
+    get,
+    path = "/config",
+    context_path = "/api/bootloader",
+    operation_id = "get_bootloader_config",
+    responses(
+        (status = 200, description = "bootloader configuration", body = BootloaderSettings),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn get_config(
+    State(state): State<BootloaderState<'_>>,
+) -> Result<Json<BootloaderSettings>, Error> {
+    let mut settings = state.client.get_config().await.map_err(Error::Service)?;
+    if let Some(password) = settings.password.as_mut() {
+        password.redact();
+    }
+    Ok(Json(settings))
+}
+
+/// Sets the bootloader configuration.
+///
+/// The bootloader must be supported by the product and usable with the system firmware (e.g.,
+/// systemd-boot cannot be used on BIOS). Repeated kernel parameters are removed. When kdump is
+/// enabled, the `crashkernel` parameter is added and the reservation must fit in the memory.
+/// The names of the kernel modules to load or blacklist are checked for basic sanity.
+///
+/// If Secure Boot is not explicitly set, the setup follows the firmware state. The choices which
+/// work but are not recommended (e.g., a small crash kernel reservation) are reported by
+/// `/warnings`.
+///
+/// The bootloader password is hashed if it is given in plain text. If neither the password nor
+/// the hash are given (e.g., when sending back the current configuration), the current one is
+/// kept.
+///
+/// * `state`: service state.
+/// * `config`: bootloader configuration.
+#[utoipa::path(
+    put,
+    path = "/config",
+    context_path = "/api/bootloader",
+    operation_id = "set_bootloader_config",
+    responses(
+        (status = 200, description = "Set the bootloader configuration"),
+        (status = 400, description = "The D-Bus service could not perform the action"),
+        (status = 422, description = "Invalid bootloader configuration. Details are in body", body = Vec<String>),
+    )
+)]
+async fn set_config(
+    State(state): State<BootloaderState<'_>>,
+    Json(mut settings): Json<BootloaderSettings>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(password) = settings.password.as_mut() {
+        if password.password.is_none() && password.hashed_password.is_none() {
+            let current = state.client.get_config().await?;
+            password.hashed_password = current.password.and_then(|p| p.hashed_password);
+        }
+    }
+
+    let supported = state.client.supported_bootloaders().await?;
+    let efi = state.client.is_efi().await?;
+    let facts = HardwareFacts::detect().await;
+    let mut issues = settings.validate(&supported, efi);
+    issues.extend(settings.validate_kdump(facts.memory));
+    if !issues.is_empty() {
+        return Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(issues).into_response(),
+        ));
+    }
+
+    if let Some(password) = settings.password.as_mut() {
+        if let Some(plain) = password.password.take() {
+            let hash = hash_password(&plain)
+                .map_err(|e| Error::Anyhow(format!("Could not hash the password: {}", e)))?;
+            password.hashed_password = Some(hash);
+        }
+    }
+
+    if efi {
+        settings.secure_boot = Some(settings.uses_secure_boot(facts.secure_boot));
+    }
+    settings.apply_kdump_params();
+    settings.dedup_kernel_params();
+    state.client.set_config(&settings).await?;
+    Ok((StatusCode::OK, ().into_response()))
+}
+
+/// Returns the warnings about the bootloader configuration.
+///
+/// They do not prevent the installation: a crash kernel reservation which might be too small or
+/// a choice which would not boot with Secure Boot enabled.
+///
+/// * `state`: service state.
+#[utoipa::path(
+    get,
+    path = "/warnings",
+    context_path = "/api/bootloader",
+    responses(
+        (status = 200, description = "Warnings about the bootloader configuration", body = Vec<String>),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn warnings(State(state): State<BootloaderState<'_>>) -> Result<Json<Vec<String>>, Error> {
+    let settings = state.client.get_config().await?;
+    let facts = HardwareFacts::detect().await;
+    let mut warnings = settings.kdump_warnings();
+    warnings.extend(settings.secure_boot_warnings(facts.secure_boot));
+    Ok(Json(warnings))
+}
+
+/// Returns the bootloaders supported by the selected product.
+///
+/// * `state`: service state.
+#[utoipa::path(
+    get,
+    path = "/supported",
+    context_path = "/api/bootloader",
+    responses(
+        (status = 200, description = "Supported bootloaders", body = Vec<BootloaderType>),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn supported_bootloaders(
+    State(state): State<BootloaderState<'_>>,
+) -> Result<Json<Vec<BootloaderType>>, Error> {
+    Ok(Json(state.client.supported_bootloaders().await?))
+}
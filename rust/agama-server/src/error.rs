@@ -26,7 +26,11 @@ use axum::{
 };
 use serde_json::json;
 
-use crate::{l10n::LocaleError, questions::QuestionsError};
+use crate::{
+    l10n::LocaleError,
+    questions::QuestionsError,
+    web::{current_request_id, translate},
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -38,7 +42,7 @@ pub enum Error {
     Service(#[from] ServiceError),
     #[error("Questions service error: {0}")]
     Questions(QuestionsError),
-    #[error("Software service error: {0}")]
+    #[error("Localization service error: {0}")]
     Locale(#[from] LocaleError),
 }
 
@@ -60,11 +64,81 @@ impl From<Error> for zbus::fdo::Error {
     }
 }
 
+/// D-Bus error returned by the Agama services when they cannot run an action because another one
+/// is in progress (e.g., a storage commit).
+pub const BUSY_ERROR: &str = "org.opensuse.Agama1.Error.Busy";
+
+impl Error {
+    /// Name of the D-Bus error, if the error comes from a D-Bus method call.
+    fn dbus_error_name(&self) -> Option<&str> {
+        let dbus_error = match self {
+            Self::DBus(error) => error,
+            Self::Service(ServiceError::DBus(error)) => error,
+            _ => return None,
+        };
+        match dbus_error {
+            zbus::Error::MethodError(name, _, _) => Some(name.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Whether the error is caused by a D-Bus service which is not running.
+    fn is_unavailable(&self) -> bool {
+        matches!(
+            self.dbus_error_name(),
+            Some("org.freedesktop.DBus.Error.ServiceUnknown")
+                | Some("org.freedesktop.DBus.Error.NameHasNoOwner")
+        )
+    }
+
+    /// Whether the D-Bus service refused the action because another one is in progress.
+    pub fn is_busy(&self) -> bool {
+        self.dbus_error_name() == Some(BUSY_ERROR)
+    }
+}
+
+impl Error {
+    /// Machine-readable code of the error. Unlike the message, it is not translated.
+    fn code(&self) -> &'static str {
+        match self {
+            Self::DBus(_) => "dbus",
+            Self::Anyhow(_) => "generic",
+            Self::Service(_) => "service",
+            Self::Questions(_) => "questions",
+            Self::Locale(_) => "locale",
+        }
+    }
+
+    /// Message of the error, translated to the language of the current request.
+    ///
+    /// Only the description of the kind of error (the text before the first colon) is
+    /// translated. The details come from other components (e.g., the D-Bus services) and they are
+    /// kept as they are.
+    fn localized_message(&self) -> String {
+        let message = self.to_string();
+        match message.split_once(": ") {
+            Some((description, details)) => format!("{}: {}", translate(description), details),
+            None => translate(&message),
+        }
+    }
+}
+
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
-        let body = json!({
-            "error": self.to_string()
+        let mut body = json!({
+            "error": self.localized_message(),
+            "code": self.code()
         });
-        (StatusCode::BAD_REQUEST, Json(body)).into_response()
+        if let Some(id) = current_request_id() {
+            body["requestId"] = json!(id);
+        }
+        let status = if self.is_unavailable() {
+            StatusCode::SERVICE_UNAVAILABLE
+        } else if self.is_busy() {
+            StatusCode::CONFLICT
+        } else {
+            StatusCode::BAD_REQUEST
+        };
+        (status, Json(body)).into_response()
     }
 }
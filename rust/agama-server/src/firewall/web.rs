@@ -0,0 +1,166 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! This module implements the web API for the handling of firewall settings.
+//!
+//! The module offers one public function:
+//!
+//! * `firewall_service` which returns the Axum service.
+
+use agama_lib::{
+    error::ServiceError,
+    firewall::{
+        client::FirewallClient,
+        model::{FirewallSettings, FirewallZone},
+    },
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, put},
+    Json, Router,
+};
+
+use crate::error::Error;
+
+#[derive(Clone)]
+struct FirewallState<'a> {
+    client: FirewallClient<'a>,
+}
+
+/// Sets up and returns the axum service for the firewall settings.
+///
+/// * `dbus`: D-Bus connection.
+pub async fn firewall_service(dbus: zbus::Connection) -> Result<Router, ServiceError> {
+    let client = FirewallClient::new(dbus).await?;
+    let state = FirewallState { client };
+    let router = Router::new()
+        .route("/config", put(set_config).get(get_config))
+        .route("/services", get(known_services))
+        .route("/zones/:name", put(set_zone))
+        .with_state(state);
+    Ok(router)
+}
+
+/// Returns the planned firewall configuration.
+///
+/// * `state` : service state.
+#[utoipa::path(
+    get,
+    path = "/config",
+    context_path = "/api/firewall",
+    operation_id = "get_firewall_config",
+    responses(
+        (status = 200, description = "firewall configuration", body = FirewallSettings),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn get_config(
+    State(state): State<FirewallState<'_>>,
+) -> Result<Json<FirewallSettings>, Error> {
+    Ok(Json(state.client.get_config().await?))
+}
+
+/// Sets the firewall configuration.
+///
+/// The services must be known by firewalld and the ports must use the "port/protocol"
+/// syntax.
+///
+/// * `state`: service state.
+/// * `settings`: firewall configuration.
+#[utoipa::path(
+    put,
+    path = "/config",
+    context_path = "/api/firewall",
+    operation_id = "set_firewall_config",
+    responses(
+        (status = 200, description = "Set the firewall configuration"),
+        (status = 400, description = "The D-Bus service could not perform the action"),
+        (status = 422, description = "Invalid firewall configuration. Details are in body", body = Vec<String>),
+    )
+)]
+async fn set_config(
+    State(state): State<FirewallState<'_>>,
+    Json(settings): Json<FirewallSettings>,
+) -> Result<impl IntoResponse, Error> {
+    update_config(&state, settings).await
+}
+
+/// Opens the given services and ports in a zone, replacing its previous settings.
+///
+/// * `state`: service state.
+/// * `name`: zone name.
+/// * `zone`: services and ports to open.
+#[utoipa::path(
+    put,
+    path = "/zones/:name",
+    context_path = "/api/firewall",
+    params(("name" = String, Path, description = "Zone name")),
+    responses(
+        (status = 200, description = "Set the zone configuration"),
+        (status = 400, description = "The D-Bus service could not perform the action"),
+        (status = 422, description = "Invalid zone configuration. Details are in body", body = Vec<String>),
+    )
+)]
+async fn set_zone(
+    State(state): State<FirewallState<'_>>,
+    Path(name): Path<String>,
+    Json(zone): Json<FirewallZone>,
+) -> Result<impl IntoResponse, Error> {
+    let mut settings = state.client.get_config().await?;
+    settings.set_zone(FirewallZone { name, ..zone });
+    update_config(&state, settings).await
+}
+
+/// Returns the firewalld services that can be opened.
+///
+/// * `state`: service state.
+#[utoipa::path(
+    get,
+    path = "/services",
+    context_path = "/api/firewall",
+    responses(
+        (status = 200, description = "Known firewalld services", body = Vec<String>),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn known_services(
+    State(state): State<FirewallState<'_>>,
+) -> Result<Json<Vec<String>>, Error> {
+    Ok(Json(state.client.known_services().await?))
+}
+
+async fn update_config(
+    state: &FirewallState<'_>,
+    settings: FirewallSettings,
+) -> Result<(StatusCode, Response), Error> {
+    let known_services = state.client.known_services().await?;
+    let issues = settings.validate(&known_services);
+    if !issues.is_empty() {
+        return Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(issues).into_response(),
+        ));
+    }
+
+    state.client.set_config(&settings).await?;
+    Ok((StatusCode::OK, ().into_response()))
+}
@@ -21,16 +21,19 @@
 mod dbus;
 pub mod error;
 pub mod helpers;
-mod keyboard;
+pub mod keyboard;
 pub mod l10n;
 mod locale;
+pub mod ntp;
+pub mod suggestion;
+pub mod time;
 mod timezone;
 pub mod web;
 
 pub use agama_lib::localization::model::LocaleConfig;
 pub use dbus::export_dbus_objects;
 pub use error::LocaleError;
-pub use keyboard::Keymap;
+pub use keyboard::{KeyboardMapping, Keymap};
 pub use l10n::L10n;
-pub use locale::LocaleEntry;
+pub use locale::{locale_conf, LocaleEntry, LocaleGeneration};
 pub use timezone::TimezoneEntry;
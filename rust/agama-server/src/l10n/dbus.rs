@@ -18,7 +18,10 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use std::sync::{Arc, RwLock};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
 
 use agama_locale_data::{KeymapId, LocaleId};
 use zbus::{dbus_interface, Connection};
@@ -102,6 +105,90 @@ impl L10nInterface {
         Ok(())
     }
 
+    #[dbus_interface(property)]
+    pub fn locale_categories(&self) -> HashMap<String, String> {
+        let backend = self.backend.read().unwrap();
+        backend.locale_categories.clone().into_iter().collect()
+    }
+
+    #[dbus_interface(property)]
+    pub fn set_locale_categories(
+        &mut self,
+        categories: HashMap<String, String>,
+    ) -> zbus::fdo::Result<()> {
+        let mut backend = self.backend.write().unwrap();
+        backend
+            .set_locale_categories(&categories.into_iter().collect())
+            .map_err(|e| {
+                zbus::fdo::Error::InvalidArgs(format!("Could not set the locale categories: {e}"))
+            })?;
+        Ok(())
+    }
+
+    #[dbus_interface(property)]
+    pub fn ntp_servers(&self) -> Vec<String> {
+        let backend = self.backend.read().unwrap();
+        backend.ntp_servers.to_owned()
+    }
+
+    #[dbus_interface(property)]
+    pub fn set_ntp_servers(&mut self, servers: Vec<String>) -> zbus::fdo::Result<()> {
+        let mut backend = self.backend.write().unwrap();
+        backend.set_ntp_servers(&servers).map_err(|e| {
+            zbus::fdo::Error::InvalidArgs(format!("Could not set the NTP servers: {}", e))
+        })?;
+        Ok(())
+    }
+
+    /// How to use the NTP servers offered via DHCP ("ignore", "prefer" or "merge").
+    #[dbus_interface(property, name = "NtpDHCP")]
+    pub fn ntp_dhcp(&self) -> String {
+        let backend = self.backend.read().unwrap();
+        backend.ntp_dhcp.as_str().to_string()
+    }
+
+    #[dbus_interface(property, name = "NtpDHCP")]
+    pub fn set_ntp_dhcp(&mut self, policy: &str) -> zbus::fdo::Result<()> {
+        let mut backend = self.backend.write().unwrap();
+        backend.ntp_dhcp = policy
+            .parse()
+            .map_err(|e| zbus::fdo::Error::InvalidArgs(format!("{e}")))?;
+        Ok(())
+    }
+
+    /// NTP servers offered via DHCP to the installer.
+    #[dbus_interface(property, name = "DHCPNtpServers")]
+    pub fn dhcp_ntp_servers(&self) -> Vec<String> {
+        let backend = self.backend.read().unwrap();
+        backend.dhcp_ntp_servers.to_owned()
+    }
+
+    #[dbus_interface(property, name = "DHCPNtpServers")]
+    pub fn set_dhcp_ntp_servers(&mut self, servers: Vec<String>) {
+        let mut backend = self.backend.write().unwrap();
+        backend.set_dhcp_ntp_servers(&servers);
+    }
+
+    /// NTP servers that will be written to the target system.
+    #[dbus_interface(property)]
+    pub fn effective_ntp_servers(&self) -> Vec<String> {
+        let backend = self.backend.read().unwrap();
+        backend.effective_ntp_servers()
+    }
+
+    /// Whether the hardware clock is kept in local time instead of UTC.
+    #[dbus_interface(property, name = "LocalRTC")]
+    pub fn local_rtc(&self) -> bool {
+        let backend = self.backend.read().unwrap();
+        backend.local_rtc
+    }
+
+    #[dbus_interface(property, name = "LocalRTC")]
+    pub fn set_local_rtc(&mut self, local: bool) {
+        let mut backend = self.backend.write().unwrap();
+        backend.local_rtc = local;
+    }
+
     // TODO: what should be returned value for commit?
     pub fn commit(&mut self) -> zbus::fdo::Result<()> {
         let backend = self.backend.read().unwrap();
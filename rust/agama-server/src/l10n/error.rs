@@ -24,12 +24,18 @@ use agama_locale_data::{InvalidKeymap, KeymapId};
 pub enum LocaleError {
     #[error("Unknown locale code: {0}")]
     UnknownLocale(String),
+    #[error("Unknown locale category: {0}")]
+    UnknownLocaleCategory(String),
     #[error("Unknown timezone: {0}")]
     UnknownTimezone(String),
     #[error("Unknown keymap: {0}")]
     UnknownKeymap(KeymapId),
     #[error("Invalid keymap: {0}")]
     InvalidKeymap(#[from] InvalidKeymap),
+    #[error("Invalid NTP server: {0}")]
+    InvalidNtpServer(String),
+    #[error("Could not synchronize the clock: {0}")]
+    ClockSync(String),
     #[error("Could not apply the changes")]
     Commit(#[from] std::io::Error),
 }
@@ -23,6 +23,12 @@ use gettextrs::*;
 use serde::ser::{Serialize, SerializeStruct};
 use std::collections::HashMap;
 
+/// Path of the X11 keyboard configuration, relative to the target system root.
+pub const X11_KEYBOARD_PATH: &str = "etc/X11/xorg.conf.d/00-keyboard.conf";
+/// Path of the dracut snippet to include the console keymap in the initrd, relative to the
+/// target system root.
+pub const DRACUT_KEYMAP_PATH: &str = "etc/dracut.conf.d/10-agama-keymap.conf";
+
 // Minimal representation of a keymap
 #[derive(Clone, Debug, utoipa::ToSchema)]
 pub struct Keymap {
@@ -57,11 +63,73 @@ impl Serialize for Keymap {
     }
 }
 
+/// Keyboard layout applied to the X11 server and to the console.
+///
+/// The console keymap is also used at early boot (e.g., to enter the LUKS passphrase).
+#[derive(Clone, Debug, PartialEq, serde::Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyboardMapping {
+    /// X11 layout (e.g., "cz").
+    pub x11_layout: String,
+    /// X11 variant (e.g., "qwerty").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x11_variant: Option<String>,
+    /// Console keymap (e.g., "cz-qwerty").
+    pub console: String,
+}
+
+impl From<&KeymapId> for KeyboardMapping {
+    fn from(id: &KeymapId) -> Self {
+        Self {
+            x11_layout: id.layout.clone(),
+            x11_variant: id.variant.clone(),
+            console: id.dashed(),
+        }
+    }
+}
+
+impl KeyboardMapping {
+    /// Renders the X11 keyboard configuration.
+    pub fn x11_config(&self) -> String {
+        let mut config = format!(
+            "Section \"InputClass\"\n        Identifier \"system-keyboard\"\n        \
+             MatchIsKeyboard \"on\"\n        Option \"XkbLayout\" \"{}\"\n",
+            self.x11_layout
+        );
+        if let Some(variant) = &self.x11_variant {
+            config.push_str(&format!("        Option \"XkbVariant\" \"{}\"\n", variant));
+        }
+        config.push_str("EndSection\n");
+        config
+    }
+}
+
+/// Renders the dracut snippet which includes the console keymap in the initrd.
+pub fn dracut_keymap_config() -> String {
+    "# Include the console keymap (/etc/vconsole.conf) to enter the encryption passphrase\n\
+     add_dracutmodules+=\" i18n \"\n"
+        .to_string()
+}
+
+/// Whether any device in the given crypttab asks for a passphrase at boot time.
+///
+/// The devices using a key file (e.g., swap encrypted with a random key) are ignored.
+///
+/// * `crypttab`: content of the crypttab file.
+pub fn needs_passphrase(crypttab: &str) -> bool {
+    crypttab
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .any(|l| matches!(l.split_whitespace().nth(2), None | Some("none") | Some("-")))
+}
+
 /// Represents the keymaps database.
 ///
 /// The list of supported keymaps is read from `systemd-localed` and the
 /// descriptions from the X Keyboard Configuraiton Database (see
-/// `agama_locale_data::XkbConfigRegistry`).
+/// `agama_locale_data::XkbConfigRegistry`). Hence, all of them have an X11 and a console
+/// mapping.
 #[derive(Default)]
 pub struct KeymapsDatabase {
     keymaps: Vec<Keymap>,
@@ -82,6 +150,11 @@ impl KeymapsDatabase {
         self.keymaps.iter().any(|k| &k.id == id)
     }
 
+    /// Returns the X11 and console mappings of a keymap, if it is known.
+    pub fn mapping(&self, id: &KeymapId) -> Option<KeyboardMapping> {
+        self.exists(id).then(|| KeyboardMapping::from(id))
+    }
+
     /// Returns the list of keymaps.
     pub fn entries(&self) -> &Vec<Keymap> {
         &self.keymaps
@@ -126,3 +199,36 @@ fn get_keymap_descriptions() -> HashMap<String, String> {
 
     keymaps
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{needs_passphrase, KeyboardMapping};
+    use agama_locale_data::KeymapId;
+
+    #[test]
+    fn test_keyboard_mapping() {
+        let id: KeymapId = "cz(qwerty)".parse().unwrap();
+        let mapping = KeyboardMapping::from(&id);
+        assert_eq!(mapping.console, "cz-qwerty");
+        assert_eq!(mapping.x11_layout, "cz");
+        assert_eq!(mapping.x11_variant.as_deref(), Some("qwerty"));
+        assert!(mapping
+            .x11_config()
+            .contains("        Option \"XkbVariant\" \"qwerty\"\n"));
+
+        let id: KeymapId = "us".parse().unwrap();
+        let mapping = KeyboardMapping::from(&id);
+        assert_eq!(mapping.console, "us");
+        assert!(!mapping.x11_config().contains("XkbVariant"));
+    }
+
+    #[test]
+    fn test_needs_passphrase() {
+        assert!(!needs_passphrase(""));
+        assert!(!needs_passphrase(
+            "# swap\ncr_swap /dev/sda2 /dev/urandom swap\n"
+        ));
+        assert!(needs_passphrase("cr_root /dev/sda3 none luks\n"));
+        assert!(needs_passphrase("cr_home UUID=1234\n"));
+    }
+}
@@ -18,30 +18,46 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
+use std::collections::BTreeMap;
 use std::env;
+use std::fs;
 use std::io;
+use std::path::Path;
 use std::process::Command;
 use std::time::Duration;
 
 use crate::error::Error;
+use agama_lib::localization::model::LOCALE_CATEGORIES;
 use agama_locale_data::{KeymapId, LocaleId};
 use regex::Regex;
 use subprocess::{Popen, PopenConfig, PopenError, Redirection};
 
-use super::keyboard::KeymapsDatabase;
-use super::locale::LocalesDatabase;
+use super::keyboard::{self, KeymapsDatabase};
+use super::locale::{self, LocalesDatabase};
 use super::timezone::TimezonesDatabase;
-use super::{helpers, LocaleError};
+use super::{helpers, ntp, time, LocaleError};
 
 pub struct L10n {
     pub timezone: String,
     pub timezones_db: TimezonesDatabase,
     pub locales: Vec<String>,
+    /// Locale of the categories (e.g., "LC_TIME") which do not use the primary locale.
+    pub locale_categories: BTreeMap<String, String>,
     pub locales_db: LocalesDatabase,
     pub keymap: KeymapId,
     pub keymaps_db: KeymapsDatabase,
     pub ui_locale: LocaleId,
     pub ui_keymap: KeymapId,
+    /// Manually configured NTP servers.
+    pub ntp_servers: Vec<String>,
+    /// How to use the NTP servers offered via DHCP.
+    pub ntp_dhcp: ntp::DhcpNtpPolicy,
+    /// NTP servers offered via DHCP to the installer.
+    pub dhcp_ntp_servers: Vec<String>,
+    /// Whether the clock of the running system was synchronized with the current servers.
+    pub ntp_synced: bool,
+    /// Whether the hardware clock of the target system is kept in local time instead of UTC.
+    pub local_rtc: bool,
 }
 
 // timeout for the setxkbmap call (in seconds), when there is an authentication
@@ -117,6 +133,14 @@ fn display() -> String {
     }
 }
 
+/// Writes a file, creating the parent directories if needed.
+fn write_file(path: &Path, content: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)
+}
+
 impl L10n {
     pub fn new_with_locale(ui_locale: &LocaleId) -> Result<Self, Error> {
         const DEFAULT_TIMEZONE: &str = "Europe/Berlin";
@@ -148,11 +172,17 @@ impl L10n {
             keymap: "us".parse().unwrap(),
             timezone: default_timezone,
             locales: vec![default_locale],
+            locale_categories: BTreeMap::new(),
             locales_db,
             timezones_db,
             keymaps_db,
             ui_locale: ui_locale.clone(),
             ui_keymap: ui_keymap.parse().unwrap_or_default(),
+            ntp_servers: vec![],
+            ntp_dhcp: ntp::DhcpNtpPolicy::default(),
+            dhcp_ntp_servers: vec![],
+            ntp_synced: false,
+            local_rtc: false,
         };
 
         Ok(locale)
@@ -168,6 +198,46 @@ impl L10n {
         Ok(())
     }
 
+    /// Sets the locale of individual categories, replacing the previous ones.
+    ///
+    /// The categories set to the primary locale are not kept, so they follow it if it changes.
+    pub fn set_locale_categories(
+        &mut self,
+        categories: &BTreeMap<String, String>,
+    ) -> Result<(), LocaleError> {
+        for (category, locale) in categories {
+            if !LOCALE_CATEGORIES.contains(&category.as_str()) {
+                return Err(LocaleError::UnknownLocaleCategory(category.to_string()));
+            }
+            if !self.locales_db.exists(locale.as_str()) {
+                return Err(LocaleError::UnknownLocale(locale.to_string()));
+            }
+        }
+        let primary = self.locales.first();
+        self.locale_categories = categories
+            .iter()
+            .filter(|(_, locale)| Some(*locale) != primary)
+            .map(|(c, l)| (c.clone(), l.clone()))
+            .collect();
+        Ok(())
+    }
+
+    /// Returns the locale of each category, using the primary locale for the unset ones.
+    pub fn categories(&self) -> BTreeMap<String, String> {
+        let primary = self.locales.first().cloned().unwrap_or_default();
+        LOCALE_CATEGORIES
+            .iter()
+            .map(|category| {
+                let locale = self
+                    .locale_categories
+                    .get(*category)
+                    .unwrap_or(&primary)
+                    .clone();
+                (category.to_string(), locale)
+            })
+            .collect()
+    }
+
     pub fn set_timezone(&mut self, timezone: &str) -> Result<(), LocaleError> {
         // TODO: modify exists() to receive an `&str`
         if !self.timezones_db.exists(&timezone.to_string()) {
@@ -177,8 +247,11 @@ impl L10n {
         Ok(())
     }
 
+    /// Sets the keymap of the installed system.
+    ///
+    /// It must be a known keymap, so it has an X11 and a console mapping.
     pub fn set_keymap(&mut self, keymap_id: KeymapId) -> Result<(), LocaleError> {
-        if !self.keymaps_db.exists(&keymap_id) {
+        if self.keymaps_db.mapping(&keymap_id).is_none() {
             return Err(LocaleError::UnknownKeymap(keymap_id));
         }
 
@@ -186,6 +259,53 @@ impl L10n {
         Ok(())
     }
 
+    pub fn set_ntp_servers(&mut self, servers: &[String]) -> Result<(), LocaleError> {
+        if let Some(invalid) = ntp::invalid_servers(servers).first() {
+            return Err(LocaleError::InvalidNtpServer(invalid.to_string()));
+        }
+
+        self.ntp_servers = servers.to_vec();
+        Ok(())
+    }
+
+    /// Sets the NTP servers offered via DHCP.
+    ///
+    /// The invalid servers are ignored, as they are not under the user's control.
+    pub fn set_dhcp_ntp_servers(&mut self, servers: &[String]) {
+        let invalid = ntp::invalid_servers(servers);
+        if !invalid.is_empty() {
+            log::warn!(
+                "Ignoring invalid NTP servers offered via DHCP: {:?}",
+                invalid
+            );
+        }
+        self.dhcp_ntp_servers = servers
+            .iter()
+            .filter(|s| !invalid.contains(s))
+            .cloned()
+            .collect();
+    }
+
+    /// Returns the NTP servers that will be written to the target system.
+    pub fn effective_ntp_servers(&self) -> Vec<String> {
+        ntp::effective_servers(self.ntp_dhcp, &self.ntp_servers, &self.dhcp_ntp_servers)
+    }
+
+    /// Discards the user settings, going back to the defaults for the current UI locale.
+    ///
+    /// The UI settings are kept.
+    pub fn reset(&mut self) -> Result<(), Error> {
+        let defaults = Self::new_with_locale(&self.ui_locale)?;
+        self.locales = defaults.locales;
+        self.locale_categories = BTreeMap::new();
+        self.timezone = defaults.timezone;
+        self.keymap = defaults.keymap;
+        self.ntp_servers = vec![];
+        self.ntp_dhcp = ntp::DhcpNtpPolicy::default();
+        self.local_rtc = false;
+        Ok(())
+    }
+
     // TODO: use LocaleError
     pub fn translate(&mut self, locale: &LocaleId) -> Result<(), Error> {
         helpers::set_service_locale(locale);
@@ -221,8 +341,23 @@ impl L10n {
     }
 
     // TODO: what should be returned value for commit?
+    /// Writes the configuration to the target system.
+    ///
+    /// The locale.conf file includes the categories which do not use the primary locale. The
+    /// keymap is applied to the X11 server and to the console. If any encrypted device asks
+    /// for a passphrase at boot time, the console keymap is included in the initrd too. It
+    /// relies on the crypttab written by the storage service, which regenerates the initrd
+    /// afterwards. The adjtime file records whether the hardware clock is kept in UTC or in
+    /// local time.
     pub fn commit(&self) -> Result<(), LocaleError> {
         const ROOT: &str = "/mnt";
+        const DEFAULT_LOCALE: &str = "en_US.UTF-8";
+        let mapping = keyboard::KeyboardMapping::from(&self.keymap);
+        let lang = self
+            .locales
+            .first()
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_LOCALE);
 
         Command::new("/usr/bin/systemd-firstboot")
             .args([
@@ -230,13 +365,36 @@ impl L10n {
                 ROOT,
                 "--force",
                 "--locale",
-                self.locales.first().unwrap_or(&"en_US.UTF-8".to_string()),
+                lang,
                 "--keymap",
-                &self.keymap.to_string(),
+                &mapping.console,
                 "--timezone",
                 &self.timezone,
             ])
             .status()?;
+
+        let root = Path::new(ROOT);
+        // systemd-firstboot only sets LANG
+        if !self.locale_categories.is_empty() {
+            write_file(
+                &root.join("etc/locale.conf"),
+                &locale::locale_conf(lang, &self.locale_categories),
+            )?;
+        }
+        write_file(
+            &root.join(keyboard::X11_KEYBOARD_PATH),
+            &mapping.x11_config(),
+        )?;
+        let crypttab = fs::read_to_string(root.join("etc/crypttab")).unwrap_or_default();
+        if keyboard::needs_passphrase(&crypttab) {
+            write_file(
+                &root.join(keyboard::DRACUT_KEYMAP_PATH),
+                &keyboard::dracut_keymap_config(),
+            )?;
+        }
+
+        ntp::write_config(root, &self.effective_ntp_servers(), self.ntp_dhcp)?;
+        time::write_adjtime(root, self.local_rtc)?;
         Ok(())
     }
 
@@ -25,7 +25,7 @@ use agama_locale_data::{InvalidLocaleCode, LocaleId};
 use anyhow::Context;
 use serde::Serialize;
 use serde_with::{serde_as, DisplayFromStr};
-use std::{fs, process::Command};
+use std::{collections::BTreeMap, fs, process::Command};
 
 /// Represents a locale, including the localized language and territory.
 #[serde_as]
@@ -40,6 +40,74 @@ pub struct LocaleEntry {
     pub territory: String,
 }
 
+/// Locales that will be generated in the target system.
+#[derive(Debug, Default, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct LocaleGeneration {
+    /// Full identifiers of the locales (e.g., "es_ES.UTF-8").
+    pub locales: Vec<String>,
+    /// Problems found while resolving the requested locales.
+    pub warnings: Vec<String>,
+}
+
+/// Resolves the requested locales to the full identifiers of the locales to generate.
+///
+/// A language without a territory (e.g., "es") is expanded to a known locale for
+/// that language. Locales which are not known are left out with a warning.
+///
+/// * `requested`: requested locales, the primary one first.
+/// * `known`: locales that can be generated.
+pub fn resolve_locales(requested: &[String], known: &[LocaleId]) -> LocaleGeneration {
+    let mut result = LocaleGeneration::default();
+
+    for code in requested {
+        let resolved = match LocaleId::try_from(code.as_str()) {
+            Ok(id) if known.contains(&id) => Some(id),
+            Ok(_) => None,
+            Err(_) => {
+                let language = code.split('.').next().unwrap_or_default();
+                let candidate = known
+                    .iter()
+                    .find(|l| l.language == language && l.territory == language.to_uppercase())
+                    .or_else(|| known.iter().find(|l| l.language == language));
+                if let Some(candidate) = candidate {
+                    result.warnings.push(format!(
+                        "'{}' does not include a territory, using '{}'",
+                        code, candidate
+                    ));
+                }
+                candidate.cloned()
+            }
+        };
+
+        match resolved {
+            Some(id) => {
+                let id = id.to_string();
+                if !result.locales.contains(&id) {
+                    result.locales.push(id);
+                }
+            }
+            None => result.warnings.push(format!(
+                "'{}' cannot be generated on the target system",
+                code
+            )),
+        }
+    }
+
+    result
+}
+
+/// Renders the locale.conf file.
+///
+/// * `lang`: primary locale.
+/// * `categories`: locale of the categories which do not use the primary one.
+pub fn locale_conf(lang: &str, categories: &BTreeMap<String, String>) -> String {
+    let mut content = format!("LANG={}\n", lang);
+    for (category, locale) in categories {
+        content.push_str(&format!("{}={}\n", category, locale));
+    }
+    content
+}
+
 /// Represents the locales database.
 ///
 /// The list of supported locales is read from `systemd-localed`. However, the
@@ -85,6 +153,16 @@ impl LocalesDatabase {
         &self.locales
     }
 
+    /// Returns the identifiers of the known locales.
+    pub fn ids(&self) -> &[LocaleId] {
+        &self.known_locales
+    }
+
+    /// Resolves the locales to generate. See [resolve_locales].
+    pub fn resolve(&self, requested: &[String]) -> LocaleGeneration {
+        resolve_locales(requested, &self.known_locales)
+    }
+
     /// Gets the supported locales information.
     ///
     /// * `ui_language`: language to use in the translations.
@@ -158,8 +236,51 @@ impl LocalesDatabase {
 
 #[cfg(test)]
 mod tests {
-    use super::LocalesDatabase;
+    use super::{locale_conf, resolve_locales, LocalesDatabase};
     use agama_locale_data::LocaleId;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_resolve_locales() {
+        let known: Vec<LocaleId> = ["en_US", "es_ES", "es_AR", "pt_BR"]
+            .into_iter()
+            .map(|l| l.try_into().unwrap())
+            .collect();
+        let requested: Vec<String> = ["es_AR", "en_US.UTF-8", "es", "pt", "de_DE", "en_US"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        let result = resolve_locales(&requested, &known);
+        assert_eq!(
+            result.locales,
+            vec!["es_AR.UTF-8", "en_US.UTF-8", "es_ES.UTF-8", "pt_BR.UTF-8"]
+        );
+        assert_eq!(
+            result.warnings,
+            vec![
+                "'es' does not include a territory, using 'es_ES.UTF-8'",
+                "'pt' does not include a territory, using 'pt_BR.UTF-8'",
+                "'de_DE' cannot be generated on the target system",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_locale_conf() {
+        let categories = BTreeMap::from([
+            ("LC_TIME".to_string(), "de_DE.UTF-8".to_string()),
+            ("LC_NUMERIC".to_string(), "de_DE.UTF-8".to_string()),
+        ]);
+        assert_eq!(
+            locale_conf("en_US.UTF-8", &categories),
+            "LANG=en_US.UTF-8\nLC_NUMERIC=de_DE.UTF-8\nLC_TIME=de_DE.UTF-8\n"
+        );
+        assert_eq!(
+            locale_conf("en_US.UTF-8", &BTreeMap::new()),
+            "LANG=en_US.UTF-8\n"
+        );
+    }
 
     #[test]
     fn test_read_locales() {
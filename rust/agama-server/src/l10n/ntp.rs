@@ -0,0 +1,250 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Handles the configuration of the NTP servers.
+//!
+//! The servers are written to a chrony configuration snippet in the target system. The servers
+//! offered via DHCP can be used instead of, or in addition to, the manually configured ones (see
+//! [DhcpNtpPolicy]).
+
+use super::LocaleError;
+use serde::{Deserialize, Serialize};
+use std::{fs, io, net::IpAddr, path::Path, process::Command, str::FromStr};
+
+/// Path of the chrony configuration, relative to the target system root.
+pub const CHRONY_CONFIG_PATH: &str = "etc/chrony.d/agama.conf";
+/// Maximum time (in seconds) to wait for the clock synchronization.
+const SYNC_TIMEOUT: u32 = 30;
+/// Directory where the NetworkManager dispatcher writes the NTP servers offered via DHCP.
+const CHRONY_DHCP_DIR: &str = "/run/chrony-dhcp";
+
+/// How to use the NTP servers offered via DHCP.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum DhcpNtpPolicy {
+    /// Use only the manually configured servers.
+    #[default]
+    Ignore,
+    /// Use the DHCP servers, falling back to the manual ones when DHCP does not offer any.
+    Prefer,
+    /// Use both the DHCP and the manual servers.
+    Merge,
+}
+
+impl DhcpNtpPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ignore => "ignore",
+            Self::Prefer => "prefer",
+            Self::Merge => "merge",
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown DHCP NTP policy: '{0}'")]
+pub struct UnknownDhcpNtpPolicy(String);
+
+impl FromStr for DhcpNtpPolicy {
+    type Err = UnknownDhcpNtpPolicy;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ignore" => Ok(Self::Ignore),
+            "prefer" => Ok(Self::Prefer),
+            "merge" => Ok(Self::Merge),
+            _ => Err(UnknownDhcpNtpPolicy(s.to_string())),
+        }
+    }
+}
+
+/// Whether the given string is a valid IP address or hostname.
+pub fn is_valid_server(server: &str) -> bool {
+    if server.parse::<IpAddr>().is_ok() {
+        return true;
+    }
+
+    let hostname = server.strip_suffix('.').unwrap_or(server);
+    !hostname.is_empty()
+        && hostname.len() <= 253
+        && hostname.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Returns the servers that are not valid IP addresses or hostnames.
+pub fn invalid_servers(servers: &[String]) -> Vec<String> {
+    servers
+        .iter()
+        .filter(|s| !is_valid_server(s))
+        .cloned()
+        .collect()
+}
+
+/// Returns the servers to write to the target system, in order of preference.
+///
+/// * `policy`: how to use the servers offered via DHCP.
+/// * `manual`: manually configured servers.
+/// * `dhcp`: servers offered via DHCP.
+pub fn effective_servers(policy: DhcpNtpPolicy, manual: &[String], dhcp: &[String]) -> Vec<String> {
+    let candidates: Vec<&String> = match policy {
+        DhcpNtpPolicy::Ignore => manual.iter().collect(),
+        DhcpNtpPolicy::Prefer if !dhcp.is_empty() => dhcp.iter().collect(),
+        DhcpNtpPolicy::Prefer => manual.iter().collect(),
+        DhcpNtpPolicy::Merge => dhcp.iter().chain(manual).collect(),
+    };
+
+    let mut servers: Vec<String> = vec![];
+    for server in candidates {
+        if !servers.contains(server) {
+            servers.push(server.clone());
+        }
+    }
+    servers
+}
+
+/// Renders the chrony configuration for the given servers.
+///
+/// * `servers`: NTP servers.
+/// * `policy`: how to use the servers offered via DHCP. Unless they are ignored, chrony reads
+///   also the servers that the installed system gets via DHCP.
+pub fn chrony_config(servers: &[String], policy: DhcpNtpPolicy) -> String {
+    let mut config = String::from("# Generated by Agama\n");
+    for server in servers {
+        config.push_str(&format!("server {} iburst\n", server));
+    }
+    if policy != DhcpNtpPolicy::Ignore {
+        config.push_str(&format!("sourcedir {}\n", CHRONY_DHCP_DIR));
+    }
+    config
+}
+
+/// Writes the chrony configuration to the target system.
+///
+/// If there are no servers and the DHCP ones are ignored, any configuration written before is
+/// removed.
+///
+/// * `root`: directory where the target system is mounted.
+/// * `servers`: NTP servers.
+/// * `policy`: how to use the servers offered via DHCP.
+pub fn write_config(root: &Path, servers: &[String], policy: DhcpNtpPolicy) -> io::Result<()> {
+    let path = root.join(CHRONY_CONFIG_PATH);
+    if servers.is_empty() && policy == DhcpNtpPolicy::Ignore {
+        return match fs::remove_file(path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        };
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, chrony_config(servers, policy))
+}
+
+/// Synchronizes the clock of the running system using the given servers.
+///
+/// It runs chrony once and it waits until the clock is set or the timeout is reached.
+pub fn sync_clock(servers: &[String]) -> Result<(), LocaleError> {
+    let directives: Vec<String> = servers
+        .iter()
+        .map(|s| format!("server {} iburst", s))
+        .collect();
+    let status = Command::new("/usr/sbin/chronyd")
+        .args(["-q", "-t", &SYNC_TIMEOUT.to_string()])
+        .args(directives)
+        .status()?;
+
+    if !status.success() {
+        return Err(LocaleError::ClockSync(status.to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_server() {
+        assert!(is_valid_server("pool.ntp.org"));
+        assert!(is_valid_server("ntp1."));
+        assert!(is_valid_server("192.168.1.1"));
+        assert!(is_valid_server("2001:db8::1"));
+        assert!(!is_valid_server(""));
+        assert!(!is_valid_server("-ntp.example.net"));
+        assert!(!is_valid_server("ntp server"));
+        assert!(!is_valid_server("ntp..example.net"));
+    }
+
+    #[test]
+    fn test_write_config() {
+        let root = tempfile::tempdir().unwrap();
+        let servers = vec!["0.pool.ntp.org".to_string(), "192.168.1.1".to_string()];
+        write_config(root.path(), &servers, DhcpNtpPolicy::Ignore).unwrap();
+
+        let path = root.path().join(CHRONY_CONFIG_PATH);
+        let config = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            config,
+            "# Generated by Agama\nserver 0.pool.ntp.org iburst\nserver 192.168.1.1 iburst\n"
+        );
+
+        write_config(root.path(), &[], DhcpNtpPolicy::Ignore).unwrap();
+        assert!(!path.exists());
+        write_config(root.path(), &[], DhcpNtpPolicy::Ignore).unwrap();
+    }
+
+    #[test]
+    fn test_effective_servers() {
+        let manual = vec!["pool.ntp.org".to_string(), "192.168.1.1".to_string()];
+        let dhcp = vec!["192.168.1.1".to_string(), "ntp.example.net".to_string()];
+
+        assert_eq!(
+            effective_servers(DhcpNtpPolicy::Ignore, &manual, &dhcp),
+            manual
+        );
+        assert_eq!(
+            effective_servers(DhcpNtpPolicy::Prefer, &manual, &dhcp),
+            dhcp
+        );
+        assert_eq!(
+            effective_servers(DhcpNtpPolicy::Prefer, &manual, &[]),
+            manual
+        );
+        assert_eq!(
+            effective_servers(DhcpNtpPolicy::Merge, &manual, &dhcp),
+            vec!["192.168.1.1", "ntp.example.net", "pool.ntp.org"]
+        );
+    }
+
+    #[test]
+    fn test_chrony_config_with_dhcp() {
+        let servers = vec!["ntp.example.net".to_string()];
+        assert_eq!(
+            chrony_config(&servers, DhcpNtpPolicy::Merge),
+            "# Generated by Agama\nserver ntp.example.net iburst\nsourcedir /run/chrony-dhcp\n"
+        );
+    }
+}
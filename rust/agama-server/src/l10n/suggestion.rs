@@ -0,0 +1,293 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Suggests a locale and a keymap from the browser preferences (`Accept-Language` header).
+
+use agama_locale_data::{keyboard::xkeyboard::XKeyboards, KeymapId, LocaleId};
+use serde::Serialize;
+
+/// Locale used when none of the requested languages is supported.
+pub const FALLBACK_LOCALE: &str = "en_US.UTF-8";
+/// Keymap used when there is no keymap for the suggested locale.
+pub const FALLBACK_KEYMAP: &str = "us";
+
+/// Locale and keymap suggested for the user. It is not applied.
+#[derive(Clone, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LocaleSuggestion {
+    /// Suggested locale (e.g., "de_CH.UTF-8").
+    pub locale: String,
+    /// Suggested keymap (e.g., "ch").
+    pub keymap: String,
+    /// Language tag of the header matching the locale (e.g., "de-CH"). It is not set when using
+    /// the fallback locale.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_tag: Option<String>,
+    /// Whether none of the requested languages is supported.
+    pub fallback: bool,
+}
+
+/// Parses an `Accept-Language` header (e.g., "de-CH, de;q=0.9, en;q=0.8").
+///
+/// It returns the language tags sorted by their quality value, keeping the order of the header
+/// for the same quality. The wildcard and the tags with a zero (or wrong) quality are discarded.
+///
+/// * `header`: value of the header.
+pub fn parse_accept_language(header: &str) -> Vec<String> {
+    let mut tags: Vec<(String, f32)> = header
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';').map(str::trim);
+            let tag = parts.next().filter(|t| !t.is_empty() && *t != "*")?;
+            let mut quality = 1.0;
+            for param in parts {
+                if let Some(value) = param.strip_prefix("q=") {
+                    quality = value.parse().ok()?;
+                }
+            }
+            (quality > 0.0).then(|| (tag.to_string(), quality))
+        })
+        .collect();
+    // the sort is stable, so the tags with the same quality keep their order
+    tags.sort_by(|a, b| b.1.total_cmp(&a.1));
+    tags.into_iter().map(|(tag, _)| tag).collect()
+}
+
+/// Finds the known locale which better matches a language tag.
+///
+/// The tag territory is preferred (e.g., "de_CH" for "de-CH"). If it is not given or not known,
+/// the main territory of the language (e.g., "de_DE" for "de") or any other one is used. UTF-8
+/// locales are preferred.
+///
+/// * `tag`: language tag (e.g., "de-CH" or "pt_BR").
+/// * `known`: known locales.
+pub fn match_locale<'a>(tag: &str, known: &'a [LocaleId]) -> Option<&'a LocaleId> {
+    let mut parts = tag.split(['-', '_']);
+    let language = parts.next()?.to_lowercase();
+    // skip the script subtag, if any (e.g., "Latn" in "sr-Latn-RS")
+    let territory = parts.find(|p| p.len() == 2).map(|p| p.to_uppercase());
+
+    let candidates: Vec<&LocaleId> = known.iter().filter(|l| l.language == language).collect();
+    let best = |territory: &str| {
+        let in_territory = candidates.iter().filter(|l| l.territory == territory);
+        in_territory
+            .clone()
+            .find(|l| l.encoding == "UTF-8")
+            .or(in_territory.clone().next())
+            .copied()
+    };
+
+    territory
+        .as_deref()
+        .and_then(best)
+        .or_else(|| best(&language.to_uppercase()))
+        .or_else(|| {
+            candidates
+                .iter()
+                .find(|l| l.encoding == "UTF-8")
+                .or(candidates.first())
+                .copied()
+        })
+}
+
+/// Finds the best keymap for a locale.
+///
+/// It uses the keyboard with the highest rank for the locale territory and, if there is none,
+/// for the locale language. Only the known keymaps are considered.
+///
+/// * `locale`: locale to find the keymap for.
+/// * `keyboards`: keyboards database.
+/// * `known`: known keymaps.
+pub fn match_keymap(
+    locale: &LocaleId,
+    keyboards: &XKeyboards,
+    known: &[KeymapId],
+) -> Option<KeymapId> {
+    let usable = || {
+        keyboards
+            .keyboard
+            .iter()
+            .filter_map(|k| Some((k, k.id.parse::<KeymapId>().ok()?)))
+            .filter(|(_, id)| known.contains(id))
+    };
+
+    let by_territory = usable()
+        .filter_map(|(k, id)| {
+            let ranked = k.territories.territory.iter();
+            let rank = ranked.filter(|t| t.id == locale.territory).map(|t| t.rank);
+            Some((rank.max()?, id))
+        })
+        .max_by_key(|(rank, _)| *rank);
+    let by_language = || {
+        usable()
+            .filter_map(|(k, id)| {
+                let ranked = k.languages.language.iter();
+                let rank = ranked.filter(|l| l.id == locale.language).map(|l| l.rank);
+                Some((rank.max()?, id))
+            })
+            .max_by_key(|(rank, _)| *rank)
+    };
+    by_territory.or_else(by_language).map(|(_, id)| id)
+}
+
+/// Suggests a locale and a keymap for an `Accept-Language` header.
+///
+/// * `header`: value of the header, if any.
+/// * `locales`: known locales.
+/// * `keyboards`: keyboards database, if it could be read.
+/// * `keymaps`: known keymaps.
+pub fn suggest(
+    header: Option<&str>,
+    locales: &[LocaleId],
+    keyboards: Option<&XKeyboards>,
+    keymaps: &[KeymapId],
+) -> LocaleSuggestion {
+    let matched = parse_accept_language(header.unwrap_or_default())
+        .into_iter()
+        .find_map(|tag| Some((match_locale(&tag, locales)?, tag)));
+
+    let keymap = matched
+        .as_ref()
+        .zip(keyboards)
+        .and_then(|((locale, _), keyboards)| match_keymap(locale, keyboards, keymaps))
+        .map(|k| k.to_string())
+        .unwrap_or(FALLBACK_KEYMAP.to_string());
+
+    match matched {
+        Some((locale, tag)) => LocaleSuggestion {
+            locale: locale.to_string(),
+            keymap,
+            language_tag: Some(tag),
+            fallback: false,
+        },
+        None => LocaleSuggestion {
+            locale: FALLBACK_LOCALE.to_string(),
+            keymap,
+            language_tag: None,
+            fallback: true,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agama_locale_data::{
+        keyboard::xkeyboard::XKeyboard,
+        ranked::{RankedLanguage, RankedLanguages, RankedTerritories, RankedTerritory},
+    };
+
+    fn locales(codes: &[&str]) -> Vec<LocaleId> {
+        codes.iter().map(|c| (*c).try_into().unwrap()).collect()
+    }
+
+    fn keyboard(id: &str, languages: &[(&str, u16)], territories: &[(&str, u16)]) -> XKeyboard {
+        XKeyboard {
+            id: id.to_string(),
+            description: id.to_string(),
+            ascii: true,
+            comment: None,
+            languages: RankedLanguages {
+                language: languages
+                    .iter()
+                    .map(|(id, rank)| RankedLanguage {
+                        id: id.to_string(),
+                        rank: *rank,
+                    })
+                    .collect(),
+            },
+            territories: RankedTerritories {
+                territory: territories
+                    .iter()
+                    .map(|(id, rank)| RankedTerritory {
+                        id: id.to_string(),
+                        rank: *rank,
+                    })
+                    .collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_accept_language() {
+        assert_eq!(
+            parse_accept_language("en;q=0.8, de-CH, *;q=0.5, fr;q=0, de;q=0.9, es;q=0.8"),
+            vec!["de-CH", "de", "en", "es"]
+        );
+        assert_eq!(parse_accept_language("de;q=high, en"), vec!["en"]);
+        assert!(parse_accept_language("").is_empty());
+    }
+
+    #[test]
+    fn test_match_locale() {
+        let known = locales(&[
+            "de_AT.UTF-8",
+            "de_DE.UTF-8",
+            "pt_PT.UTF-8",
+            "pt_BR.UTF-8",
+            "sr_RS.UTF-8",
+        ]);
+        let matched = |tag| match_locale(tag, &known).map(|l| l.to_string());
+        assert_eq!(matched("de-AT"), Some("de_AT.UTF-8".to_string()));
+        assert_eq!(matched("DE-ch"), Some("de_DE.UTF-8".to_string()));
+        assert_eq!(matched("de"), Some("de_DE.UTF-8".to_string()));
+        assert_eq!(matched("pt_BR"), Some("pt_BR.UTF-8".to_string()));
+        assert_eq!(matched("sr-Latn-RS"), Some("sr_RS.UTF-8".to_string()));
+        assert_eq!(matched("ja"), None);
+    }
+
+    #[test]
+    fn test_suggest() {
+        let known = locales(&["de_CH.UTF-8", "de_DE.UTF-8", "en_US.UTF-8"]);
+        let keyboards = XKeyboards {
+            keyboard: vec![
+                keyboard("de", &[("de", 100)], &[("DE", 100), ("CH", 10)]),
+                keyboard("ch", &[("de", 50)], &[("CH", 100)]),
+                keyboard("us", &[("en", 100)], &[("US", 100)]),
+            ],
+        };
+        let keymaps: Vec<KeymapId> = ["de", "ch", "us"]
+            .iter()
+            .map(|k| k.parse().unwrap())
+            .collect();
+
+        let suggestion = suggest(Some("ja, de-CH;q=0.9"), &known, Some(&keyboards), &keymaps);
+        assert_eq!(
+            suggestion,
+            LocaleSuggestion {
+                locale: "de_CH.UTF-8".to_string(),
+                keymap: "ch".to_string(),
+                language_tag: Some("de-CH".to_string()),
+                fallback: false,
+            }
+        );
+
+        // the keymap follows the language if there is none for the territory
+        let known = locales(&["de_LU.UTF-8"]);
+        let suggestion = suggest(Some("de-LU"), &known, Some(&keyboards), &keymaps);
+        assert_eq!(suggestion.keymap, "de");
+
+        let suggestion = suggest(Some("ja"), &known, None, &keymaps);
+        assert!(suggestion.fallback);
+        assert_eq!(suggestion.locale, FALLBACK_LOCALE);
+        assert_eq!(suggestion.keymap, FALLBACK_KEYMAP);
+        assert_eq!(suggest(None, &known, None, &keymaps), suggestion);
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Handles how the hardware clock (RTC) of the target system is interpreted.
+//!
+//! The policy is written to the adjtime file, as `timedatectl set-local-rtc` does. Keeping the
+//! RTC in local time is only meant for dual-boot setups with Windows.
+
+use std::{fs, io, path::Path};
+
+/// Path of the adjtime file, relative to the target system root.
+pub const ADJTIME_PATH: &str = "etc/adjtime";
+
+/// Warning to show when the RTC is kept in local time.
+pub const LOCAL_RTC_WARNING: &str = "Keeping the hardware clock in local time is discouraged \
+    unless the system is shared with Windows: the clock is not adjusted on daylight saving time \
+    changes";
+
+/// Renders the adjtime file for the given policy.
+///
+/// The drift and the calibration values of an existing file are kept.
+///
+/// * `current`: current content of the adjtime file (empty if it does not exist).
+/// * `local`: whether the RTC is kept in local time.
+pub fn adjtime(current: &str, local: bool) -> String {
+    let mut lines = current.lines();
+    let drift = lines.next().unwrap_or("0.0 0 0.0");
+    let calibration = lines.next().unwrap_or("0");
+    let mode = if local { "LOCAL" } else { "UTC" };
+    format!("{}\n{}\n{}\n", drift, calibration, mode)
+}
+
+/// Writes the RTC policy to the target system.
+///
+/// * `root`: directory where the target system is mounted.
+/// * `local`: whether the RTC is kept in local time.
+pub fn write_adjtime(root: &Path, local: bool) -> io::Result<()> {
+    let path = root.join(ADJTIME_PATH);
+    let current = fs::read_to_string(&path).unwrap_or_default();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, adjtime(&current, local))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjtime() {
+        assert_eq!(adjtime("", false), "0.0 0 0.0\n0\nUTC\n");
+        assert_eq!(adjtime("", true), "0.0 0 0.0\n0\nLOCAL\n");
+        assert_eq!(
+            adjtime("0.012 1700000000 0.0\n1700000000\nUTC\n", true),
+            "0.012 1700000000 0.0\n1700000000\nLOCAL\n"
+        );
+    }
+
+    #[test]
+    fn test_write_adjtime() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        write_adjtime(root, true).unwrap();
+        let content = fs::read_to_string(root.join(ADJTIME_PATH)).unwrap();
+        assert!(content.ends_with("LOCAL\n"));
+
+        write_adjtime(root, false).unwrap();
+        let content = fs::read_to_string(root.join(ADJTIME_PATH)).unwrap();
+        assert!(content.ends_with("UTC\n"));
+    }
+}
@@ -21,24 +21,35 @@
 //! This module implements the web API for the localization module.
 
 use super::{
-    error::LocaleError, keyboard::Keymap, locale::LocaleEntry, timezone::TimezoneEntry, L10n,
+    error::LocaleError,
+    keyboard::{KeyboardMapping, Keymap},
+    locale::{LocaleEntry, LocaleGeneration},
+    ntp::{self, DhcpNtpPolicy},
+    suggestion::{self, LocaleSuggestion},
+    time,
+    timezone::TimezoneEntry,
+    L10n,
 };
 use crate::{
     error::Error,
-    web::{Event, EventsSender},
+    network::NetworkManagerClient,
+    web::{CommitProvider, Event, EventsSender},
 };
 use agama_lib::{
     error::ServiceError, localization::model::LocaleConfig, localization::LocaleProxy,
     proxies::LocaleProxy as ManagerLocaleProxy,
 };
 use agama_locale_data::LocaleId;
+use async_trait::async_trait;
 use axum::{
     extract::State,
-    http::StatusCode,
-    response::IntoResponse,
-    routing::{get, patch},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, patch, post},
     Json, Router,
 };
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -47,6 +58,8 @@ struct LocaleState<'a> {
     locale: Arc<RwLock<L10n>>,
     proxy: LocaleProxy<'a>,
     manager_proxy: ManagerLocaleProxy<'a>,
+    /// Connection to the system bus, where NetworkManager lives.
+    system_dbus: zbus::Connection,
     events: EventsSender,
 }
 
@@ -61,18 +74,26 @@ pub async fn l10n_service(
     let locale = L10n::new_with_locale(&id).unwrap();
     let proxy = LocaleProxy::new(&dbus).await?;
     let manager_proxy = ManagerLocaleProxy::new(&dbus).await?;
+    let system_dbus = zbus::Connection::system().await?;
     let state = LocaleState {
         locale: Arc::new(RwLock::new(locale)),
         proxy,
         manager_proxy,
+        system_dbus,
         events,
     };
 
     let router = Router::new()
         .route("/keymaps", get(keymaps))
+        .route("/keyboard", get(keyboard))
         .route("/locales", get(locales))
+        .route("/locales/generated", get(generated_locales))
         .route("/timezones", get(timezones))
+        .route("/suggestion", get(suggestion))
         .route("/config", patch(set_config).get(get_config))
+        .route("/ntp", get(get_ntp).put(set_ntp))
+        .route("/time", get(get_time).put(set_time))
+        .route("/reset", post(reset))
         .with_state(state);
     Ok(router)
 }
@@ -86,6 +107,25 @@ async fn locales(State(state): State<LocaleState<'_>>) -> Json<Vec<LocaleEntry>>
     Json(locales)
 }
 
+/// Returns the locales that will be generated in the target system.
+///
+/// The requested locales are expanded to full identifiers (e.g., "es" to "es_ES.UTF-8"). The
+/// locales of the individual categories are generated too.
+#[utoipa::path(
+    get,
+    path = "/locales/generated",
+    context_path = "/api/l10n",
+    responses(
+      (status = 200, description = "Locales to generate", body = LocaleGeneration)
+  )
+)]
+async fn generated_locales(State(state): State<LocaleState<'_>>) -> Json<LocaleGeneration> {
+    let data = state.locale.read().await;
+    let mut requested = data.locales.clone();
+    requested.extend(data.locale_categories.values().cloned());
+    Json(data.locales_db.resolve(&requested))
+}
+
 #[utoipa::path(
     get,
     path = "/timezones",
@@ -114,6 +154,64 @@ async fn keymaps(State(state): State<LocaleState<'_>>) -> Json<Vec<Keymap>> {
     Json(keymaps)
 }
 
+/// Suggests a locale and a keymap from the `Accept-Language` header, without applying them.
+///
+/// The languages are tried in order of preference. If none of them is supported, it falls back
+/// to the "en_US.UTF-8" locale and the "us" keymap.
+#[utoipa::path(
+    get,
+    path = "/suggestion",
+    context_path = "/api/l10n",
+    responses(
+      (status = 200, description = "Suggested locale and keymap", body = LocaleSuggestion)
+    )
+)]
+async fn suggestion(
+    State(state): State<LocaleState<'_>>,
+    headers: HeaderMap,
+) -> Json<LocaleSuggestion> {
+    let header = headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|h| h.to_str().ok());
+    let keyboards = match agama_locale_data::get_xkeyboards() {
+        Ok(keyboards) => Some(keyboards),
+        Err(error) => {
+            log::warn!("Could not read the keyboards database: {error}");
+            None
+        }
+    };
+    let data = state.locale.read().await;
+    let keymaps: Vec<_> = data
+        .keymaps_db
+        .entries()
+        .iter()
+        .map(|k| k.id.clone())
+        .collect();
+    Json(suggestion::suggest(
+        header,
+        data.locales_db.ids(),
+        keyboards.as_ref(),
+        &keymaps,
+    ))
+}
+
+/// Returns the X11 and console keyboard mappings to apply to the target system.
+///
+/// The console keymap is also included in the initrd if an encrypted device asks for a
+/// passphrase at boot time.
+#[utoipa::path(
+    get,
+    path = "/keyboard",
+    context_path = "/api/l10n",
+    responses(
+      (status = 200, description = "Keyboard mappings", body = KeyboardMapping)
+    )
+)]
+async fn keyboard(State(state): State<LocaleState<'_>>) -> Json<KeyboardMapping> {
+    let data = state.locale.read().await;
+    Json(KeyboardMapping::from(&data.keymap))
+}
+
 // TODO: update all or nothing
 // TODO: send only the attributes that have changed
 #[utoipa::path(
@@ -142,6 +240,11 @@ async fn set_config(
         changes.timezone.clone_from(&value.timezone);
     }
 
+    if let Some(categories) = &value.categories {
+        data.set_locale_categories(categories)?;
+        changes.categories = Some(data.categories());
+    }
+
     if let Some(keymap_id) = &value.keymap {
         let keymap_id = keymap_id.parse().map_err(LocaleError::InvalidKeymap)?;
         data.set_keymap(keymap_id)?;
@@ -191,11 +294,288 @@ async fn get_config(State(state): State<LocaleState<'_>>) -> Json<LocaleConfig>
         locales: Some(data.locales.clone()),
         keymap: Some(data.keymap.to_string()),
         timezone: Some(data.timezone.to_string()),
+        categories: Some(data.categories()),
         ui_locale: Some(data.ui_locale.to_string()),
         ui_keymap: Some(data.ui_keymap.to_string()),
     })
 }
 
+/// NTP configuration
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NtpConfig {
+    /// Manually configured NTP servers.
+    servers: Vec<String>,
+    /// How to use the NTP servers offered via DHCP.
+    dhcp: DhcpNtpPolicy,
+    /// NTP servers offered via DHCP to the installer.
+    dhcp_servers: Vec<String>,
+    /// NTP servers that will be written to the target system.
+    effective_servers: Vec<String>,
+    /// Whether the clock of the installer was synchronized with the current servers.
+    synced: bool,
+}
+
+impl From<&L10n> for NtpConfig {
+    fn from(data: &L10n) -> Self {
+        Self {
+            servers: data.ntp_servers.clone(),
+            dhcp: data.ntp_dhcp,
+            dhcp_servers: data.dhcp_ntp_servers.clone(),
+            effective_servers: data.effective_ntp_servers(),
+            synced: data.ntp_synced,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, utoipa::ToSchema)]
+pub struct NtpParams {
+    /// NTP servers (hostnames or IP addresses).
+    servers: Vec<String>,
+    /// How to use the NTP servers offered via DHCP. They are ignored by default.
+    #[serde(default)]
+    dhcp: DhcpNtpPolicy,
+    /// Whether to synchronize the clock of the installer now.
+    #[serde(default)]
+    sync: bool,
+}
+
+/// Refreshes the NTP servers offered via DHCP to the installer and returns them.
+///
+/// The servers are read from NetworkManager, unless the policy is "ignore", and they are written
+/// to the localization D-Bus service, so its effective servers are up to date. An empty list is
+/// used if NetworkManager cannot be queried.
+///
+/// * `proxy`: localization D-Bus service.
+/// * `system_dbus`: connection to the system bus, where NetworkManager lives.
+/// * `policy`: how to use the NTP servers offered via DHCP.
+async fn refresh_dhcp_ntp_servers(
+    proxy: &LocaleProxy<'_>,
+    system_dbus: &zbus::Connection,
+    policy: DhcpNtpPolicy,
+) -> Vec<String> {
+    let servers = if policy == DhcpNtpPolicy::Ignore {
+        Ok(vec![])
+    } else {
+        match NetworkManagerClient::new(system_dbus.clone()).await {
+            Ok(client) => client.dhcp_ntp_servers().await,
+            Err(e) => Err(e),
+        }
+    };
+    let servers = servers.unwrap_or_else(|e| {
+        log::warn!("Could not read the NTP servers offered via DHCP: {e}");
+        vec![]
+    });
+
+    let dhcp: Vec<_> = servers.iter().map(String::as_str).collect();
+    if let Err(e) = proxy.set_dhcp_ntp_servers(&dhcp).await {
+        log::warn!("Could not synchronize DHCP NTP servers in the localization D-Bus service: {e}");
+    }
+    servers
+}
+
+/// Returns the NTP configuration.
+///
+/// The servers offered via DHCP are the ones read when the configuration was set. They are read
+/// again right before writing the configuration to the target system.
+#[utoipa::path(
+    get,
+    path = "/ntp",
+    context_path = "/api/l10n",
+    responses(
+        (status = 200, description = "NTP configuration", body = NtpConfig)
+    )
+)]
+async fn get_ntp(State(state): State<LocaleState<'_>>) -> Json<NtpConfig> {
+    let data = state.locale.read().await;
+    Json(NtpConfig::from(&*data))
+}
+
+/// Sets the NTP servers for the target system.
+///
+/// Unless the DHCP policy is "ignore", the servers offered via DHCP are read from NetworkManager
+/// and the effective list is reported back. The manual servers are validated, but the invalid
+/// DHCP ones are just discarded.
+#[utoipa::path(
+    put,
+    path = "/ntp",
+    context_path = "/api/l10n",
+    request_body = NtpParams,
+    responses(
+        (status = 200, description = "NTP configuration", body = NtpConfig),
+        (status = 422, description = "Invalid NTP servers")
+    )
+)]
+async fn set_ntp(
+    State(state): State<LocaleState<'_>>,
+    Json(params): Json<NtpParams>,
+) -> Result<Response, Error> {
+    let invalid = ntp::invalid_servers(&params.servers);
+    if !invalid.is_empty() {
+        let body = json!({ "error": "Invalid NTP servers", "invalid": invalid });
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response());
+    }
+
+    let servers: Vec<_> = params.servers.iter().map(String::as_str).collect();
+    if let Err(e) = state.proxy.set_ntp_servers(&servers).await {
+        log::warn!("Could not synchronize NTP servers in the localization D-Bus service: {e}");
+    }
+    if let Err(e) = state.proxy.set_ntp_dhcp(params.dhcp.as_str()).await {
+        log::warn!(
+            "Could not synchronize the DHCP NTP policy in the localization D-Bus service: {e}"
+        );
+    }
+    let dhcp_servers =
+        refresh_dhcp_ntp_servers(&state.proxy, &state.system_dbus, params.dhcp).await;
+
+    let mut data = state.locale.write().await;
+    let previous = data.effective_ntp_servers();
+    data.set_ntp_servers(&params.servers)?;
+    data.ntp_dhcp = params.dhcp;
+    data.set_dhcp_ntp_servers(&dhcp_servers);
+    let effective = data.effective_ntp_servers();
+    // a previous synchronization does not count for other servers
+    if effective != previous {
+        data.ntp_synced = false;
+    }
+    drop(data);
+
+    let mut synced = false;
+    if params.sync && !effective.is_empty() {
+        let result = tokio::task::spawn_blocking(move || ntp::sync_clock(&effective))
+            .await
+            .map_err(|e| Error::Anyhow(e.to_string()))?;
+        match result {
+            Ok(()) => synced = true,
+            Err(e) => log::warn!("Could not synchronize the clock: {e}"),
+        }
+    }
+
+    let mut data = state.locale.write().await;
+    if params.sync {
+        data.ntp_synced = synced;
+    }
+    Ok(Json(NtpConfig::from(&*data)).into_response())
+}
+
+/// Hardware clock configuration
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeConfig {
+    /// Whether the hardware clock is kept in local time instead of UTC.
+    local_rtc: bool,
+    /// Warnings about the chosen policy.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+}
+
+impl TimeConfig {
+    fn new(local_rtc: bool) -> Self {
+        let warnings = if local_rtc {
+            vec![time::LOCAL_RTC_WARNING.to_string()]
+        } else {
+            vec![]
+        };
+        Self {
+            local_rtc,
+            warnings,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeParams {
+    /// Whether to keep the hardware clock in local time (`false` means UTC).
+    local_rtc: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/time",
+    context_path = "/api/l10n",
+    responses(
+        (status = 200, description = "Hardware clock configuration", body = TimeConfig)
+    )
+)]
+async fn get_time(State(state): State<LocaleState<'_>>) -> Json<TimeConfig> {
+    let data = state.locale.read().await;
+    Json(TimeConfig::new(data.local_rtc))
+}
+
+/// Sets whether the hardware clock of the target system is kept in UTC or in local time.
+#[utoipa::path(
+    put,
+    path = "/time",
+    context_path = "/api/l10n",
+    request_body = TimeParams,
+    responses(
+        (status = 200, description = "Hardware clock configuration", body = TimeConfig),
+        (status = 422, description = "The policy is not a boolean value")
+    )
+)]
+async fn set_time(
+    State(state): State<LocaleState<'_>>,
+    Json(params): Json<TimeParams>,
+) -> Json<TimeConfig> {
+    if let Err(e) = state.proxy.set_local_rtc(params.local_rtc).await {
+        log::warn!("Could not synchronize the RTC policy in the localization D-Bus service: {e}");
+    }
+
+    let mut data = state.locale.write().await;
+    data.local_rtc = params.local_rtc;
+    Json(TimeConfig::new(data.local_rtc))
+}
+
+/// Discards the localization settings, going back to the defaults.
+///
+/// The UI locale and keymap are not affected.
+#[utoipa::path(
+    post,
+    path = "/reset",
+    context_path = "/api/l10n",
+    operation_id = "reset_l10n_config",
+    responses(
+        (status = 200, description = "Default localization configuration", body = LocaleConfig)
+    )
+)]
+async fn reset(State(state): State<LocaleState<'_>>) -> Result<Json<LocaleConfig>, Error> {
+    let mut data = state.locale.write().await;
+    data.reset()?;
+
+    let changes = LocaleConfig {
+        locales: Some(data.locales.clone()),
+        keymap: Some(data.keymap.to_string()),
+        timezone: Some(data.timezone.to_string()),
+        categories: Some(data.categories()),
+        ..Default::default()
+    };
+    if let Err(e) = update_dbus(&state.proxy, &changes).await {
+        log::warn!("Could not synchronize settings in the localization D-Bus service: {e}");
+    }
+    if let Err(e) = state.proxy.set_ntp_servers(&[]).await {
+        log::warn!("Could not synchronize NTP servers in the localization D-Bus service: {e}");
+    }
+    if let Err(e) = state
+        .proxy
+        .set_ntp_dhcp(DhcpNtpPolicy::Ignore.as_str())
+        .await
+    {
+        log::warn!(
+            "Could not synchronize the DHCP NTP policy in the localization D-Bus service: {e}"
+        );
+    }
+    if let Err(e) = state.proxy.set_local_rtc(false).await {
+        log::warn!("Could not synchronize the RTC policy in the localization D-Bus service: {e}");
+    }
+
+    _ = state.events.send(Event::ConfigReset {
+        service: "l10n".to_string(),
+    });
+    _ = state.events.send(Event::L10nConfigChanged(changes.clone()));
+    Ok(Json(changes))
+}
+
 pub async fn update_dbus(
     client: &LocaleProxy<'_>,
     config: &LocaleConfig,
@@ -213,9 +593,56 @@ pub async fn update_dbus(
         client.set_timezone(timezone).await?;
     }
 
+    if let Some(categories) = &config.categories {
+        let categories = categories
+            .iter()
+            .map(|(c, l)| (c.as_str(), l.as_str()))
+            .collect();
+        client.set_locale_categories(categories).await?;
+    }
+
     if let Some(ui_locale) = &config.ui_locale {
         client.set_uilocale(ui_locale).await?;
     }
 
     Ok(())
 }
+
+/// Writes the localization settings to the installed system.
+///
+/// The settings are written to the target file systems, so the storage module must be committed
+/// first.
+///
+/// The NTP servers offered via DHCP are refreshed before writing them.
+pub struct L10nCommit<'a> {
+    proxy: LocaleProxy<'a>,
+    /// Connection to the system bus, where NetworkManager lives.
+    system_dbus: zbus::Connection,
+}
+
+impl<'a> L10nCommit<'a> {
+    pub async fn new(dbus: zbus::Connection) -> Result<L10nCommit<'a>, ServiceError> {
+        Ok(Self {
+            proxy: LocaleProxy::new(&dbus).await?,
+            system_dbus: zbus::Connection::system().await?,
+        })
+    }
+}
+
+#[async_trait]
+impl<'a> CommitProvider for L10nCommit<'a> {
+    fn requires(&self) -> &[&str] {
+        &["storage"]
+    }
+
+    async fn commit(&self) -> Result<(), Error> {
+        let policy = self
+            .proxy
+            .ntp_dhcp()
+            .await?
+            .parse()
+            .unwrap_or(DhcpNtpPolicy::Ignore);
+        refresh_dhcp_ntp_servers(&self.proxy, &self.system_dbus, policy).await;
+        Ok(self.proxy.commit().await?)
+    }
+}
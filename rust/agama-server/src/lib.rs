@@ -18,14 +18,19 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
+pub mod bootloader;
 pub mod cert;
 pub mod dbus;
 pub mod error;
+pub mod firewall;
 pub mod l10n;
 pub mod logs;
 pub mod manager;
 pub mod network;
+pub mod preview;
+pub mod profile;
 pub mod questions;
+pub mod scripts;
 pub mod software;
 pub mod storage;
 pub mod users;
@@ -20,24 +20,90 @@
 
 //! Functions to work with logs.
 
-use anyhow::Context;
+use anyhow::{anyhow, Context};
 use libsystemd::logging;
-use tracing_subscriber::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use tracing::Level;
+use tracing_subscriber::{filter::LevelFilter, prelude::*, reload, Registry};
+
+/// Handle to change the log level once the logging mechanism is initialized.
+static LEVEL_HANDLE: OnceLock<reload::Handle<LevelFilter, Registry>> = OnceLock::new();
+
+/// Log verbosity.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(value: LogLevel) -> Self {
+        match value {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+impl From<Level> for LogLevel {
+    fn from(value: Level) -> Self {
+        match value {
+            Level::ERROR => LogLevel::Error,
+            Level::WARN => LogLevel::Warn,
+            Level::INFO => LogLevel::Info,
+            Level::DEBUG => LogLevel::Debug,
+            Level::TRACE => LogLevel::Trace,
+        }
+    }
+}
 
 /// Initializes the logging mechanism.
 ///
 /// It is based on [Tracing](https://github.com/tokio-rs/tracing), part of the Tokio ecosystem.
+/// The messages are logged from the info level on until the level is changed with
+/// [set_log_level].
 pub fn init_logging() -> anyhow::Result<()> {
+    let (filter, handle) = reload::Layer::new(LevelFilter::INFO);
+    let registry = tracing_subscriber::registry().with(filter);
     if logging::connected_to_journal() {
         let journald = tracing_journald::layer().context("could not connect to journald")?;
-        tracing_subscriber::registry().with(journald).init();
+        registry.with(journald).init();
     } else {
-        let subscriber = tracing_subscriber::fmt()
+        let fmt = tracing_subscriber::fmt::layer()
             .with_file(true)
             .with_line_number(true)
-            .compact()
-            .finish();
-        tracing::subscriber::set_global_default(subscriber)?;
+            .compact();
+        tracing::subscriber::set_global_default(registry.with(fmt))?;
     }
+    _ = LEVEL_HANDLE.set(handle);
     Ok(())
 }
+
+/// Returns the current log level.
+///
+/// It returns `None` if the logging mechanism is not initialized or everything is filtered out.
+pub fn log_level() -> Option<LogLevel> {
+    let handle = LEVEL_HANDLE.get()?;
+    let filter = handle.with_current(|filter| *filter).ok()?;
+    filter.into_level().map(LogLevel::from)
+}
+
+/// Changes the log level, which takes effect immediately.
+///
+/// * `level`: new log level.
+pub fn set_log_level(level: LogLevel) -> anyhow::Result<()> {
+    let handle = LEVEL_HANDLE
+        .get()
+        .ok_or_else(|| anyhow!("the logging mechanism is not initialized"))?;
+    handle
+        .reload(LevelFilter::from(level))
+        .context("could not change the log level")
+}
@@ -18,5 +18,8 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
+pub mod facts;
+pub mod journal;
+pub mod result;
 pub mod web;
 pub use web::manager_service;
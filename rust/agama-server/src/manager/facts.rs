@@ -0,0 +1,144 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Detects hardware facts which are relevant for the installation.
+
+use serde::Serialize;
+use std::{fs, path::Path};
+
+/// Hardware facts of the system.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HardwareFacts {
+    /// Whether there is a usable TPM 2.0 device.
+    pub tpm2: bool,
+    /// Total amount of memory, in bytes (0 if it cannot be determined).
+    pub memory: u64,
+    /// Whether the system was booted with UEFI Secure Boot enabled.
+    pub secure_boot: bool,
+}
+
+impl HardwareFacts {
+    /// Detects the facts of the running system.
+    pub fn probe() -> Self {
+        Self::probe_root(Path::new("/"))
+    }
+
+    /// Detects the facts of the running system in a blocking task, so it can be called from
+    /// async code.
+    pub async fn detect() -> Self {
+        tokio::task::spawn_blocking(Self::probe)
+            .await
+            .unwrap_or_default()
+    }
+
+    /// Detects the facts using the given root directory (useful for testing).
+    ///
+    /// * `root`: directory containing the `sys` and `proc` file systems.
+    pub fn probe_root(root: &Path) -> Self {
+        Self {
+            tpm2: has_tpm2(root),
+            memory: total_memory(root).unwrap_or_default(),
+            secure_boot: secure_boot_enabled(root),
+        }
+    }
+}
+
+/// Total amount of memory (in bytes), according to `/proc/meminfo`.
+fn total_memory(root: &Path) -> Option<u64> {
+    let meminfo = fs::read_to_string(root.join("proc/meminfo")).ok()?;
+    let line = meminfo.lines().find(|l| l.starts_with("MemTotal:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Whether there is a TPM 2.0 device, according to the `/sys/class/tpm` directory.
+fn has_tpm2(root: &Path) -> bool {
+    let Ok(entries) = fs::read_dir(root.join("sys/class/tpm")) else {
+        return false;
+    };
+
+    entries.flatten().any(|entry| {
+        fs::read_to_string(entry.path().join("tpm_version_major"))
+            .is_ok_and(|version| version.trim() == "2")
+    })
+}
+
+/// EFI variable holding the Secure Boot state (in the EFI global variable namespace).
+const SECURE_BOOT_EFIVAR: &str =
+    "sys/firmware/efi/efivars/SecureBoot-8be4df61-93ca-11d2-aa0d-00e098032b8c";
+
+/// Whether Secure Boot is enabled, according to the `SecureBoot` EFI variable.
+///
+/// The variable starts with 4 bytes of attributes, followed by the value (1 if enabled).
+fn secure_boot_enabled(root: &Path) -> bool {
+    fs::read(root.join(SECURE_BOOT_EFIVAR)).is_ok_and(|data| data.get(4) == Some(&1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HardwareFacts;
+    use std::fs;
+
+    #[test]
+    fn test_probe_tpm2() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        assert!(!HardwareFacts::probe_root(root).tpm2);
+
+        let tpm = root.join("sys/class/tpm/tpm0");
+        fs::create_dir_all(&tpm).unwrap();
+        fs::write(tpm.join("tpm_version_major"), "1\n").unwrap();
+        assert!(!HardwareFacts::probe_root(root).tpm2);
+
+        fs::write(tpm.join("tpm_version_major"), "2\n").unwrap();
+        assert!(HardwareFacts::probe_root(root).tpm2);
+    }
+
+    #[test]
+    fn test_probe_memory() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        assert_eq!(HardwareFacts::probe_root(root).memory, 0);
+
+        fs::create_dir_all(root.join("proc")).unwrap();
+        fs::write(
+            root.join("proc/meminfo"),
+            "MemTotal:        4028484 kB\nMemFree:          250000 kB\n",
+        )
+        .unwrap();
+        assert_eq!(HardwareFacts::probe_root(root).memory, 4028484 * 1024);
+    }
+
+    #[test]
+    fn test_probe_secure_boot() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        assert!(!HardwareFacts::probe_root(root).secure_boot);
+
+        let efivar = root.join(super::SECURE_BOOT_EFIVAR);
+        fs::create_dir_all(efivar.parent().unwrap()).unwrap();
+        fs::write(&efivar, [6, 0, 0, 0, 0]).unwrap();
+        assert!(!HardwareFacts::probe_root(root).secure_boot);
+
+        fs::write(&efivar, [6, 0, 0, 0, 1]).unwrap();
+        assert!(HardwareFacts::probe_root(root).secure_boot);
+    }
+}
@@ -0,0 +1,152 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Follows the journal of the installer services.
+//!
+//! Each follower runs its own `journalctl` process, so the number of followers and the amount of
+//! past lines they get are limited.
+
+use std::{
+    pin::Pin,
+    process::Stdio,
+    sync::{Arc, OnceLock},
+};
+
+use futures_util::stream::{self, Stream};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader, Lines},
+    process::{Child, ChildStdout, Command},
+    sync::{OwnedSemaphorePermit, Semaphore},
+};
+
+use crate::error::Error;
+
+/// Units whose journal is followed.
+pub const JOURNAL_UNITS: [&str; 2] = ["agama.service", "agama-web-server.service"];
+
+/// Maximum number of past lines sent when following the journal from the start.
+const MAX_BACKLOG_LINES: usize = 5000;
+
+/// Maximum number of `journalctl` processes running at the same time.
+const MAX_FOLLOWERS: usize = 4;
+
+/// Permits to follow the journal, one for each running `journalctl` process.
+fn followers() -> Arc<Semaphore> {
+    static FOLLOWERS: OnceLock<Arc<Semaphore>> = OnceLock::new();
+    FOLLOWERS
+        .get_or_init(|| Arc::new(Semaphore::new(MAX_FOLLOWERS)))
+        .clone()
+}
+
+/// Returns the arguments for `journalctl`.
+///
+/// * `from_start`: whether to start from the beginning of the current boot (up to
+///   [MAX_BACKLOG_LINES] lines) or only from the new entries.
+fn journalctl_args(from_start: bool) -> Vec<String> {
+    let mut args = vec![
+        "--follow".to_string(),
+        "--no-pager".to_string(),
+        "--output=short-iso".to_string(),
+    ];
+    args.extend(JOURNAL_UNITS.iter().map(|u| format!("--unit={}", u)));
+    if from_start {
+        args.push("--boot".to_string());
+        args.push(format!("--lines={}", MAX_BACKLOG_LINES));
+    } else {
+        args.push("--lines=0".to_string());
+    }
+    args
+}
+
+/// Returns a stream of the journal lines as they are written.
+///
+/// The `journalctl` process is killed when the stream is dropped. It fails if there are already
+/// [MAX_FOLLOWERS] streams.
+///
+/// * `from_start`: whether to start from the beginning of the current boot or only from the new
+///   entries.
+pub fn follow(from_start: bool) -> Result<Pin<Box<dyn Stream<Item = String> + Send>>, Error> {
+    let permit = followers()
+        .try_acquire_owned()
+        .map_err(|_| Error::Anyhow("Too many journal followers".to_string()))?;
+    let mut child = Command::new("journalctl")
+        .args(journalctl_args(from_start))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| Error::Anyhow(format!("Could not follow the journal: {}", e)))?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| Error::Anyhow("Could not read the journal".to_string()))?;
+    Ok(lines_stream(BufReader::new(stdout).lines(), child, permit))
+}
+
+/// Turns the output of the child process into a stream of lines, keeping the process (and its
+/// permit) alive while the stream exists.
+fn lines_stream(
+    lines: Lines<BufReader<ChildStdout>>,
+    child: Child,
+    permit: OwnedSemaphorePermit,
+) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+    Box::pin(stream::unfold(
+        (lines, child, permit),
+        |(mut lines, child, permit)| async move {
+            match lines.next_line().await {
+                Ok(Some(line)) => Some((line, (lines, child, permit))),
+                Ok(None) => None,
+                Err(e) => {
+                    tracing::warn!("Could not read the journal: {}", e);
+                    None
+                }
+            }
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{followers, journalctl_args, MAX_FOLLOWERS};
+
+    #[test]
+    fn test_journalctl_args() {
+        let args = journalctl_args(false);
+        assert!(args.contains(&"--follow".to_string()));
+        assert!(args.contains(&"--unit=agama.service".to_string()));
+        assert!(args.contains(&"--lines=0".to_string()));
+        assert!(!args.contains(&"--boot".to_string()));
+
+        let args = journalctl_args(true);
+        assert!(args.contains(&"--boot".to_string()));
+        assert!(args.contains(&"--lines=5000".to_string()));
+        assert!(!args.contains(&"--lines=0".to_string()));
+    }
+
+    #[test]
+    fn test_followers() {
+        let permits: Vec<_> = (0..MAX_FOLLOWERS)
+            .map(|_| followers().try_acquire_owned().unwrap())
+            .collect();
+        assert!(followers().try_acquire_owned().is_err());
+        drop(permits);
+        assert!(followers().try_acquire_owned().is_ok());
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Summary of a finished installation.
+//!
+//! It is meant to be archived by automation tools (e.g., CI pipelines) and it does not include
+//! any secret (e.g., passwords).
+
+use agama_lib::{network::settings::NetworkConnection, storage::model::Action};
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Start and end of the installation, as seconds since the Unix epoch.
+#[derive(Clone, Debug, Default)]
+pub struct InstallationTimes {
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+}
+
+impl InstallationTimes {
+    /// Registers the start of the installation, discarding the previous times.
+    pub fn start(&mut self) {
+        self.started_at = Some(now());
+        self.finished_at = None;
+    }
+
+    /// Registers the end of the installation.
+    pub fn finish(&mut self) {
+        if self.started_at.is_some() {
+            self.finished_at = Some(now());
+        }
+    }
+
+    /// Duration of the installation in seconds, if it is known.
+    pub fn duration(&self) -> Option<u64> {
+        Some(self.finished_at?.saturating_sub(self.started_at?))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Installed product.
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductSummary {
+    /// Product ID (e.g., "Tumbleweed").
+    pub id: String,
+    /// Product name (e.g., "openSUSE Tumbleweed").
+    pub name: String,
+    /// Version of the product (e.g., "16.0"), if the product defines it.
+    pub version: Option<String>,
+}
+
+/// Summary of a finished installation.
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallationResult {
+    /// Whether the installation succeeded. It is not set if the manager service does not report
+    /// it.
+    pub success: Option<bool>,
+    pub product: ProductSummary,
+    /// Storage actions which were applied to the disks.
+    pub storage_actions: Vec<Action>,
+    /// Network connections of the installed system, without secrets.
+    pub network: Vec<NetworkConnection>,
+    /// Names of the created users.
+    pub users: Vec<String>,
+    /// Start of the installation (seconds since the Unix epoch).
+    pub started_at: Option<u64>,
+    /// End of the installation (seconds since the Unix epoch).
+    pub finished_at: Option<u64>,
+    /// Duration of the installation in seconds.
+    pub duration: Option<u64>,
+    /// Warnings reported by the modules (e.g., "storage: ...").
+    pub warnings: Vec<String>,
+}
+
+/// Removes the secrets of a network connection.
+///
+/// * `connection`: connection to clean up.
+pub fn remove_connection_secrets(connection: &mut NetworkConnection) {
+    if let Some(wireless) = connection.wireless.as_mut() {
+        wireless.password = None;
+    }
+    if let Some(ieee_8021x) = connection.ieee_8021x.as_mut() {
+        ieee_8021x.password = None;
+        ieee_8021x.ca_cert_password = None;
+        ieee_8021x.client_cert_password = None;
+        ieee_8021x.private_key_password = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_installation_times() {
+        let mut times = InstallationTimes::default();
+        times.finish();
+        assert_eq!(times.duration(), None);
+
+        times.start();
+        assert_eq!(times.duration(), None);
+        times.finish();
+        assert!(times.duration().is_some());
+    }
+}
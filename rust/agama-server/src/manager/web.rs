@@ -27,27 +27,51 @@
 
 use agama_lib::{
     error::ServiceError,
-    manager::{InstallationPhase, ManagerClient},
+    manager::{InstallationPhase, MachineIdPolicy, ManagerClient, SelfUpdateInfo},
+    network::{settings::NetworkConnection, types::Status},
+    product::ProductClient,
     proxies::Manager1Proxy,
+    storage::StorageClient,
+    users::UsersClient,
 };
+use anyhow::Context;
 use axum::{
-    extract::{Request, State},
+    extract::{Query, Request, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{
+        sse::{Event as SseEvent, KeepAlive},
+        IntoResponse, Response, Sse,
+    },
     routing::{get, post},
     Json, Router,
 };
 use rand::distributions::{Alphanumeric, DistString};
-use serde::Serialize;
-use std::{pin::Pin, process::Command};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{convert::Infallible, pin::Pin, process::Command, sync::Arc};
+use tokio::sync::RwLock;
 use tokio_stream::{Stream, StreamExt};
 use tower_http::services::ServeFile;
 
 use crate::{
     error::Error,
+    logs::{self, LogLevel},
+    manager::{
+        facts::HardwareFacts,
+        journal,
+        result::{self, InstallationResult, InstallationTimes, ProductSummary},
+    },
+    network::system::NetworkSystemClient,
+    storage::{
+        impact::{ImpactConfirmation, ImpactToken},
+        web::CommitLock,
+    },
     web::{
-        common::{progress_router, service_status_router},
-        Event,
+        common::{
+            operations_router, progress_router, service_status_router, ApplyParams, Operations,
+        },
+        generous_timeout, no_timeout, CommittedModules, Event, EventsSender, IssueSeverity,
+        IssuesReadiness, ReadinessProvider,
     },
 };
 
@@ -55,6 +79,12 @@ use crate::{
 pub struct ManagerState<'a> {
     dbus: zbus::Connection,
     manager: ManagerClient<'a>,
+    network: NetworkSystemClient,
+    times: Arc<RwLock<InstallationTimes>>,
+    committed: CommittedModules,
+    storage: StorageClient<'a>,
+    impact: ImpactToken,
+    operations: Operations,
 }
 
 /// Holds information about the manager's status.
@@ -101,27 +131,73 @@ pub async fn manager_stream(
 }
 
 /// Sets up and returns the axum service for the manager module
-pub async fn manager_service(dbus: zbus::Connection) -> Result<Router, ServiceError> {
+///
+/// * `dbus`: D-Bus connection.
+/// * `network`: network client, used to summarize the installation.
+/// * `committed`: modules committed through `/api/commit`, which prevent running the whole
+///   installation.
+/// * `impact`: confirmation token of the storage impact, required to start the installation.
+/// * `events`: channel to send the events (e.g., when a probing operation finishes).
+pub async fn manager_service(
+    dbus: zbus::Connection,
+    network: NetworkSystemClient,
+    committed: CommittedModules,
+    impact: ImpactToken,
+    events: EventsSender,
+) -> Result<Router, ServiceError> {
     const DBUS_SERVICE: &str = "org.opensuse.Agama.Manager1";
     const DBUS_PATH: &str = "/org/opensuse/Agama/Manager1";
 
     let status_router = service_status_router(&dbus, DBUS_SERVICE, DBUS_PATH).await?;
     let progress_router = progress_router(&dbus, DBUS_SERVICE, DBUS_PATH).await?;
     let manager = ManagerClient::new(dbus.clone()).await?;
-    let state = ManagerState { manager, dbus };
+    let storage = StorageClient::new(dbus.clone()).await?;
+    let times = Arc::new(RwLock::new(InstallationTimes::default()));
+    tokio::spawn({
+        let dbus = dbus.clone();
+        let manager = manager.clone();
+        let times = Arc::clone(&times);
+        async move {
+            if let Err(error) = track_installation(dbus, manager, times).await {
+                tracing::warn!("Could not track the installation times: {}", error);
+            }
+        }
+    });
+    let state = ManagerState {
+        manager,
+        dbus,
+        network,
+        times,
+        committed,
+        storage,
+        impact,
+        operations: Operations::new("manager", events),
+    };
+    let operations = operations_router(state.operations.clone());
     Ok(Router::new()
-        .route("/probe", post(probe_action))
-        .route("/probe_sync", post(probe_sync_action))
-        .route("/install", post(install_action))
-        .route("/finish", post(finish_action))
+        .route("/probe", generous_timeout(post(probe_action)))
+        .route("/probe_sync", no_timeout(post(probe_sync_action)))
+        .route("/install", no_timeout(post(install_action)))
+        .route("/finish", no_timeout(post(finish_action)))
         .route("/installer", get(installer_status))
-        .route("/logs", get(download_logs))
+        .route("/result", get(installation_result))
+        .route("/facts", get(facts))
+        .route("/self_update", get(self_update))
+        .route("/self_update/skip", post(skip_self_update))
+        .route("/machine_id", get(machine_id).put(set_machine_id))
+        .route("/logs", no_timeout(get(download_logs)))
+        .route("/logs/level", get(log_level).put(set_log_level))
+        .route("/logs/follow", get(follow_logs))
+        .nest("/operations", operations)
         .merge(status_router)
         .merge(progress_router)
         .with_state(state))
 }
 
 /// Starts the probing process.
+///
+/// Using the `wait` query parameter, the probing runs as an operation which can be waited for or
+/// polled through `/api/manager/operations`.
 // The Probe D-Bus method is blocking and will not return until the probing is finished. To avoid a
 // long-lived HTTP connection, this method returns immediately (with a 200) and runs the request on
 // a separate task.
@@ -129,14 +205,31 @@ pub async fn manager_service(dbus: zbus::Connection) -> Result<Router, ServiceEr
     post,
     path = "/probe",
     context_path = "/api/manager",
+    params(ApplyParams),
     responses(
       (
           status = 200,
-          description = "The probing was requested but there is no way to know whether it succeeded."
-       )
+          description = "The probing was requested but there is no way to know whether it succeeded (the probing finished, if the wait parameter is set)."
+       ),
+      (status = 202, description = "The system is being probed", body = crate::web::common::Operation)
     )
 )]
-async fn probe_action(State(state): State<ManagerState<'_>>) -> Result<(), Error> {
+async fn probe_action(
+    State(state): State<ManagerState<'static>>,
+    Query(params): Query<ApplyParams>,
+) -> Result<Response, Error> {
+    if params.wait.is_some() {
+        let manager = state.manager.clone();
+        let response = state
+            .operations
+            .run(&params, async move {
+                manager.probe().await.map_err(|e| e.to_string())?;
+                Ok(serde_json::Value::Null)
+            })
+            .await;
+        return Ok(response);
+    }
+
     let dbus = state.dbus.clone();
     tokio::spawn(async move {
         let result = dbus
@@ -152,7 +245,7 @@ async fn probe_action(State(state): State<ManagerState<'_>>) -> Result<(), Error
             tracing::error!("Could not start probing: {:?}", error);
         }
     });
-    Ok(())
+    Ok(().into_response())
 }
 
 /// Starts the probing process and waits until it is done.
@@ -171,16 +264,80 @@ async fn probe_sync_action(State(state): State<ManagerState<'_>>) -> Result<(),
 }
 
 /// Starts the installation process.
+///
+/// It requires the token from `GET /api/storage/impact`, as the installation commits the storage
+/// changes. It is refused once any module was committed through `/api/commit`, as the
+/// installation would apply its changes again, and while a storage commit is in progress.
 #[utoipa::path(
     post,
     path = "/install",
     context_path = "/api/manager",
+    request_body = ImpactConfirmation,
     responses(
-      (status = 200, description = "The installation process was started.")
+      (status = 200, description = "The installation process was started."),
+      (status = 409, description = "Some modules were already committed or a storage commit is in progress."),
+      (status = 422, description = "The token is missing or the storage proposal changed since its impact was reviewed", body = Vec<String>)
     )
 )]
-async fn install_action(State(state): State<ManagerState<'_>>) -> Result<(), Error> {
-    state.manager.install().await?;
+async fn install_action(
+    State(state): State<ManagerState<'static>>,
+    confirmation: Option<Json<ImpactConfirmation>>,
+) -> Result<Response, Error> {
+    let committed = state.committed.list();
+    if !committed.is_empty() {
+        let body = json!({
+            "error": format!("Some modules were already committed: {}", committed.join(", "))
+        });
+        return Ok((StatusCode::CONFLICT, Json(body)).into_response());
+    }
+
+    let Json(confirmation) = confirmation.unwrap_or_default();
+    let problems = state
+        .impact
+        .verify(&state.storage, confirmation.token.as_deref())
+        .await?;
+    if !problems.is_empty() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(problems)).into_response());
+    }
+
+    let Some(guard) = CommitLock::shared().try_acquire() else {
+        let body = json!({ "error": "A storage commit is already in progress" });
+        return Ok((StatusCode::CONFLICT, Json(body)).into_response());
+    };
+
+    // the lock is released when the installation finishes, even if the client goes away
+    let manager = state.manager.clone();
+    let result = tokio::spawn(async move {
+        let _guard = guard;
+        manager.install().await
+    })
+    .await
+    .map_err(|e| ServiceError::InternalError(e.to_string()))?;
+    result?;
+    Ok(StatusCode::OK.into_response())
+}
+
+/// Records the start and the end of the installation.
+///
+/// It watches the installation phase instead of the install action, so the times are recorded no
+/// matter how the installation was started (e.g., directly through D-Bus).
+async fn track_installation(
+    dbus: zbus::Connection,
+    manager: ManagerClient<'_>,
+    times: Arc<RwLock<InstallationTimes>>,
+) -> Result<(), ServiceError> {
+    let proxy = Manager1Proxy::new(&dbus).await?;
+    let mut changes = proxy.receive_current_installation_phase_changed().await;
+    while let Some(change) = changes.next().await {
+        let phase = InstallationPhase::try_from(change.get().await?)?;
+        // the manager is busy while installing, so an already finished installation is skipped
+        if phase != InstallationPhase::Install || !manager.is_busy().await {
+            continue;
+        }
+        times.write().await.start();
+        manager.wait().await?;
+        times.write().await.finish();
+    }
     Ok(())
 }
 
@@ -225,6 +382,203 @@ async fn installer_status(
     Ok(Json(status))
 }
 
+/// D-Bus objects whose warnings are included in the installation result: (module, service, path).
+const WARNING_SOURCES: [(&str, &str, &str); 4] = [
+    (
+        "product",
+        "org.opensuse.Agama.Software1",
+        "/org/opensuse/Agama/Software1/Product",
+    ),
+    (
+        "software",
+        "org.opensuse.Agama.Software1",
+        "/org/opensuse/Agama/Software1",
+    ),
+    (
+        "storage",
+        "org.opensuse.Agama.Storage1",
+        "/org/opensuse/Agama/Storage1",
+    ),
+    (
+        "users",
+        "org.opensuse.Agama.Manager1",
+        "/org/opensuse/Agama/Users1",
+    ),
+];
+
+/// Returns a machine-readable summary of the installation.
+///
+/// It is available once the installation finishes, even if it failed, and it does not include any
+/// secret.
+#[utoipa::path(
+    get,
+    path = "/result",
+    context_path = "/api/manager",
+    responses(
+      (status = 200, description = "Summary of the installation.", body = InstallationResult),
+      (status = 409, description = "The installation has not finished yet.")
+    )
+)]
+async fn installation_result(State(state): State<ManagerState<'_>>) -> Result<Response, Error> {
+    let phase = state.manager.current_installation_phase().await?;
+    if phase != InstallationPhase::Install || state.manager.is_busy().await {
+        let body = json!({ "error": "The installation has not finished yet" });
+        return Ok((StatusCode::CONFLICT, Json(body)).into_response());
+    }
+
+    let product = ProductClient::new(state.dbus.clone()).await?;
+    let id = product.product().await?;
+    let (name, version) = product
+        .products()
+        .await?
+        .into_iter()
+        .find(|p| p.id == id)
+        .map(|p| (p.name, p.version))
+        .unwrap_or_default();
+
+    let storage = StorageClient::new(state.dbus.clone()).await?;
+    let network: Vec<NetworkConnection> = state
+        .network
+        .get_connections()
+        .await
+        .context("Could not read the network connections")?
+        .into_iter()
+        .filter(|c| c.status != Status::Removed)
+        .filter_map(|c| NetworkConnection::try_from(c).ok())
+        .map(|mut c| {
+            result::remove_connection_secrets(&mut c);
+            c
+        })
+        .collect();
+
+    let users = UsersClient::new(state.dbus.clone()).await?;
+    let first_user = users.first_user().await?.user_name;
+
+    let mut warnings = vec![];
+    for (module, service, path) in WARNING_SOURCES {
+        let issues = IssuesReadiness::new(&state.dbus, service, path)
+            .await?
+            .issues()
+            .await?;
+        warnings.extend(
+            issues
+                .into_iter()
+                .filter(|i| i.severity == IssueSeverity::Warning)
+                .map(|i| format!("{}: {}", module, i.message)),
+        );
+    }
+
+    let times = state.times.read().await.clone();
+    let result = InstallationResult {
+        success: state
+            .manager
+            .installation_failed()
+            .await?
+            .map(|failed| !failed),
+        product: ProductSummary { id, name, version },
+        storage_actions: storage.actions().await?,
+        network,
+        users: Some(first_user)
+            .into_iter()
+            .filter(|u| !u.is_empty())
+            .collect(),
+        started_at: times.started_at,
+        finished_at: times.finished_at,
+        duration: times.duration(),
+        warnings,
+    };
+    Ok(Json(result).into_response())
+}
+
+/// Returns the hardware facts of the system.
+#[utoipa::path(
+    get,
+    path = "/facts",
+    context_path = "/api/manager",
+    responses(
+      (status = 200, description = "Hardware facts.", body = HardwareFacts)
+    )
+)]
+async fn facts() -> Json<HardwareFacts> {
+    Json(HardwareFacts::detect().await)
+}
+
+/// Returns whether an installer self-update is available or was applied.
+#[utoipa::path(
+    get,
+    path = "/self_update",
+    context_path = "/api/manager",
+    responses(
+      (status = 200, description = "Self-update state.", body = SelfUpdateInfo),
+      (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn self_update(State(state): State<ManagerState<'_>>) -> Result<Json<SelfUpdateInfo>, Error> {
+    Ok(Json(state.manager.self_update().await?))
+}
+
+/// Opts out of the installer self-update for the rest of the session.
+#[utoipa::path(
+    post,
+    path = "/self_update/skip",
+    context_path = "/api/manager",
+    responses(
+      (status = 204, description = "The self-update will not be applied."),
+      (status = 409, description = "The self-update was already applied."),
+      (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn skip_self_update(State(state): State<ManagerState<'_>>) -> Result<Response, Error> {
+    if state.manager.self_update().await?.applied {
+        let body = json!({ "error": "The self-update was already applied" });
+        return Ok((StatusCode::CONFLICT, Json(body)).into_response());
+    }
+    state.manager.skip_self_update().await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Returns the machine-id policy for the installed system.
+#[utoipa::path(
+    get,
+    path = "/machine_id",
+    context_path = "/api/manager",
+    responses(
+      (status = 200, description = "Machine-id policy.", body = MachineIdPolicy),
+      (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn machine_id(State(state): State<ManagerState<'_>>) -> Result<Json<MachineIdPolicy>, Error> {
+    Ok(Json(state.manager.machine_id_policy().await?))
+}
+
+/// Sets the machine-id policy for the installed system.
+///
+/// An explicit machine-id must consist of 32 hexadecimal characters.
+#[utoipa::path(
+    put,
+    path = "/machine_id",
+    context_path = "/api/manager",
+    request_body = MachineIdPolicy,
+    responses(
+      (status = 200, description = "The policy was set.", body = MachineIdPolicy),
+      (status = 422, description = "The policy is not valid.", body = Vec<String>),
+      (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn set_machine_id(
+    State(state): State<ManagerState<'_>>,
+    Json(policy): Json<MachineIdPolicy>,
+) -> Result<Response, Error> {
+    let errors = policy.validate();
+    if !errors.is_empty() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(errors)).into_response());
+    }
+
+    let policy = policy.normalize();
+    state.manager.set_machine_id_policy(&policy).await?;
+    Ok(Json(state.manager.machine_id_policy().await?).into_response())
+}
+
 /// Returns agama logs
 #[utoipa::path(get, path = "/api/manager/logs", responses(
   (status = 200, description = "Download logs blob.")
@@ -245,6 +599,115 @@ pub async fn download_logs() -> impl IntoResponse {
     }
 }
 
+#[derive(Clone, Debug, Default, Deserialize, utoipa::IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowLogsParams {
+    /// Whether to start from the beginning of the current boot instead of only the new lines.
+    #[serde(default)]
+    from_start: bool,
+}
+
+/// Items of the journal stream.
+enum FollowItem {
+    /// A journal line.
+    Line(String),
+    /// `journalctl` exited.
+    Exited,
+    /// The installation finished.
+    Finished,
+}
+
+/// Follows the journal of the installer services.
+///
+/// Each journal line is sent as an SSE event. Once the installation finishes, a `finished` event
+/// is sent and the stream is closed. If `journalctl` exits before, the stream is closed without
+/// the `finished` event. When following from the start, only the last lines are sent.
+#[utoipa::path(
+    get,
+    path = "/logs/follow",
+    context_path = "/api/manager",
+    params(FollowLogsParams),
+    responses(
+        (status = 200, description = "Stream of journal lines (text/event-stream)."),
+        (status = 400, description = "The journal could not be followed (e.g., too many followers)")
+    )
+)]
+async fn follow_logs(
+    State(state): State<ManagerState<'_>>,
+    Query(params): Query<FollowLogsParams>,
+) -> Result<Sse<impl Stream<Item = Result<SseEvent, Infallible>>>, Error> {
+    let lines = journal::follow(params.from_start)?;
+    let manager = ManagerClient::new(state.dbus.clone())
+        .await
+        .map_err(ServiceError::from)?;
+    let finished = async move {
+        if let Err(error) = manager.wait_for_installation().await {
+            tracing::warn!("Could not wait for the installation: {}", error);
+            std::future::pending::<()>().await;
+        }
+        FollowItem::Finished
+    };
+    let lines = lines
+        .map(FollowItem::Line)
+        .chain(tokio_stream::once(FollowItem::Exited));
+    let items = futures_util::stream::select(lines, futures_util::stream::once(Box::pin(finished)));
+    let stream = futures_util::StreamExt::scan(items, false, |done, item| {
+        let event = match item {
+            _ if *done => None,
+            FollowItem::Line(line) => Some(SseEvent::default().data(line)),
+            FollowItem::Finished => {
+                *done = true;
+                Some(SseEvent::default().event("finished").data(""))
+            }
+            FollowItem::Exited => None,
+        };
+        std::future::ready(event)
+    })
+    .map(Ok);
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Log level of the web server.
+#[derive(Clone, Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct LogLevelConfig {
+    pub level: LogLevel,
+}
+
+/// Returns the current log level of the web server.
+#[utoipa::path(
+    get,
+    path = "/logs/level",
+    context_path = "/api/manager",
+    responses(
+        (status = 200, description = "Current log level", body = LogLevelConfig),
+        (status = 400, description = "The log level is not available")
+    )
+)]
+async fn log_level() -> Result<Json<LogLevelConfig>, Error> {
+    let level =
+        logs::log_level().ok_or_else(|| Error::Anyhow("The log level is not available".into()))?;
+    Ok(Json(LogLevelConfig { level }))
+}
+
+/// Changes the log level of the web server.
+///
+/// The new level applies immediately to the subsequent log output.
+#[utoipa::path(
+    put,
+    path = "/logs/level",
+    context_path = "/api/manager",
+    request_body = LogLevelConfig,
+    responses(
+        (status = 200, description = "The log level was changed", body = LogLevelConfig),
+        (status = 400, description = "The log level could not be changed")
+    )
+)]
+async fn set_log_level(Json(config): Json<LogLevelConfig>) -> Result<Json<LogLevelConfig>, Error> {
+    logs::set_log_level(config.level)?;
+    tracing::info!("Log level changed to {:?}", config.level);
+    Ok(Json(config))
+}
+
 async fn generate_logs() -> Result<String, Error> {
     let random_name: String = Alphanumeric.sample_string(&mut rand::thread_rng(), 8);
     let path = format!("/run/agama/logs_{random_name}");
@@ -61,13 +61,18 @@
 mod action;
 mod adapter;
 pub mod error;
+pub mod gateway;
 pub mod model;
 mod nm;
+pub mod stack;
 pub mod system;
 pub mod web;
 
 pub use action::Action;
 pub use adapter::{Adapter, NetworkAdapterError};
 pub use model::NetworkState;
-pub use nm::NetworkManagerAdapter;
+pub use nm::{
+    connection_to_keyfile, keyfile_name, keyfile_to_connection, KeyfileError,
+    NetworkManagerAdapter, NetworkManagerClient,
+};
 pub use system::NetworkSystem;
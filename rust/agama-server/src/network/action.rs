@@ -23,7 +23,11 @@ use agama_lib::network::types::DeviceType;
 use tokio::sync::oneshot;
 use uuid::Uuid;
 
-use super::{error::NetworkStateError, model::GeneralState, NetworkAdapterError};
+use super::{
+    error::NetworkStateError,
+    model::{Checkpoint, CheckpointStatus, ConnectivityState, DnsEntry, GeneralState},
+    NetworkAdapterError,
+};
 
 pub type Responder<T> = oneshot::Sender<T>;
 pub type ControllerConnection = (Connection, Vec<String>);
@@ -62,6 +66,10 @@ pub enum Action {
     /// Gets all the existent devices
     GetDevices(Responder<Vec<Device>>),
     GetGeneralState(Responder<GeneralState>),
+    /// Gets the connectivity state
+    GetConnectivity(Responder<Result<ConnectivityState, NetworkAdapterError>>),
+    /// Gets the DNS configuration in use
+    GetDnsConfiguration(Responder<Result<Vec<DnsEntry>, NetworkAdapterError>>),
     /// Sets a controller's ports. It uses the Uuid of the controller and the IDs or interface names
     /// of the ports.
     SetPorts(
@@ -71,6 +79,9 @@ pub enum Action {
     ),
     /// Updates a connection (replacing the old one).
     UpdateConnection(Box<Connection>, Responder<Result<(), NetworkStateError>>),
+    /// Adds or updates a set of connections at once. It returns, for each connection, whether it
+    /// was added.
+    SetConnections(Box<Vec<Connection>>, Responder<Vec<bool>>),
     /// Updates the general network configuration
     UpdateGeneralState(GeneralState),
     /// Forces a wireless networks scan refresh
@@ -79,4 +90,19 @@ pub enum Action {
     RemoveConnection(String, Responder<Result<(), NetworkStateError>>),
     /// Apply the current configuration.
     Apply(Responder<Result<(), NetworkAdapterError>>),
+    /// Discard the changes and read the configuration from the system again.
+    Reset(Responder<Result<(), NetworkAdapterError>>),
+    /// Copies the connections which are active in the live system into the configuration. It
+    /// returns the IDs of the copied connections.
+    CopyLiveConnections(Responder<Result<Vec<String>, NetworkAdapterError>>),
+    /// Creates a checkpoint which is rolled back after the given timeout (in seconds).
+    CreateCheckpoint(u32, Responder<Result<Checkpoint, NetworkStateError>>),
+    /// Gets the pending checkpoint, if any.
+    GetCheckpoint(Responder<Option<CheckpointStatus>>),
+    /// Keeps the changes done after creating the checkpoint.
+    ConfirmCheckpoint(Responder<Result<(), NetworkStateError>>),
+    /// Rolls back the changes done after creating the checkpoint.
+    RollbackCheckpoint(Responder<Result<(), NetworkStateError>>),
+    /// Notifies that the timeout of the checkpoint with the given ID expired.
+    ExpireCheckpoint(String),
 }
@@ -18,7 +18,10 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use crate::network::{model::StateConfig, Action, NetworkState};
+use crate::network::{
+    model::{ConnectivityState, DnsEntry, StateConfig},
+    Action, NetworkState,
+};
 use agama_lib::error::ServiceError;
 use async_trait::async_trait;
 use thiserror::Error;
@@ -41,6 +44,18 @@ pub enum NetworkAdapterError {
 pub trait Adapter {
     async fn read(&self, config: StateConfig) -> Result<NetworkState, NetworkAdapterError>;
     async fn write(&self, network: &NetworkState) -> Result<(), NetworkAdapterError>;
+    /// Returns the connectivity state of the system.
+    async fn connectivity(&self) -> Result<ConnectivityState, NetworkAdapterError>;
+    /// Returns the DNS configuration in use, from the most to the least preferred entry.
+    async fn dns_configuration(&self) -> Result<Vec<DnsEntry>, NetworkAdapterError>;
+    /// Creates a checkpoint, rolled back by the backend after `timeout` seconds.
+    ///
+    /// Returns the checkpoint identifier.
+    async fn create_checkpoint(&self, timeout: u32) -> Result<String, NetworkAdapterError>;
+    /// Destroys the checkpoint with the given identifier, keeping the changes.
+    async fn confirm_checkpoint(&self, id: &str) -> Result<(), NetworkAdapterError>;
+    /// Rolls back the changes done after creating the checkpoint with the given identifier.
+    async fn rollback_checkpoint(&self, id: &str) -> Result<(), NetworkAdapterError>;
     /// Returns the watcher, which is responsible for listening for network changes.
     fn watcher(&self) -> Option<Box<dyn Watcher + Send>> {
         None
@@ -70,6 +70,26 @@ pub enum NetworkStateError {
     InvalidWirelessBand(String),
     #[error("Invalid bssid: '{0}'")]
     InvalidBssid(String),
+    #[error("There is already a pending checkpoint")]
+    CheckpointExists,
+    #[error("There is no pending checkpoint")]
+    NoCheckpoint,
+    #[error("The checkpoint timeout must be at least 1 second")]
+    InvalidCheckpointTimeout,
+    #[error("The {0} DNS priority cannot be set when {0} is disabled")]
+    InvalidDnsPriority(String),
+    #[error("The IPv6 privacy extensions cannot be set when IPv6 is disabled")]
+    InvalidIpv6Privacy,
+    #[error("The gateway check timeout must be between 1 and {1} seconds, not {0}")]
+    InvalidGatewayCheckTimeout(u32, u32),
+    #[error("The connectivity check timeout must be between 1 and {1} seconds, not {0}")]
+    InvalidConnectivityTimeout(u64, u64),
+    #[error("Invalid VLAN ID {0} (it must be between 1 and 4094)")]
+    InvalidVlanId(u32),
+    #[error("Invalid VLAN protocol: '{0}'")]
+    InvalidVlanProtocol(String),
+    #[error("The VLAN connection '{0}' has no parent")]
+    MissingVlanParent(String),
 }
 
 impl From<NetworkStateError> for zbus::fdo::Error {
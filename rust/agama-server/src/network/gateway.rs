@@ -0,0 +1,205 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Checks whether the gateway of an active connection is reachable.
+//!
+//! A successful DHCP negotiation does not guarantee that the gateway answers (e.g., because of
+//! a wrong VLAN or a switch port issue). When the check is enabled for a connection,
+//! NetworkManager delays the activation until the gateway replies or the timeout is reached, and
+//! the connection state reports whether it is reachable.
+//!
+//! The check runs once, in the background, after the connection is activated. The result is
+//! kept until the connection is deactivated.
+
+use super::model::{Connection, Device};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::process::Command;
+
+/// Result of the gateway reachability check.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum GatewayCheck {
+    /// The gateway answered.
+    Reachable,
+    /// The gateway did not answer before the timeout.
+    Unreachable,
+    /// The device has no gateway.
+    NoGateway,
+    /// The check is running.
+    Checking,
+}
+
+/// Results of the gateway checks, by connection ID.
+#[derive(Clone, Default)]
+pub struct GatewayChecks(Arc<Mutex<HashMap<String, GatewayCheck>>>);
+
+impl GatewayChecks {
+    /// Returns the result of the check for an active connection.
+    ///
+    /// The check is started in the background the first time, reporting
+    /// [GatewayCheck::Checking] until it finishes. It returns `None` if the connection does not
+    /// enable the check.
+    ///
+    /// * `connection`: connection active on the device.
+    /// * `device`: device to check.
+    pub fn get(&self, connection: &Connection, device: &Device) -> Option<GatewayCheck> {
+        connection.gateway_check_timeout?;
+        let mut checks = self.0.lock().unwrap();
+        if let Some(check) = checks.get(&connection.id) {
+            return Some(*check);
+        }
+        checks.insert(connection.id.clone(), GatewayCheck::Checking);
+
+        let checks = self.clone();
+        let connection = connection.clone();
+        let device = device.clone();
+        tokio::spawn(async move {
+            let result = check_gateway(&connection, &device)
+                .await
+                .unwrap_or(GatewayCheck::NoGateway);
+            // the connection could be deactivated while checking
+            if let Some(check) = checks.0.lock().unwrap().get_mut(&connection.id) {
+                if *check == GatewayCheck::Checking {
+                    *check = result;
+                }
+            }
+        });
+        Some(GatewayCheck::Checking)
+    }
+
+    /// Forgets the result of the check, so it runs again on the next activation.
+    ///
+    /// * `id`: connection ID.
+    pub fn reset(&self, id: &str) {
+        self.0.lock().unwrap().remove(id);
+    }
+}
+
+/// Returns the gateway to check for a device, preferring the IPv4 one.
+///
+/// * `device`: device where the connection is active.
+pub fn device_gateway(device: &Device) -> Option<IpAddr> {
+    let ip_config = device.ip_config.as_ref()?;
+    ip_config.gateway4.or(ip_config.gateway6)
+}
+
+/// Checks whether the gateway of the device is reachable, if the connection enables the check.
+///
+/// * `connection`: connection active on the device.
+/// * `device`: device to check.
+pub async fn check_gateway(connection: &Connection, device: &Device) -> Option<GatewayCheck> {
+    let timeout = connection.gateway_check_timeout?;
+    let Some(gateway) = device_gateway(device) else {
+        return Some(GatewayCheck::NoGateway);
+    };
+
+    if ping(gateway, &device.name, timeout).await {
+        Some(GatewayCheck::Reachable)
+    } else {
+        Some(GatewayCheck::Unreachable)
+    }
+}
+
+/// Sends a single ping to the gateway through the given interface.
+///
+/// * `gateway`: address to ping.
+/// * `interface`: interface to send the ping through.
+/// * `timeout`: seconds to wait for the answer.
+async fn ping(gateway: IpAddr, interface: &str, timeout: u32) -> bool {
+    let mut command = Command::new("ping");
+    command
+        .args(["-c", "1", "-n", "-W", &timeout.to_string(), "-I", interface])
+        .arg(gateway.to_string())
+        .kill_on_drop(true);
+    // give the command some extra time to finish on its own
+    let limit = Duration::from_secs(u64::from(timeout) + 1);
+    match tokio::time::timeout(limit, command.output()).await {
+        Ok(Ok(output)) => output.status.success(),
+        Ok(Err(error)) => {
+            log::warn!("Could not ping the gateway {}: {}", gateway, error);
+            false
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::model::IpConfig;
+
+    #[tokio::test]
+    async fn test_check_gateway() {
+        let mut connection = Connection::default();
+        let mut device = Device {
+            name: "eth0".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(check_gateway(&connection, &device).await, None);
+
+        connection.gateway_check_timeout = Some(1);
+        assert_eq!(
+            check_gateway(&connection, &device).await,
+            Some(GatewayCheck::NoGateway)
+        );
+
+        device.ip_config = Some(IpConfig {
+            gateway6: Some("fe80::1".parse().unwrap()),
+            ..Default::default()
+        });
+        assert_eq!(device_gateway(&device), "fe80::1".parse().ok());
+    }
+
+    #[tokio::test]
+    async fn test_gateway_checks() {
+        let checks = GatewayChecks::default();
+        let mut connection = Connection::default();
+        let device = Device {
+            name: "eth0".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(checks.get(&connection, &device), None);
+
+        connection.gateway_check_timeout = Some(1);
+        assert_eq!(
+            checks.get(&connection, &device),
+            Some(GatewayCheck::Checking)
+        );
+        while checks.get(&connection, &device) == Some(GatewayCheck::Checking) {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(
+            checks.get(&connection, &device),
+            Some(GatewayCheck::NoGateway)
+        );
+
+        checks.reset(&connection.id);
+        assert_eq!(
+            checks.get(&connection, &device),
+            Some(GatewayCheck::Checking)
+        );
+    }
+}
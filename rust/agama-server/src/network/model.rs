@@ -24,9 +24,12 @@
 //! agnostic from the real network service (e.g., NetworkManager).
 use crate::network::error::NetworkStateError;
 use agama_lib::network::settings::{
-    BondSettings, IEEE8021XSettings, NetworkConnection, WirelessSettings,
+    BondSettings, BridgeSettings, IEEE8021XSettings, NetworkConnection, VlanSettings,
+    WirelessSettings,
+};
+use agama_lib::network::types::{
+    BondMode, DeviceState, DeviceType, DnsOverTls, Ipv6Privacy, Metered, Status, SSID,
 };
-use agama_lib::network::types::{BondMode, DeviceState, DeviceType, Status, SSID};
 use cidr::IpInet;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, skip_serializing_none, DisplayFromStr};
@@ -175,6 +178,61 @@ impl NetworkState {
         Ok(())
     }
 
+    /// Adds or updates a set of connections at once.
+    ///
+    /// The existing connections with the same ID are replaced, keeping their UUID. It returns,
+    /// for each connection, whether it was added (`true`) or updated (`false`).
+    pub fn set_connections(&mut self, conns: Vec<Connection>) -> Vec<bool> {
+        conns
+            .into_iter()
+            .map(|mut conn| match self.get_connection_mut(&conn.id) {
+                Some(old_conn) => {
+                    conn.uuid = old_conn.uuid;
+                    *old_conn = conn;
+                    false
+                }
+                None => {
+                    self.connections.push(conn);
+                    true
+                }
+            })
+            .collect()
+    }
+
+    /// Copies the connections which are active in another state (usually, the one read from the
+    /// live system).
+    ///
+    /// The connections are copied as they are, replacing the ones with the same UUID or ID. It
+    /// returns the IDs of the copied connections.
+    ///
+    /// * `live`: state containing the active connections.
+    pub fn copy_active_connections(&mut self, live: NetworkState) -> Vec<String> {
+        let NetworkState {
+            devices,
+            connections,
+            ..
+        } = live;
+        let active = connections.into_iter().filter(|c| {
+            devices.iter().any(|d| {
+                d.state == DeviceState::Activated && d.connection.as_deref() == Some(&c.id)
+            })
+        });
+
+        let mut ids = vec![];
+        for conn in active {
+            ids.push(conn.id.clone());
+            match self
+                .connections
+                .iter_mut()
+                .find(|c| c.uuid == conn.uuid || c.id == conn.id)
+            {
+                Some(old_conn) => *old_conn = conn,
+                None => self.connections.push(conn),
+            }
+        }
+        ids
+    }
+
     /// Removes a connection from the state.
     ///
     /// Additionally, it registers the connection to be removed when the changes are applied.
@@ -221,7 +279,7 @@ impl NetworkState {
         controller: &Connection,
         ports: Vec<String>,
     ) -> Result<(), NetworkStateError> {
-        if let ConnectionConfig::Bond(_) = &controller.config {
+        if let ConnectionConfig::Bond(_) | ConnectionConfig::Bridge(_) = &controller.config {
             let mut controlled = vec![];
             for port in ports {
                 let connection = self
@@ -349,6 +407,136 @@ mod tests {
         assert_eq!(found.uuid, uuid);
     }
 
+    #[test]
+    fn test_dns_priority_requires_enabled_method() {
+        let conn = agama_lib::network::settings::NetworkConnection {
+            id: "eth0".to_string(),
+            method4: Some("auto".to_string()),
+            dns_priority4: Some(-10),
+            ..Default::default()
+        };
+        let conn = Connection::try_from(conn).unwrap();
+        assert_eq!(conn.ip_config.dns_priority4, Some(-10));
+
+        let conn = agama_lib::network::settings::NetworkConnection {
+            id: "eth0".to_string(),
+            method4: Some("auto".to_string()),
+            dns_priority6: Some(50),
+            ..Default::default()
+        };
+        let error = Connection::try_from(conn).unwrap_err();
+        assert!(matches!(error, NetworkStateError::InvalidDnsPriority(_)));
+    }
+
+    #[test]
+    fn test_ipv6_privacy() {
+        let conn = agama_lib::network::settings::NetworkConnection {
+            id: "eth0".to_string(),
+            method6: Some("auto".to_string()),
+            ipv6_privacy: Some(Ipv6Privacy::PreferTemporary),
+            ..Default::default()
+        };
+        let conn = Connection::try_from(conn).unwrap();
+        assert_eq!(conn.ip_config.ipv6_privacy, Ipv6Privacy::PreferTemporary);
+        let settings = agama_lib::network::settings::NetworkConnection::try_from(conn).unwrap();
+        assert_eq!(settings.ipv6_privacy, Some(Ipv6Privacy::PreferTemporary));
+
+        let conn = agama_lib::network::settings::NetworkConnection {
+            id: "eth0".to_string(),
+            method6: Some("disabled".to_string()),
+            ipv6_privacy: Some(Ipv6Privacy::Enabled),
+            ..Default::default()
+        };
+        let error = Connection::try_from(conn).unwrap_err();
+        assert!(matches!(error, NetworkStateError::InvalidIpv6Privacy));
+    }
+
+    #[test]
+    fn test_gateway_check_timeout() {
+        let conn = agama_lib::network::settings::NetworkConnection {
+            id: "eth0".to_string(),
+            gateway_check_timeout: Some(10),
+            ..Default::default()
+        };
+        let conn = Connection::try_from(conn).unwrap();
+        assert_eq!(conn.gateway_check_timeout, Some(10));
+        let settings = agama_lib::network::settings::NetworkConnection::try_from(conn).unwrap();
+        assert_eq!(settings.gateway_check_timeout, Some(10));
+
+        for timeout in [0, 601] {
+            let conn = agama_lib::network::settings::NetworkConnection {
+                id: "eth0".to_string(),
+                gateway_check_timeout: Some(timeout),
+                ..Default::default()
+            };
+            let error = Connection::try_from(conn).unwrap_err();
+            assert!(matches!(
+                error,
+                NetworkStateError::InvalidGatewayCheckTimeout(_, 600)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_set_connections() {
+        let mut state = NetworkState::default();
+        let uuid = Uuid::new_v4();
+        let eth0 = Connection {
+            id: "eth0".to_string(),
+            uuid,
+            ..Default::default()
+        };
+        state.add_connection(eth0).unwrap();
+
+        let eth0 = Connection {
+            id: "eth0".to_string(),
+            mtu: 9000,
+            ..Default::default()
+        };
+        let eth1 = Connection::new("eth1".to_string(), DeviceType::Ethernet);
+        assert_eq!(state.set_connections(vec![eth0, eth1]), vec![false, true]);
+
+        let found = state.get_connection("eth0").unwrap();
+        assert_eq!(found.uuid, uuid);
+        assert_eq!(found.mtu, 9000);
+        assert!(state.get_connection("eth1").is_some());
+    }
+
+    #[test]
+    fn test_copy_active_connections() {
+        let mut state = NetworkState::default();
+        let mut eth0 = Connection::new("eth0".to_string(), DeviceType::Ethernet);
+        state.add_connection(eth0.clone()).unwrap();
+        state.remove_connection("eth0").unwrap();
+
+        eth0.mtu = 9000;
+        let eth1 = Connection::new("eth1".to_string(), DeviceType::Ethernet);
+        let device = |name: &str, state: DeviceState| Device {
+            name: name.to_string(),
+            connection: Some(name.to_string()),
+            state,
+            ..Default::default()
+        };
+        let live = NetworkState::new(
+            GeneralState::default(),
+            vec![],
+            vec![
+                device("eth0", DeviceState::Activated),
+                device("eth1", DeviceState::Disconnected),
+            ],
+            vec![eth0, eth1],
+        );
+
+        assert_eq!(
+            state.copy_active_connections(live),
+            vec!["eth0".to_string()]
+        );
+        let found = state.get_connection("eth0").unwrap();
+        assert!(!found.is_removed());
+        assert_eq!(found.mtu, 9000);
+        assert!(state.get_connection("eth1").is_none());
+    }
+
     #[test]
     fn test_update_unknown_connection() {
         let mut state = NetworkState::default();
@@ -461,6 +649,99 @@ pub struct GeneralState {
     pub networking_enabled: bool, // pub network_state: NMSTATE
 }
 
+/// DNS configuration of a connection, as used by NetworkManager to build the global resolver
+/// configuration.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsEntry {
+    pub nameservers: Vec<String>,
+    /// Search and routing domains.
+    pub domains: Vec<String>,
+    /// Interface the servers are reached through.
+    pub interface: Option<String>,
+    /// ID of the connection bound to the interface, if known.
+    pub connection: Option<String>,
+    /// DNS priority: the lower, the more preferred.
+    pub priority: i32,
+    /// Whether the entry comes from a VPN.
+    pub vpn: bool,
+}
+
+/// Connectivity state as reported by NetworkManager.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectivityState {
+    /// The connectivity is not known (e.g., the check is disabled).
+    #[default]
+    Unknown,
+    /// The host is not connected to any network.
+    None,
+    /// The host is behind a captive portal.
+    Portal,
+    /// The host is connected to a network, but it cannot reach the internet.
+    Limited,
+    /// The host can reach the internet.
+    Full,
+}
+
+impl From<u32> for ConnectivityState {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => ConnectivityState::None,
+            2 => ConnectivityState::Portal,
+            3 => ConnectivityState::Limited,
+            4 => ConnectivityState::Full,
+            _ => ConnectivityState::Unknown,
+        }
+    }
+}
+
+/// Checkpoint of the network configuration.
+///
+/// NetworkManager rolls back the changes done after creating the checkpoint unless it is confirmed
+/// before the timeout expires.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    /// Backend identifier (e.g., the D-Bus path of the NetworkManager checkpoint).
+    pub id: String,
+    /// Rollback timeout, in seconds.
+    pub timeout: u32,
+    /// When the checkpoint was created.
+    pub created: std::time::Instant,
+}
+
+impl Checkpoint {
+    pub fn new(id: String, timeout: u32) -> Self {
+        Self {
+            id,
+            timeout,
+            created: std::time::Instant::now(),
+        }
+    }
+
+    /// Seconds left before the automatic rollback.
+    pub fn remaining(&self) -> u64 {
+        u64::from(self.timeout).saturating_sub(self.created.elapsed().as_secs())
+    }
+
+    pub fn status(&self) -> CheckpointStatus {
+        CheckpointStatus {
+            timeout: self.timeout,
+            remaining: self.remaining(),
+        }
+    }
+}
+
+/// State of the pending checkpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointStatus {
+    /// Rollback timeout, in seconds.
+    pub timeout: u32,
+    /// Seconds left before the automatic rollback.
+    pub remaining: u64,
+}
+
 /// Access Point
 #[serde_as]
 #[derive(Default, Debug, Clone, Serialize, utoipa::ToSchema)]
@@ -493,6 +774,46 @@ pub struct Device {
     pub state_reason: u8,
 }
 
+impl Device {
+    /// Returns the reason why the activation of the device failed, if it is the case.
+    pub fn failure_reason(&self) -> Option<String> {
+        (self.state == DeviceState::Failed).then(|| state_reason_description(self.state_reason))
+    }
+}
+
+/// Returns a human readable description of a NetworkManager device state reason.
+///
+/// * `reason`: NetworkManager device state reason code (NMDeviceStateReason).
+pub fn state_reason_description(reason: u8) -> String {
+    let description = match reason {
+        0 => "No reason given",
+        4 => "The device could not be configured",
+        5 => "The IP configuration could not be reserved",
+        6 => "The IP configuration is no longer valid",
+        7 => "Secrets were required, but not provided",
+        8 => "The 802.1X supplicant disconnected",
+        9 => "The 802.1X supplicant configuration failed",
+        10 => "The 802.1X supplicant failed",
+        11 => "The 802.1X supplicant took too long to authenticate",
+        15 => "The DHCP client failed to start",
+        16 => "DHCP client error",
+        17 => "The DHCP client failed",
+        36 => "The device was removed",
+        38 => "The connection was removed",
+        39 => "Disconnected by the user",
+        40 => "The link is down (no carrier)",
+        50 => "A dependency of the connection failed",
+        53 => "The Wi-Fi network could not be found",
+        62 => "The IP address is already in use",
+        63 => "The IP method is not supported",
+        _ => return format!("Unknown reason (code {})", reason),
+    };
+    description.to_string()
+}
+
+/// Maximum time (in seconds) to wait for the gateway, as supported by NetworkManager.
+pub const MAX_GATEWAY_CHECK_TIMEOUT: u32 = 600;
+
 /// Represents a known network connection.
 #[serde_as]
 #[skip_serializing_none]
@@ -507,6 +828,15 @@ pub struct Connection {
     pub mtu: u32,
     pub ip_config: IpConfig,
     pub status: Status,
+    /// Whether NetworkManager activates the connection automatically.
+    pub autoconnect: bool,
+    pub metered: Metered,
+    pub dns_over_tls: DnsOverTls,
+    /// Free-text description of the connection.
+    pub description: Option<String>,
+    /// Seconds to wait for the gateway to answer after activating the connection, if the check
+    /// is enabled.
+    pub gateway_check_timeout: Option<u32>,
     pub interface: Option<String>,
     pub controller: Option<Uuid>,
     pub port_config: PortConfig,
@@ -578,6 +908,11 @@ impl Default for Connection {
             mtu: Default::default(),
             ip_config: Default::default(),
             status: Default::default(),
+            autoconnect: true,
+            metered: Default::default(),
+            dns_over_tls: Default::default(),
+            description: Default::default(),
+            gateway_check_timeout: Default::default(),
             interface: Default::default(),
             controller: Default::default(),
             port_config: Default::default(),
@@ -595,6 +930,18 @@ impl TryFrom<NetworkConnection> for Connection {
         let id = conn.clone().id;
         let mut connection = Connection::new(id, conn.device_type());
 
+        if let Some(autoconnect) = conn.autoconnect {
+            connection.autoconnect = autoconnect;
+        }
+
+        if let Some(metered) = conn.metered {
+            connection.metered = metered;
+        }
+
+        if let Some(dns_over_tls) = conn.dns_over_tls {
+            connection.dns_over_tls = dns_over_tls;
+        }
+
         if let Some(method) = conn.clone().method4 {
             let method: Ipv4Method = method.parse().unwrap();
             connection.ip_config.method4 = method;
@@ -623,6 +970,18 @@ impl TryFrom<NetworkConnection> for Connection {
             connection.config = config.into();
         }
 
+        if let Some(bridge_config) = conn.bridge {
+            connection.config = BridgeConfig::from(bridge_config).into();
+        }
+
+        if let Some(vlan_config) = conn.vlan {
+            let parent = conn
+                .parent
+                .clone()
+                .ok_or_else(|| NetworkStateError::MissingVlanParent(conn.id.clone()))?;
+            connection.config = VlanConfig::try_from((vlan_config, parent))?.into();
+        }
+
         if let Some(ieee_8021x_config) = conn.ieee_8021x {
             connection.ieee_8021x_config = Some(IEEE8021XConfig::try_from(ieee_8021x_config)?);
         }
@@ -630,9 +989,34 @@ impl TryFrom<NetworkConnection> for Connection {
         connection.ip_config.addresses = conn.addresses;
         connection.ip_config.nameservers = conn.nameservers;
         connection.ip_config.dns_searchlist = conn.dns_searchlist;
+        connection.ip_config.dns_priority4 = conn.dns_priority4.filter(|p| *p != 0);
+        connection.ip_config.dns_priority6 = conn.dns_priority6.filter(|p| *p != 0);
+        let ip_config = &connection.ip_config;
+        if ip_config.dns_priority4.is_some() && ip_config.method4 == Ipv4Method::Disabled {
+            return Err(NetworkStateError::InvalidDnsPriority("IPv4".to_string()));
+        }
+        if ip_config.dns_priority6.is_some() && ip_config.method6 == Ipv6Method::Disabled {
+            return Err(NetworkStateError::InvalidDnsPriority("IPv6".to_string()));
+        }
+        if let Some(ipv6_privacy) = conn.ipv6_privacy {
+            if ipv6_privacy != Ipv6Privacy::Default && ip_config.method6 == Ipv6Method::Disabled {
+                return Err(NetworkStateError::InvalidIpv6Privacy);
+            }
+            connection.ip_config.ipv6_privacy = ipv6_privacy;
+        }
         connection.ip_config.gateway4 = conn.gateway4;
         connection.ip_config.gateway6 = conn.gateway6;
         connection.interface = conn.interface;
+        connection.description = conn.description;
+        if let Some(timeout) = conn.gateway_check_timeout {
+            if timeout == 0 || timeout > MAX_GATEWAY_CHECK_TIMEOUT {
+                return Err(NetworkStateError::InvalidGatewayCheckTimeout(
+                    timeout,
+                    MAX_GATEWAY_CHECK_TIMEOUT,
+                ));
+            }
+            connection.gateway_check_timeout = Some(timeout);
+        }
         connection.mtu = conn.mtu;
 
         Ok(connection)
@@ -651,11 +1035,19 @@ impl TryFrom<Connection> for NetworkConnection {
         let nameservers = conn.ip_config.nameservers;
         let dns_searchlist = conn.ip_config.dns_searchlist;
         let ignore_auto_dns = Some(conn.ip_config.ignore_auto_dns);
+        let dns_priority4 = conn.ip_config.dns_priority4;
+        let dns_priority6 = conn.ip_config.dns_priority6;
+        let ipv6_privacy = Some(conn.ip_config.ipv6_privacy);
+        let dns_over_tls = Some(conn.dns_over_tls);
         let addresses = conn.ip_config.addresses;
         let gateway4 = conn.ip_config.gateway4;
         let gateway6 = conn.ip_config.gateway6;
         let interface = conn.interface;
         let status = Some(conn.status);
+        let autoconnect = Some(conn.autoconnect);
+        let metered = Some(conn.metered);
+        let description = conn.description;
+        let gateway_check_timeout = conn.gateway_check_timeout;
         let mtu = conn.mtu;
         let ieee_8021x: Option<IEEE8021XSettings> = conn
             .ieee_8021x_config
@@ -664,6 +1056,10 @@ impl TryFrom<Connection> for NetworkConnection {
         let mut connection = NetworkConnection {
             id,
             status,
+            autoconnect,
+            metered,
+            description,
+            gateway_check_timeout,
             method4,
             method6,
             gateway4,
@@ -671,6 +1067,10 @@ impl TryFrom<Connection> for NetworkConnection {
             nameservers,
             dns_searchlist,
             ignore_auto_dns,
+            dns_priority4,
+            dns_priority6,
+            ipv6_privacy,
+            dns_over_tls,
             mac_address,
             interface,
             addresses,
@@ -686,6 +1086,19 @@ impl TryFrom<Connection> for NetworkConnection {
             ConnectionConfig::Bond(config) => {
                 connection.bond = Some(BondSettings::try_from(config)?);
             }
+            ConnectionConfig::Bridge(config) => {
+                connection.bridge = Some(BridgeSettings {
+                    stp: Some(config.stp),
+                    ..Default::default()
+                });
+            }
+            ConnectionConfig::Vlan(config) => {
+                connection.parent = Some(config.parent);
+                connection.vlan = Some(VlanSettings {
+                    id: config.id,
+                    protocol: Some(config.protocol.to_string()),
+                });
+            }
             _ => {}
         }
 
@@ -720,6 +1133,18 @@ impl From<BondConfig> for ConnectionConfig {
     }
 }
 
+impl From<BridgeConfig> for ConnectionConfig {
+    fn from(value: BridgeConfig) -> Self {
+        Self::Bridge(value)
+    }
+}
+
+impl From<VlanConfig> for ConnectionConfig {
+    fn from(value: VlanConfig) -> Self {
+        Self::Vlan(value)
+    }
+}
+
 impl From<WirelessConfig> for ConnectionConfig {
     fn from(value: WirelessConfig) -> Self {
         Self::Wireless(value)
@@ -803,6 +1228,12 @@ pub struct IpConfig {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub dns_searchlist: Vec<String>,
     pub ignore_auto_dns: bool,
+    /// Priority of the IPv4 DNS servers (`None` for the default one).
+    pub dns_priority4: Option<i32>,
+    /// Priority of the IPv6 DNS servers (`None` for the default one).
+    pub dns_priority6: Option<i32>,
+    /// IPv6 privacy extensions.
+    pub ipv6_privacy: Ipv6Privacy,
     pub gateway4: Option<IpAddr>,
     pub gateway6: Option<IpAddr>,
     pub routes4: Option<Vec<IpRoute>>,
@@ -979,6 +1410,31 @@ pub struct VlanConfig {
     pub protocol: VlanProtocol,
 }
 
+/// Highest VLAN ID (4095 is reserved).
+pub const MAX_VLAN_ID: u32 = 4094;
+
+impl TryFrom<(VlanSettings, String)> for VlanConfig {
+    type Error = NetworkStateError;
+
+    /// Builds the VLAN configuration from the settings and the parent connection.
+    fn try_from((settings, parent): (VlanSettings, String)) -> Result<Self, Self::Error> {
+        if settings.id == 0 || settings.id > MAX_VLAN_ID {
+            return Err(NetworkStateError::InvalidVlanId(settings.id));
+        }
+        let protocol = match settings.protocol {
+            Some(protocol) => VlanProtocol::from_str(&protocol)
+                .map_err(|_| NetworkStateError::InvalidVlanProtocol(protocol))?,
+            None => VlanProtocol::default(),
+        };
+
+        Ok(VlanConfig {
+            parent,
+            id: settings.id,
+            protocol,
+        })
+    }
+}
+
 #[serde_as]
 #[derive(Debug, Default, PartialEq, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -1493,6 +1949,15 @@ pub struct BridgeConfig {
     pub ageing_time: Option<u32>,
 }
 
+impl From<BridgeSettings> for BridgeConfig {
+    fn from(settings: BridgeSettings) -> Self {
+        BridgeConfig {
+            stp: settings.stp.unwrap_or(true),
+            ..Default::default()
+        }
+    }
+}
+
 #[derive(Debug, Default, PartialEq, Clone, Serialize)]
 pub struct BridgePortConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -1567,6 +2032,15 @@ pub enum NetworkChange {
     /// original device name, which is especially useful if the
     /// device gets renamed.
     DeviceUpdated(String, Device),
+    /// The activation of a connection failed.
+    ConnectionFailed {
+        /// Connection ID.
+        id: String,
+        /// Human readable reason.
+        reason: String,
+    },
+    /// The changes were rolled back because the checkpoint was not confirmed in time.
+    CheckpointRolledBack,
 }
 
 #[derive(Default, Debug, PartialEq, Clone, Serialize)]
@@ -29,10 +29,12 @@ mod builder;
 mod client;
 mod dbus;
 mod error;
+mod keyfile;
 mod model;
 mod proxies;
 mod watcher;
 
 pub use adapter::NetworkManagerAdapter;
 pub use client::NetworkManagerClient;
+pub use keyfile::{connection_to_keyfile, keyfile_name, keyfile_to_connection, KeyfileError};
 pub use watcher::NetworkManagerWatcher;
@@ -20,7 +20,7 @@
 
 use crate::network::{
     adapter::Watcher,
-    model::{Connection, NetworkState, StateConfig},
+    model::{Connection, ConnectivityState, DnsEntry, NetworkState, StateConfig},
     nm::{NetworkManagerClient, NetworkManagerWatcher},
     Adapter, NetworkAdapterError,
 };
@@ -29,6 +29,7 @@ use async_trait::async_trait;
 use core::time;
 use log;
 use std::thread;
+use zbus::zvariant::ObjectPath;
 
 /// An adapter for NetworkManager
 pub struct NetworkManagerAdapter<'a> {
@@ -94,6 +95,45 @@ impl<'a> Adapter for NetworkManagerAdapter<'a> {
         Ok(state)
     }
 
+    async fn connectivity(&self) -> Result<ConnectivityState, NetworkAdapterError> {
+        self.client
+            .connectivity()
+            .await
+            .map_err(NetworkAdapterError::Read)
+    }
+
+    async fn dns_configuration(&self) -> Result<Vec<DnsEntry>, NetworkAdapterError> {
+        self.client
+            .dns_configuration()
+            .await
+            .map_err(NetworkAdapterError::Read)
+    }
+
+    async fn create_checkpoint(&self, timeout: u32) -> Result<String, NetworkAdapterError> {
+        let path = self
+            .client
+            .create_checkpoint(timeout)
+            .await
+            .map_err(NetworkAdapterError::Checkpoint)?;
+        Ok(path.to_string())
+    }
+
+    async fn confirm_checkpoint(&self, id: &str) -> Result<(), NetworkAdapterError> {
+        let path = checkpoint_path(id)?;
+        self.client
+            .destroy_checkpoint(&path)
+            .await
+            .map_err(NetworkAdapterError::Checkpoint)
+    }
+
+    async fn rollback_checkpoint(&self, id: &str) -> Result<(), NetworkAdapterError> {
+        let path = checkpoint_path(id)?;
+        self.client
+            .rollback_checkpoint(&path)
+            .await
+            .map_err(NetworkAdapterError::Checkpoint)
+    }
+
     /// Writes the connections to NetworkManager.
     ///
     /// Internally, it creates an ordered list of connections before processing them. The reason is
@@ -105,7 +145,7 @@ impl<'a> Adapter for NetworkManagerAdapter<'a> {
         let old_state = self.read(StateConfig::default()).await?;
         let checkpoint = self
             .client
-            .create_checkpoint()
+            .create_checkpoint(0)
             .await
             .map_err(NetworkAdapterError::Checkpoint)?;
 
@@ -176,6 +216,12 @@ impl<'a> Adapter for NetworkManagerAdapter<'a> {
     }
 }
 
+/// Converts a checkpoint identifier into its D-Bus path.
+fn checkpoint_path(id: &str) -> Result<ObjectPath<'_>, NetworkAdapterError> {
+    ObjectPath::try_from(id)
+        .map_err(|e| NetworkAdapterError::Checkpoint(ServiceError::DBus(e.into())))
+}
+
 /// Returns the connections in the order they should be processed.
 ///
 /// * `network`: network model.
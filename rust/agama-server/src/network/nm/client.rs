@@ -28,10 +28,13 @@ use super::dbus::{
 };
 use super::model::NmDeviceType;
 use super::proxies::{
-    AccessPointProxy, ActiveConnectionProxy, ConnectionProxy, DeviceProxy, NetworkManagerProxy,
-    SettingsProxy, WirelessProxy,
+    AccessPointProxy, ActiveConnectionProxy, ConnectionProxy, DHCP4ConfigProxy, DHCP6ConfigProxy,
+    DeviceProxy, DnsManagerProxy, NetworkManagerProxy, SettingsProxy, WirelessProxy,
 };
-use crate::network::model::{AccessPoint, Connection, Device, GeneralState};
+use crate::network::model::{
+    AccessPoint, Connection, ConnectivityState, Device, DnsEntry, GeneralState,
+};
+use agama_lib::dbus::{get_optional_property, get_property};
 use agama_lib::error::ServiceError;
 use agama_lib::network::types::{DeviceType, SSID};
 use log;
@@ -39,6 +42,9 @@ use uuid::Uuid;
 use zbus;
 use zbus::zvariant::{ObjectPath, OwnedObjectPath};
 
+/// NM_CHECKPOINT_CREATE_FLAG_ALLOW_OVERLAPPING
+const CHECKPOINT_ALLOW_OVERLAPPING: u32 = 0x08;
+
 /// Simplified NetworkManager D-Bus client.
 ///
 /// Implements a minimal API to be used internally. At this point, it allows to query the list of
@@ -79,6 +85,87 @@ impl<'a> NetworkManagerClient<'a> {
         })
     }
 
+    /// Returns the connectivity state.
+    ///
+    /// When the connectivity check is enabled, it asks NetworkManager to perform a new one.
+    /// Otherwise, it returns the last known state.
+    pub async fn connectivity(&self) -> Result<ConnectivityState, ServiceError> {
+        let check_enabled = self.nm_proxy.connectivity_check_available().await?
+            && self.nm_proxy.connectivity_check_enabled().await?;
+        let state = if check_enabled {
+            self.nm_proxy.check_connectivity().await?
+        } else {
+            self.nm_proxy.connectivity().await?
+        };
+        Ok(state.into())
+    }
+
+    /// Returns the DNS configuration in use, from the most to the least preferred entry.
+    ///
+    /// NetworkManager prefers the entries with a lower priority value.
+    pub async fn dns_configuration(&self) -> Result<Vec<DnsEntry>, ServiceError> {
+        let proxy = DnsManagerProxy::new(&self.connection).await?;
+        let mut entries = vec![];
+        for entry in proxy.configuration().await? {
+            entries.push(DnsEntry {
+                nameservers: get_property(&entry, "nameservers")?,
+                domains: get_optional_property(&entry, "domains")?.unwrap_or_default(),
+                interface: get_optional_property(&entry, "interface")?,
+                connection: None,
+                priority: get_property(&entry, "priority")?,
+                vpn: get_optional_property(&entry, "vpn")?.unwrap_or_default(),
+            });
+        }
+        entries.sort_by_key(|e| e.priority);
+        Ok(entries)
+    }
+
+    /// Returns the NTP servers offered via DHCP (v4 and v6) to the active connections.
+    ///
+    /// The servers are not validated and the duplicates are removed.
+    pub async fn dhcp_ntp_servers(&self) -> Result<Vec<String>, ServiceError> {
+        let mut options = vec![];
+        for active_path in &self.nm_proxy.active_connections().await? {
+            let proxy = ActiveConnectionProxy::builder(&self.connection)
+                .path(active_path.as_str())?
+                .build()
+                .await?;
+
+            let dhcp4_path = proxy.dhcp4_config().await?;
+            if dhcp4_path.as_str() != "/" {
+                let dhcp4 = DHCP4ConfigProxy::builder(&self.connection)
+                    .path(dhcp4_path.as_str())?
+                    .build()
+                    .await?;
+                options.push(dhcp4.options().await?);
+            }
+
+            let dhcp6_path = proxy.dhcp6_config().await?;
+            if dhcp6_path.as_str() != "/" {
+                let dhcp6 = DHCP6ConfigProxy::builder(&self.connection)
+                    .path(dhcp6_path.as_str())?
+                    .build()
+                    .await?;
+                options.push(dhcp6.options().await?);
+            }
+        }
+
+        let mut servers: Vec<String> = vec![];
+        for option in &options {
+            for key in ["ntp_servers", "dhcp6_ntp_servers"] {
+                let Some(value) = get_optional_property::<String>(option, key)? else {
+                    continue;
+                };
+                for server in value.split_whitespace() {
+                    if !servers.iter().any(|s| s == server) {
+                        servers.push(server.to_string());
+                    }
+                }
+            }
+        }
+        Ok(servers)
+    }
+
     /// Updates the general state
     pub async fn update_general_state(&self, state: &GeneralState) -> Result<(), ServiceError> {
         let wireless_enabled = self.nm_proxy.wireless_enabled().await?;
@@ -272,8 +359,19 @@ impl<'a> NetworkManagerClient<'a> {
     }
 
     /// Creates a checkpoint.
-    pub async fn create_checkpoint(&self) -> Result<OwnedObjectPath, ServiceError> {
-        let path = self.nm_proxy.checkpoint_create(&[], 0, 0).await?;
+    ///
+    /// It can overlap with other checkpoints, so a change can be applied while a checkpoint with a
+    /// rollback timeout is pending.
+    ///
+    /// * `rollback_timeout`: seconds to roll back the configuration automatically (0 means never).
+    pub async fn create_checkpoint(
+        &self,
+        rollback_timeout: u32,
+    ) -> Result<OwnedObjectPath, ServiceError> {
+        let path = self
+            .nm_proxy
+            .checkpoint_create(&[], rollback_timeout, CHECKPOINT_ALLOW_OVERLAPPING)
+            .await?;
         Ok(path)
     }
 
@@ -26,7 +26,7 @@ use super::model::*;
 use crate::network::model::*;
 use agama_lib::{
     dbus::{NestedHash, OwnedNestedHash},
-    network::types::{BondMode, SSID},
+    network::types::{BondMode, DnsOverTls, Ipv6Privacy, Metered, SSID},
 };
 use cidr::IpInet;
 use macaddr::MacAddr6;
@@ -46,6 +46,9 @@ const BRIDGE_PORT_KEY: &str = "bridge-port";
 const INFINIBAND_KEY: &str = "infiniband";
 const TUN_KEY: &str = "tun";
 const IEEE_8021X_KEY: &str = "802-1x";
+const USER_KEY: &str = "user";
+/// Key of the user data where the connection description is stored.
+const DESCRIPTION_DATA_KEY: &str = "agama.description";
 
 /// Converts a connection struct into a HashMap that can be sent over D-Bus.
 ///
@@ -58,6 +61,13 @@ pub fn connection_to_dbus<'a>(
     let mut connection_dbus = HashMap::from([
         ("id", conn.id.as_str().into()),
         ("type", ETHERNET_KEY.into()),
+        ("autoconnect", conn.autoconnect.into()),
+        ("metered", conn.metered.to_nm().into()),
+        ("dns-over-tls", conn.dns_over_tls.to_nm().into()),
+        (
+            "gateway-ping-timeout",
+            conn.gateway_check_timeout.unwrap_or_default().into(),
+        ),
     ]);
 
     if let Some(interface) = &conn.interface {
@@ -162,6 +172,11 @@ pub fn connection_to_dbus<'a>(
         result.insert(IEEE_8021X_KEY, ieee_8021x_config_to_dbus(ieee_8021x_config));
     }
 
+    if let Some(description) = &conn.description {
+        let data = HashMap::from([(DESCRIPTION_DATA_KEY, description.as_str())]);
+        result.insert(USER_KEY, HashMap::from([("data", Value::new(data))]));
+    }
+
     result.insert("connection", connection_dbus);
     result
 }
@@ -230,6 +245,8 @@ pub fn connection_from_dbus(conn: OwnedNestedHash) -> Option<Connection> {
 /// Merges a NestedHash and an OwnedNestedHash connections.
 ///
 /// Only the top-level sections that are present in the `original` hash are considered for update.
+/// The exception is the "user" section, whose data is merged entry by entry: the description is
+/// replaced (or removed if the updated connection has none) and the rest of the entries are kept.
 ///
 /// * `original`: original hash coming from D-Bus.
 /// * `updated`: updated hash to write to D-Bus.
@@ -251,10 +268,33 @@ pub fn merge_dbus_connections<'a>(
         }
         merged.insert(key.as_str(), inner);
     }
+
+    let mut user_data = original
+        .get(USER_KEY)
+        .map(|user| user_data_from_dbus(user.get("data").map(Value::from)))
+        .unwrap_or_default();
+    user_data.remove(DESCRIPTION_DATA_KEY);
+    if let Some(user) = updated.get(USER_KEY) {
+        user_data.extend(user_data_from_dbus(user.get("data").cloned()));
+    }
+    if user_data.is_empty() {
+        merged.remove(USER_KEY);
+    } else {
+        merged.insert(USER_KEY, HashMap::from([("data", Value::new(user_data))]));
+    }
+
     cleanup_dbus_connection(&mut merged);
     merged
 }
 
+/// Converts the data of the "user" section to a map.
+fn user_data_from_dbus(data: Option<Value>) -> HashMap<String, String> {
+    let Some(Value::Dict(data)) = data else {
+        return HashMap::new();
+    };
+    HashMap::try_from(data).unwrap_or_default()
+}
+
 /// Cleans up the NestedHash that represents a connection.
 ///
 /// By now it just removes the "addresses" key from the "ipv4" and "ipv6" objects, which is
@@ -328,6 +368,10 @@ fn ip_config_to_ipv4_dbus(ip_config: &IpConfig) -> HashMap<&str, zvariant::Value
         ("dns-data", dns_data),
         ("dns-search", ip_config.dns_searchlist.clone().into()),
         ("ignore-auto-dns", ip_config.ignore_auto_dns.into()),
+        (
+            "dns-priority",
+            ip_config.dns_priority4.unwrap_or_default().into(),
+        ),
         ("method", ip_config.method4.to_string().into()),
     ]);
 
@@ -375,6 +419,11 @@ fn ip_config_to_ipv6_dbus(ip_config: &IpConfig) -> HashMap<&str, zvariant::Value
         ("dns-data", dns_data),
         ("dns-search", ip_config.dns_searchlist.clone().into()),
         ("ignore-auto-dns", ip_config.ignore_auto_dns.into()),
+        (
+            "dns-priority",
+            ip_config.dns_priority6.unwrap_or_default().into(),
+        ),
+        ("ip6-privacy", ip_config.ipv6_privacy.to_nm().into()),
         ("method", ip_config.method6.to_string().into()),
     ]);
 
@@ -686,6 +735,25 @@ fn base_connection_from_dbus(conn: &OwnedNestedHash) -> Option<Connection> {
         base_connection.firewall_zone = Some(zone.to_string());
     }
 
+    if let Some(autoconnect) = connection.get("autoconnect") {
+        base_connection.autoconnect = *autoconnect.downcast_ref::<bool>()?;
+    }
+
+    if let Some(metered) = connection.get("metered") {
+        base_connection.metered = Metered::from_nm(*metered.downcast_ref::<i32>()?);
+    }
+
+    if let Some(dns_over_tls) = connection.get("dns-over-tls") {
+        base_connection.dns_over_tls = DnsOverTls::from_nm(*dns_over_tls.downcast_ref::<i32>()?);
+    }
+
+    if let Some(timeout) = connection.get("gateway-ping-timeout") {
+        base_connection.gateway_check_timeout =
+            Some(*timeout.downcast_ref::<u32>()?).filter(|t| *t > 0);
+    }
+
+    base_connection.description = description_from_dbus(conn);
+
     if let Some(ethernet_config) = conn.get(ETHERNET_KEY) {
         base_connection.mac_address = mac_address_from_dbus(ethernet_config)?;
         base_connection.mtu = mtu_from_dbus(ethernet_config);
@@ -699,6 +767,14 @@ fn base_connection_from_dbus(conn: &OwnedNestedHash) -> Option<Connection> {
     Some(base_connection)
 }
 
+/// Reads the connection description from the user data, if any.
+fn description_from_dbus(conn: &OwnedNestedHash) -> Option<String> {
+    let user = conn.get(USER_KEY)?;
+    let data: &zvariant::Dict = user.get("data")?.downcast_ref()?;
+    let mut data = <HashMap<String, String>>::try_from(data.clone()).ok()?;
+    data.remove(DESCRIPTION_DATA_KEY)
+}
+
 fn mac_address_from_dbus(config: &HashMap<String, OwnedValue>) -> Option<MacAddress> {
     if let Some(mac_address) = config.get("assigned-mac-address") {
         match MacAddress::from_str(mac_address.downcast_ref::<str>()?) {
@@ -796,6 +872,10 @@ fn ip_config_from_dbus(conn: &OwnedNestedHash) -> Option<IpConfig> {
             ip_config.ignore_auto_dns = ignore_auto_dns.try_into().ok()?;
         }
 
+        if let Some(priority) = ipv4.get("dns-priority") {
+            ip_config.dns_priority4 = Some(*priority.downcast_ref::<i32>()?).filter(|p| *p != 0);
+        }
+
         if let Some(route_data) = ipv4.get("route-data") {
             ip_config.routes4 = routes_from_dbus(route_data);
         }
@@ -838,6 +918,14 @@ fn ip_config_from_dbus(conn: &OwnedNestedHash) -> Option<IpConfig> {
             ip_config.ignore_auto_dns = ignore_auto_dns.try_into().ok()?;
         }
 
+        if let Some(priority) = ipv6.get("dns-priority") {
+            ip_config.dns_priority6 = Some(*priority.downcast_ref::<i32>()?).filter(|p| *p != 0);
+        }
+
+        if let Some(privacy) = ipv6.get("ip6-privacy") {
+            ip_config.ipv6_privacy = Ipv6Privacy::from_nm(*privacy.downcast_ref::<i32>()?);
+        }
+
         if let Some(route_data) = ipv6.get("route-data") {
             ip_config.routes6 = routes_from_dbus(route_data);
         }
@@ -1266,7 +1354,7 @@ mod test {
         model::*,
         nm::dbus::{BOND_KEY, ETHERNET_KEY, INFINIBAND_KEY, WIRELESS_KEY, WIRELESS_SECURITY_KEY},
     };
-    use agama_lib::network::types::{BondMode, SSID};
+    use agama_lib::network::types::{BondMode, DnsOverTls, Ipv6Privacy, SSID};
     use cidr::IpInet;
     use std::{collections::HashMap, net::IpAddr, str::FromStr};
     use uuid::Uuid;
@@ -1278,6 +1366,7 @@ mod test {
         let connection_section = HashMap::from([
             ("id".to_string(), Value::new("eth0").to_owned()),
             ("uuid".to_string(), Value::new(uuid).to_owned()),
+            ("dns-over-tls".to_string(), Value::new(1_i32).to_owned()),
         ]);
 
         let address_v4_data = vec![HashMap::from([
@@ -1308,6 +1397,7 @@ mod test {
                 Value::new(vec!["suse.com", "example.com"]).to_owned(),
             ),
             ("ignore-auto-dns".to_string(), Value::new(true).to_owned()),
+            ("dns-priority".to_string(), Value::new(-10_i32).to_owned()),
             (
                 "route-data".to_string(),
                 Value::new(route_v4_data).to_owned(),
@@ -1328,6 +1418,7 @@ mod test {
 
         let ipv6_section = HashMap::from([
             ("method".to_string(), Value::new("auto").to_owned()),
+            ("ip6-privacy".to_string(), Value::new(1_i32).to_owned()),
             (
                 "address-data".to_string(),
                 Value::new(address_v6_data).to_owned(),
@@ -1395,6 +1486,10 @@ mod test {
             .dns_searchlist
             .contains(&"example.com".to_string()));
         assert!(ip_config.ignore_auto_dns);
+        assert_eq!(ip_config.dns_priority4, Some(-10));
+        assert_eq!(ip_config.dns_priority6, None);
+        assert_eq!(ip_config.ipv6_privacy, Ipv6Privacy::Enabled);
+        assert_eq!(connection.dns_over_tls, DnsOverTls::Opportunistic);
         assert_eq!(ip_config.method4, Ipv4Method::Auto);
         assert_eq!(ip_config.method6, Ipv6Method::Auto);
         assert_eq!(
@@ -1918,6 +2013,89 @@ mod test {
         check_dbus_base_connection(&ethernet_dbus);
     }
 
+    #[test]
+    fn test_connection_description() {
+        let mut ethernet = build_base_connection();
+        ethernet.description = Some("Uplink to the lab switch".to_string());
+        let ethernet_dbus = connection_to_dbus(&ethernet, None);
+
+        let original = OwnedNestedHash::from([
+            (
+                "connection".to_string(),
+                HashMap::from([
+                    ("id".to_string(), Value::new("agama").to_owned()),
+                    (
+                        "uuid".to_string(),
+                        Value::new(ethernet.uuid.to_string()).to_owned(),
+                    ),
+                ]),
+            ),
+            (ETHERNET_KEY.to_string(), HashMap::new()),
+        ]);
+        let merged = merge_dbus_connections(&original, &ethernet_dbus);
+        let owned: OwnedNestedHash = merged
+            .into_iter()
+            .map(|(k, v)| {
+                let section = v
+                    .into_iter()
+                    .map(|(ik, iv)| (ik.to_string(), iv.to_owned()))
+                    .collect();
+                (k.to_string(), section)
+            })
+            .collect();
+
+        let connection = connection_from_dbus(owned).unwrap();
+        assert_eq!(
+            connection.description,
+            Some("Uplink to the lab switch".to_string())
+        );
+    }
+
+    #[test]
+    fn test_merge_dbus_connections_user_data() {
+        let user_data = HashMap::from([
+            (DESCRIPTION_DATA_KEY, "Old description"),
+            ("org.example.owner", "lab"),
+        ]);
+        let original = OwnedNestedHash::from([
+            (
+                "connection".to_string(),
+                HashMap::from([("id".to_string(), Value::new("agama").to_owned())]),
+            ),
+            (
+                USER_KEY.to_string(),
+                HashMap::from([("data".to_string(), Value::new(user_data).to_owned())]),
+            ),
+        ]);
+        let data = |merged: &NestedHash| {
+            user_data_from_dbus(merged.get(USER_KEY).and_then(|u| u.get("data")).cloned())
+        };
+
+        let mut ethernet = build_base_connection();
+        ethernet.description = Some("New description".to_string());
+        let updated = connection_to_dbus(&ethernet, None);
+        let merged = merge_dbus_connections(&original, &updated);
+        assert_eq!(
+            data(&merged),
+            HashMap::from([
+                (
+                    DESCRIPTION_DATA_KEY.to_string(),
+                    "New description".to_string()
+                ),
+                ("org.example.owner".to_string(), "lab".to_string()),
+            ])
+        );
+
+        // the description is removed, but the rest of the data is kept
+        ethernet.description = None;
+        let updated = connection_to_dbus(&ethernet, None);
+        let merged = merge_dbus_connections(&original, &updated);
+        assert_eq!(
+            data(&merged),
+            HashMap::from([("org.example.owner".to_string(), "lab".to_string())])
+        );
+    }
+
     #[test]
     fn test_merge_dbus_connections() {
         let mut original = OwnedNestedHash::new();
@@ -2086,6 +2264,24 @@ mod test {
         let connection_dbus = conn_dbus.get("connection").unwrap();
         let id: &str = connection_dbus.get("id").unwrap().downcast_ref().unwrap();
         assert_eq!(id, "agama");
+        let autoconnect: bool = *connection_dbus
+            .get("autoconnect")
+            .unwrap()
+            .downcast_ref()
+            .unwrap();
+        assert!(autoconnect);
+        let metered: i32 = *connection_dbus
+            .get("metered")
+            .unwrap()
+            .downcast_ref()
+            .unwrap();
+        assert_eq!(metered, 0);
+        let dns_over_tls: i32 = *connection_dbus
+            .get("dns-over-tls")
+            .unwrap()
+            .downcast_ref()
+            .unwrap();
+        assert_eq!(dns_over_tls, -1);
 
         let ethernet_connection = conn_dbus.get(ETHERNET_KEY).unwrap();
         let mac_address: &str = ethernet_connection
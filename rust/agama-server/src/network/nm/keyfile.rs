@@ -0,0 +1,856 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Renders and parses connections using the NetworkManager keyfile format.
+//!
+//! The keyfile is built from the same settings that are sent to NetworkManager
+//! through D-Bus, so it reflects what will be written to the system. Likewise,
+//! parsed keyfiles are converted to D-Bus settings before building the connection.
+
+use super::dbus::{connection_from_dbus, connection_to_dbus};
+use crate::network::model::Connection;
+use agama_lib::dbus::OwnedNestedHash;
+use cidr::IpInet;
+use macaddr::MacAddr6;
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::IpAddr,
+    str::FromStr,
+};
+use uuid::Uuid;
+use zbus::zvariant::{self, OwnedValue, Value};
+
+const MASKED_SECRET: &str = "********";
+
+/// Returns the name of the keyfile for the given connection ID.
+pub fn keyfile_name(id: &str) -> String {
+    format!("{}.nmconnection", id.replace('/', "_"))
+}
+
+/// Returns the keyfile that NetworkManager would store for the given connection.
+///
+/// The result can be imported again using [keyfile_to_connection].
+///
+/// * `conn`: connection to render.
+/// * `controller`: controller of the connection, if any.
+/// * `redact`: whether to mask the secrets, so the result can be safely displayed.
+pub fn connection_to_keyfile(
+    conn: &Connection,
+    controller: Option<&Connection>,
+    redact: bool,
+) -> String {
+    let dbus = connection_to_dbus(conn, controller);
+    let mut sections: BTreeMap<&str, BTreeMap<String, String>> = BTreeMap::new();
+
+    for (name, settings) in dbus.iter() {
+        let section = sections.entry(name).or_default();
+        for (key, value) in settings.iter() {
+            add_setting(section, key, value, redact);
+        }
+    }
+    sections
+        .entry("connection")
+        .or_default()
+        .insert("uuid".to_string(), conn.uuid.to_string());
+
+    // NetworkManager writes the "connection" section first.
+    let connection = sections.remove("connection").unwrap_or_default();
+    let sections = [("connection", connection)]
+        .into_iter()
+        .chain(sections)
+        .filter(|(_, settings)| !settings.is_empty());
+
+    let mut output = vec![];
+    for (name, settings) in sections {
+        let mut lines = vec![format!("[{}]", name)];
+        lines.extend(settings.iter().map(|(k, v)| format!("{}={}", k, v)));
+        output.push(lines.join("\n"));
+    }
+    output.join("\n\n") + "\n"
+}
+
+fn add_setting(section: &mut BTreeMap<String, String>, key: &str, value: &Value, redact: bool) {
+    match (key, value) {
+        // NetworkManager does not write the default values
+        ("dns-over-tls" | "ip6-privacy", Value::I32(-1))
+        | ("dns-priority", Value::I32(0))
+        | ("gateway-ping-timeout", Value::U32(0)) => {}
+        ("options", Value::Dict(dict)) => {
+            if let Ok(options) = <HashMap<String, String>>::try_from(dict.clone()) {
+                section.extend(options);
+            }
+        }
+        ("address-data", Value::Array(addresses)) => {
+            for (index, address) in addresses.get().iter().enumerate() {
+                let Some(map) = value_to_map(address) else {
+                    continue;
+                };
+                if let (Some(Value::Str(address)), Some(Value::U32(prefix))) =
+                    (map.get("address"), map.get("prefix"))
+                {
+                    section.insert(
+                        format!("address{}", index + 1),
+                        format!("{}/{}", address, prefix),
+                    );
+                }
+            }
+        }
+        ("route-data", Value::Array(routes)) => {
+            for (index, route) in routes.get().iter().enumerate() {
+                let Some(map) = value_to_map(route) else {
+                    continue;
+                };
+                let (Some(Value::Str(dest)), Some(Value::U32(prefix))) =
+                    (map.get("dest"), map.get("prefix"))
+                else {
+                    continue;
+                };
+                let mut route = format!("{}/{}", dest, prefix);
+                if let Some(Value::Str(next_hop)) = map.get("next-hop") {
+                    route.push_str(&format!(",{}", next_hop));
+                }
+                if let Some(Value::U32(metric)) = map.get("metric") {
+                    route.push_str(&format!(",{}", metric));
+                }
+                section.insert(format!("route{}", index + 1), route);
+            }
+        }
+        _ => {
+            let Some(text) = value_to_string(value) else {
+                return;
+            };
+            if text.is_empty() {
+                return;
+            }
+            let key = if key == "dns-data" { "dns" } else { key };
+            let text = if redact && is_secret(key) {
+                MASKED_SECRET.to_string()
+            } else {
+                text
+            };
+            section.insert(key.to_string(), text);
+        }
+    }
+}
+
+/// Whether the value of the given key is a secret (passwords, PSK or WEP keys).
+fn is_secret(key: &str) -> bool {
+    key == "psk"
+        || key.ends_with("password")
+        || (key.starts_with("wep-key") && key != "wep-key-type")
+}
+
+fn value_to_map<'a>(value: &Value<'a>) -> Option<HashMap<String, Value<'a>>> {
+    let Value::Dict(dict) = value else {
+        return None;
+    };
+    <HashMap<String, Value<'_>>>::try_from(dict.clone()).ok()
+}
+
+fn value_to_string(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::Str(text) => text.to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::U8(value) => value.to_string(),
+        Value::U16(value) => value.to_string(),
+        Value::U32(value) => value.to_string(),
+        Value::U64(value) => value.to_string(),
+        Value::I16(value) => value.to_string(),
+        Value::I32(value) => value.to_string(),
+        Value::I64(value) => value.to_string(),
+        Value::Array(array) => {
+            let items = array.get();
+            if !items.is_empty() && items.iter().all(|i| matches!(i, Value::U8(_))) {
+                let bytes: Vec<u8> = items
+                    .iter()
+                    .filter_map(|i| i.downcast_ref::<u8>().copied())
+                    .collect();
+                return Some(String::from_utf8_lossy(&bytes).to_string());
+            }
+            let items: Option<Vec<String>> = items.iter().map(value_to_string).collect();
+            items?.iter().map(|i| format!("{};", i)).collect()
+        }
+        _ => return None,
+    };
+    Some(text)
+}
+
+/// Error parsing a NetworkManager keyfile.
+///
+/// The messages never include the offending values, so secrets are not leaked.
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum KeyfileError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error("line {line}: unsupported section '{name}'")]
+    UnsupportedSection { line: usize, name: String },
+    #[error("line {line}: unsupported setting '{name}'")]
+    UnsupportedSetting { line: usize, name: String },
+    #[error("missing required setting '{0}'")]
+    MissingSetting(String),
+    #[error("unsupported connection type '{0}'")]
+    UnsupportedType(String),
+    #[error("the connection settings are not valid")]
+    InvalidConnection,
+}
+
+impl KeyfileError {
+    /// Returns the offending line, if known.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Self::Syntax { line, .. }
+            | Self::UnsupportedSection { line, .. }
+            | Self::UnsupportedSetting { line, .. } => Some(*line),
+            _ => None,
+        }
+    }
+
+    fn syntax(line: usize, message: &str) -> Self {
+        Self::Syntax {
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Setting from a keyfile, including the line where it was found.
+struct KeyfileSetting<'a> {
+    key: &'a str,
+    value: &'a str,
+    line: usize,
+}
+
+/// Section from a keyfile, including the line of its header.
+struct KeyfileSection<'a> {
+    name: String,
+    line: usize,
+    settings: Vec<KeyfileSetting<'a>>,
+}
+
+/// Parses a NetworkManager keyfile into a connection.
+///
+/// Only the ethernet, wireless, bond, VLAN, loopback and dummy connections are supported.
+/// Unknown sections and settings are rejected, as they would be lost when importing the
+/// connection. Empty sections are ignored. If the keyfile does not contain a UUID, a new one is
+/// generated.
+///
+/// * `content`: keyfile content.
+pub fn keyfile_to_connection(content: &str) -> Result<Connection, KeyfileError> {
+    let sections = parse_keyfile(content)?;
+    let mut conn: OwnedNestedHash = HashMap::new();
+
+    for keyfile_section in sections.iter() {
+        let KeyfileSection {
+            name: keyfile_name,
+            line,
+            settings,
+        } = keyfile_section;
+        if settings.is_empty() {
+            continue;
+        }
+        let Some(name) = dbus_section_name(keyfile_name) else {
+            return Err(KeyfileError::UnsupportedSection {
+                line: *line,
+                name: keyfile_name.to_string(),
+            });
+        };
+        let section = conn.entry(name.to_string()).or_default();
+        for setting in settings.iter() {
+            if !is_supported_setting(name, setting.key) {
+                return Err(KeyfileError::UnsupportedSetting {
+                    line: setting.line,
+                    name: format!("{}.{}", keyfile_name, setting.key),
+                });
+            }
+            add_dbus_setting(name, section, setting)?;
+        }
+    }
+
+    let connection = conn
+        .get_mut("connection")
+        .ok_or(KeyfileError::MissingSetting("connection.id".to_string()))?;
+    if !connection.contains_key("id") {
+        return Err(KeyfileError::MissingSetting("connection.id".to_string()));
+    }
+    if !connection.contains_key("uuid") {
+        connection.insert(
+            "uuid".to_string(),
+            Value::new(Uuid::new_v4().to_string()).to_owned(),
+        );
+    }
+    let Some(type_) = connection.get("type") else {
+        return Err(KeyfileError::MissingSetting("connection.type".to_string()));
+    };
+    let type_: &str = type_.downcast_ref().unwrap_or_default();
+    let type_section = match type_ {
+        "ethernet" | "802-3-ethernet" => "802-3-ethernet",
+        "wifi" | "802-11-wireless" => "802-11-wireless",
+        "bond" | "vlan" | "loopback" | "dummy" => type_,
+        _ => return Err(KeyfileError::UnsupportedType(type_.to_string())),
+    };
+    let type_section = type_section.to_string();
+    let section = conn.entry(type_section.clone()).or_default();
+    if type_section == "802-11-wireless" && !section.contains_key("mode") {
+        section.insert("mode".to_string(), Value::new("infrastructure").to_owned());
+    }
+    if type_section == "bond" && !section.contains_key("options") {
+        let options: HashMap<String, String> = HashMap::new();
+        section.insert("options".to_string(), Value::new(options).to_owned());
+    }
+
+    connection_from_dbus(conn).ok_or(KeyfileError::InvalidConnection)
+}
+
+fn parse_keyfile(content: &str) -> Result<Vec<KeyfileSection<'_>>, KeyfileError> {
+    let mut sections: Vec<KeyfileSection> = vec![];
+
+    for (index, line) in content.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[') {
+            let name = name
+                .strip_suffix(']')
+                .filter(|n| !n.is_empty())
+                .ok_or(KeyfileError::syntax(number, "malformed section header"))?;
+            sections.push(KeyfileSection {
+                name: name.to_string(),
+                line: number,
+                settings: vec![],
+            });
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(KeyfileError::syntax(
+                number,
+                "expected a section header or a key=value pair",
+            ));
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(KeyfileError::syntax(number, "missing key"));
+        }
+        let Some(section) = sections.last_mut() else {
+            return Err(KeyfileError::syntax(number, "setting outside of a section"));
+        };
+        section.settings.push(KeyfileSetting {
+            key,
+            value: value.trim(),
+            line: number,
+        });
+    }
+
+    Ok(sections)
+}
+
+/// Returns the D-Bus name of a keyfile section or `None` if it is not supported.
+fn dbus_section_name(name: &str) -> Option<&str> {
+    let name = match name {
+        "ethernet" => "802-3-ethernet",
+        "wifi" => "802-11-wireless",
+        "wifi-security" => "802-11-wireless-security",
+        "connection"
+        | "ipv4"
+        | "ipv6"
+        | "bond"
+        | "vlan"
+        | "match"
+        | "802-3-ethernet"
+        | "802-11-wireless"
+        | "802-11-wireless-security" => name,
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Whether a setting is supported in the given section, using the keyfile name of the setting.
+///
+/// * `section_name`: D-Bus name of the section.
+/// * `key`: keyfile name of the setting.
+fn is_supported_setting(section_name: &str, key: &str) -> bool {
+    let numbered = |prefix: &str| {
+        key.strip_prefix(prefix)
+            .is_some_and(|n| n.parse::<u32>().is_ok())
+    };
+    match section_name {
+        "connection" => matches!(
+            key,
+            "id" | "uuid"
+                | "type"
+                | "interface-name"
+                | "autoconnect"
+                | "zone"
+                | "metered"
+                | "dns-over-tls"
+                | "gateway-ping-timeout"
+        ),
+        "ipv4" | "ipv6" => {
+            numbered("address")
+                || numbered("route")
+                || matches!(
+                    key,
+                    "method"
+                        | "gateway"
+                        | "dns"
+                        | "dns-search"
+                        | "ignore-auto-dns"
+                        | "dns-priority"
+                )
+                || (section_name == "ipv6" && key == "ip6-privacy")
+        }
+        "802-3-ethernet" => matches!(key, "mtu" | "cloned-mac-address" | "assigned-mac-address"),
+        "802-11-wireless" => matches!(
+            key,
+            "mode"
+                | "ssid"
+                | "band"
+                | "channel"
+                | "bssid"
+                | "hidden"
+                | "mtu"
+                | "cloned-mac-address"
+                | "assigned-mac-address"
+        ),
+        "802-11-wireless-security" => matches!(
+            key,
+            "key-mgmt"
+                | "psk"
+                | "auth-alg"
+                | "wep-key-type"
+                | "wep-tx-keyidx"
+                | "wep-key0"
+                | "wep-key1"
+                | "wep-key2"
+                | "wep-key3"
+                | "group"
+                | "pairwise"
+                | "proto"
+                | "pmf"
+        ),
+        "vlan" => matches!(key, "id" | "parent" | "protocol"),
+        "match" => matches!(
+            key,
+            "driver" | "interface-name" | "path" | "kernel-command-line"
+        ),
+        // any bond option is kept
+        "bond" => true,
+        _ => false,
+    }
+}
+
+fn add_dbus_setting(
+    section_name: &str,
+    section: &mut HashMap<String, OwnedValue>,
+    setting: &KeyfileSetting,
+) -> Result<(), KeyfileError> {
+    let KeyfileSetting { key, value, line } = *setting;
+    let invalid =
+        |what: &str| KeyfileError::syntax(line, &format!("invalid {} for '{}'", what, key));
+
+    let value: Value = match (section_name, key) {
+        ("ipv4" | "ipv6", _) => return add_ip_setting(section, setting),
+        ("bond", _) => {
+            let mut options: HashMap<String, String> = section
+                .get("options")
+                .and_then(|o| o.downcast_ref::<zvariant::Dict>())
+                .and_then(|o| HashMap::try_from(o.clone()).ok())
+                .unwrap_or_default();
+            options.insert(key.to_string(), value.to_string());
+            section.insert("options".to_string(), Value::new(options).to_owned());
+            return Ok(());
+        }
+        ("match", _) | (_, "group" | "pairwise" | "proto") => Value::new(split_list(value)),
+        (_, "autoconnect" | "hidden") => Value::new(parse_bool(value).ok_or(invalid("boolean"))?),
+        ("vlan", "id")
+        | ("connection", "gateway-ping-timeout")
+        | (_, "mtu" | "channel" | "wep-key-type" | "wep-tx-keyidx") => {
+            Value::new(value.parse::<u32>().map_err(|_| invalid("number"))?)
+        }
+        (_, "pmf" | "metered" | "dns-over-tls") => {
+            Value::new(value.parse::<i32>().map_err(|_| invalid("number"))?)
+        }
+        (_, "ssid") => Value::new(value.as_bytes().to_vec()),
+        (_, "bssid") => {
+            let mac = MacAddr6::from_str(value).map_err(|_| invalid("MAC address"))?;
+            Value::new(mac.as_bytes().to_vec())
+        }
+        (_, "cloned-mac-address" | "assigned-mac-address") => {
+            section.insert(
+                "assigned-mac-address".to_string(),
+                Value::new(value).to_owned(),
+            );
+            return Ok(());
+        }
+        _ => Value::new(value),
+    };
+    section.insert(key.to_string(), value.to_owned());
+    Ok(())
+}
+
+fn add_ip_setting(
+    section: &mut HashMap<String, OwnedValue>,
+    setting: &KeyfileSetting,
+) -> Result<(), KeyfileError> {
+    let KeyfileSetting { key, value, line } = *setting;
+    let invalid =
+        |what: &str| KeyfileError::syntax(line, &format!("invalid {} for '{}'", what, key));
+
+    if !section.contains_key("address-data") {
+        let addresses: Vec<HashMap<String, Value>> = vec![];
+        section.insert("address-data".to_string(), Value::new(addresses).to_owned());
+    }
+
+    if key.starts_with("address") && key["address".len()..].parse::<u32>().is_ok() {
+        let (address, gateway) = match value.split_once(',') {
+            Some((address, gateway)) => (address, Some(gateway)),
+            None => (address_without_gateway(value), None),
+        };
+        let address = IpInet::from_str(address).map_err(|_| invalid("address"))?;
+        let mut addresses = owned_dicts(section.get("address-data"));
+        addresses.push(HashMap::from([
+            (
+                "address".to_string(),
+                Value::new(address.address().to_string()),
+            ),
+            (
+                "prefix".to_string(),
+                Value::new(address.network_length() as u32),
+            ),
+        ]));
+        section.insert("address-data".to_string(), Value::new(addresses).to_owned());
+        if let Some(gateway) = gateway {
+            let gateway = IpAddr::from_str(gateway).map_err(|_| invalid("gateway"))?;
+            section
+                .entry("gateway".to_string())
+                .or_insert(Value::new(gateway.to_string()).to_owned());
+        }
+        return Ok(());
+    }
+
+    if key.starts_with("route") && key["route".len()..].parse::<u32>().is_ok() {
+        let mut parts = value.split(',');
+        let destination = parts.next().unwrap_or_default();
+        let destination = IpInet::from_str(destination).map_err(|_| invalid("route"))?;
+        let mut route = HashMap::from([
+            (
+                "dest".to_string(),
+                Value::new(destination.address().to_string()),
+            ),
+            (
+                "prefix".to_string(),
+                Value::new(destination.network_length() as u32),
+            ),
+        ]);
+        if let Some(next_hop) = parts.next().filter(|h| !h.is_empty()) {
+            let next_hop = IpAddr::from_str(next_hop).map_err(|_| invalid("route"))?;
+            route.insert("next-hop".to_string(), Value::new(next_hop.to_string()));
+        }
+        if let Some(metric) = parts.next() {
+            let metric = metric.parse::<u32>().map_err(|_| invalid("route"))?;
+            route.insert("metric".to_string(), Value::new(metric));
+        }
+        let mut routes = owned_dicts(section.get("route-data"));
+        routes.push(route);
+        section.insert("route-data".to_string(), Value::new(routes).to_owned());
+        return Ok(());
+    }
+
+    let value: Value = match key {
+        "method" => Value::new(value),
+        "gateway" => {
+            let gateway = IpAddr::from_str(value).map_err(|_| invalid("gateway"))?;
+            Value::new(gateway.to_string())
+        }
+        "dns" => {
+            let servers = split_list(value);
+            if servers.iter().any(|s| IpAddr::from_str(s).is_err()) {
+                return Err(invalid("address"));
+            }
+            section.insert("dns-data".to_string(), Value::new(servers).to_owned());
+            return Ok(());
+        }
+        "dns-search" => Value::new(split_list(value)),
+        "ignore-auto-dns" => Value::new(parse_bool(value).ok_or(invalid("boolean"))?),
+        "dns-priority" | "ip6-privacy" => {
+            Value::new(value.parse::<i32>().map_err(|_| invalid("number"))?)
+        }
+        _ => return Ok(()),
+    };
+    section.insert(key.to_string(), value.to_owned());
+    Ok(())
+}
+
+/// Removes the gateway from an old-style address ("192.168.1.1/24;192.168.1.254").
+fn address_without_gateway(address: &str) -> &str {
+    address.split(';').next().unwrap_or_default()
+}
+
+fn owned_dicts(value: Option<&OwnedValue>) -> Vec<HashMap<String, Value<'static>>> {
+    let Some(Value::Array(array)) = value.map(|v| Value::from(v.clone())) else {
+        return vec![];
+    };
+    array
+        .get()
+        .iter()
+        .filter_map(|item| {
+            let Value::Dict(dict) = item else {
+                return None;
+            };
+            let map: HashMap<String, Value> = HashMap::try_from(dict.clone()).ok()?;
+            Some(
+                map.into_iter()
+                    .map(|(k, v)| (k, v.to_owned().into()))
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split([';', ','])
+        .map(str::trim)
+        .filter(|i| !i.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" | "yes" | "1" => Some(true),
+        "false" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::model::{
+        ConnectionConfig, IpConfig, Ipv4Method, Ipv6Method, SecurityProtocol, WirelessConfig,
+    };
+    use agama_lib::network::types::{DnsOverTls, Ipv6Privacy, SSID};
+    use cidr::IpInet;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_connection_to_keyfile() {
+        let conn = Connection {
+            id: "eth0".to_string(),
+            interface: Some("eth0".to_string()),
+            ip_config: IpConfig {
+                method4: Ipv4Method::Manual,
+                addresses: vec![IpInet::from_str("192.168.1.10/24").unwrap()],
+                nameservers: vec!["192.168.1.1".parse().unwrap()],
+                gateway4: Some("192.168.1.1".parse().unwrap()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let keyfile = connection_to_keyfile(&conn, None, true);
+        assert!(keyfile.starts_with("[connection]\nautoconnect=true\nid=eth0\n"));
+        assert!(keyfile.contains(&format!("uuid={}\n", conn.uuid)));
+        assert!(keyfile.contains("type=802-3-ethernet\n"));
+        assert!(keyfile
+            .contains("[ipv4]\naddress1=192.168.1.10/24\ndns=192.168.1.1;\ngateway=192.168.1.1\n"));
+    }
+
+    #[test]
+    fn test_connection_to_keyfile_masks_secrets() {
+        let conn = Connection {
+            id: "wlan0".to_string(),
+            config: ConnectionConfig::Wireless(WirelessConfig {
+                ssid: SSID(b"agama".to_vec()),
+                password: Some("s3cr3t".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let keyfile = connection_to_keyfile(&conn, None, true);
+        assert!(keyfile.contains("ssid=agama\n"));
+        assert!(keyfile.contains("psk=********\n"));
+        assert!(!keyfile.contains("s3cr3t"));
+    }
+
+    #[test]
+    fn test_keyfile_to_connection() {
+        let keyfile = r#"
+# Generated by hand
+[connection]
+id=eth0
+uuid=4e9f7d2a-0c64-4bd1-9ce3-a462b5a6f3cb
+type=ethernet
+interface-name=eth0
+autoconnect=false
+
+[ethernet]
+mtu=1500
+
+[ipv4]
+method=manual
+address1=192.168.1.10/24,192.168.1.1
+dns=192.168.1.1;8.8.8.8;
+
+[ipv6]
+method=auto
+"#;
+
+        let conn = keyfile_to_connection(keyfile).unwrap();
+        assert_eq!(conn.id, "eth0");
+        assert_eq!(
+            conn.uuid.to_string(),
+            "4e9f7d2a-0c64-4bd1-9ce3-a462b5a6f3cb"
+        );
+        assert_eq!(conn.interface, Some("eth0".to_string()));
+        assert!(!conn.autoconnect);
+        assert_eq!(conn.mtu, 1500);
+        assert_eq!(conn.ip_config.method4, Ipv4Method::Manual);
+        assert_eq!(
+            conn.ip_config.addresses,
+            vec![IpInet::from_str("192.168.1.10/24").unwrap()]
+        );
+        assert_eq!(
+            conn.ip_config.gateway4,
+            Some("192.168.1.1".parse().unwrap())
+        );
+        assert_eq!(conn.ip_config.nameservers.len(), 2);
+    }
+
+    #[test]
+    fn test_keyfile_to_wireless_connection() {
+        let keyfile = "[connection]\nid=home\ntype=wifi\n\n[wifi]\nssid=agama\n\n\
+                       [wifi-security]\nkey-mgmt=wpa-psk\npsk=s3cr3t\n";
+
+        let conn = keyfile_to_connection(keyfile).unwrap();
+        let ConnectionConfig::Wireless(config) = conn.config else {
+            panic!("Not a wireless connection");
+        };
+        assert_eq!(config.ssid, SSID(b"agama".to_vec()));
+        assert!(config.password.is_some());
+    }
+
+    #[test]
+    fn test_keyfile_to_connection_errors() {
+        let error = keyfile_to_connection("[connection]\nid=eth0\nnot a setting\n").unwrap_err();
+        assert_eq!(error.line(), Some(3));
+
+        let error = keyfile_to_connection("id=eth0\n").unwrap_err();
+        assert_eq!(error.line(), Some(1));
+
+        let error =
+            keyfile_to_connection("[connection]\nid=eth0\ntype=ethernet\nautoconnect=maybe\n")
+                .unwrap_err();
+        assert_eq!(error.line(), Some(4));
+
+        let keyfile =
+            "[connection]\nid=eth0\ntype=ethernet\n[ipv4]\nmethod=manual\naddress1=10.0.0.300/8\n";
+        let error = keyfile_to_connection(keyfile).unwrap_err();
+        assert_eq!(error.line(), Some(6));
+
+        let error = keyfile_to_connection("[connection]\ntype=ethernet\n").unwrap_err();
+        assert_eq!(
+            error,
+            KeyfileError::MissingSetting("connection.id".to_string())
+        );
+
+        let keyfile = "[connection]\nid=eth0\ntype=ethernet\n\n[proxy]\n\n[ipv6]\nmethod=auto\n\
+                       addr-gen-mode=stable-privacy\n";
+        let error = keyfile_to_connection(keyfile).unwrap_err();
+        assert_eq!(
+            error,
+            KeyfileError::UnsupportedSetting {
+                line: 8,
+                name: "ipv6.addr-gen-mode".to_string()
+            }
+        );
+
+        let keyfile = "[connection]\nid=eth0\ntype=ethernet\n[ethtool]\nfeature-tso=false\n";
+        let error = keyfile_to_connection(keyfile).unwrap_err();
+        assert_eq!(
+            error,
+            KeyfileError::UnsupportedSection {
+                line: 4,
+                name: "ethtool".to_string()
+            }
+        );
+
+        let error = keyfile_to_connection("[connection]\nid=vpn\ntype=wireguard\n").unwrap_err();
+        assert_eq!(
+            error,
+            KeyfileError::UnsupportedType("wireguard".to_string())
+        );
+    }
+
+    #[test]
+    fn test_keyfile_round_trip() {
+        let conn = Connection {
+            id: "wlan0".to_string(),
+            interface: Some("wlan0".to_string()),
+            ip_config: IpConfig {
+                method4: Ipv4Method::Manual,
+                addresses: vec![IpInet::from_str("10.0.0.2/8").unwrap()],
+                gateway4: Some("10.0.0.1".parse().unwrap()),
+                dns_priority4: Some(-50),
+                method6: Ipv6Method::Auto,
+                ipv6_privacy: Ipv6Privacy::PreferTemporary,
+                ..Default::default()
+            },
+            dns_over_tls: DnsOverTls::Yes,
+            config: ConnectionConfig::Wireless(WirelessConfig {
+                ssid: SSID(b"agama".to_vec()),
+                password: Some("s3cr3t".to_string()),
+                security: SecurityProtocol::WPA2,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let keyfile = connection_to_keyfile(&conn, None, false);
+        assert!(keyfile.contains("psk=s3cr3t\n"));
+        assert!(keyfile.contains("dns-over-tls=2\n"));
+        assert!(keyfile.contains("dns-priority=-50\n"));
+        assert!(keyfile.contains("ip6-privacy=2\n"));
+
+        let imported = keyfile_to_connection(&keyfile).unwrap();
+        assert_eq!(imported.id, conn.id);
+        assert_eq!(imported.uuid, conn.uuid);
+        assert_eq!(imported.interface, conn.interface);
+        assert_eq!(imported.ip_config.addresses, conn.ip_config.addresses);
+        assert_eq!(imported.ip_config.gateway4, conn.ip_config.gateway4);
+        assert_eq!(imported.ip_config.dns_priority4, Some(-50));
+        assert_eq!(imported.ip_config.dns_priority6, None);
+        assert_eq!(
+            imported.ip_config.ipv6_privacy,
+            Ipv6Privacy::PreferTemporary
+        );
+        assert_eq!(imported.dns_over_tls, DnsOverTls::Yes);
+        let ConnectionConfig::Wireless(config) = imported.config else {
+            panic!("Not a wireless connection");
+        };
+        assert_eq!(config.ssid, SSID(b"agama".to_vec()));
+        assert_eq!(config.security, SecurityProtocol::WPA2);
+    }
+}
@@ -902,3 +902,50 @@ trait IP6Config {
     #[dbus_proxy(property)]
     fn searches(&self) -> zbus::Result<Vec<String>>;
 }
+
+#[dbus_proxy(
+    interface = "org.freedesktop.NetworkManager.DnsManager",
+    default_service = "org.freedesktop.NetworkManager",
+    default_path = "/org/freedesktop/NetworkManager/DnsManager"
+)]
+trait DnsManager {
+    /// Configuration property
+    #[dbus_proxy(property)]
+    fn configuration(
+        &self,
+    ) -> zbus::Result<Vec<std::collections::HashMap<String, zbus::zvariant::OwnedValue>>>;
+
+    /// Mode property
+    #[dbus_proxy(property)]
+    fn mode(&self) -> zbus::Result<String>;
+
+    /// RcManager property
+    #[dbus_proxy(property)]
+    fn rc_manager(&self) -> zbus::Result<String>;
+}
+
+#[dbus_proxy(
+    interface = "org.freedesktop.NetworkManager.DHCP4Config",
+    default_service = "org.freedesktop.NetworkManager",
+    default_path = "/org/freedesktop/NetworkManager/DHCP4Config/1"
+)]
+trait DHCP4Config {
+    /// Options property
+    #[dbus_proxy(property)]
+    fn options(
+        &self,
+    ) -> zbus::Result<std::collections::HashMap<String, zbus::zvariant::OwnedValue>>;
+}
+
+#[dbus_proxy(
+    interface = "org.freedesktop.NetworkManager.DHCP6Config",
+    default_service = "org.freedesktop.NetworkManager",
+    default_path = "/org/freedesktop/NetworkManager/DHCP6Config/1"
+)]
+trait DHCP6Config {
+    /// Options property
+    #[dbus_proxy(property)]
+    fn options(
+        &self,
+    ) -> zbus::Result<std::collections::HashMap<String, zbus::zvariant::OwnedValue>>;
+}
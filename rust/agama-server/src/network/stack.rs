@@ -0,0 +1,296 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Handles stacks of connections, like a VLAN on top of a bond on top of physical interfaces.
+//!
+//! Each layer references its lower layers by connection ID or interface name: bonds and bridges
+//! list their ports and VLANs set their parent. A stack is added as a set of connections, so the
+//! references are validated as a whole before changing anything.
+
+use super::{error::NetworkStateError, model::Connection};
+use agama_lib::network::settings::NetworkConnection;
+use std::collections::BTreeMap;
+
+/// Returns the problems found in the stacks of a set of connections.
+///
+/// The lower layers must be in the set or already exist, a connection can be a port of only one
+/// bond or bridge, a port cannot carry a VLAN and the layers cannot form a loop.
+///
+/// * `conns`: connections to check.
+/// * `known`: names (IDs or interfaces) of the existing connections and devices.
+pub fn validate_stacks(conns: &[NetworkConnection], known: &[String]) -> Vec<String> {
+    let mut issues = vec![];
+    let mut controllers: BTreeMap<&str, &str> = BTreeMap::new();
+
+    for conn in conns {
+        let ports = conn.ports().unwrap_or_default();
+        for layer in conn.lower_layers() {
+            if names(conn).any(|n| n == layer) {
+                issues.push(format!(
+                    "Connection '{}' cannot be stacked on itself",
+                    conn.id
+                ));
+            } else if find(layer, conns).is_none() && !known.iter().any(|k| k == layer) {
+                issues.push(format!(
+                    "Connection '{}' uses an unknown lower layer '{}'",
+                    conn.id, layer
+                ));
+            }
+        }
+
+        for port in ports {
+            let port = find(port, conns).map_or(port.as_str(), |p| p.id.as_str());
+            if let Some(other) = controllers.insert(port, &conn.id) {
+                issues.push(format!(
+                    "'{}' is a port of both '{}' and '{}'",
+                    port, other, conn.id
+                ));
+            }
+        }
+    }
+
+    for conn in conns.iter().filter(|c| c.vlan.is_some()) {
+        let Some(parent) = conn.parent.as_deref() else {
+            continue;
+        };
+        let parent = find(parent, conns).map_or(parent, |p| p.id.as_str());
+        if let Some(controller) = controllers.get(parent) {
+            issues.push(format!(
+                "The VLAN '{}' cannot use '{}', which is a port of '{}'",
+                conn.id, parent, controller
+            ));
+        }
+    }
+
+    let mut reported: Vec<&str> = vec![];
+    for conn in conns {
+        let mut path = vec![];
+        if let Some(cycle) = find_loop(conn, conns, &mut path) {
+            if !cycle.iter().any(|c| reported.contains(c)) {
+                reported.extend(&cycle);
+                issues.push(format!(
+                    "The connections {} form a loop",
+                    cycle
+                        .iter()
+                        .map(|c| format!("'{}'", c))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+        }
+    }
+    issues
+}
+
+/// Interface to use as the parent of a VLAN.
+///
+/// NetworkManager expects an interface name, so a connection ID is replaced with the interface of
+/// the connection, looking first in the set of new connections.
+///
+/// * `parent`: connection ID or interface name.
+/// * `conns`: connections being added.
+/// * `existing`: existing connections.
+pub fn resolve_parent(
+    parent: &str,
+    conns: &[NetworkConnection],
+    existing: &[Connection],
+) -> String {
+    let interface = match conns.iter().find(|c| c.id == parent) {
+        Some(conn) => conn.interface.clone(),
+        None => existing
+            .iter()
+            .find(|c| c.id == parent)
+            .and_then(|c| c.interface.clone()),
+    };
+    interface.unwrap_or_else(|| parent.to_string())
+}
+
+/// Converts the connections to settings, including the ports of the bonds and the bridges.
+///
+/// The ports are referenced by their interface names (or their IDs, if they are not bound to an
+/// interface), so the whole stack can be read back.
+///
+/// * `conns`: connections to convert.
+pub fn to_settings(conns: &[Connection]) -> Result<Vec<NetworkConnection>, NetworkStateError> {
+    let mut settings = Vec::with_capacity(conns.len());
+    for conn in conns {
+        let mut setting = NetworkConnection::try_from(conn.clone())?;
+        let ports = conns
+            .iter()
+            .filter(|c| c.controller == Some(conn.uuid))
+            .map(|c| c.interface.clone().unwrap_or_else(|| c.id.clone()));
+        if let Some(bond) = setting.bond.as_mut() {
+            bond.ports = ports.collect();
+        } else if let Some(bridge) = setting.bridge.as_mut() {
+            bridge.ports = ports.collect();
+        }
+        settings.push(setting);
+    }
+    Ok(settings)
+}
+
+/// Names which can be used to reference a connection.
+fn names(conn: &NetworkConnection) -> impl Iterator<Item = &str> {
+    std::iter::once(conn.id.as_str()).chain(conn.interface.as_deref())
+}
+
+fn find<'a>(name: &str, conns: &'a [NetworkConnection]) -> Option<&'a NetworkConnection> {
+    conns.iter().find(|c| names(c).any(|n| n == name))
+}
+
+/// Follows the lower layers of a connection, returning the IDs of the connections in a loop, if
+/// any.
+fn find_loop<'a>(
+    conn: &'a NetworkConnection,
+    conns: &'a [NetworkConnection],
+    path: &mut Vec<&'a str>,
+) -> Option<Vec<&'a str>> {
+    if let Some(start) = path.iter().position(|c| *c == conn.id) {
+        return Some(path[start..].to_vec());
+    }
+    path.push(&conn.id);
+    for layer in conn.lower_layers() {
+        // stacking on itself is reported on its own
+        if names(conn).any(|n| n == layer) {
+            continue;
+        }
+        if let Some(lower) = find(layer, conns) {
+            if let Some(cycle) = find_loop(lower, conns, path) {
+                return Some(cycle);
+            }
+        }
+    }
+    path.pop();
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::model::{BondConfig, ConnectionConfig};
+    use agama_lib::network::{
+        settings::{BondSettings, VlanSettings},
+        types::DeviceType,
+    };
+
+    fn bond(id: &str, ports: &[&str]) -> NetworkConnection {
+        NetworkConnection {
+            id: id.to_string(),
+            interface: Some(id.to_string()),
+            bond: Some(BondSettings {
+                ports: ports.iter().map(|p| p.to_string()).collect(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn vlan(id: &str, parent: &str) -> NetworkConnection {
+        NetworkConnection {
+            id: id.to_string(),
+            parent: Some(parent.to_string()),
+            vlan: Some(VlanSettings {
+                id: 100,
+                protocol: None,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_stacks() {
+        let known = vec!["eth0".to_string(), "eth1".to_string()];
+        let conns = vec![vlan("bond0.100", "bond0"), bond("bond0", &["eth0", "eth1"])];
+        assert!(validate_stacks(&conns, &known).is_empty());
+
+        let conns = vec![vlan("bond0.100", "bond1"), bond("bond0", &["eth0", "eth2"])];
+        assert_eq!(
+            validate_stacks(&conns, &known),
+            vec![
+                "Connection 'bond0.100' uses an unknown lower layer 'bond1'".to_string(),
+                "Connection 'bond0' uses an unknown lower layer 'eth2'".to_string(),
+            ]
+        );
+
+        let conns = vec![
+            bond("bond0", &["eth0"]),
+            bond("bond1", &["eth0"]),
+            vlan("eth0.100", "eth0"),
+        ];
+        assert_eq!(
+            validate_stacks(&conns, &known),
+            vec![
+                "'eth0' is a port of both 'bond0' and 'bond1'".to_string(),
+                "The VLAN 'eth0.100' cannot use 'eth0', which is a port of 'bond1'".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_stacks_loops() {
+        let conns = vec![bond("bond0", &["bond0.100"]), vlan("bond0.100", "bond0")];
+        assert_eq!(
+            validate_stacks(&conns, &[]),
+            vec!["The connections 'bond0', 'bond0.100' form a loop".to_string()]
+        );
+
+        let conns = vec![bond("bond0", &["bond0"])];
+        assert_eq!(
+            validate_stacks(&conns, &[]),
+            vec!["Connection 'bond0' cannot be stacked on itself".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_resolve_parent() {
+        let mut existing = Connection::new("Wired connection 1".to_string(), DeviceType::Ethernet);
+        existing.interface = Some("eth0".to_string());
+        let conns = vec![bond("bond0", &[])];
+
+        assert_eq!(resolve_parent("bond0", &conns, &[]), "bond0");
+        assert_eq!(
+            resolve_parent("Wired connection 1", &conns, &[existing.clone()]),
+            "eth0"
+        );
+        assert_eq!(resolve_parent("eth1", &conns, &[existing]), "eth1");
+    }
+
+    #[test]
+    fn test_to_settings() {
+        let mut bond = Connection::new("bond0".to_string(), DeviceType::Bond);
+        bond.config = ConnectionConfig::Bond(BondConfig::default());
+        let mut eth0 = Connection::new("eth0".to_string(), DeviceType::Ethernet);
+        eth0.interface = Some("enp1s0".to_string());
+        eth0.controller = Some(bond.uuid);
+        let mut eth1 = Connection::new("eth1".to_string(), DeviceType::Ethernet);
+        eth1.controller = Some(bond.uuid);
+        let vlan = Connection::try_from(vlan("bond0.100", "bond0")).unwrap();
+
+        let settings = to_settings(&[bond, eth0, eth1, vlan]).unwrap();
+        assert_eq!(
+            settings[0].bond.as_ref().unwrap().ports,
+            vec!["enp1s0".to_string(), "eth1".to_string()]
+        );
+        assert_eq!(settings[3].parent, Some("bond0".to_string()));
+        let vlan = settings[3].vlan.as_ref().unwrap();
+        assert_eq!(vlan.id, 100);
+        assert_eq!(vlan.protocol, Some("802.1Q".to_string()));
+    }
+}
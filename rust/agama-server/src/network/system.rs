@@ -20,15 +20,21 @@
 
 use super::{
     error::NetworkStateError,
-    model::{AccessPoint, Device, NetworkChange, StateConfig},
+    model::{
+        AccessPoint, Checkpoint, CheckpointStatus, ConnectivityState, Device, DnsEntry,
+        NetworkChange, StateConfig,
+    },
     NetworkAdapterError,
 };
 use crate::network::{
     model::{Connection, GeneralState},
     Action, Adapter, NetworkState,
 };
-use agama_lib::{error::ServiceError, network::types::DeviceType};
-use std::error::Error;
+use agama_lib::{
+    error::ServiceError,
+    network::types::{DeviceState, DeviceType},
+};
+use std::{error::Error, time::Duration};
 use tokio::sync::{
     broadcast::{self, Receiver},
     mpsc::{self, error::SendError, UnboundedReceiver, UnboundedSender},
@@ -114,6 +120,7 @@ impl<T: Adapter + Send + Sync + 'static> NetworkSystem<T> {
                 input: actions_rx,
                 output: updates_tx_clone,
                 adapter: self.adapter,
+                checkpoint: None,
             };
 
             server.listen().await;
@@ -148,6 +155,20 @@ impl NetworkSystemClient {
         Ok(rx.await?)
     }
 
+    /// Returns the connectivity state.
+    pub async fn get_connectivity(&self) -> Result<ConnectivityState, NetworkSystemError> {
+        let (tx, rx) = oneshot::channel();
+        self.actions.send(Action::GetConnectivity(tx))?;
+        Ok(rx.await??)
+    }
+
+    /// Returns the DNS configuration in use, from the most to the least preferred entry.
+    pub async fn get_dns_configuration(&self) -> Result<Vec<DnsEntry>, NetworkSystemError> {
+        let (tx, rx) = oneshot::channel();
+        self.actions.send(Action::GetDnsConfiguration(tx))?;
+        Ok(rx.await??)
+    }
+
     /// Updates the network general state.
     pub fn update_state(&self, state: GeneralState) -> Result<(), NetworkSystemError> {
         self.actions.send(Action::UpdateGeneralState(state))?;
@@ -202,6 +223,37 @@ impl NetworkSystemClient {
         Ok(result?)
     }
 
+    /// Adds or updates a set of connections at once.
+    ///
+    /// It returns, for each connection, whether it was added (`true`) or updated (`false`).
+    ///
+    /// * `connections`: connections to add or update, identified by their ID.
+    pub async fn set_connections(
+        &self,
+        connections: Vec<Connection>,
+    ) -> Result<Vec<bool>, NetworkSystemError> {
+        let (tx, rx) = oneshot::channel();
+        self.actions
+            .send(Action::SetConnections(Box::new(connections), tx))?;
+        Ok(rx.await?)
+    }
+
+    /// Sets the ports of a bond or a bridge.
+    ///
+    /// * `uuid`: UUID of the bond or the bridge.
+    /// * `ports`: IDs or interface names of the ports.
+    pub async fn set_ports(
+        &self,
+        uuid: Uuid,
+        ports: Vec<String>,
+    ) -> Result<(), NetworkSystemError> {
+        let (tx, rx) = oneshot::channel();
+        self.actions
+            .send(Action::SetPorts(uuid, Box::new(ports), tx))?;
+        let result = rx.await?;
+        Ok(result?)
+    }
+
     /// Removes the connection with the given ID.
     ///
     /// * `id`: Connection ID.
@@ -221,6 +273,68 @@ impl NetworkSystemClient {
         Ok(result?)
     }
 
+    /// Discards the changes and reads the network configuration from the system again.
+    pub async fn reset(&self) -> Result<(), NetworkSystemError> {
+        let (tx, rx) = oneshot::channel();
+        self.actions.send(Action::Reset(tx))?;
+        let result = rx.await?;
+        Ok(result?)
+    }
+
+    /// Copies the connections which are active in the live system into the configuration.
+    ///
+    /// It returns the IDs of the copied connections.
+    pub async fn copy_live_connections(&self) -> Result<Vec<String>, NetworkSystemError> {
+        let (tx, rx) = oneshot::channel();
+        self.actions.send(Action::CopyLiveConnections(tx))?;
+        let result = rx.await?;
+        Ok(result?)
+    }
+
+    /// Creates a checkpoint which is rolled back after `timeout` seconds unless it is confirmed.
+    ///
+    /// When the timeout expires, the configuration is read from the system again.
+    pub async fn create_checkpoint(
+        &self,
+        timeout: u32,
+    ) -> Result<CheckpointStatus, NetworkSystemError> {
+        let (tx, rx) = oneshot::channel();
+        self.actions.send(Action::CreateCheckpoint(timeout, tx))?;
+        let checkpoint = rx.await??;
+
+        let actions = self.actions.clone();
+        let id = checkpoint.id.clone();
+        tokio::spawn(async move {
+            // give the backend some time to complete the rollback
+            tokio::time::sleep(Duration::from_secs(u64::from(timeout) + 1)).await;
+            _ = actions.send(Action::ExpireCheckpoint(id));
+        });
+        Ok(checkpoint.status())
+    }
+
+    /// Returns the pending checkpoint, if any.
+    pub async fn get_checkpoint(&self) -> Result<Option<CheckpointStatus>, NetworkSystemError> {
+        let (tx, rx) = oneshot::channel();
+        self.actions.send(Action::GetCheckpoint(tx))?;
+        Ok(rx.await?)
+    }
+
+    /// Confirms the pending checkpoint, keeping the changes.
+    pub async fn confirm_checkpoint(&self) -> Result<(), NetworkSystemError> {
+        let (tx, rx) = oneshot::channel();
+        self.actions.send(Action::ConfirmCheckpoint(tx))?;
+        Ok(rx.await??)
+    }
+
+    /// Rolls back the changes done after creating the pending checkpoint.
+    ///
+    /// The configuration is read from the system again.
+    pub async fn rollback_checkpoint(&self) -> Result<(), NetworkSystemError> {
+        let (tx, rx) = oneshot::channel();
+        self.actions.send(Action::RollbackCheckpoint(tx))?;
+        Ok(rx.await??)
+    }
+
     /// Returns the collection of access points.
     pub async fn get_access_points(&self) -> Result<Vec<AccessPoint>, NetworkSystemError> {
         let (tx, rx) = oneshot::channel();
@@ -242,6 +356,7 @@ struct NetworkSystemServer<T: Adapter> {
     input: UnboundedReceiver<Action>,
     output: broadcast::Sender<NetworkChange>,
     adapter: T,
+    checkpoint: Option<Checkpoint>,
 }
 
 impl<T: Adapter> NetworkSystemServer<T> {
@@ -294,6 +409,14 @@ impl<T: Adapter> NetworkSystemServer<T> {
                 let config = self.state.general_state.clone();
                 tx.send(config.clone()).unwrap();
             }
+            Action::GetConnectivity(tx) => {
+                let result = self.adapter.connectivity().await;
+                tx.send(result).unwrap();
+            }
+            Action::GetDnsConfiguration(tx) => {
+                let result = self.adapter.dns_configuration().await;
+                tx.send(result).unwrap();
+            }
             Action::GetConnection(id, tx) => {
                 let conn = self.state.get_connection(id.as_ref());
                 tx.send(conn.cloned()).unwrap();
@@ -326,7 +449,23 @@ impl<T: Adapter> NetworkSystemServer<T> {
                 self.state.add_device(*device.clone())?;
                 return Ok(Some(NetworkChange::DeviceAdded(*device)));
             }
-            Action::UpdateDevice(name, device) => {
+            Action::UpdateDevice(name, mut device) => {
+                let old_device = self.state.get_device(&name).cloned();
+                if let Some(old_device) = old_device {
+                    // the connection is not applied anymore, but keep it to report the failure
+                    if device.state == DeviceState::Failed && device.connection.is_none() {
+                        device.connection.clone_from(&old_device.connection);
+                    }
+                    if let (Some(id), Some(reason)) = (&device.connection, device.failure_reason())
+                    {
+                        if old_device.state != DeviceState::Failed {
+                            _ = self.output.send(NetworkChange::ConnectionFailed {
+                                id: id.clone(),
+                                reason,
+                            });
+                        }
+                    }
+                }
                 self.state.update_device(&name, *device.clone())?;
                 return Ok(Some(NetworkChange::DeviceUpdated(name, *device)));
             }
@@ -345,6 +484,10 @@ impl<T: Adapter> NetworkSystemServer<T> {
                 let result = self.state.update_connection(*conn);
                 tx.send(result).unwrap();
             }
+            Action::SetConnections(conns, tx) => {
+                let result = self.state.set_connections(*conns);
+                tx.send(result).unwrap();
+            }
             Action::UpdateGeneralState(general_state) => {
                 self.state.general_state = general_state;
             }
@@ -357,6 +500,38 @@ impl<T: Adapter> NetworkSystemServer<T> {
                 let result = self.write().await;
                 tx.send(result).unwrap();
             }
+            Action::Reset(tx) => {
+                let result = self.reset().await;
+                tx.send(result).unwrap();
+            }
+            Action::CopyLiveConnections(tx) => {
+                let result = self.copy_live_connections().await;
+                tx.send(result).unwrap();
+            }
+            Action::CreateCheckpoint(timeout, tx) => {
+                let result = self.create_checkpoint(timeout).await;
+                tx.send(result).unwrap();
+            }
+            Action::GetCheckpoint(tx) => {
+                let status = self.checkpoint.as_ref().map(Checkpoint::status);
+                tx.send(status).unwrap();
+            }
+            Action::ConfirmCheckpoint(tx) => {
+                let result = self.confirm_checkpoint().await;
+                tx.send(result).unwrap();
+            }
+            Action::RollbackCheckpoint(tx) => {
+                let result = self.rollback_checkpoint().await;
+                tx.send(result).unwrap();
+            }
+            Action::ExpireCheckpoint(id) => {
+                if self.checkpoint.as_ref().is_some_and(|c| c.id == id) {
+                    log::info!("The network checkpoint was not confirmed, rolling back");
+                    self.checkpoint = None;
+                    self.reset().await?;
+                    return Ok(Some(NetworkChange::CheckpointRolledBack));
+                }
+            }
         }
 
         Ok(None)
@@ -405,6 +580,63 @@ impl<T: Adapter> NetworkSystemServer<T> {
         Ok((conn, controlled))
     }
 
+    async fn create_checkpoint(&mut self, timeout: u32) -> Result<Checkpoint, NetworkStateError> {
+        // NetworkManager never rolls back a checkpoint without a timeout
+        if timeout == 0 {
+            return Err(NetworkStateError::InvalidCheckpointTimeout);
+        }
+        if self.checkpoint.is_some() {
+            return Err(NetworkStateError::CheckpointExists);
+        }
+
+        let id = self
+            .adapter
+            .create_checkpoint(timeout)
+            .await
+            .map_err(|e| NetworkStateError::AdapterError(e.to_string()))?;
+        let checkpoint = Checkpoint::new(id, timeout);
+        self.checkpoint = Some(checkpoint.clone());
+        Ok(checkpoint)
+    }
+
+    async fn confirm_checkpoint(&mut self) -> Result<(), NetworkStateError> {
+        let checkpoint = self
+            .checkpoint
+            .take()
+            .ok_or(NetworkStateError::NoCheckpoint)?;
+        self.adapter
+            .confirm_checkpoint(&checkpoint.id)
+            .await
+            .map_err(|e| NetworkStateError::AdapterError(e.to_string()))
+    }
+
+    async fn rollback_checkpoint(&mut self) -> Result<(), NetworkStateError> {
+        let checkpoint = self
+            .checkpoint
+            .take()
+            .ok_or(NetworkStateError::NoCheckpoint)?;
+        self.adapter
+            .rollback_checkpoint(&checkpoint.id)
+            .await
+            .map_err(|e| NetworkStateError::AdapterError(e.to_string()))?;
+        self.reset()
+            .await
+            .map_err(|e| NetworkStateError::AdapterError(e.to_string()))
+    }
+
+    /// Replaces the current state with the one read from the system.
+    pub async fn reset(&mut self) -> Result<(), NetworkAdapterError> {
+        self.state = self.adapter.read(StateConfig::default()).await?;
+        Ok(())
+    }
+
+    /// Copies the connections which are active in the live system, keeping the rest of the
+    /// configuration.
+    pub async fn copy_live_connections(&mut self) -> Result<Vec<String>, NetworkAdapterError> {
+        let live = self.adapter.read(StateConfig::default()).await?;
+        Ok(self.state.copy_active_connections(live))
+    }
+
     /// Writes the network configuration.
     pub async fn write(&mut self) -> Result<(), NetworkAdapterError> {
         self.adapter.write(&self.state).await?;
@@ -22,11 +22,17 @@
 
 use crate::{
     error::Error,
-    web::{Event, EventsSender},
+    software::repo_check::http_client,
+    web::{
+        common::{operations_router, ApplyParams, Operations},
+        generous_timeout, CommitProvider, Event, EventsSender, ReadinessIssue, ReadinessProvider,
+        SnapshotProvider,
+    },
 };
 use anyhow::Context;
+use async_trait::async_trait;
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Response},
     routing::{delete, get, patch, post},
@@ -35,15 +41,31 @@ use axum::{
 
 use super::{
     error::NetworkStateError,
-    model::{AccessPoint, GeneralState},
+    gateway::{GatewayCheck, GatewayChecks},
+    model::{
+        AccessPoint, CheckpointStatus, ConnectivityState, DnsEntry, GeneralState, NetworkChange,
+    },
+    stack::{resolve_parent, to_settings, validate_stacks},
     system::{NetworkSystemClient, NetworkSystemError},
     Adapter,
 };
 
-use crate::network::{model::Connection, model::Device, NetworkSystem};
-use agama_lib::{error::ServiceError, network::settings::NetworkConnection};
+use crate::network::{
+    connection_to_keyfile, keyfile_name, keyfile_to_connection, model::Connection, model::Device,
+    NetworkSystem,
+};
+use agama_lib::{
+    error::ServiceError,
+    network::{
+        settings::NetworkConnection,
+        types::{DeviceState, Status},
+    },
+    product::{ProductClient, RegistrationRequirement},
+};
 
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::BTreeMap;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -58,6 +80,8 @@ pub enum NetworkError {
     CannotUpdate(String),
     #[error("Cannot apply configuration")]
     CannotApplyConfig,
+    #[error("Cannot read the configuration from the system")]
+    CannotReset,
     // TODO: to be removed after adapting to the NetworkSystemServer API
     #[error("Network state error: {0}")]
     Error(#[from] NetworkStateError),
@@ -77,6 +101,9 @@ impl IntoResponse for NetworkError {
 #[derive(Clone)]
 struct NetworkServiceState {
     network: NetworkSystemClient,
+    events: EventsSender,
+    operations: Operations,
+    gateway_checks: GatewayChecks,
 }
 
 /// Sets up and returns the axum service for the network module.
@@ -94,12 +121,38 @@ pub async fn network_service<T: Adapter + Send + Sync + 'static>(
         .await
         .context("Could not start the network configuration service.")?;
 
+    Ok(network_router(client, events))
+}
+
+/// Sets up and returns the axum service for an already running network system.
+///
+/// It allows sharing the network system with other services.
+///
+/// * `client`: client to interact with the network system.
+/// * `events`: sending-half of the broadcast channel.
+pub fn network_router(client: NetworkSystemClient, events: EventsSender) -> Router {
     let mut changes = client.subscribe();
+    let state = NetworkServiceState {
+        network: client,
+        events: events.clone(),
+        operations: Operations::new("network", events.clone()),
+        gateway_checks: GatewayChecks::default(),
+    };
+    let operations = operations_router(state.operations.clone());
     tokio::spawn(async move {
         loop {
             match changes.recv().await {
                 Ok(message) => {
-                    if let Err(e) = events.send(Event::NetworkChange { change: message }) {
+                    let event = match message {
+                        NetworkChange::ConnectionFailed { id, reason } => {
+                            Event::NetworkConnectionError { id, reason }
+                        }
+                        NetworkChange::CheckpointRolledBack => Event::ConfigReset {
+                            service: "network".to_string(),
+                        },
+                        change => Event::NetworkChange { change },
+                    };
+                    if let Err(e) = events.send(event) {
                         eprintln!("Could not send the event: {}", e);
                     }
                 }
@@ -110,11 +163,18 @@ pub async fn network_service<T: Adapter + Send + Sync + 'static>(
         }
     });
 
-    let state = NetworkServiceState { network: client };
-
-    Ok(Router::new()
+    Router::new()
         .route("/state", get(general_state).put(update_general_state))
-        .route("/connections", get(connections).post(add_connection))
+        .route(
+            "/connections",
+            get(connections).post(add_connection).put(set_connections),
+        )
+        .route(
+            "/connections/import",
+            generous_timeout(post(import_connections)),
+        )
+        .route("/connections/copy_live", post(copy_live_connections))
+        .route("/connections/export", get(export_connections))
         .route(
             "/connections/:id",
             delete(delete_connection)
@@ -123,10 +183,19 @@ pub async fn network_service<T: Adapter + Send + Sync + 'static>(
         )
         .route("/connections/:id/connect", patch(connect))
         .route("/connections/:id/disconnect", patch(disconnect))
+        .route("/connections/:id/autoconnect", patch(set_autoconnect))
+        .route("/connections/:id/state", get(connection_state))
         .route("/devices", get(devices))
-        .route("/system/apply", post(apply))
+        .route("/system/apply", generous_timeout(post(apply)))
+        .route("/reset", post(reset))
         .route("/wifi", get(wifi_networks))
-        .with_state(state))
+        .route("/connectivity", get(connectivity))
+        .route("/dns", get(dns_configuration))
+        .route("/checkpoint", get(checkpoint).post(create_checkpoint))
+        .route("/checkpoint/confirm", post(confirm_checkpoint))
+        .route("/checkpoint/rollback", post(rollback_checkpoint))
+        .nest("/operations", operations)
+        .with_state(state)
 }
 
 #[utoipa::path(
@@ -185,6 +254,233 @@ async fn wifi_networks(
     Ok(Json(networks))
 }
 
+/// Default time (in seconds) to wait for the active connectivity check.
+const CONNECTIVITY_TIMEOUT: u64 = 10;
+
+/// Maximum time (in seconds) to wait for the active connectivity check.
+const MAX_CONNECTIVITY_TIMEOUT: u64 = 60;
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct ConnectivityParams {
+    /// URL to fetch as an active connectivity check. If it is not given, only the state reported
+    /// by NetworkManager is checked.
+    url: Option<String>,
+    /// Maximum time (in seconds) to wait for the URL, between 1 and 60. Defaults to 10 seconds.
+    timeout: Option<u64>,
+}
+
+/// Result of a single connectivity check.
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct ConnectivityCheck {
+    /// Check name ("networkManager" or "url").
+    name: String,
+    /// Whether the check succeeded.
+    success: bool,
+    /// Checked URL, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    /// Reason of the failure, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Connectivity state and the result of each check.
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct Connectivity {
+    /// State reported by NetworkManager.
+    state: ConnectivityState,
+    checks: Vec<ConnectivityCheck>,
+}
+
+/// Default time (in seconds) before rolling back a checkpoint.
+const CHECKPOINT_TIMEOUT: u32 = 60;
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CheckpointParams {
+    /// Seconds to wait for the confirmation before rolling back the changes (60 by default). It
+    /// must be at least 1.
+    timeout: Option<u32>,
+}
+
+/// Creates a checkpoint of the network configuration.
+///
+/// The changes applied afterwards are rolled back automatically unless the checkpoint is
+/// confirmed before the timeout expires. When that happens, the configuration is read from the
+/// system again and a `ConfigReset` event is emitted.
+#[utoipa::path(
+    post,
+    path = "/checkpoint",
+    context_path = "/api/network",
+    request_body = CheckpointParams,
+    responses(
+      (status = 200, description = "The checkpoint was created", body = CheckpointStatus),
+      (status = 400, description = "There is a pending checkpoint, the timeout is 0 or it could not be created")
+    )
+)]
+async fn create_checkpoint(
+    State(state): State<NetworkServiceState>,
+    Json(params): Json<CheckpointParams>,
+) -> Result<Json<CheckpointStatus>, NetworkError> {
+    let timeout = params.timeout.unwrap_or(CHECKPOINT_TIMEOUT);
+    let status = state.network.create_checkpoint(timeout).await?;
+    Ok(Json(status))
+}
+
+/// Returns the pending checkpoint, including the remaining time before the rollback.
+#[utoipa::path(
+    get,
+    path = "/checkpoint",
+    context_path = "/api/network",
+    responses(
+      (status = 200, description = "Pending checkpoint (null if there is none)", body = Option<CheckpointStatus>)
+    )
+)]
+async fn checkpoint(
+    State(state): State<NetworkServiceState>,
+) -> Result<Json<Option<CheckpointStatus>>, NetworkError> {
+    Ok(Json(state.network.get_checkpoint().await?))
+}
+
+/// Confirms the pending checkpoint, keeping the changes.
+#[utoipa::path(
+    post,
+    path = "/checkpoint/confirm",
+    context_path = "/api/network",
+    responses(
+      (status = 204, description = "The changes were confirmed"),
+      (status = 400, description = "There is no pending checkpoint")
+    )
+)]
+async fn confirm_checkpoint(
+    State(state): State<NetworkServiceState>,
+) -> Result<impl IntoResponse, NetworkError> {
+    state.network.confirm_checkpoint().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Rolls back the changes done after creating the pending checkpoint.
+///
+/// The configuration is read from the system again and a `ConfigReset` event is emitted.
+#[utoipa::path(
+    post,
+    path = "/checkpoint/rollback",
+    context_path = "/api/network",
+    responses(
+      (status = 204, description = "The changes were rolled back"),
+      (status = 400, description = "There is no pending checkpoint")
+    )
+)]
+async fn rollback_checkpoint(
+    State(state): State<NetworkServiceState>,
+) -> Result<impl IntoResponse, NetworkError> {
+    state.network.rollback_checkpoint().await?;
+    _ = state.events.send(Event::ConfigReset {
+        service: "network".to_string(),
+    });
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Checks the connectivity of the system.
+///
+/// It reports the NetworkManager connectivity state and, optionally, whether the given URL can
+/// be reached.
+#[utoipa::path(
+    get,
+    path = "/connectivity",
+    context_path = "/api/network",
+    params(ConnectivityParams),
+    responses(
+      (status = 200, description = "Connectivity checks results", body = Connectivity),
+      (status = 400, description = "Invalid timeout")
+    )
+)]
+async fn connectivity(
+    State(state): State<NetworkServiceState>,
+    Query(params): Query<ConnectivityParams>,
+) -> Result<Json<Connectivity>, NetworkError> {
+    let timeout = params.timeout.unwrap_or(CONNECTIVITY_TIMEOUT);
+    if timeout == 0 || timeout > MAX_CONNECTIVITY_TIMEOUT {
+        return Err(NetworkStateError::InvalidConnectivityTimeout(
+            timeout,
+            MAX_CONNECTIVITY_TIMEOUT,
+        )
+        .into());
+    }
+
+    let connectivity = state.network.get_connectivity().await?;
+    let mut checks = vec![ConnectivityCheck {
+        name: "networkManager".to_string(),
+        success: connectivity == ConnectivityState::Full,
+        url: None,
+        error: match connectivity {
+            ConnectivityState::Full => None,
+            ConnectivityState::Unknown => Some("The connectivity state is unknown".to_string()),
+            ConnectivityState::None => Some("Not connected to any network".to_string()),
+            ConnectivityState::Portal => Some("Behind a captive portal".to_string()),
+            ConnectivityState::Limited => Some("The internet cannot be reached".to_string()),
+        },
+    }];
+
+    if let Some(url) = params.url {
+        checks.push(check_url(url, timeout).await);
+    }
+
+    Ok(Json(Connectivity {
+        state: connectivity,
+        checks,
+    }))
+}
+
+/// Fetches the given URL to check whether it can be reached.
+///
+/// * `url`: URL to fetch.
+/// * `timeout`: maximum time (in seconds) to wait for the response.
+async fn check_url(url: String, timeout: u64) -> ConnectivityCheck {
+    // go through the same proxy as the repositories
+    let result = match http_client(std::time::Duration::from_secs(timeout)) {
+        Ok(client) => client.get(&url).send().await,
+        Err(error) => Err(error),
+    };
+    let error = match result {
+        Ok(response) if response.status().is_success() => None,
+        Ok(response) => Some(format!("Unexpected HTTP status: {}", response.status())),
+        Err(error) => Some(error.to_string()),
+    };
+
+    ConnectivityCheck {
+        name: "url".to_string(),
+        success: error.is_none(),
+        url: Some(url),
+        error,
+    }
+}
+
+/// Returns the DNS configuration in use, from the most to the least preferred entry.
+///
+/// It reflects the resolver order computed by NetworkManager according to the DNS priority of
+/// the connections (the lower, the more preferred).
+#[utoipa::path(
+    get,
+    path = "/dns",
+    context_path = "/api/network",
+    responses(
+      (status = 200, description = "DNS configuration in use", body = Vec<DnsEntry>)
+    )
+)]
+async fn dns_configuration(
+    State(state): State<NetworkServiceState>,
+) -> Result<Json<Vec<DnsEntry>>, NetworkError> {
+    let mut entries = state.network.get_dns_configuration().await?;
+    let connections = state.network.get_connections().await?;
+    for entry in entries.iter_mut() {
+        entry.connection = connections
+            .iter()
+            .find(|c| c.interface.is_some() && c.interface == entry.interface)
+            .map(|c| c.id.clone());
+    }
+    Ok(Json(entries))
+}
+
 #[utoipa::path(
     get,
     path = "/devices",
@@ -211,12 +507,7 @@ async fn connections(
     State(state): State<NetworkServiceState>,
 ) -> Result<Json<Vec<NetworkConnection>>, NetworkError> {
     let connections = state.network.get_connections().await?;
-    let connections = connections
-        .iter()
-        .map(|c| NetworkConnection::try_from(c.clone()).unwrap())
-        .collect();
-
-    Ok(Json(connections))
+    Ok(Json(to_settings(&connections)?))
 }
 
 #[utoipa::path(
@@ -231,16 +522,296 @@ async fn add_connection(
     State(state): State<NetworkServiceState>,
     Json(conn): Json<NetworkConnection>,
 ) -> Result<Json<Connection>, NetworkError> {
-    let conn = Connection::try_from(conn)?;
+    let existing = state.network.get_connections().await?;
+    let settings = with_resolved_parent(conn, &[], &existing);
+    let conn = Connection::try_from(settings.clone())?;
     let id = conn.id.clone();
 
     state.network.add_connection(conn).await?;
+    set_ports(&state, &[settings]).await?;
     match state.network.get_connection(&id).await? {
         None => Err(NetworkError::CannotAddConnection(id.clone())),
         Some(conn) => Ok(Json(conn)),
     }
 }
 
+/// What was done with a connection of a batch.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum BatchAction {
+    Added,
+    Updated,
+}
+
+/// Result of adding or updating a connection of a batch.
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+pub struct BatchResult {
+    /// Connection ID.
+    id: String,
+    action: BatchAction,
+}
+
+/// Returns the list of problems found in a set of connections.
+///
+/// The IDs must be unique and two connections cannot be bound to the same interface or MAC
+/// address.
+///
+/// * `connections`: connections to check.
+fn validate_connections(connections: &[NetworkConnection]) -> Vec<String> {
+    let mut issues = vec![];
+    let mut ids: Vec<&str> = vec![];
+    let mut interfaces: BTreeMap<&str, &str> = BTreeMap::new();
+    let mut macs: BTreeMap<String, &str> = BTreeMap::new();
+
+    for conn in connections {
+        if ids.contains(&conn.id.as_str()) {
+            issues.push(format!("Duplicated connection ID: '{}'", conn.id));
+            continue;
+        }
+        ids.push(&conn.id);
+
+        if let Some(interface) = conn.interface.as_deref().filter(|i| !i.is_empty()) {
+            if let Some(other) = interfaces.insert(interface, &conn.id) {
+                issues.push(format!(
+                    "Connections '{}' and '{}' are bound to the same interface '{}'",
+                    other, conn.id, interface
+                ));
+            }
+        }
+        // only the actual addresses, not the special values like "preserve"
+        if let Some(mac) = conn.mac_address.as_deref().filter(|m| m.contains(':')) {
+            if let Some(other) = macs.insert(mac.to_lowercase(), &conn.id) {
+                issues.push(format!(
+                    "Connections '{}' and '{}' are bound to the same MAC address '{}'",
+                    other, conn.id, mac
+                ));
+            }
+        }
+    }
+    issues
+}
+
+/// Adds or updates a set of connections as a unit.
+///
+/// The whole set is validated before changing anything: the IDs must be unique and two
+/// connections cannot be bound to the same interface or MAC address. Existing connections with
+/// the same ID are replaced. Other connections are not affected.
+#[utoipa::path(
+    put,
+    path = "/connections",
+    context_path = "/api/network",
+    request_body = Vec<NetworkConnection>,
+    responses(
+      (status = 200, description = "What was done with each connection", body = Vec<BatchResult>),
+      (status = 422, description = "The set of connections is not valid", body = Vec<String>)
+    )
+)]
+async fn set_connections(
+    State(state): State<NetworkServiceState>,
+    Json(conns): Json<Vec<NetworkConnection>>,
+) -> Result<Response, NetworkError> {
+    let existing = state.network.get_connections().await?;
+    let devices = state.network.get_devices().await?;
+    let known: Vec<String> = existing
+        .iter()
+        .flat_map(|c| std::iter::once(c.id.clone()).chain(c.interface.clone()))
+        .chain(devices.into_iter().map(|d| d.name))
+        .collect();
+
+    let mut issues = validate_connections(&conns);
+    issues.extend(validate_stacks(&conns, &known));
+    let mut connections = Vec::with_capacity(conns.len());
+    for conn in &conns {
+        let id = conn.id.clone();
+        match Connection::try_from(with_resolved_parent(conn.clone(), &conns, &existing)) {
+            Ok(conn) => connections.push(conn),
+            Err(error) => issues.push(format!("Invalid connection '{}': {}", id, error)),
+        }
+    }
+    if !issues.is_empty() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(issues)).into_response());
+    }
+
+    let ids: Vec<String> = connections.iter().map(|c| c.id.clone()).collect();
+    let added = state.network.set_connections(connections).await?;
+    set_ports(&state, &conns).await?;
+    let results: Vec<BatchResult> = ids
+        .into_iter()
+        .zip(added)
+        .map(|(id, added)| BatchResult {
+            id,
+            action: if added {
+                BatchAction::Added
+            } else {
+                BatchAction::Updated
+            },
+        })
+        .collect();
+    Ok(Json(results).into_response())
+}
+
+/// Replaces the parent of a VLAN connection with the interface it references.
+///
+/// * `conn`: connection to add or update.
+/// * `conns`: other connections added at the same time.
+/// * `existing`: existing connections.
+fn with_resolved_parent(
+    mut conn: NetworkConnection,
+    conns: &[NetworkConnection],
+    existing: &[Connection],
+) -> NetworkConnection {
+    if conn.vlan.is_some() {
+        conn.parent = conn
+            .parent
+            .as_deref()
+            .map(|p| resolve_parent(p, conns, existing));
+    }
+    conn
+}
+
+/// Sets the ports of the bonds and the bridges, once all the connections are in the state.
+///
+/// An empty list keeps the current ports, so a bond defined only by its mode does not lose them.
+///
+/// * `conns`: added or updated connections.
+async fn set_ports(
+    state: &NetworkServiceState,
+    conns: &[NetworkConnection],
+) -> Result<(), NetworkError> {
+    for conn in conns {
+        let Some(ports) = conn.ports().filter(|p| !p.is_empty()) else {
+            continue;
+        };
+        let controller = state
+            .network
+            .get_connection(&conn.id)
+            .await?
+            .ok_or_else(|| NetworkError::UnknownConnection(conn.id.clone()))?;
+        state
+            .network
+            .set_ports(controller.uuid, ports.to_vec())
+            .await?;
+    }
+    Ok(())
+}
+
+/// NetworkManager keyfiles to import.
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct ImportParams {
+    /// Contents of the `.nmconnection` files.
+    keyfiles: Vec<String>,
+}
+
+/// Imports connections from NetworkManager keyfiles.
+///
+/// All the keyfiles are parsed before importing any of them. Existing connections with the
+/// same ID are replaced. It returns the IDs of the imported connections.
+#[utoipa::path(
+    post,
+    path = "/connections/import",
+    context_path = "/api/network",
+    request_body = ImportParams,
+    responses(
+      (status = 200, description = "IDs of the imported connections", body = Vec<String>),
+      (status = 422, description = "Some keyfile could not be parsed")
+    )
+)]
+async fn import_connections(
+    State(state): State<NetworkServiceState>,
+    Json(params): Json<ImportParams>,
+) -> Result<Response, NetworkError> {
+    let mut connections = Vec::with_capacity(params.keyfiles.len());
+    for (index, keyfile) in params.keyfiles.iter().enumerate() {
+        match keyfile_to_connection(keyfile) {
+            Ok(conn) => connections.push(conn),
+            Err(error) => {
+                let body = json!({
+                    "error": error.to_string(),
+                    "keyfile": index,
+                    "line": error.line()
+                });
+                return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response());
+            }
+        }
+    }
+
+    let mut ids = vec![];
+    for conn in connections {
+        let id = conn.id.clone();
+        if state.network.get_connection(&id).await?.is_some() {
+            state.network.update_connection(conn).await?;
+        } else {
+            state.network.add_connection(conn).await?;
+        }
+        ids.push(id);
+    }
+    Ok(Json(ids).into_response())
+}
+
+/// Copies the connections which are active in the live system into the configuration.
+///
+/// The connections are copied as they are (e.g., a connection configured by DHCP or manually
+/// before starting the installation), replacing the ones with the same ID. They are written to
+/// the system when the configuration is applied and, as the rest of the connections, they are
+/// copied to the installed system.
+#[utoipa::path(
+    post,
+    path = "/connections/copy_live",
+    context_path = "/api/network",
+    responses(
+      (status = 200, description = "IDs of the copied connections", body = Vec<String>)
+    )
+)]
+async fn copy_live_connections(
+    State(state): State<NetworkServiceState>,
+) -> Result<Json<Vec<String>>, NetworkError> {
+    Ok(Json(state.network.copy_live_connections().await?))
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct ExportParams {
+    /// Whether to mask the secrets (passwords, keys, etc.). Enabled by default.
+    #[serde(default = "default_redact")]
+    redact: bool,
+}
+
+fn default_redact() -> bool {
+    true
+}
+
+/// Exports the connections as NetworkManager keyfiles.
+///
+/// It returns a map where the keys are the file names and the values are the keyfiles.
+#[utoipa::path(
+    get,
+    path = "/connections/export",
+    context_path = "/api/network",
+    params(ExportParams),
+    responses(
+      (status = 200, description = "Keyfiles indexed by file name", body = BTreeMap<String, String>)
+    )
+)]
+async fn export_connections(
+    State(state): State<NetworkServiceState>,
+    Query(params): Query<ExportParams>,
+) -> Result<Json<BTreeMap<String, String>>, NetworkError> {
+    let connections = state.network.get_connections().await?;
+    let keyfiles = connections
+        .iter()
+        .filter(|c| c.status != Status::Removed)
+        .map(|conn| {
+            let controller = conn
+                .controller
+                .and_then(|uuid| connections.iter().find(|c| c.uuid == uuid));
+            (
+                keyfile_name(&conn.id),
+                connection_to_keyfile(conn, controller, params.redact),
+            )
+        })
+        .collect();
+    Ok(Json(keyfiles))
+}
+
 #[utoipa::path(
     get,
     path = "/network/connections/:id",
@@ -252,14 +823,12 @@ async fn connection(
     State(state): State<NetworkServiceState>,
     Path(id): Path<String>,
 ) -> Result<Json<NetworkConnection>, NetworkError> {
-    let conn = state
-        .network
-        .get_connection(&id)
-        .await?
+    let connections = state.network.get_connections().await?;
+    let conn = to_settings(&connections)?
+        .into_iter()
+        .find(|c| c.id == id)
         .ok_or_else(|| NetworkError::UnknownConnection(id.clone()))?;
 
-    let conn = NetworkConnection::try_from(conn)?;
-
     Ok(Json(conn))
 }
 
@@ -300,7 +869,9 @@ async fn update_connection(
         .get_connection(&id)
         .await?
         .ok_or_else(|| NetworkError::UnknownConnection(id.clone()))?;
-    let mut conn = Connection::try_from(conn)?;
+    let existing = state.network.get_connections().await?;
+    let settings = with_resolved_parent(conn, &[], &existing);
+    let mut conn = Connection::try_from(settings.clone())?;
     if orig_conn.id != id {
         // FIXME: why?
         return Err(NetworkError::UnknownConnection(id));
@@ -309,6 +880,7 @@ async fn update_connection(
     }
 
     state.network.update_connection(conn).await?;
+    set_ports(&state, &[settings]).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -376,22 +948,273 @@ async fn disconnect(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct AutoconnectParams {
+    /// Whether to activate the connection automatically.
+    enabled: bool,
+}
+
+#[utoipa::path(
+    patch,
+    path = "/connections/:id/autoconnect",
+    context_path = "/api/network",
+    request_body = AutoconnectParams,
+    responses(
+      (status = 204, description = "Set whether the connection is activated automatically")
+    )
+)]
+async fn set_autoconnect(
+    State(state): State<NetworkServiceState>,
+    Path(id): Path<String>,
+    Json(params): Json<AutoconnectParams>,
+) -> Result<impl IntoResponse, NetworkError> {
+    let Some(mut conn) = state.network.get_connection(&id).await? else {
+        return Err(NetworkError::UnknownConnection(id));
+    };
+    conn.autoconnect = params.enabled;
+
+    state
+        .network
+        .update_connection(conn)
+        .await
+        .map_err(|_| NetworkError::CannotApplyConfig)?;
+
+    state
+        .network
+        .apply()
+        .await
+        .map_err(|_| NetworkError::CannotApplyConfig)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Configured and current state of a connection.
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct ConnectionState {
+    /// Configured status (up or down).
+    status: Status,
+    /// Whether the connection is activated automatically.
+    autoconnect: bool,
+    /// Whether the connection is currently active on any device.
+    active: bool,
+    /// Reason why the last activation failed, if it is the case.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// Result of the gateway check, if it is enabled and the connection is active. The check runs
+    /// once after the activation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gateway: Option<GatewayCheck>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/connections/:id/state",
+    context_path = "/api/network",
+    responses(
+      (status = 200, description = "Connection state", body = ConnectionState)
+    )
+)]
+async fn connection_state(
+    State(state): State<NetworkServiceState>,
+    Path(id): Path<String>,
+) -> Result<Json<ConnectionState>, NetworkError> {
+    let Some(conn) = state.network.get_connection(&id).await? else {
+        return Err(NetworkError::UnknownConnection(id));
+    };
+    let devices = state.network.get_devices().await?;
+    let devices: Vec<_> = devices
+        .iter()
+        .filter(|d| d.connection.as_deref() == Some(conn.id.as_str()))
+        .collect();
+    let active = devices.iter().any(|d| d.state == DeviceState::Activated);
+    let error = devices.iter().find_map(|d| d.failure_reason());
+    let gateway = match devices.iter().find(|d| d.state == DeviceState::Activated) {
+        Some(device) => state.gateway_checks.get(&conn, device),
+        None => {
+            state.gateway_checks.reset(&conn.id);
+            None
+        }
+    };
+
+    Ok(Json(ConnectionState {
+        status: conn.status,
+        autoconnect: conn.autoconnect,
+        active,
+        error,
+        gateway,
+    }))
+}
+
 #[utoipa::path(
     post,
     path = "/system/apply",
     context_path = "/api/network",
+    params(ApplyParams),
     responses(
-      (status = 204, description = "Apply configuration")
+      (status = 204, description = "Apply configuration"),
+      (status = 200, description = "The configuration was applied", body = crate::web::common::Operation),
+      (status = 202, description = "The configuration is being applied", body = crate::web::common::Operation)
     )
 )]
 async fn apply(
     State(state): State<NetworkServiceState>,
+    Query(params): Query<ApplyParams>,
+) -> Result<Response, NetworkError> {
+    if params.wait.is_none() {
+        state
+            .network
+            .apply()
+            .await
+            .map_err(|_| NetworkError::CannotApplyConfig)?;
+        return Ok(StatusCode::NO_CONTENT.into_response());
+    }
+
+    let network = state.network.clone();
+    let response = state
+        .operations
+        .run(&params, async move {
+            network
+                .apply()
+                .await
+                .map_err(|_| NetworkError::CannotApplyConfig.to_string())?;
+            let connections: Vec<NetworkConnection> = network
+                .get_connections()
+                .await
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .filter_map(|c| NetworkConnection::try_from(c).ok())
+                .collect();
+            serde_json::to_value(connections).map_err(|e| e.to_string())
+        })
+        .await;
+    Ok(response)
+}
+
+/// Discards the network configuration changes.
+///
+/// The configuration is read from the system again and a `ConfigReset` event is emitted.
+#[utoipa::path(
+    post,
+    path = "/reset",
+    context_path = "/api/network",
+    operation_id = "reset_network_config",
+    responses(
+      (status = 204, description = "The configuration was reset")
+    )
+)]
+async fn reset(
+    State(state): State<NetworkServiceState>,
 ) -> Result<impl IntoResponse, NetworkError> {
     state
         .network
-        .apply()
+        .reset()
         .await
-        .map_err(|_| NetworkError::CannotApplyConfig)?;
+        .map_err(|_| NetworkError::CannotReset)?;
 
+    _ = state.events.send(Event::ConfigReset {
+        service: "network".to_string(),
+    });
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Applies the network configuration to the system.
+pub struct NetworkCommit {
+    network: NetworkSystemClient,
+}
+
+impl NetworkCommit {
+    pub fn new(network: NetworkSystemClient) -> Self {
+        Self { network }
+    }
+}
+
+#[async_trait]
+impl CommitProvider for NetworkCommit {
+    async fn commit(&self) -> Result<(), Error> {
+        self.network
+            .apply()
+            .await
+            .map_err(|e| Error::Anyhow(e.to_string()))
+    }
+}
+
+/// Reports the general network state and the connections.
+pub struct NetworkSnapshot {
+    network: NetworkSystemClient,
+}
+
+impl NetworkSnapshot {
+    pub fn new(network: NetworkSystemClient) -> Self {
+        Self { network }
+    }
+}
+
+#[async_trait]
+impl SnapshotProvider for NetworkSnapshot {
+    async fn snapshot(&self) -> Result<serde_json::Value, Error> {
+        let state = self
+            .network
+            .get_state()
+            .await
+            .map_err(|e| Error::Anyhow(e.to_string()))?;
+        let connections: Vec<NetworkConnection> = self
+            .network
+            .get_connections()
+            .await
+            .map_err(|e| Error::Anyhow(e.to_string()))?
+            .into_iter()
+            .filter_map(|c| NetworkConnection::try_from(c).ok())
+            .collect();
+        Ok(json!({ "state": state, "connections": connections }))
+    }
+}
+
+/// Reports whether the network connectivity required by the installation is missing.
+///
+/// The registration of the product is the only step which requires network connectivity: the
+/// remaining network issues are just warnings.
+pub struct NetworkReadiness<'a> {
+    network: NetworkSystemClient,
+    product: ProductClient<'a>,
+}
+
+impl<'a> NetworkReadiness<'a> {
+    pub async fn new(
+        network: NetworkSystemClient,
+        dbus: zbus::Connection,
+    ) -> Result<NetworkReadiness<'a>, ServiceError> {
+        Ok(Self {
+            network,
+            product: ProductClient::new(dbus).await?,
+        })
+    }
+}
+
+#[async_trait]
+impl<'a> ReadinessProvider for NetworkReadiness<'a> {
+    async fn issues(&self) -> Result<Vec<ReadinessIssue>, Error> {
+        let connectivity = self
+            .network
+            .get_connectivity()
+            .await
+            .map_err(|e| Error::Anyhow(e.to_string()))?;
+        if connectivity == ConnectivityState::Full {
+            return Ok(vec![]);
+        }
+
+        let requirement = self.product.registration_requirement().await?;
+        let registered = !self.product.registration_code().await?.is_empty();
+        let issue = if matches!(requirement, RegistrationRequirement::Mandatory) && !registered {
+            ReadinessIssue::blocking(
+                "required",
+                "The product must be registered, which requires network connectivity",
+            )
+        } else {
+            ReadinessIssue::warning(
+                "offline",
+                "There is no network connectivity, so only the local repositories can be used",
+            )
+        };
+        Ok(vec![issue])
+    }
+}
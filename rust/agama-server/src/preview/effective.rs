@@ -0,0 +1,196 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Helpers to build the effective configuration, which merges the configuration of all the
+//! modules in a single document.
+
+use agama_lib::{network::settings::NetworkConnection, users::FirstUser};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Encryption methods of the storage configuration which hold a password.
+const ENCRYPTION_METHODS: [&str; 3] = ["luks1", "luks2", "pervasiveLuks2"];
+
+/// Value replacing the secrets.
+pub const REDACTED: &str = "<redacted>";
+
+/// Options to read the effective configuration.
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveConfigParams {
+    /// Whether to include the secrets (e.g., passwords). They are redacted by default.
+    #[serde(default)]
+    pub include_secrets: bool,
+}
+
+/// Replaces the password of the first user with [REDACTED].
+///
+/// * `user`: first user.
+pub fn redact_first_user(user: &mut FirstUser) {
+    redact(&mut user.password);
+}
+
+/// Replaces the passwords of a network connection (wireless and 802.1X) with [REDACTED].
+///
+/// * `conn`: network connection.
+pub fn redact_connection(conn: &mut NetworkConnection) {
+    if let Some(wireless) = conn.wireless.as_mut() {
+        redact_option(&mut wireless.password);
+    }
+    if let Some(ieee_8021x) = conn.ieee_8021x.as_mut() {
+        redact_option(&mut ieee_8021x.password);
+        redact_option(&mut ieee_8021x.ca_cert_password);
+        redact_option(&mut ieee_8021x.client_cert_password);
+        redact_option(&mut ieee_8021x.private_key_password);
+    }
+}
+
+/// Replaces the encryption passwords of the storage configuration with [REDACTED].
+///
+/// The storage configuration is an opaque document, so it looks for the `encryption` objects
+/// of the profile schema: the password is either directly in them or in the object of the
+/// encryption method (e.g., `luks2`).
+///
+/// * `value`: storage configuration.
+pub fn redact_storage(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if key == "encryption" {
+                    redact_encryption(value);
+                } else {
+                    redact_storage(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact_storage),
+        _ => {}
+    }
+}
+
+fn redact_encryption(encryption: &mut Value) {
+    let Value::Object(map) = encryption else {
+        return;
+    };
+    redact_value(map.get_mut("password"));
+    for method in ENCRYPTION_METHODS {
+        if let Some(Value::Object(settings)) = map.get_mut(method) {
+            redact_value(settings.get_mut("password"));
+        }
+    }
+}
+
+/// Replaces a secret with [REDACTED].
+///
+/// Only the non-empty secrets are replaced, so it is still possible to tell whether a secret is
+/// set.
+fn redact(secret: &mut String) {
+    if !secret.is_empty() {
+        *secret = REDACTED.to_string();
+    }
+}
+
+fn redact_option(secret: &mut Option<String>) {
+    if let Some(secret) = secret.as_mut() {
+        redact(secret);
+    }
+}
+
+fn redact_value(secret: Option<&mut Value>) {
+    if let Some(Value::String(secret)) = secret {
+        redact(secret);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agama_lib::network::settings::WirelessSettings;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_first_user() {
+        let mut user = FirstUser {
+            user_name: "jane".to_string(),
+            password: "secret".to_string(),
+            ..Default::default()
+        };
+        redact_first_user(&mut user);
+        assert_eq!(user.password, REDACTED);
+
+        let mut user = FirstUser::default();
+        redact_first_user(&mut user);
+        assert_eq!(user.password, "");
+    }
+
+    #[test]
+    fn test_redact_connection() {
+        let mut conn = NetworkConnection {
+            id: "wlan".to_string(),
+            wireless: Some(WirelessSettings {
+                password: Some("secret".to_string()),
+                ..Default::default()
+            }),
+            ieee_8021x: serde_json::from_value(
+                json!({ "eap": ["peap"], "identity": "jane", "password": "secret" }),
+            )
+            .unwrap(),
+            ..Default::default()
+        };
+        redact_connection(&mut conn);
+        assert_eq!(conn.wireless.unwrap().password.as_deref(), Some(REDACTED));
+        let ieee_8021x = conn.ieee_8021x.unwrap();
+        assert_eq!(ieee_8021x.password.as_deref(), Some(REDACTED));
+        assert_eq!(ieee_8021x.identity.as_deref(), Some("jane"));
+        assert_eq!(ieee_8021x.ca_cert_password, None);
+    }
+
+    #[test]
+    fn test_redact_storage() {
+        let mut config = json!({
+            "storage": {
+                "drives": [{
+                    "partitions": [
+                        { "encryption": { "luks2": { "password": "luks", "label": "root" } } },
+                        { "encryption": { "luks1": { "password": "" } } },
+                        { "filesystem": { "path": "/home", "label": "password" } }
+                    ]
+                }],
+                "guided": { "encryption": { "password": "luks", "method": "luks2" } }
+            }
+        });
+        redact_storage(&mut config);
+        assert_eq!(
+            config,
+            json!({
+                "storage": {
+                    "drives": [{
+                        "partitions": [
+                            { "encryption": { "luks2": { "password": REDACTED, "label": "root" } } },
+                            { "encryption": { "luks1": { "password": "" } } },
+                            { "filesystem": { "path": "/home", "label": "password" } }
+                        ]
+                    }],
+                    "guided": { "encryption": { "password": REDACTED, "method": "luks2" } }
+                }
+            })
+        );
+    }
+}
@@ -0,0 +1,563 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Renders the configuration files that Agama writes to the target system.
+
+use crate::network::keyfile_name;
+use agama_lib::{
+    bootloader::model::BootloaderType,
+    storage::model::{Device, Filesystem, MountBy, VolumeQuota, PROJECT_QUOTA_OPTION},
+};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+};
+
+/// Directory where the target system is mounted during the installation.
+pub const TARGET_ROOT: &str = "/mnt";
+
+/// Configuration file to be written to the target system.
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+pub struct GeneratedFile {
+    /// Service which generates the file.
+    pub service: String,
+    /// Content of the file.
+    pub content: String,
+    /// Whether the file already exists in the target system and it would be overwritten. It is
+    /// unknown (`null`) until the target system is mounted.
+    pub overwrite: Option<bool>,
+}
+
+/// Collection of generated files indexed by their path in the target system.
+#[derive(Debug)]
+pub struct ConfigPreview {
+    root: Option<PathBuf>,
+    files: BTreeMap<String, GeneratedFile>,
+}
+
+impl ConfigPreview {
+    /// Creates an empty preview.
+    ///
+    /// * `root`: directory where the target system is mounted, if it is already mounted (see
+    ///   [mounted_target]).
+    pub fn new(root: Option<&Path>) -> Self {
+        Self {
+            root: root.map(Path::to_path_buf),
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a file to the preview.
+    ///
+    /// * `service`: service which generates the file.
+    /// * `path`: absolute path in the target system.
+    /// * `content`: content of the file.
+    pub fn add(&mut self, service: &str, path: &str, content: String) {
+        let overwrite = self
+            .root
+            .as_ref()
+            .map(|root| root.join(path.trim_start_matches('/')).exists());
+        let file = GeneratedFile {
+            service: service.to_string(),
+            content,
+            overwrite,
+        };
+        self.files.insert(path.to_string(), file);
+    }
+
+    /// Returns the generated files indexed by their path.
+    pub fn files(self) -> BTreeMap<String, GeneratedFile> {
+        self.files
+    }
+}
+
+/// Returns the directory where the target system is mounted, if it is already mounted.
+///
+/// Before mounting it, checking the files in [TARGET_ROOT] would only find the files of the
+/// installation system.
+pub fn mounted_target() -> Option<&'static Path> {
+    let root = Path::new(TARGET_ROOT);
+    is_mount_point(root).then_some(root)
+}
+
+/// Whether the given directory is a mount point (i.e., it is in a different device than its
+/// parent or it is the root directory).
+fn is_mount_point(dir: &Path) -> bool {
+    let (Ok(metadata), Ok(parent)) = (fs::metadata(dir), fs::metadata(dir.join(".."))) else {
+        return false;
+    };
+    metadata.dev() != parent.dev() || metadata.ino() == parent.ino()
+}
+
+/// Renders the fstab for the file systems of the given devices.
+///
+/// Devices without a mount point are ignored.
+///
+/// * `random_key_swap`: whether swap is encrypted with a random key, so it is mounted through the
+///   device mapper.
+/// * `overlay`: directory keeping the changes if the root file system is read-only. The root file
+///   system is mounted with the `ro` option and an overlay is mounted on top of it.
+/// * `project_quotas`: mount paths of the file systems using XFS project quotas, which are
+///   mounted with the `prjquota` option.
+/// * `mount_by`: returns how the file system mounted at the given path is referenced. The device
+///   name is used if it returns `None`.
+pub fn fstab<F>(
+    devices: &[Device],
+    random_key_swap: bool,
+    overlay: Option<&str>,
+    project_quotas: &[&str],
+    mount_by: F,
+) -> String
+where
+    F: Fn(&str) -> Option<MountBy>,
+{
+    let mut entries: Vec<_> = devices
+        .iter()
+        .filter_map(|d| Some((d, d.filesystem.as_ref()?)))
+        .filter(|(_, fs)| !fs.mount_path.is_empty())
+        .collect();
+    entries.sort_by(|a, b| a.1.mount_path.cmp(&b.1.mount_path));
+
+    let mut fstab: String = entries
+        .iter()
+        .map(|(device, fs)| {
+            let name = if random_key_swap && fs.fs_type == "swap" {
+                format!("/dev/mapper/{}", swap_mapper_name(device))
+            } else {
+                fstab_spec(device, fs, mount_by(&fs.mount_path))
+            };
+            let mut options = if overlay.is_some() && fs.mount_path == "/" {
+                "ro".to_string()
+            } else {
+                "defaults".to_string()
+            };
+            if project_quotas.contains(&fs.mount_path.as_str()) {
+                options.push(',');
+                options.push_str(PROJECT_QUOTA_OPTION);
+            }
+            format!(
+                "{}  {}  {}  {}  0  0\n",
+                name, fs.mount_path, fs.fs_type, options
+            )
+        })
+        .collect();
+
+    // mounted after the file system containing the overlay directory
+    if let Some(overlay) = overlay.map(|o| o.trim_end_matches('/')) {
+        fstab.push_str(&format!(
+            "overlay  /  overlay  lowerdir=/,upperdir={0}/upper,workdir={0}/work,x-systemd.requires-mounts-for={0}  0  0\n",
+            overlay
+        ));
+    }
+    fstab
+}
+
+/// Returns the reference to the file system in the fstab.
+///
+/// It falls back to the device name if the chosen reference is not known (e.g., the file system
+/// has no label). New file systems get their UUID when they are formatted.
+fn fstab_spec(device: &Device, fs: &Filesystem, mount_by: Option<MountBy>) -> String {
+    let block_device = device.block_device.as_ref();
+    let spec = match mount_by {
+        Some(MountBy::Uuid) => Some(format!(
+            "UUID={}",
+            fs.uuid.as_deref().unwrap_or("<generated>")
+        )),
+        Some(MountBy::Label) if !fs.label.is_empty() => Some(format!("LABEL={}", fs.label)),
+        Some(MountBy::Path) => block_device
+            .and_then(|b| b.udev_paths.first())
+            .map(|p| format!("/dev/disk/by-path/{}", p)),
+        Some(MountBy::Id) => block_device
+            .and_then(|b| b.udev_ids.first())
+            .map(|i| format!("/dev/disk/by-id/{}", i)),
+        _ => None,
+    };
+    spec.unwrap_or_else(|| device.device_info.name.clone())
+}
+
+/// Renders the crypttab to encrypt the swap devices with a random key on each boot.
+///
+/// The swap devices are referenced by a persistent name, as the kernel names could change and
+/// the random key would overwrite any other device.
+pub fn crypttab(devices: &[Device]) -> String {
+    devices
+        .iter()
+        .filter(|d| d.filesystem.as_ref().is_some_and(|fs| fs.fs_type == "swap"))
+        .map(|d| {
+            format!(
+                "{}  {}  /dev/urandom  swap\n",
+                swap_mapper_name(d),
+                persistent_name(d)
+            )
+        })
+        .collect()
+}
+
+/// Renders the /etc/projects and /etc/projid files defining the XFS projects of the quotas.
+///
+/// The projects are numbered in order, starting at 1, and named after their full path (e.g.,
+/// "home_alice" for "alice" in "/home"). It returns `None` if there are no projects.
+///
+/// * `quotas`: mount path and quotas of the volumes using XFS project quotas.
+pub fn xfs_projects(quotas: &[(&str, &VolumeQuota)]) -> Option<(String, String)> {
+    let paths: Vec<String> = quotas
+        .iter()
+        .flat_map(|(mount_path, quota)| {
+            quota.limits.iter().map(move |l| {
+                format!(
+                    "{}/{}",
+                    mount_path.trim_end_matches('/'),
+                    l.path.trim_matches('/')
+                )
+            })
+        })
+        .collect();
+    if paths.is_empty() {
+        return None;
+    }
+
+    let mut projects = String::new();
+    let mut projid = String::new();
+    for (id, path) in (1..).zip(&paths) {
+        projects.push_str(&format!("{}:{}\n", id, path));
+        projid.push_str(&format!(
+            "{}:{}\n",
+            path.trim_start_matches('/').replace('/', "_"),
+            id
+        ));
+    }
+    Some((projects, projid))
+}
+
+/// Renders the modules-load.d file loading the kernel modules of the cached devices.
+///
+/// It returns `None` if there are no cached devices.
+pub fn cache_modules(devices: &[Device]) -> Option<String> {
+    let mut modules: Vec<&str> = devices
+        .iter()
+        .filter_map(|d| d.cache.as_ref())
+        .map(|c| c.method.kernel_module())
+        .collect();
+    modules.sort();
+    modules.dedup();
+    if modules.is_empty() {
+        return None;
+    }
+    Some(modules.iter().map(|m| format!("{}\n", m)).collect())
+}
+
+/// Persistent name of a device (by-id or by-path), falling back to the kernel name.
+fn persistent_name(device: &Device) -> String {
+    let block_device = device.block_device.as_ref();
+    block_device
+        .and_then(|b| b.udev_ids.first())
+        .map(|i| format!("/dev/disk/by-id/{}", i))
+        .or_else(|| {
+            block_device
+                .and_then(|b| b.udev_paths.first())
+                .map(|p| format!("/dev/disk/by-path/{}", p))
+        })
+        .unwrap_or_else(|| device.device_info.name.clone())
+}
+
+/// Device mapper name for an encrypted swap device (e.g., "cr_ata-QEMU_HARDDISK_QM1-part3"), based
+/// on its persistent name.
+fn swap_mapper_name(device: &Device) -> String {
+    let name = persistent_name(device);
+    let name = name.rsplit('/').next().unwrap_or(&name);
+    format!("cr_{}", name)
+}
+
+/// Renders the locale.conf file.
+///
+/// * `locales`: selected locales; the first one is used as the system locale.
+/// * `categories`: locale of the categories which do not use the system locale.
+pub fn locale_conf(locales: &[String], categories: &BTreeMap<String, String>) -> String {
+    let locale = locales.first().map(String::as_str).unwrap_or("en_US.UTF-8");
+    crate::l10n::locale_conf(locale, categories)
+}
+
+/// Renders the vconsole.conf file.
+pub fn vconsole_conf(keymap: &str) -> String {
+    format!("KEYMAP={}\n", keymap)
+}
+
+/// Path of the bootloader sysconfig file in the target system.
+pub const SYSCONFIG_BOOTLOADER_PATH: &str = "/etc/sysconfig/bootloader";
+
+/// Renders the bootloader sysconfig file, as read by perl-Bootloader.
+///
+/// * `bootloader`: bootloader to install.
+/// * `efi`: whether the system boots using EFI.
+/// * `secure_boot`: whether a Secure Boot capable setup (shim and signed kernel) is installed.
+pub fn sysconfig_bootloader(bootloader: BootloaderType, efi: bool, secure_boot: bool) -> String {
+    let loader_type = match bootloader {
+        BootloaderType::Grub2 if efi => "grub2-efi",
+        BootloaderType::Grub2 => "grub2",
+        BootloaderType::SystemdBoot => "systemd-boot",
+    };
+    let secure_boot = if efi && secure_boot { "yes" } else { "no" };
+    format!(
+        "LOADER_TYPE=\"{}\"\nSECURE_BOOT=\"{}\"\n",
+        loader_type, secure_boot
+    )
+}
+
+/// Returns the path of the NetworkManager keyfile for the given connection ID.
+pub fn keyfile_path(id: &str) -> String {
+    format!(
+        "/etc/NetworkManager/system-connections/{}",
+        keyfile_name(id)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agama_lib::storage::model::{
+        BlockDevice, Cache, CacheMethod, CacheMode, DeviceInfo, Filesystem, QuotaLimit, QuotaType,
+        ShrinkingInfo,
+    };
+    use std::fs;
+
+    fn device(name: &str, fs_type: &str, mount_path: &str) -> Device {
+        Device {
+            device_info: DeviceInfo {
+                sid: 1.into(),
+                name: name.to_string(),
+                description: String::new(),
+            },
+            block_device: None,
+            component: None,
+            drive: None,
+            filesystem: Some(Filesystem {
+                sid: 1.into(),
+                fs_type: fs_type.to_string(),
+                mount_path: mount_path.to_string(),
+                label: String::new(),
+                free_space: None,
+                uuid: None,
+            }),
+            lvm_lv: None,
+            lvm_vg: None,
+            md: None,
+            multipath: None,
+            partition: None,
+            partition_table: None,
+            raid: None,
+            cache: None,
+            contents: None,
+        }
+    }
+
+    #[test]
+    fn test_fstab() {
+        let devices = vec![
+            device("/dev/vda3", "xfs", "/home"),
+            device("/dev/vda2", "btrfs", "/"),
+            device("/dev/vda4", "ext4", ""),
+        ];
+        assert_eq!(
+            fstab(&devices, false, None, &[], |_| None),
+            "/dev/vda2  /  btrfs  defaults  0  0\n/dev/vda3  /home  xfs  defaults  0  0\n"
+        );
+    }
+
+    #[test]
+    fn test_fstab_read_only_root() {
+        let devices = vec![
+            device("/dev/vda2", "btrfs", "/"),
+            device("/dev/vda3", "xfs", "/var"),
+        ];
+        assert_eq!(
+            fstab(&devices, false, Some("/var/lib/overlay"), &[], |_| None),
+            "/dev/vda2  /  btrfs  ro  0  0\n\
+             /dev/vda3  /var  xfs  defaults  0  0\n\
+             overlay  /  overlay  lowerdir=/,upperdir=/var/lib/overlay/upper,workdir=/var/lib/overlay/work,x-systemd.requires-mounts-for=/var/lib/overlay  0  0\n"
+        );
+    }
+
+    #[test]
+    fn test_fstab_mount_by() {
+        let mut root = device("/dev/vda2", "btrfs", "/");
+        root.filesystem.as_mut().unwrap().uuid = Some("1234-abcd".to_string());
+        let mut home = device("/dev/vda3", "xfs", "/home");
+        home.filesystem.as_mut().unwrap().label = "home".to_string();
+        let data = device("/dev/vda4", "ext4", "/data");
+        let devices = vec![root, home, data];
+
+        // "/data" has no label, so it falls back to the device name
+        let fstab = fstab(&devices, false, None, &[], |path| {
+            Some(if path == "/" {
+                MountBy::Uuid
+            } else {
+                MountBy::Label
+            })
+        });
+        assert_eq!(
+            fstab,
+            "UUID=1234-abcd  /  btrfs  defaults  0  0\n\
+             /dev/vda4  /data  ext4  defaults  0  0\n\
+             LABEL=home  /home  xfs  defaults  0  0\n"
+        );
+    }
+
+    #[test]
+    fn test_project_quotas() {
+        let devices = vec![device("/dev/vda3", "xfs", "/home")];
+        assert_eq!(
+            fstab(&devices, false, None, &["/home"], |_| None),
+            "/dev/vda3  /home  xfs  defaults,prjquota  0  0\n"
+        );
+
+        let quota = VolumeQuota {
+            quota_type: QuotaType::Project,
+            limits: vec![
+                QuotaLimit {
+                    path: "alice".to_string(),
+                    size: 1024.into(),
+                },
+                QuotaLimit {
+                    path: "shared/data/".to_string(),
+                    size: 2048.into(),
+                },
+            ],
+        };
+        assert_eq!(xfs_projects(&[]), None);
+        assert_eq!(
+            xfs_projects(&[("/home", &quota)]),
+            Some((
+                "1:/home/alice\n2:/home/shared/data\n".to_string(),
+                "home_alice:1\nhome_shared_data:2\n".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_cache_modules() {
+        let mut bcache = device("/dev/bcache0", "xfs", "/srv");
+        bcache.cache = Some(Cache {
+            method: CacheMethod::Bcache,
+            mode: CacheMode::Writeback,
+            cache_device: 2.into(),
+            backing_device: 3.into(),
+        });
+        let mut devices = vec![device("/dev/vda2", "btrfs", "/")];
+        assert_eq!(cache_modules(&devices), None);
+
+        devices.push(bcache.clone());
+        devices.push(bcache);
+        assert_eq!(cache_modules(&devices), Some("bcache\n".to_string()));
+    }
+
+    #[test]
+    fn test_random_key_swap() {
+        let devices = vec![
+            device("/dev/vda2", "btrfs", "/"),
+            device("/dev/vda3", "swap", "swap"),
+        ];
+        assert_eq!(
+            fstab(&devices, true, None, &[], |_| None),
+            "/dev/vda2  /  btrfs  defaults  0  0\n/dev/mapper/cr_vda3  swap  swap  defaults  0  0\n"
+        );
+        assert_eq!(
+            crypttab(&devices),
+            "cr_vda3  /dev/vda3  /dev/urandom  swap\n"
+        );
+    }
+
+    #[test]
+    fn test_random_key_swap_persistent_name() {
+        let mut swap = device("/dev/vda3", "swap", "swap");
+        swap.block_device = Some(BlockDevice {
+            active: true,
+            encrypted: false,
+            size: 1024.into(),
+            shrinking: ShrinkingInfo::Unsupported(vec![]),
+            start: 0,
+            systems: vec![],
+            udev_ids: vec!["virtio-123-part3".to_string()],
+            udev_paths: vec!["pci-0000:00:04.0-part3".to_string()],
+            logical_sector_size: None,
+            physical_sector_size: None,
+            offset: None,
+        });
+        let devices = vec![swap];
+        assert_eq!(
+            crypttab(&devices),
+            "cr_virtio-123-part3  /dev/disk/by-id/virtio-123-part3  /dev/urandom  swap\n"
+        );
+        assert_eq!(
+            fstab(&devices, true, None, &[], |_| None),
+            "/dev/mapper/cr_virtio-123-part3  swap  swap  defaults  0  0\n"
+        );
+    }
+
+    #[test]
+    fn test_preview_overwrite() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("etc")).unwrap();
+        fs::write(root.path().join("etc/hostname"), "old\n").unwrap();
+
+        let mut preview = ConfigPreview::new(Some(root.path()));
+        preview.add("network", "/etc/hostname", "agama\n".to_string());
+        preview.add(
+            "l10n",
+            "/etc/locale.conf",
+            locale_conf(&[], &BTreeMap::new()),
+        );
+        let files = preview.files();
+
+        assert_eq!(files["/etc/hostname"].overwrite, Some(true));
+        assert_eq!(files["/etc/locale.conf"].overwrite, Some(false));
+        assert_eq!(files["/etc/locale.conf"].content, "LANG=en_US.UTF-8\n");
+
+        let mut preview = ConfigPreview::new(None);
+        preview.add("network", "/etc/hostname", "agama\n".to_string());
+        assert_eq!(preview.files()["/etc/hostname"].overwrite, None);
+    }
+
+    #[test]
+    fn test_is_mount_point() {
+        assert!(is_mount_point(Path::new("/")));
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!is_mount_point(dir.path()));
+        assert!(!is_mount_point(&dir.path().join("missing")));
+    }
+
+    #[test]
+    fn test_sysconfig_bootloader() {
+        assert_eq!(
+            sysconfig_bootloader(BootloaderType::Grub2, true, true),
+            "LOADER_TYPE=\"grub2-efi\"\nSECURE_BOOT=\"yes\"\n"
+        );
+        assert_eq!(
+            sysconfig_bootloader(BootloaderType::Grub2, false, true),
+            "LOADER_TYPE=\"grub2\"\nSECURE_BOOT=\"no\"\n"
+        );
+        assert_eq!(
+            sysconfig_bootloader(BootloaderType::SystemdBoot, true, false),
+            "LOADER_TYPE=\"systemd-boot\"\nSECURE_BOOT=\"no\"\n"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! This module implements the web API for the configuration preview and the effective
+//! configuration.
+//!
+//! Both are built from the current in-memory configuration of each
+//! service and nothing is written to disk.
+
+use super::{
+    effective::{self, EffectiveConfigParams},
+    files::{self, ConfigPreview, GeneratedFile},
+};
+use crate::{
+    error::Error,
+    l10n::{
+        keyboard::{self, KeyboardMapping},
+        ntp,
+    },
+    manager::facts::HardwareFacts,
+    network::{connection_to_keyfile, system::NetworkSystemClient},
+    storage::fstab::FstabEntries,
+};
+use agama_lib::{
+    bootloader::{client::BootloaderClient, model::BootloaderType},
+    error::ServiceError,
+    localization::LocaleProxy,
+    manager::ManagerClient,
+    network::{settings::NetworkConnection, types::Status},
+    product::ProductClient,
+    software::{model::SoftwareConfig, SoftwareClient},
+    storage::{
+        model::{QuotaType, VolumeEncryption, VolumeQuota},
+        StorageClient,
+    },
+    users::{model::RootConfig, UsersClient},
+};
+use anyhow::Context;
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json, Router,
+};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+#[derive(Clone)]
+struct PreviewState<'a> {
+    bootloader: BootloaderClient<'a>,
+    storage: StorageClient<'a>,
+    locale: LocaleProxy<'a>,
+    software: SoftwareClient<'a>,
+    product: ProductClient<'a>,
+    users: UsersClient<'a>,
+    manager: ManagerClient<'a>,
+    network: NetworkSystemClient,
+    fstab: FstabEntries,
+}
+
+/// Sets up and returns the axum service for the configuration preview.
+///
+/// * `dbus`: D-Bus connection.
+/// * `network`: client to read the network configuration.
+/// * `fstab`: additional fstab entries.
+pub async fn preview_service(
+    dbus: zbus::Connection,
+    network: NetworkSystemClient,
+    fstab: FstabEntries,
+) -> Result<Router, ServiceError> {
+    let state = PreviewState {
+        bootloader: BootloaderClient::new(dbus.clone()).await?,
+        storage: StorageClient::new(dbus.clone()).await?,
+        locale: LocaleProxy::new(&dbus).await?,
+        software: SoftwareClient::new(dbus.clone()).await?,
+        product: ProductClient::new(dbus.clone()).await?,
+        users: UsersClient::new(dbus.clone()).await?,
+        manager: ManagerClient::new(dbus.clone()).await?,
+        network,
+        fstab,
+    };
+    Ok(Router::new()
+        .route("/", get(effective_config))
+        .route("/preview", get(preview))
+        .with_state(state))
+}
+
+/// Returns the configuration files that would be written to the target system.
+///
+/// Whether each file would overwrite an existing one is only known once the target system is
+/// mounted.
+#[utoipa::path(
+    get,
+    path = "/preview",
+    context_path = "/api/config",
+    responses(
+        (status = 200, description = "Generated files indexed by their path", body = BTreeMap<String, GeneratedFile>),
+        (status = 400, description = "The configuration could not be read")
+    )
+)]
+async fn preview(
+    State(state): State<PreviewState<'_>>,
+) -> Result<Json<BTreeMap<String, GeneratedFile>>, Error> {
+    let mut preview = ConfigPreview::new(files::mounted_target());
+
+    let devices = state.storage.staging_devices().await?;
+    let settings = state.storage.proposal_settings().await.ok();
+    let random_key_swap = settings.as_ref().is_some_and(|settings| {
+        settings
+            .volumes
+            .iter()
+            .any(|v| v.is_swap() && v.encryption() == Some(VolumeEncryption::RandomKey))
+    });
+    let overlay = settings.as_ref().and_then(|s| s.read_only_root.overlay());
+    let project_quotas: Vec<(&str, &VolumeQuota)> = settings
+        .iter()
+        .flat_map(|s| s.volumes.iter())
+        .filter_map(|v| Some((v.mount_path(), v.quota()?)))
+        .filter(|(_, q)| q.quota_type == QuotaType::Project)
+        .collect();
+    let project_paths: Vec<&str> = project_quotas.iter().map(|(path, _)| *path).collect();
+    let mut fstab = files::fstab(&devices, random_key_swap, overlay, &project_paths, |path| {
+        settings.as_ref().and_then(|s| s.mount_by_for(path))
+    });
+    for entry in state.fstab.get().await {
+        fstab.push_str(&entry.to_fstab_line());
+    }
+    preview.add("storage", "/etc/fstab", fstab);
+    if random_key_swap {
+        preview.add("storage", "/etc/crypttab", files::crypttab(&devices));
+    }
+    if let Some((projects, projid)) = files::xfs_projects(&project_quotas) {
+        preview.add("storage", "/etc/projects", projects);
+        preview.add("storage", "/etc/projid", projid);
+    }
+    if let Some(modules) = files::cache_modules(&devices) {
+        preview.add("storage", "/etc/modules-load.d/agama-cache.conf", modules);
+    }
+
+    // the defaults are used if the bootloader settings cannot be read
+    let bootloader = state.bootloader.get_config().await.unwrap_or_default();
+    let bootloader_type = match bootloader.bootloader {
+        Some(bootloader_type) => bootloader_type,
+        None => state
+            .bootloader
+            .supported_bootloaders()
+            .await
+            .unwrap_or_default()
+            .first()
+            .copied()
+            .unwrap_or(BootloaderType::Grub2),
+    };
+    let efi = state.bootloader.is_efi().await.unwrap_or(false);
+    let secure_boot = bootloader.uses_secure_boot(HardwareFacts::detect().await.secure_boot);
+    preview.add(
+        "bootloader",
+        files::SYSCONFIG_BOOTLOADER_PATH,
+        files::sysconfig_bootloader(bootloader_type, efi, secure_boot),
+    );
+
+    let locales = state.locale.locales().await?;
+    let categories = state
+        .locale
+        .locale_categories()
+        .await?
+        .into_iter()
+        .collect();
+    preview.add(
+        "l10n",
+        "/etc/locale.conf",
+        files::locale_conf(&locales, &categories),
+    );
+    let keymap = state.locale.keymap().await?;
+    if let Ok(keymap) = keymap.parse::<agama_locale_data::KeymapId>() {
+        let mapping = KeyboardMapping::from(&keymap);
+        preview.add(
+            "l10n",
+            "/etc/vconsole.conf",
+            files::vconsole_conf(&mapping.console),
+        );
+        let path = format!("/{}", keyboard::X11_KEYBOARD_PATH);
+        preview.add("l10n", &path, mapping.x11_config());
+    }
+    let ntp_servers = state.locale.effective_ntp_servers().await?;
+    let ntp_dhcp = state
+        .locale
+        .ntp_dhcp()
+        .await?
+        .parse()
+        .unwrap_or(ntp::DhcpNtpPolicy::Ignore);
+    if !ntp_servers.is_empty() || ntp_dhcp != ntp::DhcpNtpPolicy::Ignore {
+        let path = format!("/{}", ntp::CHRONY_CONFIG_PATH);
+        preview.add("l10n", &path, ntp::chrony_config(&ntp_servers, ntp_dhcp));
+    }
+
+    let repositories = state.software.extra_repositories().await?;
+    for repo in repositories
+        .iter()
+        .filter(|r| r.persistent && r.validate().is_empty())
+    {
+        let path = format!("/{}", repo.repo_file_path());
+        preview.add("software", &path, repo.to_repo_file());
+    }
+
+    let general = state
+        .network
+        .get_state()
+        .await
+        .context("Could not read the network state")?;
+    if !general.hostname.is_empty() {
+        preview.add(
+            "network",
+            "/etc/hostname",
+            format!("{}\n", general.hostname),
+        );
+    }
+
+    let connections = state
+        .network
+        .get_connections()
+        .await
+        .context("Could not read the network connections")?;
+    for conn in connections.iter().filter(|c| c.status != Status::Removed) {
+        let controller = conn
+            .controller
+            .and_then(|uuid| connections.iter().find(|c| c.uuid == uuid));
+        preview.add(
+            "network",
+            &files::keyfile_path(&conn.id),
+            connection_to_keyfile(conn, controller, true),
+        );
+    }
+
+    Ok(Json(preview.files()))
+}
+
+/// Returns the effective configuration, merging the configuration of all the modules.
+///
+/// It reflects the current in-memory state of each module and it can be used as the basis of a
+/// profile. The secrets (e.g., passwords) are redacted unless they are explicitly requested.
+#[utoipa::path(
+    get,
+    path = "/config",
+    context_path = "/api",
+    params(EffectiveConfigParams),
+    responses(
+        (status = 200, description = "Configuration of each module, indexed by module name"),
+        (status = 400, description = "The configuration could not be read")
+    )
+)]
+async fn effective_config(
+    State(state): State<PreviewState<'_>>,
+    Query(params): Query<EffectiveConfigParams>,
+) -> Result<Json<serde_json::Value>, Error> {
+    let l10n = json!({
+        "locales": state.locale.locales().await?,
+        "keymap": state.locale.keymap().await?,
+        "timezone": state.locale.timezone().await?,
+        "categories": state.locale.locale_categories().await?,
+        "ntpServers": state.locale.ntp_servers().await?,
+        "ntpDhcp": state.locale.ntp_dhcp().await?,
+    });
+
+    let general = state
+        .network
+        .get_state()
+        .await
+        .context("Could not read the network state")?;
+    let mut connections: Vec<NetworkConnection> = state
+        .network
+        .get_connections()
+        .await
+        .context("Could not read the network connections")?
+        .into_iter()
+        .filter(|c| c.status != Status::Removed)
+        .filter_map(|c| NetworkConnection::try_from(c).ok())
+        .collect();
+
+    let product = state.product.product().await?;
+    let software = SoftwareConfig {
+        patterns: Some(
+            state
+                .software
+                .user_selected_patterns()
+                .await?
+                .into_iter()
+                .map(|p| (p, true))
+                .collect(),
+        ),
+        product: Some(product).filter(|p| !p.is_empty()),
+        default_target: state.software.default_target().await?,
+        extra_repositories: Some(state.software.extra_repositories().await?),
+        only_required: Some(state.software.only_required().await?),
+        services: Some(state.software.services().await?),
+    };
+
+    let root = RootConfig {
+        password: state.users.is_root_password().await?,
+        sshkey: state.users.root_ssh_key().await?,
+    };
+    let mut first_user = state.users.first_user().await?;
+    let mut storage =
+        serde_json::to_value(state.storage.get_config().await?).map_err(ServiceError::from)?;
+    let mut bootloader = state.bootloader.get_config().await.ok();
+    if !params.include_secrets {
+        effective::redact_first_user(&mut first_user);
+        connections
+            .iter_mut()
+            .for_each(effective::redact_connection);
+        effective::redact_storage(&mut storage);
+        if let Some(password) = bootloader.as_mut().and_then(|b| b.password.as_mut()) {
+            password.redact();
+        }
+    }
+
+    let mut config = json!({
+        "l10n": l10n,
+        "network": { "state": general, "connections": connections },
+        "software": software,
+        "storage": storage,
+        "users": { "firstUser": first_user, "root": root },
+    });
+    if let Some(bootloader) = bootloader {
+        config["bootloader"] = json!(bootloader);
+    }
+    if let Ok(machine_id) = state.manager.machine_id_policy().await {
+        config["manager"] = json!({ "machineId": machine_id });
+    }
+    Ok(Json(config))
+}
@@ -0,0 +1,92 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! This module implements the web API for handling the installation profiles.
+//!
+//! The module offers one public function:
+//!
+//! * `profile_service` which returns the Axum service.
+
+use agama_lib::{
+    error::ProfileError,
+    profile::{AutoyastConversion, ProfileValidator, ValidationResult},
+};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use serde::Serialize;
+
+use crate::error::Error;
+
+/// Agama profile converted from AutoYaST and the problems found in it.
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileConversion {
+    #[serde(flatten)]
+    pub conversion: AutoyastConversion,
+    /// Problems found when validating the converted profile against the schema.
+    pub issues: Vec<String>,
+}
+
+/// Sets up and returns the axum service for the profiles.
+pub fn profile_service() -> Router {
+    Router::new().route("/autoyast", post(convert_autoyast))
+}
+
+/// Converts an AutoYaST XML profile into an Agama profile.
+///
+/// Only the storage, software, product, localization, networking and users sections are converted.
+/// The rest of the top-level sections are reported as unsupported. The pre-scripts of the profile
+/// are not executed. The converted profile is validated, so it can be used as any other Agama
+/// profile.
+#[utoipa::path(
+    post,
+    path = "/autoyast",
+    context_path = "/api/profile",
+    request_body(content = String, description = "AutoYaST profile", content_type = "application/xml"),
+    responses(
+        (status = 200, description = "The converted profile", body = ProfileConversion),
+        (status = 400, description = "The profile could not be converted"),
+        (status = 422, description = "The profile is not a valid XML file", body = Vec<String>)
+    )
+)]
+async fn convert_autoyast(xml: String) -> Result<Response, Error> {
+    let result = tokio::task::spawn_blocking(move || AutoyastConversion::from_xml(&xml))
+        .await
+        .map_err(|e| Error::Anyhow(e.to_string()))?;
+    let conversion = match result {
+        Ok(conversion) => conversion,
+        Err(error @ ProfileError::XmlError(_)) => {
+            let issues = vec![error.to_string()];
+            return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(issues)).into_response());
+        }
+        Err(error) => return Err(Error::Anyhow(error.to_string())),
+    };
+
+    let validator = ProfileValidator::default_schema().map_err(|e| Error::Anyhow(e.to_string()))?;
+    let issues = match validator.validate_value(&conversion.profile) {
+        ValidationResult::Valid => vec![],
+        ValidationResult::NotValid(issues) => issues,
+    };
+    Ok(Json(ProfileConversion { conversion, issues }).into_response())
+}
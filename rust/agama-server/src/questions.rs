@@ -18,21 +18,42 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use std::collections::HashMap;
-
-use agama_lib::questions::{self, GenericQuestion, WithPassword};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use agama_lib::questions::{
+    self, model::AnsweredQuestionTuple, GenericQuestion, WithPassword, WithTextInput,
+};
 use log;
-use zbus::{dbus_interface, fdo::ObjectManager, zvariant::ObjectPath, Connection};
+use zbus::{dbus_interface, fdo::ObjectManager, zvariant::ObjectPath, Connection, SignalContext};
 
 mod answers;
 pub mod web;
 
+/// Maximum number of answered questions kept in the history.
+const HISTORY_SIZE: usize = 100;
+
+/// Default location of the saved answers, which are loaded at startup.
+const SAVED_ANSWERS_PATH: &str = "/etc/agama.d/answers.json";
+
+/// Environment variable to override the location of the saved answers.
+const SAVED_ANSWERS_VAR: &str = "AGAMA_ANSWERS_FILE";
+
 #[derive(thiserror::Error, Debug)]
 pub enum QuestionsError {
     #[error("Could not read the answers file: {0}")]
     IO(std::io::Error),
     #[error("Could not deserialize the answers file: {0}")]
     Deserialize(serde_json::Error),
+    #[error("Could not serialize the answers: {0}")]
+    Serialize(serde_json::Error),
+    #[error("Could not write the answers file: {0}")]
+    Write(std::io::Error),
+    #[error("Invalid answers file name: '{0}'")]
+    InvalidPath(String),
 }
 
 #[derive(Clone, Debug)]
@@ -100,10 +121,42 @@ impl WithPasswordObject {
     }
 }
 
+/// Mixin interface for questions that are base + expect a free-form text
+struct WithTextInputObject(questions::WithTextInput);
+
+#[dbus_interface(name = "org.opensuse.Agama1.Questions.WithTextInput")]
+impl WithTextInputObject {
+    #[dbus_interface(property)]
+    pub fn text(&self) -> &str {
+        self.0.text.as_str()
+    }
+
+    #[dbus_interface(property)]
+    pub fn set_text(&mut self, value: &str) {
+        self.0.text = value.to_string();
+    }
+
+    #[dbus_interface(property)]
+    pub fn pattern(&self) -> &str {
+        self.0.pattern.as_deref().unwrap_or_default()
+    }
+
+    #[dbus_interface(property)]
+    pub fn min_length(&self) -> u32 {
+        self.0.min_length.unwrap_or_default()
+    }
+
+    #[dbus_interface(property)]
+    pub fn max_length(&self) -> u32 {
+        self.0.max_length.unwrap_or_default()
+    }
+}
+
 /// Question types used to be able to properly remove object from dbus
 enum QuestionType {
     Base,
     BaseWithPassword,
+    BaseWithTextInput,
 }
 
 /// Trait for objects that can provide answers to all kind of Question.
@@ -158,6 +211,14 @@ pub struct Questions {
     connection: Connection,
     last_id: u32,
     answer_strategies: Vec<Box<dyn AnswerStrategy + Sync + Send>>,
+    /// Answers remembered for the rest of the session, indexed by question class.
+    remembered: HashMap<String, String>,
+    /// IDs of the pending questions that were answered automatically.
+    auto_answered: HashSet<u32>,
+    /// Questions answered during the session, from the oldest to the newest one.
+    history: VecDeque<AnsweredQuestionTuple>,
+    /// Answers of the session which can be saved, excluding the questions with password.
+    session_answers: Vec<answers::Answer>,
 }
 
 #[dbus_interface(name = "org.opensuse.Agama1.Questions")]
@@ -184,7 +245,8 @@ impl Questions {
             default_option.to_string(),
             data,
         );
-        self.fill_answer(&mut question);
+        self.fill_answer_or_remembered(&mut question);
+        self.track_auto_answer(&question);
         let object_path = ObjectPath::try_from(question.object_path()).unwrap();
         let question_object = GenericQuestionObject(question);
 
@@ -223,6 +285,7 @@ impl Questions {
 
         let base_question = question.base.clone();
         self.fill_answer_with_password(&mut question);
+        self.track_auto_answer(&base_question);
         let base_object = GenericQuestionObject(base_question);
 
         self.connection
@@ -240,11 +303,63 @@ impl Questions {
         Ok(object_path)
     }
 
+    /// creates new question that expects a free-form text answer
+    ///
+    /// An empty `pattern` and zero lengths mean that there is no such restriction.
+    #[allow(clippy::too_many_arguments)]
+    async fn new_with_text_input(
+        &mut self,
+        class: &str,
+        text: &str,
+        options: Vec<&str>,
+        default_option: &str,
+        data: HashMap<String, String>,
+        pattern: &str,
+        min_length: u32,
+        max_length: u32,
+    ) -> zbus::fdo::Result<ObjectPath<'_>> {
+        log::info!("Creating new question with text input with text: {}.", text);
+        let id = self.last_id;
+        self.last_id += 1; // TODO use some thread safety
+        let options = options.iter().map(|o| o.to_string()).collect();
+        let base = questions::GenericQuestion::new(
+            id,
+            class.to_string(),
+            text.to_string(),
+            options,
+            default_option.to_string(),
+            data,
+        );
+        let pattern = Some(pattern.to_string()).filter(|p| !p.is_empty());
+        let min_length = Some(min_length).filter(|l| *l > 0);
+        let max_length = Some(max_length).filter(|l| *l > 0);
+        let mut question = WithTextInput::new(base, pattern, min_length, max_length);
+        let object_path = ObjectPath::try_from(question.base.object_path()).unwrap();
+
+        self.fill_answer(&mut question.base);
+        self.track_auto_answer(&question.base);
+        let base_object = GenericQuestionObject(question.base.clone());
+
+        self.connection
+            .object_server()
+            .at(object_path.clone(), WithTextInputObject(question))
+            .await?;
+        // NOTE: as for questions with password, GenericQuestion must be the last interface
+        self.connection
+            .object_server()
+            .at(object_path.clone(), base_object)
+            .await?;
+
+        self.questions.insert(id, QuestionType::BaseWithTextInput);
+        Ok(object_path)
+    }
+
     /// Removes question at given object path
     /// TODO: use id as parameter ( need at first check other users of method )
     async fn delete(&mut self, question: ObjectPath<'_>) -> zbus::fdo::Result<()> {
         // TODO: error checking
         let id: u32 = question.rsplit('/').next().unwrap().parse().unwrap();
+        self.record_history(id, &question).await?;
         let qtype = self.questions.get(&id).unwrap();
         match qtype {
             QuestionType::Base => {
@@ -263,6 +378,16 @@ impl Questions {
                     .remove::<WithPasswordObject, _>(question.clone())
                     .await?;
             }
+            QuestionType::BaseWithTextInput => {
+                self.connection
+                    .object_server()
+                    .remove::<GenericQuestionObject, _>(question.clone())
+                    .await?;
+                self.connection
+                    .object_server()
+                    .remove::<WithTextInputObject, _>(question.clone())
+                    .await?;
+            }
         };
         self.questions.remove(&id);
         Ok(())
@@ -295,6 +420,73 @@ impl Questions {
         }
     }
 
+    /// Remembers the answer for the subsequent generic questions of the given class
+    ///
+    /// The answer is only used if it is one of the options of the question and the answer files
+    /// do not answer it.
+    async fn remember_answer(
+        &mut self,
+        class: &str,
+        answer: &str,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> zbus::fdo::Result<()> {
+        log::info!("Remembering answer '{}' for class {}", answer, class);
+        self.remembered
+            .insert(class.to_string(), answer.to_string());
+        self.remembered_answers_changed(&ctxt).await?;
+        Ok(())
+    }
+
+    /// Forgets all the remembered answers
+    async fn forget_answers(
+        &mut self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> zbus::fdo::Result<()> {
+        log::info!("Forgetting the remembered answers");
+        self.remembered.clear();
+        self.remembered_answers_changed(&ctxt).await?;
+        Ok(())
+    }
+
+    /// Questions answered during the session (id, class, text, answer, whether it was answered
+    /// automatically and the Unix timestamp), from the oldest to the newest one
+    ///
+    /// Only the last questions are kept and the passwords are never included.
+    fn history(&self) -> Vec<AnsweredQuestionTuple> {
+        self.history.iter().cloned().collect()
+    }
+
+    /// Answers remembered for the session, indexed by question class
+    #[dbus_interface(property)]
+    fn remembered_answers(&self) -> HashMap<String, String> {
+        self.remembered.clone()
+    }
+
+    /// Saves the answers given during the session to a file
+    ///
+    /// The questions with password are not saved. The file is always written to the directory of
+    /// the default location, so `name` must be a relative path without `..` components. An empty
+    /// name means the default location, which is loaded at startup. It returns the number of saved
+    /// answers.
+    fn save_answers(&self, name: String) -> zbus::fdo::Result<u32> {
+        let default_path = PathBuf::from(saved_answers_path());
+        let path = if name.is_empty() {
+            default_path
+        } else {
+            let dir = default_path.parent().unwrap_or(Path::new("/"));
+            answers::Answers::resolve_path(dir, &name)
+                .map_err(|e| zbus::fdo::Error::InvalidArgs(e.to_string()))?
+        };
+        log::info!(
+            "Saving {} answers to {}",
+            self.session_answers.len(),
+            path.display()
+        );
+        answers::Answers::save(&self.session_answers, &path)
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
+        Ok(self.session_answers.len() as u32)
+    }
+
     fn add_answer_file(&mut self, path: String) -> zbus::fdo::Result<()> {
         log::info!("Adding answer file {}", path);
         let answers = answers::Answers::new_from_file(path.as_str())
@@ -313,24 +505,137 @@ impl Questions {
             connection: connection.to_owned(),
             last_id: 0,
             answer_strategies: vec![],
+            remembered: HashMap::new(),
+            auto_answered: HashSet::new(),
+            history: VecDeque::new(),
+            session_answers: vec![],
+        }
+    }
+
+    /// loads the saved answers, if any, as an answer strategy
+    ///
+    /// The malformed answers are skipped and logged.
+    fn load_saved_answers(&mut self, path: &str) {
+        if !std::path::Path::new(path).exists() {
+            return;
+        }
+        match answers::Answers::load_lenient(path) {
+            Ok((answers, warnings)) => {
+                for warning in warnings {
+                    log::warn!("{}: {}", path, warning);
+                }
+                log::info!("Loaded saved answers from {}", path);
+                self.answer_strategies.push(Box::new(answers));
+            }
+            Err(error) => log::warn!("Could not load the saved answers: {}", error),
         }
     }
 
+    /// remembers whether the question was answered right after creating it
+    fn track_auto_answer(&mut self, question: &GenericQuestion) {
+        if !question.answer.is_empty() {
+            self.auto_answered.insert(question.id);
+        }
+    }
+
+    /// adds the question to the history if it was answered
+    ///
+    /// The history only keeps the last [HISTORY_SIZE] questions.
+    async fn record_history(&mut self, id: u32, path: &ObjectPath<'_>) -> zbus::fdo::Result<()> {
+        let auto_answered = self.auto_answered.remove(&id);
+        let iface = self
+            .connection
+            .object_server()
+            .interface::<_, GenericQuestionObject>(path.clone())
+            .await?;
+        let question = iface.get().await.0.clone();
+        if question.answer.is_empty() {
+            return Ok(());
+        }
+
+        if !matches!(
+            self.questions.get(&id),
+            Some(QuestionType::BaseWithPassword)
+        ) {
+            answers::Answers::upsert(
+                &mut self.session_answers,
+                answers::Answer::from_question(&question),
+            );
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        if self.history.len() == HISTORY_SIZE {
+            self.history.pop_front();
+        }
+        self.history.push_back((
+            question.id,
+            question.class,
+            question.text,
+            question.answer,
+            auto_answered,
+            timestamp,
+        ));
+        Ok(())
+    }
+
+    /// tries to answer the question using the answer strategies or the remembered answers
+    ///
+    /// The answer strategies (e.g., the answer files) take precedence over the answer
+    /// remembered for the question class. The default answers strategy (non-interactive
+    /// mode) is the exception: it is only used if there is no remembered answer.
+    fn fill_answer_or_remembered(&self, question: &mut GenericQuestion) {
+        let (defaults, strategies): (Vec<_>, Vec<_>) = self
+            .answer_strategies
+            .iter()
+            .map(|s| s.as_ref())
+            .partition(|s| s.id() == DefaultAnswers::id());
+        if Self::fill_answer_with(strategies, question) || self.fill_remembered_answer(question) {
+            return;
+        }
+        Self::fill_answer_with(defaults, question);
+    }
+
+    /// tries to answer the question with the answer remembered for its class
+    ///
+    /// It returns whether the question was answered. The remembered answer is
+    /// ignored if it is not one of the options of the question.
+    fn fill_remembered_answer(&self, question: &mut GenericQuestion) -> bool {
+        let Some(answer) = self.remembered.get(&question.class) else {
+            return false;
+        };
+        if !question.options.contains(answer) {
+            return false;
+        }
+        question.answer = answer.clone();
+        true
+    }
+
     /// tries to provide answer to question using answer strategies
     ///
     /// What happens under the hood is that it uses answer_strategies vector
     /// and try to find the first strategy that provides answer. When
     /// answer is provided, it returns immediately.
     fn fill_answer(&self, question: &mut GenericQuestion) {
-        for strategy in self.answer_strategies.iter() {
-            match strategy.answer(question) {
-                None => (),
-                Some(answer) => {
-                    question.answer = answer;
-                    return;
-                }
+        Self::fill_answer_with(self.answer_strategies.iter().map(|s| s.as_ref()), question);
+    }
+
+    /// tries to answer the question using the given strategies, in order
+    ///
+    /// It returns whether the question was answered.
+    fn fill_answer_with<'a, I>(strategies: I, question: &mut GenericQuestion) -> bool
+    where
+        I: IntoIterator<Item = &'a (dyn AnswerStrategy + Sync + Send)>,
+    {
+        for strategy in strategies {
+            if let Some(answer) = strategy.answer(question) {
+                question.answer = answer;
+                return true;
             }
         }
+        false
     }
 
     /// tries to provide answer to question using answer strategies
@@ -352,6 +657,11 @@ impl Questions {
     }
 }
 
+/// Returns the location of the saved answers.
+fn saved_answers_path() -> String {
+    std::env::var(SAVED_ANSWERS_VAR).unwrap_or_else(|_| SAVED_ANSWERS_PATH.to_string())
+}
+
 /// Starts questions dbus service together with Object manager
 pub async fn export_dbus_objects(
     connection: &Connection,
@@ -359,7 +669,8 @@ pub async fn export_dbus_objects(
     const PATH: &str = "/org/opensuse/Agama1/Questions";
 
     // When serving, request the service name _after_ exposing the main object
-    let questions = Questions::new(connection);
+    let mut questions = Questions::new(connection);
+    questions.load_saved_answers(&saved_answers_path());
     connection.object_server().at(PATH, questions).await?;
     connection.object_server().at(PATH, ObjectManager).await?;
 
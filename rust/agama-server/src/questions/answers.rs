@@ -18,7 +18,10 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+};
 
 use agama_lib::questions::GenericQuestion;
 use serde::{Deserialize, Serialize};
@@ -29,19 +32,43 @@ use super::QuestionsError;
 /// corresponding [agama_lib::questions::GenericQuestion] fields.
 /// The *matcher* part is: `class`, `text`, `data`.
 /// The *answer* part is: `answer`, `password`.
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
-struct Answer {
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct Answer {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub class: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub text: Option<String>,
     /// A matching GenericQuestion can have other data fields too
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<HashMap<String, String>>,
     /// The answer text is the only mandatory part of an Answer
     pub answer: String,
     /// All possible mixins have to be here, so they can be specified in an Answer
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub password: Option<String>,
 }
 
 impl Answer {
+    /// Builds an answer which responds to the questions with the same class and data.
+    ///
+    /// The text is not included because it is meant for humans (and it might be translated).
+    ///
+    /// * `question`: answered question.
+    pub fn from_question(question: &GenericQuestion) -> Self {
+        Self {
+            class: Some(question.class.clone()),
+            text: None,
+            data: Some(question.data.clone()).filter(|d| !d.is_empty()),
+            answer: question.answer.clone(),
+            password: None,
+        }
+    }
+
+    /// Whether both answers respond to the same questions.
+    fn same_matcher(&self, other: &Answer) -> bool {
+        self.class == other.class && self.text == other.text && self.data == other.data
+    }
+
     /// Determines whether the answer responds to the given question.
     ///
     /// * `question`: question to compare with.
@@ -88,6 +115,83 @@ impl Answers {
         Ok(result)
     }
 
+    /// Reads the answers from a file, skipping the malformed ones.
+    ///
+    /// It returns the answers and a warning for each skipped one. It fails only if the file
+    /// cannot be read or it does not contain an `answers` list.
+    ///
+    /// * `path`: file to read.
+    pub fn load_lenient(path: &str) -> Result<(Self, Vec<String>), QuestionsError> {
+        let contents = std::fs::read_to_string(path).map_err(QuestionsError::IO)?;
+        Self::parse_lenient(&contents)
+    }
+
+    fn parse_lenient(contents: &str) -> Result<(Self, Vec<String>), QuestionsError> {
+        #[derive(Deserialize)]
+        struct RawAnswers {
+            answers: Vec<serde_json::Value>,
+        }
+
+        let raw: RawAnswers =
+            serde_json::from_str(contents).map_err(QuestionsError::Deserialize)?;
+        let mut answers = vec![];
+        let mut warnings = vec![];
+        for (index, value) in raw.answers.into_iter().enumerate() {
+            match serde_json::from_value::<Answer>(value) {
+                Ok(answer) if answer.answer.is_empty() => warnings.push(format!(
+                    "Skipping answer #{}: the answer is empty",
+                    index + 1
+                )),
+                Ok(answer) => answers.push(answer),
+                Err(error) => warnings.push(format!("Skipping answer #{}: {}", index + 1, error)),
+            }
+        }
+        Ok((Self { answers }, warnings))
+    }
+
+    /// Returns where to write an answers file, which must be inside the given directory.
+    ///
+    /// Absolute paths and paths containing `..` components are rejected.
+    ///
+    /// * `dir`: directory of the answers files.
+    /// * `name`: relative path of the file (e.g., "answers.json").
+    pub fn resolve_path(dir: &Path, name: &str) -> Result<PathBuf, QuestionsError> {
+        let relative = Path::new(name);
+        let valid = relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+        if name.is_empty() || !valid || relative.file_name().is_none() {
+            return Err(QuestionsError::InvalidPath(name.to_string()));
+        }
+        Ok(dir.join(relative))
+    }
+
+    /// Writes the given answers to a file, creating the parent directories if needed.
+    ///
+    /// * `answers`: answers to write.
+    /// * `path`: file to write.
+    pub fn save(answers: &[Answer], path: &Path) -> Result<(), QuestionsError> {
+        let answers = Self {
+            answers: answers.to_vec(),
+        };
+        let json = serde_json::to_string_pretty(&answers).map_err(QuestionsError::Serialize)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(QuestionsError::Write)?;
+        }
+        std::fs::write(path, json).map_err(QuestionsError::Write)
+    }
+
+    /// Adds or replaces (if it responds to the same questions) an answer.
+    ///
+    /// * `answers`: list of answers.
+    /// * `answer`: answer to add.
+    pub fn upsert(answers: &mut Vec<Answer>, answer: Answer) {
+        match answers.iter_mut().find(|a| a.same_matcher(&answer)) {
+            Some(found) => *found = answer,
+            None => answers.push(answer),
+        }
+    }
+
     pub fn id() -> u8 {
         2
     }
@@ -297,6 +401,75 @@ mod tests {
         assert_eq!(Some("Yes".to_string()), answers.answer(&question));
     }
 
+    #[test]
+    fn test_loading_lenient_json() {
+        let file = r#"
+            {
+                "answers": [
+                { "class": "without_data", "answer": "OK" },
+                { "class": "with_data", "data": "wrong" , "answer": "Cancel" },
+                { "class": "empty", "answer": "" }
+                ]
+            }
+        "#;
+        let (result, warnings) = Answers::parse_lenient(file).unwrap();
+        assert_eq!(result.answers.len(), 1);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("Skipping answer #2"));
+        assert!(Answers::parse_lenient(r#"{ "rules": [] }"#).is_err());
+    }
+
+    #[test]
+    fn test_saving_json() {
+        let question = GenericQuestion {
+            id: 1,
+            class: "with_data".to_string(),
+            text: "Hard question?".to_string(),
+            options: vec!["Ok".to_string(), "Cancel".to_string()],
+            default_option: "Cancel".to_string(),
+            data: HashMap::from([("data1".to_string(), "value1".to_string())]),
+            answer: "Cancel".to_string(),
+        };
+        let mut answers = vec![];
+        Answers::upsert(&mut answers, Answer::from_question(&question));
+        let question = GenericQuestion {
+            answer: "Ok".to_string(),
+            ..question
+        };
+        Answers::upsert(&mut answers, Answer::from_question(&question));
+        assert_eq!(answers.len(), 1);
+
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path().join("agama.d/answers.json");
+        Answers::save(&answers, &path).unwrap();
+        let (loaded, warnings) = Answers::load_lenient(path.to_str().unwrap()).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(Some("Ok".to_string()), loaded.answer(&question));
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        let dir = Path::new("/etc/agama.d");
+        assert_eq!(
+            Answers::resolve_path(dir, "answers.json").unwrap(),
+            PathBuf::from("/etc/agama.d/answers.json")
+        );
+        assert_eq!(
+            Answers::resolve_path(dir, "saved/answers.json").unwrap(),
+            PathBuf::from("/etc/agama.d/saved/answers.json")
+        );
+        for name in [
+            "",
+            "/etc/shadow",
+            "../shadow",
+            "saved/../../shadow",
+            ".",
+            "saved/..",
+        ] {
+            assert!(Answers::resolve_path(dir, name).is_err(), "{name}");
+        }
+    }
+
     #[test]
     fn test_loading_json() {
         let file = r#"
@@ -25,21 +25,32 @@
 //! * `questions_service` which returns the Axum service.
 //! * `questions_stream` which offers an stream that emits questions related signals.
 
-use crate::{error::Error, web::Event};
+use crate::{
+    error::Error,
+    web::{translate, translate_with, Event},
+};
 use agama_lib::{
     dbus::{extract_id_from_path, get_property},
     error::ServiceError,
-    proxies::{GenericQuestionProxy, QuestionWithPasswordProxy, Questions1Proxy},
-    questions::model::{Answer, GenericQuestion, PasswordAnswer, Question, QuestionWithPassword},
+    proxies::{
+        GenericQuestionProxy, QuestionWithPasswordProxy, QuestionWithTextInputProxy,
+        Questions1Proxy,
+    },
+    questions::model::{
+        Answer, AnsweredQuestion, GenericQuestion, PasswordAnswer, Question, QuestionWithPassword,
+        QuestionWithTextInput, RememberedAnswer, SaveAnswersParams, SavedAnswers, TextInputAnswer,
+    },
 };
 use anyhow::Context;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::{IntoResponse, Response},
-    routing::{delete, get},
+    routing::{delete, get, post},
     Json, Router,
 };
+use regex::Regex;
+use serde_json::json;
 use std::{collections::HashMap, pin::Pin};
 use tokio_stream::{Stream, StreamExt};
 use zbus::{
@@ -56,6 +67,7 @@ struct QuestionsClient<'a> {
     questions_proxy: Questions1Proxy<'a>,
     generic_interface: OwnedInterfaceName,
     with_password_interface: OwnedInterfaceName,
+    with_text_input_interface: OwnedInterfaceName,
 }
 
 impl<'a> QuestionsClient<'a> {
@@ -78,6 +90,10 @@ impl<'a> QuestionsClient<'a> {
                 "org.opensuse.Agama1.Questions.WithPassword",
             )
             .into(),
+            with_text_input_interface: InterfaceName::from_str_unchecked(
+                "org.opensuse.Agama1.Questions.WithTextInput",
+            )
+            .into(),
         })
     }
 
@@ -90,7 +106,21 @@ impl<'a> QuestionsClient<'a> {
             .iter()
             .map(|(k, v)| (k.as_str(), v.as_str()))
             .collect();
-        let path = if question.with_password.is_some() {
+        let path = if let Some(hints) = &question.with_text_input {
+            tracing::info!("creating a question with text input");
+            self.questions_proxy
+                .new_with_text_input(
+                    &generic.class,
+                    &generic.text,
+                    &options,
+                    &generic.default_option,
+                    data,
+                    hints.pattern.as_deref().unwrap_or_default(),
+                    hints.min_length.unwrap_or_default(),
+                    hints.max_length.unwrap_or_default(),
+                )
+                .await?
+        } else if question.with_password.is_some() {
             tracing::info!("creating a question with password");
             self.questions_proxy
                 .new_with_password(
@@ -142,6 +172,10 @@ impl<'a> QuestionsClient<'a> {
                 question.with_password = Some(QuestionWithPassword {});
             }
 
+            if let Some(text_input) = interfaces_hash.get(&self.with_text_input_interface) {
+                question.with_text_input = Some(build_text_input(text_input)?);
+            }
+
             result.push(question);
         }
         Ok(result)
@@ -161,11 +195,89 @@ impl<'a> QuestionsClient<'a> {
                 data: get_property(properties, "Data")?,
             },
             with_password: None,
+            with_text_input: None,
         };
 
         Ok(result)
     }
 
+    /// Returns the text input hints of the question or `None` if it is not a text input question.
+    pub async fn text_input(&self, id: u32) -> Result<Option<QuestionWithTextInput>, ServiceError> {
+        let question_path = OwnedObjectPath::from(
+            ObjectPath::try_from(format!("/org/opensuse/Agama1/Questions/{}", id))
+                .context("Failed to create dbus path")?,
+        );
+        let objects = self.objects_proxy.get_managed_objects().await?;
+        let question = objects
+            .get(&question_path)
+            .ok_or(ServiceError::QuestionNotExist(id))?;
+        question
+            .get(&self.with_text_input_interface)
+            .map(build_text_input)
+            .transpose()
+    }
+
+    /// Returns the class of a question and whether it is a generic one (neither with password nor
+    /// with text input).
+    pub async fn question_class(&self, id: u32) -> Result<(String, bool), ServiceError> {
+        let question_path = OwnedObjectPath::from(
+            ObjectPath::try_from(format!("/org/opensuse/Agama1/Questions/{}", id))
+                .context("Failed to create dbus path")?,
+        );
+        let objects = self.objects_proxy.get_managed_objects().await?;
+        let question = objects
+            .get(&question_path)
+            .ok_or(ServiceError::QuestionNotExist(id))?;
+        let generic_properties = question
+            .get(&self.generic_interface)
+            .context("Question does not have generic interface")?;
+        let class: String = get_property(generic_properties, "Class")?;
+        let generic = !question.contains_key(&self.with_password_interface)
+            && !question.contains_key(&self.with_text_input_interface);
+        Ok((class, generic))
+    }
+
+    /// Remembers the answer for the subsequent questions of the given class.
+    pub async fn remember_answer(&self, class: &str, answer: &str) -> Result<(), ServiceError> {
+        Ok(self.questions_proxy.remember_answer(class, answer).await?)
+    }
+
+    /// Returns the remembered answers, sorted by class.
+    pub async fn remembered_answers(&self) -> Result<Vec<RememberedAnswer>, ServiceError> {
+        let mut answers: Vec<_> = self
+            .questions_proxy
+            .remembered_answers()
+            .await?
+            .into_iter()
+            .map(|(class, answer)| RememberedAnswer { class, answer })
+            .collect();
+        answers.sort_by(|a, b| a.class.cmp(&b.class));
+        Ok(answers)
+    }
+
+    /// Forgets all the remembered answers.
+    pub async fn forget_answers(&self) -> Result<(), ServiceError> {
+        Ok(self.questions_proxy.forget_answers().await?)
+    }
+
+    /// Saves the answers of the session to the given file (or the default location, if empty).
+    ///
+    /// It returns the number of saved answers.
+    pub async fn save_answers(&self, path: &str) -> Result<u32, ServiceError> {
+        Ok(self.questions_proxy.save_answers(path).await?)
+    }
+
+    /// Returns the questions answered during the session, from the oldest to the newest one.
+    pub async fn history(&self) -> Result<Vec<AnsweredQuestion>, ServiceError> {
+        Ok(self
+            .questions_proxy
+            .history()
+            .await?
+            .into_iter()
+            .map(AnsweredQuestion::from)
+            .collect())
+    }
+
     pub async fn delete(&self, id: u32) -> Result<(), ServiceError> {
         let question_path = ObjectPath::try_from(format!("/org/opensuse/Agama1/Questions/{}", id))
             .context("Failed to create a D-Bus path")?;
@@ -196,6 +308,12 @@ impl<'a> QuestionsClient<'a> {
                 password: get_property(password_iface, "Password")?,
             });
         }
+        if let Some(text_iface) = question.get(&self.with_text_input_interface) {
+            let text: String = get_property(text_iface, "Text")?;
+            if !text.is_empty() {
+                result.with_text_input = Some(TextInputAnswer { text });
+            }
+        }
         let generic_interface = OwnedInterfaceName::from(
             InterfaceName::from_static_str("org.opensuse.Agama1.Questions.Generic")
                 .context("Failed to create interface name for generic question")?,
@@ -227,6 +345,14 @@ impl<'a> QuestionsClient<'a> {
                 .set_password(password.password.as_str())
                 .await?
         }
+        if let Some(text_input) = answer.with_text_input {
+            let dbus_text = QuestionWithTextInputProxy::builder(&self.connection)
+                .path(&question_path)?
+                .cache_properties(zbus::CacheProperties::No)
+                .build()
+                .await?;
+            dbus_text.set_text(text_input.text.as_str()).await?
+        }
         let dbus_generic = GenericQuestionProxy::builder(&self.connection)
             .path(&question_path)?
             .cache_properties(zbus::CacheProperties::No)
@@ -239,6 +365,83 @@ impl<'a> QuestionsClient<'a> {
     }
 }
 
+fn build_text_input(
+    properties: &HashMap<String, OwnedValue>,
+) -> Result<QuestionWithTextInput, ServiceError> {
+    let pattern: String = get_property(properties, "Pattern")?;
+    let min_length: u32 = get_property(properties, "MinLength")?;
+    let max_length: u32 = get_property(properties, "MaxLength")?;
+    Ok(QuestionWithTextInput {
+        pattern: Some(pattern).filter(|p| !p.is_empty()),
+        min_length: Some(min_length).filter(|l| *l > 0),
+        max_length: Some(max_length).filter(|l| *l > 0),
+    })
+}
+
+/// Checks the hints of a question with a text input.
+///
+/// It returns the list of problems, if any.
+fn validate_text_hints(hints: &QuestionWithTextInput) -> Vec<String> {
+    let mut issues = vec![];
+    if let Some(pattern) = &hints.pattern {
+        if Regex::new(&format!("^(?:{})$", pattern)).is_err() {
+            issues.push(translate_with("Invalid pattern: '{}'", &[pattern]));
+        }
+    }
+
+    if let (Some(min), Some(max)) = (hints.min_length, hints.max_length) {
+        if min > max {
+            issues.push(translate_with(
+                "The minimum length ({}) is greater than the maximum one ({})",
+                &[&min, &max],
+            ));
+        }
+    }
+    issues
+}
+
+/// Checks a text answer against the hints of the question.
+///
+/// It returns the list of problems, if any.
+fn validate_text_answer(hints: &QuestionWithTextInput, text: &str) -> Vec<String> {
+    let mut issues = vec![];
+    let length = text.chars().count() as u32;
+
+    if let Some(min) = hints.min_length {
+        if length < min {
+            issues.push(translate_with(
+                "The answer must be at least {} characters long",
+                &[&min],
+            ));
+        }
+    }
+
+    if let Some(max) = hints.max_length {
+        if length > max {
+            issues.push(translate_with(
+                "The answer must be at most {} characters long",
+                &[&max],
+            ));
+        }
+    }
+
+    if let Some(pattern) = &hints.pattern {
+        match Regex::new(&format!("^(?:{})$", pattern)) {
+            Ok(regex) if !regex.is_match(text) => issues.push(translate_with(
+                "The answer does not match the pattern '{}'",
+                &[pattern],
+            )),
+            Ok(_) => {}
+            Err(_) => issues.push(translate_with(
+                "Invalid pattern in the question: '{}'",
+                &[pattern],
+            )),
+        }
+    }
+
+    issues
+}
+
 #[derive(Clone)]
 struct QuestionsState<'a> {
     questions: QuestionsClient<'a>,
@@ -250,6 +453,12 @@ pub async fn questions_service(dbus: zbus::Connection) -> Result<Router, Service
     let state = QuestionsState { questions };
     let router = Router::new()
         .route("/", get(list_questions).post(create_question))
+        .route(
+            "/remembered",
+            get(remembered_answers).delete(forget_answers),
+        )
+        .route("/history", get(history))
+        .route("/answers/save", post(save_answers))
         .route("/:id", delete(delete_question))
         .route("/:id/answer", get(get_answer).put(answer_question))
         .with_state(state);
@@ -284,6 +493,9 @@ pub async fn questions_stream(
 
 /// Returns the list of questions that waits for answer.
 ///
+/// The texts are translated to the language of the request (`Accept-Language` header) when a
+/// translation exists. The classes are not translated.
+///
 /// * `state`: service state.
 #[utoipa::path(get, path = "/questions", responses(
     (status = 200, description = "List of open questions", body = Vec<Question>),
@@ -292,7 +504,11 @@ pub async fn questions_stream(
 async fn list_questions(
     State(state): State<QuestionsState<'_>>,
 ) -> Result<Json<Vec<Question>>, Error> {
-    Ok(Json(state.questions.questions().await?))
+    let mut questions = state.questions.questions().await?;
+    for question in questions.iter_mut() {
+        question.generic.text = translate(&question.generic.text);
+    }
+    Ok(Json(questions))
 }
 
 /// Get answer to question.
@@ -321,17 +537,109 @@ async fn get_answer(
 /// * `state`: service state.
 /// * `questions_id`: id of question
 /// * `answer`: struct with answer and possible other data needed for answer like password
+///
+/// Text answers are validated against the hints of the question before being submitted. If the
+/// answer is marked to be remembered, it is used for the subsequent questions of the same class.
+/// Only the answers to generic questions can be remembered.
 #[utoipa::path(put, path = "/questions/:id/answer", responses(
     (status = 200, description = "answer question"),
+    (status = 422, description = "The text answer is not valid or it cannot be remembered"),
     (status = 400, description = "The D-Bus service could not perform the action")
 ))]
 async fn answer_question(
     State(state): State<QuestionsState<'_>>,
     Path(question_id): Path<u32>,
     Json(answer): Json<Answer>,
-) -> Result<(), Error> {
-    let res = state.questions.answer(question_id, answer).await;
-    Ok(res?)
+) -> Result<Response, Error> {
+    if let Some(text_input) = &answer.with_text_input {
+        let Some(hints) = state.questions.text_input(question_id).await? else {
+            let body = json!({ "error": translate("The question does not expect a text answer") });
+            return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response());
+        };
+        let issues = validate_text_answer(&hints, &text_input.text);
+        if !issues.is_empty() {
+            return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(issues)).into_response());
+        }
+    }
+
+    let remember = if answer.remember {
+        let (class, generic) = state.questions.question_class(question_id).await?;
+        if !generic {
+            let body = json!({
+                "error": translate("Only the answers to generic questions can be remembered")
+            });
+            return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response());
+        }
+        Some((class, answer.generic.answer.clone()))
+    } else {
+        None
+    };
+
+    state.questions.answer(question_id, answer).await?;
+    if let Some((class, answer)) = remember {
+        state.questions.remember_answer(&class, &answer).await?;
+    }
+    Ok(().into_response())
+}
+
+/// Returns the answers remembered for the session.
+///
+/// * `state`: service state.
+#[utoipa::path(get, path = "/questions/remembered", responses(
+    (status = 200, description = "Remembered answers", body = Vec<RememberedAnswer>),
+    (status = 400, description = "The D-Bus service could not perform the action")
+))]
+async fn remembered_answers(
+    State(state): State<QuestionsState<'_>>,
+) -> Result<Json<Vec<RememberedAnswer>>, Error> {
+    Ok(Json(state.questions.remembered_answers().await?))
+}
+
+/// Forgets the answers remembered for the session.
+///
+/// * `state`: service state.
+#[utoipa::path(delete, path = "/questions/remembered", responses(
+    (status = 200, description = "The remembered answers were forgotten"),
+    (status = 400, description = "The D-Bus service could not perform the action")
+))]
+async fn forget_answers(State(state): State<QuestionsState<'_>>) -> Result<(), Error> {
+    Ok(state.questions.forget_answers().await?)
+}
+
+/// Saves the answers given during the session, so later installations can load them.
+///
+/// The questions with password are not saved.
+///
+/// * `state`: service state.
+/// * `params`: where to save the answers.
+#[utoipa::path(post, path = "/questions/answers/save",
+    request_body = SaveAnswersParams,
+    responses(
+    (status = 200, description = "The answers were saved", body = SavedAnswers),
+    (status = 400, description = "The answers could not be saved")
+))]
+async fn save_answers(
+    State(state): State<QuestionsState<'_>>,
+    Json(params): Json<SaveAnswersParams>,
+) -> Result<Json<SavedAnswers>, Error> {
+    let path = params.path.unwrap_or_default();
+    let count = state.questions.save_answers(&path).await?;
+    Ok(Json(SavedAnswers { count }))
+}
+
+/// Returns the questions answered during the session.
+///
+/// Only the last questions are kept and the passwords are never included.
+///
+/// * `state`: service state.
+#[utoipa::path(get, path = "/questions/history", responses(
+    (status = 200, description = "Answered questions, from the oldest to the newest one", body = Vec<AnsweredQuestion>),
+    (status = 400, description = "The D-Bus service could not perform the action")
+))]
+async fn history(
+    State(state): State<QuestionsState<'_>>,
+) -> Result<Json<Vec<AnsweredQuestion>>, Error> {
+    Ok(Json(state.questions.history().await?))
 }
 
 /// Deletes question.
@@ -352,16 +660,68 @@ async fn delete_question(
 
 /// Create new question.
 ///
+/// The hints of the text input, if any, are validated (e.g., the pattern must be a valid regular
+/// expression).
+///
 /// * `state`: service state.
 /// * `question`: struct with question where id of question is ignored and will be assigned
 #[utoipa::path(post, path = "/questions", responses(
     (status = 200, description = "answer question"),
+    (status = 422, description = "The hints of the text input are not valid", body = Vec<String>),
     (status = 400, description = "The D-Bus service could not perform the action")
 ))]
 async fn create_question(
     State(state): State<QuestionsState<'_>>,
     Json(question): Json<Question>,
-) -> Result<Json<Question>, Error> {
+) -> Result<Response, Error> {
+    if let Some(hints) = &question.with_text_input {
+        let issues = validate_text_hints(hints);
+        if !issues.is_empty() {
+            return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(issues)).into_response());
+        }
+    }
+
     let res = state.questions.create_question(question).await?;
-    Ok(Json(res))
+    Ok(Json(res).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_text_answer() {
+        let hints = QuestionWithTextInput {
+            pattern: Some("/[a-z/]+".to_string()),
+            min_length: Some(3),
+            max_length: Some(8),
+        };
+        assert!(validate_text_answer(&hints, "/home").is_empty());
+        assert_eq!(validate_text_answer(&hints, "/a").len(), 1);
+        assert_eq!(validate_text_answer(&hints, "/home/user/data").len(), 1);
+        assert_eq!(validate_text_answer(&hints, "home").len(), 1);
+        assert_eq!(validate_text_answer(&hints, "H").len(), 2);
+    }
+
+    #[test]
+    fn test_validate_text_hints() {
+        let mut hints = QuestionWithTextInput {
+            pattern: Some("[0-9]+".to_string()),
+            min_length: Some(1),
+            max_length: Some(4),
+        };
+        assert!(validate_text_hints(&hints).is_empty());
+        assert!(validate_text_hints(&QuestionWithTextInput::default()).is_empty());
+
+        hints.pattern = Some("[0-9".to_string());
+        hints.min_length = Some(5);
+        assert_eq!(validate_text_hints(&hints).len(), 2);
+    }
+
+    #[test]
+    fn test_validate_text_answer_without_hints() {
+        let hints = QuestionWithTextInput::default();
+        assert!(validate_text_answer(&hints, "").is_empty());
+        assert!(validate_text_answer(&hints, "anything goes").is_empty());
+    }
 }
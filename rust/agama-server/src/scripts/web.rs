@@ -0,0 +1,170 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! This module implements the web API for the scripts to run in the installed system.
+//!
+//! The module offers one public function:
+//!
+//! * `scripts_service` which returns the Axum service.
+
+use agama_lib::{
+    error::ServiceError,
+    scripts::{
+        client::ScriptsClient,
+        model::{Script, ScriptsSettings},
+    },
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::put,
+    Json, Router,
+};
+
+use crate::error::Error;
+
+#[derive(Clone)]
+struct ScriptsState<'a> {
+    client: ScriptsClient<'a>,
+}
+
+/// Sets up and returns the axum service for the scripts.
+///
+/// * `dbus`: D-Bus connection.
+pub async fn scripts_service(dbus: zbus::Connection) -> Result<Router, ServiceError> {
+    let client = ScriptsClient::new(dbus).await?;
+    let state = ScriptsState { client };
+    let router = Router::new()
+        .route("/config", put(set_config).get(get_config))
+        .route("/:name", put(set_script).delete(remove_script))
+        .with_state(state);
+    Ok(router)
+}
+
+/// Returns the scripts registered to run in the installed system and their triggers.
+///
+/// * `state` : service state.
+#[utoipa::path(
+    get,
+    path = "/config",
+    context_path = "/api/scripts",
+    operation_id = "get_scripts_config",
+    responses(
+        (status = 200, description = "Registered scripts", body = ScriptsSettings),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn get_config(State(state): State<ScriptsState<'_>>) -> Result<Json<ScriptsSettings>, Error> {
+    Ok(Json(state.client.get_config().await?))
+}
+
+/// Sets the scripts to run in the installed system, replacing the registered ones.
+///
+/// The scripts cannot be empty and their shells must exist.
+///
+/// * `state`: service state.
+/// * `settings`: scripts to register.
+#[utoipa::path(
+    put,
+    path = "/config",
+    context_path = "/api/scripts",
+    operation_id = "set_scripts_config",
+    responses(
+        (status = 200, description = "Set the scripts"),
+        (status = 400, description = "The D-Bus service could not perform the action"),
+        (status = 422, description = "Invalid scripts. Details are in body", body = Vec<String>),
+    )
+)]
+async fn set_config(
+    State(state): State<ScriptsState<'_>>,
+    Json(settings): Json<ScriptsSettings>,
+) -> Result<impl IntoResponse, Error> {
+    update_config(&state, settings).await
+}
+
+/// Registers a script, replacing the existing one with the same name.
+///
+/// * `state`: service state.
+/// * `name`: script name.
+/// * `script`: script to register.
+#[utoipa::path(
+    put,
+    path = "/:name",
+    context_path = "/api/scripts",
+    params(("name" = String, Path, description = "Script name")),
+    responses(
+        (status = 200, description = "Registered the script"),
+        (status = 400, description = "The D-Bus service could not perform the action"),
+        (status = 422, description = "Invalid script. Details are in body", body = Vec<String>),
+    )
+)]
+async fn set_script(
+    State(state): State<ScriptsState<'_>>,
+    Path(name): Path<String>,
+    Json(script): Json<Script>,
+) -> Result<impl IntoResponse, Error> {
+    let mut settings = state.client.get_config().await?;
+    settings.set_script(Script { name, ..script });
+    update_config(&state, settings).await
+}
+
+/// Unregisters a script.
+///
+/// * `state`: service state.
+/// * `name`: script name.
+#[utoipa::path(
+    delete,
+    path = "/:name",
+    context_path = "/api/scripts",
+    params(("name" = String, Path, description = "Script name")),
+    responses(
+        (status = 200, description = "Unregistered the script"),
+        (status = 404, description = "There is no script with the given name"),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn remove_script(
+    State(state): State<ScriptsState<'_>>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, Error> {
+    let mut settings = state.client.get_config().await?;
+    if !settings.remove_script(&name) {
+        return Ok((StatusCode::NOT_FOUND, ().into_response()));
+    }
+    state.client.set_config(&settings).await?;
+    Ok((StatusCode::OK, ().into_response()))
+}
+
+async fn update_config(
+    state: &ScriptsState<'_>,
+    settings: ScriptsSettings,
+) -> Result<(StatusCode, Response), Error> {
+    let issues = settings.validate();
+    if !issues.is_empty() {
+        return Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(issues).into_response(),
+        ));
+    }
+
+    state.client.set_config(&settings).await?;
+    Ok((StatusCode::OK, ().into_response()))
+}
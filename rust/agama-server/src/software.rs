@@ -18,5 +18,9 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
+pub mod certificates;
+mod iso;
+pub mod release_notes;
+pub mod repo_check;
 pub mod web;
 pub use web::{software_service, software_streams};
@@ -0,0 +1,316 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Handles the additional CA certificates trusted during the installation.
+//!
+//! The certificates are written to [ANCHORS_DIR] and the system trust store is regenerated, so
+//! they are used by the software service to fetch the repositories and to register the product,
+//! as well as by the repository checks. As the installation system lives in memory, they are
+//! only trusted for the current session and they are not copied to the installed system.
+
+use openssl::{
+    asn1::Asn1Time,
+    hash::MessageDigest,
+    x509::{X509NameRef, X509Ref, X509},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Directory of the additional trust anchors.
+pub const ANCHORS_DIR: &str = "/etc/pki/trust/anchors";
+
+/// Prefix of the files written by Agama, to tell them apart from the system ones.
+const FILE_PREFIX: &str = "agama-";
+
+#[derive(thiserror::Error, Debug)]
+pub enum CertificatesError {
+    #[error("Could not write the certificate: {0}")]
+    Write(#[from] io::Error),
+    #[error("Could not encode the certificate: {0}")]
+    Encode(#[from] openssl::error::ErrorStack),
+    #[error("Could not update the trust store: {0}")]
+    Update(String),
+}
+
+/// CA certificates to trust.
+#[derive(Clone, Deserialize, utoipa::ToSchema)]
+pub struct CertificatesParams {
+    /// One or more PEM-encoded certificates.
+    pub pem: String,
+}
+
+/// Certificate trusted during the installation.
+#[derive(Clone, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedCertificate {
+    /// Subject (e.g., "CN=Example CA, O=Example").
+    pub subject: String,
+    pub issuer: String,
+    /// SHA-256 fingerprint, as colon-separated hexadecimal bytes.
+    pub fingerprint: String,
+    /// End of the validity period.
+    pub not_after: String,
+}
+
+impl TrustedCertificate {
+    fn new(cert: &X509Ref) -> Self {
+        Self {
+            subject: name_to_string(cert.subject_name()),
+            issuer: name_to_string(cert.issuer_name()),
+            fingerprint: fingerprint(cert),
+            not_after: cert.not_after().to_string(),
+        }
+    }
+}
+
+/// Parses and validates the given PEM-encoded certificates.
+///
+/// It returns the list of problems if any certificate is not valid.
+///
+/// * `pem`: one or more PEM-encoded certificates.
+pub fn parse(pem: &str) -> Result<Vec<X509>, Vec<String>> {
+    let certs = X509::stack_from_pem(pem.as_bytes())
+        .map_err(|e| vec![format!("Invalid PEM data: {}", e)])?;
+    if certs.is_empty() {
+        return Err(vec!["No certificates found".to_string()]);
+    }
+
+    let mut issues = vec![];
+    let now = Asn1Time::days_from_now(0).map_err(|e| vec![e.to_string()])?;
+    for cert in &certs {
+        let subject = name_to_string(cert.subject_name());
+        if cert.not_after() < now {
+            issues.push(format!("The certificate '{}' has expired", subject));
+        } else if cert.not_before() > now {
+            issues.push(format!("The certificate '{}' is not valid yet", subject));
+        }
+    }
+
+    if issues.is_empty() {
+        Ok(certs)
+    } else {
+        Err(issues)
+    }
+}
+
+/// Certificates written to a trust anchors directory.
+#[derive(Clone)]
+pub struct TrustedCertificates {
+    dir: PathBuf,
+}
+
+impl Default for TrustedCertificates {
+    fn default() -> Self {
+        Self::new(ANCHORS_DIR)
+    }
+}
+
+impl TrustedCertificates {
+    /// * `dir`: trust anchors directory.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Returns the certificates added during the installation.
+    pub fn list(&self) -> Vec<TrustedCertificate> {
+        self.read()
+            .iter()
+            .map(|cert| TrustedCertificate::new(cert))
+            .collect()
+    }
+
+    /// Writes the given certificates to the anchors directory.
+    ///
+    /// Adding an already trusted certificate has no effect. It returns the certificates which
+    /// were not trusted yet. The trust store is not updated (see [update_trust_store]).
+    ///
+    /// * `certs`: certificates to add.
+    pub fn add(&self, certs: &[X509]) -> Result<Vec<TrustedCertificate>, CertificatesError> {
+        fs::create_dir_all(&self.dir)?;
+        let mut added = vec![];
+        for cert in certs {
+            let trusted = TrustedCertificate::new(cert);
+            let name: String = trusted.fingerprint.replace(':', "").to_lowercase();
+            let path = self.dir.join(format!("{}{}.pem", FILE_PREFIX, &name[..16]));
+            if path.exists() {
+                continue;
+            }
+            fs::write(path, cert.to_pem()?)?;
+            added.push(trusted);
+        }
+        Ok(added)
+    }
+
+    /// Returns the certificates added during the installation.
+    pub fn read(&self) -> Vec<X509> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return vec![];
+        };
+
+        let mut paths: Vec<_> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(FILE_PREFIX) && n.ends_with(".pem"))
+            })
+            .collect();
+        paths.sort();
+
+        paths
+            .iter()
+            .filter_map(|path| match fs::read(path).map(|c| X509::from_pem(&c)) {
+                Ok(Ok(cert)) => Some(cert),
+                _ => {
+                    log::warn!("Could not read the certificate {}", path.display());
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// Regenerates the system trust store, so the added certificates are used.
+pub fn update_trust_store() -> Result<(), CertificatesError> {
+    let output = Command::new("update-ca-certificates")
+        .output()
+        .map_err(|e| CertificatesError::Update(e.to_string()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(CertificatesError::Update(stderr.trim().to_string()));
+    }
+    Ok(())
+}
+
+fn fingerprint(cert: &X509Ref) -> String {
+    cert.digest(MessageDigest::sha256())
+        .map(|digest| {
+            digest
+                .iter()
+                .map(|b| format!("{:02X}", b))
+                .collect::<Vec<_>>()
+                .join(":")
+        })
+        .unwrap_or_default()
+}
+
+fn name_to_string(name: &X509NameRef) -> String {
+    name.entries()
+        .map(|entry| {
+            let key = entry.object().nid().short_name().unwrap_or("?");
+            let value = entry
+                .data()
+                .as_utf8()
+                .map(|v| v.to_string())
+                .unwrap_or_default();
+            format!("{}={}", key, value)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::{
+        nid::Nid,
+        pkey::PKey,
+        rsa::Rsa,
+        x509::{X509Builder, X509NameBuilder},
+    };
+
+    fn certificate(name: &str, not_after: Asn1Time) -> X509 {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut subject = X509NameBuilder::new().unwrap();
+        subject
+            .append_entry_by_nid(Nid::ORGANIZATIONNAME, "Example")
+            .unwrap();
+        subject.append_entry_by_nid(Nid::COMMONNAME, name).unwrap();
+        let subject = subject.build();
+
+        let mut builder = X509Builder::new().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_subject_name(&subject).unwrap();
+        builder.set_issuer_name(&subject).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder
+            .set_not_before(&Asn1Time::from_unix(0).unwrap())
+            .unwrap();
+        builder.set_not_after(&not_after).unwrap();
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+        builder.build()
+    }
+
+    fn valid_certificate(name: &str) -> X509 {
+        certificate(name, Asn1Time::days_from_now(30).unwrap())
+    }
+
+    fn to_pem(cert: &X509) -> String {
+        String::from_utf8(cert.to_pem().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let pem = format!(
+            "{}{}",
+            to_pem(&valid_certificate("Example CA")),
+            to_pem(&valid_certificate("Other CA"))
+        );
+        assert_eq!(parse(&pem).unwrap().len(), 2);
+
+        let issues = parse(&to_pem(&certificate(
+            "Old CA",
+            Asn1Time::from_unix(3600).unwrap(),
+        )))
+        .unwrap_err();
+        assert!(issues[0].contains("'O=Example, CN=Old CA' has expired"));
+
+        assert!(parse("not a certificate").is_err());
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn test_add_and_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let certificates = TrustedCertificates::new(dir.path());
+        assert!(certificates.list().is_empty());
+
+        let cert = valid_certificate("Example CA");
+        let added = certificates.add(&[cert.clone(), cert.clone()]).unwrap();
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].subject, "O=Example, CN=Example CA");
+        assert_eq!(added[0].fingerprint.len(), 95);
+
+        let other = valid_certificate("Other CA");
+        let added_again = certificates.add(&[cert, other]).unwrap();
+        assert_eq!(added_again.len(), 1);
+        assert_eq!(added_again[0].subject, "O=Example, CN=Other CA");
+
+        fs::write(dir.path().join("system.pem"), "ignored").unwrap();
+        assert_eq!(certificates.list().len(), 2);
+    }
+}
@@ -0,0 +1,206 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Handles the ISO images backing the additional repositories.
+//!
+//! The images are loop-mounted under [MOUNT_DIR] and registered in the software service as
+//! local directories. They are unmounted when the repositories are replaced or removed.
+//!
+//! Mounting and unmounting run in blocking tasks, so they do not block the async runtime.
+
+use agama_lib::software::model::{has_repository_metadata, RepositoryConfig, RepositoryType};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+};
+use tokio::sync::Mutex;
+
+/// Directory where the ISO images are mounted.
+pub const MOUNT_DIR: &str = "/run/agama/repos";
+
+#[derive(thiserror::Error, Debug)]
+pub enum IsoMountError {
+    #[error("Could not mount the ISO image for repository '{0}': {1}")]
+    Mount(String, String),
+    #[error("No repository metadata found in the ISO image for repository '{0}'")]
+    NoMetadata(String),
+    #[error("The previous ISO image for repository '{0}' could not be unmounted")]
+    Busy(String),
+}
+
+/// Mounted ISO image.
+#[derive(Clone, Debug)]
+struct IsoMount {
+    /// Path of the image.
+    iso: PathBuf,
+    /// Original URL of the repository.
+    url: String,
+    /// Where the image is mounted.
+    mount_point: PathBuf,
+}
+
+/// Keeps track of the mounted ISO images, indexed by repository alias.
+#[derive(Clone, Default)]
+pub struct IsoMounts {
+    mounts: Arc<Mutex<HashMap<String, IsoMount>>>,
+}
+
+impl IsoMounts {
+    /// Mounts the ISO images of the given repositories and unmounts the ones which are not used
+    /// anymore.
+    ///
+    /// It returns the repositories to register, where the URLs of the ISO-backed ones point to
+    /// their mount points.
+    ///
+    /// * `repositories`: repositories to use.
+    pub async fn prepare(
+        &self,
+        repositories: &[RepositoryConfig],
+    ) -> Result<Vec<RepositoryConfig>, IsoMountError> {
+        let mut mounts = self.mounts.lock().await;
+        let wanted: HashMap<&str, PathBuf> = repositories
+            .iter()
+            .filter(|r| r.repository_type() == RepositoryType::Iso)
+            .filter_map(|r| Some((r.alias.as_str(), r.local_path()?)))
+            .collect();
+
+        // the images which could not be unmounted are kept, so it is tried again later
+        let unused: Vec<(String, PathBuf)> = mounts
+            .iter()
+            .filter(|(alias, mount)| wanted.get(alias.as_str()) != Some(&mount.iso))
+            .map(|(alias, mount)| (alias.clone(), mount.mount_point.clone()))
+            .collect();
+        for (alias, mount_point) in unused {
+            let unmounted = tokio::task::spawn_blocking(move || unmount(&mount_point))
+                .await
+                .unwrap_or(false);
+            if unmounted {
+                mounts.remove(&alias);
+            }
+        }
+
+        let mut result = vec![];
+        for repo in repositories {
+            let Some(iso) = wanted.get(repo.alias.as_str()) else {
+                result.push(repo.clone());
+                continue;
+            };
+
+            let mount = match mounts.get(&repo.alias) {
+                Some(mount) if &mount.iso == iso => mount.clone(),
+                Some(_) => return Err(IsoMountError::Busy(repo.alias.clone())),
+                None => {
+                    let (repo, iso) = (repo.clone(), iso.clone());
+                    let alias = repo.alias.clone();
+                    let mount = tokio::task::spawn_blocking(move || mount_iso(&repo, &iso))
+                        .await
+                        .map_err(|e| IsoMountError::Mount(alias.clone(), e.to_string()))??;
+                    mounts.insert(alias, mount.clone());
+                    mount
+                }
+            };
+            result.push(RepositoryConfig {
+                url: format!("dir://{}", mount.mount_point.display()),
+                ..repo.clone()
+            });
+        }
+        Ok(result)
+    }
+
+    /// Restores the original URLs of the ISO-backed repositories and adds the repository type
+    /// and source.
+    ///
+    /// * `repositories`: repositories as registered in the software service.
+    pub async fn restore(&self, repositories: Vec<RepositoryConfig>) -> Vec<RepositoryConfig> {
+        let mounts = self.mounts.lock().await;
+        repositories
+            .into_iter()
+            .map(|mut repo| {
+                if let Some(mount) = mounts.get(&repo.alias) {
+                    if repo.local_path().as_ref() == Some(&mount.mount_point) {
+                        repo.url.clone_from(&mount.url);
+                    }
+                }
+                repo.with_source()
+            })
+            .collect()
+    }
+}
+
+/// Mounts (read-only) the ISO image of a repository.
+///
+/// * `repo`: repository backed by the image. Its alias is used to build the mount point.
+/// * `iso`: path of the ISO image.
+fn mount_iso(repo: &RepositoryConfig, iso: &Path) -> Result<IsoMount, IsoMountError> {
+    let alias = repo.alias.as_str();
+    let mount_point = Path::new(MOUNT_DIR).join(alias);
+    let mount_error = |e: io::Error| IsoMountError::Mount(alias.to_string(), e.to_string());
+
+    fs::create_dir_all(&mount_point).map_err(mount_error)?;
+    let output = Command::new("mount")
+        .args(["-o", "loop,ro"])
+        .arg(iso)
+        .arg(&mount_point)
+        .output()
+        .map_err(mount_error)?;
+    if !output.status.success() {
+        _ = fs::remove_dir(&mount_point);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(IsoMountError::Mount(
+            alias.to_string(),
+            stderr.trim().to_string(),
+        ));
+    }
+
+    if !has_repository_metadata(&mount_point) {
+        unmount(&mount_point);
+        return Err(IsoMountError::NoMetadata(alias.to_string()));
+    }
+
+    Ok(IsoMount {
+        iso: iso.to_path_buf(),
+        url: repo.url.clone(),
+        mount_point,
+    })
+}
+
+/// Unmounts an ISO image and removes its mount point.
+///
+/// It returns whether the image was unmounted. Errors are only logged, as they should not prevent
+/// using the rest of repositories.
+fn unmount(mount_point: &Path) -> bool {
+    match Command::new("umount").arg(mount_point).status() {
+        Ok(status) if status.success() => {
+            _ = fs::remove_dir(mount_point);
+            true
+        }
+        Ok(status) => {
+            log::warn!("Could not unmount {}: {}", mount_point.display(), status);
+            false
+        }
+        Err(error) => {
+            log::warn!("Could not unmount {}: {}", mount_point.display(), error);
+            false
+        }
+    }
+}
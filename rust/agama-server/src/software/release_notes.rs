@@ -0,0 +1,216 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Fetches the release notes of the products in the language of the user interface.
+//!
+//! The products list the location of their release notes for each language. When there are no
+//! notes in the language of the user interface, the English version is used. The fetched notes
+//! are kept in memory for the rest of the session.
+
+use agama_lib::product::Product;
+use serde::Serialize;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::Mutex;
+
+/// Language to use when there are no release notes in the language of the user interface.
+pub const FALLBACK_LANGUAGE: &str = "en";
+
+/// Maximum time to wait for the release notes.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(thiserror::Error, Debug)]
+pub enum ReleaseNotesError {
+    #[error("Release notes are not available for product '{0}'")]
+    NotAvailable(String),
+    #[error("Could not read the release notes from '{0}': {1}")]
+    Read(String, String),
+}
+
+/// Release notes of a product.
+#[derive(Clone, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseNotes {
+    /// Product ID (e.g., "Tumbleweed").
+    pub product: String,
+    /// Language of the release notes (e.g., "en", "pt_BR").
+    pub language: String,
+    /// Location of the release notes.
+    pub url: String,
+    /// Content of the release notes.
+    pub content: String,
+}
+
+/// Returns the languages to look for, from the most to the least specific one.
+///
+/// The encoding and the modifier of the locale are ignored and the fallback language is always
+/// the last candidate (e.g., "pt_BR.UTF-8" gives "pt_BR", "pt" and "en").
+///
+/// * `locale`: locale of the user interface.
+pub fn language_candidates(locale: &str) -> Vec<String> {
+    let locale = locale
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('-', "_");
+    let mut candidates = vec![];
+    if !locale.is_empty() {
+        candidates.push(locale.clone());
+        if let Some((language, _)) = locale.split_once('_') {
+            candidates.push(language.to_string());
+        }
+    }
+    candidates.push(FALLBACK_LANGUAGE.to_string());
+    candidates.dedup();
+    candidates
+}
+
+/// Keeps the release notes fetched during the session, indexed by product and language.
+#[derive(Clone, Default)]
+pub struct ReleaseNotesCache {
+    notes: Arc<Mutex<HashMap<(String, String), ReleaseNotes>>>,
+}
+
+impl ReleaseNotesCache {
+    /// Returns the release notes of a product in the most suitable language.
+    ///
+    /// If the notes in a language cannot be read, the next candidate is tried. The cache is not
+    /// locked while fetching the notes, so a slow server does not block other requests.
+    ///
+    /// * `product`: product to get the release notes for.
+    /// * `locale`: locale of the user interface.
+    pub async fn get(
+        &self,
+        product: &Product,
+        locale: &str,
+    ) -> Result<ReleaseNotes, ReleaseNotesError> {
+        let mut error = ReleaseNotesError::NotAvailable(product.id.clone());
+        for language in language_candidates(locale) {
+            let key = (product.id.clone(), language.clone());
+            if let Some(cached) = self.notes.lock().await.get(&key) {
+                return Ok(cached.clone());
+            }
+            let Some(url) = product.release_notes.get(&language) else {
+                continue;
+            };
+            match fetch(url).await {
+                Ok(content) => {
+                    let release_notes = ReleaseNotes {
+                        product: product.id.clone(),
+                        language,
+                        url: url.clone(),
+                        content,
+                    };
+                    self.notes.lock().await.insert(key, release_notes.clone());
+                    return Ok(release_notes);
+                }
+                Err(e) => {
+                    log::warn!("Could not fetch the release notes from {}: {}", url, e);
+                    error = e;
+                }
+            }
+        }
+        Err(error)
+    }
+}
+
+/// Reads the release notes from a remote server (HTTP or HTTPS) or from a local file.
+///
+/// * `url`: location of the release notes.
+async fn fetch(url: &str) -> Result<String, ReleaseNotesError> {
+    let read_error =
+        |e: &dyn std::fmt::Display| ReleaseNotesError::Read(url.to_string(), e.to_string());
+
+    if url.starts_with("http://") || url.starts_with("https://") {
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .map_err(|e| read_error(&e))?;
+        let response = client
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| read_error(&e))?;
+        return response.text().await.map_err(|e| read_error(&e));
+    }
+
+    let path = url.strip_prefix("file://").unwrap_or(url);
+    if !path.starts_with('/') {
+        return Err(read_error(&"unsupported location"));
+    }
+    std::fs::read_to_string(path).map_err(|e| read_error(&e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_candidates() {
+        assert_eq!(
+            language_candidates("pt_BR.UTF-8"),
+            vec!["pt_BR", "pt", "en"]
+        );
+        assert_eq!(language_candidates("de"), vec!["de", "en"]);
+        assert_eq!(
+            language_candidates("sr_RS@latin"),
+            vec!["sr_RS", "sr", "en"]
+        );
+        assert_eq!(language_candidates("en_US.UTF-8"), vec!["en_US", "en"]);
+        assert_eq!(language_candidates(""), vec!["en"]);
+    }
+
+    #[tokio::test]
+    async fn test_release_notes_fallback() {
+        let root = tempfile::tempdir().unwrap();
+        let english = root.path().join("en.txt");
+        std::fs::write(&english, "Release notes").unwrap();
+        let product = Product {
+            id: "Tumbleweed".to_string(),
+            release_notes: HashMap::from([
+                ("en".to_string(), english.to_string_lossy().to_string()),
+                ("cs".to_string(), "/missing/cs.txt".to_string()),
+            ]),
+            ..Default::default()
+        };
+
+        let cache = ReleaseNotesCache::default();
+        let notes = cache.get(&product, "de_DE.UTF-8").await.unwrap();
+        assert_eq!(notes.language, "en");
+        assert_eq!(notes.content, "Release notes");
+
+        // the Czech notes cannot be read, so the English ones are used
+        let notes = cache.get(&product, "cs_CZ.UTF-8").await.unwrap();
+        assert_eq!(notes.language, "en");
+
+        // the notes are kept for the session
+        std::fs::remove_file(&english).unwrap();
+        assert!(cache.get(&product, "en_US.UTF-8").await.is_ok());
+
+        let product = Product {
+            id: "MicroOS".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            cache.get(&product, "en_US.UTF-8").await,
+            Err(ReleaseNotesError::NotAvailable(_))
+        ));
+    }
+}
@@ -0,0 +1,331 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Checks whether an online repository can be reached and whether its metadata has a signature.
+//!
+//! Only the repository metadata (`repodata/repomd.xml`), its signature and its public key are
+//! downloaded. The signature is not verified and the key is not imported: its fingerprint is
+//! reported so the user can decide whether to trust it. The requests go through the proxy
+//! configured in [SYSCONFIG_PROXY] and the CA certificates added during the installation are
+//! trusted.
+
+use super::certificates::TrustedCertificates;
+use openssl::{base64, sha::sha1};
+use serde::{Deserialize, Serialize};
+use std::{fs, time::Duration};
+
+/// Proxy configuration of the installation system.
+pub const SYSCONFIG_PROXY: &str = "/etc/sysconfig/proxy";
+
+/// Maximum time to wait for each response.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Path of the metadata, relative to the repository URL.
+const REPOMD_PATH: &str = "repodata/repomd.xml";
+
+/// OpenPGP packet tag of a public key.
+const PUBLIC_KEY_TAG: u8 = 6;
+
+#[derive(Clone, Deserialize, utoipa::ToSchema)]
+pub struct RepositoryCheckParams {
+    /// URL of the repository (only HTTP and HTTPS are supported).
+    pub url: String,
+}
+
+/// Result of checking a repository.
+#[derive(Clone, Debug, Default, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositoryCheck {
+    /// Whether the repository metadata could be downloaded.
+    pub reachable: bool,
+    /// HTTP status of the metadata request, if the server answered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Whether the metadata has a detached signature (`repomd.xml.asc`). The signature is not
+    /// verified.
+    pub signature_present: bool,
+    /// Fingerprint of the key published with the metadata (`repomd.xml.key`), if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_fingerprint: Option<String>,
+    /// Problem found, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Proxy settings, as written by the `proxy` boot option.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct ProxySettings {
+    http: Option<String>,
+    https: Option<String>,
+    no_proxy: Option<String>,
+}
+
+impl ProxySettings {
+    /// Reads the settings from the content of a sysconfig file.
+    ///
+    /// It returns `None` if the proxy is disabled (`PROXY_ENABLED="no"`).
+    fn from_sysconfig(content: &str) -> Option<Self> {
+        let mut settings = Self::default();
+        for line in content.lines() {
+            let Some((key, value)) = line.trim().split_once('=') else {
+                continue;
+            };
+            let value = value.trim().trim_matches('"').to_string();
+            let value = (!value.is_empty()).then_some(value);
+            match key.trim() {
+                "PROXY_ENABLED" if value.as_deref() == Some("no") => return None,
+                "HTTP_PROXY" => settings.http = value,
+                "HTTPS_PROXY" => settings.https = value,
+                "NO_PROXY" => settings.no_proxy = value,
+                _ => {}
+            }
+        }
+        Some(settings)
+    }
+
+    fn read() -> Option<Self> {
+        Self::from_sysconfig(&fs::read_to_string(SYSCONFIG_PROXY).ok()?)
+    }
+
+    /// Builds the HTTP client using these settings.
+    ///
+    /// Without settings, the proxy environment variables are used.
+    ///
+    /// * `timeout`: maximum time to wait for each response.
+    fn client(settings: Option<Self>, timeout: Duration) -> reqwest::Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder().timeout(timeout);
+        for cert in TrustedCertificates::default().read() {
+            if let Ok(pem) = cert.to_pem() {
+                builder = builder.add_root_certificate(reqwest::Certificate::from_pem(&pem)?);
+            }
+        }
+        if let Some(settings) = settings {
+            let no_proxy = settings
+                .no_proxy
+                .as_deref()
+                .and_then(reqwest::NoProxy::from_string);
+            if let Some(url) = &settings.http {
+                builder = builder.proxy(reqwest::Proxy::http(url)?.no_proxy(no_proxy.clone()));
+            }
+            if let Some(url) = &settings.https {
+                builder = builder.proxy(reqwest::Proxy::https(url)?.no_proxy(no_proxy));
+            }
+        }
+        builder.build()
+    }
+}
+
+/// Builds an HTTP client which uses the proxy of the installation system and trusts the CA
+/// certificates added during the installation.
+///
+/// * `timeout`: maximum time to wait for each response.
+pub fn http_client(timeout: Duration) -> reqwest::Result<reqwest::Client> {
+    ProxySettings::client(ProxySettings::read(), timeout)
+}
+
+/// Returns the problem with the given URL, if it cannot be checked.
+pub fn validate_url(url: &str) -> Option<String> {
+    match reqwest::Url::parse(url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => None,
+        Ok(url) => Some(format!(
+            "Only HTTP and HTTPS repositories can be checked, not '{}'",
+            url.scheme()
+        )),
+        Err(error) => Some(format!("Invalid repository URL '{}': {}", url, error)),
+    }
+}
+
+/// Checks the repository at the given URL.
+///
+/// * `url`: repository URL, already validated with [validate_url].
+pub async fn check_repository(url: &str) -> RepositoryCheck {
+    let client = match http_client(CHECK_TIMEOUT) {
+        Ok(client) => client,
+        Err(error) => {
+            return RepositoryCheck {
+                error: Some(format!("Invalid proxy settings: {}", error)),
+                ..Default::default()
+            }
+        }
+    };
+    let repomd = format!("{}/{}", url.trim_end_matches('/'), REPOMD_PATH);
+
+    let mut result = RepositoryCheck::default();
+    match client.get(&repomd).send().await {
+        Ok(response) => {
+            result.status = Some(response.status().as_u16());
+            result.reachable = response.status().is_success();
+            if !result.reachable {
+                result.error = Some(format!("Could not download {}", repomd));
+                return result;
+            }
+        }
+        Err(error) => {
+            result.error = Some(error.to_string());
+            return result;
+        }
+    }
+
+    result.signature_present = fetch(&client, &format!("{}.asc", repomd)).await.is_some();
+    result.key_fingerprint = fetch(&client, &format!("{}.key", repomd))
+        .await
+        .as_deref()
+        .and_then(key_fingerprint);
+    if !result.signature_present {
+        result.error = Some("The repository metadata has no signature".to_string());
+    }
+    result
+}
+
+/// Downloads a file, returning `None` if it is not available.
+async fn fetch(client: &reqwest::Client, url: &str) -> Option<String> {
+    let response = client.get(url).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    response.text().await.ok()
+}
+
+/// Returns the fingerprint of the first key in an ASCII-armored OpenPGP public key block.
+///
+/// Only version 4 keys are supported.
+fn key_fingerprint(armored: &str) -> Option<String> {
+    let body: String = armored
+        .lines()
+        .map(str::trim)
+        .skip_while(|l| !l.starts_with("-----BEGIN PGP PUBLIC KEY BLOCK"))
+        .skip(1)
+        // skip the armor headers
+        .skip_while(|l| !l.is_empty() && l.contains(": "))
+        .take_while(|l| !l.starts_with("-----END"))
+        .filter(|l| !l.is_empty() && !l.starts_with('='))
+        .collect();
+    let data = base64::decode_block(&body).ok()?;
+
+    let (tag, packet) = first_packet(&data)?;
+    if tag != PUBLIC_KEY_TAG || packet.first() != Some(&4) {
+        return None;
+    }
+    let length = u16::try_from(packet.len()).ok()?;
+    let mut hashed = vec![0x99];
+    hashed.extend(length.to_be_bytes());
+    hashed.extend(packet);
+    Some(sha1(&hashed).iter().map(|b| format!("{:02X}", b)).collect())
+}
+
+/// Returns the tag and the body of the first OpenPGP packet.
+fn first_packet(data: &[u8]) -> Option<(u8, &[u8])> {
+    let header = *data.first()?;
+    if header & 0x80 == 0 {
+        return None;
+    }
+
+    let (tag, length, offset) = if header & 0x40 == 0 {
+        // old format
+        let tag = (header >> 2) & 0x0f;
+        match header & 0x03 {
+            0 => (tag, *data.get(1)? as usize, 2),
+            1 => (
+                tag,
+                u16::from_be_bytes([*data.get(1)?, *data.get(2)?]) as usize,
+                3,
+            ),
+            2 => (
+                tag,
+                u32::from_be_bytes(data.get(1..5)?.try_into().ok()?) as usize,
+                5,
+            ),
+            _ => return None,
+        }
+    } else {
+        let tag = header & 0x3f;
+        match *data.get(1)? as usize {
+            first @ 0..=191 => (tag, first, 2),
+            first @ 192..=223 => (tag, ((first - 192) << 8) + *data.get(2)? as usize + 192, 3),
+            255 => (
+                tag,
+                u32::from_be_bytes(data.get(2..6)?.try_into().ok()?) as usize,
+                6,
+            ),
+            _ => return None,
+        }
+    };
+    Some((tag, data.get(offset..offset + length)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEas9eBBYJKwYBBAHaRw8BAQdApu8UePr55l/u91CYq6jaTw0d7qVYDdfUQOTv
+ZKVUkou0HUFnYW1hIFRlc3QgPHRlc3RAZXhhbXBsZS5uZXQ+iJAEExYIADgWIQR+
+3Ga8D5D6wELw20FC989TafgcNwUCas9eBAIbAwULCQgHAgYVCgkICwIEFgIDAQIe
+AQIXgAAKCRBC989TafgcN30cAQD8fb6f4oa3LPIpIykf24AW/EoOja6a73ILzxHj
+jmNtFwD/T2SFejiYeTKkhBHvvv5hIP3qz+DuU/0J/nIo8d5fEQY=
+=T2Kk
+-----END PGP PUBLIC KEY BLOCK-----
+";
+
+    #[test]
+    fn test_key_fingerprint() {
+        assert_eq!(
+            key_fingerprint(KEY).as_deref(),
+            Some("7EDC66BC0F90FAC042F0DB4142F7CF5369F81C37")
+        );
+        assert_eq!(key_fingerprint("not a key"), None);
+    }
+
+    #[test]
+    fn test_proxy_settings() {
+        let content = r#"
+PROXY_ENABLED="yes"
+HTTP_PROXY="http://proxy.example.net:3128"
+HTTPS_PROXY=""
+NO_PROXY="localhost, 127.0.0.1"
+"#;
+        assert_eq!(
+            ProxySettings::from_sysconfig(content),
+            Some(ProxySettings {
+                http: Some("http://proxy.example.net:3128".to_string()),
+                https: None,
+                no_proxy: Some("localhost, 127.0.0.1".to_string()),
+            })
+        );
+        assert_eq!(
+            ProxySettings::from_sysconfig("PROXY_ENABLED=\"no\"\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_validate_url() {
+        assert_eq!(
+            validate_url("https://download.opensuse.org/tumbleweed/repo/oss"),
+            None
+        );
+        assert_eq!(
+            validate_url("dir:///mnt/repo"),
+            Some("Only HTTP and HTTPS repositories can be checked, not 'dir'".to_string())
+        );
+        assert!(validate_url("not a url").is_some());
+    }
+}
@@ -25,31 +25,54 @@
 //! * `software_service` which returns the Axum service.
 //! * `software_stream` which offers an stream that emits the software events coming from D-Bus.
 
+use super::{
+    certificates::{self, CertificatesParams, TrustedCertificate, TrustedCertificates},
+    iso::IsoMounts,
+    release_notes::{ReleaseNotesCache, ReleaseNotesError, FALLBACK_LANGUAGE},
+    repo_check::{check_repository, validate_url, RepositoryCheckParams},
+};
 use crate::{
     error::Error,
+    manager::facts::HardwareFacts,
     web::{
-        common::{issues_router, progress_router, service_status_router, EventStreams},
-        Event,
+        common::{
+            issues_router, operations_router, progress_router, service_status_router, ApplyParams,
+            EventStreams, Operations,
+        },
+        generous_timeout, no_timeout, CapabilitiesProvider, Event, EventsSender,
+        ModuleCapabilities, SnapshotProvider,
     },
 };
 
 use agama_lib::{
     error::ServiceError,
-    product::{proxies::RegistrationProxy, Product, ProductClient},
+    localization::LocaleProxy,
+    product::{
+        check_requirements, proxies::RegistrationProxy, Product, ProductClient,
+        RegistrationRequirement, SystemResources,
+    },
     software::{
-        model::{RegistrationInfo, RegistrationParams, SoftwareConfig},
+        model::{
+            validate_repositories, validate_services, ArchitectureParams, ArchitectureSettings,
+            PackageChange, RegistrationInfo, RegistrationParams, ServiceAction, ServiceConfig,
+            SoftwareConfig, SubscriptionStatus,
+        },
+        only_required_warnings,
         proxies::{Software1Proxy, SoftwareProductProxy},
         Pattern, SelectedBy, SoftwareClient, UnknownSelectedBy,
     },
+    storage::StorageClient,
 };
+use async_trait::async_trait;
 use axum::{
-    extract::State,
+    extract::{Query, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     routing::{get, post, put},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::collections::HashMap;
 use tokio_stream::{Stream, StreamExt};
 
@@ -57,6 +80,11 @@ use tokio_stream::{Stream, StreamExt};
 struct SoftwareState<'a> {
     product: ProductClient<'a>,
     software: SoftwareClient<'a>,
+    storage: StorageClient<'a>,
+    locale: LocaleProxy<'a>,
+    iso_mounts: IsoMounts,
+    release_notes: ReleaseNotesCache,
+    operations: Operations,
 }
 
 /// Returns an stream that emits software related events coming from D-Bus.
@@ -86,6 +114,10 @@ pub async fn software_streams(dbus: zbus::Connection) -> Result<EventStreams, Er
             "registration_email_changed",
             Box::pin(registration_email_changed_stream(dbus.clone()).await?),
         ),
+        (
+            "package_progress",
+            Box::pin(package_progress_stream(dbus.clone()).await?),
+        ),
     ];
 
     Ok(result)
@@ -131,6 +163,30 @@ async fn patterns_changed_stream(
     Ok(stream)
 }
 
+/// Emits the progress of the packages installation.
+///
+/// The software service only reports the progress while installing the packages. The installation
+/// phase is not queried for each signal, as the manager service is busy during the installation.
+async fn package_progress_stream(
+    dbus: zbus::Connection,
+) -> Result<impl Stream<Item = Event>, Error> {
+    let proxy = Software1Proxy::new(&dbus).await?;
+    let stream = proxy
+        .receive_package_progress()
+        .await?
+        .filter_map(|signal| {
+            let args = signal.args().ok()?;
+            Some(Event::SoftwareProgress {
+                current_package: args.current_package.to_string(),
+                installed: args.installed,
+                total: args.total,
+                bytes_done: args.bytes_done,
+                bytes_total: args.bytes_total,
+            })
+        });
+    Ok(stream)
+}
+
 async fn registration_requirement_changed_stream(
     dbus: zbus::Connection,
 ) -> Result<impl Stream<Item = Event>, Error> {
@@ -202,7 +258,13 @@ fn reason_to_selected_by(
 }
 
 /// Sets up and returns the axum service for the software module.
-pub async fn software_service(dbus: zbus::Connection) -> Result<Router, ServiceError> {
+///
+/// * `dbus`: D-Bus connection.
+/// * `events`: channel to send the events (e.g., when a probing operation finishes).
+pub async fn software_service(
+    dbus: zbus::Connection,
+    events: EventsSender,
+) -> Result<Router, ServiceError> {
     const DBUS_SERVICE: &str = "org.opensuse.Agama.Software1";
     const DBUS_PATH: &str = "/org/opensuse/Agama/Software1";
     const DBUS_PRODUCT_PATH: &str = "/org/opensuse/Agama/Software1/Product";
@@ -213,26 +275,126 @@ pub async fn software_service(dbus: zbus::Connection) -> Result<Router, ServiceE
     let product_issues = issues_router(&dbus, DBUS_SERVICE, DBUS_PRODUCT_PATH).await?;
 
     let product = ProductClient::new(dbus.clone()).await?;
-    let software = SoftwareClient::new(dbus).await?;
-    let state = SoftwareState { product, software };
+    let software = SoftwareClient::new(dbus.clone()).await?;
+    let locale = LocaleProxy::new(&dbus).await?;
+    let storage = StorageClient::new(dbus).await?;
+    let state = SoftwareState {
+        product,
+        software,
+        storage,
+        locale,
+        iso_mounts: IsoMounts::default(),
+        release_notes: ReleaseNotesCache::default(),
+        operations: Operations::new("software", events),
+    };
+    let operations = operations_router(state.operations.clone());
     let router = Router::new()
         .route("/patterns", get(patterns))
         .route("/products", get(products))
+        .route("/product", generous_timeout(put(select_product)))
+        .route("/product/requirements", get(product_requirements))
+        .route(
+            "/product/release_notes",
+            generous_timeout(get(product_release_notes)),
+        )
         .route(
             "/registration",
             get(get_registration).post(register).delete(deregister),
         )
+        .route("/registration/status", get(subscription_status))
         .route("/proposal", get(proposal))
-        .route("/config", put(set_config).get(get_config))
-        .route("/probe", post(probe))
+        .route("/proposal/packages", get(package_changes))
+        .route("/architecture", get(get_architecture).put(set_architecture))
+        .route("/config", generous_timeout(put(set_config).get(get_config)))
+        .route("/probe", no_timeout(post(probe)))
+        .route(
+            "/repositories/test",
+            generous_timeout(post(test_repository)),
+        )
+        .route(
+            "/certificates",
+            get(get_certificates).post(add_certificates),
+        )
         .merge(status_router)
         .merge(progress_router)
         .nest("/issues/product", product_issues)
+        .nest("/operations", operations)
         .nest("/issues/software", software_issues)
         .with_state(state);
     Ok(router)
 }
 
+/// Checks whether a repository can be reached and whether its metadata has a signature.
+///
+/// The repository is not added, the signature is not verified and the key is not imported.
+///
+/// * `params`: repository to check.
+#[utoipa::path(
+    post,
+    path = "/repositories/test",
+    context_path = "/api/software",
+    request_body = RepositoryCheckParams,
+    responses(
+        (status = 200, description = "Result of the check", body = RepositoryCheck),
+        (status = 422, description = "Unsupported or invalid URL", body = Vec<String>)
+    )
+)]
+async fn test_repository(Json(params): Json<RepositoryCheckParams>) -> Response {
+    if let Some(issue) = validate_url(&params.url) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(vec![issue])).into_response();
+    }
+    Json(check_repository(&params.url).await).into_response()
+}
+
+/// Returns the CA certificates trusted during the installation.
+#[utoipa::path(
+    get,
+    path = "/certificates",
+    context_path = "/api/software",
+    responses(
+        (status = 200, description = "Additional CA certificates", body = Vec<TrustedCertificate>)
+    )
+)]
+async fn get_certificates() -> Json<Vec<TrustedCertificate>> {
+    Json(TrustedCertificates::default().list())
+}
+
+/// Trusts the given CA certificates for the rest of the installation.
+///
+/// They are used when fetching the repositories and registering the product. It returns the
+/// certificates which were not trusted yet.
+///
+/// * `params`: PEM-encoded certificates.
+#[utoipa::path(
+    post,
+    path = "/certificates",
+    context_path = "/api/software",
+    request_body = CertificatesParams,
+    responses(
+        (status = 200, description = "Added certificates (the already trusted ones are not included)", body = Vec<TrustedCertificate>),
+        (status = 422, description = "Invalid or expired certificates", body = Vec<String>),
+        (status = 400, description = "The certificates could not be trusted")
+    )
+)]
+async fn add_certificates(Json(params): Json<CertificatesParams>) -> Result<Response, Error> {
+    let certs = match certificates::parse(&params.pem) {
+        Ok(certs) => certs,
+        Err(issues) => return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(issues)).into_response()),
+    };
+
+    let added = tokio::task::spawn_blocking(move || {
+        let added = TrustedCertificates::default().add(&certs)?;
+        if !added.is_empty() {
+            certificates::update_trust_store()?;
+        }
+        Ok::<_, certificates::CertificatesError>(added)
+    })
+    .await
+    .map_err(|e| Error::Anyhow(e.to_string()))?
+    .map_err(|e| Error::Anyhow(e.to_string()))?;
+    Ok(Json(added).into_response())
+}
+
 /// Returns the list of available products.
 ///
 /// * `state`: service state.
@@ -250,6 +412,143 @@ async fn products(State(state): State<SoftwareState<'_>>) -> Result<Json<Vec<Pro
     Ok(Json(products))
 }
 
+#[derive(Clone, Deserialize, utoipa::ToSchema)]
+pub struct SelectProductParams {
+    /// ID of the product to install.
+    id: String,
+}
+
+/// Selects the product to install.
+///
+/// The software service adapts the repositories and the patterns to the new product and a
+/// `ProductChanged` event is emitted.
+///
+/// * `state`: service state.
+/// * `params`: product to select.
+#[utoipa::path(
+    put,
+    path = "/product",
+    context_path = "/api/software",
+    request_body = SelectProductParams,
+    responses(
+        (status = 200, description = "The product was selected"),
+        (status = 422, description = "Unknown product"),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn select_product(
+    State(state): State<SoftwareState<'_>>,
+    Json(params): Json<SelectProductParams>,
+) -> Result<Response, Error> {
+    let ids: Vec<String> = state
+        .product
+        .products()
+        .await?
+        .into_iter()
+        .map(|p| p.id)
+        .collect();
+    if !ids.contains(&params.id) {
+        let body = json!({
+            "error": format!("Unknown product: '{}'", params.id),
+            "available": ids
+        });
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response());
+    }
+
+    state.product.select_product(&params.id).await?;
+    Ok(Json(()).into_response())
+}
+
+/// Checks the system against the hardware requirements of the selected product.
+///
+/// It reports whether the memory, the biggest disk and the architecture pass the checks, are below
+/// the recommended values (warn) or make the installation impossible (fail).
+///
+/// * `state`: service state.
+#[utoipa::path(
+    get,
+    path = "/product/requirements",
+    context_path = "/api/software",
+    responses(
+        (status = 200, description = "Result of the checks", body = agama_lib::product::RequirementsReport),
+        (status = 422, description = "No product is selected"),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn product_requirements(State(state): State<SoftwareState<'_>>) -> Result<Response, Error> {
+    let selected = state.product.product().await?;
+    let Some(product) = state
+        .product
+        .products()
+        .await?
+        .into_iter()
+        .find(|p| p.id == selected)
+    else {
+        let body = json!({ "error": "No product is selected" });
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response());
+    };
+
+    let disk = state
+        .storage
+        .system_devices()
+        .await?
+        .iter()
+        .filter(|d| d.drive.is_some())
+        .filter_map(|d| d.block_device.as_ref().map(|b| b.size.bytes()))
+        .max();
+    let resources = SystemResources {
+        memory: HardwareFacts::detect().await.memory,
+        disk,
+        arch: state.software.architecture().await?,
+    };
+    let report = check_requirements(&product.requirements.unwrap_or_default(), &resources);
+    Ok(Json(report).into_response())
+}
+
+/// Returns the release notes of the selected product in the language of the user interface.
+///
+/// When there are no release notes in that language, the English ones are returned.
+///
+/// * `state`: service state.
+#[utoipa::path(
+    get,
+    path = "/product/release_notes",
+    context_path = "/api/software",
+    responses(
+        (status = 200, description = "Release notes of the selected product", body = super::release_notes::ReleaseNotes),
+        (status = 404, description = "The release notes are not available for the product"),
+        (status = 422, description = "No product is selected"),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn product_release_notes(State(state): State<SoftwareState<'_>>) -> Result<Response, Error> {
+    let selected = state.product.product().await?;
+    let Some(product) = state
+        .product
+        .products()
+        .await?
+        .into_iter()
+        .find(|p| p.id == selected)
+    else {
+        let body = json!({ "error": "No product is selected" });
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response());
+    };
+
+    let locale = state
+        .locale
+        .uilocale()
+        .await
+        .unwrap_or_else(|_| FALLBACK_LANGUAGE.to_string());
+    match state.release_notes.get(&product, &locale).await {
+        Ok(notes) => Ok(Json(notes).into_response()),
+        Err(error @ ReleaseNotesError::NotAvailable(_)) => {
+            let body = json!({ "error": error.to_string() });
+            Ok((StatusCode::NOT_FOUND, Json(body)).into_response())
+        }
+        Err(error) => Err(Error::Anyhow(error.to_string())),
+    }
+}
+
 /// returns registration info
 ///
 /// * `state`: service state.
@@ -273,6 +572,28 @@ async fn get_registration(
     Ok(Json(result))
 }
 
+/// Returns the subscription status of the registered product.
+///
+/// It includes the registered product, the expiration date, the activated extensions and the
+/// remaining entitlements (when the registration server reports them). If the product is not
+/// registered, `registered` is false and the rest of the fields are empty.
+///
+/// * `state`: service state.
+#[utoipa::path(
+    get,
+    path = "/registration/status",
+    context_path = "/api/software",
+    responses(
+        (status = 200, description = "Subscription status", body = SubscriptionStatus),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn subscription_status(
+    State(state): State<SoftwareState<'_>>,
+) -> Result<Json<SubscriptionStatus>, Error> {
+    Ok(Json(state.product.subscription_status().await?))
+}
+
 #[derive(Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct FailureDetails {
     /// ID of error. See dbus API for possible values
@@ -355,6 +676,9 @@ async fn patterns(State(state): State<SoftwareState<'_>>) -> Result<Json<Vec<Pat
 
 /// Sets the software configuration.
 ///
+/// The warnings about the configuration (e.g., patterns affected by disabling the recommended
+/// packages) are written to the log.
+///
 /// * `state`: service state.
 /// * `config`: software configuration.
 #[utoipa::path(
@@ -364,13 +688,29 @@ async fn patterns(State(state): State<SoftwareState<'_>>) -> Result<Json<Vec<Pat
     operation_id = "set_software_config",
     responses(
         (status = 200, description = "Set the software configuration"),
+        (status = 422, description = "Invalid repositories or systemd units", body = Vec<String>),
         (status = 400, description = "The D-Bus service could not perform the action")
     )
 )]
 async fn set_config(
     State(state): State<SoftwareState<'_>>,
     Json(config): Json<SoftwareConfig>,
-) -> Result<(), Error> {
+) -> Result<Response, Error> {
+    if let Some(repositories) = &config.extra_repositories {
+        let mut issues = validate_repositories(repositories);
+        issues.extend(repositories.iter().filter_map(|r| r.validate_source()));
+        if !issues.is_empty() {
+            return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(issues)).into_response());
+        }
+    }
+
+    if let Some(services) = &config.services {
+        let issues = validate_services(services);
+        if !issues.is_empty() {
+            return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(issues)).into_response());
+        }
+    }
+
     if let Some(product) = config.product {
         state.product.select_product(&product).await?;
     }
@@ -379,7 +719,80 @@ async fn set_config(
         state.software.select_patterns(patterns).await?;
     }
 
-    Ok(())
+    if let Some(target) = config.default_target {
+        state.software.set_default_target(target).await?;
+    }
+
+    if let Some(repositories) = config.extra_repositories {
+        let repositories = match state.iso_mounts.prepare(&repositories).await {
+            Ok(repositories) => repositories,
+            Err(error) => {
+                let issues = vec![error.to_string()];
+                return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(issues)).into_response());
+            }
+        };
+        state.software.set_extra_repositories(&repositories).await?;
+    }
+
+    let mut warnings = vec![];
+    if let Some(only_required) = config.only_required {
+        let was_only_required = state.software.only_required().await?;
+        if only_required && !was_only_required {
+            let before = state.software.selected_patterns().await?;
+            state.software.set_only_required(true).await?;
+            let after = state.software.selected_patterns().await?;
+            warnings = only_required_warnings(&before, &after);
+        } else {
+            state.software.set_only_required(only_required).await?;
+        }
+    }
+
+    if let Some(services) = config.services {
+        state.software.set_services(&services).await?;
+        warnings.extend(missing_units_warnings(&state.software, &services).await?);
+    }
+
+    for warning in warnings {
+        tracing::warn!("Software configuration: {}", warning);
+    }
+    Ok(().into_response())
+}
+
+/// Returns a warning for each unit to enable or disable whose package is not selected.
+///
+/// Only the units provided by some well-known packages are checked. Masking does not need the
+/// unit to exist.
+///
+/// * `software`: software client.
+/// * `services`: units and their actions.
+async fn missing_units_warnings(
+    software: &SoftwareClient<'_>,
+    services: &[ServiceConfig],
+) -> Result<Vec<String>, Error> {
+    let checked: Vec<(&ServiceConfig, &str)> = services
+        .iter()
+        .filter(|s| s.action != ServiceAction::Mask)
+        .filter_map(|s| Some((s, s.package()?)))
+        .collect();
+    if checked.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let packages: Vec<&str> = checked.iter().map(|(_, p)| *p).collect();
+    let selected = software.provisions_selected(&packages).await?;
+    let warnings = checked
+        .iter()
+        .zip(selected)
+        .filter(|(_, selected)| !selected)
+        .map(|((service, package), _)| {
+            format!(
+                "The unit '{}' will not exist because the package '{}' is not selected",
+                service.unit(),
+                package
+            )
+        })
+        .collect();
+    Ok(warnings)
 }
 
 /// Returns the software configuration.
@@ -409,13 +822,66 @@ async fn get_config(State(state): State<SoftwareState<'_>>) -> Result<Json<Softw
         .into_iter()
         .map(|p| (p, true))
         .collect();
+    let default_target = state.software.default_target().await?;
+    let extra_repositories = state
+        .iso_mounts
+        .restore(state.software.extra_repositories().await?)
+        .await;
     let config = SoftwareConfig {
         patterns: Some(patterns),
         product,
+        default_target,
+        extra_repositories: Some(extra_repositories),
+        only_required: Some(state.software.only_required().await?),
+        services: Some(state.software.services().await?),
     };
     Ok(Json(config))
 }
 
+/// Returns the install architecture and whether multilib packages are allowed.
+///
+/// * `state`: service state.
+#[utoipa::path(
+    get,
+    path = "/architecture",
+    context_path = "/api/software",
+    responses(
+        (status = 200, description = "Architecture settings", body = ArchitectureSettings),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn get_architecture(
+    State(state): State<SoftwareState<'_>>,
+) -> Result<Json<ArchitectureSettings>, Error> {
+    let settings = ArchitectureSettings {
+        architecture: state.software.architecture().await?,
+        multilib: state.software.multilib().await?,
+    };
+    Ok(Json(settings))
+}
+
+/// Sets whether multilib (e.g., 32-bit on 64-bit) packages are allowed.
+///
+/// * `state`: service state.
+/// * `params`: architecture settings to change.
+#[utoipa::path(
+    put,
+    path = "/architecture",
+    context_path = "/api/software",
+    request_body = ArchitectureParams,
+    responses(
+        (status = 200, description = "The architecture settings were changed"),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn set_architecture(
+    State(state): State<SoftwareState<'_>>,
+    Json(params): Json<ArchitectureParams>,
+) -> Result<Json<()>, Error> {
+    state.software.set_multilib(params.multilib).await?;
+    Ok(Json(()))
+}
+
 #[derive(Serialize, utoipa::ToSchema)]
 /// Software proposal information.
 pub struct SoftwareProposal {
@@ -445,20 +911,109 @@ async fn proposal(State(state): State<SoftwareState<'_>>) -> Result<Json<Softwar
     Ok(Json(proposal))
 }
 
+/// Returns the packages added, removed, upgraded or downgraded compared to the installed system.
+///
+/// It is meant for reviewing the changes before committing an upgrade.
+#[utoipa::path(
+    get,
+    path = "/proposal/packages",
+    context_path = "/api/software",
+    responses(
+        (status = 200, description = "Packages whose version changes", body = Vec<PackageChange>),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn package_changes(
+    State(state): State<SoftwareState<'_>>,
+) -> Result<Json<Vec<PackageChange>>, Error> {
+    Ok(Json(state.software.package_changes().await?))
+}
+
 /// Returns the proposal information.
 ///
 /// At this point, only the required space is reported.
+///
+/// Using the `wait` query parameter, the probing runs as an operation which can be waited for or
+/// polled through `/api/software/operations`.
 #[utoipa::path(
     post,
     path = "/probe",
     context_path = "/api/software",
+    params(ApplyParams),
     responses(
         (status = 200, description = "Read repositories data"),
+        (status = 202, description = "The repositories are being read", body = crate::web::common::Operation),
         (status = 400, description = "The D-Bus service could not perform the action
 ")
     )
 )]
-async fn probe(State(state): State<SoftwareState<'_>>) -> Result<Json<()>, Error> {
-    state.software.probe().await?;
-    Ok(Json(()))
+async fn probe(
+    State(state): State<SoftwareState<'static>>,
+    Query(params): Query<ApplyParams>,
+) -> Result<Response, Error> {
+    if params.wait.is_none() {
+        state.software.probe().await?;
+        return Ok(Json(()).into_response());
+    }
+
+    let software = state.software.clone();
+    let response = state
+        .operations
+        .run(&params, async move {
+            software.probe().await.map_err(|e| e.to_string())?;
+            Ok(serde_json::Value::Null)
+        })
+        .await;
+    Ok(response)
+}
+
+/// Reports the optional software features.
+pub struct SoftwareCapabilities {
+    product: ProductClient<'static>,
+}
+
+impl SoftwareCapabilities {
+    pub async fn new(dbus: zbus::Connection) -> Result<Self, ServiceError> {
+        Ok(Self {
+            product: ProductClient::new(dbus).await?,
+        })
+    }
+}
+
+#[async_trait]
+impl CapabilitiesProvider for SoftwareCapabilities {
+    /// Reports whether the selected product supports registration.
+    async fn capabilities(&self) -> ModuleCapabilities {
+        let registration = self
+            .product
+            .registration_requirement()
+            .await
+            .is_ok_and(|r| !matches!(r, RegistrationRequirement::NotRequired));
+        ModuleCapabilities::from([("registration".to_string(), registration)])
+    }
+}
+
+/// Reports the selected product and patterns.
+pub struct SoftwareSnapshot {
+    product: ProductClient<'static>,
+    software: SoftwareClient<'static>,
+}
+
+impl SoftwareSnapshot {
+    pub async fn new(dbus: zbus::Connection) -> Result<Self, ServiceError> {
+        Ok(Self {
+            product: ProductClient::new(dbus.clone()).await?,
+            software: SoftwareClient::new(dbus).await?,
+        })
+    }
+}
+
+#[async_trait]
+impl SnapshotProvider for SoftwareSnapshot {
+    async fn snapshot(&self) -> Result<serde_json::Value, Error> {
+        Ok(json!({
+            "product": self.product.product().await?,
+            "patterns": self.software.selected_patterns().await?,
+        }))
+    }
 }
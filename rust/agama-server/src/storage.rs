@@ -18,5 +18,9 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
+pub mod fstab;
+pub mod impact;
+pub mod smart;
+pub mod target;
 pub mod web;
 pub use web::{storage_service, storage_streams};
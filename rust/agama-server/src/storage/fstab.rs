@@ -0,0 +1,343 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Additional fstab entries for remote file systems (NFS and CIFS).
+//!
+//! The storage service only writes the entries of the local volumes. The extra entries are
+//! appended to the fstab of the target system once it is installed, so the shares are mounted at
+//! first boot.
+//!
+//! The entries are kept in [PENDING_ENTRIES_PATH] too, which the storage service reads when it
+//! finishes the installation. The storage commit (`/api/commit`) appends them itself, as it does
+//! not finish the installation.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::sync::RwLock;
+
+/// Path of the fstab, relative to the target system root.
+pub const FSTAB_PATH: &str = "etc/fstab";
+
+/// File where the entries are written for the storage service, as fstab lines.
+pub const PENDING_ENTRIES_PATH: &str = "/run/agama/storage/fstab";
+
+/// Remote file system types which can be added to the fstab.
+pub const REMOTE_FILESYSTEMS: [&str; 4] = ["nfs", "nfs4", "cifs", "smb3"];
+
+/// Options which embed credentials in the fstab (world-readable).
+const CREDENTIAL_OPTIONS: [&str; 4] = ["password", "pass", "username", "user"];
+
+/// Entry to add to the fstab.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FstabEntry {
+    /// Remote share (e.g., "server:/export" for NFS or "//server/share" for CIFS).
+    pub device: String,
+    /// Where the share is mounted in the target system (e.g., "/srv/data").
+    pub mount_point: String,
+    /// File system type ("nfs", "nfs4", "cifs" or "smb3").
+    pub fs_type: String,
+    /// Mount options (e.g., "vers=4.2"). The default options are used if there are none.
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+impl FstabEntry {
+    /// Returns the problems (errors and warnings) found in the entry.
+    ///
+    /// The type must be a supported remote file system and the share must match it. The options
+    /// cannot contain separators. Embedding credentials in the options is allowed, but it causes
+    /// a warning.
+    pub fn validate(&self) -> (Vec<String>, Vec<String>) {
+        let mut errors = vec![];
+        let mut warnings = vec![];
+
+        if !REMOTE_FILESYSTEMS.contains(&self.fs_type.as_str()) {
+            errors.push(format!(
+                "Unsupported file system type '{}' for {} (supported: {})",
+                self.fs_type,
+                self.mount_point,
+                REMOTE_FILESYSTEMS.join(", ")
+            ));
+        } else if !self.valid_device() {
+            errors.push(format!(
+                "Invalid {} share '{}' for {}",
+                self.fs_type, self.device, self.mount_point
+            ));
+        }
+
+        if !self.mount_point.starts_with('/')
+            || self.mount_point == "/"
+            || self.mount_point.contains(char::is_whitespace)
+        {
+            errors.push(format!("Invalid mount point '{}'", self.mount_point));
+        }
+
+        for option in &self.options {
+            if option.is_empty()
+                || option.contains([',', '#'])
+                || option.contains(char::is_whitespace)
+            {
+                errors.push(format!(
+                    "Invalid mount option '{}' for {}",
+                    option, self.mount_point
+                ));
+                continue;
+            }
+            if let Some((key, value)) = option.split_once('=') {
+                if key.is_empty() {
+                    errors.push(format!(
+                        "Invalid mount option '{}' for {}",
+                        option, self.mount_point
+                    ));
+                } else if CREDENTIAL_OPTIONS.contains(&key) && !value.is_empty() {
+                    warnings.push(format!(
+                        "The mount options of {} include credentials ('{}'), which are visible to any user. Consider using a credentials file",
+                        self.mount_point, key
+                    ));
+                }
+            }
+        }
+        (errors, warnings)
+    }
+
+    /// Whether the share matches the file system type.
+    fn valid_device(&self) -> bool {
+        if self.device.contains(char::is_whitespace) {
+            return false;
+        }
+        match self.fs_type.as_str() {
+            "cifs" | "smb3" => self
+                .device
+                .strip_prefix("//")
+                .and_then(|d| d.split_once('/'))
+                .is_some_and(|(host, share)| !host.is_empty() && !share.is_empty()),
+            _ => self
+                .device
+                .split_once(":/")
+                .is_some_and(|(host, _)| !host.is_empty()),
+        }
+    }
+
+    /// Renders the entry as an fstab line.
+    pub fn to_fstab_line(&self) -> String {
+        let options = if self.options.is_empty() {
+            "defaults".to_string()
+        } else {
+            self.options.join(",")
+        };
+        format!(
+            "{}  {}  {}  {}  0  0\n",
+            self.device, self.mount_point, self.fs_type, options
+        )
+    }
+}
+
+/// Returns the problems (errors and warnings) found in the entries.
+///
+/// Besides checking each entry, the mount points cannot be repeated.
+pub fn validate_entries(entries: &[FstabEntry]) -> (Vec<String>, Vec<String>) {
+    let mut errors = vec![];
+    let mut warnings = vec![];
+    let mut mount_points = HashSet::new();
+    for entry in entries {
+        let (entry_errors, entry_warnings) = entry.validate();
+        errors.extend(entry_errors);
+        warnings.extend(entry_warnings);
+        if !mount_points.insert(entry.mount_point.as_str()) {
+            errors.push(format!("Duplicated mount point {}", entry.mount_point));
+        }
+    }
+    (errors, warnings)
+}
+
+/// Extra fstab entries, shared by the storage HTTP service, the storage commit and the preview.
+#[derive(Clone)]
+pub struct FstabEntries {
+    entries: Arc<RwLock<Vec<FstabEntry>>>,
+    pending: Arc<PathBuf>,
+}
+
+impl FstabEntries {
+    /// * `pending`: file to write the entries for the storage service (see
+    ///   [PENDING_ENTRIES_PATH]).
+    pub fn new<P: Into<PathBuf>>(pending: P) -> Self {
+        Self {
+            entries: Default::default(),
+            pending: Arc::new(pending.into()),
+        }
+    }
+
+    pub async fn get(&self) -> Vec<FstabEntry> {
+        self.entries.read().await.clone()
+    }
+
+    /// Replaces the entries and updates the file for the storage service.
+    ///
+    /// The file is removed when there are no entries.
+    pub async fn set(&self, entries: Vec<FstabEntry>) -> io::Result<()> {
+        let mut current = self.entries.write().await;
+        if entries.is_empty() {
+            match fs::remove_file(self.pending.as_path()) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+                _ => {}
+            }
+        } else {
+            if let Some(parent) = self.pending.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let lines: String = entries.iter().map(FstabEntry::to_fstab_line).collect();
+            fs::write(self.pending.as_path(), lines)?;
+        }
+        *current = entries;
+        Ok(())
+    }
+
+    /// Appends the entries to the fstab of the target system.
+    ///
+    /// The mount points already included in the fstab are skipped, so it can be called again.
+    ///
+    /// * `root`: directory where the target system is mounted.
+    pub async fn write(&self, root: &Path) -> io::Result<()> {
+        let entries = self.get().await;
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let path = root.join(FSTAB_PATH);
+        let mut fstab = fs::read_to_string(&path).unwrap_or_default();
+        let existing: HashSet<String> = fstab
+            .lines()
+            .filter(|l| !l.trim_start().starts_with('#'))
+            .filter_map(|l| l.split_whitespace().nth(1).map(str::to_string))
+            .collect();
+        if !fstab.is_empty() && !fstab.ends_with('\n') {
+            fstab.push('\n');
+        }
+        for entry in entries
+            .iter()
+            .filter(|e| !existing.contains(&e.mount_point))
+        {
+            fstab.push_str(&entry.to_fstab_line());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, fstab)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(device: &str, mount_point: &str, fs_type: &str, options: &[&str]) -> FstabEntry {
+        FstabEntry {
+            device: device.to_string(),
+            mount_point: mount_point.to_string(),
+            fs_type: fs_type.to_string(),
+            options: options.iter().map(|o| o.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_validate_entries() {
+        let nfs = entry(
+            "server:/export",
+            "/srv/data",
+            "nfs",
+            &["vers=4.2", "_netdev"],
+        );
+        let cifs = entry("//server/share", "/srv/share", "cifs", &["password=secret"]);
+        let (errors, warnings) = validate_entries(&[nfs.clone(), cifs]);
+        assert!(errors.is_empty());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("/srv/share"));
+
+        let (errors, _) = validate_entries(&[
+            nfs.clone(),
+            nfs,
+            entry("server/export", "/srv/other", "nfs", &[]),
+            entry("//server/share", "srv", "cifs", &["a b", "=x"]),
+            entry("/dev/sdb1", "/srv/local", "ext4", &[]),
+        ]);
+        assert_eq!(
+            errors,
+            vec![
+                "Duplicated mount point /srv/data".to_string(),
+                "Invalid nfs share 'server/export' for /srv/other".to_string(),
+                "Invalid mount point 'srv'".to_string(),
+                "Invalid mount option 'a b' for srv".to_string(),
+                "Invalid mount option '=x' for srv".to_string(),
+                "Unsupported file system type 'ext4' for /srv/local (supported: nfs, nfs4, cifs, smb3)"
+                    .to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set() {
+        let dir = tempfile::tempdir().unwrap();
+        let pending = dir.path().join("storage/fstab");
+
+        let entries = FstabEntries::new(&pending);
+        entries
+            .set(vec![entry("server:/export", "/srv/data", "nfs4", &[])])
+            .await
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(&pending).unwrap(),
+            "server:/export  /srv/data  nfs4  defaults  0  0\n"
+        );
+
+        entries.set(vec![]).await.unwrap();
+        assert!(!pending.exists());
+        assert!(entries.get().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("mnt");
+        fs::create_dir_all(root.join("etc")).unwrap();
+        fs::write(root.join(FSTAB_PATH), "/dev/sda2  /  btrfs  defaults  0  0").unwrap();
+
+        let entries = FstabEntries::new(dir.path().join("fstab"));
+        entries
+            .set(vec![
+                entry("server:/export", "/srv/data", "nfs4", &[]),
+                entry("//server/share", "/srv/share", "cifs", &["vers=3.0", "ro"]),
+            ])
+            .await
+            .unwrap();
+        entries.write(&root).await.unwrap();
+        entries.write(&root).await.unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join(FSTAB_PATH)).unwrap(),
+            "/dev/sda2  /  btrfs  defaults  0  0\n\
+             server:/export  /srv/data  nfs4  defaults  0  0\n\
+             //server/share  /srv/share  cifs  vers=3.0,ro  0  0\n"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Confirmation of the storage impact.
+//!
+//! `GET /api/storage/impact` issues a random token bound to the actions of the proposal. The
+//! destructive requests (`POST /api/storage/install`, `POST /api/manager/install` and committing
+//! the storage through `/api/commit`) must include it, and they are refused if the proposal
+//! changed since its impact was reviewed.
+
+use agama_lib::{
+    error::ServiceError,
+    storage::{model::Action, StorageClient},
+};
+use rand::distributions::{Alphanumeric, DistString};
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+
+/// Length of the confirmation token.
+const TOKEN_LENGTH: usize = 32;
+
+/// Body of the destructive requests.
+#[derive(Default, Deserialize, utoipa::ToSchema)]
+pub struct ImpactConfirmation {
+    /// Token from `GET /api/storage/impact`.
+    pub token: Option<String>,
+}
+
+/// Token issued for the last reviewed impact and the actions it was issued for.
+///
+/// Reviewing the impact again replaces the previous token.
+#[derive(Clone, Default)]
+pub struct ImpactToken(Arc<Mutex<Option<(String, Vec<Action>)>>>);
+
+impl ImpactToken {
+    /// Issues a new token for the given actions.
+    pub fn issue(&self, actions: &[Action]) -> String {
+        let token = Alphanumeric.sample_string(&mut rand::thread_rng(), TOKEN_LENGTH);
+        *self.0.lock().unwrap() = Some((token.clone(), actions.to_vec()));
+        token
+    }
+
+    /// Returns the reasons why the token does not confirm the current actions of the proposal.
+    /// An empty list means that it does.
+    ///
+    /// * `client`: storage client to read the actions.
+    /// * `token`: token sent by the client, if any.
+    pub async fn verify(
+        &self,
+        client: &StorageClient<'_>,
+        token: Option<&str>,
+    ) -> Result<Vec<String>, ServiceError> {
+        if token.is_none() {
+            return Ok(self.check(None, &[]));
+        }
+        let actions = client.actions().await?;
+        Ok(self.check(token, &actions))
+    }
+
+    fn check(&self, token: Option<&str>, actions: &[Action]) -> Vec<String> {
+        let Some(token) = token else {
+            return vec![
+                "A confirmation token from the storage impact (/api/storage/impact) is required"
+                    .to_string(),
+            ];
+        };
+        match &*self.0.lock().unwrap() {
+            Some((issued, reviewed)) if issued == token => {
+                if reviewed == actions {
+                    vec![]
+                } else {
+                    vec!["The storage proposal changed since its impact was reviewed".to_string()]
+                }
+            }
+            _ => vec!["The storage impact token is unknown or outdated".to_string()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ImpactToken;
+
+    #[test]
+    fn test_check() {
+        let tokens = ImpactToken::default();
+        assert_eq!(tokens.check(Some("token"), &[]).len(), 1);
+
+        let first = tokens.issue(&[]);
+        assert_eq!(first.len(), 32);
+        assert!(tokens.check(Some(&first), &[]).is_empty());
+        assert_eq!(tokens.check(None, &[]).len(), 1);
+
+        let second = tokens.issue(&[]);
+        assert_ne!(first, second);
+        assert!(tokens.check(Some(&second), &[]).is_empty());
+        assert_eq!(
+            tokens.check(Some(&first), &[]),
+            vec!["The storage impact token is unknown or outdated".to_string()]
+        );
+    }
+}
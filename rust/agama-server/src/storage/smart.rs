@@ -0,0 +1,186 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Reads the SMART data of the drives.
+//!
+//! The storage service does not report the SMART status, so it is read with `smartctl`. Reading
+//! it can take some time, so the data is cached until the devices are explicitly probed again.
+
+use agama_lib::storage::model::{Device, SmartHealth, SmartInfo};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::process::Command;
+
+const SMARTCTL_PATH: &str = "/usr/sbin/smartctl";
+
+/// Maximum time to wait for `smartctl` to read the data of a device.
+const SMARTCTL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// ATA attribute containing the number of reallocated sectors.
+const REALLOCATED_SECTORS_ATTR: u64 = 5;
+
+/// Cache of the SMART data, indexed by device name.
+#[derive(Clone, Default)]
+pub struct SmartCache(Arc<Mutex<HashMap<String, SmartInfo>>>);
+
+impl SmartCache {
+    /// Sets the SMART data of the drives.
+    ///
+    /// * `devices`: devices to update. Only the drives are considered.
+    pub async fn fill(&self, devices: &mut [Device]) {
+        for device in devices.iter_mut() {
+            let Some(drive) = device.drive.as_mut() else {
+                continue;
+            };
+            let name = device.device_info.name.clone();
+            let cached = self.0.lock().unwrap().get(&name).cloned();
+            let smart = match cached {
+                Some(smart) => smart,
+                None => {
+                    let smart = read(&name).await;
+                    if smart.is_failing() {
+                        log::warn!("The SMART status of {} reports that it is failing", name);
+                    }
+                    self.0.lock().unwrap().insert(name, smart.clone());
+                    smart
+                }
+            };
+            drive.smart = smart;
+        }
+    }
+
+    /// Forgets the cached data, so it is read again.
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+}
+
+/// Reads the SMART data of the given device.
+///
+/// It returns an unknown status if `smartctl` is not available, the device does not support
+/// SMART or it does not answer in time.
+///
+/// * `device`: device name (e.g., "/dev/sda").
+pub async fn read(device: &str) -> SmartInfo {
+    let mut command = Command::new(SMARTCTL_PATH);
+    command
+        .args(["--json", "--health", "--attributes", device])
+        .kill_on_drop(true);
+    // smartctl uses the exit code as a bit mask (e.g., to report a failing disk), so the output
+    // is parsed anyway.
+    match tokio::time::timeout(SMARTCTL_TIMEOUT, command.output()).await {
+        Ok(Ok(output)) => serde_json::from_slice(&output.stdout)
+            .map(|json| parse(&json))
+            .unwrap_or_default(),
+        Ok(Err(error)) => {
+            log::debug!("Could not read the SMART data of {}: {}", device, error);
+            SmartInfo::default()
+        }
+        Err(_) => {
+            log::warn!("Timed out reading the SMART data of {}", device);
+            SmartInfo::default()
+        }
+    }
+}
+
+/// Extracts the SMART data from the JSON output of `smartctl`.
+///
+/// It supports the ATA, NVMe and SCSI formats.
+///
+/// * `json`: output of `smartctl --json`.
+pub fn parse(json: &Value) -> SmartInfo {
+    let health = match json
+        .pointer("/smart_status/passed")
+        .and_then(Value::as_bool)
+    {
+        Some(true) => SmartHealth::Passed,
+        Some(false) => SmartHealth::Failing,
+        None => SmartHealth::Unknown,
+    };
+
+    let power_on_hours = json
+        .pointer("/power_on_time/hours")
+        .or_else(|| json.pointer("/nvme_smart_health_information_log/power_on_hours"))
+        .and_then(Value::as_u64);
+
+    let reallocated_sectors = json
+        .pointer("/ata_smart_attributes/table")
+        .and_then(Value::as_array)
+        .and_then(|table| {
+            table
+                .iter()
+                .find(|a| a.get("id").and_then(Value::as_u64) == Some(REALLOCATED_SECTORS_ATTR))
+        })
+        .and_then(|attr| attr.pointer("/raw/value"))
+        .or_else(|| json.get("scsi_grown_defect_list"))
+        .and_then(Value::as_u64);
+
+    SmartInfo {
+        health,
+        reallocated_sectors,
+        power_on_hours,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_ata() {
+        let output = json!({
+            "smart_status": { "passed": true },
+            "power_on_time": { "hours": 1234 },
+            "ata_smart_attributes": {
+                "table": [
+                    { "id": 1, "name": "Raw_Read_Error_Rate", "raw": { "value": 0 } },
+                    { "id": 5, "name": "Reallocated_Sector_Ct", "raw": { "value": 8 } }
+                ]
+            }
+        });
+        let smart = parse(&output);
+        assert_eq!(smart.health, SmartHealth::Passed);
+        assert_eq!(smart.power_on_hours, Some(1234));
+        assert_eq!(smart.reallocated_sectors, Some(8));
+    }
+
+    #[test]
+    fn test_parse_nvme_failing() {
+        let output = json!({
+            "smart_status": { "passed": false },
+            "nvme_smart_health_information_log": { "power_on_hours": 42 }
+        });
+        let smart = parse(&output);
+        assert!(smart.is_failing());
+        assert_eq!(smart.power_on_hours, Some(42));
+        assert_eq!(smart.reallocated_sectors, None);
+    }
+
+    #[test]
+    fn test_parse_unsupported() {
+        let output = json!({ "smartctl": { "exit_status": 4 } });
+        assert_eq!(parse(&output), SmartInfo::default());
+    }
+}
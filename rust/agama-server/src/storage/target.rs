@@ -0,0 +1,169 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Detects the facts about the target devices which are not reported by the storage service.
+
+use agama_lib::storage::model::{Device, TargetDeviceFacts, Volume};
+use std::{fs, path::Path};
+
+/// Mount points of the installation medium.
+const LIVE_MOUNT_POINTS: [&str; 2] = ["/run/initramfs/live", "/run/initramfs/isoscan"];
+
+/// Detects the facts about the given device in the running system.
+///
+/// * `device`: device to check.
+/// * `volumes`: volumes of the proposal, to calculate the needed space.
+pub fn probe(device: &Device, volumes: &[Volume]) -> TargetDeviceFacts {
+    probe_root(Path::new("/"), device, volumes)
+}
+
+/// Detects the facts using the given root directory (useful for testing).
+///
+/// * `root`: directory containing the `sys` and `proc` file systems.
+/// * `device`: device to check.
+/// * `volumes`: volumes of the proposal, to calculate the needed space.
+pub fn probe_root(root: &Path, device: &Device, volumes: &[Volume]) -> TargetDeviceFacts {
+    let name = &device.device_info.name;
+    let live_medium = live_medium_sources(root)
+        .iter()
+        .any(|source| belongs_to(source, name))
+        || device
+            .filesystem
+            .as_ref()
+            .is_some_and(|fs| fs.fs_type == "iso9660");
+    TargetDeviceFacts {
+        live_medium,
+        read_only: is_read_only(root, name),
+        min_size: volumes.iter().map(|v| v.min_size_in(name)).sum(),
+    }
+}
+
+/// Devices mounted as the installation medium, according to `/proc/mounts`.
+fn live_medium_sources(root: &Path) -> Vec<String> {
+    let Ok(mounts) = fs::read_to_string(root.join("proc/mounts")) else {
+        return vec![];
+    };
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let source = fields.next()?;
+            let mount_point = fields.next()?;
+            LIVE_MOUNT_POINTS
+                .contains(&mount_point)
+                .then(|| source.to_string())
+        })
+        .collect()
+}
+
+/// Whether the source device is the given device or one of its partitions (e.g.,
+/// "/dev/nvme0n1p1" belongs to "/dev/nvme0n1").
+fn belongs_to(source: &str, name: &str) -> bool {
+    let Some(rest) = source.strip_prefix(name) else {
+        return false;
+    };
+    let number = rest.strip_prefix('p').unwrap_or(rest);
+    rest.is_empty() || (!number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Whether the device is read-only, according to the `/sys/class/block` directory.
+fn is_read_only(root: &Path, name: &str) -> bool {
+    let Some(kernel_name) = Path::new(name).file_name() else {
+        return false;
+    };
+    let path = root.join("sys/class/block").join(kernel_name).join("ro");
+    fs::read_to_string(path).is_ok_and(|ro| ro.trim() == "1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{belongs_to, probe_root};
+    use agama_lib::storage::model::{BlockDevice, Device, DeviceInfo, ShrinkingInfo};
+    use std::fs;
+
+    fn disk(name: &str) -> Device {
+        Device {
+            device_info: DeviceInfo {
+                sid: 1.into(),
+                name: name.to_string(),
+                description: String::new(),
+            },
+            block_device: Some(BlockDevice {
+                active: true,
+                encrypted: false,
+                size: 1024.into(),
+                shrinking: ShrinkingInfo::Unsupported(vec![]),
+                start: 0,
+                systems: vec![],
+                udev_ids: vec![],
+                udev_paths: vec![],
+                logical_sector_size: None,
+                physical_sector_size: None,
+                offset: None,
+            }),
+            component: None,
+            drive: None,
+            filesystem: None,
+            lvm_lv: None,
+            lvm_vg: None,
+            md: None,
+            multipath: None,
+            partition: None,
+            partition_table: None,
+            raid: None,
+            cache: None,
+            contents: None,
+        }
+    }
+
+    #[test]
+    fn test_belongs_to() {
+        assert!(belongs_to("/dev/sdb", "/dev/sdb"));
+        assert!(belongs_to("/dev/sdb1", "/dev/sdb"));
+        assert!(belongs_to("/dev/nvme0n1p2", "/dev/nvme0n1"));
+        assert!(!belongs_to("/dev/sdba", "/dev/sdb"));
+        assert!(!belongs_to("/dev/sda1", "/dev/sdb"));
+    }
+
+    #[test]
+    fn test_probe_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let sdb = disk("/dev/sdb");
+        let facts = probe_root(root, &sdb, &[]);
+        assert!(!facts.live_medium);
+        assert!(!facts.read_only);
+
+        fs::create_dir_all(root.join("proc")).unwrap();
+        fs::write(
+            root.join("proc/mounts"),
+            "/dev/sdb1 /run/initramfs/live iso9660 ro 0 0\n/dev/sda2 / btrfs rw 0 0\n",
+        )
+        .unwrap();
+        let ro = root.join("sys/class/block/sdb");
+        fs::create_dir_all(&ro).unwrap();
+        fs::write(ro.join("ro"), "1\n").unwrap();
+
+        let facts = probe_root(root, &sdb, &[]);
+        assert!(facts.live_medium);
+        assert!(facts.read_only);
+        assert!(!probe_root(root, &disk("/dev/sda"), &[]).live_medium);
+    }
+}
@@ -26,37 +26,71 @@
 //! * `storage_stream` which offers an stream that emits the storage events coming from D-Bus.
 
 use agama_lib::{
+    bootloader::client::BootloaderClient,
     error::ServiceError,
+    proxies::{IssuesProxy, ServiceStatusProxy},
     storage::{
-        model::{Action, Device, DeviceSid, ProposalSettings, ProposalSettingsPatch, Volume},
+        client::{dasd::DASDClient, UnlockResult},
+        model::{
+            Action, CacheSettings, Device, DeviceSid, DeviceValidation, DiskSelection,
+            EncryptionKeyFile, EspSettings, ExcludedDevice, ExplicitPartition, ProposalIssue,
+            ProposalIssues, ProposalSettings, ProposalSettingsPatch, ProposalTarget, RaidLevel,
+            ResizeError, ResizeSummary, SpaceAction, SpaceActionSettings, StorageImpact,
+            SupportedFilesystem, Volume, VolumeSize, MOUNTABLE_FILESYSTEMS, TPM_FDE_METHOD,
+        },
         proxies::Storage1Proxy,
-        StorageClient, StorageSettings,
+        ISCSIClient, NVMeoFClient, StorageClient, StorageSettings, ZFCPClient,
     },
 };
+use async_trait::async_trait;
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
     routing::{get, post, put},
     Json, Router,
 };
+use nvmeof::nvmeof_service;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock,
+    },
+};
 use tokio_stream::{Stream, StreamExt};
 use zfcp::{zfcp_service, zfcp_stream};
 
 pub mod dasd;
 pub mod iscsi;
+pub mod nvmeof;
 pub mod zfcp;
 
+/// Directory to keep the uploaded encryption key files.
+const KEY_FILE_DIR: &str = "/run/agama/storage";
+
 use crate::{
     error::Error,
-    storage::web::{
-        dasd::{dasd_service, dasd_stream},
-        iscsi::{iscsi_service, iscsi_stream},
+    manager::facts::HardwareFacts,
+    storage::{
+        fstab::{validate_entries, FstabEntries, FstabEntry},
+        impact::{ImpactConfirmation, ImpactToken},
+        smart::SmartCache,
+        target,
+        web::{
+            dasd::{dasd_service, dasd_stream},
+            iscsi::{iscsi_service, iscsi_stream},
+        },
     },
     web::{
         common::{
-            issues_router, jobs_service, progress_router, service_status_router, EventStreams,
+            build_service_status_proxy, issues_router, jobs_service, operations_router,
+            progress_router, ApplyParams, EventStreams, Operations,
         },
-        Event,
+        generous_timeout, no_timeout, CapabilitiesProvider, CommitProvider, Event, EventsSender,
+        ModuleCapabilities, SnapshotProvider,
     },
 };
 
@@ -93,45 +127,150 @@ async fn devices_dirty_stream(dbus: zbus::Connection) -> Result<impl Stream<Item
 #[derive(Clone)]
 struct StorageState<'a> {
     client: StorageClient<'a>,
+    bootloader: BootloaderClient<'a>,
+    status: ServiceStatusProxy<'a>,
+    issues: IssuesProxy<'a>,
+    commit: CommitLock,
+    operations: Operations,
+    events: EventsSender,
+    smart: SmartCache,
+    /// Target disk chosen from the preferred ones.
+    disk_selection: Arc<tokio::sync::RwLock<DiskSelection>>,
+    fstab: FstabEntries,
+    impact: ImpactToken,
+}
+
+/// Ensures that only one storage commit is in flight.
+///
+/// It only covers the commits started through the web server (the storage and the manager
+/// services and the [StorageCommit] provider). The storage D-Bus service refuses its own
+/// concurrent commits with a busy error.
+#[derive(Clone, Default)]
+pub struct CommitLock(Arc<AtomicBool>);
+
+impl CommitLock {
+    /// Returns the lock shared by the storage and manager services and the [StorageCommit]
+    /// provider.
+    pub fn shared() -> Self {
+        static LOCK: OnceLock<CommitLock> = OnceLock::new();
+        LOCK.get_or_init(CommitLock::default).clone()
+    }
+
+    /// Tries to acquire the lock, returning `None` if a commit is already running.
+    pub fn try_acquire(&self) -> Option<CommitGuard> {
+        self.0
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| CommitGuard(Arc::clone(&self.0)))
+    }
+
+    /// Whether a commit is running.
+    pub fn is_running(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+/// Releases the commit lock when dropped.
+pub struct CommitGuard(Arc<AtomicBool>);
+
+impl Drop for CommitGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
 }
 
 /// Sets up and returns the axum service for the storage module.
-pub async fn storage_service(dbus: zbus::Connection) -> Result<Router, ServiceError> {
+///
+/// * `dbus`: D-Bus connection.
+/// * `events`: channel to send the events.
+/// * `fstab`: additional fstab entries, shared with the [StorageCommit] provider.
+/// * `impact`: confirmation token of the storage impact, shared with the destructive requests.
+pub async fn storage_service(
+    dbus: zbus::Connection,
+    events: EventsSender,
+    fstab: FstabEntries,
+    impact: ImpactToken,
+) -> Result<Router, ServiceError> {
     const DBUS_SERVICE: &str = "org.opensuse.Agama.Storage1";
     const DBUS_PATH: &str = "/org/opensuse/Agama/Storage1";
     const DBUS_DESTINATION: &str = "org.opensuse.Agama.Storage1";
 
-    let status_router = service_status_router(&dbus, DBUS_SERVICE, DBUS_PATH).await?;
     let progress_router = progress_router(&dbus, DBUS_SERVICE, DBUS_PATH).await?;
     let issues_router = issues_router(&dbus, DBUS_SERVICE, DBUS_PATH).await?;
-    let iscsi_router = iscsi_service(&dbus).await?;
+    let operations = Operations::new("storage", events.clone());
+    let iscsi_router = iscsi_service(&dbus, operations.clone()).await?;
     let dasd_router = dasd_service(&dbus).await?;
     let zfcp_router = zfcp_service(&dbus).await?;
+    let nvmeof_router = nvmeof_service(&dbus).await?;
     let jobs_router = jobs_service(&dbus, DBUS_DESTINATION, DBUS_PATH).await?;
 
     let client = StorageClient::new(dbus.clone()).await?;
-    let state = StorageState { client };
+    let status = build_service_status_proxy(&dbus, DBUS_SERVICE, DBUS_PATH).await?;
+    let bootloader = BootloaderClient::new(dbus.clone()).await?;
+    let issues = IssuesProxy::builder(&dbus)
+        .destination(DBUS_SERVICE)?
+        .path(DBUS_PATH)?
+        .build()
+        .await?;
+    let state = StorageState {
+        client,
+        bootloader,
+        status,
+        issues,
+        commit: CommitLock::shared(),
+        operations,
+        events,
+        smart: SmartCache::default(),
+        disk_selection: Default::default(),
+        fstab,
+        impact,
+    };
+    let operations = operations_router(state.operations.clone());
     let router = Router::new()
-        .route("/config", put(set_config).get(get_config))
-        .route("/probe", post(probe))
+        .route("/config", generous_timeout(put(set_config).get(get_config)))
+        .route("/probe", no_timeout(post(probe)))
+        .route("/fstab", get(get_fstab_entries).put(set_fstab_entries))
+        .route("/impact", get(storage_impact))
+        .route("/raid_levels", get(raid_levels))
+        .route("/reset", no_timeout(post(reset)))
+        .route("/install", no_timeout(post(install)))
+        .route("/status", get(storage_status))
         .route("/devices/dirty", get(devices_dirty))
         .route("/devices/system", get(system_devices))
         .route("/devices/result", get(staging_devices))
+        .route("/devices/unlock", post(unlock_device))
+        .route("/devices/:id/validate", post(validate_device))
         .route("/product/volume_for", get(volume_for))
         .route("/product/params", get(product_params))
+        .route("/product/filesystems", get(supported_filesystems))
         .route("/proposal/actions", get(actions))
+        .route(
+            "/proposal/calculation",
+            generous_timeout(post(calculate_proposal)),
+        )
+        .route("/proposal/disk_selection", get(disk_selection))
+        .route("/proposal/excluded_devices", get(excluded_devices))
+        .route("/proposal/issues", get(proposal_issues))
+        .route("/proposal/mounts", get(planned_mounts).post(mount_existing))
+        .route("/proposal/partition_tables", get(planned_partition_tables))
+        .route(
+            "/proposal/partitions",
+            generous_timeout(post(create_partition)),
+        )
+        .route("/proposal/resize", post(resize_partition))
         .route("/proposal/usable_devices", get(usable_devices))
         .route(
             "/proposal/settings",
-            get(get_proposal_settings).put(set_proposal_settings),
+            generous_timeout(get(get_proposal_settings).put(set_proposal_settings)),
         )
         .merge(progress_router)
-        .merge(status_router)
         .merge(jobs_router)
         .nest("/issues", issues_router)
         .nest("/iscsi", iscsi_router)
         .nest("/dasd", dasd_router)
         .nest("/zfcp", zfcp_router)
+        .nest("/nvmeof", nvmeof_router)
+        .nest("/operations", operations)
         .with_state(state);
     Ok(router)
 }
@@ -164,35 +303,188 @@ async fn get_config(State(state): State<StorageState<'_>>) -> Result<Json<Storag
     path = "/config",
     context_path = "/api/storage",
     operation_id = "set_storage_config",
+    params(ApplyParams),
     responses(
-        (status = 200, description = "Set the storage configuration"),
+        (status = 200, description = "Set the storage configuration", body = crate::web::common::Operation),
+        (status = 202, description = "The storage configuration is being applied", body = crate::web::common::Operation),
         (status = 400, description = "The D-Bus service could not perform the action")
     )
 )]
 async fn set_config(
-    State(state): State<StorageState<'_>>,
+    State(state): State<StorageState<'static>>,
+    Query(params): Query<ApplyParams>,
     Json(settings): Json<StorageSettings>,
-) -> Result<Json<()>, Error> {
-    let _status: u32 = state
-        .client
-        .set_config(settings)
-        .await
-        .map_err(Error::Service)?;
-    Ok(Json(()))
+) -> Result<Response, Error> {
+    if params.wait.is_none() {
+        let _status: u32 = state
+            .client
+            .set_config(settings)
+            .await
+            .map_err(Error::Service)?;
+        return Ok(Json(()).into_response());
+    }
+
+    let client = state.client.clone();
+    let response = state
+        .operations
+        .run(&params, async move {
+            let status = client
+                .set_config(settings)
+                .await
+                .map_err(|e| e.to_string())?;
+            if status != 0 {
+                return Err("The storage configuration could not be applied".to_string());
+            }
+            let config = client.get_config().await.map_err(|e| e.to_string())?;
+            serde_json::to_value(config).map_err(|e| e.to_string())
+        })
+        .await;
+    Ok(response)
 }
 
 /// Probes the storage devices.
+///
+/// Using the `wait` query parameter, the probing runs as a storage operation. It cannot be
+/// canceled, as stopping it halfway would leave the devicegraph in an inconsistent state.
 #[utoipa::path(
     post,
     path = "/probe",
     context_path = "/api/storage",
+    params(ApplyParams),
     responses(
         (status = 200, description = "Devices were probed and an initial proposal were performed"),
+        (status = 202, description = "The devices are being probed", body = crate::web::common::Operation),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn probe(
+    State(state): State<StorageState<'static>>,
+    Query(params): Query<ApplyParams>,
+) -> Result<Response, Error> {
+    state.smart.clear();
+    if params.wait.is_none() {
+        state.client.probe().await?;
+        return Ok(Json(()).into_response());
+    }
+
+    let client = state.client.clone();
+    let response = state
+        .operations
+        .run(&params, async move {
+            client.probe().await.map_err(|e| e.to_string())?;
+            Ok(serde_json::Value::Null)
+        })
+        .await;
+    Ok(response)
+}
+
+/// Discards the storage configuration, going back to the default proposal.
+///
+/// The devices are probed again and the proposal is calculated using the product settings.
+#[utoipa::path(
+    post,
+    path = "/reset",
+    context_path = "/api/storage",
+    operation_id = "reset_storage_config",
+    responses(
+        (status = 200, description = "The configuration was reset"),
+        (status = 409, description = "A storage commit is in progress"),
         (status = 400, description = "The D-Bus service could not perform the action")
     )
 )]
-async fn probe(State(state): State<StorageState<'_>>) -> Result<Json<()>, Error> {
-    Ok(Json(state.client.probe().await?))
+async fn reset(State(state): State<StorageState<'_>>) -> Result<Response, Error> {
+    if state.commit.is_running() {
+        let body = json!({ "error": "A storage commit is in progress" });
+        return Ok((StatusCode::CONFLICT, Json(body)).into_response());
+    }
+
+    state.client.probe().await?;
+    _ = state.events.send(Event::ConfigReset {
+        service: "storage".to_string(),
+    });
+    Ok(Json(()).into_response())
+}
+
+/// Commits the storage changes to the system.
+///
+/// It requires the token from `GET /api/storage/impact`. Only one commit can be in flight. The
+/// lock is released when the commit finishes, even if it fails or the client goes away.
+#[utoipa::path(
+    post,
+    path = "/install",
+    context_path = "/api/storage",
+    request_body = ImpactConfirmation,
+    responses(
+        (status = 200, description = "The storage changes were committed"),
+        (status = 409, description = "A storage commit is already in progress", body = StorageStatus),
+        (status = 422, description = "The token is missing or the proposal changed since its impact was reviewed", body = Vec<String>),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn install(
+    State(state): State<StorageState<'static>>,
+    confirmation: Option<Json<ImpactConfirmation>>,
+) -> Result<Response, Error> {
+    let Json(confirmation) = confirmation.unwrap_or_default();
+    let problems = state
+        .impact
+        .verify(&state.client, confirmation.token.as_deref())
+        .await?;
+    if !problems.is_empty() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(problems)).into_response());
+    }
+
+    let Some(guard) = state.commit.try_acquire() else {
+        let status = read_storage_status(&state).await?;
+        let body = json!({
+            "error": "A storage commit is already in progress",
+            "status": status
+        });
+        return Ok((StatusCode::CONFLICT, Json(body)).into_response());
+    };
+
+    let client = state.client.clone();
+    let result = tokio::spawn(async move {
+        let _guard = guard;
+        client.install().await
+    })
+    .await
+    .map_err(|e| ServiceError::InternalError(e.to_string()))?;
+    result?;
+    Ok(Json(()).into_response())
+}
+
+/// Returns the storage service status.
+#[utoipa::path(
+    get,
+    path = "/status",
+    context_path = "/api/storage",
+    operation_id = "get_storage_status",
+    responses(
+        (status = 200, description = "Storage service status", body = StorageStatus),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn storage_status(
+    State(state): State<StorageState<'_>>,
+) -> Result<Json<StorageStatus>, Error> {
+    Ok(Json(read_storage_status(&state).await?))
+}
+
+async fn read_storage_status(state: &StorageState<'_>) -> Result<StorageStatus, Error> {
+    Ok(StorageStatus {
+        current: state.status.current().await?,
+        commit_running: state.commit.is_running(),
+    })
+}
+
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageStatus {
+    /// Current service status.
+    current: u32,
+    /// Whether a storage commit is in progress.
+    commit_running: bool,
 }
 
 /// Gets whether the system is in a deprecated status.
@@ -217,6 +509,8 @@ async fn devices_dirty(State(state): State<StorageState<'_>>) -> Result<Json<boo
 }
 
 /// Gets the probed devices.
+///
+/// The drives include their SMART health, which is read from the system.
 #[utoipa::path(
     get,
     path = "/devices/system",
@@ -227,7 +521,51 @@ async fn devices_dirty(State(state): State<StorageState<'_>>) -> Result<Json<boo
     )
 )]
 async fn system_devices(State(state): State<StorageState<'_>>) -> Result<Json<Vec<Device>>, Error> {
-    Ok(Json(state.client.system_devices().await?))
+    let mut devices = state.client.system_devices().await?;
+    state.smart.fill(&mut devices).await;
+    Ok(Json(devices))
+}
+
+/// Checks whether a device is usable as installation target.
+///
+/// The device fails the check if it contains the installation medium, it is read-only, it is too
+/// small for the volumes of the proposal or its SMART status reports that it is failing. Other
+/// problems, like existing data or removable drives, only produce warnings.
+#[utoipa::path(
+    post,
+    path = "/devices/:id/validate",
+    context_path = "/api/storage",
+    params(("id" = u32, Path, description = "Storage ID (SID) of the device")),
+    responses(
+        (status = 200, description = "Result of the check", body = DeviceValidation),
+        (status = 404, description = "The device does not exist"),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn validate_device(
+    State(state): State<StorageState<'_>>,
+    Path(id): Path<u32>,
+) -> Result<Response, Error> {
+    let mut devices = state.client.system_devices().await?;
+    state.smart.fill(&mut devices).await;
+    let sid = DeviceSid::from(id);
+    let Some(device) = devices.iter().find(|d| d.device_info.sid == sid) else {
+        let body = json!({ "error": format!("Unknown device {}", id) });
+        return Ok((StatusCode::NOT_FOUND, Json(body)).into_response());
+    };
+
+    let volumes = match state.client.proposal_settings().await {
+        Ok(settings) => settings.volumes,
+        Err(_) => {
+            let mut volumes = vec![];
+            for mount_path in state.client.product_mount_points().await? {
+                volumes.push(state.client.volume_for(&mount_path).await?);
+            }
+            volumes
+        }
+    };
+    let facts = target::probe(device, &volumes);
+    Ok(Json(DeviceValidation::check(device, &devices, &facts)).into_response())
 }
 
 /// Gets the resulting devices of applying the requested actions.
@@ -243,7 +581,64 @@ async fn system_devices(State(state): State<StorageState<'_>>) -> Result<Json<Ve
 async fn staging_devices(
     State(state): State<StorageState<'_>>,
 ) -> Result<Json<Vec<Device>>, Error> {
-    Ok(Json(state.client.staging_devices().await?))
+    let mut devices = state.client.staging_devices().await?;
+    state.smart.fill(&mut devices).await;
+    Ok(Json(devices))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct UnlockParams {
+    /// Name of the encrypted device (e.g., "/dev/sda2").
+    device: String,
+    /// Passphrase to open the device.
+    passphrase: String,
+}
+
+// Do not leak the passphrase in the logs.
+impl std::fmt::Debug for UnlockParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UnlockParams")
+            .field("device", &self.device)
+            .field("passphrase", &"<hidden>")
+            .finish()
+    }
+}
+
+/// Unlocks an existing encrypted (LUKS) device.
+///
+/// After unlocking it, the device and its content are included in the system devices.
+#[utoipa::path(
+    post,
+    path = "/devices/unlock",
+    context_path = "/api/storage",
+    request_body = UnlockParams,
+    responses(
+        (status = 200, description = "The device was unlocked"),
+        (status = 404, description = "The device does not exist or it is not encrypted"),
+        (status = 422, description = "Wrong passphrase"),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn unlock_device(
+    State(state): State<StorageState<'_>>,
+    Json(params): Json<UnlockParams>,
+) -> Result<Response, Error> {
+    let result = state
+        .client
+        .unlock_device(&params.device, &params.passphrase)
+        .await?;
+    let (status, message) = match result {
+        UnlockResult::Success => return Ok(Json(()).into_response()),
+        UnlockResult::DeviceNotFound => (StatusCode::NOT_FOUND, "Encrypted device not found"),
+        UnlockResult::WrongPassphrase => (StatusCode::UNPROCESSABLE_ENTITY, "Wrong passphrase"),
+        UnlockResult::Failed => (StatusCode::BAD_REQUEST, "The device could not be unlocked"),
+    };
+    let body = json!({
+        "error": message,
+        "device": params.device,
+        "result": result
+    });
+    Ok((status, Json(body)).into_response())
 }
 
 /// Gets the default values for a volume with the given mount path.
@@ -285,9 +680,14 @@ struct VolumeForQuery {
 async fn product_params(
     State(state): State<StorageState<'_>>,
 ) -> Result<Json<ProductParams>, Error> {
+    let mut encryption_methods = state.client.encryption_methods().await?;
+    // Do not offer the TPM-based encryption if there is no TPM.
+    if !HardwareFacts::detect().await.tpm2 {
+        encryption_methods.retain(|m| m != TPM_FDE_METHOD);
+    }
     let params = ProductParams {
         mount_points: state.client.product_mount_points().await?,
-        encryption_methods: state.client.encryption_methods().await?,
+        encryption_methods,
     };
     Ok(Json(params))
 }
@@ -301,6 +701,104 @@ pub struct ProductParams {
     encryption_methods: Vec<String>,
 }
 
+/// Gets the file systems which can be created, and their capabilities.
+///
+/// They are taken from the volume templates of the product, so the clients can offer only the
+/// supported file systems.
+#[utoipa::path(
+    get,
+    path = "/product/filesystems",
+    context_path = "/api/storage",
+    responses(
+        (status = 200, description = "Supported file systems", body = Vec<SupportedFilesystem>),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn supported_filesystems(
+    State(state): State<StorageState<'_>>,
+) -> Result<Json<Vec<SupportedFilesystem>>, Error> {
+    Ok(Json(state.client.supported_filesystems().await?))
+}
+
+/// Gets the RAID levels which can be created and their constraints (number of
+/// members, redundancy and usable capacity).
+///
+/// When the number of members is given, only the levels which can be built with them are
+/// returned (e.g., RAID5 is not offered for two disks).
+#[utoipa::path(
+    get,
+    path = "/raid_levels",
+    context_path = "/api/storage",
+    params(RaidLevelsQuery),
+    responses(
+        (status = 200, description = "Supported RAID levels", body = Vec<RaidLevel>)
+    )
+)]
+async fn raid_levels(query: Query<RaidLevelsQuery>) -> Json<Vec<RaidLevel>> {
+    let mut levels = RaidLevel::supported();
+    if let Some(members) = query.members {
+        levels.retain(|l| l.accepts(members));
+    }
+    Json(levels)
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+struct RaidLevelsQuery {
+    /// Number of members of the RAID.
+    members: Option<u32>,
+}
+
+/// Returns the problems of the current proposal, split into errors and warnings.
+///
+/// It includes the issues reported by the proposal engine, where the code is their kind (e.g.,
+/// "proposal_failed"), the checks of the planned layout (e.g., "no_swap") and the drives whose
+/// SMART status reports that they are failing ("smart_failing").
+#[utoipa::path(
+    get,
+    path = "/proposal/issues",
+    context_path = "/api/storage",
+    responses(
+        (status = 200, description = "Errors and warnings of the proposal", body = ProposalIssues),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn proposal_issues(
+    State(state): State<StorageState<'_>>,
+) -> Result<Json<ProposalIssues>, Error> {
+    let mut issues = ProposalIssues::default();
+    for (description, details, _source, severity, kind) in state.issues.all_with_kind().await? {
+        let code = if kind.is_empty() { "generic" } else { &kind };
+        let mut issue = ProposalIssue::new(code, description);
+        issue.details = (!details.is_empty()).then_some(details);
+        // severity 1 means error
+        if severity == 1 {
+            issues.errors.push(issue);
+        } else {
+            issues.warnings.push(issue);
+        }
+    }
+
+    // the layout checks only make sense when there is a proposal
+    if let Ok(settings) = state.client.proposal_settings().await {
+        issues
+            .warnings
+            .extend(ProposalIssues::layout_warnings(&settings.volumes));
+
+        let mut devices = state.client.system_devices().await?;
+        state.smart.fill(&mut devices).await;
+        let used: Vec<String> = settings
+            .target_device
+            .iter()
+            .chain(settings.target_pv_devices.iter().flatten())
+            .chain([&settings.boot_device])
+            .filter(|name| !name.is_empty())
+            .cloned()
+            .collect();
+        issues.add_smart_issues(&devices, &used);
+    }
+    Ok(Json(issues))
+}
+
 /// Gets the actions to perform in the storage devices.
 #[utoipa::path(
     get,
@@ -315,6 +813,29 @@ async fn actions(State(state): State<StorageState<'_>>) -> Result<Json<Vec<Actio
     Ok(Json(state.client.actions().await?))
 }
 
+/// Gets the destructive impact of the storage proposal: the devices to erase, the estimated
+/// size of the data to lose and the actions which cannot be undone.
+///
+/// The returned token is required to commit the storage (including the whole installation). It
+/// is random and it is refused if the proposal changes after reviewing its impact.
+#[utoipa::path(
+    get,
+    path = "/impact",
+    context_path = "/api/storage",
+    responses(
+        (status = 200, description = "Impact of the storage proposal", body = StorageImpact),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn storage_impact(
+    State(state): State<StorageState<'_>>,
+) -> Result<Json<StorageImpact>, Error> {
+    let actions = state.client.actions().await?;
+    let devices = state.client.system_devices().await?;
+    let token = state.impact.issue(&actions);
+    Ok(Json(StorageImpact::new(&actions, &devices, token)))
+}
+
 /// Gets the SID (Storage ID) of the devices usable for the installation.
 ///
 /// Note that not all the existing devices can be selected as target device for the installation.
@@ -330,11 +851,110 @@ async fn actions(State(state): State<StorageState<'_>>) -> Result<Json<Vec<Actio
 async fn usable_devices(
     State(state): State<StorageState<'_>>,
 ) -> Result<Json<Vec<DeviceSid>>, Error> {
-    let sids = state.client.available_devices().await?;
+    let mut sids = state.client.available_devices().await?;
+    let excluded = read_excluded_devices(&state).await?;
+    sids.retain(|sid| !excluded.iter().any(|d| &d.sid == sid));
     Ok(Json(sids))
 }
 
+/// Gets the additional fstab entries for remote file systems.
+#[utoipa::path(
+    get,
+    path = "/fstab",
+    context_path = "/api/storage",
+    responses(
+        (status = 200, description = "Additional fstab entries", body = Vec<FstabEntry>)
+    )
+)]
+async fn get_fstab_entries(State(state): State<StorageState<'_>>) -> Json<Vec<FstabEntry>> {
+    Json(state.fstab.get().await)
+}
+
+/// Sets the additional fstab entries for remote file systems (NFS or CIFS), replacing the
+/// previous ones.
+///
+/// The entries are appended to the fstab of the target system after installing it. It returns
+/// the warnings about the entries (e.g., embedded credentials).
+#[utoipa::path(
+    put,
+    path = "/fstab",
+    context_path = "/api/storage",
+    request_body = Vec<FstabEntry>,
+    responses(
+        (status = 200, description = "Warnings about the entries", body = Vec<String>),
+        (status = 422, description = "Unsupported file system type, invalid share, mount point or options", body = Vec<String>),
+        (status = 400, description = "The entries could not be saved")
+    )
+)]
+async fn set_fstab_entries(
+    State(state): State<StorageState<'_>>,
+    Json(entries): Json<Vec<FstabEntry>>,
+) -> Result<Response, Error> {
+    let (errors, warnings) = validate_entries(&entries);
+    if !errors.is_empty() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(errors)).into_response());
+    }
+    state
+        .fstab
+        .set(entries)
+        .await
+        .map_err(|e| Error::Anyhow(format!("Could not save the fstab entries: {}", e)))?;
+    Ok(Json(warnings).into_response())
+}
+
+/// Gets the order in which the preferred disks were tried and which one is used as target device.
+///
+/// The preferred disks are not considered when the proposal settings set the target device.
+#[utoipa::path(
+    get,
+    path = "/proposal/disk_selection",
+    context_path = "/api/storage",
+    responses(
+        (status = 200, description = "Preferred disks and the selected one", body = DiskSelection)
+    )
+)]
+async fn disk_selection(State(state): State<StorageState<'_>>) -> Json<DiskSelection> {
+    Json(state.disk_selection.read().await.clone())
+}
+
+/// Gets the devices which are not considered for the installation and the reason.
+///
+/// Removable devices (e.g., USB sticks) are not automatically chosen as target unless
+/// `includeRemovable` is enabled in the proposal settings. Nothing is excluded when the target
+/// device is explicitly set.
+#[utoipa::path(
+    get,
+    path = "/proposal/excluded_devices",
+    context_path = "/api/storage",
+    responses(
+        (status = 200, description = "Excluded devices", body = Vec<ExcludedDevice>),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn excluded_devices(
+    State(state): State<StorageState<'_>>,
+) -> Result<Json<Vec<ExcludedDevice>>, Error> {
+    Ok(Json(read_excluded_devices(&state).await?))
+}
+
+async fn read_excluded_devices(state: &StorageState<'_>) -> Result<Vec<ExcludedDevice>, Error> {
+    // There might be no proposal yet.
+    let Ok(settings) = state.client.proposal_settings().await else {
+        return Ok(vec![]);
+    };
+    if settings.excluded_devices.is_empty() {
+        return Ok(vec![]);
+    }
+    let devices = state.client.system_devices().await?;
+    Ok(ExcludedDevice::removable(
+        &devices,
+        &settings.excluded_devices,
+    ))
+}
+
 /// Gets the settings that were used for calculating the current proposal.
+///
+/// They include the preferred disks, in the order they were tried.
 #[utoipa::path(
     get,
     path = "/proposal/settings",
@@ -347,10 +967,15 @@ async fn usable_devices(
 async fn get_proposal_settings(
     State(state): State<StorageState<'_>>,
 ) -> Result<Json<ProposalSettings>, Error> {
-    Ok(Json(state.client.proposal_settings().await?))
+    let mut settings = state.client.proposal_settings().await?;
+    settings.preferred_devices = state.disk_selection.read().await.preferred.clone();
+    Ok(Json(settings))
 }
 
 /// Tries to calculates a new proposal with the given settings.
+///
+/// It only reports whether the proposal was calculated. Use `POST /proposal/calculation` to get
+/// the adjustments and the volume sizes too.
 #[utoipa::path(
     put,
     path = "/proposal/settings",
@@ -358,13 +983,791 @@ async fn get_proposal_settings(
     request_body(content = ProposalSettingsPatch, description = "Proposal settings", content_type = "application/json"),
     responses(
         (status = 200, description = "Whether the proposal was successfully calculated", body = bool),
+        (status = 422, description = "Invalid encryption, key file, preferred device, size range, alignment, file system, quota or fstab settings", body = Vec<String>),
         (status = 400, description = "The D-Bus service could not perform the action")
     )
 )]
 async fn set_proposal_settings(
     State(state): State<StorageState<'_>>,
     Json(config): Json<ProposalSettingsPatch>,
-) -> Result<Json<bool>, Error> {
-    let result = state.client.calculate(config).await?;
-    Ok(Json(result == 0))
+) -> Result<Response, Error> {
+    match calculate_with_settings(&state, config).await? {
+        Ok(calculation) => Ok(Json(calculation.success).into_response()),
+        Err(issues) => Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(issues)).into_response()),
+    }
+}
+
+/// Tries to calculates a new proposal with the given settings, reporting the adjustments made
+/// to them and the resulting volume sizes.
+#[utoipa::path(
+    post,
+    path = "/proposal/calculation",
+    context_path = "/api/storage",
+    request_body(content = ProposalSettingsPatch, description = "Proposal settings", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Result of the proposal calculation", body = ProposalCalculation),
+        (status = 422, description = "Invalid encryption, key file, preferred device, size range, alignment, file system, quota, fstab, read-only root or cache settings", body = Vec<String>),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn calculate_proposal(
+    State(state): State<StorageState<'_>>,
+    Json(config): Json<ProposalSettingsPatch>,
+) -> Result<Response, Error> {
+    match calculate_with_settings(&state, config).await? {
+        Ok(calculation) => Ok(Json(calculation).into_response()),
+        Err(issues) => Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(issues)).into_response()),
+    }
+}
+
+/// Validates the settings and calculates the proposal.
+///
+/// It returns the problems found in the settings, if any, instead of calculating the proposal.
+async fn calculate_with_settings(
+    state: &StorageState<'_>,
+    mut config: ProposalSettingsPatch,
+) -> Result<Result<ProposalCalculation, Vec<String>>, Error> {
+    // There might be no proposal yet.
+    let current = state.client.proposal_settings().await.ok();
+    let explicit_target = config.preferred_devices.is_none() && config.target_device.is_some();
+
+    let mut issues = config.validate_alignment();
+    issues.extend(config.validate_volume_encryption());
+    issues.extend(config.validate_quotas());
+    issues.extend(config.validate_default_passphrase(current.as_ref()));
+    let mut selection = match config.preferred_devices.clone() {
+        Some(preferred) => {
+            let (selection, errors) =
+                choose_preferred_device(state, &mut config, current.as_ref(), &preferred).await?;
+            issues.extend(errors);
+            Some(selection)
+        }
+        None => None,
+    };
+    if config.volumes.is_some() {
+        let devices = state.client.system_devices().await?;
+        let target_disk = proposal_disk(&config, current.as_ref(), &devices);
+        issues.extend(
+            config.validate_size_ranges(target_disk.as_ref().map(|(n, s)| (n.as_str(), *s))),
+        );
+    }
+    if let Some(key_file) = &config.encryption_key_file {
+        let method = config
+            .encryption_method
+            .as_deref()
+            .or(current.as_ref().map(|c| c.encryption_method.as_str()));
+        let encrypted = match &config.encryption_password {
+            Some(password) => !password.is_empty(),
+            None => current
+                .as_ref()
+                .is_some_and(|c| !c.encryption_password.is_empty()),
+        };
+        issues.extend(key_file.validate(method, encrypted));
+    }
+    let mut warnings = vec![];
+    let resizes = config
+        .space_actions
+        .iter()
+        .flatten()
+        .any(|a| matches!(a.action, SpaceAction::Resize) && a.size.is_some());
+    if config.partition_tables.is_some()
+        || config.esp.is_some()
+        || config.caches.is_some()
+        || config.partitions.is_some()
+        || resizes
+    {
+        let devices = state.client.system_devices().await?;
+        let efi = state.bootloader.is_efi().await.unwrap_or_else(|e| {
+            tracing::warn!("Could not determine whether the system uses EFI: {e}");
+            false
+        });
+        for action in config.space_actions.iter().flatten() {
+            if matches!(action.action, SpaceAction::Resize) && action.size.is_some() {
+                if let Err(error) = action.validate_resize(&devices) {
+                    issues.push(error.to_string());
+                }
+            }
+        }
+        if let Some(esp) = &config.esp {
+            let (errors, esp_warnings) = esp.validate(efi, &devices);
+            issues.extend(errors);
+            warnings.extend(esp_warnings);
+        }
+        if let Some(caches) = &config.caches {
+            let (errors, cache_warnings) = CacheSettings::validate(caches, &devices);
+            issues.extend(errors);
+            warnings.extend(cache_warnings);
+        }
+        if let Some(partitions) = config.partitions.as_mut() {
+            let alignment = config
+                .alignment
+                .clone()
+                .or_else(|| current.as_ref().map(|c| c.alignment.clone()))
+                .unwrap_or_default();
+            let (errors, partition_warnings) =
+                ExplicitPartition::resolve(partitions, &devices, &alignment);
+            issues.extend(errors);
+            warnings.extend(partition_warnings);
+        }
+        let disks: HashMap<String, u64> = devices
+            .into_iter()
+            .filter(|d| d.drive.is_some())
+            .filter_map(|d| Some((d.device_info.name, d.block_device?.size.bytes())))
+            .collect();
+        let (errors, ptable_warnings) =
+            config.resolve_partition_tables(efi, &disks, current.as_ref());
+        issues.extend(errors);
+        warnings.extend(ptable_warnings);
+    }
+    if let Some(read_only_root) = &config.read_only_root {
+        let volumes = config
+            .volumes
+            .as_deref()
+            .or(current.as_ref().map(|c| c.volumes.as_slice()))
+            .unwrap_or_default();
+        issues.extend(read_only_root.validate(volumes));
+    }
+    if config.sets_mount_by() {
+        let devices = state.client.system_devices().await?;
+        issues.extend(config.validate_mount_by(current.as_ref(), &devices));
+    }
+    if config.encryption_method.as_deref() == Some(TPM_FDE_METHOD) {
+        let current_password = current
+            .as_ref()
+            .map(|s| s.encryption_password.as_str())
+            .unwrap_or_default();
+        issues.extend(
+            config.validate_encryption(HardwareFacts::detect().await.tpm2, current_password),
+        );
+    }
+    if !issues.is_empty() {
+        return Ok(Err(issues));
+    }
+
+    let (volumes, alignment) = current
+        .map(|s| (s.volumes, s.alignment))
+        .unwrap_or_default();
+    let mut volumes = config.volumes.clone().unwrap_or(volumes);
+    if config.home_fs_type.is_some() && !volumes.iter().any(|v| v.mount_path() == "/home") {
+        volumes.push(state.client.volume_for("/home").await?);
+    }
+    let issues = config.apply_fs_types(volumes);
+    if !issues.is_empty() {
+        return Ok(Err(issues));
+    }
+
+    warnings.extend(config.align_volumes(&alignment));
+    // the settings replace the previous ones, so the old key is not needed anymore
+    let key_dir = std::path::Path::new(KEY_FILE_DIR);
+    match config.encryption_key_file.as_mut() {
+        Some(key_file) if key_file.is_enabled() => key_file
+            .write_key(key_dir)
+            .map_err(|e| Error::Anyhow(format!("Could not write the key file: {}", e)))?,
+        _ => EncryptionKeyFile::remove_key(key_dir)
+            .map_err(|e| Error::Anyhow(format!("Could not remove the key file: {}", e)))?,
+    }
+    let volumes = config.volumes.clone().unwrap_or_default();
+    let result = match selection.as_mut() {
+        Some(selection) if !selection.candidates().is_empty() => {
+            try_preferred_devices(state, config, selection).await?
+        }
+        _ => state.client.calculate(config).await?,
+    };
+    match selection {
+        Some(selection) => {
+            warnings.extend(selection.warnings());
+            if selection.selected.is_none() && !selection.preferred.is_empty() {
+                warnings.push("None of the preferred devices can hold the proposal".to_string());
+                warnings.extend(selection.failures());
+            }
+            *state.disk_selection.write().await = selection;
+        }
+        // an explicit target device replaces the preferred ones
+        None if explicit_target => *state.disk_selection.write().await = DiskSelection::default(),
+        None => {}
+    }
+    let sizes = if result == 0 {
+        VolumeSize::from_devices(&volumes, &state.client.staging_devices().await?)
+    } else {
+        vec![]
+    };
+    Ok(Ok(ProposalCalculation {
+        success: result == 0,
+        warnings,
+        sizes,
+    }))
+}
+
+/// Checks the preferred disks and sets the first one which can be used as target device.
+///
+/// It returns the selection and the problems found. The rest of usable disks are tried later
+/// through the proposal, see [try_preferred_devices]. The preferred disks are not used if the
+/// patch sets the target device.
+///
+/// * `preferred`: disks in order of preference.
+async fn choose_preferred_device(
+    state: &StorageState<'_>,
+    config: &mut ProposalSettingsPatch,
+    current: Option<&ProposalSettings>,
+    preferred: &[String],
+) -> Result<(DiskSelection, Vec<String>), Error> {
+    if preferred.is_empty() || config.target_device.is_some() {
+        return Ok((DiskSelection::default(), vec![]));
+    }
+    let selection = DiskSelection {
+        preferred: preferred.to_vec(),
+        ..Default::default()
+    };
+    let target = config
+        .target
+        .as_ref()
+        .or(current.map(|c| &c.target))
+        .unwrap_or(&ProposalTarget::Disk);
+    if !matches!(target, ProposalTarget::Disk) {
+        let issue = "The preferred devices can only be used when the target is a disk";
+        return Ok((selection, vec![issue.to_string()]));
+    }
+
+    let mut issues = vec![];
+    let mut devices = state.client.system_devices().await?;
+    state.smart.fill(&mut devices).await;
+    let volumes = config
+        .volumes
+        .as_deref()
+        .or(current.map(|c| c.volumes.as_slice()))
+        .unwrap_or_default();
+    let mut validations = vec![];
+    for name in preferred {
+        match devices.iter().find(|d| &d.device_info.name == name) {
+            Some(device) => {
+                let facts = target::probe(device, volumes);
+                validations.push(DeviceValidation::check(device, &devices, &facts));
+            }
+            None => issues.push(format!("Unknown preferred device {}", name)),
+        }
+    }
+    if !issues.is_empty() {
+        return Ok((selection, issues));
+    }
+
+    let selection = DiskSelection::new(preferred, validations);
+    match selection.candidates().first() {
+        Some(device) => config.target_device = Some(device.clone()),
+        None => {
+            issues.push("None of the preferred devices can be used".to_string());
+            issues.extend(selection.failures());
+        }
+    }
+    Ok((selection, issues))
+}
+
+/// Calculates the proposal on each usable preferred disk, in order, until it succeeds.
+///
+/// The disks where the proposal fails are skipped. If it fails on all of them, the proposal
+/// calculated on the last one is kept.
+///
+/// * `config`: settings of the proposal. The target device is replaced by each disk.
+/// * `selection`: disks to try. It records the selected disk and the skipped ones.
+async fn try_preferred_devices(
+    state: &StorageState<'_>,
+    mut config: ProposalSettingsPatch,
+    selection: &mut DiskSelection,
+) -> Result<u32, Error> {
+    let mut result = 1;
+    for device in selection.candidates() {
+        config.target_device = Some(device.clone());
+        result = state.client.calculate(config.clone()).await?;
+        if result == 0 {
+            selection.selected = Some(device);
+            break;
+        }
+        selection.reject(&device);
+    }
+    Ok(result)
+}
+
+/// Returns the name and size of the disk where the proposal creates the volumes.
+///
+/// It is only known when the target is a disk.
+fn proposal_disk(
+    config: &ProposalSettingsPatch,
+    current: Option<&ProposalSettings>,
+    devices: &[Device],
+) -> Option<(String, u64)> {
+    let target = config.target.as_ref().or(current.map(|c| &c.target))?;
+    if !matches!(target, ProposalTarget::Disk) {
+        return None;
+    }
+    let name = config
+        .target_device
+        .as_ref()
+        .or(current.and_then(|c| c.target_device.as_ref()))?;
+    let device = devices.iter().find(|d| &d.device_info.name == name)?;
+    Some((name.clone(), device.block_device.as_ref()?.size.bytes()))
+}
+
+/// Mount point included in the proposal.
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedMount {
+    mount_path: String,
+    /// Device to mount, if it is already known.
+    device: Option<String>,
+    fs_type: String,
+    /// Whether the device is formatted or its existing file system is mounted.
+    format: bool,
+}
+
+/// Returns the mount points of the proposal, distinguishing the formatted
+/// devices from the existing file systems.
+#[utoipa::path(
+    get,
+    path = "/proposal/mounts",
+    context_path = "/api/storage",
+    responses(
+        (status = 200, description = "Planned mount points", body = Vec<PlannedMount>),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn planned_mounts(
+    State(state): State<StorageState<'_>>,
+) -> Result<Json<Vec<PlannedMount>>, Error> {
+    let settings = state.client.proposal_settings().await?;
+    let mut mounts: Vec<_> = settings
+        .volumes
+        .iter()
+        .map(|v| PlannedMount {
+            mount_path: v.mount_path().to_string(),
+            device: v.target_device().map(str::to_string),
+            fs_type: v.fs_type().to_string(),
+            format: v.formats(),
+        })
+        .collect();
+    if let Some(mount_path) = settings.esp.mount_path() {
+        let device = match &settings.esp {
+            EspSettings::Reuse { device, .. } => Some(device.clone()),
+            _ => None,
+        };
+        mounts.push(PlannedMount {
+            mount_path: mount_path.to_string(),
+            format: device.is_none(),
+            device,
+            fs_type: "vfat".to_string(),
+        });
+    }
+    Ok(Json(mounts))
+}
+
+/// Partition table of a disk according to the proposal.
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedPartitionTable {
+    /// Name of the disk (e.g., "/dev/sda").
+    device: String,
+    /// Partition table type (e.g., "gpt" or "msdos").
+    #[serde(rename = "type")]
+    ptable_type: String,
+}
+
+/// Returns the partition table type of each disk in the proposal.
+#[utoipa::path(
+    get,
+    path = "/proposal/partition_tables",
+    context_path = "/api/storage",
+    responses(
+        (status = 200, description = "Planned partition tables", body = Vec<PlannedPartitionTable>),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn planned_partition_tables(
+    State(state): State<StorageState<'_>>,
+) -> Result<Json<Vec<PlannedPartitionTable>>, Error> {
+    let tables = state
+        .client
+        .staging_devices()
+        .await?
+        .into_iter()
+        .filter(|d| d.drive.is_some())
+        .filter_map(|d| {
+            Some(PlannedPartitionTable {
+                device: d.device_info.name,
+                ptable_type: d.partition_table?.ptable_type,
+            })
+        })
+        .collect();
+    Ok(Json(tables))
+}
+
+#[derive(Clone, Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MountExistingParams {
+    /// Name of the device containing the file system (e.g., "/dev/sda3").
+    device: String,
+    mount_path: String,
+}
+
+/// Mounts an existing file system without formatting it, keeping its data.
+#[utoipa::path(
+    post,
+    path = "/proposal/mounts",
+    context_path = "/api/storage",
+    request_body = MountExistingParams,
+    responses(
+        (status = 200, description = "Result of the proposal calculation", body = ProposalCalculation),
+        (status = 404, description = "The device does not exist"),
+        (status = 422, description = "The device does not contain a mountable file system"),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn mount_existing(
+    State(state): State<StorageState<'_>>,
+    Json(params): Json<MountExistingParams>,
+) -> Result<Response, Error> {
+    let devices = state.client.system_devices().await?;
+    let Some(device) = devices.iter().find(|d| d.device_info.name == params.device) else {
+        let body = json!({ "error": "Device not found", "device": params.device });
+        return Ok((StatusCode::NOT_FOUND, Json(body)).into_response());
+    };
+
+    let fs_type = device.filesystem.as_ref().map(|fs| fs.fs_type.as_str());
+    let Some(fs_type) = fs_type.filter(|t| MOUNTABLE_FILESYSTEMS.contains(t)) else {
+        let body = json!({
+            "error": "The device does not contain a mountable file system",
+            "device": params.device,
+            "allowed": MOUNTABLE_FILESYSTEMS
+        });
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response());
+    };
+
+    let mut warnings = vec![];
+    if let Some(fs) = device
+        .filesystem
+        .as_ref()
+        .filter(|fs| !fs.mount_path.is_empty())
+    {
+        warnings.push(format!(
+            "The file system of {} is in use (mounted at {})",
+            params.device, fs.mount_path
+        ));
+    }
+
+    let mut volumes = state.client.proposal_settings().await?.volumes;
+    let index = match volumes
+        .iter()
+        .position(|v| v.mount_path() == params.mount_path)
+    {
+        Some(index) => index,
+        None => {
+            volumes.push(state.client.volume_for(&params.mount_path).await?);
+            volumes.len() - 1
+        }
+    };
+    volumes[index].keep_filesystem(&params.device, fs_type);
+
+    let patch = ProposalSettingsPatch {
+        volumes: Some(volumes),
+        ..Default::default()
+    };
+    let result = state.client.calculate(patch).await?;
+    Ok(Json(ProposalCalculation {
+        success: result == 0,
+        warnings,
+        sizes: vec![],
+    })
+    .into_response())
+}
+
+#[derive(Clone, Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResizeParams {
+    /// Name of the partition to resize (e.g., "/dev/sda2").
+    device: String,
+    /// Target size in bytes. If not set, the partition is shrunk as much as possible.
+    size: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+pub struct ResizeCalculation {
+    /// Whether the proposal was successfully calculated.
+    success: bool,
+    /// Planned resizing, including the freed space.
+    resize: ResizeSummary,
+}
+
+/// Resizes an existing partition (and its file system) to make room for the installation.
+///
+/// The partition can only be shrunk as long as the file system supports it and the existing data
+/// fits in the new size.
+#[utoipa::path(
+    post,
+    path = "/proposal/resize",
+    context_path = "/api/storage",
+    request_body = ResizeParams,
+    responses(
+        (status = 200, description = "Result of the proposal calculation", body = ResizeCalculation),
+        (status = 404, description = "The device does not exist"),
+        (status = 422, description = "The partition cannot be resized to the given size"),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn resize_partition(
+    State(state): State<StorageState<'_>>,
+    Json(params): Json<ResizeParams>,
+) -> Result<Response, Error> {
+    let devices = state.client.system_devices().await?;
+    let action = SpaceActionSettings {
+        device: params.device.clone(),
+        action: SpaceAction::Resize,
+        size: params.size,
+    };
+    let summary = match action.validate_resize(&devices) {
+        Ok(summary) => summary,
+        Err(error @ ResizeError::UnknownDevice(_)) => {
+            let body = json!({ "error": error.to_string(), "device": params.device });
+            return Ok((StatusCode::NOT_FOUND, Json(body)).into_response());
+        }
+        Err(error) => {
+            let mut body = json!({ "error": error.to_string(), "device": params.device });
+            if let ResizeError::BelowMinimum { min_size, .. } = error {
+                body["minSize"] = json!(min_size);
+            }
+            return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response());
+        }
+    };
+
+    let mut actions = state.client.proposal_settings().await?.space_actions;
+    actions.retain(|a| a.device != params.device);
+    actions.push(action);
+    let patch = ProposalSettingsPatch {
+        space_policy: Some("custom".to_string()),
+        space_actions: Some(actions),
+        ..Default::default()
+    };
+    let result = state.client.calculate(patch).await?;
+    Ok(Json(ResizeCalculation {
+        success: result == 0,
+        resize: summary,
+    })
+    .into_response())
+}
+
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+pub struct PartitionCalculation {
+    /// Whether the proposal was successfully calculated.
+    success: bool,
+    /// Planned partition, with its region in sectors.
+    partition: ExplicitPartition,
+    /// Problems which do not prevent creating the partition (e.g., a misaligned region).
+    warnings: Vec<String>,
+}
+
+/// Creates a partition at an explicit position of a disk.
+///
+/// The region can be given as start and end sectors or as an offset and a size in bytes. It must
+/// be free space of the disk, and it is created as given even if it is not aligned.
+#[utoipa::path(
+    post,
+    path = "/proposal/partitions",
+    context_path = "/api/storage",
+    request_body = ExplicitPartition,
+    responses(
+        (status = 200, description = "Result of the proposal calculation", body = PartitionCalculation),
+        (status = 422, description = "The partition cannot be created in the given region", body = Vec<String>),
+        (status = 400, description = "The D-Bus service could not perform the action")
+    )
+)]
+async fn create_partition(
+    State(state): State<StorageState<'_>>,
+    Json(partition): Json<ExplicitPartition>,
+) -> Result<Response, Error> {
+    let devices = state.client.system_devices().await?;
+    let settings = state.client.proposal_settings().await?;
+    let mut partitions = settings.partitions;
+    partitions.push(partition);
+
+    let (errors, warnings) =
+        ExplicitPartition::resolve(&mut partitions, &devices, &settings.alignment);
+    if !errors.is_empty() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(errors)).into_response());
+    }
+
+    let partition = partitions[partitions.len() - 1].clone();
+    let patch = ProposalSettingsPatch {
+        partitions: Some(partitions),
+        ..Default::default()
+    };
+    let result = state.client.calculate(patch).await?;
+    Ok(Json(PartitionCalculation {
+        success: result == 0,
+        partition,
+        warnings,
+    })
+    .into_response())
+}
+
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+pub struct ProposalCalculation {
+    /// Whether the proposal was successfully calculated.
+    success: bool,
+    /// Adjustments made to the requested settings (e.g., rounded sizes).
+    warnings: Vec<String>,
+    /// Sizes of the volumes in the calculated proposal.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    sizes: Vec<VolumeSize>,
+}
+
+/// Reports the optional storage features.
+pub struct StorageCapabilities {
+    dasd: DASDClient<'static>,
+    zfcp: ZFCPClient<'static>,
+    iscsi: ISCSIClient<'static>,
+    nvmeof: NVMeoFClient<'static>,
+    bootloader: BootloaderClient<'static>,
+}
+
+impl StorageCapabilities {
+    pub async fn new(dbus: zbus::Connection) -> Result<Self, ServiceError> {
+        Ok(Self {
+            dasd: DASDClient::new(dbus.clone()).await?,
+            zfcp: ZFCPClient::new(dbus.clone()).await?,
+            iscsi: ISCSIClient::new(dbus.clone()).await?,
+            nvmeof: NVMeoFClient::new(dbus.clone()).await?,
+            bootloader: BootloaderClient::new(dbus).await?,
+        })
+    }
+}
+
+#[async_trait]
+impl CapabilitiesProvider for StorageCapabilities {
+    /// Reports whether DASD, zFCP, iSCSI, NVMe-oF, TPM-based encryption and EFI are available.
+    async fn capabilities(&self) -> ModuleCapabilities {
+        let efi = self.bootloader.is_efi().await.unwrap_or_else(|e| {
+            tracing::warn!("Could not determine whether the system uses EFI: {e}");
+            false
+        });
+        ModuleCapabilities::from([
+            (
+                "dasd".to_string(),
+                self.dasd.supported().await.unwrap_or(false),
+            ),
+            (
+                "zfcp".to_string(),
+                self.zfcp.supported().await.unwrap_or(false),
+            ),
+            (
+                "iscsi".to_string(),
+                self.iscsi.supported().await.unwrap_or(false),
+            ),
+            (
+                "nvmeof".to_string(),
+                self.nvmeof.supported().await.unwrap_or(false),
+            ),
+            ("tpm".to_string(), HardwareFacts::detect().await.tpm2),
+            ("efi".to_string(), efi),
+        ])
+    }
+}
+
+/// Reports the storage configuration and the actions to perform.
+pub struct StorageSnapshot {
+    client: StorageClient<'static>,
+}
+
+impl StorageSnapshot {
+    pub async fn new(dbus: zbus::Connection) -> Result<Self, ServiceError> {
+        Ok(Self {
+            client: StorageClient::new(dbus).await?,
+        })
+    }
+}
+
+#[async_trait]
+impl SnapshotProvider for StorageSnapshot {
+    async fn snapshot(&self) -> Result<serde_json::Value, Error> {
+        Ok(json!({
+            "config": self.client.get_config().await?,
+            "actions": self.client.actions().await?,
+        }))
+    }
+}
+
+/// Writes the storage changes to the system.
+///
+/// It cannot be committed until a target device is chosen.
+pub struct StorageCommit {
+    client: StorageClient<'static>,
+    lock: CommitLock,
+    fstab: FstabEntries,
+    impact: ImpactToken,
+}
+
+impl StorageCommit {
+    /// * `dbus`: D-Bus connection.
+    /// * `fstab`: additional fstab entries, shared with the storage service.
+    /// * `impact`: confirmation token of the storage impact, shared with the storage service.
+    pub async fn new(
+        dbus: zbus::Connection,
+        fstab: FstabEntries,
+        impact: ImpactToken,
+    ) -> Result<Self, ServiceError> {
+        Ok(Self {
+            client: StorageClient::new(dbus).await?,
+            lock: CommitLock::shared(),
+            fstab,
+            impact,
+        })
+    }
+}
+
+#[async_trait]
+impl CommitProvider for StorageCommit {
+    async fn blockers(&self) -> Result<Vec<String>, Error> {
+        let Ok(settings) = self.client.proposal_settings().await else {
+            return Ok(vec!["There is no storage proposal".to_string()]);
+        };
+        let chosen = settings
+            .target_device
+            .as_ref()
+            .is_some_and(|d| !d.is_empty());
+        if matches!(settings.target, ProposalTarget::Disk) && !chosen {
+            return Ok(vec!["No target device was chosen".to_string()]);
+        }
+        Ok(vec![])
+    }
+
+    async fn check_token(&self, token: Option<&str>) -> Result<Vec<String>, Error> {
+        Ok(self.impact.verify(&self.client, token).await?)
+    }
+
+    async fn commit(&self) -> Result<(), Error> {
+        let Some(_guard) = self.lock.try_acquire() else {
+            return Err(Error::Anyhow(
+                "A storage commit is already in progress".to_string(),
+            ));
+        };
+        self.client.install().await?;
+        self.fstab
+            .write(std::path::Path::new("/mnt"))
+            .await
+            .map_err(|e| Error::Anyhow(format!("Could not write the fstab entries: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommitLock;
+
+    #[test]
+    fn test_commit_lock() {
+        let lock = CommitLock::default();
+        assert!(!lock.is_running());
+
+        let guard = lock.try_acquire().unwrap();
+        assert!(lock.is_running());
+        assert!(lock.clone().try_acquire().is_none());
+
+        drop(guard);
+        assert!(!lock.is_running());
+        assert!(lock.try_acquire().is_some());
+    }
 }
@@ -36,7 +36,10 @@ use axum::{
 };
 use serde::Deserialize;
 
-use crate::{error::Error, web::common::EventStreams};
+use crate::{
+    error::Error,
+    web::{common::EventStreams, generous_timeout},
+};
 
 use self::stream::{DASDDeviceStream, DASDFormatJobStream};
 
@@ -72,7 +75,7 @@ pub async fn dasd_service<T>(dbus: &zbus::Connection) -> Result<Router<T>, Servi
         .route("/supported", get(supported))
         .route("/devices", get(devices))
         .route("/probe", post(probe))
-        .route("/format", post(format))
+        .route("/format", generous_timeout(post(format)))
         .route("/enable", post(enable))
         .route("/disable", post(disable))
         .route("/diag", put(set_diag))
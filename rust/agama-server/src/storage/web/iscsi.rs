@@ -27,24 +27,31 @@
 
 use crate::{
     error::Error,
-    web::{common::EventStreams, Event},
+    web::{
+        common::{ApplyParams, EventStreams, OperationStatus, Operations},
+        generous_timeout, Event,
+    },
 };
 use agama_lib::{
     dbus::{get_optional_property, to_owned_hash},
     error::ServiceError,
     storage::{
-        client::iscsi::{ISCSIAuth, ISCSIInitiator, ISCSINode, LoginResult},
+        client::iscsi::{
+            ISCSIActiveSession, ISCSIAuth, ISCSIAuthMode, ISCSIInitiator, ISCSIInterface,
+            ISCSINode, InitiatorNameReset, LoginError, STARTUP_MODES,
+        },
         ISCSIClient,
     },
 };
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::IntoResponse,
-    routing::{delete, get, post},
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
     Json, Router,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 
 mod stream;
 use stream::ISCSINodeStream;
@@ -107,6 +114,8 @@ fn handle_initiator_change(change: PropertiesChanged) -> Result<Option<Event>, S
 #[derive(Clone)]
 struct ISCSIState<'a> {
     client: ISCSIClient<'a>,
+    /// Registry of the storage operations, used for the discoveries.
+    operations: Operations,
 }
 
 /// Sets up and returns the Axum service for the iSCSI part of the storage module.
@@ -114,16 +123,26 @@ struct ISCSIState<'a> {
 /// It acts as a proxy to Agama D-Bus service.
 ///
 /// * `dbus`: D-Bus connection to use.
-pub async fn iscsi_service<T>(dbus: &zbus::Connection) -> Result<Router<T>, ServiceError> {
+/// * `operations`: registry to track the discoveries.
+pub async fn iscsi_service<T>(
+    dbus: &zbus::Connection,
+    operations: Operations,
+) -> Result<Router<T>, ServiceError> {
     let client = ISCSIClient::new(dbus.clone()).await?;
-    let state = ISCSIState { client };
+    let state = ISCSIState { client, operations };
     let router = Router::new()
         .route("/initiator", get(initiator).patch(update_initiator))
+        .route("/initiator/name", delete(reset_initiator_name))
+        .route("/initiator/interfaces", get(interfaces))
         .route("/nodes", get(nodes))
+        .route("/nodes/startup", patch(update_nodes_startup))
         .route("/nodes/:id", delete(delete_node).patch(update_node))
-        .route("/nodes/:id/login", post(login_node))
+        .route("/nodes/:id/login", generous_timeout(post(login_node)))
         .route("/nodes/:id/logout", post(logout_node))
-        .route("/discover", post(discover))
+        .route("/nodes/:id/test-auth", post(test_node_auth))
+        .route("/nodes/:id/session", get(node_session))
+        .route("/sessions", get(sessions))
+        .route("/discover", generous_timeout(post(discover)))
         .with_state(state);
     Ok(router)
 }
@@ -145,6 +164,25 @@ async fn initiator(State(state): State<ISCSIState<'_>>) -> Result<Json<ISCSIInit
     Ok(Json(initiator))
 }
 
+/// Returns the iSCSI interfaces, including the offload engines.
+///
+/// The interface of the default software initiator is not included, so the list is empty when
+/// there are no other interfaces.
+#[utoipa::path(
+    get,
+    path="/initiator/interfaces",
+    context_path="/api/storage/iscsi",
+    responses(
+        (status = OK, description = "iSCSI interfaces.", body = Vec<ISCSIInterface>),
+        (status = BAD_REQUEST, description = "It could not read the iSCSI interfaces."),
+    )
+)]
+async fn interfaces(
+    State(state): State<ISCSIState<'_>>,
+) -> Result<Json<Vec<ISCSIInterface>>, Error> {
+    Ok(Json(state.client.interfaces_details().await?))
+}
+
 #[derive(Deserialize, utoipa::ToSchema)]
 pub struct InitiatorParams {
     /// iSCSI initiator name.
@@ -169,6 +207,26 @@ async fn update_initiator(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Restores the iSCSI initiator name.
+///
+/// The name provided by the iBFT is used if it exists. Otherwise, it is set to the name
+/// configured in /etc/iscsi/initiatorname.iscsi. Nothing is changed if it already has that name.
+#[utoipa::path(
+    delete,
+    path="/initiator/name",
+    context_path="/api/storage/iscsi",
+    responses(
+        (status = OK, description = "Resulting initiator name.", body = InitiatorNameReset),
+        (status = BAD_REQUEST, description = "It could not restore the initiator name."),
+    )
+)]
+async fn reset_initiator_name(
+    State(state): State<ISCSIState<'_>>,
+) -> Result<Json<InitiatorNameReset>, Error> {
+    let reset = state.client.reset_initiator_name().await?;
+    Ok(Json(reset))
+}
+
 /// Returns the list of known iSCSI nodes.
 #[utoipa::path(
     get,
@@ -184,6 +242,51 @@ async fn nodes(State(state): State<ISCSIState<'_>>) -> Result<Json<Vec<ISCSINode
     Ok(Json(nodes))
 }
 
+/// Checks whether the startup value is allowed.
+///
+/// It returns an "unprocessable entity" response, including the allowed values, if it is not.
+///
+/// * `startup`: startup value.
+fn check_startup(startup: &str) -> Option<Response> {
+    if STARTUP_MODES.contains(&startup) {
+        return None;
+    }
+
+    let body = json!({
+        "error": format!("Invalid startup value: '{}'", startup),
+        "allowed": STARTUP_MODES
+    });
+    Some((StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response())
+}
+
+/// Checks whether the iSCSI interface exists.
+///
+/// It returns an "unprocessable entity" response, including the known interfaces, if it does not.
+///
+/// * `client`: iSCSI client.
+/// * `interface`: interface name (`None` for the default interface).
+async fn check_interface(
+    client: &ISCSIClient<'_>,
+    interface: Option<&str>,
+) -> Result<Option<Response>, Error> {
+    let Some(interface) = interface else {
+        return Ok(None);
+    };
+
+    let interfaces = client.interfaces().await?;
+    if interfaces.iter().any(|i| i == interface) {
+        return Ok(None);
+    }
+
+    let body = json!({
+        "error": format!("Unknown iSCSI interface: '{}'", interface),
+        "allowed": interfaces
+    });
+    Ok(Some(
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response(),
+    ))
+}
+
 #[derive(Deserialize, utoipa::ToSchema)]
 pub struct NodeParams {
     /// Startup value.
@@ -203,6 +306,7 @@ pub struct NodeParams {
     responses(
         (status = NO_CONTENT, description = "The iSCSI node was updated.", body = NodeParams),
         (status = BAD_REQUEST, description = "Could not update the iSCSI node."),
+        (status = UNPROCESSABLE_ENTITY, description = "Invalid startup value."),
     )
 )]
 async fn update_node(
@@ -210,8 +314,95 @@ async fn update_node(
     Path(id): Path<u32>,
     Json(params): Json<NodeParams>,
 ) -> Result<impl IntoResponse, Error> {
+    if let Some(response) = check_startup(&params.startup) {
+        return Ok(response);
+    }
+
     state.client.set_startup(id, &params.startup).await?;
-    Ok(StatusCode::NO_CONTENT)
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Nodes to apply a change to.
+#[derive(Deserialize, utoipa::ToSchema)]
+#[serde(untagged)]
+pub enum NodesSelection {
+    /// All the known nodes ("all").
+    All(AllNodes),
+    /// List of iSCSI artificial IDs.
+    Ids(Vec<u32>),
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub enum AllNodes {
+    #[serde(rename = "all")]
+    All,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct NodesStartupParams {
+    /// Nodes to update.
+    ids: NodesSelection,
+    /// Startup value.
+    startup: String,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct NodeStartupResult {
+    /// iSCSI artificial ID.
+    id: u32,
+    /// Whether the startup value was updated.
+    success: bool,
+    /// Error message if the node could not be updated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Updates the startup option of several iSCSI nodes at once.
+#[utoipa::path(
+    patch,
+    path="/nodes/startup",
+    context_path="/api/storage/iscsi",
+    request_body = NodesStartupParams,
+    responses(
+        (status = OK, description = "Result of updating each node.", body = Vec<NodeStartupResult>),
+        (status = BAD_REQUEST, description = "Could not read the iSCSI nodes."),
+        (status = UNPROCESSABLE_ENTITY, description = "Invalid startup value."),
+    )
+)]
+async fn update_nodes_startup(
+    State(state): State<ISCSIState<'_>>,
+    Json(params): Json<NodesStartupParams>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(response) = check_startup(&params.startup) {
+        return Ok(response);
+    }
+
+    let ids = match params.ids {
+        NodesSelection::All(_) => state
+            .client
+            .get_nodes()
+            .await?
+            .into_iter()
+            .map(|n| n.id)
+            .collect(),
+        NodesSelection::Ids(ids) => ids,
+    };
+
+    let mut results = Vec::with_capacity(ids.len());
+    for id in ids {
+        let error = state
+            .client
+            .set_startup(id, &params.startup)
+            .await
+            .err()
+            .map(|e| e.to_string());
+        results.push(NodeStartupResult {
+            id,
+            success: error.is_none(),
+            error,
+        });
+    }
+    Ok(Json(results).into_response())
 }
 
 /// Deletes the iSCSI node.
@@ -242,6 +433,9 @@ pub struct LoginParams {
     auth: ISCSIAuth,
     /// Startup value.
     startup: String,
+    /// iSCSI interface to bind the session to. The default interface is used if it is not set.
+    #[serde(default)]
+    interface: Option<String>,
 }
 
 #[utoipa::path(
@@ -254,8 +448,8 @@ pub struct LoginParams {
     responses(
         (status = NO_CONTENT, description = "The login request was successful."),
         (status = BAD_REQUEST, description = "Could not reach the iSCSI server."),
-        (status = UNPROCESSABLE_ENTITY, description = "The login request failed.",
-             body = LoginResult),
+        (status = UNPROCESSABLE_ENTITY, description = "The login request failed or the startup value or the authentication options are invalid.",
+             body = agama_lib::storage::client::iscsi::LoginError),
     )
 )]
 async fn login_node(
@@ -263,16 +457,103 @@ async fn login_node(
     Path(id): Path<u32>,
     Json(params): Json<LoginParams>,
 ) -> Result<impl IntoResponse, Error> {
-    let result = state.client.login(id, params.auth, params.startup).await?;
+    if let Some(response) = check_startup(&params.startup) {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, response));
+    }
+
+    let issues = params.auth.validate();
+    if !issues.is_empty() {
+        return Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(issues).into_response(),
+        ));
+    }
+
+    if let Some(response) = check_interface(&state.client, params.interface.as_deref()).await? {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, response));
+    }
+
+    let result = state
+        .client
+        .login(id, params.auth, params.startup, params.interface)
+        .await?;
     match result {
-        LoginResult::Success => Ok((StatusCode::NO_CONTENT, ().into_response())),
-        error => Ok((
+        Ok(()) => Ok((StatusCode::NO_CONTENT, ().into_response())),
+        Err(error) => Ok((
             StatusCode::UNPROCESSABLE_ENTITY,
             Json(error).into_response(),
         )),
     }
 }
 
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct TestAuthParams {
+    /// CHAP credentials to check.
+    #[serde(flatten)]
+    auth: ISCSIAuth,
+    /// iSCSI interface to bind the session to. The default interface is used if it is not set.
+    #[serde(default)]
+    interface: Option<String>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct TestAuthResult {
+    /// Whether the target accepted the credentials.
+    success: bool,
+    /// Reason of the failure, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<LoginError>,
+}
+
+/// Checks whether the target of a node accepts the given CHAP credentials.
+///
+/// It logs into the node and out of it right away, so no session is kept. The startup value and
+/// the credentials stored in the node are not changed. The node must not be connected.
+#[utoipa::path(
+    post,
+    path="/nodes/{id}/test-auth",
+    context_path="/api/storage/iscsi",
+    params(
+        ("id" = u32, Path, description = "iSCSI artificial ID.")
+    ),
+    request_body = TestAuthParams,
+    responses(
+        (status = 200, description = "Result of the authentication.", body = TestAuthResult),
+        (status = 400, description = "Could not reach the iSCSI server or close the session."),
+        (status = 422, description = "Unknown iSCSI interface or invalid credentials."),
+    )
+)]
+async fn test_node_auth(
+    State(state): State<ISCSIState<'_>>,
+    Path(id): Path<u32>,
+    Json(params): Json<TestAuthParams>,
+) -> Result<Response, Error> {
+    let mut issues = params.auth.validate();
+    let chap = params.auth.auth_mode != Some(ISCSIAuthMode::None)
+        && params.auth.username.is_some()
+        && params.auth.password.is_some();
+    if !chap {
+        issues.push("A CHAP username and password are required".to_string());
+    }
+    if !issues.is_empty() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(issues)).into_response());
+    }
+
+    if let Some(response) = check_interface(&state.client, params.interface.as_deref()).await? {
+        return Ok(response);
+    }
+
+    let result = state
+        .client
+        .test_auth(id, params.auth, params.interface)
+        .await?;
+    let result = TestAuthResult {
+        success: result.is_ok(),
+        error: result.err(),
+    };
+    Ok(Json(result).into_response())
+}
+
 #[utoipa::path(
     post,
     path="/nodes/{id}/logout",
@@ -297,6 +578,58 @@ async fn logout_node(
     }
 }
 
+/// Returns the live session of a logged-in node.
+#[utoipa::path(
+    get,
+    path="/nodes/{id}/session",
+    context_path="/api/storage/iscsi",
+    params(
+        ("id" = u32, Path, description = "iSCSI artificial ID.")
+    ),
+    responses(
+        (status = 200, description = "Session details.",
+             body = agama_lib::storage::client::iscsi::ISCSISession),
+        (status = 400, description = "Could not reach the iSCSI server."),
+        (status = 404, description = "The node is not connected or its session is unknown."),
+    )
+)]
+async fn node_session(
+    State(state): State<ISCSIState<'_>>,
+    Path(id): Path<u32>,
+) -> Result<Response, Error> {
+    match state.client.session(id).await? {
+        Some(session) => Ok(Json(session).into_response()),
+        None => {
+            let body = json!({
+                "error": "The iSCSI node is not connected or its session is unknown",
+                "id": id
+            });
+            Ok((StatusCode::NOT_FOUND, Json(body)).into_response())
+        }
+    }
+}
+
+/// Returns the iSCSI sessions currently held by the system.
+///
+/// Unlike the nodes, it includes the sessions established at boot time (e.g., through iBFT). Each
+/// session references the configured node for the same target, if any. The list is empty when
+/// the storage service does not report the live sessions.
+#[utoipa::path(
+    get,
+    path="/sessions",
+    context_path="/api/storage/iscsi",
+    responses(
+        (status = 200, description = "Active iSCSI sessions.",
+             body = Vec<agama_lib::storage::client::iscsi::ISCSIActiveSession>),
+        (status = 400, description = "Could not read the iSCSI sessions."),
+    )
+)]
+async fn sessions(
+    State(state): State<ISCSIState<'_>>,
+) -> Result<Json<Vec<ISCSIActiveSession>>, Error> {
+    Ok(Json(state.client.active_sessions().await?))
+}
+
 #[derive(Deserialize, utoipa::ToSchema)]
 pub struct DiscoverParams {
     /// iSCSI server address.
@@ -306,29 +639,87 @@ pub struct DiscoverParams {
     /// Authentication options.
     #[serde(default)]
     options: ISCSIAuth,
+    /// iSCSI interface to bind the discovery to. The default interface is used if it is not set.
+    #[serde(default)]
+    interface: Option<String>,
 }
 
 /// Performs an iSCSI discovery.
+///
+/// The discovery runs as a storage operation, so an unreachable portal does not block the client
+/// until the timeout expires. Using the `wait` query parameter returns the operation instead of
+/// the discovery result (see `/api/storage/operations`). The discovery cannot be canceled.
 #[utoipa::path(
     post,
     path="/discover",
     context_path="/api/storage/iscsi",
+    params(ApplyParams),
     responses(
-        (status = 204, description = "The iSCSI discovery request was successful."),
+        (status = 200, description = "The iSCSI discovery request was successful.",
+             body = agama_lib::storage::client::iscsi::DiscoveryResult),
+        (status = 202, description = "The iSCSI discovery is running.",
+             body = crate::web::common::Operation),
         (status = 400, description = "The iSCSI discovery request failed."),
+        (status = 422, description = "Unknown iSCSI interface or invalid authentication options."),
     )
 )]
 async fn discover(
-    State(state): State<ISCSIState<'_>>,
+    State(state): State<ISCSIState<'static>>,
+    Query(apply): Query<ApplyParams>,
     Json(params): Json<DiscoverParams>,
 ) -> Result<impl IntoResponse, Error> {
-    let result = state
-        .client
-        .discover(&params.address, params.port, params.options)
-        .await?;
-    if result {
-        Ok(StatusCode::NO_CONTENT)
-    } else {
-        Ok(StatusCode::BAD_REQUEST)
+    if let Some(response) = check_interface(&state.client, params.interface.as_deref()).await? {
+        return Ok(response);
+    }
+
+    let issues = params.options.validate();
+    if !issues.is_empty() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(issues)).into_response());
+    }
+
+    let client = state.client.clone();
+    let future = async move {
+        let result = client
+            .discover_nodes(
+                &params.address,
+                params.port,
+                params.options,
+                params.interface.as_deref(),
+            )
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "The iSCSI discovery failed".to_string())?;
+        serde_json::to_value(result).map_err(|e| e.to_string())
+    };
+
+    if apply.wait.is_some() {
+        return Ok(state.operations.run(&apply, future).await);
+    }
+
+    let (_, done) = state.operations.start(future);
+    let Ok(finished) = done.await else {
+        return Ok(StatusCode::BAD_REQUEST.into_response());
+    };
+    match (finished.status, finished.result) {
+        (OperationStatus::Finished, Some(result)) => Ok(Json(result).into_response()),
+        _ => Ok(StatusCode::BAD_REQUEST.into_response()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_startup;
+    use axum::http::StatusCode;
+
+    #[test]
+    fn test_check_startup() {
+        for startup in ["onboot", "automatic", "manual"] {
+            assert!(check_startup(startup).is_none());
+        }
+
+        for startup in ["", "on-boot", "Manual"] {
+            let response = check_startup(startup).unwrap();
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        }
     }
 }
@@ -0,0 +1,184 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! This module implements the web API for the NVMe-oF handling of the storage service.
+//!
+//! The module offers one public function:
+//!
+//! * `nvmeof_service` which returns the Axum service.
+
+use agama_lib::{
+    error::ServiceError,
+    storage::{
+        model::nvmeof::{NVMeoFController, NVMeoFResult, NVMeoFTarget},
+        NVMeoFClient,
+    },
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
+    Json, Router,
+};
+
+use crate::{error::Error, web::generous_timeout};
+
+#[derive(Clone)]
+struct NVMeoFState<'a> {
+    client: NVMeoFClient<'a>,
+}
+
+/// Sets up and returns the Axum service for the NVMe-oF part of the storage module.
+///
+/// It acts as a proxy to Agama D-Bus service.
+///
+/// * `dbus`: D-Bus connection to use.
+pub async fn nvmeof_service<T>(dbus: &zbus::Connection) -> Result<Router<T>, ServiceError> {
+    let client = NVMeoFClient::new(dbus.clone()).await?;
+    let state = NVMeoFState { client };
+    let router = Router::new()
+        .route("/supported", get(supported))
+        .route("/discover", generous_timeout(post(discover)))
+        .route("/connect", generous_timeout(post(connect)))
+        .route("/controllers", get(controllers))
+        .route("/controllers/:name", delete(disconnect))
+        .with_state(state);
+    Ok(router)
+}
+
+/// Returns whether NVMe-oF is supported or not.
+#[utoipa::path(
+    get,
+    path="/supported",
+    context_path="/api/storage/nvmeof",
+    responses(
+        (status = OK, description = "Returns whether NVMe-oF is supported")
+    )
+)]
+async fn supported(State(state): State<NVMeoFState<'_>>) -> Result<Json<bool>, Error> {
+    Ok(Json(state.client.supported().await?))
+}
+
+/// Returns the connected NVMe-oF controllers.
+#[utoipa::path(
+    get,
+    path="/controllers",
+    context_path="/api/storage/nvmeof",
+    responses(
+        (status = OK, description = "Connected NVMe-oF controllers.", body = Vec<NVMeoFController>),
+        (status = BAD_REQUEST, description = "It could not read the NVMe-oF controllers."),
+    )
+)]
+async fn controllers(
+    State(state): State<NVMeoFState<'_>>,
+) -> Result<Json<Vec<NVMeoFController>>, Error> {
+    Ok(Json(state.client.controllers().await?))
+}
+
+/// Asks a discovery controller for the NVMe-oF subsystems it exports.
+///
+/// The subsystem NQN is ignored and the default discovery port (8009) is used if the service ID
+/// is not set.
+#[utoipa::path(
+    post,
+    path="/discover",
+    context_path="/api/storage/nvmeof",
+    request_body = NVMeoFTarget,
+    responses(
+        (status = OK, description = "The discovered targets.", body = Vec<NVMeoFTarget>),
+        (status = BAD_REQUEST, description = "The D-Bus service could not perform the action."),
+        (status = UNPROCESSABLE_ENTITY, description = "The address is invalid or the discovery failed.",
+             body = agama_lib::storage::model::nvmeof::NVMeoFError),
+    )
+)]
+async fn discover(
+    State(state): State<NVMeoFState<'_>>,
+    Json(target): Json<NVMeoFTarget>,
+) -> Result<Response, Error> {
+    let issues = target.validate_address();
+    if !issues.is_empty() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(issues)).into_response());
+    }
+
+    match state.client.discover(&target).await? {
+        Ok(targets) => Ok(Json(targets).into_response()),
+        Err(error) => Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(error)).into_response()),
+    }
+}
+
+/// Connects to an NVMe-oF subsystem.
+///
+/// The default port (4420) is used if the service ID is not set.
+#[utoipa::path(
+    post,
+    path="/connect",
+    context_path="/api/storage/nvmeof",
+    request_body = NVMeoFTarget,
+    responses(
+        (status = NO_CONTENT, description = "The connection request was successful."),
+        (status = BAD_REQUEST, description = "The D-Bus service could not perform the action."),
+        (status = UNPROCESSABLE_ENTITY, description = "The target is invalid or the connection failed.",
+             body = agama_lib::storage::model::nvmeof::NVMeoFError),
+    )
+)]
+async fn connect(
+    State(state): State<NVMeoFState<'_>>,
+    Json(target): Json<NVMeoFTarget>,
+) -> Result<Response, Error> {
+    let issues = target.validate();
+    if !issues.is_empty() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(issues)).into_response());
+    }
+
+    match state.client.connect(&target).await? {
+        Ok(()) => Ok(StatusCode::NO_CONTENT.into_response()),
+        Err(error) => Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(error)).into_response()),
+    }
+}
+
+/// Disconnects an NVMe-oF controller.
+#[utoipa::path(
+    delete,
+    path="/controllers/{name}",
+    context_path="/api/storage/nvmeof",
+    params(
+        ("name" = String, Path, description = "Controller name (e.g., nvme1).")
+    ),
+    responses(
+        (status = NO_CONTENT, description = "The controller was disconnected."),
+        (status = BAD_REQUEST, description = "The D-Bus service could not perform the action."),
+        (status = NOT_FOUND, description = "Unknown controller."),
+        (status = UNPROCESSABLE_ENTITY, description = "The disconnection failed.",
+             body = agama_lib::storage::model::nvmeof::NVMeoFError),
+    )
+)]
+async fn disconnect(
+    State(state): State<NVMeoFState<'_>>,
+    Path(name): Path<String>,
+) -> Result<Response, Error> {
+    match state.client.disconnect(&name).await? {
+        Ok(()) => Ok(StatusCode::NO_CONTENT.into_response()),
+        Err(error) if error.code == NVMeoFResult::UnknownController => {
+            Ok((StatusCode::NOT_FOUND, Json(error)).into_response())
+        }
+        Err(error) => Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(error)).into_response()),
+    }
+}
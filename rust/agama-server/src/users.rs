@@ -18,5 +18,6 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
+pub mod password;
 pub mod web;
 pub use web::{users_service, users_streams};
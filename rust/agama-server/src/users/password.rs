@@ -0,0 +1,169 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Generates random passwords for the users.
+
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+const LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &[u8] = b"0123456789";
+/// Symbols which do not need to be quoted or escaped in a shell or in a JSON string.
+const SYMBOLS: &[u8] = b"!#%+,-./:=?@^_~";
+
+/// Characters and length of a generated password.
+#[derive(Clone, Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PasswordParams {
+    /// Number of characters (16 by default).
+    pub length: usize,
+    /// Whether to include lowercase letters.
+    pub lowercase: bool,
+    /// Whether to include uppercase letters.
+    pub uppercase: bool,
+    /// Whether to include digits.
+    pub digits: bool,
+    /// Whether to include symbols.
+    pub symbols: bool,
+}
+
+impl Default for PasswordParams {
+    fn default() -> Self {
+        Self {
+            length: 16,
+            lowercase: true,
+            uppercase: true,
+            digits: true,
+            symbols: true,
+        }
+    }
+}
+
+impl PasswordParams {
+    pub const MIN_LENGTH: usize = 8;
+    pub const MAX_LENGTH: usize = 128;
+
+    /// Returns the list of problems found in the parameters.
+    ///
+    /// An empty list means that a password can be generated.
+    pub fn validate(&self) -> Vec<String> {
+        let mut issues = vec![];
+
+        if !(Self::MIN_LENGTH..=Self::MAX_LENGTH).contains(&self.length) {
+            issues.push(format!(
+                "The password length must be between {} and {} characters",
+                Self::MIN_LENGTH,
+                Self::MAX_LENGTH
+            ));
+        }
+
+        if self.classes().is_empty() {
+            issues.push("At least one class of characters must be included".to_string());
+        }
+
+        issues
+    }
+
+    fn classes(&self) -> Vec<&'static [u8]> {
+        [
+            (self.lowercase, LOWERCASE),
+            (self.uppercase, UPPERCASE),
+            (self.digits, DIGITS),
+            (self.symbols, SYMBOLS),
+        ]
+        .into_iter()
+        .filter_map(|(included, class)| included.then_some(class))
+        .collect()
+    }
+}
+
+/// Password generated for a user. It is not stored anywhere.
+#[derive(Clone, Serialize, utoipa::ToSchema)]
+pub struct GeneratedPassword {
+    /// Password in plain text.
+    pub password: String,
+}
+
+/// Generates a random password containing, at least, one character of each included class.
+///
+/// The parameters are expected to be valid (see [PasswordParams::validate]).
+///
+/// * `params`: characters and length of the password.
+pub fn generate_password(params: &PasswordParams) -> GeneratedPassword {
+    let classes = params.classes();
+    let all: Vec<u8> = classes.concat();
+    let mut rng = rand::thread_rng();
+
+    let mut chars: Vec<u8> = classes
+        .iter()
+        .map(|class| class[rng.gen_range(0..class.len())])
+        .collect();
+    while chars.len() < params.length {
+        chars.push(all[rng.gen_range(0..all.len())]);
+    }
+    // otherwise, the first characters would always follow the order of the classes
+    chars.shuffle(&mut rng);
+
+    GeneratedPassword {
+        password: chars.into_iter().map(char::from).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_password() {
+        let params = PasswordParams::default();
+        assert!(params.validate().is_empty());
+        let password = generate_password(&params).password;
+        assert_eq!(password.len(), 16);
+        assert!(password.bytes().any(|c| LOWERCASE.contains(&c)));
+        assert!(password.bytes().any(|c| UPPERCASE.contains(&c)));
+        assert!(password.bytes().any(|c| DIGITS.contains(&c)));
+        assert!(password.bytes().any(|c| SYMBOLS.contains(&c)));
+
+        let params = PasswordParams {
+            length: 10,
+            symbols: false,
+            uppercase: false,
+            ..Default::default()
+        };
+        let password = generate_password(&params).password;
+        assert_eq!(password.len(), 10);
+        assert!(password
+            .bytes()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn test_validate_password_params() {
+        let params = PasswordParams {
+            length: 4,
+            lowercase: false,
+            uppercase: false,
+            digits: false,
+            symbols: false,
+        };
+        assert_eq!(params.validate().len(), 2);
+    }
+}
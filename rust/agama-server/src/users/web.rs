@@ -26,6 +26,7 @@
 
 use crate::{
     error::Error,
+    users::password::{generate_password, GeneratedPassword, PasswordParams},
     web::{
         common::{issues_router, service_status_router, EventStreams},
         Event,
@@ -33,18 +34,26 @@ use crate::{
 };
 use agama_lib::{
     error::ServiceError,
+    software::SoftwareClient,
     users::{
-        model::{RootConfig, RootPatchSettings},
+        model::{RootConfig, RootPatchSettings, UserAccount, UserExpiration, UserGroup},
         proxies::Users1Proxy,
         FirstUser, UsersClient,
     },
 };
-use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Json, Router,
+};
 use tokio_stream::{Stream, StreamExt};
 
 #[derive(Clone)]
 struct UsersState<'a> {
     users: UsersClient<'a>,
+    software: SoftwareClient<'a>,
 }
 
 /// Returns streams that emits users related events coming from D-Bus.
@@ -85,15 +94,8 @@ async fn first_user_changed_stream(
         .receive_first_user_changed()
         .await
         .then(|change| async move {
-            if let Ok(user) = change.get().await {
-                let user_struct = FirstUser {
-                    full_name: user.0,
-                    user_name: user.1,
-                    password: user.2,
-                    autologin: user.3,
-                    data: user.4,
-                };
-                return Some(Event::FirstUserChanged(user_struct));
+            if let Ok(user) = FirstUser::from_dbus(change.get().await) {
+                return Some(Event::FirstUserChanged(user));
             }
             None
         })
@@ -147,7 +149,8 @@ pub async fn users_service(dbus: zbus::Connection) -> Result<Router, ServiceErro
     const DBUS_PATH: &str = "/org/opensuse/Agama/Users1";
 
     let users = UsersClient::new(dbus.clone()).await?;
-    let state = UsersState { users };
+    let software = SoftwareClient::new(dbus.clone()).await?;
+    let state = UsersState { users, software };
     let issues_router = issues_router(&dbus, DBUS_SERVICE, DBUS_PATH).await?;
     let status_router = service_status_router(&dbus, DBUS_SERVICE, DBUS_PATH).await?;
     let router = Router::new()
@@ -157,7 +160,15 @@ pub async fn users_service(dbus: zbus::Connection) -> Result<Router, ServiceErro
                 .put(set_first_user)
                 .delete(remove_first_user),
         )
+        .route("/first/expiration", get(get_user_expiration))
+        .route(
+            "/default_shell",
+            get(get_default_shell).put(set_default_shell),
+        )
         .route("/root", get(get_root_config).patch(patch_root))
+        .route("/password", post(generate_user_password))
+        .route("/groups", get(get_groups).put(set_groups))
+        .route("/groups/:name", put(set_group).delete(remove_group))
         .merge(status_router)
         .nest("/issues", issues_router)
         .with_state(state);
@@ -174,6 +185,11 @@ async fn remove_first_user(State(state): State<UsersState<'_>>) -> Result<(), Er
     Ok(())
 }
 
+/// Sets the first user.
+///
+/// The UID, GID, home directory and shell are optional. When they are set, the IDs must be in the
+/// range for regular accounts, the home directory must be an absolute path and the shell must be
+/// available in the target system.
 #[utoipa::path(put, path = "/users/first", responses(
     (status = 200, description = "Sets the first user"),
     (status = 400, description = "The D-Bus service could not perform the action"),
@@ -183,6 +199,20 @@ async fn set_first_user(
     State(state): State<UsersState<'_>>,
     Json(config): Json<FirstUser>,
 ) -> Result<impl IntoResponse, Error> {
+    let mut issues = config.expiration.validate();
+    issues.extend(config.account.validate());
+    if issues.is_empty() {
+        if let Some(shell) = &config.account.shell {
+            issues.extend(check_shell(&state, shell).await?);
+        }
+    }
+    if !issues.is_empty() {
+        return Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(issues).into_response(),
+        ));
+    }
+
     // issues: for example, trying to use a system user id; empty password
     // success: simply issues.is_empty()
     let (_success, issues) = state.users.set_first_user(&config).await?;
@@ -195,12 +225,29 @@ async fn set_first_user(
     Ok((status, Json(issues).into_response()))
 }
 
+/// Returns the first user.
+///
+/// When the user has no explicit shell, it reports the default one.
 #[utoipa::path(get, path = "/users/first", responses(
     (status = 200, description = "Configuration for the first user", body = FirstUser),
     (status = 400, description = "The D-Bus service could not perform the action"),
 ))]
 async fn get_user_config(State(state): State<UsersState<'_>>) -> Result<Json<FirstUser>, Error> {
-    Ok(Json(state.users.first_user().await?))
+    let mut user = state.users.first_user().await?;
+    if !user.user_name.is_empty() && user.account.shell.is_none() {
+        user.account.shell = Some(state.users.default_shell().await?);
+    }
+    Ok(Json(user))
+}
+
+#[utoipa::path(get, path = "/users/first/expiration", responses(
+    (status = 200, description = "Expiration policy for the first user", body = UserExpiration),
+    (status = 400, description = "The D-Bus service could not perform the action"),
+))]
+async fn get_user_expiration(
+    State(state): State<UsersState<'_>>,
+) -> Result<Json<UserExpiration>, Error> {
+    Ok(Json(state.users.first_user().await?.expiration))
 }
 
 #[utoipa::path(patch, path = "/users/root", responses(
@@ -243,3 +290,163 @@ async fn get_root_config(State(state): State<UsersState<'_>>) -> Result<Json<Roo
     let config = RootConfig { password, sshkey };
     Ok(Json(config))
 }
+
+/// Generates a random password for a user.
+///
+/// It can be used as it is to set the first user or the root password. The password is not
+/// stored nor logged, so it is only available in the response.
+#[utoipa::path(post, path = "/users/password", request_body = PasswordParams, responses(
+    (status = 200, description = "Generated password", body = GeneratedPassword),
+    (status = 422, description = "Invalid parameters. Details are in body", body = Vec<String>),
+))]
+async fn generate_user_password(Json(params): Json<PasswordParams>) -> Response {
+    let issues = params.validate();
+    if !issues.is_empty() {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(issues)).into_response();
+    }
+
+    (
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(generate_password(&params)),
+    )
+        .into_response()
+}
+
+#[utoipa::path(get, path = "/users/default_shell", responses(
+    (status = 200, description = "Login shell for the users without an explicit one", body = String),
+    (status = 400, description = "The D-Bus service could not perform the action"),
+))]
+async fn get_default_shell(State(state): State<UsersState<'_>>) -> Result<Json<String>, Error> {
+    Ok(Json(state.users.default_shell().await?))
+}
+
+/// Sets the login shell for the users without an explicit one.
+///
+/// The shell must be available in the target system.
+#[utoipa::path(put, path = "/users/default_shell", responses(
+    (status = 200, description = "Sets the default shell"),
+    (status = 400, description = "The D-Bus service could not perform the action"),
+    (status = 422, description = "Invalid shell. Details are in body", body = Vec<String>),
+))]
+async fn set_default_shell(
+    State(state): State<UsersState<'_>>,
+    Json(shell): Json<String>,
+) -> Result<impl IntoResponse, Error> {
+    let issues = match UserAccount::validate_shell(&shell) {
+        Some(issue) => vec![issue],
+        None => check_shell(&state, &shell).await?,
+    };
+    if !issues.is_empty() {
+        return Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(issues).into_response(),
+        ));
+    }
+
+    state.users.set_default_shell(&shell).await?;
+    Ok((StatusCode::OK, ().into_response()))
+}
+
+/// Checks whether a shell will exist in the target system.
+///
+/// The shell is available when any selected package provides it.
+async fn check_shell(state: &UsersState<'_>, shell: &str) -> Result<Vec<String>, Error> {
+    let selected = state.software.provisions_selected(&[shell]).await?;
+    if selected.first().copied().unwrap_or(false) {
+        return Ok(vec![]);
+    }
+    Ok(vec![format!(
+        "Shell '{}' is not available in the target system",
+        shell
+    )])
+}
+
+#[utoipa::path(get, path = "/users/groups", responses(
+    (status = 200, description = "Groups to create in the target system", body = Vec<UserGroup>),
+    (status = 400, description = "The D-Bus service could not perform the action"),
+))]
+async fn get_groups(State(state): State<UsersState<'_>>) -> Result<Json<Vec<UserGroup>>, Error> {
+    Ok(Json(state.users.groups().await?))
+}
+
+/// Sets the groups to create in the target system, replacing the previous ones.
+///
+/// The members must be configured users (root or the first user).
+#[utoipa::path(put, path = "/users/groups", responses(
+    (status = 200, description = "Sets the groups"),
+    (status = 400, description = "The D-Bus service could not perform the action"),
+    (status = 422, description = "Invalid groups. Details are in body", body = Vec<String>),
+))]
+async fn set_groups(
+    State(state): State<UsersState<'_>>,
+    Json(groups): Json<Vec<UserGroup>>,
+) -> Result<impl IntoResponse, Error> {
+    update_groups(&state, groups).await
+}
+
+/// Adds a group or replaces the one with the same name.
+#[utoipa::path(put, path = "/users/groups/:name",
+    params(("name" = String, Path, description = "Group name")),
+    responses(
+        (status = 200, description = "Sets the group"),
+        (status = 400, description = "The D-Bus service could not perform the action"),
+        (status = 422, description = "Invalid group. Details are in body", body = Vec<String>),
+    )
+)]
+async fn set_group(
+    State(state): State<UsersState<'_>>,
+    Path(name): Path<String>,
+    Json(group): Json<UserGroup>,
+) -> Result<impl IntoResponse, Error> {
+    let mut groups = state.users.groups().await?;
+    let group = UserGroup { name, ..group };
+    match groups.iter_mut().find(|g| g.name == group.name) {
+        Some(current) => *current = group,
+        None => groups.push(group),
+    }
+    update_groups(&state, groups).await
+}
+
+/// Removes a group.
+#[utoipa::path(delete, path = "/users/groups/:name",
+    params(("name" = String, Path, description = "Group name")),
+    responses(
+        (status = 200, description = "Removes the group"),
+        (status = 400, description = "The D-Bus service could not perform the action"),
+        (status = 404, description = "Unknown group"),
+    )
+)]
+async fn remove_group(
+    State(state): State<UsersState<'_>>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, Error> {
+    let mut groups = state.users.groups().await?;
+    let count = groups.len();
+    groups.retain(|g| g.name != name);
+    if groups.len() == count {
+        return Ok(StatusCode::NOT_FOUND);
+    }
+    state.users.set_groups(&groups).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn update_groups(
+    state: &UsersState<'_>,
+    groups: Vec<UserGroup>,
+) -> Result<(StatusCode, Response), Error> {
+    let first_user = state.users.first_user().await?;
+    let mut users = vec!["root"];
+    if !first_user.user_name.is_empty() {
+        users.push(first_user.user_name.as_str());
+    }
+    let issues = UserGroup::validate_all(&groups, &users);
+    if !issues.is_empty() {
+        return Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(issues).into_response(),
+        ));
+    }
+
+    state.users.set_groups(&groups).await?;
+    Ok((StatusCode::OK, ().into_response()))
+}
@@ -25,34 +25,73 @@
 //! * Serve the code for the web user interface (not implemented yet).
 
 use crate::{
+    bootloader::web::bootloader_service,
     error::Error,
-    l10n::web::l10n_service,
+    firewall::web::firewall_service,
+    l10n::web::{l10n_service, L10nCommit},
     manager::web::{manager_service, manager_stream},
-    network::{web::network_service, NetworkManagerAdapter},
+    network::{
+        web::{network_router, NetworkCommit, NetworkReadiness, NetworkSnapshot},
+        NetworkManagerAdapter, NetworkSystem,
+    },
+    preview::preview_service,
+    profile::web::profile_service,
     questions::web::{questions_service, questions_stream},
-    software::web::{software_service, software_streams},
-    storage::web::{storage_service, storage_streams},
+    scripts::web::scripts_service,
+    software::web::{software_service, software_streams, SoftwareCapabilities, SoftwareSnapshot},
+    storage::{
+        fstab::{FstabEntries, PENDING_ENTRIES_PATH},
+        impact::ImpactToken,
+        web::{
+            storage_service, storage_streams, StorageCapabilities, StorageCommit, StorageSnapshot,
+        },
+    },
     users::web::{users_service, users_streams},
     web::common::{issues_stream, jobs_stream, progress_stream, service_status_stream},
 };
+use anyhow::Context;
 use axum::Router;
 
 mod auth;
+mod backends;
+mod body_limit;
+mod capabilities;
+mod changes;
+mod commit;
 pub mod common;
 mod config;
+pub mod cors;
 mod docs;
+mod estimate;
 mod event;
+mod history;
 mod http;
+mod language;
+mod metrics;
+mod read_only;
+mod readiness;
+mod request_id;
 mod service;
+mod snapshot;
+mod sse;
 mod state;
+mod timeout;
 mod ws;
 
 use agama_lib::{connection, error::ServiceError};
+pub use backends::Backend;
+pub use capabilities::{CapabilitiesProvider, ModuleCapabilities};
+pub use commit::{CommitProvider, CommittedModules};
 pub use config::ServiceConfig;
 pub use docs::ApiDoc;
 pub use event::{Event, EventsReceiver, EventsSender};
+pub use language::{translate, translate_with};
+pub use readiness::{IssueSeverity, IssuesReadiness, ReadinessIssue, ReadinessProvider};
+pub use request_id::{current_request_id, REQUEST_ID_HEADER};
 pub use service::MainServiceBuilder;
+pub use snapshot::SnapshotProvider;
 use std::path::Path;
+pub use timeout::{generous_timeout, no_timeout};
 use tokio_stream::{StreamExt, StreamMap};
 
 /// Returns a service that implements the web-based Agama API.
@@ -73,16 +112,132 @@ where
     let network_adapter = NetworkManagerAdapter::from_system()
         .await
         .expect("Could not connect to NetworkManager to read the configuration");
+    let network = NetworkSystem::new(network_adapter)
+        .start()
+        .await
+        .context("Could not start the network configuration service.")?;
+    // NetworkManager is on the system bus
+    let system_dbus = zbus::Connection::system().await?;
+
+    let committed = CommittedModules::default();
+    let fstab = FstabEntries::new(PENDING_ENTRIES_PATH);
+    let impact = ImpactToken::default();
 
     let router = MainServiceBuilder::new(events.clone(), web_ui_dir)
         .add_service("/l10n", l10n_service(dbus.clone(), events.clone()).await?)
-        .add_service("/manager", manager_service(dbus.clone()).await?)
-        .add_service("/software", software_service(dbus.clone()).await?)
-        .add_service("/storage", storage_service(dbus.clone()).await?)
-        .add_service("/network", network_service(network_adapter, events).await?)
+        .add_service(
+            "/manager",
+            manager_service(
+                dbus.clone(),
+                network.clone(),
+                committed.clone(),
+                impact.clone(),
+                events.clone(),
+            )
+            .await?,
+        )
+        .add_service(
+            "/software",
+            software_service(dbus.clone(), events.clone()).await?,
+        )
+        .add_service(
+            "/storage",
+            storage_service(dbus.clone(), events.clone(), fstab.clone(), impact.clone()).await?,
+        )
+        .add_service("/bootloader", bootloader_service(dbus.clone()).await?)
+        .add_service("/firewall", firewall_service(dbus.clone()).await?)
+        .add_service("/network", network_router(network.clone(), events.clone()))
+        .add_service(
+            "/config",
+            preview_service(dbus.clone(), network.clone(), fstab.clone()).await?,
+        )
+        .add_service("/profile", profile_service())
         .add_service("/questions", questions_service(dbus.clone()).await?)
+        .add_service("/scripts", scripts_service(dbus.clone()).await?)
         .add_service("/users", users_service(dbus.clone()).await?)
+        .add_capabilities("software", SoftwareCapabilities::new(dbus.clone()).await?)
+        .add_capabilities("storage", StorageCapabilities::new(dbus.clone()).await?)
+        .add_readiness(
+            "network",
+            NetworkReadiness::new(network.clone(), dbus.clone()).await?,
+        )
+        .add_readiness(
+            "product",
+            IssuesReadiness::new(
+                &dbus,
+                "org.opensuse.Agama.Software1",
+                "/org/opensuse/Agama/Software1/Product",
+            )
+            .await?,
+        )
+        .add_readiness(
+            "software",
+            IssuesReadiness::new(
+                &dbus,
+                "org.opensuse.Agama.Software1",
+                "/org/opensuse/Agama/Software1",
+            )
+            .await?,
+        )
+        .add_readiness(
+            "storage",
+            IssuesReadiness::new(
+                &dbus,
+                "org.opensuse.Agama.Storage1",
+                "/org/opensuse/Agama/Storage1",
+            )
+            .await?,
+        )
+        .add_readiness(
+            "users",
+            IssuesReadiness::new(
+                &dbus,
+                "org.opensuse.Agama.Manager1",
+                "/org/opensuse/Agama/Users1",
+            )
+            .await?,
+        )
+        .add_snapshot("network", NetworkSnapshot::new(network.clone()))
+        .add_snapshot("software", SoftwareSnapshot::new(dbus.clone()).await?)
+        .add_snapshot("storage", StorageSnapshot::new(dbus.clone()).await?)
+        .add_commit("l10n", L10nCommit::new(dbus.clone()).await?)
+        .add_commit("network", NetworkCommit::new(network.clone()))
+        .add_commit(
+            "storage",
+            StorageCommit::new(dbus.clone(), fstab, impact).await?,
+        )
+        .add_backend(
+            "manager",
+            Backend::new(
+                &dbus,
+                "org.opensuse.Agama.Manager1",
+                &["/manager", "/users", "/firewall", "/scripts"],
+            ),
+        )
+        .add_backend(
+            "network",
+            Backend::new(
+                &system_dbus,
+                "org.freedesktop.NetworkManager",
+                &["/network"],
+            ),
+        )
+        .add_backend(
+            "software",
+            Backend::new(&dbus, "org.opensuse.Agama.Software1", &["/software"]),
+        )
+        .add_backend(
+            "storage",
+            Backend::new(
+                &dbus,
+                "org.opensuse.Agama.Storage1",
+                &["/storage", "/bootloader"],
+            ),
+        )
+        .with_committed_modules(committed)
+        .with_dbus(dbus)
         .with_config(config)
+        .with_runtime(tokio::runtime::Handle::current())
         .build();
     Ok(router)
 }
@@ -0,0 +1,274 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the status of the D-Bus backend services.
+//!
+//! Most of the API relies on D-Bus services (e.g., `org.opensuse.Agama.Storage1`). They are
+//! registered using [add_backend](super::MainServiceBuilder::add_backend), together with the API
+//! paths that depend on them. The `/api/backends` endpoint reports whether each service is running
+//! and answers to a ping. When a service is not available, the requests to its paths are rejected
+//! with a `503 Service Unavailable` status naming the backend, instead of failing with a generic
+//! error, and the readiness report includes a blocking issue.
+
+use super::state::ServiceState;
+use crate::web::current_request_id;
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use utoipa::ToSchema;
+
+/// Time to wait for the answer to a ping.
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Time to reuse the status of a backend before checking it again.
+const STATUS_TTL: Duration = Duration::from_secs(5);
+
+/// D-Bus errors meaning that nobody answered the ping.
+const UNAVAILABLE_ERRORS: [&str; 4] = [
+    "org.freedesktop.DBus.Error.ServiceUnknown",
+    "org.freedesktop.DBus.Error.NameHasNoOwner",
+    "org.freedesktop.DBus.Error.NoReply",
+    "org.freedesktop.DBus.Error.Timeout",
+];
+
+/// Status of a D-Bus backend service.
+#[derive(Clone, Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendStatus {
+    /// Backend name (e.g., "storage").
+    pub name: String,
+    /// D-Bus service name (e.g., "org.opensuse.Agama.Storage1").
+    pub service: String,
+    /// Whether the service is running (it owns its D-Bus name).
+    pub running: bool,
+    /// Whether the service answered to a ping in time.
+    pub responsive: bool,
+    /// Time to answer the ping, in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// Reason why the service is not available.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl BackendStatus {
+    /// Whether the service can handle the requests.
+    pub fn available(&self) -> bool {
+        self.running && self.responsive
+    }
+}
+
+/// D-Bus service the API depends on.
+pub struct Backend {
+    connection: zbus::Connection,
+    service: String,
+    paths: Vec<String>,
+    cached: Mutex<Option<(Instant, BackendStatus)>>,
+}
+
+impl Backend {
+    /// * `connection`: D-Bus connection.
+    /// * `service`: D-Bus service name (e.g., "org.opensuse.Agama.Storage1").
+    /// * `paths`: API paths, relative to `/api`, depending on the service (e.g., "/storage").
+    pub fn new(connection: &zbus::Connection, service: &str, paths: &[&str]) -> Self {
+        Self {
+            connection: connection.clone(),
+            service: service.to_string(),
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Whether the given API path (relative to `/api`) depends on this backend.
+    pub fn serves(&self, path: &str) -> bool {
+        self.paths.iter().any(|p| is_under(path, p))
+    }
+
+    /// Returns the status of the backend, checking it again if the last one is too old.
+    ///
+    /// * `name`: backend name.
+    pub async fn status(&self, name: &str) -> BackendStatus {
+        if let Some((checked_at, status)) = self.cached.lock().unwrap().as_ref() {
+            if checked_at.elapsed() < STATUS_TTL {
+                return status.clone();
+            }
+        }
+
+        let status = self.check(name).await;
+        *self.cached.lock().unwrap() = Some((Instant::now(), status.clone()));
+        status
+    }
+
+    async fn check(&self, name: &str) -> BackendStatus {
+        let mut status = BackendStatus {
+            name: name.to_string(),
+            service: self.service.clone(),
+            running: false,
+            responsive: false,
+            latency_ms: None,
+            error: None,
+        };
+
+        match self.has_owner().await {
+            Ok(true) => status.running = true,
+            Ok(false) => {
+                status.error = Some(format!("{} is not running", self.service));
+                return status;
+            }
+            Err(error) => {
+                status.error = Some(error.to_string());
+                return status;
+            }
+        }
+
+        let start = Instant::now();
+        match tokio::time::timeout(PING_TIMEOUT, self.ping()).await {
+            Ok(Ok(())) => {
+                status.responsive = true;
+                status.latency_ms = Some(start.elapsed().as_millis() as u64);
+            }
+            Ok(Err(error)) => status.error = Some(error.to_string()),
+            Err(_) => {
+                status.error = Some(format!(
+                    "{} did not answer in {} seconds",
+                    self.service,
+                    PING_TIMEOUT.as_secs()
+                ))
+            }
+        }
+        status
+    }
+
+    async fn has_owner(&self) -> zbus::Result<bool> {
+        let proxy = zbus::fdo::DBusProxy::new(&self.connection).await?;
+        let name = zbus::names::BusName::try_from(self.service.as_str())?;
+        Ok(proxy.name_has_owner(name).await?)
+    }
+
+    /// Pings the main object of the service (e.g., `/org/opensuse/Agama/Storage1`).
+    ///
+    /// Any answer, even an error because the object does not implement the
+    /// `org.freedesktop.DBus.Peer` interface, means that the service is responsive.
+    async fn ping(&self) -> zbus::Result<()> {
+        let path = format!("/{}", self.service.replace('.', "/"));
+        let proxy = zbus::fdo::PeerProxy::builder(&self.connection)
+            .destination(self.service.as_str())?
+            .path(path)?
+            .build()
+            .await?;
+        match proxy.ping().await {
+            Ok(()) => Ok(()),
+            Err(zbus::fdo::Error::ZBus(zbus::Error::MethodError(name, _, _)))
+                if !UNAVAILABLE_ERRORS.contains(&name.as_str()) =>
+            {
+                Ok(())
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+/// Whether the path is the given base path or one of its subpaths.
+fn is_under(path: &str, base: &str) -> bool {
+    path.strip_prefix(base)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Registered backends, indexed by name.
+pub type Backends = Arc<BTreeMap<String, Backend>>;
+
+/// Returns the status of all the backends.
+pub async fn statuses(backends: &Backends) -> Vec<BackendStatus> {
+    let checks = backends.iter().map(|(name, b)| b.status(name));
+    futures_util::future::join_all(checks).await
+}
+
+#[utoipa::path(get, path = "/backends", responses(
+    (status = 200, description = "Status of the D-Bus backend services", body = Vec<BackendStatus>)
+))]
+pub async fn backends(State(state): State<ServiceState>) -> Json<Vec<BackendStatus>> {
+    Json(statuses(&state.backends).await)
+}
+
+/// Middleware which rejects the requests to the paths whose backend is not available.
+///
+/// As it is nested under `/api`, the paths do not include that prefix.
+pub async fn backends_middleware(
+    State(state): State<ServiceState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    let backend = state.backends.iter().find(|(_, b)| b.serves(path));
+    if let Some((name, backend)) = backend {
+        let status = backend.status(name).await;
+        if !status.available() {
+            let mut body = json!({
+                "error": format!("The {} service is not available", name),
+                "backend": name,
+                "details": status.error,
+            });
+            if let Some(id) = current_request_id() {
+                body["requestId"] = json!(id);
+            }
+            return (StatusCode::SERVICE_UNAVAILABLE, Json(body)).into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// Returns the name of the backend a module (e.g., "users") depends on, if any.
+///
+/// A module depends on the backend with the same name or on the one serving its path (e.g.,
+/// "/users").
+///
+/// * `backends`: registered backends.
+/// * `module`: module name.
+pub fn backend_of<'a>(backends: &'a Backends, module: &str) -> Option<&'a str> {
+    let path = format!("/{}", module);
+    backends
+        .iter()
+        .find(|(name, backend)| name.as_str() == module || backend.serves(&path))
+        .map(|(name, _)| name.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_under() {
+        assert!(is_under("/storage", "/storage"));
+        assert!(is_under("/storage/devices/system", "/storage"));
+        assert!(!is_under("/storage2", "/storage"));
+        assert!(!is_under("/software", "/storage"));
+    }
+}
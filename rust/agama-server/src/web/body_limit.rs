@@ -0,0 +1,105 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Limits the size of the request bodies.
+//!
+//! The requests are limited to `max_body_size` bytes (2 MiB by default). The endpoints which
+//! import large payloads use the higher `max_import_size` limit (16 MiB by default):
+//!
+//! * `/api/network/connections/import` (NetworkManager keyfiles).
+//! * `/api/storage/config` (storage section of the profile, which can be converted from a large
+//!   AutoYAST partitioning).
+//! * `/api/software/certificates` (bundles of CA certificates).
+//!
+//! Bigger requests are rejected with a `413 Payload Too Large` status, either upfront (according
+//! to the `Content-Length` header) or when the body is read.
+
+use super::state::ServiceState;
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use http_body_util::Limited;
+use serde_json::json;
+
+/// Paths which accept large payloads.
+const IMPORT_PATHS: [&str; 3] = [
+    "/api/network/connections/import",
+    "/api/storage/config",
+    "/api/software/certificates",
+];
+
+/// Returns the maximum body size for the given path.
+///
+/// * `path`: path of the request.
+/// * `max_body_size`: limit for the regular requests.
+/// * `max_import_size`: limit for the import requests.
+pub fn body_limit(path: &str, max_body_size: usize, max_import_size: usize) -> usize {
+    if IMPORT_PATHS.contains(&path.trim_end_matches('/')) {
+        max_import_size
+    } else {
+        max_body_size
+    }
+}
+
+/// Middleware which rejects the requests whose body is bigger than the limit.
+pub async fn body_limit_middleware(
+    State(state): State<ServiceState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let limit = body_limit(
+        request.uri().path(),
+        state.config.max_body_size,
+        state.config.max_import_size,
+    );
+    let length = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|l| l.to_str().ok())
+        .and_then(|l| l.parse::<usize>().ok());
+    if length.is_some_and(|l| l > limit) {
+        let body = json!({
+            "error": format!("The request body is bigger than {} bytes", limit)
+        });
+        return (StatusCode::PAYLOAD_TOO_LARGE, Json(body)).into_response();
+    }
+
+    let request = request.map(|body| Body::new(Limited::new(body, limit)));
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_body_limit() {
+        assert_eq!(body_limit("/api/storage/config", 10, 100), 100);
+        assert_eq!(body_limit("/api/network/connections/import/", 10, 100), 100);
+        assert_eq!(body_limit("/api/software/certificates", 10, 100), 100);
+        assert_eq!(body_limit("/api/software/config", 10, 100), 10);
+        assert_eq!(body_limit("/api/storage/config/extra", 10, 100), 10);
+    }
+}
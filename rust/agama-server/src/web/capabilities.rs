@@ -0,0 +1,73 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the feature discovery (capabilities) endpoint.
+//!
+//! Each module can report the optional features it supports (e.g., whether zFCP is available) by
+//! registering a [CapabilitiesProvider] in the [MainServiceBuilder](super::MainServiceBuilder).
+//! The `/api/capabilities` endpoint aggregates all of them, so the clients can hide the irrelevant
+//! options.
+
+use super::state::ServiceState;
+use async_trait::async_trait;
+use axum::{extract::State, Json};
+use serde::Serialize;
+use std::{collections::BTreeMap, sync::Arc};
+use utoipa::ToSchema;
+
+/// Features supported by a module, indexed by name.
+pub type ModuleCapabilities = BTreeMap<String, bool>;
+
+/// Reports the features supported by a module.
+///
+/// The capabilities are evaluated on each request, as they could depend on the selected product.
+#[async_trait]
+pub trait CapabilitiesProvider: Send + Sync {
+    async fn capabilities(&self) -> ModuleCapabilities;
+}
+
+/// Registered capabilities providers, indexed by module name.
+pub type CapabilitiesProviders = Arc<BTreeMap<String, Box<dyn CapabilitiesProvider>>>;
+
+/// Features enabled in the web server.
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    /// Whether the server rejects the requests which could change the installer state
+    read_only: bool,
+    /// Features supported by each module
+    #[schema(value_type = Object)]
+    modules: BTreeMap<String, ModuleCapabilities>,
+}
+
+#[utoipa::path(get, path = "/capabilities", responses(
+    (status = 200, description = "Features enabled in the web server", body = Capabilities)
+))]
+pub async fn capabilities(State(state): State<ServiceState>) -> Json<Capabilities> {
+    let mut modules = BTreeMap::new();
+    for (name, provider) in state.capabilities.iter() {
+        modules.insert(name.clone(), provider.capabilities().await);
+    }
+
+    Json(Capabilities {
+        read_only: state.config.read_only,
+        modules,
+    })
+}
@@ -0,0 +1,259 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the endpoint reporting the changes made to the default configuration.
+//!
+//! When the installer enters the configuration phase (i.e., once the system has been probed),
+//! the snapshot of each module registered with
+//! [add_snapshot](super::MainServiceBuilder::add_snapshot) is recorded as the baseline. The
+//! `/api/changes` endpoint compares the current snapshots with that baseline and reports the
+//! added, removed and changed entries. The baseline is only recorded once, so a new probing
+//! (e.g., after selecting another product) keeps the changes. The modules which could not be
+//! recorded are tried again on the next probing.
+
+use super::{state::ServiceState, Event, EventsReceiver};
+use agama_lib::manager::InstallationPhase;
+use axum::{extract::State, Json};
+use serde::Serialize;
+use serde_json::Value;
+use std::{collections::BTreeMap, sync::Arc};
+use tokio::sync::RwLock;
+use utoipa::ToSchema;
+
+/// Snapshots of the modules right after probing, indexed by module name.
+pub type Baseline = Arc<RwLock<BTreeMap<String, Value>>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeKind {
+    /// The entry does not exist in the baseline.
+    Added,
+    /// The entry does not exist anymore.
+    Removed,
+    /// The entry has a different value.
+    Changed,
+}
+
+/// Change of a single entry of a module state.
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChange {
+    /// JSON pointer to the entry (e.g., "/config/drives/0/search").
+    pub path: String,
+    pub kind: ChangeKind,
+    /// Value in the baseline, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub old: Option<Value>,
+    /// Current value, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub new: Option<Value>,
+}
+
+impl ConfigChange {
+    fn new(path: String, old: Option<&Value>, new: Option<&Value>) -> Self {
+        let kind = match (old, new) {
+            (None, _) => ChangeKind::Added,
+            (_, None) => ChangeKind::Removed,
+            _ => ChangeKind::Changed,
+        };
+        Self {
+            path,
+            kind,
+            old: old.cloned(),
+            new: new.cloned(),
+        }
+    }
+}
+
+/// Changes made to the configuration of each module.
+#[derive(Debug, Default, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChanges {
+    /// Changes of each module, sorted by path. Modules without changes are included with an
+    /// empty list.
+    modules: BTreeMap<String, Vec<ConfigChange>>,
+    /// Modules which cannot be compared because there is no baseline or their current state
+    /// cannot be read.
+    unavailable: Vec<String>,
+}
+
+/// Returns the differences between two JSON values.
+///
+/// Objects are compared key by key and arrays element by element, so an element inserted in
+/// the middle of an array is reported as a sequence of changes plus an addition at the end.
+///
+/// * `old`: baseline value.
+/// * `new`: current value.
+pub fn diff(old: &Value, new: &Value) -> Vec<ConfigChange> {
+    let mut changes = vec![];
+    diff_into(old, new, "", &mut changes);
+    changes
+}
+
+fn diff_into(old: &Value, new: &Value, path: &str, changes: &mut Vec<ConfigChange>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let path = format!("{}/{}", path, escape(key));
+                match (old.get(key), new.get(key)) {
+                    (Some(old), Some(new)) => diff_into(old, new, &path, changes),
+                    (old, new) => changes.push(ConfigChange::new(path, old, new)),
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for index in 0..old.len().max(new.len()) {
+                let path = format!("{}/{}", path, index);
+                match (old.get(index), new.get(index)) {
+                    (Some(old), Some(new)) => diff_into(old, new, &path, changes),
+                    (old, new) => changes.push(ConfigChange::new(path, old, new)),
+                }
+            }
+        }
+        (old, new) if old != new => {
+            changes.push(ConfigChange::new(path.to_string(), Some(old), Some(new)))
+        }
+        _ => {}
+    }
+}
+
+/// Escapes a key to be used in a JSON pointer (RFC 6901).
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Records the snapshots of the modules which are not in the baseline yet.
+///
+/// The modules whose state cannot be read are left out, so they are reported as unavailable.
+async fn record_baseline(state: &ServiceState) {
+    let recorded: Vec<String> = state.baseline.read().await.keys().cloned().collect();
+    let mut snapshots = BTreeMap::new();
+    for (name, provider) in state.snapshots.iter() {
+        if recorded.contains(name) {
+            continue;
+        }
+        match provider.snapshot().await {
+            Ok(snapshot) => {
+                snapshots.insert(name.clone(), snapshot);
+            }
+            Err(error) => tracing::warn!("Could not record the {} baseline: {}", name, error),
+        }
+    }
+    state.baseline.write().await.extend(snapshots);
+}
+
+/// Records the baseline when the installer enters the configuration phase.
+///
+/// * `state`: service state.
+/// * `events`: channel to receive the events from.
+pub async fn watch_events(state: ServiceState, mut events: EventsReceiver) {
+    while let Ok(event) = events.recv().await {
+        if let Event::InstallationPhaseChanged {
+            phase: InstallationPhase::Config,
+        } = event
+        {
+            record_baseline(&state).await;
+        }
+    }
+}
+
+#[utoipa::path(get, path = "/changes", responses(
+    (status = 200, description = "Changes made to the configuration of each module since the system was probed", body = ConfigChanges)
+))]
+pub async fn changes(State(state): State<ServiceState>) -> Json<ConfigChanges> {
+    let baseline = state.baseline.read().await;
+    let mut result = ConfigChanges::default();
+    for (name, provider) in state.snapshots.iter() {
+        let Some(old) = baseline.get(name) else {
+            result.unavailable.push(name.clone());
+            continue;
+        };
+
+        match provider.snapshot().await {
+            Ok(new) => {
+                result.modules.insert(name.clone(), diff(old, &new));
+            }
+            Err(error) => {
+                tracing::warn!("Could not build the {} snapshot: {}", name, error);
+                result.unavailable.push(name.clone());
+            }
+        }
+    }
+    Json(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff() {
+        let old = json!({
+            "product": "Tumbleweed",
+            "patterns": ["base", "gnome"],
+            "config": { "boot": { "configure": true }, "a/b": 1 }
+        });
+        let new = json!({
+            "product": "Leap",
+            "patterns": ["base"],
+            "config": { "boot": { "configure": true, "device": "sda" }, "a/b": 1 }
+        });
+
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                ConfigChange {
+                    path: "/config/boot/device".to_string(),
+                    kind: ChangeKind::Added,
+                    old: None,
+                    new: Some(json!("sda"))
+                },
+                ConfigChange {
+                    path: "/patterns/1".to_string(),
+                    kind: ChangeKind::Removed,
+                    old: Some(json!("gnome")),
+                    new: None
+                },
+                ConfigChange {
+                    path: "/product".to_string(),
+                    kind: ChangeKind::Changed,
+                    old: Some(json!("Tumbleweed")),
+                    new: Some(json!("Leap"))
+                },
+            ]
+        );
+        assert!(diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_diff_type_change() {
+        let changes = diff(&json!({ "a~b": [1] }), &json!({ "a~b": { "x": 1 } }));
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path, "/a~0b");
+        assert_eq!(changes[0].kind, ChangeKind::Changed);
+    }
+}
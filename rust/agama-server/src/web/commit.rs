@@ -0,0 +1,298 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the endpoint to commit a subset of the modules.
+//!
+//! Instead of writing the whole configuration at the end of the installation, a client can
+//! commit some modules independently (e.g., apply the network configuration right away and the
+//! storage one later). Each module registers a [CommitProvider] in the
+//! [MainServiceBuilder](super::MainServiceBuilder), declaring the modules it depends on and the
+//! reasons which prevent it from being committed.
+//!
+//! The `POST /api/commit` endpoint commits the requested modules in the given order, stopping at
+//! the first failure, and `GET /api/commit` lists the modules and whether they were committed.
+//! The request includes confirmation tokens for the modules which require them (e.g., storage),
+//! so they are not committed if their configuration changed after the user reviewed it.
+//!
+//! The committed modules are recorded in a [CommittedModules] list, which is shared with the
+//! manager service so it refuses to run the whole installation afterwards.
+
+use super::state::ServiceState;
+use crate::error::Error;
+use async_trait::async_trait;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Arc};
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+
+/// Writes the configuration of a module to the system.
+#[async_trait]
+pub trait CommitProvider: Send + Sync {
+    /// Modules that must be committed before this one.
+    fn requires(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Reasons why the module cannot be committed yet. An empty list means that it can.
+    async fn blockers(&self) -> Result<Vec<String>, Error> {
+        Ok(vec![])
+    }
+
+    /// Problems with the token sent by the client to confirm the commit (e.g., the storage
+    /// impact token, which is required). An empty list means that it is valid.
+    ///
+    /// * `token`: token sent by the client, if any.
+    async fn check_token(&self, _token: Option<&str>) -> Result<Vec<String>, Error> {
+        Ok(vec![])
+    }
+
+    async fn commit(&self) -> Result<(), Error>;
+}
+
+/// Modules committed during the session.
+#[derive(Clone, Default)]
+pub struct CommittedModules(Arc<std::sync::Mutex<Vec<String>>>);
+
+impl CommittedModules {
+    /// Returns the committed modules, in the order they were committed.
+    pub fn list(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Whether the given module was committed.
+    pub fn contains(&self, module: &str) -> bool {
+        self.0.lock().unwrap().iter().any(|m| m == module)
+    }
+
+    fn push(&self, module: String) {
+        self.0.lock().unwrap().push(module);
+    }
+}
+
+/// Registered commit providers and the modules committed during the session.
+///
+/// Only one commit runs at a time.
+pub struct Commits {
+    providers: BTreeMap<String, Box<dyn CommitProvider>>,
+    committed: CommittedModules,
+    running: Mutex<()>,
+}
+
+impl Commits {
+    /// * `providers`: commit providers by module.
+    /// * `committed`: list to record the committed modules.
+    pub fn new(
+        providers: BTreeMap<String, Box<dyn CommitProvider>>,
+        committed: CommittedModules,
+    ) -> Self {
+        Self {
+            providers,
+            committed,
+            running: Mutex::new(()),
+        }
+    }
+
+    /// Checks whether the modules can be committed in the given order.
+    ///
+    /// * `modules`: modules to commit.
+    /// * `tokens`: confirmation tokens by module.
+    /// * `committed`: modules already committed.
+    async fn validate(
+        &self,
+        modules: &[String],
+        tokens: &BTreeMap<String, String>,
+        committed: &[String],
+    ) -> Vec<String> {
+        let mut issues = vec![];
+        if modules.is_empty() {
+            issues.push("No modules to commit".to_string());
+        }
+        for module in tokens.keys().filter(|m| !modules.contains(m)) {
+            issues.push(format!(
+                "A token was given for the {} module, which is not committed",
+                module
+            ));
+        }
+
+        for (index, module) in modules.iter().enumerate() {
+            let Some(provider) = self.providers.get(module) else {
+                issues.push(format!("Unknown module '{}'", module));
+                continue;
+            };
+            if modules[..index].contains(module) {
+                issues.push(format!("The {} module is requested more than once", module));
+                continue;
+            }
+            if committed.contains(module) {
+                issues.push(format!("The {} module was already committed", module));
+                continue;
+            }
+
+            for required in provider.requires() {
+                let done = committed.iter().any(|c| c == required)
+                    || modules[..index].iter().any(|m| m == required);
+                if !done {
+                    issues.push(format!(
+                        "The {} module must be committed before {}",
+                        required, module
+                    ));
+                }
+            }
+
+            match provider.blockers().await {
+                Ok(blockers) => {
+                    issues.extend(blockers.into_iter().map(|b| format!("{}: {}", module, b)))
+                }
+                Err(error) => issues.push(format!(
+                    "{}: could not check whether it can be committed: {}",
+                    module, error
+                )),
+            }
+
+            match provider
+                .check_token(tokens.get(module).map(String::as_str))
+                .await
+            {
+                Ok(problems) => {
+                    issues.extend(problems.into_iter().map(|p| format!("{}: {}", module, p)))
+                }
+                Err(error) => {
+                    issues.push(format!("{}: could not check the token: {}", module, error))
+                }
+            }
+        }
+        issues
+    }
+}
+
+/// Modules to commit.
+#[derive(Deserialize, ToSchema)]
+pub struct CommitRequest {
+    /// Module names, in the order they are committed (e.g., `["network", "storage"]`).
+    modules: Vec<String>,
+    /// Confirmation tokens by module (e.g., `{"storage": "<token>"}` with the token from
+    /// `GET /api/storage/impact`, which is required). A module is not committed if its token is
+    /// missing or outdated.
+    #[serde(default)]
+    tokens: BTreeMap<String, String>,
+}
+
+/// Module that could not be committed.
+#[derive(Serialize, ToSchema)]
+pub struct CommitFailure {
+    module: String,
+    error: String,
+}
+
+/// Result of a commit request.
+#[derive(Serialize, ToSchema)]
+pub struct CommitReport {
+    /// Modules committed by this request.
+    committed: Vec<String>,
+    /// Module which failed, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failed: Option<CommitFailure>,
+    /// Requested modules which were not committed because of the failure.
+    pending: Vec<String>,
+}
+
+/// Commit step of a module.
+#[derive(Serialize, ToSchema)]
+pub struct CommitStep {
+    module: String,
+    /// Modules that must be committed before this one.
+    requires: Vec<String>,
+    /// Whether the module was already committed.
+    committed: bool,
+}
+
+#[utoipa::path(get, path = "/commit", responses(
+    (status = 200, description = "Modules which can be committed", body = Vec<CommitStep>)
+))]
+pub async fn commit_steps(State(state): State<ServiceState>) -> Json<Vec<CommitStep>> {
+    let committed = state.commits.committed.list();
+    let steps = state
+        .commits
+        .providers
+        .iter()
+        .map(|(module, provider)| CommitStep {
+            module: module.clone(),
+            requires: provider.requires().iter().map(|r| r.to_string()).collect(),
+            committed: committed.contains(module),
+        })
+        .collect();
+    Json(steps)
+}
+
+#[utoipa::path(post, path = "/commit", request_body = CommitRequest, responses(
+    (status = 200, description = "The modules were committed", body = CommitReport),
+    (status = 400, description = "A module could not be committed", body = CommitReport),
+    (status = 422, description = "The modules cannot be committed", body = Vec<String>)
+))]
+pub async fn commit(
+    State(state): State<ServiceState>,
+    Json(request): Json<CommitRequest>,
+) -> Response {
+    let _running = state.commits.running.lock().await;
+    let committed = state.commits.committed.list();
+    let issues = state
+        .commits
+        .validate(&request.modules, &request.tokens, &committed)
+        .await;
+    if !issues.is_empty() {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(issues)).into_response();
+    }
+
+    let mut report = CommitReport {
+        committed: vec![],
+        failed: None,
+        pending: vec![],
+    };
+    let mut modules = request.modules.into_iter();
+    for module in modules.by_ref() {
+        tracing::info!("Committing the {} module", module);
+        // validated above, so the provider exists
+        let provider = &state.commits.providers[&module];
+        if let Err(error) = provider.commit().await {
+            tracing::error!("Could not commit the {} module: {}", module, error);
+            report.failed = Some(CommitFailure {
+                module,
+                error: error.to_string(),
+            });
+            break;
+        }
+        state.commits.committed.push(module.clone());
+        report.committed.push(module);
+    }
+    report.pending = modules.collect();
+
+    let status = if report.failed.is_some() {
+        StatusCode::BAD_REQUEST
+    } else {
+        StatusCode::OK
+    };
+    (status, Json(report)).into_response()
+}
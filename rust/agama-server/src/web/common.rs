@@ -38,6 +38,11 @@ use crate::error::Error;
 mod jobs;
 pub use jobs::{jobs_service, jobs_stream};
 
+mod operations;
+pub use operations::{
+    operations_router, ApplyParams, CancelResult, Operation, OperationStatus, Operations,
+};
+
 use super::Event;
 
 pub type EventStreams = Vec<(&'static str, Pin<Box<dyn Stream<Item = Event> + Send>>)>;
@@ -129,7 +134,7 @@ pub async fn service_status_stream(
     Ok(Box::pin(stream))
 }
 
-async fn build_service_status_proxy<'a>(
+pub(crate) async fn build_service_status_proxy<'a>(
     dbus: &zbus::Connection,
     destination: &str,
     path: &str,
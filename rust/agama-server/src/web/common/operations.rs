@@ -0,0 +1,366 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Tracks the operations (e.g., applying the configuration) that finish asynchronously.
+//!
+//! The endpoints which apply some configuration accept an `ApplyParams` query. Using `wait=true`
+//! blocks until the operation finishes (or the timeout expires), returning its final state. Using
+//! `wait=false` returns immediately with the operation ID, which can be polled through the router
+//! returned by [operations_router] or watched via the `OperationFinished` event.
+//!
+//! The operations run in the D-Bus services, which cannot interrupt them. Hence, a cancel request
+//! reports that the operation cannot be canceled instead of just dropping the request.
+
+use super::super::{Event, EventsSender};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::oneshot;
+
+/// Timeout (in seconds) when waiting for an operation, unless a different one is given.
+const DEFAULT_TIMEOUT: u64 = 60;
+
+/// Maximum timeout (in seconds) when waiting for an operation. It matches the timeout of the
+/// routes which might take a while (see [generous_timeout](super::super::generous_timeout)).
+const MAX_TIMEOUT: u64 = 300;
+
+/// Number of finished operations to remember.
+const MAX_OPERATIONS: usize = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum OperationStatus {
+    Running,
+    Finished,
+    Failed,
+}
+
+/// Operation applying some configuration.
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Operation {
+    pub id: u32,
+    pub status: OperationStatus,
+    /// Whether the operation can be canceled while it is running. The D-Bus services cannot
+    /// interrupt their operations, so it is always `false` for now.
+    pub cancelable: bool,
+    /// Final state, once the operation is finished.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub result: Option<serde_json::Value>,
+    /// Error message, if the operation failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// How to wait for an operation.
+#[derive(Clone, Debug, Default, Deserialize, utoipa::IntoParams)]
+pub struct ApplyParams {
+    /// Whether to block until the operation finishes (`true`) or to return the operation ID
+    /// immediately (`false`). If it is not set, the endpoint keeps its usual behavior.
+    pub wait: Option<bool>,
+    /// Maximum time to block, in seconds (60 by default, at most 300).
+    pub timeout: Option<u64>,
+}
+
+impl ApplyParams {
+    /// Returns the time to block, bounded to [MAX_TIMEOUT].
+    pub fn wait_timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout.unwrap_or(DEFAULT_TIMEOUT).min(MAX_TIMEOUT))
+    }
+}
+
+/// Outcome of a cancel request.
+#[derive(Clone, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelResult {
+    /// Whether the operation was canceled.
+    pub canceled: bool,
+    /// Why the operation was not canceled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Registry of the operations of a service.
+#[derive(Clone)]
+pub struct Operations {
+    service: String,
+    events: EventsSender,
+    operations: Arc<Mutex<HashMap<u32, Operation>>>,
+}
+
+impl Operations {
+    /// * `service`: name of the service, included in the events.
+    /// * `events`: channel to send the `OperationFinished` events.
+    pub fn new(service: &str, events: EventsSender) -> Self {
+        Self {
+            service: service.to_string(),
+            events,
+            operations: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the operation with the given ID.
+    pub fn get(&self, id: u32) -> Option<Operation> {
+        self.operations.lock().unwrap().get(&id).cloned()
+    }
+
+    /// Cancels a running operation.
+    ///
+    /// None of the operations can be canceled yet, so it only reports why the request is not
+    /// honored. It returns `None` if the operation is unknown.
+    ///
+    /// * `id`: operation ID.
+    pub fn cancel(&self, id: u32) -> Option<CancelResult> {
+        let operation = self.get(id)?;
+        let reason = if operation.status != OperationStatus::Running {
+            "The operation is not running"
+        } else {
+            "The operation cannot be canceled"
+        };
+        Some(CancelResult {
+            canceled: false,
+            reason: Some(reason.to_string()),
+        })
+    }
+
+    /// Runs the given future in the background as a new operation.
+    ///
+    /// It returns the running operation and a channel to receive the finished one.
+    ///
+    /// * `future`: future returning the final state or an error message.
+    pub fn start<F>(&self, future: F) -> (Operation, oneshot::Receiver<Operation>)
+    where
+        F: Future<Output = Result<serde_json::Value, String>> + Send + 'static,
+    {
+        let operation = {
+            let mut operations = self.operations.lock().unwrap();
+            let id = operations.keys().max().map_or(1, |id| id + 1);
+            if operations.len() >= MAX_OPERATIONS {
+                let oldest = operations
+                    .values()
+                    .filter(|o| o.status != OperationStatus::Running)
+                    .map(|o| o.id)
+                    .min();
+                if let Some(oldest) = oldest {
+                    operations.remove(&oldest);
+                }
+            }
+            let operation = Operation {
+                id,
+                status: OperationStatus::Running,
+                cancelable: false,
+                result: None,
+                error: None,
+            };
+            operations.insert(id, operation.clone());
+            operation
+        };
+
+        let (tx, rx) = oneshot::channel();
+        let registry = self.clone();
+        let id = operation.id;
+        tokio::spawn(async move {
+            let result = future.await;
+            let finished = registry.finish(id, result);
+            _ = tx.send(finished);
+        });
+        (operation, rx)
+    }
+
+    /// Runs the given future as an operation, honoring the apply parameters.
+    ///
+    /// It answers with a 200 and the final state if the operation finished within the timeout.
+    /// Otherwise, it answers with a 202 and the running operation.
+    ///
+    /// * `params`: how to wait for the operation.
+    /// * `future`: future returning the final state or an error message.
+    pub async fn run<F>(&self, params: &ApplyParams, future: F) -> Response
+    where
+        F: Future<Output = Result<serde_json::Value, String>> + Send + 'static,
+    {
+        Self::wait(params, self.start(future)).await
+    }
+
+    async fn wait(
+        params: &ApplyParams,
+        (operation, rx): (Operation, oneshot::Receiver<Operation>),
+    ) -> Response {
+        if params.wait != Some(true) {
+            return (StatusCode::ACCEPTED, Json(operation)).into_response();
+        }
+
+        match tokio::time::timeout(params.wait_timeout(), rx).await {
+            Ok(Ok(finished)) => Json(finished).into_response(),
+            _ => (StatusCode::ACCEPTED, Json(operation)).into_response(),
+        }
+    }
+
+    /// Stores the final state of an operation.
+    fn finish(&self, id: u32, result: Result<serde_json::Value, String>) -> Operation {
+        let (status, result, error) = match result {
+            Ok(value) => (OperationStatus::Finished, Some(value), None),
+            Err(error) => (OperationStatus::Failed, None, Some(error)),
+        };
+        let operation = Operation {
+            id,
+            status,
+            cancelable: false,
+            result,
+            error,
+        };
+        self.operations
+            .lock()
+            .unwrap()
+            .insert(id, operation.clone());
+        _ = self.events.send(Event::OperationFinished {
+            service: self.service.clone(),
+            id,
+            status,
+        });
+        operation
+    }
+}
+
+/// Builds a router to query the operations of a service.
+///
+/// * `operations`: registry of the operations.
+pub fn operations_router<T>(operations: Operations) -> Router<T> {
+    Router::new()
+        .route("/:id", get(operation))
+        .route("/:id/cancel", post(cancel_operation))
+        .with_state(operations)
+}
+
+async fn operation(State(operations): State<Operations>, Path(id): Path<u32>) -> Response {
+    match operations.get(id) {
+        Some(operation) => Json(operation).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "Unknown operation", "id": id })),
+        )
+            .into_response(),
+    }
+}
+
+/// Requests to cancel a running operation. The result tells whether it was canceled.
+async fn cancel_operation(State(operations): State<Operations>, Path(id): Path<u32>) -> Response {
+    match operations.cancel(id) {
+        Some(result) => Json(result).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "Unknown operation", "id": id })),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::broadcast::channel;
+
+    #[tokio::test]
+    async fn test_run_operation() {
+        let (tx, mut rx) = channel(16);
+        let operations = Operations::new("test", tx);
+
+        let params = ApplyParams {
+            wait: Some(true),
+            timeout: None,
+        };
+        let response = operations.run(&params, async { Ok(json!(42)) }).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let operation = operations.get(1).unwrap();
+        assert_eq!(operation.status, OperationStatus::Finished);
+        assert_eq!(operation.result, Some(json!(42)));
+        assert!(matches!(
+            rx.recv().await.unwrap(),
+            Event::OperationFinished { id: 1, .. }
+        ));
+
+        let params = ApplyParams {
+            wait: Some(false),
+            timeout: None,
+        };
+        let (started, done) = operations.start(async { Err("failed".to_string()) });
+        assert_eq!(started.id, 2);
+        assert_eq!(started.status, OperationStatus::Running);
+        let finished = done.await.unwrap();
+        assert_eq!(finished.status, OperationStatus::Failed);
+        assert_eq!(finished.error.as_deref(), Some("failed"));
+
+        let response = operations
+            .run(&params, async {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                Ok(json!(null))
+            })
+            .await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+
+    #[test]
+    fn test_wait_timeout() {
+        let mut params = ApplyParams::default();
+        assert_eq!(params.wait_timeout(), Duration::from_secs(DEFAULT_TIMEOUT));
+        params.timeout = Some(10);
+        assert_eq!(params.wait_timeout(), Duration::from_secs(10));
+        params.timeout = Some(u64::MAX);
+        assert_eq!(params.wait_timeout(), Duration::from_secs(MAX_TIMEOUT));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_operation() {
+        let (tx, _rx) = channel(16);
+        let operations = Operations::new("test", tx);
+
+        let (started, done) = operations.start(async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            Ok(json!(null))
+        });
+        assert!(!started.cancelable);
+        let result = operations.cancel(started.id).unwrap();
+        assert!(!result.canceled);
+        assert_eq!(
+            result.reason.as_deref(),
+            Some("The operation cannot be canceled")
+        );
+        assert_eq!(done.await.unwrap().status, OperationStatus::Finished);
+
+        let result = operations.cancel(started.id).unwrap();
+        assert_eq!(
+            result.reason.as_deref(),
+            Some("The operation is not running")
+        );
+        assert!(operations.cancel(100).is_none());
+    }
+}
@@ -30,15 +30,66 @@
 //! All the settings are merged into a single configuration. The values in the latter locations
 //! take precedence.
 
+use super::cors::CorsConfig;
 use config::{Config, ConfigError, File};
 use rand::distributions::{Alphanumeric, DistString};
 use serde::Deserialize;
+use std::collections::HashMap;
 
 /// Web service configuration.
 #[derive(Clone, Debug, Deserialize)]
 pub struct ServiceConfig {
     /// Key to sign the JSON Web Tokens.
     pub jwt_secret: String,
+    /// CORS policy.
+    #[serde(default)]
+    pub cors: CorsConfig,
+    /// Whether to reject the requests which could change the installer state.
+    #[serde(default)]
+    pub read_only: bool,
+    /// Maximum number of concurrent event streams (WebSockets and SSE).
+    #[serde(default = "default_max_event_streams")]
+    pub max_event_streams: usize,
+    /// Maximum size of the request bodies, in bytes.
+    #[serde(default = "default_max_body_size")]
+    pub max_body_size: usize,
+    /// Maximum size of the request bodies for the endpoints importing large payloads, in bytes.
+    #[serde(default = "default_max_import_size")]
+    pub max_import_size: usize,
+    /// Maximum time to answer a request, in seconds.
+    #[serde(default = "default_request_timeout")]
+    pub request_timeout: u64,
+    /// Maximum time to answer the requests to specific paths, in seconds (0 means no timeout).
+    #[serde(default)]
+    pub route_timeouts: HashMap<String, u64>,
+}
+
+/// Maximum number of concurrent event streams when it is not configured.
+pub const DEFAULT_MAX_EVENT_STREAMS: usize = 64;
+
+fn default_max_event_streams() -> usize {
+    DEFAULT_MAX_EVENT_STREAMS
+}
+
+/// Maximum size of the request bodies when it is not configured (2 MiB).
+pub const DEFAULT_MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// Maximum size of the import request bodies when it is not configured (16 MiB).
+pub const DEFAULT_MAX_IMPORT_SIZE: usize = 16 * 1024 * 1024;
+
+fn default_max_body_size() -> usize {
+    DEFAULT_MAX_BODY_SIZE
+}
+
+fn default_max_import_size() -> usize {
+    DEFAULT_MAX_IMPORT_SIZE
+}
+
+/// Maximum time to answer a request when it is not configured, in seconds.
+pub const DEFAULT_REQUEST_TIMEOUT: u64 = 30;
+
+fn default_request_timeout() -> u64 {
+    DEFAULT_REQUEST_TIMEOUT
 }
 
 impl ServiceConfig {
@@ -53,7 +104,12 @@ impl ServiceConfig {
             .add_source(File::with_name("/etc/agama.d/server").required(false))
             .add_source(File::with_name("etc/agama.d/server").required(false))
             .build()?;
-        config.try_deserialize()
+        let config: Self = config.try_deserialize()?;
+        config
+            .cors
+            .validate()
+            .map_err(|e| ConfigError::Message(format!("Invalid CORS configuration: {}", e)))?;
+        Ok(config)
     }
 }
 
@@ -61,6 +117,13 @@ impl Default for ServiceConfig {
     fn default() -> Self {
         Self {
             jwt_secret: "".to_string(),
+            cors: CorsConfig::default(),
+            read_only: false,
+            max_event_streams: DEFAULT_MAX_EVENT_STREAMS,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            max_import_size: DEFAULT_MAX_IMPORT_SIZE,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            route_timeouts: HashMap::new(),
         }
     }
 }
@@ -0,0 +1,228 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the CORS (Cross-Origin Resource Sharing) policy of the web service.
+//!
+//! By default, no cross-origin requests are allowed. The policy can be opened up through the
+//! `cors` section of the server configuration:
+//!
+//! ```yaml
+//! cors:
+//!   allowed_origins: ["https://console.example.net"]
+//!   allowed_methods: ["GET", "PUT"]
+//!   allowed_headers: ["content-type", "authorization"]
+//!   allow_credentials: true
+//! ```
+//!
+//! Use `"*"` to allow any origin, method or header. Browsers forbid combining the wildcard with
+//! credentials, so such a configuration is rejected.
+//!
+//! Browsers do not apply CORS to WebSockets, so the WebSocket endpoint checks the origin by itself
+//! once a policy is configured.
+
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use serde::Deserialize;
+use std::str::FromStr;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+
+const WILDCARD: &str = "*";
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum CorsConfigError {
+    #[error("Invalid origin: '{0}'")]
+    InvalidOrigin(String),
+    #[error("Invalid method: '{0}'")]
+    InvalidMethod(String),
+    #[error("Invalid header: '{0}'")]
+    InvalidHeader(String),
+    #[error("Wildcards cannot be used when allowing credentials ({0})")]
+    WildcardWithCredentials(&'static str),
+}
+
+/// CORS policy configuration.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Origins allowed to perform cross-origin requests (e.g., "https://example.net").
+    pub allowed_origins: Vec<String>,
+    /// Allowed methods. If empty, GET, POST, PUT, PATCH and DELETE are allowed.
+    pub allowed_methods: Vec<String>,
+    /// Allowed request headers. If empty, "content-type" and "authorization" are allowed.
+    pub allowed_headers: Vec<String>,
+    /// Whether to allow credentials (cookies or authorization headers).
+    pub allow_credentials: bool,
+}
+
+impl CorsConfig {
+    /// Checks whether the configuration is valid.
+    pub fn validate(&self) -> Result<(), CorsConfigError> {
+        self.layer().map(|_| ())
+    }
+
+    /// Whether a CORS policy is configured (i.e., some origin is allowed).
+    pub fn is_enabled(&self) -> bool {
+        !self.allowed_origins.is_empty()
+    }
+
+    /// Whether the given origin is allowed to perform cross-origin requests.
+    ///
+    /// * `origin`: value of the `Origin` header.
+    pub fn allows_origin(&self, origin: &str) -> bool {
+        self.allowed_origins
+            .iter()
+            .any(|o| o == WILDCARD || o.trim_end_matches('/') == origin)
+    }
+
+    /// Builds the CORS layer.
+    ///
+    /// It returns `None` if no cross-origin request is allowed.
+    pub fn layer(&self) -> Result<Option<CorsLayer>, CorsConfigError> {
+        if !self.is_enabled() {
+            return Ok(None);
+        }
+
+        let origins = if self.allowed_origins.iter().any(|o| o == WILDCARD) {
+            if self.allow_credentials {
+                return Err(CorsConfigError::WildcardWithCredentials("origins"));
+            }
+            AllowOrigin::any()
+        } else {
+            let origins = self
+                .allowed_origins
+                .iter()
+                .map(|o| {
+                    HeaderValue::from_str(o.trim_end_matches('/'))
+                        .map_err(|_| CorsConfigError::InvalidOrigin(o.to_string()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            AllowOrigin::list(origins)
+        };
+
+        let methods = if self.allowed_methods.iter().any(|m| m == WILDCARD) {
+            if self.allow_credentials {
+                return Err(CorsConfigError::WildcardWithCredentials("methods"));
+            }
+            AllowMethods::any()
+        } else if self.allowed_methods.is_empty() {
+            AllowMethods::list([
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+            ])
+        } else {
+            let methods = self
+                .allowed_methods
+                .iter()
+                .map(|m| {
+                    Method::from_str(&m.to_uppercase())
+                        .map_err(|_| CorsConfigError::InvalidMethod(m.to_string()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            AllowMethods::list(methods)
+        };
+
+        let headers = if self.allowed_headers.iter().any(|h| h == WILDCARD) {
+            if self.allow_credentials {
+                return Err(CorsConfigError::WildcardWithCredentials("headers"));
+            }
+            AllowHeaders::any()
+        } else if self.allowed_headers.is_empty() {
+            AllowHeaders::list([header::CONTENT_TYPE, header::AUTHORIZATION])
+        } else {
+            let headers = self
+                .allowed_headers
+                .iter()
+                .map(|h| {
+                    HeaderName::from_str(h)
+                        .map_err(|_| CorsConfigError::InvalidHeader(h.to_string()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            AllowHeaders::list(headers)
+        };
+
+        let layer = CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .allow_credentials(self.allow_credentials);
+        Ok(Some(layer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CorsConfig, CorsConfigError};
+
+    fn config_with_origins(origins: &[&str]) -> CorsConfig {
+        CorsConfig {
+            allowed_origins: origins.iter().map(|o| o.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_default_config() {
+        let config = CorsConfig::default();
+        assert!(config.layer().unwrap().is_none());
+        assert!(!config.is_enabled());
+        assert!(!config.allows_origin("https://example.net"));
+    }
+
+    #[test]
+    fn test_allowed_origins() {
+        let config = config_with_origins(&["https://example.net/"]);
+        assert!(config.layer().unwrap().is_some());
+        assert!(config.allows_origin("https://example.net"));
+        assert!(!config.allows_origin("https://example.org"));
+
+        let config = config_with_origins(&["*"]);
+        assert!(config.allows_origin("https://example.org"));
+    }
+
+    #[test]
+    fn test_wildcard_with_credentials() {
+        let mut config = config_with_origins(&["*"]);
+        config.allow_credentials = true;
+        assert_eq!(
+            config.validate(),
+            Err(CorsConfigError::WildcardWithCredentials("origins"))
+        );
+
+        let mut config = config_with_origins(&["https://example.net"]);
+        config.allow_credentials = true;
+        config.allowed_headers = vec!["*".to_string()];
+        assert_eq!(
+            config.validate(),
+            Err(CorsConfigError::WildcardWithCredentials("headers"))
+        );
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let mut config = config_with_origins(&["https://example.net"]);
+        config.allowed_methods = vec!["GET POST".to_string()];
+        assert_eq!(
+            config.validate(),
+            Err(CorsConfigError::InvalidMethod("GET POST".to_string()))
+        );
+    }
+}
@@ -23,52 +23,136 @@ use utoipa::OpenApi;
 #[openapi(
     info(description = "Agama web API description"),
     paths(
+        crate::bootloader::web::get_config,
+        crate::bootloader::web::set_config,
+        crate::bootloader::web::supported_bootloaders,
+        crate::bootloader::web::warnings,
+        crate::firewall::web::get_config,
+        crate::firewall::web::known_services,
+        crate::firewall::web::set_config,
+        crate::firewall::web::set_zone,
+        crate::l10n::web::generated_locales,
         crate::l10n::web::get_config,
+        crate::l10n::web::get_ntp,
+        crate::l10n::web::get_time,
+        crate::l10n::web::keyboard,
         crate::l10n::web::keymaps,
         crate::l10n::web::locales,
+        crate::l10n::web::reset,
         crate::l10n::web::set_config,
+        crate::l10n::web::set_ntp,
+        crate::l10n::web::set_time,
+        crate::l10n::web::suggestion,
         crate::l10n::web::timezones,
+        crate::manager::web::facts,
         crate::manager::web::finish_action,
+        crate::manager::web::follow_logs,
         crate::manager::web::install_action,
+        crate::manager::web::installation_result,
         crate::manager::web::installer_status,
+        crate::manager::web::log_level,
+        crate::manager::web::machine_id,
         crate::manager::web::probe_action,
+        crate::manager::web::self_update,
+        crate::manager::web::set_log_level,
+        crate::manager::web::set_machine_id,
+        crate::manager::web::skip_self_update,
         crate::network::web::add_connection,
         crate::network::web::apply,
         crate::network::web::connect,
+        crate::network::web::connection_state,
         crate::network::web::connections,
+        crate::network::web::connectivity,
+        crate::network::web::checkpoint,
+        crate::network::web::create_checkpoint,
+        crate::network::web::confirm_checkpoint,
+        crate::network::web::rollback_checkpoint,
+        crate::network::web::copy_live_connections,
         crate::network::web::delete_connection,
         crate::network::web::devices,
         crate::network::web::disconnect,
+        crate::network::web::dns_configuration,
+        crate::network::web::export_connections,
+        crate::network::web::import_connections,
+        crate::network::web::reset,
+        crate::network::web::set_autoconnect,
+        crate::network::web::set_connections,
         crate::network::web::update_connection,
+        crate::preview::web::effective_config,
+        crate::preview::web::preview,
+        crate::profile::web::convert_autoyast,
         crate::questions::web::answer_question,
         crate::questions::web::get_answer,
         crate::questions::web::delete_question,
         crate::questions::web::create_question,
         crate::questions::web::list_questions,
+        crate::questions::web::remembered_answers,
+        crate::questions::web::forget_answers,
+        crate::questions::web::history,
+        crate::questions::web::save_answers,
+        crate::scripts::web::get_config,
+        crate::scripts::web::remove_script,
+        crate::scripts::web::set_config,
+        crate::scripts::web::set_script,
+        crate::software::web::add_certificates,
+        crate::software::web::get_architecture,
+        crate::software::web::get_certificates,
         crate::software::web::get_config,
+        crate::software::web::package_changes,
         crate::software::web::patterns,
         crate::software::web::probe,
+        crate::software::web::product_release_notes,
+        crate::software::web::product_requirements,
         crate::software::web::products,
         crate::software::web::proposal,
+        crate::software::web::select_product,
+        crate::software::web::set_architecture,
         crate::software::web::set_config,
+        crate::software::web::subscription_status,
+        crate::software::web::test_repository,
         crate::storage::web::actions,
+        crate::storage::web::calculate_proposal,
+        crate::storage::web::create_partition,
         crate::storage::web::devices_dirty,
+        crate::storage::web::disk_selection,
+        crate::storage::web::excluded_devices,
+        crate::storage::web::get_fstab_entries,
         crate::storage::web::get_proposal_settings,
+        crate::storage::web::install,
+        crate::storage::web::mount_existing,
+        crate::storage::web::planned_mounts,
+        crate::storage::web::planned_partition_tables,
         crate::storage::web::probe,
+        crate::storage::web::proposal_issues,
         crate::storage::web::product_params,
+        crate::storage::web::raid_levels,
+        crate::storage::web::reset,
+        crate::storage::web::resize_partition,
+        crate::storage::web::set_fstab_entries,
         crate::storage::web::set_proposal_settings,
         crate::storage::web::staging_devices,
+        crate::storage::web::storage_impact,
+        crate::storage::web::storage_status,
+        crate::storage::web::supported_filesystems,
         crate::storage::web::system_devices,
+        crate::storage::web::unlock_device,
         crate::storage::web::usable_devices,
+        crate::storage::web::validate_device,
         crate::storage::web::volume_for,
         crate::storage::web::iscsi::delete_node,
         crate::storage::web::iscsi::discover,
         crate::storage::web::iscsi::initiator,
+        crate::storage::web::iscsi::interfaces,
         crate::storage::web::iscsi::login_node,
         crate::storage::web::iscsi::logout_node,
+        crate::storage::web::iscsi::node_session,
         crate::storage::web::iscsi::nodes,
+        crate::storage::web::iscsi::reset_initiator_name,
+        crate::storage::web::iscsi::sessions,
+        crate::storage::web::iscsi::test_node_auth,
         crate::storage::web::iscsi::update_initiator,
         crate::storage::web::iscsi::update_node,
+        crate::storage::web::iscsi::update_nodes_startup,
         crate::storage::web::dasd::probe,
         crate::storage::web::dasd::supported,
         crate::storage::web::dasd::devices,
@@ -76,6 +160,11 @@ use utoipa::OpenApi;
         crate::storage::web::dasd::enable,
         crate::storage::web::dasd::disable,
         crate::storage::web::dasd::set_diag,
+        crate::storage::web::nvmeof::connect,
+        crate::storage::web::nvmeof::controllers,
+        crate::storage::web::nvmeof::disconnect,
+        crate::storage::web::nvmeof::discover,
+        crate::storage::web::nvmeof::supported,
         crate::storage::web::zfcp::supported,
         crate::storage::web::zfcp::controllers,
         crate::storage::web::zfcp::activate_controller,
@@ -84,76 +173,250 @@ use utoipa::OpenApi;
         crate::storage::web::zfcp::get_disks,
         crate::storage::web::zfcp::get_wwpns,
         crate::storage::web::zfcp::get_luns,
+        crate::users::web::generate_user_password,
+        crate::users::web::get_default_shell,
+        crate::users::web::get_groups,
         crate::users::web::get_root_config,
         crate::users::web::get_user_config,
+        crate::users::web::get_user_expiration,
         crate::users::web::patch_root,
         crate::users::web::remove_first_user,
+        crate::users::web::remove_group,
+        crate::users::web::set_default_shell,
         crate::users::web::set_first_user,
+        crate::users::web::set_group,
+        crate::users::web::set_groups,
+        super::backends::backends,
+        super::capabilities::capabilities,
+        super::readiness::readiness,
+        super::changes::changes,
+        super::commit::commit,
+        super::commit::commit_steps,
+        super::estimate::estimate,
         super::http::ping
     ),
     components(
+        schemas(agama_lib::bootloader::model::BootloaderPassword),
+        schemas(agama_lib::bootloader::model::BootloaderSettings),
+        schemas(agama_lib::bootloader::model::BootloaderType),
+        schemas(agama_lib::bootloader::model::KdumpSettings),
+        schemas(agama_lib::bootloader::model::KernelModule),
+        schemas(agama_lib::bootloader::model::KernelModulesSettings),
+        schemas(agama_lib::firewall::model::FirewallSettings),
+        schemas(agama_lib::firewall::model::FirewallZone),
+        schemas(agama_lib::scripts::model::Script),
+        schemas(agama_lib::scripts::model::ScriptTrigger),
+        schemas(agama_lib::scripts::model::ScriptsSettings),
         schemas(agama_lib::manager::InstallationPhase),
+        schemas(agama_lib::manager::MachineIdPolicy),
+        schemas(agama_lib::manager::SelfUpdateInfo),
         schemas(agama_lib::network::settings::NetworkConnection),
         schemas(agama_lib::network::types::DeviceType),
+        schemas(agama_lib::network::types::DnsOverTls),
+        schemas(agama_lib::network::types::Ipv6Privacy),
+        schemas(agama_lib::network::types::Metered),
         schemas(agama_lib::product::Product),
+        schemas(agama_lib::product::ProductRequirements),
+        schemas(agama_lib::product::RequirementCheck),
+        schemas(agama_lib::product::RequirementStatus),
+        schemas(agama_lib::product::RequirementsReport),
+        schemas(crate::software::release_notes::ReleaseNotes),
+        schemas(agama_lib::profile::AutoyastConversion),
         schemas(agama_lib::software::Pattern),
         schemas(agama_lib::storage::model::Action),
         schemas(agama_lib::storage::model::BlockDevice),
+        schemas(agama_lib::storage::model::Cache),
+        schemas(agama_lib::storage::model::CacheMethod),
+        schemas(agama_lib::storage::model::CacheMode),
+        schemas(agama_lib::storage::model::CacheSettings),
         schemas(agama_lib::storage::model::Component),
+        schemas(agama_lib::storage::model::DetectedFilesystem),
         schemas(agama_lib::storage::model::Device),
+        schemas(agama_lib::storage::model::DeviceContents),
         schemas(agama_lib::storage::model::DeviceInfo),
+        schemas(agama_lib::storage::model::DeviceMembership),
         schemas(agama_lib::storage::model::DeviceSid),
+        schemas(agama_lib::storage::model::DeviceValidation),
+        schemas(agama_lib::storage::model::DeviceValidationReason),
+        schemas(agama_lib::storage::model::DeviceValidationStatus),
+        schemas(agama_lib::storage::model::DiskSelection),
         schemas(agama_lib::storage::model::Drive),
         schemas(agama_lib::storage::model::DriveInfo),
         schemas(agama_lib::storage::model::DeviceSize),
+        schemas(agama_lib::storage::model::EspSettings),
+        schemas(agama_lib::storage::model::ErasedDevice),
+        schemas(agama_lib::storage::model::ExcludedDevice),
+        schemas(agama_lib::storage::model::ExplicitPartition),
         schemas(agama_lib::storage::model::Filesystem),
+        schemas(agama_lib::storage::model::ImpactSeverity),
+        schemas(agama_lib::storage::model::IrreversibleAction),
         schemas(agama_lib::storage::model::LvmLv),
         schemas(agama_lib::storage::model::LvmVg),
         schemas(agama_lib::storage::model::Md),
+        schemas(agama_lib::storage::model::MountBy),
         schemas(agama_lib::storage::model::Multipath),
         schemas(agama_lib::storage::model::Partition),
+        schemas(agama_lib::storage::model::PartitionAlignment),
+        schemas(agama_lib::storage::model::PartitionRegion),
         schemas(agama_lib::storage::model::PartitionTable),
+        schemas(agama_lib::storage::model::PartitionTableSettings),
+        schemas(agama_lib::storage::model::PartitionTableType),
+        schemas(agama_lib::storage::model::ProposalIssue),
+        schemas(agama_lib::storage::model::ProposalIssues),
         schemas(agama_lib::storage::model::ProposalSettings),
         schemas(agama_lib::storage::model::ProposalSettingsPatch),
         schemas(agama_lib::storage::model::ProposalTarget),
+        schemas(agama_lib::storage::model::QuotaLimit),
+        schemas(agama_lib::storage::model::QuotaType),
         schemas(agama_lib::storage::model::Raid),
+        schemas(agama_lib::storage::model::RaidLevel),
+        schemas(agama_lib::storage::model::ReadOnlyRootSettings),
+        schemas(agama_lib::storage::model::SmartHealth),
+        schemas(agama_lib::storage::model::SmartInfo),
         schemas(agama_lib::storage::model::SpaceAction),
+        schemas(agama_lib::storage::model::ResizeSummary),
         schemas(agama_lib::storage::model::SpaceActionSettings),
+        schemas(agama_lib::storage::model::StorageImpact),
+        schemas(agama_lib::storage::model::SupportedFilesystem),
         schemas(agama_lib::storage::model::UnusedSlot),
         schemas(agama_lib::storage::model::Volume),
+        schemas(agama_lib::storage::model::VolumeEncryption),
         schemas(agama_lib::storage::model::VolumeOutline),
+        schemas(agama_lib::storage::model::VolumeQuota),
+        schemas(agama_lib::storage::model::VolumeSize),
         schemas(agama_lib::storage::model::VolumeTarget),
         schemas(agama_lib::storage::model::dasd::DASDDevice),
         schemas(agama_lib::storage::model::dasd::DASDFormatSummary),
+        schemas(agama_lib::storage::model::nvmeof::NVMeoFController),
+        schemas(agama_lib::storage::model::nvmeof::NVMeoFError),
+        schemas(agama_lib::storage::model::nvmeof::NVMeoFResult),
+        schemas(agama_lib::storage::model::nvmeof::NVMeoFTarget),
+        schemas(agama_lib::storage::model::nvmeof::NVMeoFTransport),
         schemas(agama_lib::storage::model::zfcp::ZFCPDisk),
         schemas(agama_lib::storage::model::zfcp::ZFCPController),
+        schemas(agama_lib::storage::client::iscsi::DiscoveryResult),
+        schemas(agama_lib::storage::client::iscsi::ISCSIActiveSession),
         schemas(agama_lib::storage::client::iscsi::ISCSIAuth),
+        schemas(agama_lib::storage::client::iscsi::ISCSIAuthMode),
+        schemas(agama_lib::storage::client::iscsi::ISCSISession),
         schemas(agama_lib::storage::client::iscsi::ISCSIInitiator),
+        schemas(agama_lib::storage::client::iscsi::ISCSIInterface),
         schemas(agama_lib::storage::client::iscsi::ISCSINode),
+        schemas(agama_lib::storage::client::iscsi::InitiatorNameReset),
+        schemas(agama_lib::storage::client::iscsi::InitiatorNameSource),
+        schemas(agama_lib::storage::client::iscsi::LoginError),
         schemas(agama_lib::storage::client::iscsi::LoginResult),
         schemas(agama_lib::users::FirstUser),
+        schemas(crate::l10n::KeyboardMapping),
         schemas(crate::l10n::Keymap),
         schemas(crate::l10n::LocaleEntry),
+        schemas(crate::l10n::LocaleGeneration),
         schemas(crate::l10n::TimezoneEntry),
+        schemas(crate::l10n::suggestion::LocaleSuggestion),
+        schemas(crate::l10n::ntp::DhcpNtpPolicy),
+        schemas(crate::l10n::web::NtpConfig),
+        schemas(crate::l10n::web::NtpParams),
+        schemas(crate::l10n::web::TimeConfig),
+        schemas(crate::l10n::web::TimeParams),
         schemas(agama_lib::localization::model::LocaleConfig),
+        schemas(crate::manager::facts::HardwareFacts),
+        schemas(crate::manager::result::InstallationResult),
+        schemas(crate::manager::result::ProductSummary),
         schemas(crate::manager::web::InstallerStatus),
+        schemas(crate::manager::web::LogLevelConfig),
+        schemas(crate::logs::LogLevel),
         schemas(crate::network::model::Connection),
+        schemas(crate::network::model::ConnectivityState),
         schemas(crate::network::model::Device),
+        schemas(crate::network::model::DnsEntry),
+        schemas(crate::network::web::AutoconnectParams),
+        schemas(crate::network::web::BatchAction),
+        schemas(crate::network::web::BatchResult),
+        schemas(crate::network::gateway::GatewayCheck),
+        schemas(crate::network::web::ConnectionState),
+        schemas(crate::network::web::Connectivity),
+        schemas(crate::network::web::ConnectivityCheck),
+        schemas(crate::network::web::CheckpointParams),
+        schemas(crate::network::model::CheckpointStatus),
+        schemas(crate::network::web::ImportParams),
+        schemas(crate::preview::files::GeneratedFile),
+        schemas(crate::profile::web::ProfileConversion),
         schemas(agama_lib::questions::model::Answer),
         schemas(agama_lib::questions::model::GenericAnswer),
         schemas(agama_lib::questions::model::GenericQuestion),
         schemas(agama_lib::questions::model::PasswordAnswer),
         schemas(agama_lib::questions::model::Question),
         schemas(agama_lib::questions::model::QuestionWithPassword),
+        schemas(agama_lib::questions::model::QuestionWithTextInput),
+        schemas(agama_lib::questions::model::RememberedAnswer),
+        schemas(agama_lib::questions::model::SaveAnswersParams),
+        schemas(agama_lib::questions::model::SavedAnswers),
+        schemas(agama_lib::questions::model::AnsweredQuestion),
+        schemas(agama_lib::questions::model::TextInputAnswer),
+        schemas(agama_lib::software::model::ActivatedExtension),
+        schemas(agama_lib::software::model::ArchitectureParams),
+        schemas(agama_lib::software::model::ArchitectureSettings),
+        schemas(agama_lib::software::model::PackageChange),
+        schemas(agama_lib::software::model::PackageChangeKind),
+        schemas(agama_lib::software::model::RepositoryConfig),
+        schemas(agama_lib::software::model::RepositoryType),
+        schemas(agama_lib::software::model::ServiceAction),
+        schemas(agama_lib::software::model::ServiceConfig),
         schemas(agama_lib::software::model::SoftwareConfig),
+        schemas(agama_lib::software::model::SubscriptionStatus),
+        schemas(agama_lib::software::model::SystemdTarget),
+        schemas(crate::software::certificates::CertificatesParams),
+        schemas(crate::software::certificates::TrustedCertificate),
+        schemas(crate::software::repo_check::RepositoryCheck),
+        schemas(crate::software::repo_check::RepositoryCheckParams),
+        schemas(crate::software::web::SelectProductParams),
         schemas(crate::software::web::SoftwareProposal),
+        schemas(crate::storage::fstab::FstabEntry),
+        schemas(crate::storage::impact::ImpactConfirmation),
+        schemas(crate::storage::web::MountExistingParams),
+        schemas(crate::storage::web::PartitionCalculation),
+        schemas(crate::storage::web::PlannedMount),
+        schemas(crate::storage::web::PlannedPartitionTable),
         schemas(crate::storage::web::ProductParams),
+        schemas(crate::storage::web::ProposalCalculation),
+        schemas(crate::storage::web::ResizeCalculation),
+        schemas(crate::storage::web::ResizeParams),
+        schemas(crate::storage::web::StorageStatus),
+        schemas(crate::storage::web::UnlockParams),
+        schemas(agama_lib::storage::client::UnlockResult),
         schemas(crate::storage::web::iscsi::DiscoverParams),
         schemas(crate::storage::web::iscsi::InitiatorParams),
         schemas(crate::storage::web::iscsi::LoginParams),
         schemas(crate::storage::web::iscsi::NodeParams),
+        schemas(crate::storage::web::iscsi::AllNodes),
+        schemas(crate::storage::web::iscsi::NodeStartupResult),
+        schemas(crate::storage::web::iscsi::NodesSelection),
+        schemas(crate::storage::web::iscsi::NodesStartupParams),
+        schemas(crate::storage::web::iscsi::TestAuthParams),
+        schemas(crate::storage::web::iscsi::TestAuthResult),
         schemas(agama_lib::users::model::RootConfig),
         schemas(agama_lib::users::model::RootPatchSettings),
+        schemas(agama_lib::users::model::UserAccount),
+        schemas(agama_lib::users::model::UserExpiration),
+        schemas(agama_lib::users::model::UserGroup),
+        schemas(crate::users::password::GeneratedPassword),
+        schemas(crate::users::password::PasswordParams),
+        schemas(super::common::CancelResult),
+        schemas(super::common::Operation),
+        schemas(super::common::OperationStatus),
+        schemas(super::backends::BackendStatus),
+        schemas(super::capabilities::Capabilities),
+        schemas(super::changes::ChangeKind),
+        schemas(super::changes::ConfigChange),
+        schemas(super::changes::ConfigChanges),
+        schemas(super::commit::CommitFailure),
+        schemas(super::commit::CommitReport),
+        schemas(super::commit::CommitRequest),
+        schemas(super::commit::CommitStep),
+        schemas(super::estimate::EstimateBasis),
+        schemas(super::estimate::InstallEstimate),
+        schemas(super::readiness::ModuleIssue),
+        schemas(super::readiness::Readiness),
         schemas(super::http::PingResponse)
     )
 )]
@@ -0,0 +1,293 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the estimation of the remaining installation time.
+//!
+//! The [InstallEstimator] follows the installation phase and the progress of the packages
+//! installation (`SoftwareProgress` events). The remaining time is extrapolated from the rate
+//! observed since the installation started, so it is only a rough estimate which gets refined
+//! as the installation progresses. It is reported by the `/api/estimate` endpoint and through
+//! `InstallationEstimate` events.
+
+use super::{state::ServiceState, Event, EventsReceiver, EventsSender};
+use agama_lib::manager::InstallationPhase;
+use axum::{extract::State, Json};
+use serde::Serialize;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::broadcast::error::RecvError;
+use utoipa::ToSchema;
+
+/// Time for the steps after installing the packages (e.g., the boot loader installation).
+const FINALIZATION_ESTIMATE: Duration = Duration::from_secs(60);
+
+/// Minimum time between two `InstallationEstimate` events.
+const EVENTS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Data used to calculate the estimate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum EstimateBasis {
+    /// The installation has not started or there is not enough data yet.
+    #[default]
+    Unavailable,
+    /// Rate of the downloaded and installed bytes.
+    Bytes,
+    /// Rate of the installed packages (the sizes are unknown).
+    Packages,
+}
+
+/// Estimate of the remaining installation time.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallEstimate {
+    /// Current installation phase, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phase: Option<InstallationPhase>,
+    /// Estimated remaining time in seconds. It is only an approximation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_remaining_seconds: Option<u64>,
+    /// Time since the installation started in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_seconds: Option<u64>,
+    /// Observed rate in bytes per second.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_per_second: Option<u64>,
+    pub basis: EstimateBasis,
+}
+
+/// Progress of the packages installation at a given time.
+#[derive(Clone, Copy, Debug)]
+struct Sample {
+    at: Instant,
+    installed: u32,
+    total: u32,
+    bytes_done: u64,
+    bytes_total: u64,
+}
+
+#[derive(Debug, Default)]
+struct EstimatorState {
+    phase: Option<InstallationPhase>,
+    started: Option<Instant>,
+    first: Option<Sample>,
+    last: Option<Sample>,
+    last_event: Option<Instant>,
+}
+
+/// Estimates the remaining installation time.
+#[derive(Debug, Default)]
+pub struct InstallEstimator {
+    state: Mutex<EstimatorState>,
+}
+
+impl InstallEstimator {
+    /// Registers a change of the installation phase.
+    ///
+    /// Entering the installation phase starts a new measurement.
+    fn phase_changed(&self, phase: InstallationPhase, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        if phase == InstallationPhase::Install {
+            *state = EstimatorState {
+                started: Some(now),
+                ..Default::default()
+            };
+        }
+        state.phase = Some(phase);
+    }
+
+    /// Registers the progress of the packages installation.
+    ///
+    /// Returns whether an event should be emitted, limiting them to one each [EVENTS_INTERVAL].
+    fn add_progress(
+        &self,
+        installed: u32,
+        total: u32,
+        bytes_done: u64,
+        bytes_total: u64,
+        now: Instant,
+    ) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let sample = Sample {
+            at: now,
+            installed,
+            total,
+            bytes_done,
+            bytes_total,
+        };
+        state.first.get_or_insert(sample);
+        state.last = Some(sample);
+
+        let emit = state
+            .last_event
+            .map_or(true, |last| now.duration_since(last) >= EVENTS_INTERVAL);
+        if emit {
+            state.last_event = Some(now);
+        }
+        emit
+    }
+
+    /// Returns the estimate at the given time.
+    fn estimate_at(&self, now: Instant) -> InstallEstimate {
+        let state = self.state.lock().unwrap();
+        let mut estimate = InstallEstimate {
+            phase: state.phase,
+            elapsed_seconds: state.started.map(|s| now.duration_since(s).as_secs()),
+            ..Default::default()
+        };
+        let (Some(first), Some(last)) = (state.first, state.last) else {
+            return estimate;
+        };
+
+        if last.total > 0 && last.installed >= last.total {
+            let finished = now.duration_since(last.at);
+            estimate.estimated_remaining_seconds =
+                Some(FINALIZATION_ESTIMATE.saturating_sub(finished).as_secs());
+            estimate.basis = EstimateBasis::Packages;
+            return estimate;
+        }
+
+        // the rate is measured from the first sample, as the download starts after it
+        let elapsed = last.at.duration_since(first.at).as_secs_f64();
+        if elapsed <= 0.0 {
+            return estimate;
+        }
+        let bytes_rate = (last.bytes_done.saturating_sub(first.bytes_done)) as f64 / elapsed;
+        let packages_rate = (last.installed.saturating_sub(first.installed)) as f64 / elapsed;
+        let remaining = if last.bytes_total > 0 && bytes_rate > 0.0 {
+            estimate.basis = EstimateBasis::Bytes;
+            estimate.bytes_per_second = Some(bytes_rate as u64);
+            last.bytes_total.saturating_sub(last.bytes_done) as f64 / bytes_rate
+        } else if packages_rate > 0.0 {
+            estimate.basis = EstimateBasis::Packages;
+            last.total.saturating_sub(last.installed) as f64 / packages_rate
+        } else {
+            return estimate;
+        };
+
+        // the time since the last sample is already part of the remaining time
+        let since_last = now.duration_since(last.at).as_secs_f64();
+        let remaining = (remaining - since_last).max(0.0) + FINALIZATION_ESTIMATE.as_secs_f64();
+        estimate.estimated_remaining_seconds = Some(remaining.round() as u64);
+        estimate
+    }
+
+    /// Returns the current estimate.
+    pub fn estimate(&self) -> InstallEstimate {
+        self.estimate_at(Instant::now())
+    }
+}
+
+/// Updates the estimate according to the events, emitting an `InstallationEstimate` event when
+/// it changes.
+///
+/// * `estimator`: estimator to update.
+/// * `events`: channel to receive the events from.
+/// * `sender`: channel to send the estimates through.
+pub async fn watch_events(
+    estimator: Arc<InstallEstimator>,
+    mut events: EventsReceiver,
+    sender: EventsSender,
+) {
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+        let now = Instant::now();
+        let emit = match event {
+            Event::InstallationPhaseChanged { phase } => {
+                estimator.phase_changed(phase, now);
+                phase == InstallationPhase::Install
+            }
+            Event::SoftwareProgress {
+                installed,
+                total,
+                bytes_done,
+                bytes_total,
+                ..
+            } => estimator.add_progress(installed, total, bytes_done, bytes_total, now),
+            _ => false,
+        };
+        if emit {
+            _ = sender.send(Event::InstallationEstimate {
+                estimate: estimator.estimate_at(now),
+            });
+        }
+    }
+}
+
+#[utoipa::path(get, path = "/estimate", responses(
+    (status = 200, description = "Estimate of the remaining installation time", body = InstallEstimate)
+))]
+pub async fn estimate(State(state): State<ServiceState>) -> Json<InstallEstimate> {
+    Json(state.estimator.estimate())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_by_bytes() {
+        let estimator = InstallEstimator::default();
+        let start = Instant::now();
+        assert_eq!(
+            estimator.estimate_at(start).basis,
+            EstimateBasis::Unavailable
+        );
+
+        estimator.phase_changed(InstallationPhase::Install, start);
+        assert!(estimator.add_progress(0, 100, 0, 1000, start));
+        assert!(!estimator.add_progress(10, 100, 100, 1000, start + Duration::from_secs(1)));
+
+        // 100 bytes per second and 900 remaining bytes
+        let estimate = estimator.estimate_at(start + Duration::from_secs(1));
+        assert_eq!(estimate.phase, Some(InstallationPhase::Install));
+        assert_eq!(estimate.basis, EstimateBasis::Bytes);
+        assert_eq!(estimate.bytes_per_second, Some(100));
+        assert_eq!(estimate.elapsed_seconds, Some(1));
+        assert_eq!(estimate.estimated_remaining_seconds, Some(9 + 60));
+
+        let estimate = estimator.estimate_at(start + Duration::from_secs(4));
+        assert_eq!(estimate.estimated_remaining_seconds, Some(6 + 60));
+    }
+
+    #[test]
+    fn test_estimate_by_packages() {
+        let estimator = InstallEstimator::default();
+        let start = Instant::now();
+        estimator.phase_changed(InstallationPhase::Install, start);
+        estimator.add_progress(0, 10, 0, 0, start);
+        estimator.add_progress(5, 10, 0, 0, start + Duration::from_secs(10));
+
+        let estimate = estimator.estimate_at(start + Duration::from_secs(10));
+        assert_eq!(estimate.basis, EstimateBasis::Packages);
+        assert_eq!(estimate.estimated_remaining_seconds, Some(10 + 60));
+
+        // all the packages are installed
+        estimator.add_progress(10, 10, 0, 0, start + Duration::from_secs(20));
+        let estimate = estimator.estimate_at(start + Duration::from_secs(50));
+        assert_eq!(estimate.estimated_remaining_seconds, Some(30));
+    }
+}
@@ -18,6 +18,8 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
+use super::common::OperationStatus;
+use super::estimate::InstallEstimate;
 use crate::network::model::NetworkChange;
 use agama_lib::{
     jobs::Job,
@@ -45,6 +47,10 @@ use super::common::Issue;
 #[serde(tag = "type")]
 pub enum Event {
     L10nConfigChanged(LocaleConfig),
+    /// The user configuration of a service was discarded.
+    ConfigReset {
+        service: String,
+    },
     LocaleChanged {
         locale: String,
     },
@@ -72,11 +78,25 @@ pub enum Event {
         #[serde(flatten)]
         change: NetworkChange,
     },
+    /// The activation of a network connection failed.
+    NetworkConnectionError {
+        id: String,
+        reason: String,
+    },
     // TODO: it should include the full software proposal or, at least,
     // all the relevant changes.
     SoftwareProposalChanged {
         patterns: HashMap<String, SelectedBy>,
     },
+    /// Progress of the packages installation (only during the installation phase).
+    #[serde(rename_all = "camelCase")]
+    SoftwareProgress {
+        current_package: String,
+        installed: u32,
+        total: u32,
+        bytes_done: u64,
+        bytes_total: u64,
+    },
     QuestionsChanged,
     InstallationPhaseChanged {
         phase: InstallationPhase,
@@ -149,6 +169,21 @@ pub enum Event {
     ZFCPControllerRemoved {
         device: ZFCPController,
     },
+    OperationFinished {
+        service: String,
+        id: u32,
+        status: OperationStatus,
+    },
+    /// Estimate of the remaining installation time.
+    InstallationEstimate {
+        #[serde(flatten)]
+        estimate: InstallEstimate,
+    },
+    /// Current state of a module, sent on subscription if requested.
+    StateSnapshot {
+        module: String,
+        state: serde_json::Value,
+    },
 }
 
 pub type EventsSender = Sender<Event>;
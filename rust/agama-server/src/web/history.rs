@@ -0,0 +1,162 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the history of the events sent to the WebSocket and SSE clients.
+//!
+//! Each event gets a sequence number and the most recent ones are kept, so a client which
+//! reconnects can ask for the events it missed (see the `since` query parameter). If the
+//! requested events are not kept anymore, the client gets the oldest ones available and it can
+//! detect the gap by looking at the sequence numbers.
+
+use super::{Event, EventsReceiver};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Number of events which are kept.
+pub const HISTORY_SIZE: usize = 256;
+
+/// Event with its sequence number.
+#[derive(Clone, Debug)]
+pub struct SequencedEvent {
+    pub seq: u64,
+    pub event: Event,
+}
+
+pub type SequencedReceiver = broadcast::Receiver<SequencedEvent>;
+
+#[derive(Default)]
+struct HistoryInner {
+    last_seq: u64,
+    events: VecDeque<SequencedEvent>,
+}
+
+/// Numbers the events and keeps the most recent ones.
+pub struct EventHistory {
+    inner: Mutex<HistoryInner>,
+    sender: broadcast::Sender<SequencedEvent>,
+    capacity: usize,
+}
+
+impl EventHistory {
+    /// Creates an empty history.
+    ///
+    /// * `capacity`: number of events to keep.
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            inner: Mutex::new(Default::default()),
+            sender,
+            capacity,
+        }
+    }
+
+    /// Numbers the event, keeps it and sends it to the subscribers.
+    pub fn push(&self, event: Event) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.last_seq += 1;
+        let event = SequencedEvent {
+            seq: inner.last_seq,
+            event,
+        };
+        if inner.events.len() >= self.capacity {
+            inner.events.pop_front();
+        }
+        inner.events.push_back(event.clone());
+        // send it while holding the lock, so the subscribers do not miss nor repeat events
+        _ = self.sender.send(event);
+    }
+
+    /// Subscribes to the new events.
+    ///
+    /// It returns the kept events after the given sequence number (if any) and the receiver of
+    /// the new ones.
+    ///
+    /// * `since`: sequence number of the last event the client received.
+    pub fn subscribe(&self, since: Option<u64>) -> (Vec<SequencedEvent>, SequencedReceiver) {
+        let inner = self.inner.lock().unwrap();
+        let missed = match since {
+            Some(since) => inner
+                .events
+                .iter()
+                .filter(|e| e.seq > since)
+                .cloned()
+                .collect(),
+            None => vec![],
+        };
+        (missed, self.sender.subscribe())
+    }
+}
+
+impl Default for EventHistory {
+    fn default() -> Self {
+        Self::new(HISTORY_SIZE)
+    }
+}
+
+/// Adds the events to the history.
+///
+/// * `history`: history to add the events to.
+/// * `events`: channel to receive the events from.
+pub async fn watch_events(history: Arc<EventHistory>, mut events: EventsReceiver) {
+    loop {
+        match events.recv().await {
+            Ok(event) => history.push(event),
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!("The events history lagged, {} events skipped", skipped);
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventHistory;
+    use crate::web::Event;
+
+    fn dirty(dirty: bool) -> Event {
+        Event::DevicesDirty { dirty }
+    }
+
+    #[test]
+    fn test_subscribe_since() {
+        let history = EventHistory::new(2);
+        history.push(dirty(true));
+        history.push(dirty(false));
+        history.push(dirty(true));
+
+        let (missed, _) = history.subscribe(None);
+        assert!(missed.is_empty());
+
+        let (missed, _) = history.subscribe(Some(0));
+        let seqs: Vec<_> = missed.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![2, 3]);
+
+        let (missed, mut rx) = history.subscribe(Some(2));
+        assert_eq!(missed.len(), 1);
+        assert_eq!(missed[0].seq, 3);
+
+        history.push(dirty(false));
+        assert_eq!(rx.try_recv().unwrap().seq, 4);
+    }
+}
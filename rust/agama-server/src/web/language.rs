@@ -0,0 +1,325 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the request-scoped language.
+//!
+//! The language is taken from the `Accept-Language` header and it is used to translate the
+//! human-readable messages of the responses (e.g., the error bodies or the question texts). The
+//! translations come from the gettext catalogs of the Agama services ("agama" text domain), so
+//! the messages are translated as the rest of the installer. The messages without a translation,
+//! and the requests without a supported language, use the default (English) message.
+//! Machine-readable values (e.g., error codes or question classes) are never translated.
+
+use crate::l10n::suggestion::parse_accept_language;
+use axum::{
+    extract::Request,
+    http::header::ACCEPT_LANGUAGE,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+};
+
+/// Directory containing the compiled translations of the Agama services.
+const LOCALE_DIR: &str = "/usr/share/YaST2/locale";
+
+/// Text domain of the Agama services.
+const TEXT_DOMAIN: &str = "agama";
+
+/// Magic number of the compiled (.mo) catalogs.
+const MO_MAGIC: u32 = 0x950412de;
+
+/// Translations of a language, read from a compiled gettext catalog.
+#[derive(Debug, Default)]
+struct Catalog {
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Reads the catalog of a language from the given locale directory.
+    ///
+    /// It returns `None` if the language has no catalog or it cannot be read.
+    ///
+    /// * `dir`: locale directory (e.g., "/usr/share/YaST2/locale").
+    /// * `language`: language (e.g., "de" or "pt_BR").
+    fn read(dir: &Path, language: &str) -> Option<Self> {
+        let path = catalog_path(dir, language);
+        let data = std::fs::read(&path).ok()?;
+        let catalog = Self::from_mo(&data);
+        if catalog.is_none() {
+            tracing::warn!("Could not read the translations from {}", path.display());
+        }
+        catalog
+    }
+
+    /// Parses a compiled (.mo) catalog.
+    ///
+    /// Only the singular form of each message is used and the messages with a context are
+    /// ignored.
+    ///
+    /// * `data`: content of the catalog.
+    fn from_mo(data: &[u8]) -> Option<Self> {
+        let word = |offset: usize, big_endian: bool| -> Option<u32> {
+            let bytes: [u8; 4] = data.get(offset..offset + 4)?.try_into().ok()?;
+            Some(if big_endian {
+                u32::from_be_bytes(bytes)
+            } else {
+                u32::from_le_bytes(bytes)
+            })
+        };
+        let big_endian = word(0, true)? == MO_MAGIC;
+        if !big_endian && word(0, false)? != MO_MAGIC {
+            return None;
+        }
+        let read = |offset: usize| word(offset, big_endian).map(|w| w as usize);
+        let string = |table: usize, index: usize| -> Option<&str> {
+            let length = read(table + index * 8)?;
+            let offset = read(table + index * 8 + 4)?;
+            let bytes = data.get(offset..offset + length)?;
+            // plural forms are separated by NUL characters
+            let singular = bytes.split(|b| *b == 0).next().unwrap_or_default();
+            std::str::from_utf8(singular).ok()
+        };
+
+        let count = read(8)?;
+        let originals = read(12)?;
+        let translations = read(16)?;
+        let mut messages = HashMap::new();
+        for index in 0..count {
+            let original = string(originals, index)?;
+            let translation = string(translations, index)?;
+            // the empty message is the header and the context is separated by EOT
+            if original.is_empty() || original.contains('\u{4}') || translation.is_empty() {
+                continue;
+            }
+            messages.insert(original.to_string(), translation.to_string());
+        }
+        Some(Self { messages })
+    }
+
+    /// Returns the translation of a message, if any.
+    ///
+    /// * `message`: message in English.
+    fn get(&self, message: &str) -> Option<&str> {
+        self.messages.get(message).map(String::as_str)
+    }
+}
+
+/// Path of the catalog of a language.
+///
+/// * `dir`: locale directory.
+/// * `language`: language (e.g., "de" or "pt_BR").
+fn catalog_path(dir: &Path, language: &str) -> PathBuf {
+    dir.join(language)
+        .join("LC_MESSAGES")
+        .join(format!("{TEXT_DOMAIN}.mo"))
+}
+
+/// Returns the catalog of a language, reading it the first time it is requested.
+///
+/// * `language`: language (e.g., "de" or "pt_BR").
+fn catalog(language: &str) -> Option<Arc<Catalog>> {
+    static CATALOGS: OnceLock<Mutex<HashMap<String, Option<Arc<Catalog>>>>> = OnceLock::new();
+    let mut catalogs = CATALOGS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    catalogs
+        .entry(language.to_string())
+        .or_insert_with(|| Catalog::read(Path::new(LOCALE_DIR), language).map(Arc::new))
+        .clone()
+}
+
+tokio::task_local! {
+    static LANGUAGE: Option<Arc<Catalog>>;
+}
+
+/// Middleware which sets the language of each request.
+pub async fn language_middleware(request: Request, next: Next) -> Response {
+    let catalog = request
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|header| select_language(header, catalog));
+    LANGUAGE
+        .scope(catalog, next.run(request))
+        .await
+        .into_response()
+}
+
+/// Selects the catalog of the first language of an `Accept-Language` header which has
+/// translations.
+///
+/// The territory is preferred (e.g., "pt_BR" for "pt-BR"), falling back to the language (e.g.,
+/// "de" for "de-CH"). It returns `None` when English comes before any translated language.
+///
+/// * `header`: value of the header.
+/// * `find`: function to find the catalog of a language.
+fn select_language<T>(header: &str, find: impl Fn(&str) -> Option<T>) -> Option<T> {
+    for tag in parse_accept_language(header) {
+        let mut parts = tag.split(['-', '_']);
+        let language = parts.next().unwrap_or_default().to_lowercase();
+        if language == "en" {
+            return None;
+        }
+        if let Some(territory) = parts.next() {
+            let locale = format!("{}_{}", language, territory.to_uppercase());
+            if let Some(catalog) = find(&locale) {
+                return Some(catalog);
+            }
+        }
+        if let Some(catalog) = find(&language) {
+            return Some(catalog);
+        }
+    }
+    None
+}
+
+/// Translates a message to the language of the current request.
+///
+/// It returns the message itself if there is no translation.
+///
+/// * `message`: message in English.
+pub fn translate(message: &str) -> String {
+    let translation = LANGUAGE
+        .try_with(|catalog| {
+            catalog
+                .as_ref()
+                .and_then(|c| c.get(message).map(str::to_string))
+        })
+        .ok()
+        .flatten();
+    translation.unwrap_or_else(|| message.to_string())
+}
+
+/// Translates a message and replaces its `{}` placeholders with the given arguments.
+///
+/// * `message`: message in English.
+/// * `args`: arguments to replace the placeholders with, in order.
+pub fn translate_with(message: &str, args: &[&dyn Display]) -> String {
+    let translation = translate(message);
+    let mut parts = translation.split("{}");
+    let mut result = parts.next().unwrap_or_default().to_string();
+    // the arguments are not searched for placeholders (e.g., a pattern like "a{}")
+    for (i, part) in parts.enumerate() {
+        if let Some(arg) = args.get(i) {
+            result.push_str(&arg.to_string());
+        }
+        result.push_str(part);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a little-endian compiled catalog containing the given messages.
+    fn build_mo(messages: &[(&str, &str)]) -> Vec<u8> {
+        let header = 28;
+        let strings = header + messages.len() * 16;
+        let mut tables = vec![];
+        let mut data = vec![];
+        for (original, _) in messages {
+            tables.push((original.len(), strings + data.len()));
+            data.extend_from_slice(original.as_bytes());
+            data.push(0);
+        }
+        for (_, translation) in messages {
+            tables.push((translation.len(), strings + data.len()));
+            data.extend_from_slice(translation.as_bytes());
+            data.push(0);
+        }
+
+        let count = messages.len() as u32;
+        let mut mo = vec![];
+        for word in [MO_MAGIC, 0, count, 28, 28 + count * 8, 0, 0] {
+            mo.extend_from_slice(&word.to_le_bytes());
+        }
+        for (length, offset) in tables {
+            mo.extend_from_slice(&(length as u32).to_le_bytes());
+            mo.extend_from_slice(&(offset as u32).to_le_bytes());
+        }
+        mo.extend(data);
+        mo
+    }
+
+    #[test]
+    fn test_read_catalog() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = catalog_path(dir.path(), "de");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mo = build_mo(&[
+            ("", "Content-Type: text/plain; charset=UTF-8\n"),
+            ("D-Bus error", "D-Bus-Fehler"),
+            ("file\0files", "Datei\0Dateien"),
+            ("menu\u{4}Open", "Öffnen"),
+        ]);
+        std::fs::write(&path, mo).unwrap();
+
+        let catalog = Catalog::read(dir.path(), "de").unwrap();
+        assert_eq!(catalog.get("D-Bus error"), Some("D-Bus-Fehler"));
+        assert_eq!(catalog.get("file"), Some("Datei"));
+        assert_eq!(catalog.get("Open"), None);
+        assert_eq!(catalog.get(""), None);
+        assert!(Catalog::read(dir.path(), "es").is_none());
+        assert!(Catalog::from_mo(b"not a catalog").is_none());
+    }
+
+    #[test]
+    fn test_select_language() {
+        let find = |language: &str| {
+            ["de", "es", "pt_BR"]
+                .contains(&language)
+                .then(|| language.to_string())
+        };
+        let select = |header: &str| select_language(header, find);
+        assert_eq!(select("fr, de-CH;q=0.9, es;q=0.8").as_deref(), Some("de"));
+        assert_eq!(select("pt-br, ES").as_deref(), Some("pt_BR"));
+        assert_eq!(select("fr, en, de"), None);
+        assert_eq!(select(""), None);
+    }
+
+    #[test]
+    fn test_translate() {
+        let message = "The answer must be at least {} characters long";
+        assert_eq!(
+            translate_with(message, &[&3]),
+            "The answer must be at least 3 characters long"
+        );
+
+        let catalog = Catalog::from_mo(&build_mo(&[
+            ("D-Bus error", "D-Bus-Fehler"),
+            (message, "Die Antwort muss mindestens {} Zeichen lang sein"),
+        ]));
+        LANGUAGE.sync_scope(catalog.map(Arc::new), || {
+            assert_eq!(translate("D-Bus error"), "D-Bus-Fehler");
+            assert_eq!(
+                translate_with(message, &[&3]),
+                "Die Antwort muss mindestens 3 Zeichen lang sein"
+            );
+            assert_eq!(translate("Unknown message"), "Unknown message");
+        });
+    }
+}
@@ -0,0 +1,469 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the metrics of the web service in the Prometheus text format.
+//!
+//! The following metrics are exposed at `/api/metrics`:
+//!
+//! * `agama_http_requests_total` and `agama_http_request_duration_seconds`: number and latency
+//!   of the HTTP requests by method, route and status.
+//! * `agama_dbus_calls_total` and `agama_dbus_call_errors_total`: D-Bus calls answered on the
+//!   Agama bus and the failed ones by error name. They are counted from the replies received on
+//!   the connection, so every call is included no matter which client performs it.
+//! * `agama_websocket_connections` and `agama_sse_connections`: number of connected event
+//!   stream clients.
+//! * `agama_event_streams_rejected_total`: event stream connections rejected because the limit
+//!   was reached.
+//! * `agama_installation_phase`: current installation phase.
+//!
+//! The route label is the route template (e.g., `/api/storage/iscsi/nodes/:id`), so identifiers
+//! or secrets in the paths are not leaked. The requests which do not match any route are labeled
+//! as `unmatched`.
+
+use super::{state::ServiceState, Event, EventsReceiver};
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicI64, AtomicU32, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+use tokio_stream::StreamExt;
+use zbus::{MessageStream, MessageType};
+
+/// Upper bounds (in seconds) of the latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 8] = [0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 30.0];
+
+/// Route label of the requests which do not match any route.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Value of the installation phase gauge when the phase is unknown.
+const UNKNOWN_PHASE: u32 = u32::MAX;
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct RequestKey {
+    method: String,
+    route: String,
+    status: u16,
+}
+
+#[derive(Clone, Debug, Default)]
+struct RequestStats {
+    count: u64,
+    sum: f64,
+    buckets: [u64; LATENCY_BUCKETS.len()],
+}
+
+/// Metrics of the web service.
+pub struct Metrics {
+    requests: Mutex<BTreeMap<RequestKey, RequestStats>>,
+    websocket_connections: AtomicI64,
+    sse_connections: AtomicI64,
+    rejected_streams: AtomicU64,
+    installation_phase: AtomicU32,
+    dbus_calls: AtomicU64,
+    dbus_errors: Mutex<BTreeMap<String, u64>>,
+}
+
+/// Route template of a request served by a nested service.
+///
+/// The router of a nested service is the only one which knows the matched route, so it is
+/// passed back to the metrics middleware in the response extensions.
+#[derive(Clone, Debug)]
+struct RouteLabel(String);
+
+/// Kinds of event streams.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StreamKind {
+    WebSocket,
+    Sse,
+}
+
+/// Keeps an event stream accounted for until it is dropped (e.g., when the client disconnects).
+pub struct StreamGuard {
+    metrics: Arc<Metrics>,
+    kind: StreamKind,
+}
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        self.metrics
+            .connections(self.kind)
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            requests: Mutex::new(BTreeMap::new()),
+            websocket_connections: AtomicI64::new(0),
+            sse_connections: AtomicI64::new(0),
+            rejected_streams: AtomicU64::new(0),
+            installation_phase: AtomicU32::new(UNKNOWN_PHASE),
+            dbus_calls: AtomicU64::new(0),
+            dbus_errors: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl Metrics {
+    /// Records a finished HTTP request.
+    ///
+    /// * `method`: request method.
+    /// * `route`: matched route template.
+    /// * `status`: response status code.
+    /// * `seconds`: request duration.
+    pub fn record_request(&self, method: &str, route: &str, status: u16, seconds: f64) {
+        let key = RequestKey {
+            method: method.to_string(),
+            route: route.to_string(),
+            status,
+        };
+        let Ok(mut requests) = self.requests.lock() else {
+            return;
+        };
+        let stats = requests.entry(key).or_default();
+        stats.count += 1;
+        stats.sum += seconds;
+        for (bucket, bound) in stats.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+    }
+
+    /// Registers a new event stream unless the limit is reached.
+    ///
+    /// It returns `None` if there are already `max` streams (WebSockets and SSE). Otherwise, the
+    /// stream is accounted for until the returned guard is dropped.
+    ///
+    /// * `kind`: kind of stream.
+    /// * `max`: maximum number of concurrent streams.
+    pub fn open_stream(self: &Arc<Self>, kind: StreamKind, max: usize) -> Option<StreamGuard> {
+        self.connections(kind).fetch_add(1, Ordering::Relaxed);
+        let guard = StreamGuard {
+            metrics: Arc::clone(self),
+            kind,
+        };
+        if self.open_streams() > max as i64 {
+            self.rejected_streams.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        Some(guard)
+    }
+
+    /// Returns the number of open event streams.
+    pub fn open_streams(&self) -> i64 {
+        self.websocket_connections.load(Ordering::Relaxed)
+            + self.sse_connections.load(Ordering::Relaxed)
+    }
+
+    fn connections(&self, kind: StreamKind) -> &AtomicI64 {
+        match kind {
+            StreamKind::WebSocket => &self.websocket_connections,
+            StreamKind::Sse => &self.sse_connections,
+        }
+    }
+
+    /// Updates the metrics according to the events (e.g., installation phase changes).
+    ///
+    /// * `events`: channel to receive the events from.
+    pub async fn watch_events(&self, mut events: EventsReceiver) {
+        while let Ok(event) = events.recv().await {
+            if let Event::InstallationPhaseChanged { phase } = event {
+                self.installation_phase
+                    .store(phase as u32, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Records an answered D-Bus call.
+    ///
+    /// * `error`: error name if the call failed.
+    pub fn record_dbus_reply(&self, error: Option<&str>) {
+        self.dbus_calls.fetch_add(1, Ordering::Relaxed);
+        let Some(error) = error else {
+            return;
+        };
+        if let Ok(mut errors) = self.dbus_errors.lock() {
+            *errors.entry(error.to_string()).or_default() += 1;
+        }
+    }
+
+    /// Counts the D-Bus calls answered on the given connection.
+    ///
+    /// * `connection`: D-Bus connection shared by the services.
+    pub async fn watch_dbus(&self, connection: zbus::Connection) {
+        let mut messages = MessageStream::from(connection);
+        while let Some(message) = messages.next().await {
+            let Ok(message) = message else {
+                continue;
+            };
+            match message.message_type() {
+                MessageType::MethodReturn => self.record_dbus_reply(None),
+                MessageType::Error => {
+                    let name = message
+                        .header()
+                        .ok()
+                        .and_then(|h| h.error_name().ok().flatten().map(|n| n.to_string()));
+                    self.record_dbus_reply(Some(name.as_deref().unwrap_or("unknown")));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Renders the metrics in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+
+        output.push_str("# HELP agama_http_requests_total Number of HTTP requests.\n");
+        output.push_str("# TYPE agama_http_requests_total counter\n");
+        let requests = self.requests.lock().map(|r| r.clone()).unwrap_or_default();
+        for (key, stats) in requests.iter() {
+            _ = writeln!(
+                output,
+                "agama_http_requests_total{{{}}} {}",
+                request_labels(key),
+                stats.count
+            );
+        }
+
+        output.push_str("# HELP agama_http_request_duration_seconds HTTP requests latency.\n");
+        output.push_str("# TYPE agama_http_request_duration_seconds histogram\n");
+        for (key, stats) in requests.iter() {
+            let labels = request_labels(key);
+            for (bucket, bound) in stats.buckets.iter().zip(LATENCY_BUCKETS) {
+                _ = writeln!(
+                    output,
+                    "agama_http_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                    labels, bound, bucket
+                );
+            }
+            _ = writeln!(
+                output,
+                "agama_http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+                labels, stats.count
+            );
+            _ = writeln!(
+                output,
+                "agama_http_request_duration_seconds_sum{{{}}} {}",
+                labels, stats.sum
+            );
+            _ = writeln!(
+                output,
+                "agama_http_request_duration_seconds_count{{{}}} {}",
+                labels, stats.count
+            );
+        }
+
+        output.push_str("# HELP agama_dbus_calls_total Number of answered D-Bus calls.\n");
+        output.push_str("# TYPE agama_dbus_calls_total counter\n");
+        _ = writeln!(
+            output,
+            "agama_dbus_calls_total {}",
+            self.dbus_calls.load(Ordering::Relaxed)
+        );
+        output.push_str("# HELP agama_dbus_call_errors_total Number of failed D-Bus calls.\n");
+        output.push_str("# TYPE agama_dbus_call_errors_total counter\n");
+        let errors = self
+            .dbus_errors
+            .lock()
+            .map(|e| e.clone())
+            .unwrap_or_default();
+        for (name, count) in errors.iter() {
+            _ = writeln!(
+                output,
+                "agama_dbus_call_errors_total{{error=\"{}\"}} {}",
+                escape_label(name),
+                count
+            );
+        }
+
+        output.push_str("# HELP agama_websocket_connections Connected WebSocket clients.\n");
+        output.push_str("# TYPE agama_websocket_connections gauge\n");
+        _ = writeln!(
+            output,
+            "agama_websocket_connections {}",
+            self.websocket_connections.load(Ordering::Relaxed)
+        );
+
+        output.push_str("# HELP agama_sse_connections Connected Server-Sent Events clients.\n");
+        output.push_str("# TYPE agama_sse_connections gauge\n");
+        _ = writeln!(
+            output,
+            "agama_sse_connections {}",
+            self.sse_connections.load(Ordering::Relaxed)
+        );
+
+        output.push_str(
+            "# HELP agama_event_streams_rejected_total Event streams rejected because of the limit.\n",
+        );
+        output.push_str("# TYPE agama_event_streams_rejected_total counter\n");
+        _ = writeln!(
+            output,
+            "agama_event_streams_rejected_total {}",
+            self.rejected_streams.load(Ordering::Relaxed)
+        );
+
+        output.push_str(
+            "# HELP agama_installation_phase Installation phase (0: startup, 1: config, 2: install).\n",
+        );
+        output.push_str("# TYPE agama_installation_phase gauge\n");
+        let phase = self.installation_phase.load(Ordering::Relaxed);
+        if phase != UNKNOWN_PHASE {
+            _ = writeln!(output, "agama_installation_phase {}", phase);
+        }
+
+        output
+    }
+}
+
+fn request_labels(key: &RequestKey) -> String {
+    format!(
+        "method=\"{}\",route=\"{}\",status=\"{}\"",
+        escape_label(&key.method),
+        escape_label(&key.route),
+        key.status
+    )
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Middleware which passes the matched route of a nested service to the metrics middleware.
+pub async fn route_label_middleware(request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string());
+    let mut response = next.run(request).await;
+    if let Some(route) = route {
+        response.extensions_mut().insert(RouteLabel(route));
+    }
+    response
+}
+
+/// Middleware which records the HTTP requests metrics.
+pub async fn metrics_middleware(
+    State(state): State<ServiceState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let matched = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string());
+    let start = Instant::now();
+    let response = next.run(request).await;
+    let route = response
+        .extensions()
+        .get::<RouteLabel>()
+        .map(|l| l.0.clone())
+        .or(matched)
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+    state.metrics.record_request(
+        &method,
+        &route,
+        response.status().as_u16(),
+        start.elapsed().as_secs_f64(),
+    );
+    response
+}
+
+/// Returns the metrics in the Prometheus text format.
+pub async fn metrics(State(state): State<ServiceState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Metrics, StreamKind};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_render() {
+        let metrics = Arc::new(Metrics::default());
+        metrics.record_request("GET", "/api/ping", 200, 0.02);
+        let _ws = metrics.open_stream(StreamKind::WebSocket, 10);
+
+        let output = metrics.render();
+        assert!(output.contains(
+            "agama_http_requests_total{method=\"GET\",route=\"/api/ping\",status=\"200\"} 1"
+        ));
+        assert!(output.contains(
+            "agama_http_request_duration_seconds_bucket{method=\"GET\",route=\"/api/ping\",status=\"200\",le=\"0.01\"} 0"
+        ));
+        assert!(output.contains(
+            "agama_http_request_duration_seconds_bucket{method=\"GET\",route=\"/api/ping\",status=\"200\",le=\"0.05\"} 1"
+        ));
+        assert!(output.contains("agama_websocket_connections 1"));
+        assert!(!output.contains("agama_installation_phase 0"));
+    }
+
+    #[test]
+    fn test_render_dbus_calls() {
+        let metrics = Metrics::default();
+        metrics.record_dbus_reply(None);
+        metrics.record_dbus_reply(Some("org.freedesktop.DBus.Error.UnknownMethod"));
+
+        let output = metrics.render();
+        assert!(output.contains("agama_dbus_calls_total 2"));
+        assert!(output.contains(
+            "agama_dbus_call_errors_total{error=\"org.freedesktop.DBus.Error.UnknownMethod\"} 1"
+        ));
+    }
+
+    #[test]
+    fn test_open_stream() {
+        let metrics = Arc::new(Metrics::default());
+        let ws = metrics.open_stream(StreamKind::WebSocket, 2);
+        let sse = metrics.open_stream(StreamKind::Sse, 2);
+        assert!(ws.is_some() && sse.is_some());
+        assert!(metrics.open_stream(StreamKind::Sse, 2).is_none());
+        assert_eq!(metrics.open_streams(), 2);
+
+        drop(sse);
+        assert_eq!(metrics.open_streams(), 1);
+        assert!(metrics.open_stream(StreamKind::Sse, 2).is_some());
+        assert_eq!(metrics.open_streams(), 1);
+
+        let output = metrics.render();
+        assert!(output.contains("agama_sse_connections 0"));
+        assert!(output.contains("agama_event_streams_rejected_total 1"));
+    }
+}
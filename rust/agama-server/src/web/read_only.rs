@@ -0,0 +1,91 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the read-only (inspection) mode of the web server.
+//!
+//! When the `read_only` setting is enabled, the API can be inspected but not modified. Any request
+//! which could change the state of the installer is rejected with a `403 Forbidden` status. The
+//! mutating requests are those using the following methods:
+//!
+//! * `POST` (e.g., `/api/manager/install` or `/api/network/system/apply`).
+//! * `PUT` (e.g., `/api/storage/config`).
+//! * `PATCH` (e.g., `/api/software/config`).
+//! * `DELETE` (e.g., `/api/network/connections/:id`).
+//!
+//! The authentication endpoint (`/api/auth`) is the only exception, so users can still log in and
+//! out. The requests using any other method (`GET`, `HEAD`, `OPTIONS`), including the WebSocket,
+//! work as usual.
+
+use super::state::ServiceState;
+use axum::{
+    extract::{Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+
+/// Paths which accept mutating requests even in read-only mode.
+const ALLOWED_PATHS: [&str; 1] = ["/api/auth"];
+
+/// Determines whether a request could change the state of the installer.
+///
+/// * `method`: HTTP method of the request.
+/// * `path`: path of the request.
+pub fn is_mutating(method: &Method, path: &str) -> bool {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return false;
+    }
+    !ALLOWED_PATHS.contains(&path.trim_end_matches('/'))
+}
+
+/// Middleware which rejects the mutating requests when the server runs in read-only mode.
+pub async fn read_only_middleware(
+    State(state): State<ServiceState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if state.config.read_only && is_mutating(request.method(), request.uri().path()) {
+        let body = json!({ "error": "The server is running in read-only mode" });
+        return (StatusCode::FORBIDDEN, Json(body)).into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_mutating() {
+        assert!(is_mutating(&Method::POST, "/api/manager/install"));
+        assert!(is_mutating(&Method::PUT, "/api/storage/config"));
+        assert!(is_mutating(&Method::PATCH, "/api/software/config"));
+        assert!(is_mutating(
+            &Method::DELETE,
+            "/api/network/connections/eth0"
+        ));
+        assert!(!is_mutating(&Method::GET, "/api/storage/config"));
+        assert!(!is_mutating(&Method::OPTIONS, "/api/ping"));
+        assert!(!is_mutating(&Method::POST, "/api/auth"));
+        assert!(!is_mutating(&Method::DELETE, "/api/auth/"));
+    }
+}
@@ -0,0 +1,279 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the installation readiness endpoint.
+//!
+//! Each module can report the issues which prevent (blocking) or discourage (warning) the
+//! installation by registering a [ReadinessProvider] in the
+//! [MainServiceBuilder](super::MainServiceBuilder). The `/api/readiness` endpoint aggregates all
+//! of them, so the clients can decide whether to enable the installation. An empty list of
+//! blocking issues means that the installation can proceed.
+//!
+//! When a D-Bus backend (see the `backends` module) is not available, the report includes a
+//! blocking issue for it and the modules depending on it are not checked.
+
+use super::{backends, state::ServiceState};
+use crate::error::Error;
+use agama_lib::{error::ServiceError, proxies::IssuesProxy};
+use async_trait::async_trait;
+use axum::{extract::State, Json};
+use serde::Serialize;
+use std::{collections::BTreeMap, sync::Arc};
+use utoipa::ToSchema;
+
+/// Severity of an issue, as reported by the `org.opensuse.Agama1.Issues` interface.
+const ERROR_SEVERITY: u32 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueSeverity {
+    /// The installation cannot start.
+    Blocking,
+    /// The installation can start, but the result might not be the expected one.
+    Warning,
+}
+
+/// Issue reported by a module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadinessIssue {
+    /// Identifier of the issue within the module (e.g., "required").
+    pub code: String,
+    /// Human-readable description.
+    pub message: String,
+    /// Additional information, if any.
+    pub details: Option<String>,
+    pub severity: IssueSeverity,
+}
+
+impl ReadinessIssue {
+    pub fn blocking(code: &str, message: impl Into<String>) -> Self {
+        Self::new(code, message, IssueSeverity::Blocking)
+    }
+
+    pub fn warning(code: &str, message: impl Into<String>) -> Self {
+        Self::new(code, message, IssueSeverity::Warning)
+    }
+
+    fn new(code: &str, message: impl Into<String>, severity: IssueSeverity) -> Self {
+        Self {
+            code: code.to_string(),
+            message: message.into(),
+            details: None,
+            severity,
+        }
+    }
+}
+
+/// Reports the issues of a module.
+///
+/// The issues are evaluated on each request, as they depend on the current configuration.
+#[async_trait]
+pub trait ReadinessProvider: Send + Sync {
+    async fn issues(&self) -> Result<Vec<ReadinessIssue>, Error>;
+}
+
+/// Registered readiness providers, indexed by module name.
+pub type ReadinessProviders = Arc<BTreeMap<String, Box<dyn ReadinessProvider>>>;
+
+/// Reports the issues of an object implementing the `org.opensuse.Agama1.Issues` interface.
+///
+/// The code is the kind of the issue as reported by the service (e.g., "missing_product"),
+/// resulting in codes like "product.missing_product".
+pub struct IssuesReadiness<'a> {
+    proxy: IssuesProxy<'a>,
+}
+
+impl<'a> IssuesReadiness<'a> {
+    /// Builds a provider for the issues of the given D-Bus object.
+    ///
+    /// * `dbus`: D-Bus connection.
+    /// * `destination`: D-Bus service name.
+    /// * `path`: D-Bus object path.
+    pub async fn new(
+        dbus: &zbus::Connection,
+        destination: &str,
+        path: &str,
+    ) -> Result<IssuesReadiness<'a>, ServiceError> {
+        let proxy = IssuesProxy::builder(dbus)
+            .destination(destination.to_string())?
+            .path(path.to_string())?
+            .build()
+            .await?;
+        Ok(Self { proxy })
+    }
+}
+
+#[async_trait]
+impl<'a> ReadinessProvider for IssuesReadiness<'a> {
+    async fn issues(&self) -> Result<Vec<ReadinessIssue>, Error> {
+        let issues = self.proxy.all_with_kind().await?;
+        Ok(issues.into_iter().map(ReadinessIssue::from_dbus).collect())
+    }
+}
+
+impl ReadinessIssue {
+    /// Builds an issue from the values of the `AllWithKind` D-Bus property.
+    ///
+    /// * `issue`: description, details, source, severity and kind.
+    fn from_dbus(issue: (String, String, u32, u32, String)) -> Self {
+        let (description, details, _source, severity, kind) = issue;
+        let severity = if severity == ERROR_SEVERITY {
+            IssueSeverity::Blocking
+        } else {
+            IssueSeverity::Warning
+        };
+        Self {
+            code: if kind.is_empty() {
+                "generic".to_string()
+            } else {
+                kind
+            },
+            message: description,
+            details: (!details.is_empty()).then_some(details),
+            severity,
+        }
+    }
+}
+
+/// Issue included in the readiness report.
+#[derive(Clone, Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleIssue {
+    /// Module reporting the issue (e.g., "storage").
+    pub module: String,
+    /// Stable identifier of the issue, prefixed with the module name (e.g., "network.required").
+    pub code: String,
+    /// Human-readable description.
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+/// Installation readiness.
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Readiness {
+    /// Whether the installation can proceed (there are no blocking issues).
+    ready: bool,
+    /// Issues preventing the installation.
+    blocking: Vec<ModuleIssue>,
+    /// Issues which do not prevent the installation.
+    warnings: Vec<ModuleIssue>,
+}
+
+impl Readiness {
+    /// Builds the report from the issues of each module.
+    ///
+    /// * `issues`: pairs of module name and issue.
+    fn new(issues: Vec<(String, ReadinessIssue)>) -> Self {
+        let mut blocking = vec![];
+        let mut warnings = vec![];
+        for (module, issue) in issues {
+            let entry = ModuleIssue {
+                code: format!("{}.{}", module, issue.code),
+                module,
+                message: issue.message,
+                details: issue.details,
+            };
+            match issue.severity {
+                IssueSeverity::Blocking => blocking.push(entry),
+                IssueSeverity::Warning => warnings.push(entry),
+            }
+        }
+
+        Self {
+            ready: blocking.is_empty(),
+            blocking,
+            warnings,
+        }
+    }
+}
+
+#[utoipa::path(get, path = "/readiness", responses(
+    (status = 200, description = "Blocking and warning issues of all the modules", body = Readiness)
+))]
+pub async fn readiness(State(state): State<ServiceState>) -> Json<Readiness> {
+    let mut issues = vec![];
+    let mut unavailable = vec![];
+    for status in backends::statuses(&state.backends).await {
+        if status.available() {
+            continue;
+        }
+        let mut issue = ReadinessIssue::blocking(
+            "unavailable",
+            format!("The {} service is not available", status.name),
+        );
+        issue.details = status.error;
+        unavailable.push(status.name.clone());
+        issues.push((status.name, issue));
+    }
+
+    for (name, provider) in state.readiness.iter() {
+        if backends::backend_of(&state.backends, name)
+            .is_some_and(|b| unavailable.iter().any(|u| u == b))
+        {
+            continue;
+        }
+        match provider.issues().await {
+            Ok(module_issues) => {
+                issues.extend(module_issues.into_iter().map(|i| (name.clone(), i)));
+            }
+            Err(error) => {
+                tracing::warn!("Could not read the {} issues: {}", name, error);
+                let mut issue =
+                    ReadinessIssue::blocking("unavailable", "Could not check the module issues");
+                issue.details = Some(error.to_string());
+                issues.push((name.clone(), issue));
+            }
+        }
+    }
+
+    Json(Readiness::new(issues))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_from_dbus() {
+        let issue = ReadinessIssue::from_dbus((
+            "Product not selected yet".to_string(),
+            "".to_string(),
+            2,
+            1,
+            "missing_product".to_string(),
+        ));
+        assert_eq!(issue.code, "missing_product");
+        assert_eq!(issue.details, None);
+        assert_eq!(issue.severity, IssueSeverity::Blocking);
+
+        let issue = ReadinessIssue::from_dbus((
+            "Unknown".to_string(),
+            "Details".to_string(),
+            0,
+            0,
+            "".to_string(),
+        ));
+        assert_eq!(issue.code, "generic");
+        assert_eq!(issue.details.as_deref(), Some("Details"));
+        assert_eq!(issue.severity, IssueSeverity::Warning);
+    }
+}
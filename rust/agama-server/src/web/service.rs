@@ -18,23 +18,30 @@
 // To contact SUSE LLC about this file by physical or electronic mail, you may
 // find current contact information at www.suse.com.
 
+use super::backends::Backend;
+use super::capabilities::CapabilitiesProvider;
+use super::commit::{CommitProvider, Commits, CommittedModules};
 use super::http::{login, login_from_query, logout, session};
+use super::metrics::Metrics;
+use super::readiness::ReadinessProvider;
+use super::snapshot::SnapshotProvider;
+use super::sse::DEFAULT_SSE_RETRY;
 use super::{config::ServiceConfig, state::ServiceState, EventsSender};
 use agama_lib::auth::TokenClaims;
 use axum::{
     body::Body,
-    extract::Request,
+    extract::{DefaultBodyLimit, Request},
     middleware,
-    response::{IntoResponse, Response},
+    response::Response,
     routing::{get, post},
     Router,
 };
-use std::time::Duration;
 use std::{
-    convert::Infallible,
+    collections::BTreeMap,
     path::{Path, PathBuf},
 };
-use tower::Service;
+use std::{sync::Arc, time::Duration};
+use tokio::runtime::Handle;
 use tower_http::{compression::CompressionLayer, services::ServeDir, trace::TraceLayer};
 use tracing::Span;
 
@@ -43,15 +50,50 @@ use tracing::Span;
 /// It is responsible for building an axum service which includes:
 ///
 /// * A static assets directory (`public_dir`).
-/// * A websocket at the `/ws` path.
+/// * A websocket at the `/ws` path. Clients can subscribe to some event types only by
+///   passing them in the `events` query parameter (e.g., `/ws?events=Progress,IssuesChanged`).
+/// * A Server-Sent Events stream at the `/events` path, with the same events and filter. The
+///   reconnection interval sent to the clients can be set using `with_sse_retry`.
+/// * Both event endpoints accept a `snapshot` query parameter to receive the current state of
+///   the modules registered with `add_snapshot` before the changes.
 /// * An authentication endpoint at `/auth`.
 /// * A 'ping' endpoint at '/ping'.
+/// * A Prometheus metrics endpoint at '/metrics' (unauthenticated).
+/// * A capabilities endpoint at '/capabilities', aggregating the ones registered with
+///   `add_capabilities`.
+/// * A readiness endpoint at '/readiness', aggregating the issues reported by the providers
+///   registered with `add_readiness`.
+/// * A changes endpoint at '/changes', comparing the state of the modules registered with
+///   `add_snapshot` with the one right after probing.
+/// * An endpoint at '/estimate' reporting the estimated remaining installation time.
+/// * A backends endpoint at '/backends', reporting the status of the D-Bus services registered
+///   with `add_backend`. The requests to the paths depending on an unavailable service are
+///   rejected with a `503 Service Unavailable` status.
+/// * A commit endpoint at '/commit', writing the configuration of the modules registered with
+///   `add_commit` to the system.
 /// * A number of authenticated services that are added using the `add_service` function.
+///
+/// Cross-origin requests are only allowed if they are enabled in the CORS configuration. In
+/// read-only mode, the mutating requests are rejected (see the `read_only` module). The size of
+/// the request bodies is limited (see the `body_limit` module), as well as the time to answer the
+/// requests (see the `timeout` module).
+///
+/// Each request gets an identifier which is included in the logs and in the `X-Request-Id`
+/// response header.
 pub struct MainServiceBuilder {
     config: ServiceConfig,
     events: EventsSender,
     api_router: Router<ServiceState>,
     public_dir: PathBuf,
+    capabilities: BTreeMap<String, Box<dyn CapabilitiesProvider>>,
+    readiness: BTreeMap<String, Box<dyn ReadinessProvider>>,
+    snapshots: BTreeMap<String, Box<dyn SnapshotProvider>>,
+    backends: BTreeMap<String, Backend>,
+    commits: BTreeMap<String, Box<dyn CommitProvider>>,
+    committed: CommittedModules,
+    dbus: Option<zbus::Connection>,
+    runtime: Option<Handle>,
+    sse_retry: Duration,
 }
 
 impl MainServiceBuilder {
@@ -63,7 +105,9 @@ impl MainServiceBuilder {
     where
         P: AsRef<Path>,
     {
-        let api_router = Router::new().route("/ws", get(super::ws::ws_handler));
+        let api_router = Router::new()
+            .route("/ws", get(super::ws::ws_handler))
+            .route("/events", get(super::sse::sse_handler));
         let config = ServiceConfig::default();
 
         Self {
@@ -71,6 +115,15 @@ impl MainServiceBuilder {
             api_router,
             config,
             public_dir: PathBuf::from(public_dir.as_ref()),
+            capabilities: BTreeMap::new(),
+            readiness: BTreeMap::new(),
+            snapshots: BTreeMap::new(),
+            backends: BTreeMap::new(),
+            commits: BTreeMap::new(),
+            committed: CommittedModules::default(),
+            dbus: None,
+            runtime: None,
+            sse_retry: DEFAULT_SSE_RETRY,
         }
     }
 
@@ -78,45 +131,206 @@ impl MainServiceBuilder {
         Self { config, ..self }
     }
 
+    /// Sets the time the SSE clients should wait before reconnecting.
+    ///
+    /// * `retry`: reconnection interval. It defaults to 3 seconds.
+    pub fn with_sse_retry(self, retry: Duration) -> Self {
+        Self {
+            sse_retry: retry,
+            ..self
+        }
+    }
+
     /// Add an authenticated service.
     ///
     /// * `path`: Path to mount the service under `/api`.
     /// * `service`: Service to mount on the given `path`.
-    pub fn add_service<T>(self, path: &str, service: T) -> Self
+    pub fn add_service(self, path: &str, service: Router) -> Self {
+        let service = service.layer(middleware::from_fn(super::metrics::route_label_middleware));
+        Self {
+            api_router: self.api_router.nest_service(path, service),
+            ..self
+        }
+    }
+
+    /// Registers the capabilities of a module, reported by the `/capabilities` endpoint.
+    ///
+    /// * `module`: module name (e.g., "storage").
+    /// * `provider`: object reporting the module capabilities.
+    pub fn add_capabilities<T>(mut self, module: &str, provider: T) -> Self
+    where
+        T: CapabilitiesProvider + 'static,
+    {
+        self.capabilities
+            .insert(module.to_string(), Box::new(provider));
+        self
+    }
+
+    /// Registers the readiness provider of a module, reported by the `/readiness` endpoint.
+    ///
+    /// * `module`: module name (e.g., "storage").
+    /// * `provider`: object reporting the module issues.
+    pub fn add_readiness<T>(mut self, module: &str, provider: T) -> Self
     where
-        T: Service<Request, Error = Infallible> + Clone + Send + 'static,
-        T::Response: IntoResponse,
-        T::Future: Send + 'static,
+        T: ReadinessProvider + 'static,
     {
+        self.readiness
+            .insert(module.to_string(), Box::new(provider));
+        self
+    }
+
+    /// Registers the snapshot provider of a module, used when a client subscribes to the events.
+    ///
+    /// * `module`: module name (e.g., "storage").
+    /// * `provider`: object reporting the module state.
+    pub fn add_snapshot<T>(mut self, module: &str, provider: T) -> Self
+    where
+        T: SnapshotProvider + 'static,
+    {
+        self.snapshots
+            .insert(module.to_string(), Box::new(provider));
+        self
+    }
+
+    /// Registers a D-Bus backend service, reported by the `/backends` endpoint.
+    ///
+    /// * `name`: backend name (e.g., "storage").
+    /// * `backend`: D-Bus service and the API paths depending on it.
+    pub fn add_backend(mut self, name: &str, backend: Backend) -> Self {
+        self.backends.insert(name.to_string(), backend);
+        self
+    }
+
+    /// Registers the commit provider of a module, used by the `/commit` endpoint.
+    ///
+    /// * `module`: module name (e.g., "storage").
+    /// * `provider`: object writing the module configuration.
+    pub fn add_commit<T>(mut self, module: &str, provider: T) -> Self
+    where
+        T: CommitProvider + 'static,
+    {
+        self.commits.insert(module.to_string(), Box::new(provider));
+        self
+    }
+
+    /// Sets the list to record the modules committed through the `/commit` endpoint.
+    ///
+    /// * `committed`: list shared with the services which depend on it (e.g., the manager).
+    pub fn with_committed_modules(self, committed: CommittedModules) -> Self {
+        Self { committed, ..self }
+    }
+
+    /// Sets the D-Bus connection whose calls are reported by the `/metrics` endpoint.
+    ///
+    /// * `dbus`: connection shared by the services.
+    pub fn with_dbus(self, dbus: zbus::Connection) -> Self {
         Self {
-            api_router: self.api_router.nest_service(path, service),
+            dbus: Some(dbus),
+            ..self
+        }
+    }
+
+    /// Sets the runtime to run the background tasks on (e.g., collecting the metrics, recording
+    /// the baseline of the changes or numbering the events sent to the clients).
+    ///
+    /// Without a runtime, those tasks are not started, so the WebSocket and SSE clients only get
+    /// the snapshots.
+    ///
+    /// * `runtime`: handle of the runtime running the service.
+    pub fn with_runtime(self, runtime: Handle) -> Self {
+        Self {
+            runtime: Some(runtime),
             ..self
         }
     }
 
     pub fn build(self) -> Router {
+        let cors = match self.config.cors.layer() {
+            Ok(cors) => cors,
+            Err(error) => {
+                tracing::error!("Ignoring the CORS configuration: {}", error);
+                None
+            }
+        };
+
+        let metrics = Arc::new(Metrics::default());
+        if let Some(runtime) = &self.runtime {
+            let receiver = self.events.subscribe();
+            let watched = Arc::clone(&metrics);
+            runtime.spawn(async move { watched.watch_events(receiver).await });
+            if let Some(dbus) = self.dbus {
+                let watched = Arc::clone(&metrics);
+                runtime.spawn(async move { watched.watch_dbus(dbus).await });
+            }
+        }
+
         let state = ServiceState {
             config: self.config,
             events: self.events,
+            history: Default::default(),
             public_dir: self.public_dir.clone(),
+            metrics,
+            capabilities: Arc::new(self.capabilities),
+            readiness: Arc::new(self.readiness),
+            snapshots: Arc::new(self.snapshots),
+            backends: Arc::new(self.backends),
+            commits: Arc::new(Commits::new(self.commits, self.committed)),
+            baseline: Default::default(),
+            estimator: Default::default(),
+            sse_retry: self.sse_retry,
         };
 
+        if let Some(runtime) = &self.runtime {
+            let receiver = state.events.subscribe();
+            let history = Arc::clone(&state.history);
+            runtime.spawn(async move { super::history::watch_events(history, receiver).await });
+
+            let receiver = state.events.subscribe();
+            let watched = state.clone();
+            runtime.spawn(async move { super::changes::watch_events(watched, receiver).await });
+
+            let receiver = state.events.subscribe();
+            let estimator = Arc::clone(&state.estimator);
+            let sender = state.events.clone();
+            runtime.spawn(async move {
+                super::estimate::watch_events(estimator, receiver, sender).await
+            });
+        }
+
         let api_router = self
             .api_router
+            .route("/capabilities", get(super::capabilities::capabilities))
+            .route("/readiness", get(super::readiness::readiness))
+            .route("/changes", get(super::changes::changes))
+            .route("/estimate", get(super::estimate::estimate))
+            .route("/backends", get(super::backends::backends))
+            .route(
+                "/commit",
+                super::no_timeout(get(super::commit::commit_steps).post(super::commit::commit)),
+            )
+            .route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                super::backends::backends_middleware,
+            ))
             .route_layer(middleware::from_extractor_with_state::<TokenClaims, _>(
                 state.clone(),
             ))
             .route("/ping", get(super::http::ping))
+            .route("/metrics", get(super::metrics::metrics))
             .route("/auth", post(login).get(session).delete(logout));
 
         tracing::info!("Serving static files from {}", self.public_dir.display());
         let serve = ServeDir::new(self.public_dir).precompressed_gzip();
 
-        Router::new()
+        let router = Router::new()
             .nest_service("/", serve)
             .route("/login", get(login_from_query))
             .route("/po.js", get(super::http::po))
             .nest("/api", api_router)
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                super::timeout::timeout_middleware,
+            ))
             .layer(
                 TraceLayer::new_for_http()
                     .on_request(|request: &Request<Body>, _span: &Span| {
@@ -128,7 +342,28 @@ impl MainServiceBuilder {
                         },
                     ),
             )
-            .layer(CompressionLayer::new().br(true))
-            .with_state(state)
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                super::read_only::read_only_middleware,
+            ))
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                super::body_limit::body_limit_middleware,
+            ))
+            .layer(DefaultBodyLimit::disable())
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                super::metrics::metrics_middleware,
+            ))
+            .layer(middleware::from_fn(super::language::language_middleware))
+            .layer(middleware::from_fn(
+                super::request_id::request_id_middleware,
+            ))
+            .layer(CompressionLayer::new().br(true));
+        let router = match cors {
+            Some(cors) => router.layer(cors),
+            None => router,
+        };
+        router.with_state(state)
     }
 }
@@ -0,0 +1,69 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the state snapshots sent when a client subscribes to the events.
+//!
+//! Each module can register a [SnapshotProvider] in the
+//! [MainServiceBuilder](super::MainServiceBuilder). When a client connects to the websocket or to
+//! the SSE stream passing the `snapshot` query parameter (e.g., `snapshot=storage,network`), it
+//! first receives an `StateSnapshot` event for each requested module and then the incremental
+//! changes. As the client subscribes before the snapshots are built, no change is lost.
+
+use super::{state::ServiceState, Event};
+use crate::error::Error;
+use async_trait::async_trait;
+use std::{collections::BTreeMap, sync::Arc};
+
+/// Value of the `snapshot` query parameter to request the snapshots of all the modules.
+const ALL_MODULES: &str = "all";
+
+/// Reports the current state of a module.
+#[async_trait]
+pub trait SnapshotProvider: Send + Sync {
+    async fn snapshot(&self) -> Result<serde_json::Value, Error>;
+}
+
+/// Registered snapshot providers, indexed by module name.
+pub type SnapshotProviders = Arc<BTreeMap<String, Box<dyn SnapshotProvider>>>;
+
+/// Returns the snapshot events for the requested modules.
+///
+/// Unknown modules are ignored, and the ones whose state cannot be read are skipped.
+///
+/// * `state`: service state.
+/// * `modules`: comma-separated list of modules or "all".
+pub async fn snapshot_events(state: &ServiceState, modules: &str) -> Vec<Event> {
+    let modules: Vec<&str> = modules.split(',').map(str::trim).collect();
+    let mut events = vec![];
+    for (name, provider) in state.snapshots.iter() {
+        if !modules.iter().any(|m| *m == name || *m == ALL_MODULES) {
+            continue;
+        }
+
+        match provider.snapshot().await {
+            Ok(snapshot) => events.push(Event::StateSnapshot {
+                module: name.clone(),
+                state: snapshot,
+            }),
+            Err(error) => tracing::warn!("Could not build the {} snapshot: {}", name, error),
+        }
+    }
+    events
+}
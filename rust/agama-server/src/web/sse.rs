@@ -0,0 +1,142 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Implements the Server-Sent Events (SSE) stream.
+//!
+//! It is an alternative to the websocket for clients (or proxies) which cannot use websockets.
+//! It sends the same events, including the `retry` field so the clients know how long to wait
+//! before reconnecting. The sequence number of each event is used as its `id`, so the clients
+//! which reconnect get the events they missed (see the `Last-Event-ID` header and the `since`
+//! query parameter).
+//!
+//! The number of concurrent streams is limited (see `ServiceConfig::max_event_streams`). When the
+//! limit is reached, new clients get a `503 Service Unavailable` response.
+
+use super::{
+    history::{SequencedEvent, SequencedReceiver},
+    metrics::{StreamGuard, StreamKind},
+    snapshot::snapshot_events,
+    state::ServiceState,
+    ws::{encode_event, EventsFilter, WsQuery},
+    Event,
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event as SseEvent, KeepAlive},
+        Sse,
+    },
+};
+use futures_util::stream::{self, Stream, StreamExt};
+use std::{convert::Infallible, time::Duration};
+use tokio::sync::broadcast::error::RecvError;
+
+/// Default time clients should wait before reconnecting to the stream.
+pub const DEFAULT_SSE_RETRY: Duration = Duration::from_secs(3);
+
+/// Header the browsers send when reconnecting, containing the last received event id.
+const LAST_EVENT_ID: &str = "last-event-id";
+
+pub async fn sse_handler(
+    State(state): State<ServiceState>,
+    Query(query): Query<WsQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<SseEvent, Infallible>>>, StatusCode> {
+    let Some(guard) = state
+        .metrics
+        .open_stream(StreamKind::Sse, state.config.max_event_streams)
+    else {
+        tracing::warn!("Too many event streams, rejecting the SSE connection");
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    };
+
+    let since = query.since.or_else(|| {
+        headers
+            .get(LAST_EVENT_ID)
+            .and_then(|id| id.to_str().ok())
+            .and_then(|id| id.parse().ok())
+    });
+    let filter = EventsFilter::new(query.events);
+    let (missed, rx) = state.history.subscribe(since);
+    let snapshots = match query.snapshot {
+        Some(modules) => snapshot_events(&state, &modules).await,
+        None => vec![],
+    };
+    let stream = events_stream(rx, snapshots, missed, filter, state.sse_retry, guard);
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Returns the stream of SSE events.
+///
+/// The first one only contains the retry interval and a comment, so the intermediaries flush
+/// the headers as soon as the client connects. Then, it sends the requested snapshots and the
+/// missed events.
+///
+/// * `rx`: channel to receive the events from.
+/// * `snapshots`: snapshot events to send before the changes.
+/// * `missed`: events the client missed since it was disconnected.
+/// * `filter`: events the client is interested in.
+/// * `retry`: time the client should wait before reconnecting.
+/// * `guard`: accounts for the stream until it is dropped.
+fn events_stream(
+    rx: SequencedReceiver,
+    snapshots: Vec<Event>,
+    missed: Vec<SequencedEvent>,
+    filter: EventsFilter,
+    retry: Duration,
+    guard: StreamGuard,
+) -> impl Stream<Item = Result<SseEvent, Infallible>> {
+    let connected = SseEvent::default().retry(retry).comment("connected");
+    let snapshots = snapshots
+        .into_iter()
+        .filter_map(|s| serde_json::to_string(&s).ok())
+        .map(|json| SseEvent::default().data(json));
+    let missed: Vec<_> = missed
+        .iter()
+        .filter_map(|e| sse_event(e, &filter))
+        .collect();
+    let events = stream::unfold((rx, filter, guard), |(mut rx, filter, guard)| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    if let Some(sse) = sse_event(&event, &filter) {
+                        return Some((sse, (rx, filter, guard)));
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("SSE client lagged, {} events skipped", skipped);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    stream::once(async move { connected })
+        .chain(stream::iter(snapshots))
+        .chain(stream::iter(missed))
+        .chain(events)
+        .map(Ok)
+}
+
+/// Returns the SSE event for the given event if it passes the filter.
+fn sse_event(event: &SequencedEvent, filter: &EventsFilter) -> Option<SseEvent> {
+    let json = encode_event(event, filter)?;
+    Some(SseEvent::default().id(event.seq.to_string()).data(json))
+}
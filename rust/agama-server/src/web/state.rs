@@ -20,15 +20,32 @@
 
 //! Implements the web service state.
 
-use super::{config::ServiceConfig, EventsSender};
-use std::path::PathBuf;
+use super::{
+    backends::Backends, capabilities::CapabilitiesProviders, changes::Baseline, commit::Commits,
+    config::ServiceConfig, estimate::InstallEstimator, history::EventHistory, metrics::Metrics,
+    readiness::ReadinessProviders, snapshot::SnapshotProviders, EventsSender,
+};
+use std::{path::PathBuf, sync::Arc, time::Duration};
 
 /// Web service state.
 ///
-/// It holds the service configuration, the current D-Bus connection and a channel to send events.
+/// It holds the service configuration, the current D-Bus connection, a channel to send events, the
+/// history of the events sent to the clients, the service metrics, the modules capabilities,
+/// readiness, snapshot and commit providers, the D-Bus backends, the baseline to report the
+/// configuration changes, the installation time estimator and the SSE retry interval.
 #[derive(Clone)]
 pub struct ServiceState {
     pub config: ServiceConfig,
     pub events: EventsSender,
+    pub history: Arc<EventHistory>,
     pub public_dir: PathBuf,
+    pub metrics: Arc<Metrics>,
+    pub capabilities: CapabilitiesProviders,
+    pub readiness: ReadinessProviders,
+    pub snapshots: SnapshotProviders,
+    pub backends: Backends,
+    pub commits: Arc<Commits>,
+    pub baseline: Baseline,
+    pub estimator: Arc<InstallEstimator>,
+    pub sse_retry: Duration,
 }
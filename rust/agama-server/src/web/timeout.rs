@@ -0,0 +1,207 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+//! Limits the time to answer a request.
+//!
+//! The requests are aborted with a `504 Gateway Timeout` status when the handler does not answer
+//! within `request_timeout` seconds (30 by default). The long-running routes (e.g., probing,
+//! installing or collecting the logs) declare a generous timeout or no timeout at all by wrapping
+//! their handlers with [generous_timeout] or [no_timeout].
+//!
+//! The timeout of any path can be adjusted through the `route_timeouts` setting, which maps the
+//! paths to the number of seconds (`0` disables the timeout). Only the time until the response
+//! starts is limited, so the event streams are not affected.
+
+use super::state::ServiceState;
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::MethodRouter,
+    Json,
+};
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time::Instant;
+
+/// Timeout (in seconds) for the endpoints which might take a while (e.g., refreshing the
+/// repositories when selecting a product).
+const GENEROUS_TIMEOUT: u64 = 300;
+
+/// Timeout declared by the matched route, if any.
+///
+/// The timeout middleware runs before the routing, so it adds this extension to the request and
+/// the route fills it when it is reached. `Some(None)` means that the route has no timeout.
+#[derive(Clone, Default)]
+struct RouteTimeout(Arc<Mutex<Option<Option<u64>>>>);
+
+impl RouteTimeout {
+    fn get(&self) -> Option<Option<u64>> {
+        *self.0.lock().unwrap()
+    }
+
+    fn set(&self, seconds: Option<u64>) {
+        *self.0.lock().unwrap() = Some(seconds);
+    }
+}
+
+/// Disables the timeout for the given route.
+pub fn no_timeout<S>(route: MethodRouter<S>) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    with_timeout(route, None)
+}
+
+/// Uses a generous timeout for the given route.
+pub fn generous_timeout<S>(route: MethodRouter<S>) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    with_timeout(route, Some(GENEROUS_TIMEOUT))
+}
+
+fn with_timeout<S>(route: MethodRouter<S>, seconds: Option<u64>) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    route.layer(middleware::from_fn(
+        move |request: Request, next: Next| async move {
+            if let Some(timeout) = request.extensions().get::<RouteTimeout>() {
+                timeout.set(seconds);
+            }
+            next.run(request).await
+        },
+    ))
+}
+
+/// Returns the timeout configured for the given path through the `route_timeouts` setting.
+///
+/// `None` means that the path is not configured and `Some(None)` that it has no timeout.
+///
+/// * `path`: path of the request.
+/// * `route_timeouts`: timeouts configured for specific paths, in seconds.
+fn configured_timeout(
+    path: &str,
+    route_timeouts: &HashMap<String, u64>,
+) -> Option<Option<Duration>> {
+    route_timeouts
+        .get(path.trim_end_matches('/'))
+        .map(|seconds| Some(*seconds).filter(|s| *s > 0).map(Duration::from_secs))
+}
+
+/// Returns the timeout to apply once the route is known.
+///
+/// * `request_timeout`: timeout for the regular requests.
+/// * `route`: timeout declared by the route, if any (see [RouteTimeout]).
+fn route_timeout(request_timeout: Duration, route: Option<Option<u64>>) -> Option<Duration> {
+    match route {
+        None => Some(request_timeout),
+        Some(None) => None,
+        Some(Some(seconds)) => Some(request_timeout.max(Duration::from_secs(seconds))),
+    }
+}
+
+/// Middleware which answers with a 504 when the request takes longer than the timeout.
+pub async fn timeout_middleware(
+    State(state): State<ServiceState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if let Some(configured) = configured_timeout(request.uri().path(), &state.config.route_timeouts)
+    {
+        let Some(timeout) = configured else {
+            return next.run(request).await;
+        };
+        return match tokio::time::timeout(timeout, next.run(request)).await {
+            Ok(response) => response,
+            Err(_) => timeout_response(timeout),
+        };
+    }
+
+    let declared = RouteTimeout::default();
+    request.extensions_mut().insert(declared.clone());
+    let start = Instant::now();
+    let mut timeout = Duration::from_secs(state.config.request_timeout);
+    let response = next.run(request);
+    tokio::pin!(response);
+
+    loop {
+        tokio::select! {
+            response = &mut response => return response,
+            _ = tokio::time::sleep_until(start + timeout) => {
+                match route_timeout(timeout, declared.get()) {
+                    None => return response.await,
+                    Some(extended) if extended > timeout => timeout = extended,
+                    Some(_) => return timeout_response(timeout),
+                }
+            }
+        }
+    }
+}
+
+fn timeout_response(timeout: Duration) -> Response {
+    let body = json!({
+        "error": format!("The request did not finish within {} seconds", timeout.as_secs())
+    });
+    (StatusCode::GATEWAY_TIMEOUT, Json(body)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_timeout() {
+        let mut routes = HashMap::new();
+        assert_eq!(configured_timeout("/api/storage/probe", &routes), None);
+
+        routes.insert("/api/storage/probe".to_string(), 600);
+        routes.insert("/api/software/patterns".to_string(), 0);
+        assert_eq!(
+            configured_timeout("/api/storage/probe/", &routes),
+            Some(Some(Duration::from_secs(600)))
+        );
+        assert_eq!(
+            configured_timeout("/api/software/patterns", &routes),
+            Some(None)
+        );
+    }
+
+    #[test]
+    fn test_route_timeout() {
+        let default = Duration::from_secs(10);
+        assert_eq!(route_timeout(default, None), Some(default));
+        assert_eq!(route_timeout(default, Some(None)), None);
+        assert_eq!(
+            route_timeout(default, Some(Some(GENEROUS_TIMEOUT))),
+            Some(Duration::from_secs(GENEROUS_TIMEOUT))
+        );
+        assert_eq!(
+            route_timeout(Duration::from_secs(600), Some(Some(GENEROUS_TIMEOUT))),
+            Some(Duration::from_secs(600))
+        );
+    }
+}
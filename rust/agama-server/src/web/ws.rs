@@ -19,28 +19,234 @@
 // find current contact information at www.suse.com.
 
 //! Implements the websocket handling.
+//!
+//! Each message contains an event, including its sequence number in the `seq` field (see the
+//! `history` module). The snapshots do not have a sequence number.
 
-use super::{state::ServiceState, EventsSender};
+use super::{
+    cors::CorsConfig,
+    history::{SequencedEvent, SequencedReceiver},
+    metrics::StreamKind,
+    snapshot::snapshot_events,
+    state::ServiceState,
+    Event,
+};
 use axum::{
     extract::{
         ws::{Message, WebSocket},
-        State, WebSocketUpgrade,
+        Query, State, WebSocketUpgrade,
     },
+    http::{header, HeaderMap, StatusCode},
     response::IntoResponse,
 };
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+
+/// Query parameters accepted by the websocket endpoint.
+#[derive(Deserialize)]
+pub struct WsQuery {
+    /// Comma-separated list of event types to receive (e.g., "Progress,IssuesChanged"). If it
+    /// is not given, all the events are sent.
+    pub events: Option<String>,
+    /// Comma-separated list of modules (or "all") whose current state is sent before the
+    /// changes (see the `snapshot` module).
+    pub snapshot: Option<String>,
+    /// Sequence number of the last event the client received. The kept events after it are sent
+    /// before the new ones.
+    pub since: Option<u64>,
+}
+
+/// Set of event types a client is interested in.
+///
+/// The events are serialized with the `type` tag as the first field, so the filter compares the
+/// beginning of the JSON representation instead of parsing it again.
+pub struct EventsFilter(Option<Vec<String>>);
+
+impl EventsFilter {
+    pub fn new(events: Option<String>) -> Self {
+        let prefixes = events.map(|e| {
+            e.split(',')
+                .map(|t| t.trim())
+                .filter(|t| !t.is_empty())
+                .map(|t| format!(r#"{{"type":"{}""#, t))
+                .collect()
+        });
+        Self(prefixes)
+    }
+
+    /// Whether the serialized event should be sent to the client.
+    fn matches(&self, json: &str) -> bool {
+        let Some(prefixes) = &self.0 else {
+            return true;
+        };
+        prefixes.iter().any(|p| json.starts_with(p))
+    }
+}
+
+/// Event as it is sent to the clients.
+#[derive(Serialize)]
+struct EventMessage<'a> {
+    #[serde(flatten)]
+    event: &'a Event,
+    seq: u64,
+}
 
 pub async fn ws_handler(
     State(state): State<ServiceState>,
+    Query(query): Query<WsQuery>,
+    headers: HeaderMap,
     ws: WebSocketUpgrade,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, state.events))
+    // Browsers do not apply CORS to WebSockets, so check the origin here.
+    if !is_allowed_origin(&state.config.cors, &headers) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let Some(guard) = state
+        .metrics
+        .open_stream(StreamKind::WebSocket, state.config.max_event_streams)
+    else {
+        tracing::warn!("Too many event streams, rejecting the WebSocket connection");
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    };
+
+    let filter = EventsFilter::new(query.events);
+    ws.on_upgrade(move |socket| async move {
+        let (missed, rx) = state.history.subscribe(query.since);
+        let snapshots = match query.snapshot {
+            Some(modules) => snapshot_events(&state, &modules).await,
+            None => vec![],
+        };
+        handle_socket(socket, rx, snapshots, missed, filter).await;
+        drop(guard);
+    })
+}
+
+/// Whether the request comes from the same origin or from one allowed by the CORS policy.
+///
+/// If no CORS policy is configured, any origin is allowed. Otherwise, setups behind a reverse
+/// proxy (where the `Host` header does not match the origin) would be rejected.
+fn is_allowed_origin(cors: &CorsConfig, headers: &HeaderMap) -> bool {
+    if !cors.is_enabled() {
+        return true;
+    }
+
+    let Some(origin) = headers.get(header::ORIGIN).and_then(|o| o.to_str().ok()) else {
+        return true;
+    };
+
+    let same_origin = headers
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .zip(origin.split_once("://"))
+        .is_some_and(|(host, (_, origin_host))| host == origin_host);
+    same_origin || cors.allows_origin(origin)
 }
 
-async fn handle_socket(mut socket: WebSocket, events: EventsSender) {
-    let mut rx = events.subscribe();
-    while let Ok(msg) = rx.recv().await {
-        if let Ok(json) = serde_json::to_string(&msg) {
-            _ = socket.send(Message::Text(json)).await;
+async fn handle_socket(
+    mut socket: WebSocket,
+    mut rx: SequencedReceiver,
+    snapshots: Vec<Event>,
+    missed: Vec<SequencedEvent>,
+    filter: EventsFilter,
+) {
+    // the snapshots were explicitly requested, so they are not filtered
+    for snapshot in snapshots {
+        let Ok(json) = serde_json::to_string(&snapshot) else {
+            continue;
+        };
+        if socket.send(Message::Text(json)).await.is_err() {
+            return;
+        }
+    }
+
+    for event in missed {
+        if let Some(json) = encode_event(&event, &filter) {
+            if socket.send(Message::Text(json)).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    loop {
+        let event = match rx.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!("WebSocket client lagged, {} events skipped", skipped);
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+        if let Some(json) = encode_event(&event, &filter) {
+            if socket.send(Message::Text(json)).await.is_err() {
+                break;
+            }
         }
     }
 }
+
+/// Returns the JSON representation of the event if it passes the filter.
+pub fn encode_event(event: &SequencedEvent, filter: &EventsFilter) -> Option<String> {
+    let message = EventMessage {
+        event: &event.event,
+        seq: event.seq,
+    };
+    let json = serde_json::to_string(&message).ok()?;
+    filter.matches(&json).then_some(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_event, is_allowed_origin, EventsFilter};
+    use crate::web::{cors::CorsConfig, history::SequencedEvent, Event};
+    use axum::http::{header, HeaderMap, HeaderValue};
+
+    fn headers(host: &str, origin: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, HeaderValue::from_str(host).unwrap());
+        headers.insert(header::ORIGIN, HeaderValue::from_str(origin).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_allowed_origin() {
+        let proxied = headers("127.0.0.1:3000", "https://agama.example.net");
+
+        let cors = CorsConfig::default();
+        assert!(is_allowed_origin(&cors, &proxied));
+
+        let cors = CorsConfig {
+            allowed_origins: vec!["https://console.example.net".to_string()],
+            ..Default::default()
+        };
+        assert!(!is_allowed_origin(&cors, &proxied));
+        assert!(is_allowed_origin(
+            &cors,
+            &headers("agama.example.net", "https://agama.example.net")
+        ));
+        assert!(is_allowed_origin(
+            &cors,
+            &headers("127.0.0.1:3000", "https://console.example.net")
+        ));
+    }
+
+    #[test]
+    fn test_filter_events() {
+        let event = SequencedEvent {
+            seq: 7,
+            event: Event::DevicesDirty { dirty: true },
+        };
+
+        let filter = EventsFilter::new(None);
+        assert!(encode_event(&event, &filter).is_some());
+
+        let filter = EventsFilter::new(Some("Progress, DevicesDirty".to_string()));
+        assert_eq!(
+            encode_event(&event, &filter).unwrap(),
+            r#"{"type":"DevicesDirty","dirty":true,"seq":7}"#
+        );
+
+        let filter = EventsFilter::new(Some("Progress,Devices".to_string()));
+        assert!(encode_event(&event, &filter).is_none());
+    }
+}
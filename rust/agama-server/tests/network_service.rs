@@ -22,11 +22,11 @@ pub mod common;
 
 use agama_lib::error::ServiceError;
 use agama_lib::network::settings::{BondSettings, NetworkConnection};
-use agama_lib::network::types::{DeviceType, SSID};
+use agama_lib::network::types::{DeviceState, DeviceType, SSID};
 use agama_server::network::web::network_service;
 use agama_server::network::{
     self,
-    model::{self, AccessPoint, GeneralState, StateConfig},
+    model::{self, AccessPoint, ConnectivityState, GeneralState, StateConfig},
     Adapter, NetworkAdapterError, NetworkState,
 };
 
@@ -73,6 +73,40 @@ impl Adapter for NetworkTestAdapter {
     async fn write(&self, _network: &network::NetworkState) -> Result<(), NetworkAdapterError> {
         unimplemented!("Not used in tests");
     }
+
+    async fn connectivity(&self) -> Result<ConnectivityState, NetworkAdapterError> {
+        Ok(ConnectivityState::Limited)
+    }
+
+    async fn dns_configuration(&self) -> Result<Vec<model::DnsEntry>, NetworkAdapterError> {
+        Ok(vec![
+            model::DnsEntry {
+                nameservers: vec!["192.168.1.1".to_string()],
+                interface: Some("eth1".to_string()),
+                priority: 100,
+                ..Default::default()
+            },
+            model::DnsEntry {
+                nameservers: vec!["10.0.0.1".to_string()],
+                domains: vec!["internal.example.net".to_string()],
+                interface: Some("eth0".to_string()),
+                priority: -10,
+                ..Default::default()
+            },
+        ])
+    }
+
+    async fn create_checkpoint(&self, _timeout: u32) -> Result<String, NetworkAdapterError> {
+        Ok("/checkpoint/1".to_string())
+    }
+
+    async fn confirm_checkpoint(&self, _id: &str) -> Result<(), NetworkAdapterError> {
+        Ok(())
+    }
+
+    async fn rollback_checkpoint(&self, _id: &str) -> Result<(), NetworkAdapterError> {
+        Ok(())
+    }
 }
 
 #[test]
@@ -132,6 +166,48 @@ async fn test_network_connections() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+async fn test_network_connection_state() -> Result<(), Box<dyn Error>> {
+    let state = build_state().await;
+    let network_service = build_service(state.clone()).await?;
+
+    let request = Request::builder()
+        .uri("/connections/eth0/state")
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap();
+
+    let response = network_service.oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body_to_string(response.into_body()).await;
+    assert_eq!(body, r#"{"status":"up","autoconnect":true,"active":false}"#);
+    Ok(())
+}
+
+#[test]
+async fn test_network_connection_failed_state() -> Result<(), Box<dyn Error>> {
+    let mut state = build_state().await;
+    state.devices[0].connection = Some("eth0".to_string());
+    state.devices[0].state = DeviceState::Failed;
+    state.devices[0].state_reason = 17;
+    let network_service = build_service(state.clone()).await?;
+
+    let request = Request::builder()
+        .uri("/connections/eth0/state")
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap();
+
+    let response = network_service.oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body_to_string(response.into_body()).await;
+    assert_eq!(
+        body,
+        r#"{"status":"up","autoconnect":true,"active":false,"error":"The DHCP client failed"}"#
+    );
+    Ok(())
+}
+
 #[test]
 async fn test_network_devices() -> Result<(), Box<dyn Error>> {
     let state = build_state().await;
@@ -181,6 +257,117 @@ async fn test_network_wifis() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+async fn test_network_connectivity() -> Result<(), Box<dyn Error>> {
+    let state = build_state().await;
+    let network_service = build_service(state.clone()).await?;
+
+    let request = Request::builder()
+        .uri("/connectivity")
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap();
+
+    let response = network_service.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body_to_string(response.into_body()).await;
+    assert!(body.contains(r#""state":"limited""#));
+    assert!(body.contains(r#""name":"networkManager","success":false"#));
+    assert!(!body.contains(r#""name":"url""#));
+
+    let request = Request::builder()
+        .uri("/connectivity?url=http://example.net/&timeout=0")
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap();
+
+    let response = network_service.oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    Ok(())
+}
+
+#[test]
+async fn test_network_dns_configuration() -> Result<(), Box<dyn Error>> {
+    let mut state = build_state().await;
+    state.connections[0].interface = Some("eth0".to_string());
+    let network_service = build_service(state).await?;
+
+    let request = Request::builder()
+        .uri("/dns")
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap();
+
+    let response = network_service.oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body_to_string(response.into_body()).await;
+    assert!(body.contains(r#""interface":"eth0","connection":"eth0","priority":-10"#));
+    assert!(body.contains(r#""interface":"eth1","connection":null,"priority":100"#));
+    Ok(())
+}
+
+#[test]
+async fn test_network_checkpoint() -> Result<(), Box<dyn Error>> {
+    let state = build_state().await;
+    let network_service = build_service(state.clone()).await?;
+
+    // NetworkManager would never roll it back
+    let request = Request::builder()
+        .uri("/checkpoint")
+        .method(Method::POST)
+        .header("Content-Type", "application/json")
+        .body(Body::from(r#"{"timeout": 0}"#))
+        .unwrap();
+    let response = network_service.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let request = Request::builder()
+        .uri("/checkpoint")
+        .method(Method::POST)
+        .header("Content-Type", "application/json")
+        .body(Body::from(r#"{"timeout": 30}"#))
+        .unwrap();
+    let response = network_service.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body_to_string(response.into_body()).await;
+    assert_eq!(body, r#"{"timeout":30,"remaining":30}"#);
+
+    // only one checkpoint at a time
+    let request = Request::builder()
+        .uri("/checkpoint")
+        .method(Method::POST)
+        .header("Content-Type", "application/json")
+        .body(Body::from("{}"))
+        .unwrap();
+    let response = network_service.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let request = Request::builder()
+        .uri("/checkpoint/confirm")
+        .method(Method::POST)
+        .body(Body::empty())
+        .unwrap();
+    let response = network_service.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let request = Request::builder()
+        .uri("/checkpoint")
+        .body(Body::empty())
+        .unwrap();
+    let response = network_service.clone().oneshot(request).await?;
+    let body = body_to_string(response.into_body()).await;
+    assert_eq!(body, "null");
+
+    let request = Request::builder()
+        .uri("/checkpoint/rollback")
+        .method(Method::POST)
+        .body(Body::empty())
+        .unwrap();
+    let response = network_service.oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    Ok(())
+}
+
 #[test]
 async fn test_add_bond_connection() -> Result<(), Box<dyn Error>> {
     let state = build_state().await;
@@ -240,3 +427,106 @@ async fn test_add_bond_connection() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+async fn test_set_connections() -> Result<(), Box<dyn Error>> {
+    let state = build_state().await;
+    let network_service = build_service(state.clone()).await?;
+
+    let eth0 = NetworkConnection {
+        id: "eth0".to_string(),
+        method4: Some("auto".to_string()),
+        interface: Some("eth0".to_string()),
+        ..Default::default()
+    };
+    let eth1 = NetworkConnection {
+        id: "eth1".to_string(),
+        interface: Some("eth0".to_string()),
+        ..Default::default()
+    };
+
+    let request = Request::builder()
+        .uri("/connections")
+        .header("Content-Type", "application/json")
+        .method(Method::PUT)
+        .body(serde_json::to_string(&vec![&eth0, &eth1])?)
+        .unwrap();
+
+    let response = network_service.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = body_to_string(response.into_body()).await;
+    assert!(body.contains("same interface 'eth0'"));
+
+    let eth1 = NetworkConnection {
+        interface: Some("eth1".to_string()),
+        ..eth1
+    };
+    let request = Request::builder()
+        .uri("/connections")
+        .header("Content-Type", "application/json")
+        .method(Method::PUT)
+        .body(serde_json::to_string(&vec![&eth0, &eth1])?)
+        .unwrap();
+
+    let response = network_service.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body_to_string(response.into_body()).await;
+    assert!(body.contains(r#"{"id":"eth0","action":"updated"}"#));
+    assert!(body.contains(r#"{"id":"eth1","action":"added"}"#));
+
+    let request = Request::builder()
+        .uri("/connections")
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap();
+
+    let response = network_service.clone().oneshot(request).await?;
+    let body = body_to_string(response.into_body()).await;
+    assert!(body.contains(r#""id":"eth0""#));
+    assert!(body.contains(r#""id":"eth1""#));
+
+    Ok(())
+}
+
+#[test]
+async fn test_reset_network_config() -> Result<(), Box<dyn Error>> {
+    let state = build_state().await;
+    let network_service = build_service(state.clone()).await?;
+
+    let eth1 = NetworkConnection {
+        id: "eth1".to_string(),
+        ..Default::default()
+    };
+
+    let request = Request::builder()
+        .uri("/connections")
+        .header("Content-Type", "application/json")
+        .method(Method::POST)
+        .body(serde_json::to_string(&eth1)?)
+        .unwrap();
+
+    let response = network_service.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let request = Request::builder()
+        .uri("/reset")
+        .method(Method::POST)
+        .body(Body::empty())
+        .unwrap();
+
+    let response = network_service.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let request = Request::builder()
+        .uri("/connections")
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap();
+
+    let response = network_service.clone().oneshot(request).await?;
+    let body = body_to_string(response.into_body()).await;
+    assert!(body.contains(r#""id":"eth0""#));
+    assert!(!body.contains(r#""id":"eth1""#));
+
+    Ok(())
+}
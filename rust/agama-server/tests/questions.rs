@@ -0,0 +1,154 @@
+// Copyright (c) [2024] SUSE LLC
+//
+// All Rights Reserved.
+//
+// This program is free software; you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by the Free
+// Software Foundation; either version 2 of the License, or (at your option)
+// any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+// more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, contact SUSE LLC.
+//
+// To contact SUSE LLC about this file by physical or electronic mail, you may
+// find current contact information at www.suse.com.
+
+pub mod common;
+
+use agama_lib::proxies::{GenericQuestionProxy, Questions1Proxy};
+use agama_server::questions::export_dbus_objects;
+use common::{DBusServer, Started};
+use std::{collections::HashMap, error::Error};
+use tokio::test;
+
+const CLASS: &str = "storage.confirm";
+
+async fn start_service() -> Result<(DBusServer<Started>, Questions1Proxy<'static>), Box<dyn Error>>
+{
+    let mut server = DBusServer::new().start().await?;
+    export_dbus_objects(&server.connection()).await?;
+    server.request_name().await?;
+    let questions = Questions1Proxy::builder(&server.connection())
+        .cache_properties(zbus::CacheProperties::No)
+        .build()
+        .await?;
+    Ok((server, questions))
+}
+
+/// Asks a question of the given class and returns the answer it got right after creating it.
+async fn ask(questions: &Questions1Proxy<'_>, class: &str) -> Result<String, Box<dyn Error>> {
+    let path = questions
+        .new_question(class, "Continue?", &["yes", "no"], "no", HashMap::new())
+        .await?;
+    let question = GenericQuestionProxy::builder(questions.inner().connection())
+        .path(path.clone())?
+        .cache_properties(zbus::CacheProperties::No)
+        .build()
+        .await?;
+    let answer = question.answer().await?;
+    questions.delete(&path).await?;
+    Ok(answer)
+}
+
+#[test]
+async fn test_remembered_answers() -> Result<(), Box<dyn Error>> {
+    let (_server, questions) = start_service().await?;
+    assert_eq!(ask(&questions, CLASS).await?, "");
+
+    questions.remember_answer(CLASS, "yes").await?;
+    assert_eq!(
+        questions.remembered_answers().await?,
+        HashMap::from([(CLASS.to_string(), "yes".to_string())])
+    );
+    assert_eq!(ask(&questions, CLASS).await?, "yes");
+    assert_eq!(ask(&questions, "storage.other").await?, "");
+
+    // the remembered answer must be one of the options
+    questions.remember_answer(CLASS, "always").await?;
+    assert_eq!(ask(&questions, CLASS).await?, "");
+
+    questions.forget_answers().await?;
+    assert!(questions.remembered_answers().await?.is_empty());
+    assert_eq!(ask(&questions, CLASS).await?, "");
+    Ok(())
+}
+
+#[test]
+async fn test_answer_files_precedence() -> Result<(), Box<dyn Error>> {
+    let (_server, questions) = start_service().await?;
+    let file = tempfile::NamedTempFile::new()?;
+    std::fs::write(
+        file.path(),
+        format!(
+            r#"{{"answers": [{{ "class": "{}", "answer": "no" }}]}}"#,
+            CLASS
+        ),
+    )?;
+
+    questions.remember_answer(CLASS, "yes").await?;
+    questions
+        .add_answer_file(&file.path().to_string_lossy())
+        .await?;
+    assert_eq!(ask(&questions, CLASS).await?, "no");
+    Ok(())
+}
+
+#[test]
+async fn test_history() -> Result<(), Box<dyn Error>> {
+    let (_server, questions) = start_service().await?;
+    questions.remember_answer(CLASS, "yes").await?;
+    ask(&questions, CLASS).await?;
+
+    let path = questions
+        .new_question(
+            "storage.other",
+            "Format?",
+            &["yes", "no"],
+            "no",
+            HashMap::new(),
+        )
+        .await?;
+    let question = GenericQuestionProxy::builder(questions.inner().connection())
+        .path(path.clone())?
+        .build()
+        .await?;
+    question.set_answer("no").await?;
+    questions.delete(&path).await?;
+
+    // unanswered questions are not recorded
+    ask(&questions, "storage.unknown").await?;
+
+    let history = questions.history().await?;
+    assert_eq!(history.len(), 2);
+    let (_, class, _, answer, auto_answered, timestamp) = &history[0];
+    assert_eq!((class.as_str(), answer.as_str()), (CLASS, "yes"));
+    assert!(auto_answered);
+    assert!(*timestamp > 0);
+    let (_, class, text, answer, auto_answered, _) = &history[1];
+    assert_eq!(
+        (class.as_str(), text.as_str(), answer.as_str()),
+        ("storage.other", "Format?", "no")
+    );
+    assert!(!auto_answered);
+    Ok(())
+}
+
+#[test]
+async fn test_history_is_bounded() -> Result<(), Box<dyn Error>> {
+    let (_server, questions) = start_service().await?;
+    questions.remember_answer(CLASS, "yes").await?;
+    for _ in 0..105 {
+        ask(&questions, CLASS).await?;
+    }
+
+    let history = questions.history().await?;
+    assert_eq!(history.len(), 100);
+    // the oldest questions are dropped
+    assert_eq!(history[0].0, 5);
+    Ok(())
+}
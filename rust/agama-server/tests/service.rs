@@ -21,15 +21,21 @@
 pub mod common;
 
 use agama_lib::auth::AuthToken;
-use agama_server::web::{MainServiceBuilder, ServiceConfig};
+use agama_server::web::{
+    cors::CorsConfig, CapabilitiesProvider, CommitProvider, CommittedModules, MainServiceBuilder,
+    ModuleCapabilities, ReadinessIssue, ReadinessProvider, ServiceConfig, SnapshotProvider,
+};
+use async_trait::async_trait;
 use axum::{
     body::Body,
     http::{Method, Request, StatusCode},
     response::Response,
-    routing::get,
+    routing::{get, post},
+    Router,
 };
 use common::body_to_string;
-use std::{error::Error, path::PathBuf};
+use http_body_util::BodyExt;
+use std::{error::Error, path::PathBuf, time::Duration};
 use tokio::{sync::broadcast::channel, test};
 use tower::ServiceExt;
 
@@ -42,7 +48,7 @@ async fn test_ping() -> Result<(), Box<dyn Error>> {
     let config = ServiceConfig::default();
     let (tx, _) = channel(16);
     let web_service = MainServiceBuilder::new(tx, public_dir())
-        .add_service("/protected", get(protected))
+        .add_service("/protected", Router::new().route("/", get(protected)))
         .with_config(config)
         .build();
 
@@ -59,6 +65,92 @@ async fn test_ping() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+async fn test_cors() -> Result<(), Box<dyn Error>> {
+    let config = ServiceConfig {
+        cors: CorsConfig {
+            allowed_origins: vec!["https://example.net".to_string()],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (tx, _) = channel(16);
+    let web_service = MainServiceBuilder::new(tx, public_dir())
+        .with_config(config)
+        .build();
+
+    let request = Request::builder()
+        .method(Method::OPTIONS)
+        .uri("/api/ping")
+        .header("Origin", "https://example.net")
+        .header("Access-Control-Request-Method", "GET")
+        .body(Body::empty())
+        .unwrap();
+    let response = web_service.clone().oneshot(request).await.unwrap();
+    assert_eq!(
+        response.headers()["access-control-allow-origin"],
+        "https://example.net"
+    );
+
+    let request = Request::builder()
+        .method(Method::OPTIONS)
+        .uri("/api/ping")
+        .header("Origin", "https://example.org")
+        .header("Access-Control-Request-Method", "GET")
+        .body(Body::empty())
+        .unwrap();
+    let response = web_service.oneshot(request).await.unwrap();
+    assert!(!response
+        .headers()
+        .contains_key("access-control-allow-origin"));
+    Ok(())
+}
+
+#[test]
+async fn test_metrics() -> Result<(), Box<dyn Error>> {
+    let token = AuthToken::generate("nots3cr3t")?;
+    let config = ServiceConfig {
+        jwt_secret: "nots3cr3t".to_string(),
+        ..Default::default()
+    };
+    let (tx, _) = channel(16);
+    let web_service = MainServiceBuilder::new(tx, public_dir())
+        .add_service("/nodes", Router::new().route("/:id/login", post(protected)))
+        .with_config(config)
+        .build();
+
+    for uri in ["/api/ping", "/api/unknown/12"] {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        web_service.clone().oneshot(request).await.unwrap();
+    }
+
+    let request = Request::builder()
+        .uri("/api/nodes/12/login")
+        .method(Method::POST)
+        .header("Authorization", format!("Bearer {}", token.as_str()))
+        .body(Body::empty())
+        .unwrap();
+    web_service.clone().oneshot(request).await.unwrap();
+
+    let request = Request::builder()
+        .uri("/api/metrics")
+        .body(Body::empty())
+        .unwrap();
+    let response = web_service.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = body_to_string(response.into_body()).await;
+    assert!(body.contains(
+        "agama_http_requests_total{method=\"GET\",route=\"/api/ping\",status=\"200\"} 1"
+    ));
+    assert!(body.contains(
+        "agama_http_requests_total{method=\"POST\",route=\"/api/nodes/:id/login\",status=\"200\"} 1"
+    ));
+    assert!(body.contains("route=\"unmatched\""));
+    assert!(!body.contains("/12"));
+    Ok(())
+}
+
 async fn protected() -> String {
     "OK".to_string()
 }
@@ -66,10 +158,11 @@ async fn protected() -> String {
 async fn access_protected_route(token: &str, jwt_secret: &str) -> Response {
     let config = ServiceConfig {
         jwt_secret: jwt_secret.to_string(),
+        ..Default::default()
     };
     let (tx, _) = channel(16);
     let web_service = MainServiceBuilder::new(tx, public_dir())
-        .add_service("/protected", get(protected))
+        .add_service("/protected", Router::new().route("/", get(protected)))
         .with_config(config)
         .build();
 
@@ -84,6 +177,30 @@ async fn access_protected_route(token: &str, jwt_secret: &str) -> Response {
 }
 
 // TODO: The following test should belong to `auth.rs`
+#[test]
+async fn test_request_id() -> Result<(), Box<dyn Error>> {
+    let (tx, _) = channel(16);
+    let web_service = MainServiceBuilder::new(tx, public_dir()).build();
+
+    let request = Request::builder()
+        .uri("/api/ping")
+        .header("X-Request-Id", "my-request.1")
+        .body(Body::empty())
+        .unwrap();
+    let response = web_service.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.headers()["x-request-id"], "my-request.1");
+
+    let request = Request::builder()
+        .uri("/api/ping")
+        .header("X-Request-Id", "invalid id")
+        .body(Body::empty())
+        .unwrap();
+    let response = web_service.oneshot(request).await.unwrap();
+    let id = response.headers()["x-request-id"].to_str()?;
+    assert!(!id.is_empty() && id != "invalid id");
+    Ok(())
+}
+
 #[test]
 async fn test_access_protected_route() -> Result<(), Box<dyn Error>> {
     let token = AuthToken::generate("nots3cr3t")?;
@@ -103,3 +220,437 @@ async fn test_access_protected_route_failed() -> Result<(), Box<dyn Error>> {
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     Ok(())
 }
+
+#[test]
+async fn test_read_only() -> Result<(), Box<dyn Error>> {
+    let config = ServiceConfig {
+        read_only: true,
+        ..Default::default()
+    };
+    let (tx, _) = channel(16);
+    let web_service = MainServiceBuilder::new(tx, public_dir())
+        .add_service(
+            "/protected",
+            Router::new().route("/", get(protected).post(protected)),
+        )
+        .with_config(config)
+        .build();
+
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/api/protected")
+        .body(Body::empty())
+        .unwrap();
+    let response = web_service.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let request = Request::builder()
+        .uri("/api/ping")
+        .body(Body::empty())
+        .unwrap();
+    let response = web_service.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    Ok(())
+}
+
+struct TestCapabilities;
+
+#[async_trait]
+impl CapabilitiesProvider for TestCapabilities {
+    async fn capabilities(&self) -> ModuleCapabilities {
+        ModuleCapabilities::from([("zfcp".to_string(), true)])
+    }
+}
+
+#[test]
+async fn test_capabilities() -> Result<(), Box<dyn Error>> {
+    let token = AuthToken::generate("nots3cr3t")?;
+    let config = ServiceConfig {
+        jwt_secret: "nots3cr3t".to_string(),
+        ..Default::default()
+    };
+    let (tx, _) = channel(16);
+    let web_service = MainServiceBuilder::new(tx, public_dir())
+        .add_capabilities("storage", TestCapabilities)
+        .with_config(config)
+        .build();
+
+    let request = Request::builder()
+        .uri("/api/capabilities")
+        .header("Authorization", format!("Bearer {}", token.as_str()))
+        .body(Body::empty())
+        .unwrap();
+    let response = web_service.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = body_to_string(response.into_body()).await;
+    assert_eq!(
+        &body,
+        "{\"readOnly\":false,\"modules\":{\"storage\":{\"zfcp\":true}}}"
+    );
+    Ok(())
+}
+
+struct TestReadiness;
+
+#[async_trait]
+impl ReadinessProvider for TestReadiness {
+    async fn issues(&self) -> Result<Vec<ReadinessIssue>, agama_server::error::Error> {
+        Ok(vec![
+            ReadinessIssue::blocking("required", "Network is required"),
+            ReadinessIssue::warning("offline", "No network"),
+        ])
+    }
+}
+
+#[test]
+async fn test_readiness() -> Result<(), Box<dyn Error>> {
+    let token = AuthToken::generate("nots3cr3t")?;
+    let config = ServiceConfig {
+        jwt_secret: "nots3cr3t".to_string(),
+        ..Default::default()
+    };
+    let (tx, _) = channel(16);
+    let web_service = MainServiceBuilder::new(tx, public_dir())
+        .add_readiness("network", TestReadiness)
+        .with_config(config)
+        .build();
+
+    let request = Request::builder()
+        .uri("/api/readiness")
+        .header("Authorization", format!("Bearer {}", token.as_str()))
+        .body(Body::empty())
+        .unwrap();
+    let response = web_service.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = body_to_string(response.into_body()).await;
+    assert!(body.contains("\"ready\":false"));
+    assert!(body.contains("\"blocking\":[{\"module\":\"network\",\"code\":\"network.required\""));
+    assert!(body.contains("\"code\":\"network.offline\""));
+    Ok(())
+}
+
+struct TestCommit {
+    requires: &'static [&'static str],
+    fails: bool,
+}
+
+#[async_trait]
+impl CommitProvider for TestCommit {
+    fn requires(&self) -> &[&str] {
+        self.requires
+    }
+
+    async fn commit(&self) -> Result<(), agama_server::error::Error> {
+        if self.fails {
+            return Err(agama_server::error::Error::Anyhow("Disk full".to_string()));
+        }
+        Ok(())
+    }
+}
+
+fn commit_request(token: &AuthToken, modules: &str) -> Request<Body> {
+    Request::builder()
+        .uri("/api/commit")
+        .method(Method::POST)
+        .header("Authorization", format!("Bearer {}", token.as_str()))
+        .header("Content-Type", "application/json")
+        .body(Body::from(format!("{{\"modules\":{}}}", modules)))
+        .unwrap()
+}
+
+#[test]
+async fn test_commit() -> Result<(), Box<dyn Error>> {
+    let token = AuthToken::generate("nots3cr3t")?;
+    let config = ServiceConfig {
+        jwt_secret: "nots3cr3t".to_string(),
+        ..Default::default()
+    };
+    let (tx, _) = channel(16);
+    let committed = CommittedModules::default();
+    let web_service = MainServiceBuilder::new(tx, public_dir())
+        .add_commit(
+            "l10n",
+            TestCommit {
+                requires: &["storage"],
+                fails: false,
+            },
+        )
+        .add_commit(
+            "network",
+            TestCommit {
+                requires: &[],
+                fails: false,
+            },
+        )
+        .add_commit(
+            "storage",
+            TestCommit {
+                requires: &[],
+                fails: false,
+            },
+        )
+        .with_committed_modules(committed.clone())
+        .with_config(config)
+        .build();
+
+    let request = commit_request(&token, "[\"l10n\",\"printer\"]");
+    let response = web_service.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = body_to_string(response.into_body()).await;
+    assert!(body.contains("The storage module must be committed before l10n"));
+    assert!(body.contains("Unknown module 'printer'"));
+
+    let request = commit_request(&token, "[\"network\"]");
+    let response = web_service.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body_to_string(response.into_body()).await;
+    assert_eq!(body, "{\"committed\":[\"network\"],\"pending\":[]}");
+
+    let request = commit_request(&token, "[\"storage\",\"l10n\"]");
+    let response = web_service.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(committed.list(), vec!["network", "storage", "l10n"]);
+
+    let request = commit_request(&token, "[\"network\"]");
+    let response = web_service.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let request = Request::builder()
+        .uri("/api/commit")
+        .header("Authorization", format!("Bearer {}", token.as_str()))
+        .body(Body::empty())
+        .unwrap();
+    let response = web_service.oneshot(request).await.unwrap();
+    let body = body_to_string(response.into_body()).await;
+    assert!(body.contains("{\"module\":\"l10n\",\"requires\":[\"storage\"],\"committed\":true}"));
+    Ok(())
+}
+
+#[test]
+async fn test_commit_failure() -> Result<(), Box<dyn Error>> {
+    let token = AuthToken::generate("nots3cr3t")?;
+    let config = ServiceConfig {
+        jwt_secret: "nots3cr3t".to_string(),
+        ..Default::default()
+    };
+    let (tx, _) = channel(16);
+    let web_service = MainServiceBuilder::new(tx, public_dir())
+        .add_commit(
+            "network",
+            TestCommit {
+                requires: &[],
+                fails: false,
+            },
+        )
+        .add_commit(
+            "storage",
+            TestCommit {
+                requires: &[],
+                fails: true,
+            },
+        )
+        .with_config(config)
+        .build();
+
+    let request = commit_request(&token, "[\"storage\",\"network\"]");
+    let response = web_service.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = body_to_string(response.into_body()).await;
+    assert_eq!(
+        body,
+        "{\"committed\":[],\"failed\":{\"module\":\"storage\",\"error\":\"Generic error: Disk full\"},\"pending\":[\"network\"]}"
+    );
+    Ok(())
+}
+
+#[test]
+async fn test_sse_retry() -> Result<(), Box<dyn Error>> {
+    let token = AuthToken::generate("nots3cr3t")?;
+    let config = ServiceConfig {
+        jwt_secret: "nots3cr3t".to_string(),
+        ..Default::default()
+    };
+    let (tx, _) = channel(16);
+    let web_service = MainServiceBuilder::new(tx, public_dir())
+        .with_config(config)
+        .with_sse_retry(Duration::from_secs(10))
+        .build();
+
+    let request = Request::builder()
+        .uri("/api/events")
+        .header("Authorization", format!("Bearer {}", token.as_str()))
+        .body(Body::empty())
+        .unwrap();
+    let response = web_service.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "text/event-stream"
+    );
+
+    let frame = response.into_body().frame().await.unwrap()?;
+    let data = frame.into_data().unwrap();
+    assert_eq!(std::str::from_utf8(&data)?, "retry:10000\n: connected\n\n");
+    Ok(())
+}
+
+struct TestSnapshot;
+
+#[async_trait]
+impl SnapshotProvider for TestSnapshot {
+    async fn snapshot(&self) -> Result<serde_json::Value, agama_server::error::Error> {
+        Ok(serde_json::json!({ "dirty": false }))
+    }
+}
+
+#[test]
+async fn test_sse_snapshot() -> Result<(), Box<dyn Error>> {
+    let token = AuthToken::generate("nots3cr3t")?;
+    let config = ServiceConfig {
+        jwt_secret: "nots3cr3t".to_string(),
+        ..Default::default()
+    };
+    let (tx, _) = channel(16);
+    let web_service = MainServiceBuilder::new(tx, public_dir())
+        .add_snapshot("storage", TestSnapshot)
+        .add_snapshot("network", TestSnapshot)
+        .with_config(config)
+        .build();
+
+    let request = Request::builder()
+        .uri("/api/events?snapshot=storage,unknown")
+        .header("Authorization", format!("Bearer {}", token.as_str()))
+        .body(Body::empty())
+        .unwrap();
+    let response = web_service.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut body = response.into_body();
+    let connected = body.frame().await.unwrap()?.into_data().unwrap();
+    assert_eq!(
+        std::str::from_utf8(&connected)?,
+        "retry:3000\n: connected\n\n"
+    );
+    let snapshot = body.frame().await.unwrap()?.into_data().unwrap();
+    assert_eq!(
+        std::str::from_utf8(&snapshot)?,
+        "data: {\"type\":\"StateSnapshot\",\"module\":\"storage\",\"state\":{\"dirty\":false}}\n\n"
+    );
+    Ok(())
+}
+
+#[test]
+async fn test_sse_limit() -> Result<(), Box<dyn Error>> {
+    let token = AuthToken::generate("nots3cr3t")?;
+    let config = ServiceConfig {
+        jwt_secret: "nots3cr3t".to_string(),
+        max_event_streams: 1,
+        ..Default::default()
+    };
+    let (tx, _) = channel(16);
+    let web_service = MainServiceBuilder::new(tx, public_dir())
+        .with_config(config)
+        .build();
+
+    let request = || {
+        Request::builder()
+            .uri("/api/events")
+            .header("Authorization", format!("Bearer {}", token.as_str()))
+            .body(Body::empty())
+            .unwrap()
+    };
+    let first = web_service.clone().oneshot(request()).await.unwrap();
+    assert_eq!(first.status(), StatusCode::OK);
+
+    let second = web_service.clone().oneshot(request()).await.unwrap();
+    assert_eq!(second.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    drop(first);
+    let third = web_service.oneshot(request()).await.unwrap();
+    assert_eq!(third.status(), StatusCode::OK);
+    Ok(())
+}
+
+async fn echo(body: String) -> String {
+    body
+}
+
+#[test]
+async fn test_body_limit() -> Result<(), Box<dyn Error>> {
+    let token = AuthToken::generate("nots3cr3t")?;
+    let config = ServiceConfig {
+        jwt_secret: "nots3cr3t".to_string(),
+        max_body_size: 8,
+        ..Default::default()
+    };
+    let (tx, _) = channel(16);
+    let web_service = MainServiceBuilder::new(tx, public_dir())
+        .add_service("/echo", Router::new().route("/", post(echo)))
+        .with_config(config)
+        .build();
+
+    let request = |body: &'static str, length: Option<usize>| {
+        let mut builder = Request::builder()
+            .uri("/api/echo")
+            .method(Method::POST)
+            .header("Authorization", format!("Bearer {}", token.as_str()));
+        if let Some(length) = length {
+            builder = builder.header("Content-Length", length);
+        }
+        builder.body(Body::from(body)).unwrap()
+    };
+
+    let response = web_service.clone().oneshot(request("small", None)).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body_to_string(response.into_body()).await, "small");
+
+    let response = web_service
+        .clone()
+        .oneshot(request("too big for the limit", None))
+        .await?;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let response = web_service.oneshot(request("", Some(1024))).await?;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    Ok(())
+}
+
+async fn slow() -> String {
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    "done".to_string()
+}
+
+#[test]
+async fn test_request_timeout() -> Result<(), Box<dyn Error>> {
+    let token = AuthToken::generate("nots3cr3t")?;
+    let config = ServiceConfig {
+        jwt_secret: "nots3cr3t".to_string(),
+        request_timeout: 1,
+        route_timeouts: [("/api/patient".to_string(), 0)].into(),
+        ..Default::default()
+    };
+    let (tx, _) = channel(16);
+    let web_service = MainServiceBuilder::new(tx, public_dir())
+        .add_service("/slow", Router::new().route("/", get(slow)))
+        .add_service("/patient", Router::new().route("/", get(slow)))
+        .with_config(config)
+        .build();
+
+    let request = |uri: &str| {
+        Request::builder()
+            .uri(uri)
+            .header("Authorization", format!("Bearer {}", token.as_str()))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let response = web_service.clone().oneshot(request("/api/slow")).await?;
+    assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+
+    let response = web_service.oneshot(request("/api/patient")).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body_to_string(response.into_body()).await, "done");
+    Ok(())
+}
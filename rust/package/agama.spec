@@ -52,6 +52,8 @@ BuildRequires:  timezone
 # required for getting the languages information
 BuildRequires:  python-langtable-data
 Requires:       python-langtable-data
+# used for reading the SMART health of the drives
+Recommends:     smartmontools
 # dependency on the YaST part of Agama
 Requires:       agama-yast
 
@@ -50,8 +50,10 @@ module Agama
     # TODO: capture reported errors (e.g., via the Report.Error function).
     class Converter
       # @param profile_url [String] Profile URL
-      def initialize(profile_url)
+      # @param run_scripts [Boolean] Whether to run the pre-scripts defined in the profile
+      def initialize(profile_url, run_scripts: true)
         @profile_url = profile_url
+        @run_scripts = run_scripts
       end
 
       # Converts the profile into a set of files that Agama can process.
@@ -69,6 +71,9 @@ module Agama
 
       attr_reader :profile_url
 
+      # @return [Boolean] Whether to run the pre-scripts
+      attr_reader :run_scripts
+
       def copy_profile; end
 
       # @return [Hash] AutoYaST profile
@@ -87,6 +92,8 @@ module Agama
 
         loop do
           Yast::Profile.ReadXML(tmp_profile_path)
+          break unless run_scripts
+
           run_pre_scripts
           break unless File.exist?(Yast::AutoinstConfig.modified_profile)
 
@@ -118,7 +125,7 @@ module Agama
 
       # Sections which have a corresponding reader. The reader is expected to be
       # named in Pascal case and adding "Reader" as suffix (e.g., "L10nReader").
-      SECTIONS = ["l10n", "product", "root", "software", "storage", "user"].freeze
+      SECTIONS = ["l10n", "network", "product", "root", "software", "storage", "user"].freeze
 
       # Builds the Agama profile
       #
@@ -19,6 +19,7 @@
 # To contact SUSE LLC about this file by physical or electronic mail, you may
 # find current contact information at www.suse.com.
 
+require "agama/dbus/secrets"
 require "agama/dbus/types"
 
 module Agama
@@ -120,7 +121,7 @@ module Agama
 
         wrong_type_keys.map do |key|
           type = scheme[key]
-          value = self.value[key]
+          value = Secrets.secret?(key) ? Secrets::FILTERED : self.value[key]
 
           issues << "D-Bus property #{key} must be #{type}: #{value} (#{value.class})"
         end
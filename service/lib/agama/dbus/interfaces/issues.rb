@@ -54,6 +54,14 @@ module Agama
           end
         end
 
+        # Issues with the D-Bus format, including their kind
+        #
+        # @return [Array<Array(String, String, Integer, Integer, String)>] The same values as
+        #   {#dbus_issues}, followed by the kind of the issue (e.g., "missing_product").
+        def dbus_issues_with_kind
+          dbus_issues.zip(issues).map { |values, issue| values + [issue.kind.to_s] }
+        end
+
         # Emits the signal for properties changed
         def issues_properties_changed
           dbus_properties_changed(ISSUES_INTERFACE,
@@ -65,6 +73,8 @@ module Agama
             dbus_interface ISSUES_INTERFACE do
               # @see {#dbus_issues}
               dbus_reader :dbus_issues, "a(ssuu)", dbus_name: "All"
+              # @see {#dbus_issues_with_kind}
+              dbus_reader :dbus_issues_with_kind, "a(ssuus)", dbus_name: "AllWithKind"
             end
           end
         end
@@ -69,6 +69,34 @@ module Agama
         dbus_reader :current_installation_phase, "u"
         dbus_reader :iguana_backend, "b"
         dbus_reader :busy_services, "as"
+        dbus_reader :installation_failed, "b"
+        dbus_reader :machine_id_policy, "a{sv}"
+        dbus_method(:SetMachineIdPolicy, "in policy:s, in id:s, out result:u") do |policy, id|
+          assign_machine_id_policy(policy, id)
+        end
+        dbus_reader :self_update, "a{sv}"
+        dbus_method(:SkipSelfUpdate, "") { skip_self_update }
+      end
+
+      FIREWALL_INTERFACE = "org.opensuse.Agama.Manager1.Firewall"
+      private_constant :FIREWALL_INTERFACE
+
+      dbus_interface FIREWALL_INTERFACE do
+        dbus_method(:SetConfig, "in serialized_config:s, out result:u") do |serialized_config|
+          load_firewall_config(serialized_config)
+        end
+        dbus_method(:GetConfig, "out serialized_config:s") { backend.firewall.to_json }
+        dbus_reader :known_services, "as"
+      end
+
+      SCRIPTS_INTERFACE = "org.opensuse.Agama.Manager1.Scripts"
+      private_constant :SCRIPTS_INTERFACE
+
+      dbus_interface SCRIPTS_INTERFACE do
+        dbus_method(:SetConfig, "in serialized_config:s, out result:u") do |serialized_config|
+          backend.scripts.load_json(serialized_config) ? 0 : 1
+        end
+        dbus_method(:GetConfig, "out serialized_config:s") { backend.scripts.to_json }
       end
 
       # Runs the config phase
@@ -129,6 +157,78 @@ module Agama
         backend.iguana?
       end
 
+      # Whether the last installation failed
+      #
+      # @return [Boolean]
+      def installation_failed
+        backend.installation_failed?
+      end
+
+      # Machine-id policy for the installed system
+      #
+      # @return [Hash] "Policy" and "Id" (only for the "explicit" policy)
+      def machine_id_policy
+        machine_id = backend.machine_id
+        result = { "Policy" => machine_id.policy }
+        result["Id"] = machine_id.id if machine_id.policy == "explicit"
+        result
+      end
+
+      # Sets the machine-id policy
+      #
+      # @param policy [String] "generate", "keep" or "explicit"
+      # @param id [String] Machine-id for the "explicit" policy
+      # @return [Integer] 0 success; 1 error
+      def assign_machine_id_policy(policy, id)
+        return 1 unless backend.machine_id.assign(policy, id)
+
+        logger.info("Setting the machine-id policy: #{policy}")
+        dbus_properties_changed(MANAGER_INTERFACE, { "MachineIdPolicy" => machine_id_policy }, [])
+        0
+      end
+
+      # State of the installer self-update
+      #
+      # @return [Hash] "Available", "Applied", "Skipped" and, if known, "FromVersion" and
+      #   "ToVersion"
+      def self_update
+        self_update = backend.self_update
+        result = {
+          "Available" => self_update.available?,
+          "Applied"   => self_update.applied?,
+          "Skipped"   => self_update.skipped?
+        }
+        result["FromVersion"] = self_update.from_version if self_update.from_version
+        result["ToVersion"] = self_update.to_version if self_update.to_version
+        result
+      end
+
+      # Opts out of the installer self-update for the rest of the session
+      def skip_self_update
+        raise ::DBus::Error, "The self-update was already applied" unless backend.self_update.skip
+
+        dbus_properties_changed(MANAGER_INTERFACE, { "SelfUpdate" => self_update }, [])
+      end
+
+      # Sets the firewall settings
+      #
+      # @param serialized_config [String] Serialized firewall settings (JSON)
+      # @return [Integer] 0 success; 1 error
+      def load_firewall_config(serialized_config)
+        firewall = backend.firewall
+        return 1 unless firewall.load_json(serialized_config)
+
+        logger.info("Setting firewall config: #{firewall.settings}")
+        0
+      end
+
+      # Firewalld services which can be opened
+      #
+      # @return [Array<String>]
+      def known_services
+        backend.firewall.known_services
+      end
+
       # Name of the services that are currently busy
       #
       # @return [Array<String>]
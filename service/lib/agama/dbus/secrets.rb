@@ -0,0 +1,59 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2026] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+module Agama
+  module DBus
+    # Helpers to keep the secrets (e.g., encryption passphrases) out of the logs
+    module Secrets
+      # Keys holding secrets, both in D-Bus hashes and in JSON documents
+      KEYS = [
+        "EncryptionPassword", "VolumeEncryptionPasswords", "Password", "ReversePassword",
+        "password", "hashedPassword"
+      ].freeze
+
+      # Value replacing the secrets
+      FILTERED = "[FILTERED]"
+
+      # Returns a copy of a value with the secrets replaced, so it can be logged
+      #
+      # @param value [Object] D-Bus value or parsed JSON (usually, a hash)
+      # @return [Object]
+      def self.redact(value)
+        case value
+        when Hash
+          value.to_h { |k, v| [k, secret?(k) ? FILTERED : redact(v)] }
+        when Array
+          value.map { |v| redact(v) }
+        else
+          value
+        end
+      end
+
+      # Whether the given key holds a secret
+      #
+      # @param key [String, Symbol]
+      # @return [Boolean]
+      def self.secret?(key)
+        KEYS.include?(key.to_s)
+      end
+    end
+  end
+end
@@ -28,6 +28,7 @@ require "agama/dbus/interfaces/locale"
 require "agama/dbus/interfaces/progress"
 require "agama/dbus/interfaces/service_status"
 require "agama/dbus/with_service_status"
+require "agama/software/extra_repository"
 
 module Agama
   module DBus
@@ -109,14 +110,102 @@ module Agama
 
           dbus_method(:UsedDiskSpace, "out SpaceSize:s") { backend.used_disk_space }
 
+          dbus_method(:PackageChanges, "out Result:a(sss)") { [backend.package_changes] }
+
+          dbus_accessor :only_required, "b"
+
+          dbus_reader :architecture, "s"
+
+          dbus_accessor :multilib, "b"
+
+          dbus_accessor :services, "a(ss)"
+
+          dbus_accessor :default_target, "s"
+
+          dbus_reader :extra_repositories, "a(sssb)"
+          dbus_method(:SetExtraRepositories, "in repositories:a(sssb)") do |repositories|
+            self.extra_repositories = repositories
+          end
+
           dbus_signal(:ProbeFinished)
 
+          dbus_signal(:PackageProgress,
+            "current_package:s, installed:u, total:u, bytes_done:t, bytes_total:t")
+
           dbus_method(:Probe) { probe }
           dbus_method(:Propose) { propose }
           dbus_method(:Install) { install }
           dbus_method(:Finish) { finish }
         end
 
+        def only_required
+          backend.only_required
+        end
+
+        def only_required=(value)
+          busy_while { backend.only_required = value }
+          dbus_properties_changed(SOFTWARE_INTERFACE, { "OnlyRequired" => value }, [])
+        end
+
+        def architecture
+          backend.architecture
+        end
+
+        def multilib
+          backend.multilib
+        end
+
+        def multilib=(value)
+          busy_while { backend.multilib = value }
+          dbus_properties_changed(SOFTWARE_INTERFACE, { "Multilib" => value }, [])
+        end
+
+        def services
+          backend.systemd_units
+        end
+
+        def services=(value)
+          logger.info "Setting the systemd units: #{value.inspect}"
+          backend.systemd_units = value
+          dbus_properties_changed(SOFTWARE_INTERFACE, { "Services" => value }, [])
+        rescue ArgumentError => e
+          raise ::DBus::Error, e.message
+        end
+
+        def default_target
+          backend.default_target || ""
+        end
+
+        def default_target=(value)
+          logger.info "Setting the default target: #{value}"
+          backend.default_target = value
+          dbus_properties_changed(SOFTWARE_INTERFACE, { "DefaultTarget" => value }, [])
+        rescue ArgumentError => e
+          raise ::DBus::Error, e.message
+        end
+
+        # Additional repositories
+        #
+        # @return [Array<Array(String, String, String, Boolean)>] Alias, URL, name and whether the
+        #   repository is persistent
+        def extra_repositories
+          backend.extra_repositories.map { |r| [r.repo_alias, r.url, r.name, r.persistent] }
+        end
+
+        # Replaces the additional repositories
+        #
+        # @param value [Array<Array(String, String, String, Boolean)>]
+        def extra_repositories=(value)
+          logger.info "Setting the extra repositories: #{value.inspect}"
+          repositories = value.map { |r| Agama::Software::ExtraRepository.new(*r) }
+          busy_while { backend.extra_repositories = repositories }
+          dbus_properties_changed(
+            SOFTWARE_INTERFACE, { "ExtraRepositories" => extra_repositories }, []
+          )
+        rescue ArgumentError => e
+          raise ::DBus::Error, e.message
+        end
+
         def probe
           busy_while { backend.probe }
           self.ProbeFinished
@@ -164,6 +253,8 @@ module Agama
           end
 
           backend.on_issues_change { issues_properties_changed }
+
+          backend.on_package_progress { |*args| PackageProgress(*args) }
         end
 
         USER_SELECTED_PATTERN = 0
@@ -60,7 +60,11 @@ module Agama
               {
                 "description" => product.localized_description,
                 "icon"        => product.icon
-              }
+              }.tap do |data|
+                data["version"] = product.version if product.version
+                data["release_notes"] = product.release_notes unless product.release_notes.empty?
+                data["requirements"] = product.requirements unless product.requirements.empty?
+              end
             ]
           end
         end
@@ -150,6 +154,29 @@ module Agama
           end
         end
 
+        # Subscription of the registered product.
+        #
+        # @return [Hash<String, String>] Empty if the product is not registered yet.
+        def subscription
+          subscription = backend.registration.subscription
+          return {} unless subscription
+
+          { "Product" => subscription.product, "ExpiresAt" => subscription.expires_at }.compact
+        end
+
+        # Extensions and modules activated for the registered product.
+        #
+        # @return [Array<Hash<String, String>>]
+        def extensions
+          backend.registration.extensions.map do |extension|
+            {
+              "Id"        => extension.id,
+              "Version"   => extension.version,
+              "ExpiresAt" => extension.expires_at
+            }.compact
+          end
+        end
+
         # Tries to register with the given registration code.
         #
         # @note Software is not automatically probed after registering the product. The reason is
@@ -226,6 +253,10 @@ module Agama
 
           dbus_reader(:requirement, "u")
 
+          dbus_reader(:subscription, "a{sv}")
+
+          dbus_reader(:extensions, "aa{sv}")
+
           dbus_method(:Register, "in reg_code:s, in options:a{sv}, out result:(us)") do |*args|
             [register(args[0], email: args[1]["Email"])]
           end
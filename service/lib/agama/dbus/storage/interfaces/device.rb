@@ -32,6 +32,7 @@ module Agama
 end
 
 require "agama/dbus/storage/interfaces/device/block"
+require "agama/dbus/storage/interfaces/device/cache"
 require "agama/dbus/storage/interfaces/device/component"
 require "agama/dbus/storage/interfaces/device/device"
 require "agama/dbus/storage/interfaces/device/drive"
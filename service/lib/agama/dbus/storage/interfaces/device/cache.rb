@@ -0,0 +1,92 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+
+require "dbus"
+
+module Agama
+  module DBus
+    module Storage
+      module Interfaces
+        module Device
+          # Interface for cached devices.
+          #
+          # @note This interface is intended to be included by {Agama::DBus::Storage::Device} if
+          #   needed.
+          module Cache
+            # Whether this interface should be implemented for the given device.
+            #
+            # @note Bcache devices with a cache set implement this interface. The LVM caches are
+            #   attached at the end of the installation, so they are not in the devicegraph.
+            #
+            # @param storage_device [Y2Storage::Device]
+            # @return [Boolean]
+            def self.apply?(storage_device)
+              storage_device.is?(:bcache) && !storage_device.bcache_cset.nil?
+            end
+
+            CACHE_INTERFACE = "org.opensuse.Agama.Storage1.Cache"
+            private_constant :CACHE_INTERFACE
+
+            # Caching technology.
+            #
+            # @return [String] "bcache"
+            def cache_method
+              "bcache"
+            end
+
+            # When the writes reach the backing device.
+            #
+            # @return [String] e.g., "writethrough" or "writeback"
+            def cache_mode
+              storage_device.cache_mode.to_sym.to_s
+            end
+
+            # Device caching the backing device.
+            #
+            # @return [::DBus::ObjectPath]
+            def cache_device
+              tree.path_for(storage_device.bcache_cset.blk_devices.first)
+            end
+
+            # Device whose data is cached.
+            #
+            # @return [::DBus::ObjectPath]
+            def cache_backing_device
+              tree.path_for(storage_device.backing_device)
+            end
+
+            def self.included(base)
+              base.class_eval do
+                dbus_interface CACHE_INTERFACE do
+                  dbus_reader :cache_method, "s", dbus_name: "Method"
+                  dbus_reader :cache_mode, "s", dbus_name: "Mode"
+                  dbus_reader :cache_device, "o", dbus_name: "CacheDevice"
+                  dbus_reader :cache_backing_device, "o", dbus_name: "BackingDevice"
+                end
+              end
+            end
+          end
+        end
+      end
+    end
+  end
+end
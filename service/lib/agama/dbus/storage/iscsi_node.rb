@@ -22,6 +22,7 @@
 require "dbus"
 require "agama/dbus/base_object"
 require "agama/dbus/storage/with_iscsi_auth"
+require "agama/dbus/storage/with_iscsi_session"
 
 module Agama
   module DBus
@@ -29,6 +30,7 @@ module Agama
       # Class representing an iSCSI node
       class ISCSINode < BaseObject
         include WithISCSIAuth
+        include WithISCSISession
 
         # @return [Agama::Storage::ISCSI::Manager]
         attr_reader :iscsi_manager
@@ -91,6 +93,22 @@ module Agama
           iscsi_node.connected?
         end
 
+        # Details of the session
+        #
+        # @return [Hash<String, Object>] Empty if the node is not connected
+        def session
+          return {} unless iscsi_node.session
+
+          iscsi_session_to_dbus(iscsi_node.session)
+        end
+
+        # Details of the last failed login
+        #
+        # @return [String] Empty if the last login succeeded
+        def login_error
+          @login_error || ""
+        end
+
         # Startup status of the connection
         #
         # @return [String] Empty if the node is not connected
@@ -129,22 +147,47 @@ module Agama
         #   @option ReverseUsername [String] Username for authentication by initiator
         #   @option ReversePassword [String] Password for authentication by inititator
         #   @option Startup [String] Valid values are "onboot", "manual", "automatic"
+        #   @option Interface [String] iSCSI interface to bind the session to
         #
-        # @return [Integer] 0 on success, 1 on failure if the given startup value is not valid, and
-        #   2 on failure because any other reason.
+        # @return [Integer] 0 on success, 1 on failure if the given startup value is not valid, 3
+        #   if the target rejected the credentials, 4 if the target was not found, 5 if the portal
+        #   could not be reached, 6 if there is already a session and 2 on failure because any
+        #   other reason. The details of the failure are available in the LoginError property.
         def login(options = {})
           auth = iscsi_auth(options)
           startup = options["Startup"]
 
           if startup && !valid_startup?(startup)
             logger.info("iSCSI login error: startup value #{startup} is not valid")
+            self.login_error = "Invalid startup value: #{startup}"
             return 1
           end
 
-          success = iscsi_manager.login(iscsi_node, auth, startup: startup)
-          return 0 if success
+          result = iscsi_manager.login(
+            iscsi_node, auth, startup: startup, interface: options["Interface"]
+          )
+          self.login_error = iscsi_manager.login_error.to_s
+          return 0 if result == :success
 
           logger.info("iSCSI login error: fail to login iSCSI node #{path}")
+          LOGIN_RESULTS.fetch(result, 2)
+        end
+
+        # Checks whether the target accepts the given credentials, without keeping the session
+        #
+        # @param options [Hash<String, String>] Authentication options, see {#login}
+        # @return [Integer] 0 on success and 2 on failure
+        # @raise [::DBus::Error] if the session cannot be closed after logging in
+        def test_auth(options = {})
+          self.login_error = ""
+          result = iscsi_manager.test_auth(iscsi_node, iscsi_auth(options))
+          if result == :logout_failed
+            raise ::DBus::Error, "Could not logout from the iSCSI node #{path}"
+          end
+
+          return 0 if result == :success
+
+          logger.info("iSCSI login error: the iSCSI node #{path} rejected the credentials")
           2 # Error code
         end
 
@@ -156,6 +199,17 @@ module Agama
           success ? 0 : 1
         end
 
+        # D-Bus codes of the login results of the iSCSI manager
+        LOGIN_RESULTS = {
+          success:               0,
+          failed:                2,
+          authentication_failed: 3,
+          target_not_found:      4,
+          network_unreachable:   5,
+          already_logged_in:     6
+        }.freeze
+        private_constant :LOGIN_RESULTS
+
         ISCSI_NODE_INTERFACE = "org.opensuse.Agama.Storage1.ISCSI.Node"
         private_constant :ISCSI_NODE_INTERFACE
 
@@ -166,13 +220,26 @@ module Agama
           dbus_reader(:interface, "s")
           dbus_reader(:ibft, "b", dbus_name: "IBFT")
           dbus_reader(:connected, "b")
+          dbus_reader(:session, "a{sv}")
+          dbus_reader(:login_error, "s")
           dbus_accessor(:startup, "s")
           dbus_method(:Login, "in options:a{sv}, out result:u") { |o| login(o) }
           dbus_method(:Logout, "out result:u") { logout }
+          dbus_method(:TestAuth, "in options:a{sv}, out result:u") { |o| test_auth(o) }
         end
 
       private
 
+        # Sets the details of the last failed login
+        #
+        # @note A properties changed signal is emitted.
+        #
+        # @param value [String]
+        def login_error=(value)
+          @login_error = value
+          dbus_properties_changed(ISCSI_NODE_INTERFACE, { "LoginError" => value }, [])
+        end
+
         # Whether the given value is a valid startup status
         #
         # @param value [String]
@@ -28,12 +28,14 @@ require "agama/dbus/interfaces/issues"
 require "agama/dbus/interfaces/locale"
 require "agama/dbus/interfaces/progress"
 require "agama/dbus/interfaces/service_status"
+require "agama/dbus/secrets"
 require "agama/dbus/storage/devices_tree"
 require "agama/dbus/storage/iscsi_nodes_tree"
 require "agama/dbus/storage/proposal"
 require "agama/dbus/storage/proposal_settings_conversion"
 require "agama/dbus/storage/volume_conversion"
 require "agama/dbus/storage/with_iscsi_auth"
+require "agama/dbus/storage/with_iscsi_session"
 require "agama/dbus/with_service_status"
 require "agama/storage/encryption_settings"
 require "agama/storage/proposal_settings"
@@ -47,6 +49,7 @@ module Agama
       # D-Bus object to manage storage installation
       class Manager < BaseObject
         include WithISCSIAuth
+        include WithISCSISession
         include WithServiceStatus
         include ::DBus::ObjectManager
         include DBus::Interfaces::Issues
@@ -72,6 +75,7 @@ module Agama
           register_progress_callbacks
           register_service_status_callbacks
           register_iscsi_callbacks
+          register_nvmeof_callbacks
           register_software_callbacks
 
           add_s390_interfaces if Yast::Arch.s390
@@ -104,9 +108,8 @@ module Agama
         # @param serialized_config [String] Serialized storage config.
         # @return [Integer] 0 success; 1 error
         def apply_config(serialized_config)
-          logger.info("Setting storage config from D-Bus: #{serialized_config}")
-
           config_json = JSON.parse(serialized_config, symbolize_names: true)
+          logger.info("Setting storage config from D-Bus: #{DBus::Secrets.redact(config_json)}")
           proposal.calculate_from_json(config_json)
           proposal.success? ? 0 : 1
         end
@@ -118,8 +121,31 @@ module Agama
           JSON.pretty_generate(proposal.config_json)
         end
 
+        # Unlocks an existing encrypted device
+        #
+        # @note The passphrase must not be logged.
+        #
+        # @param device [String] Name of the encrypted device (e.g., "/dev/sda2")
+        # @param passphrase [String]
+        # @return [Integer] 0 success; 1 device not found; 2 wrong passphrase; 3 other error
+        def unlock_device(device, passphrase)
+          busy_while { backend.unlock_device(device, passphrase) }
+        end
+
+        # Prepares the storage devices to install the system
+        #
+        # Only one commit can be in progress. The service handles other calls while it waits for
+        # the D-Bus calls made during the commit (e.g., to the software service), so a concurrent
+        # commit is refused with a busy error.
         def install
-          busy_while { backend.install }
+          raise ::DBus.error("org.opensuse.Agama1.Error.Busy") if @installing
+
+          begin
+            @installing = true
+            busy_while { backend.install }
+          ensure
+            @installing = false
+          end
         end
 
         def finish
@@ -141,6 +167,9 @@ module Agama
           dbus_method(:GetConfig, "out serialized_config:s") { recover_config }
           dbus_method(:Install) { install }
           dbus_method(:Finish) { finish }
+          dbus_method(:UnlockDevice, "in device:s, in passphrase:s, out result:u") do |d, p|
+            unlock_device(d, p)
+          end
           dbus_reader(:deprecated_system, "b")
         end
 
@@ -188,7 +217,9 @@ module Agama
         # @param settings_dbus [Hash]
         # @return [Integer] 0 success; 1 error
         def calculate_guided_proposal(settings_dbus)
-          logger.info("Calculating guided storage proposal from D-Bus: #{settings_dbus}")
+          logger.info(
+            "Calculating guided storage proposal from D-Bus: #{DBus::Secrets.redact(settings_dbus)}"
+          )
 
           settings = ProposalSettingsConversion.from_dbus(settings_dbus,
             config: config, logger: logger)
@@ -278,6 +309,13 @@ module Agama
           backend.iscsi.initiator.ibft_name?
         end
 
+        # Whether iSCSI is supported (i.e., the initiator tools are available)
+        #
+        # @return [Boolean]
+        def iscsi_supported
+          backend.iscsi.supported?
+        end
+
         # Performs an iSCSI discovery
         #
         # @param address [String] IP address of the iSCSI server
@@ -287,13 +325,54 @@ module Agama
         #   @option Password [String] Password for authentication by target
         #   @option ReverseUsername [String] Username for authentication by initiator
         #   @option ReversePassword [String] Username for authentication by inititator
+        #   @option Interface [String] iSCSI interface to bind the discovery to
         #
         # @return [Integer] 0 on success, 1 on failure
         def iscsi_discover(address, port, options = {})
-          success = backend.iscsi.discover_send_targets(address, port, iscsi_auth(options))
+          success = backend.iscsi.discover_send_targets(
+            address, port, iscsi_auth(options), interface: options["Interface"]
+          )
           success ? 0 : 1
         end
 
+        # Names of the iSCSI interfaces
+        #
+        # @return [Array<String>]
+        def iscsi_interfaces
+          backend.iscsi.interfaces
+        end
+
+        # Details of the iSCSI interfaces
+        #
+        # Empty strings are used for the missing values.
+        #
+        # @return [Array<Hash<String, String>>]
+        def iscsi_interfaces_details
+          backend.iscsi.interfaces_details.map do |interface|
+            {
+              "Name"      => interface.name,
+              "Transport" => interface.transport.to_s,
+              "HWAddress" => interface.hwaddress.to_s,
+              "IPAddress" => interface.ipaddress.to_s
+            }
+          end
+        end
+
+        # Active iSCSI sessions
+        #
+        # @return [Array<Hash<String, Object>>]
+        def iscsi_sessions
+          backend.iscsi.sessions.map do |session|
+            {
+              "Target"    => session.target.to_s,
+              "Address"   => session.address.to_s,
+              "Port"      => ::DBus::Data::UInt32.new(session.port),
+              "Interface" => session.interface,
+              "IBFT"      => session.ibft?
+            }.merge(iscsi_session_to_dbus(session))
+          end
+        end
+
         # Deletes an iSCSI node from the database
         #
         # @param path [::DBus::ObjectPath]
@@ -318,6 +397,14 @@ module Agama
 
           dbus_reader :ibft, "b", dbus_name: "IBFT"
 
+          dbus_reader :iscsi_supported, "b", dbus_name: "Supported"
+
+          dbus_reader :iscsi_interfaces, "as", dbus_name: "Interfaces"
+
+          dbus_reader :iscsi_interfaces_details, "aa{sv}", dbus_name: "InterfacesDetails"
+
+          dbus_reader :iscsi_sessions, "aa{sv}", dbus_name: "Sessions"
+
           dbus_method :Discover,
             "in address:s, in port:u, in options:a{sv}, out result:u" do |address, port, options|
             busy_while { iscsi_discover(address, port, options) }
@@ -326,6 +413,82 @@ module Agama
           dbus_method(:Delete, "in node:o, out result:u") { |n| iscsi_delete(n) }
         end
 
+        NVMEOF_MANAGER_INTERFACE = "org.opensuse.Agama.Storage1.NVMeoF.Manager"
+        private_constant :NVMEOF_MANAGER_INTERFACE
+
+        # Asks a discovery controller for the subsystems it exports
+        #
+        # @param options [Hash<String, String>] Address of the discovery controller (Transport,
+        #   TrAddr and TrSvcId)
+        # @return [Array(Integer, String, Array<Hash>)] Result code, details of the failure and
+        #   discovered targets
+        def nvmeof_discover(options)
+          code, message, targets = backend.nvmeof.discover(nvmeof_target(options))
+          [code, message, targets.map { |t| nvmeof_target_to_dbus(t) }]
+        end
+
+        # Connected NVMe-oF controllers
+        #
+        # @return [Array<Hash>]
+        def nvmeof_controllers
+          backend.nvmeof.controllers.map do |controller|
+            nvmeof_target_to_dbus(controller.target)
+              .merge("Name" => controller.name, "State" => controller.state)
+          end
+        end
+
+        dbus_interface NVMEOF_MANAGER_INTERFACE do
+          dbus_method :Discover,
+            "in options:a{sv}, out result:u, out message:s, out targets:aa{sv}" do |options|
+            busy_while { nvmeof_discover(options) }
+          end
+          dbus_method :Connect, "in options:a{sv}, out result:u, out message:s" do |options|
+            busy_while { backend.nvmeof.connect(nvmeof_target(options)) }
+          end
+          dbus_method :Disconnect, "in name:s, out result:u, out message:s" do |name|
+            busy_while { backend.nvmeof.disconnect(name) }
+          end
+          dbus_reader :nvmeof_controllers, "aa{sv}", dbus_name: "Controllers"
+        end
+
+        BOOTLOADER_INTERFACE = "org.opensuse.Agama.Storage1.Bootloader"
+        private_constant :BOOTLOADER_INTERFACE
+
+        # Sets the bootloader settings
+        #
+        # @param serialized_config [String] Serialized bootloader settings (JSON)
+        # @return [Integer] 0 success; 1 error
+        def load_bootloader_config(serialized_config)
+          bootloader = backend.bootloader
+          return 1 unless bootloader.load_json(serialized_config)
+
+          logger.info("Setting bootloader config: #{DBus::Secrets.redact(bootloader.settings)}")
+          0
+        end
+
+        # Bootloaders supported by the product
+        #
+        # @return [Array<String>]
+        def supported_bootloaders
+          backend.bootloader.supported
+        end
+
+        # Whether the system boots using EFI
+        #
+        # @return [Boolean]
+        def efi
+          backend.bootloader.efi?
+        end
+
+        dbus_interface BOOTLOADER_INTERFACE do
+          dbus_method(:SetConfig, "in serialized_config:s, out result:u") do |serialized_config|
+            load_bootloader_config(serialized_config)
+          end
+          dbus_method(:GetConfig, "out serialized_config:s") { backend.bootloader.to_json }
+          dbus_reader :supported_bootloaders, "as"
+          dbus_reader :efi, "b", dbus_name: "EFI"
+        end
+
         def locale=(locale)
           backend.locale = locale
         end
@@ -380,10 +543,39 @@ module Agama
           end
 
           backend.iscsi.on_sessions_change do
+            dbus_properties_changed(ISCSI_INITIATOR_INTERFACE,
+              { "Sessions" => iscsi_sessions }, [])
             deprecate_system
           end
         end
 
+        def register_nvmeof_callbacks
+          backend.nvmeof.on_connections_change do
+            dbus_properties_changed(NVMEOF_MANAGER_INTERFACE,
+              { "Controllers" => nvmeof_controllers }, [])
+            deprecate_system
+          end
+        end
+
+        # @param options [Hash<String, String>] Target from a D-Bus call
+        # @return [Agama::Storage::NVMeoF::Target]
+        def nvmeof_target(options)
+          Agama::Storage::NVMeoF::Target.new(
+            options["Transport"], options["TrAddr"], options["TrSvcId"], options["SubNQN"]
+          )
+        end
+
+        # @param target [Agama::Storage::NVMeoF::Target]
+        # @return [Hash]
+        def nvmeof_target_to_dbus(target)
+          {
+            "Transport" => target.transport,
+            "TrAddr"    => target.traddr.to_s,
+            "TrSvcId"   => target.trsvcid.to_s,
+            "SubNQN"    => target.subnqn.to_s
+          }
+        end
+
         def register_software_callbacks
           backend.software.on_probe_finished do
             # A PropertiesChanged signal is emitted (see ::DBus::Object.dbus_reader_attr_accessor).
@@ -20,13 +20,20 @@
 # find current contact information at www.suse.com.
 
 require "agama/dbus/hash_validator"
+require "agama/dbus/secrets"
 require "agama/dbus/storage/volume_conversion"
 require "agama/dbus/types"
+require "agama/storage/cache_device"
 require "agama/storage/device_settings"
+require "agama/storage/esp_settings"
+require "agama/storage/explicit_partition"
+require "agama/storage/partition_alignment"
 require "agama/storage/proposal_settings"
 require "agama/storage/proposal_settings_reader"
 require "agama/storage/space_settings"
 require "y2storage/encryption_method"
+require "y2storage/filesystems/mount_by_type"
+require "y2storage/partition_tables/type"
 require "y2storage/pbkd_function"
 
 module Agama
@@ -48,12 +55,14 @@ module Agama
           #
           # @return [Agama::Storage::ProposalSettings]
           def convert
-            logger.info("D-Bus settings: #{dbus_settings}")
+            logger.info("D-Bus settings: #{Agama::DBus::Secrets.redact(dbus_settings)}")
 
             dbus_settings_issues.each { |i| logger.warn(i) }
 
             Agama::Storage::ProposalSettingsReader.new(config).read.tap do |target|
               valid_dbus_properties.each { |p| conversion(target, p) }
+              key_file = target.encryption.key_file
+              target.encryption.key_file = nil if key_file&.path.to_s.empty?
             end
           end
 
@@ -92,11 +101,46 @@ module Agama
               type:       String,
               conversion: :boot_device_conversion
             },
+            {
+              name:       "Alignment",
+              type:       String,
+              conversion: :alignment_conversion
+            },
+            {
+              name:       "AlignmentGrain",
+              type:       Integer,
+              conversion: :alignment_grain_conversion
+            },
+            {
+              name:       "EspMode",
+              type:       String,
+              conversion: :esp_mode_conversion
+            },
+            {
+              name:       "EspDevice",
+              type:       String,
+              conversion: :esp_device_conversion
+            },
+            {
+              name:       "EspSize",
+              type:       Integer,
+              conversion: :esp_size_conversion
+            },
+            {
+              name:       "EspMountPath",
+              type:       String,
+              conversion: :esp_mount_path_conversion
+            },
             {
               name:       "EncryptionPassword",
               type:       String,
               conversion: :encryption_password_conversion
             },
+            {
+              name:       "VolumeEncryptionPasswords",
+              type:       Types::Hash.new(key: String, value: String),
+              conversion: :volume_encryption_passwords_conversion
+            },
             {
               name:       "EncryptionMethod",
               type:       String,
@@ -107,6 +151,26 @@ module Agama
               type:       String,
               conversion: :encryption_pbkd_function_conversion
             },
+            {
+              name:       "EncryptionKeyFile",
+              type:       String,
+              conversion: :encryption_key_file_conversion
+            },
+            {
+              name:       "EncryptionKeyDevice",
+              type:       String,
+              conversion: :encryption_key_device_conversion
+            },
+            {
+              name:       "EncryptionKeyCrypttab",
+              type:       String,
+              conversion: :encryption_key_crypttab_conversion
+            },
+            {
+              name:       "EncryptionKeySource",
+              type:       String,
+              conversion: :encryption_key_source_conversion
+            },
             {
               name:       "SpacePolicy",
               type:       String,
@@ -121,6 +185,41 @@ module Agama
               name:       "Volumes",
               type:       Types::Array.new(Types::Hash.new(key: String)),
               conversion: :volumes_conversion
+            },
+            {
+              name:       "IncludeRemovable",
+              type:       Types::BOOL,
+              conversion: :include_removable_conversion
+            },
+            {
+              name:       "Partitions",
+              type:       Types::Array.new(Types::Hash.new(key: String)),
+              conversion: :partitions_conversion
+            },
+            {
+              name:       "PartitionTables",
+              type:       Types::Hash.new(key: String, value: String),
+              conversion: :partition_tables_conversion
+            },
+            {
+              name:       "MountBy",
+              type:       String,
+              conversion: :mount_by_conversion
+            },
+            {
+              name:       "ReadOnlyRoot",
+              type:       Types::BOOL,
+              conversion: :read_only_root_conversion
+            },
+            {
+              name:       "RootOverlay",
+              type:       String,
+              conversion: :root_overlay_conversion
+            },
+            {
+              name:       "Caches",
+              type:       Types::Array.new(Types::Hash.new(key: String, value: String)),
+              conversion: :caches_conversion
             }
           ].freeze
 
@@ -206,12 +305,60 @@ module Agama
             target.boot.device = value.empty? ? nil : value
           end
 
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [String] "optimal", "cylinder" or "explicit"
+          def alignment_conversion(target, value)
+            mode = value.to_sym
+            return unless Agama::Storage::PartitionAlignment.modes.include?(mode)
+
+            target.alignment.mode = mode
+          end
+
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [Integer] Bytes
+          def alignment_grain_conversion(target, value)
+            target.alignment.explicit_grain = value if value.positive?
+          end
+
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [String] "auto", "reuse" or "create"
+          def esp_mode_conversion(target, value)
+            mode = value.to_sym
+            return unless Agama::Storage::EspSettings.modes.include?(mode)
+
+            target.boot.esp.mode = mode
+          end
+
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [String]
+          def esp_device_conversion(target, value)
+            target.boot.esp.device = value.empty? ? nil : value
+          end
+
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [Integer] Bytes
+          def esp_size_conversion(target, value)
+            target.boot.esp.size = value
+          end
+
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [String]
+          def esp_mount_path_conversion(target, value)
+            target.boot.esp.mount_path = value unless value.empty?
+          end
+
           # @param target [Agama::Storage::ProposalSettings]
           # @param value [String]
           def encryption_password_conversion(target, value)
             target.encryption.password = value.empty? ? nil : value
           end
 
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [Hash<String, String>] Passwords indexed by mount path
+          def volume_encryption_passwords_conversion(target, value)
+            target.encryption.volume_passwords = value.reject { |_, v| v.empty? }
+          end
+
           # @param target [Agama::Storage::ProposalSettings]
           # @param value [String]
           def encryption_method_conversion(target, value)
@@ -230,6 +377,38 @@ module Agama
             target.encryption.pbkd_function = function
           end
 
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [String] Empty to not use a key file
+          def encryption_key_file_conversion(target, value)
+            key_file(target).path = value
+          end
+
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [String]
+          def encryption_key_device_conversion(target, value)
+            key_file(target).device = value.empty? ? nil : value
+          end
+
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [String]
+          def encryption_key_crypttab_conversion(target, value)
+            key_file(target).crypttab = value unless value.empty?
+          end
+
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [String]
+          def encryption_key_source_conversion(target, value)
+            key_file(target).source = value.empty? ? nil : value
+          end
+
+          # Key file of the settings, creating it if needed
+          #
+          # @param target [Agama::Storage::ProposalSettings]
+          # @return [Agama::Storage::EncryptionKeyFile]
+          def key_file(target)
+            target.encryption.key_file ||= Agama::Storage::EncryptionKeyFile.new("")
+          end
+
           # @param target [Agama::Storage::ProposalSettings]
           # @param value [String]
           def space_policy_conversion(target, value)
@@ -245,6 +424,9 @@ module Agama
             target.space.actions = value.each_with_object({}) do |v, result|
               result[v["Device"]] = v["Action"].to_sym
             end
+            target.space.resize_sizes = value.each_with_object({}) do |v, result|
+              result[v["Device"]] = v["Size"] if v["Action"] == "resize" && v["Size"]
+            end
           end
 
           # @param target [Agama::Storage::ProposalSettings]
@@ -261,6 +443,70 @@ module Agama
             target.volumes = volumes + missing_volumes(required_volumes, volumes)
           end
 
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [Boolean]
+          def include_removable_conversion(target, value)
+            target.include_removable = value
+          end
+
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [Array<Hash>]
+          def partitions_conversion(target, value)
+            target.partitions = value.map do |dbus_partition|
+              Agama::Storage::ExplicitPartition.new(
+                dbus_partition["Device"], dbus_partition["Start"], dbus_partition["End"]
+              ).tap do |partition|
+                partition.fs_type = dbus_partition["FsType"]
+                partition.mount_path = dbus_partition["MountPath"]
+              end
+            end
+          end
+
+          PTABLE_TYPES = [
+            Y2Storage::PartitionTables::Type::GPT,
+            Y2Storage::PartitionTables::Type::MSDOS
+          ].freeze
+          private_constant :PTABLE_TYPES
+
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [Hash<String, String>] "gpt" or "msdos", indexed by disk name
+          def partition_tables_conversion(target, value)
+            target.partition_tables = value.each_with_object({}) do |(disk, type), tables|
+              ptable_type = PTABLE_TYPES.find { |t| t.to_s == type }
+              tables[disk] = ptable_type if ptable_type
+            end
+          end
+
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [String] "uuid", "label", "path", "device" or "id"
+          def mount_by_conversion(target, value)
+            target.mount_by = Y2Storage::Filesystems::MountByType.find(value.to_sym)
+          end
+
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [Boolean]
+          def read_only_root_conversion(target, value)
+            target.read_only_root = value
+          end
+
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [String] Empty for no overlay
+          def root_overlay_conversion(target, value)
+            target.root_overlay = value.empty? ? nil : value
+          end
+
+          # @param target [Agama::Storage::ProposalSettings]
+          # @param value [Array<Hash>]
+          def caches_conversion(target, value)
+            target.caches = value.map do |dbus_cache|
+              Agama::Storage::CacheDevice.new(
+                dbus_cache["CacheDevice"], dbus_cache["BackingDevice"], dbus_cache["Method"].to_sym
+              ).tap do |cache|
+                cache.mode = dbus_cache["Mode"].to_sym if dbus_cache["Mode"]
+              end
+            end
+          end
+
           # Missing required volumes
           #
           # @param required_volumes [Array<Agama::Storage::Volume>]
@@ -42,18 +42,43 @@ module Agama
           #   * "ConfigureBoot" [Boolean]
           #   * "BootDevice" [String]
           #   * "DefaultBootDevice" [String]
+          #   * "Alignment" [String] Optional, "cylinder" or "explicit"
+          #   * "AlignmentGrain" [Integer] Optional, grain in bytes of the explicit alignment
+          #   * "EspMode" [String] Optional, "reuse" or "create"
+          #   * "EspDevice" [String] Optional, partition to reuse
+          #   * "EspSize" [Integer] Optional, size of the ESP to create
+          #   * "EspMountPath" [String] Optional
           #   * "EncryptionPassword" [String]
+          #   * "VolumeEncryptionPasswords" [Hash<String, String>] Optional
           #   * "EncryptionMethod" [String]
           #   * "EncryptionPBKDFunction" [String]
+          #   * "EncryptionKeyFile" [String] Optional
+          #   * "EncryptionKeyDevice" [String] Optional
           #   * "SpacePolicy" [String]
           #   * "SpaceActions" [Array<Hash>] see {#space_actions_conversion}
           #   * "Volumes" [Array<Hash>] see {#volumes_conversion}
+          #   * "IncludeRemovable" [Boolean]
+          #   * "ExcludedDevices" [Array<String>]
+          #   * "Partitions" [Array<Hash>] see {#partitions_conversion}
+          #   * "PartitionTables" [Hash<String, String>] Optional, types indexed by disk name
+          #   * "MountBy" [String] Optional
+          #   * "ReadOnlyRoot" [Boolean] Optional
+          #   * "RootOverlay" [String] Optional
+          #   * "Caches" [Array<Hash>] Optional, see {#caches_conversion}
           def convert
             target = device_conversion
 
             DBUS_PROPERTIES.each do |dbus_property, conversion|
               target[dbus_property] = send(conversion)
             end
+            alignment_conversion(target)
+            esp_conversion(target)
+            volume_passwords_conversion(target)
+            key_file_conversion(target)
+            read_only_root_conversion(target)
+            target["Caches"] = caches_conversion if settings.caches.any?
+            partition_tables_conversion(target)
+            target["MountBy"] = settings.mount_by.to_s if settings.mount_by
 
             target
           end
@@ -72,7 +97,10 @@ module Agama
             "EncryptionPBKDFunction" => :encryption_pbkd_function_conversion,
             "SpacePolicy"            => :space_policy_conversion,
             "SpaceActions"           => :space_actions_conversion,
-            "Volumes"                => :volumes_conversion
+            "Volumes"                => :volumes_conversion,
+            "IncludeRemovable"       => :include_removable_conversion,
+            "ExcludedDevices"        => :excluded_devices_conversion,
+            "Partitions"             => :partitions_conversion
           }.freeze
 
           private_constant :DBUS_PROPERTIES
@@ -148,6 +176,52 @@ module Agama
             settings.encryption.pbkd_function&.value || ""
           end
 
+          # @param target [Hash]
+          def alignment_conversion(target)
+            alignment = settings.alignment
+            return if alignment.optimal?
+
+            target["Alignment"] = alignment.mode.to_s
+            target["AlignmentGrain"] = alignment.grain if alignment.mode == :explicit
+          end
+
+          # @param target [Hash]
+          def esp_conversion(target)
+            esp = settings.boot.esp
+            return if esp.auto?
+
+            target["EspMode"] = esp.mode.to_s
+            target["EspDevice"] = esp.device if esp.mode == :reuse && esp.device
+            target["EspSize"] = esp.size if esp.mode == :create && esp.size
+            target["EspMountPath"] = esp.mount_path
+          end
+
+          # @param target [Hash]
+          def volume_passwords_conversion(target)
+            passwords = settings.encryption.volume_passwords
+            return if passwords.nil? || passwords.empty?
+
+            target["VolumeEncryptionPasswords"] = passwords
+          end
+
+          # @param target [Hash]
+          def partition_tables_conversion(target)
+            return if settings.partition_tables.empty?
+
+            target["PartitionTables"] = settings.partition_tables.transform_values(&:to_s)
+          end
+
+          # The key material is not exported.
+          #
+          # @param target [Hash]
+          def key_file_conversion(target)
+            key_file = settings.encryption.key_file
+            return unless key_file
+
+            target["EncryptionKeyFile"] = key_file.path
+            target["EncryptionKeyDevice"] = key_file.device || ""
+          end
+
           # @return [String]
           def space_policy_conversion
             settings.space.policy.to_s
@@ -157,9 +231,13 @@ module Agama
           #   For each action:
           #   * "Device" [String]
           #   * "Action" [String]
+          #   * "Size" [Integer] Target size of a resized device, if it is set
           def space_actions_conversion
             settings.space.actions.each_with_object([]) do |(device, action), actions|
-              actions << { "Device" => device, "Action" => action.to_s }
+              dbus_action = { "Device" => device, "Action" => action.to_s }
+              size = settings.space.resize_sizes[device]
+              dbus_action["Size"] = size if action.to_s == "resize" && size
+              actions << dbus_action
             end
           end
 
@@ -167,6 +245,62 @@ module Agama
           def volumes_conversion
             settings.volumes.map { |v| VolumeConversion.to_dbus(v) }
           end
+
+          # @return [Boolean]
+          def include_removable_conversion
+            settings.include_removable
+          end
+
+          # @return [Array<String>]
+          def excluded_devices_conversion
+            settings.excluded_devices
+          end
+
+          # Only included if the root file system is read-only.
+          #
+          # @param target [Hash]
+          def read_only_root_conversion(target)
+            return unless settings.read_only_root
+
+            target["ReadOnlyRoot"] = true
+            target["RootOverlay"] = settings.root_overlay || ""
+          end
+
+          # @return [Array<Hash<String, Object>>]
+          #   For each partition:
+          #   * "Device" [String]
+          #   * "Start" [Integer] First sector
+          #   * "End" [Integer] Last sector
+          #   * "FsType" [String] Optional
+          #   * "MountPath" [String] Optional
+          def partitions_conversion
+            settings.partitions.map do |partition|
+              {
+                "Device"    => partition.device,
+                "Start"     => partition.start,
+                "End"       => partition.end_sector,
+                "FsType"    => partition.fs_type,
+                "MountPath" => partition.mount_path
+              }.compact
+            end
+          end
+
+          # @return [Array<Hash<String, String>>]
+          #   For each cached device:
+          #   * "CacheDevice" [String]
+          #   * "BackingDevice" [String]
+          #   * "Method" [String] "bcache" or "lvm"
+          #   * "Mode" [String] "writethrough" or "writeback"
+          def caches_conversion
+            settings.caches.map do |cache|
+              {
+                "CacheDevice"   => cache.cache_device,
+                "BackingDevice" => cache.backing_device,
+                "Method"        => cache.cache_method.to_s,
+                "Mode"          => cache.mode.to_s
+              }
+            end
+          end
         end
       end
     end
@@ -20,11 +20,14 @@
 # find current contact information at www.suse.com.
 
 require "agama/dbus/hash_validator"
+require "agama/dbus/secrets"
 require "agama/dbus/types"
 require "agama/storage/volume"
 require "agama/storage/volume_location"
+require "agama/storage/volume_quota"
 require "agama/storage/volume_templates_builder"
 require "y2storage/disk_size"
+require "y2storage/filesystems/mount_by_type"
 require "y2storage/filesystems/type"
 
 module Agama
@@ -46,7 +49,7 @@ module Agama
           #
           # @return [Agama::Storage::Volume]
           def convert
-            logger.info("D-Bus volume: #{dbus_volume}")
+            logger.info("D-Bus volume: #{Agama::DBus::Secrets.redact(dbus_volume)}")
 
             dbus_volume_issues.each { |i| logger.warn(i) }
 
@@ -113,6 +116,36 @@ module Agama
               name:       "Snapshots",
               type:       Types::BOOL,
               conversion: :snapshots_conversion
+            },
+            {
+              name:       "Encryption",
+              type:       String,
+              conversion: :encryption_conversion
+            },
+            {
+              name:       "Weight",
+              type:       Integer,
+              conversion: :weight_conversion
+            },
+            {
+              name:       "Description",
+              type:       String,
+              conversion: :description_conversion
+            },
+            {
+              name:       "Label",
+              type:       String,
+              conversion: :label_conversion
+            },
+            {
+              name:       "MountBy",
+              type:       String,
+              conversion: :mount_by_conversion
+            },
+            {
+              name:       "Quota",
+              type:       Types::Hash.new(key: String),
+              conversion: :quota_conversion
             }
           ].freeze
 
@@ -217,6 +250,55 @@ module Agama
 
             target.btrfs.snapshots = value
           end
+
+          ENCRYPTIONS = ["passphrase", "random_key"].freeze
+          private_constant :ENCRYPTIONS
+
+          # @param target [Agama::Storage::Volume]
+          # @param value [String] "passphrase" or "random_key"
+          def encryption_conversion(target, value)
+            return unless ENCRYPTIONS.include?(value)
+
+            target.encryption = value.to_sym
+          end
+
+          # @param target [Agama::Storage::Volume]
+          # @param value [Integer]
+          def weight_conversion(target, value)
+            target.weight = value
+          end
+
+          # @param target [Agama::Storage::Volume]
+          # @param value [String] An empty description is ignored
+          def description_conversion(target, value)
+            target.description = value unless value.empty?
+          end
+
+          # @param target [Agama::Storage::Volume]
+          # @param value [String] An empty label is ignored
+          def label_conversion(target, value)
+            target.label = value unless value.empty?
+          end
+
+          # @param target [Agama::Storage::Volume]
+          # @param value [String] "uuid", "label", "path", "device" or "id"
+          def mount_by_conversion(target, value)
+            target.mount_by = Y2Storage::Filesystems::MountByType.find(value.to_sym)
+          end
+
+          # @param target [Agama::Storage::Volume]
+          # @param value [Hash] "Type" ("qgroups" or "project") and "Limits" (array of path and
+          #   size in bytes)
+          def quota_conversion(target, value)
+            type = value["Type"].to_s.to_sym
+            return unless Agama::Storage::VolumeQuota.types.include?(type)
+
+            target.quota = Agama::Storage::VolumeQuota.new(type).tap do |quota|
+              Array(value["Limits"]).each do |path, size|
+                quota.limits[path] = Y2Storage::DiskSize.new(size)
+              end
+            end
+          end
         end
       end
     end
@@ -44,6 +44,12 @@ module Agama
           #   * "Snapshots" [Booelan]
           #   * "Transactional" [Boolean]
           #   * "Outline" [Hash] see {#outline_conversion}
+          #   * "Encryption" [String] Optional, "passphrase" or "random_key"
+          #   * "Weight" [Integer] Optional
+          #   * "Description" [String] Optional
+          #   * "Label" [String] Optional
+          #   * "MountBy" [String] Optional, "uuid", "label", "path", "device" or "id"
+          #   * "Quota" [Hash] Optional, see {#quota_conversion}
           def convert
             {
               "MountPath"     => volume.mount_path.to_s,
@@ -59,6 +65,12 @@ module Agama
             }.tap do |target|
               # Some volumes could not have "MaxSize".
               max_size_conversion(target)
+              target["Encryption"] = volume.encryption.to_s if volume.encryption
+              target["Weight"] = volume.weight if volume.weight
+              target["Description"] = volume.description if volume.description
+              target["Label"] = volume.label if volume.label
+              target["MountBy"] = volume.mount_by.to_s if volume.mount_by
+              target["Quota"] = quota_conversion if volume.quota
             end
           end
 
@@ -74,6 +86,18 @@ module Agama
             target["MaxSize"] = volume.max_size.to_i
           end
 
+          # Converts the volume quota to D-Bus.
+          #
+          # @return [Hash<String, Object>]
+          #   * "Type" [String] "qgroups" or "project"
+          #   * "Limits" [Array<Array(String, Integer)>] Path and size in bytes
+          def quota_conversion
+            {
+              "Type"   => volume.quota.type.to_s,
+              "Limits" => volume.quota.limits.map { |path, size| [path, size.to_i] }
+            }
+          end
+
           # Converts volume outline to D-Bus.
           #
           # @return [Hash<String, Object>]
@@ -0,0 +1,47 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require "dbus"
+
+module Agama
+  module DBus
+    module Storage
+      # Mixin for exporting an iSCSI session through D-Bus
+      module WithISCSISession
+        # Converts the session to its D-Bus representation
+        #
+        # Integer values are typed explicitly, otherwise they would be exported as signed.
+        #
+        # @param session [Agama::Storage::ISCSI::Session]
+        # @return [Hash<String, Object>]
+        def iscsi_session_to_dbus(session)
+          {
+            "State"        => session.state.to_s,
+            "Connections"  => ::DBus::Data::UInt32.new(session.connections),
+            "HeaderDigest" => session.header_digest.to_s,
+            "DataDigest"   => session.data_digest.to_s,
+            "Parameters"   => ::DBus::Data.make_typed(::DBus.type("a{ss}"), session.parameters)
+          }
+        end
+      end
+    end
+  end
+end
@@ -68,6 +68,8 @@ module Agama
 
         dbus_reader :first_user, "(sssba{sv})"
 
+        dbus_accessor :default_shell, "s"
+
         dbus_method :SetRootPassword,
           "in Value:s, in Encrypted:b, out result:u" do |value, encrypted|
           logger.info "Setting Root Password"
@@ -118,6 +120,12 @@ module Agama
           0
         end
 
+        dbus_method :GetGroups, "out groups:s" do
+          backend.groups.to_json
+        end
+
+        dbus_method(:SetGroups, "in groups:s, out result:u") { |groups| load_groups(groups) }
+
         dbus_method :Write, "out result:u" do
           logger.info "Writting users"
 
@@ -140,19 +148,52 @@ module Agama
           user.name,
           user.password_content || "",
           backend.autologin?(user),
-          {}
+          first_user_data(user)
         ]
       end
 
+      def default_shell
+        backend.default_shell
+      end
+
+      def default_shell=(value)
+        logger.info "Setting the default shell to #{value}"
+        backend.default_shell = value
+        dbus_properties_changed(USERS_INTERFACE, { "DefaultShell" => value }, [])
+      end
+
       def root_password_set
         backend.root_password?
       end
 
+      # Sets the groups to create
+      #
+      # @param serialized_groups [String] JSON array of groups
+      # @return [Integer] 0 success; 1 error
+      def load_groups(serialized_groups)
+        logger.info "Setting the groups to create"
+        backend.load_groups_json(serialized_groups) ? 0 : 1
+      end
+
     private
 
       # @return [Agama::Users]
       attr_reader :backend
 
+      # Additional data of the first user
+      #
+      # @param user [Y2Users::User]
+      # @return [Hash]
+      def first_user_data(user)
+        data = {
+          "Shell" => user.shell,
+          "UID"   => user.uid&.to_i,
+          "GID"   => user.gid&.to_i,
+          "Home"  => user.home&.path
+        }.compact
+        data.merge(backend.first_user_expiration)
+      end
+
       def register_users_callbacks
         backend.on_issues_change { issues_properties_changed }
       end
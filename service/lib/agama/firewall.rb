@@ -0,0 +1,153 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require "json"
+require "yast"
+require "yast2/execute"
+
+Yast.import "PackagesProposal"
+
+module Agama
+  # Firewall settings for the installed system
+  #
+  # The settings are received as a JSON document (see the "firewall" section of the profile)
+  # and they are written to the target system using firewall-offline-cmd.
+  class Firewall
+    # Zones predefined by firewalld
+    PREDEFINED_ZONES = [
+      "block", "dmz", "drop", "external", "home", "internal", "public", "trusted", "work"
+    ].freeze
+    private_constant :PREDEFINED_ZONES
+
+    # Directories containing the definitions of the firewalld services
+    SERVICES_DIRS = ["/usr/lib/firewalld/services", "/etc/firewalld/services"].freeze
+    private_constant :SERVICES_DIRS
+
+    PROPOSAL_ID = "agama-firewall"
+    private_constant :PROPOSAL_ID
+
+    # @return [Hash] Settings, as described in the JSON document
+    attr_reader :settings
+
+    # @param logger [Logger]
+    def initialize(logger)
+      @logger = logger
+      @settings = {}
+    end
+
+    # Loads the settings from a JSON document
+    #
+    # @param json [String]
+    # @return [Boolean] false if the document is not a valid JSON object
+    def load_json(json)
+      settings = JSON.parse(json)
+      return false unless settings.is_a?(Hash)
+
+      @settings = settings
+      true
+    rescue JSON::ParserError
+      false
+    end
+
+    # Serializes the settings
+    #
+    # @return [String]
+    def to_json(*args)
+      settings.to_json(*args)
+    end
+
+    # Names of the firewalld services which can be opened
+    #
+    # @return [Array<String>]
+    def known_services
+      SERVICES_DIRS.flat_map { |d| Dir.glob(File.join(d, "*.xml")) }
+        .map { |f| File.basename(f, ".xml") }
+        .uniq.sort
+    end
+
+    # Selects firewalld for installation if it is needed by the settings
+    def propose
+      packages = needed? ? ["firewalld"] : []
+      Yast::PackagesProposal.SetResolvables(PROPOSAL_ID, :package, packages)
+    end
+
+    # Writes the settings to the target system
+    #
+    # The errors are logged, so they do not prevent writing the rest of the configuration.
+    def write
+      if needed?
+        zones.each { |z| write_zone(z) }
+        default_zone = settings["defaultZone"]
+        offline_cmd("--set-default-zone=#{default_zone}") if default_zone
+      end
+      write_service_state
+    rescue Cheetah::ExecutionFailed => e
+      logger.error "Could not write the firewall settings: #{e.message}"
+    end
+
+  private
+
+    # @return [Logger]
+    attr_reader :logger
+
+    # Whether firewalld is needed (i.e., the firewall is enabled or configured)
+    #
+    # @return [Boolean]
+    def needed?
+      return false if settings["enabled"] == false
+
+      settings["enabled"] == true || !!settings["defaultZone"] || !zones.empty?
+    end
+
+    # @return [Array<Hash>]
+    def zones
+      settings.fetch("zones", [])
+    end
+
+    # Opens the services and ports of a zone, creating it if it is not a predefined one
+    #
+    # @param zone [Hash]
+    def write_zone(zone)
+      name = zone["name"]
+      offline_cmd("--new-zone=#{name}") unless PREDEFINED_ZONES.include?(name)
+      zone.fetch("services", []).each { |s| offline_cmd("--zone=#{name}", "--add-service=#{s}") }
+      zone.fetch("ports", []).each { |p| offline_cmd("--zone=#{name}", "--add-port=#{p}") }
+    end
+
+    # Enables or disables the firewalld service, if requested
+    def write_service_state
+      enabled = settings["enabled"]
+      return if enabled.nil?
+
+      action = enabled ? "enable" : "disable"
+      logger.info "Running systemctl #{action} firewalld"
+      Yast::Execute.on_target!("systemctl", action, "firewalld")
+    end
+
+    # Runs firewall-offline-cmd on the target system
+    #
+    # @param args [Array<String>]
+    def offline_cmd(*args)
+      logger.info "Running firewall-offline-cmd #{args.join(" ")}"
+      Yast::Execute.on_target!("firewall-offline-cmd", *args)
+    end
+  end
+end
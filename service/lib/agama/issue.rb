@@ -25,7 +25,9 @@ module Agama
   # An issue is used for notifying about some unexpected or problematic situation, for example, to
   # indicate that there is no device for installing the system.
   #
-  # Issues have a description, details, source and severity:
+  # Issues have a kind, description, details, source and severity:
+  # * Kind: identifies the kind of issue (e.g., :missing_product). Unlike the description, it is not
+  #   translated, so the clients can rely on it.
   # * Description: describes the issue, typically with a single sentence.
   # * Details: provides more details about the problem. It is useful to report the output of a
   #   command failure.
@@ -35,6 +37,11 @@ module Agama
   # * Severity: sets the severity of the issue. For now, issues could have warn or error severity.
   #   Error severity indicates that the installation cannot start.
   class Issue
+    # Kind of the issue
+    #
+    # @return [Symbol]
+    attr_reader :kind
+
     # Description of the issue
     #
     # @return [String]
@@ -70,10 +77,13 @@ module Agama
     # Constructor
     #
     # @param description [String]
+    # @param kind [Symbol]
     # @param details [String, nil]
     # @param source [symbol, nil]
     # @param severity [symbol]
-    def initialize(description, details: "", source: nil, severity: Severity::WARN)
+    def initialize(description, kind: :generic, details: "", source: nil,
+      severity: Severity::WARN)
+      @kind = kind
       @description = description
       @details = details
       @source = source
@@ -0,0 +1,101 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+
+require "fileutils"
+require "yast"
+
+Yast.import "Installation"
+
+module Agama
+  # Policy to set the machine-id of the installed system
+  #
+  # By default, the machine-id is left empty, so systemd generates a fresh one on the first boot
+  # (avoiding duplicate ids in imaged systems). It can also be copied from the installation
+  # media or set explicitly.
+  class MachineId
+    POLICIES = ["generate", "keep", "explicit"].freeze
+
+    MACHINE_ID_FILE = "/etc/machine-id"
+    private_constant :MACHINE_ID_FILE
+
+    # @return [String] One of {POLICIES}
+    attr_reader :policy
+
+    # @return [String] Machine-id to use with the "explicit" policy (empty otherwise)
+    attr_reader :id
+
+    # @param logger [Logger]
+    def initialize(logger)
+      @logger = logger
+      @policy = "generate"
+      @id = ""
+    end
+
+    # Sets the policy
+    #
+    # @param policy [String] One of {POLICIES}
+    # @param id [String] Machine-id (32 hexadecimal characters), only for the "explicit" policy
+    # @return [Boolean] false if the policy or the machine-id are not valid
+    def assign(policy, id)
+      return false unless POLICIES.include?(policy)
+
+      id = (policy == "explicit") ? id.to_s.downcase : ""
+      return false if policy == "explicit" && !valid_id?(id)
+
+      @policy = policy
+      @id = id
+      true
+    end
+
+    # Writes the machine-id file of the target system
+    def write
+      content = case policy
+      when "keep"
+        File.read(MACHINE_ID_FILE)
+      when "explicit"
+        "#{id}\n"
+      else
+        # an empty file makes systemd generate the machine-id on the first boot
+        ""
+      end
+
+      file = File.join(Yast::Installation.destdir, MACHINE_ID_FILE)
+      logger.info "Writing #{file} (#{policy} policy)"
+      FileUtils.mkdir_p(File.dirname(file))
+      File.write(file, content)
+      File.chmod(0o444, file)
+    rescue SystemCallError => e
+      logger.error "Could not write the machine-id: #{e.message}"
+    end
+
+  private
+
+    # @return [Logger]
+    attr_reader :logger
+
+    # @param id [String]
+    # @return [Boolean]
+    def valid_id?(id)
+      id.match?(/\A\h{32}\z/) && !id.match?(/\A0+\z/)
+    end
+  end
+end
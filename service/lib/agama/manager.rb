@@ -23,8 +23,12 @@ require "shellwords"
 
 require "yast"
 require "agama/config"
+require "agama/firewall"
+require "agama/machine_id"
 require "agama/network"
 require "agama/proxy_setup"
+require "agama/scripts"
+require "agama/self_update"
 require "agama/with_locale"
 require "agama/with_progress"
 require "agama/installation_phase"
@@ -122,6 +126,7 @@ module Agama
     # rubocop:disable Metrics/AbcSize
     def install_phase
       service_status.busy
+      @installation_failed = false
       installation_phase.install
       start_progress_with_descriptions(
         _("Prepare disks"),
@@ -134,6 +139,7 @@ module Agama
       progress.step do
         storage.install
         proxy.propose
+        firewall.propose
         # propose software after /mnt is already separated, so it uses proper
         # target
         software.propose
@@ -146,12 +152,16 @@ module Agama
           network.install
           language.finish
           software.finish
+          firewall.write
+          scripts.write
+          machine_id.write
           storage.finish
         end
       end
 
       logger.info("Install phase done")
     rescue StandardError => e
+      @installation_failed = true
       logger.error "Installation error: #{e.inspect}. Backtrace: #{e.backtrace}"
     ensure
       service_status.idle
@@ -198,6 +208,34 @@ module Agama
       @network ||= Network.new(logger)
     end
 
+    # Firewall settings
+    #
+    # @return [Firewall]
+    def firewall
+      @firewall ||= Firewall.new(logger)
+    end
+
+    # Scripts to run in the installed system
+    #
+    # @return [Scripts]
+    def scripts
+      @scripts ||= Scripts.new(logger)
+    end
+
+    # Machine-id policy for the installed system
+    #
+    # @return [MachineId]
+    def machine_id
+      @machine_id ||= MachineId.new(logger)
+    end
+
+    # Installer self-update state
+    #
+    # @return [SelfUpdate]
+    def self_update
+      @self_update ||= SelfUpdate.new(logger)
+    end
+
     # Storage manager
     #
     # @return [DBus::Clients::Storage]
@@ -267,6 +305,13 @@ module Agama
       Dir.exist?("/iguana")
     end
 
+    # Whether the last installation failed
+    #
+    # @return [Boolean] false if the installation succeeded or it was not performed yet
+    def installation_failed?
+      !!@installation_failed
+    end
+
   private
 
     attr_reader :config
@@ -46,6 +46,24 @@ module Agama
       MANDATORY = :mandatory
     end
 
+    # Subscription of the registered product
+    #
+    # @!attribute product
+    #   @return [String] Registered product (e.g., "SLES/16.0/x86_64")
+    # @!attribute expires_at
+    #   @return [String, nil] Expiration date, nil if the subscription does not expire
+    Subscription = Struct.new(:product, :expires_at, keyword_init: true)
+
+    # Extension activated for the registered product
+    #
+    # @!attribute id
+    #   @return [String] Extension ID (e.g., "sle-module-basesystem")
+    # @!attribute version
+    #   @return [String]
+    # @!attribute expires_at
+    #   @return [String, nil] Expiration date, nil if the subscription does not expire
+    Extension = Struct.new(:id, :version, :expires_at, keyword_init: true)
+
     # @param software_manager [Agama::Software::Manager]
     # @param logger [Logger]
     def initialize(software_manager, logger)
@@ -93,6 +111,7 @@ module Agama
 
       @reg_code = code
       @email = email
+      @activations = read_activations
       run_on_change_callbacks
     end
 
@@ -124,6 +143,7 @@ module Agama
 
       @reg_code = nil
       @email = nil
+      @activations = nil
       run_on_change_callbacks
     end
 
@@ -148,6 +168,35 @@ module Agama
       Requirement::NOT_REQUIRED
     end
 
+    # Subscription of the registered product, as reported by the registration server
+    #
+    # @return [Subscription, nil] nil if the product is not registered yet
+    def subscription
+      return unless reg_code && product
+
+      Subscription.new(
+        product:    [product.id, product.version || "1.0", Yast::Arch.rpm_arch].join("/"),
+        expires_at: expiration_date(product_activation)
+      )
+    end
+
+    # Extensions and modules activated for the registered product
+    #
+    # @return [Array<Extension>]
+    def extensions
+      return [] unless reg_code
+
+      activation = product_activation
+      (activations - [activation]).map do |extension|
+        expires_at = expiration_date(extension)
+        Extension.new(
+          id:         extension.service.product.identifier,
+          version:    extension.service.product.version.to_s,
+          expires_at: (expires_at == expiration_date(activation)) ? nil : expires_at
+        )
+      end
+    end
+
     # Callbacks to be called when registration changes (e.g., a different product is selected).
     def on_change(&block)
       @on_change_callbacks ||= []
@@ -174,6 +223,39 @@ module Agama
       "#{product.id}-#{v}-#{Yast::Arch.rpm_arch}"
     end
 
+    # Activations of the registered system
+    #
+    # @return [Array<SUSE::Connect::Remote::Activation>]
+    def activations
+      @activations || []
+    end
+
+    # Reads the activations of the system from the registration server
+    #
+    # The subscription details are not essential, so they are empty if they cannot be read.
+    #
+    # @return [Array<SUSE::Connect::Remote::Activation>]
+    def read_activations
+      SUSE::Connect::YaST.status({}).activations.select { |a| a.service&.product }
+    rescue StandardError => e
+      @logger.warn "Could not read the subscription details: #{e.message}"
+      []
+    end
+
+    # Activation of the registered product
+    #
+    # @return [SUSE::Connect::Remote::Activation, nil]
+    def product_activation
+      activations.find { |a| a.service.product.identifier == product&.id }
+    end
+
+    # @param activation [SUSE::Connect::Remote::Activation, nil]
+    # @return [String, nil]
+    def expiration_date(activation)
+      date = activation&.expires_at.to_s
+      date.empty? ? nil : date
+    end
+
     def run_on_change_callbacks
       @on_change_callbacks&.map(&:call)
     end
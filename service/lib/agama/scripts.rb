@@ -0,0 +1,160 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require "fileutils"
+require "json"
+require "yast"
+require "yast2/execute"
+
+Yast.import "Installation"
+
+module Agama
+  # Scripts to run in the installed system
+  #
+  # The scripts are received as a JSON document (see the "scripts" section of the profile).
+  # Each script is copied to the installed system and it is run by a systemd service, either
+  # on the first boot only or on every boot.
+  class Scripts
+    # Directory to copy the scripts to
+    SCRIPTS_DIR = "/var/lib/agama/scripts"
+    private_constant :SCRIPTS_DIR
+
+    # Directory for the systemd services
+    UNITS_DIR = "/etc/systemd/system"
+    private_constant :UNITS_DIR
+
+    DEFAULT_SHELL = "/bin/sh"
+    private_constant :DEFAULT_SHELL
+
+    # @return [Hash] Settings, as described in the JSON document
+    attr_reader :settings
+
+    # @param logger [Logger]
+    def initialize(logger)
+      @logger = logger
+      @settings = {}
+    end
+
+    # Loads the settings from a JSON document
+    #
+    # @param json [String]
+    # @return [Boolean] false if the document is not a valid JSON object
+    def load_json(json)
+      settings = JSON.parse(json)
+      return false unless settings.is_a?(Hash)
+
+      @settings = settings
+      true
+    rescue JSON::ParserError
+      false
+    end
+
+    # Serializes the settings
+    #
+    # @return [String]
+    def to_json(*args)
+      settings.to_json(*args)
+    end
+
+    # Copies the scripts to the target system and enables the services to run them
+    #
+    # The errors are logged, so they do not prevent writing the rest of the configuration.
+    def write
+      settings.fetch("scripts", []).each { |s| write_script(s) }
+    rescue Cheetah::ExecutionFailed, SystemCallError => e
+      logger.error "Could not register the scripts: #{e.message}"
+    end
+
+  private
+
+    # @return [Logger]
+    attr_reader :logger
+
+    # @param script [Hash]
+    def write_script(script)
+      name = script["name"]
+      path = File.join(SCRIPTS_DIR, name)
+      logger.info "Registering the script #{name}"
+
+      write_target_file(path, script["body"], 0o700)
+      write_target_file("#{path}.env", environment_file(script), 0o600)
+      write_target_file(File.join(UNITS_DIR, unit_name(name)), unit_file(script, path), 0o644)
+      Yast::Execute.on_target!("systemctl", "enable", unit_name(name))
+    end
+
+    # @param name [String] Script name
+    # @return [String]
+    def unit_name(name)
+      "agama-script-#{name}.service"
+    end
+
+    # Content of the systemd service which runs the script
+    #
+    # The scripts to run on the first boot only leave a stamp file after running.
+    #
+    # @param script [Hash]
+    # @param path [String] Path of the script in the target system
+    # @return [String]
+    def unit_file(script, path)
+      first_boot = script.fetch("trigger", "firstBoot") == "firstBoot"
+      shell = script.fetch("shell", DEFAULT_SHELL)
+      lines = [
+        "[Unit]",
+        "Description=Agama script #{script["name"]}",
+        "Wants=network-online.target",
+        "After=network-online.target"
+      ]
+      lines << "ConditionPathExists=!#{path}.done" if first_boot
+      lines.concat(
+        ["", "[Service]", "Type=oneshot", "EnvironmentFile=#{path}.env",
+         "ExecStart=#{shell} #{path}"]
+      )
+      lines << "ExecStartPost=/usr/bin/touch #{path}.done" if first_boot
+      lines.concat(["", "[Install]", "WantedBy=multi-user.target"])
+      lines.join("\n") + "\n"
+    end
+
+    # Content of the environment file, including the locale variables
+    #
+    # @param script [Hash]
+    # @return [String]
+    def environment_file(script)
+      env = script.fetch("env", {}).dup
+      env["LANG"] = script["locale"] if script["locale"]
+      env.map do |key, value|
+        escaped = value.to_s.gsub("\\") { "\\\\" }.gsub("\"", "\\\"").gsub("\n", "\\n")
+        "#{key}=\"#{escaped}\"\n"
+      end.join
+    end
+
+    # Writes a file in the target system
+    #
+    # @param path [String] Path of the file in the target system
+    # @param content [String]
+    # @param mode [Integer]
+    def write_target_file(path, content, mode)
+      file = File.join(Yast::Installation.destdir, path)
+      FileUtils.mkdir_p(File.dirname(file))
+      File.write(file, content)
+      File.chmod(mode, file)
+    end
+  end
+end
@@ -0,0 +1,147 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require "fileutils"
+require "rexml/document"
+require "yast"
+require "yast2/execute"
+
+module Agama
+  # State of the installer self-update
+  #
+  # The self-update is performed by the agama-self-update script of the Live system, which updates
+  # the Agama packages from the agama-devel repository. The script records the versions before and
+  # after the update in {STATE_FILE} and it does nothing if the user opted out ({SKIP_FILE}).
+  class SelfUpdate
+    # File written by the self-update script, containing the versions before and after the update
+    STATE_FILE = "/run/agama/self_update"
+
+    # File which disables the self-update for the rest of the session
+    SKIP_FILE = "/run/agama/self_update_skip"
+
+    # Repository containing the updated packages
+    REPOSITORY = "agama-devel"
+    private_constant :REPOSITORY
+
+    # Package whose version is reported
+    PACKAGE = "agama"
+    private_constant :PACKAGE
+
+    # @param logger [Logger]
+    def initialize(logger)
+      @logger = logger
+    end
+
+    # Whether the self-update was already applied
+    #
+    # @return [Boolean]
+    def applied?
+      File.exist?(STATE_FILE)
+    end
+
+    # Whether the user opted out of the self-update
+    #
+    # @return [Boolean]
+    def skipped?
+      File.exist?(SKIP_FILE)
+    end
+
+    # Whether an update of the installer is available (and not applied yet)
+    #
+    # @return [Boolean]
+    def available?
+      !applied? && !available_version.nil?
+    end
+
+    # Version of the installer before the update
+    #
+    # @return [String, nil] nil if it is unknown
+    def from_version
+      applied? ? recorded_versions[0] : installed_version
+    end
+
+    # Version of the installer after the update
+    #
+    # @return [String, nil] nil if there is no update
+    def to_version
+      applied? ? recorded_versions[1] : available_version
+    end
+
+    # Opts out of the self-update for the rest of the session
+    #
+    # @return [Boolean] false if the update was already applied
+    def skip
+      return false if applied?
+
+      logger.info "Skipping the installer self-update"
+      FileUtils.mkdir_p(File.dirname(SKIP_FILE))
+      FileUtils.touch(SKIP_FILE)
+      true
+    end
+
+  private
+
+    # @return [Logger]
+    attr_reader :logger
+
+    # Versions recorded by the self-update script
+    #
+    # @return [Array<String, nil>] Versions before and after the update
+    def recorded_versions
+      from, to = File.read(STATE_FILE).split
+      [from, to]
+    end
+
+    # Version of the installed package
+    #
+    # @return [String, nil]
+    def installed_version
+      output = Yast::Execute.locally!(
+        "rpm", "--query", "--queryformat", "%{VERSION}-%{RELEASE}", PACKAGE, stdout: :capture
+      )
+      output.strip
+    rescue Cheetah::ExecutionFailed => e
+      logger.warn "Could not read the installer version: #{e.message}"
+      nil
+    end
+
+    # Version of the package available in the self-update repository
+    #
+    # The repository metadata is not refreshed and it is read without taking the libzypp lock,
+    # which is held by the software service. The result is cached for the rest of the session.
+    #
+    # @return [String, nil] nil if there is no update
+    def available_version
+      return @available_version if defined?(@available_version)
+
+      output = Yast::Execute.locally!(
+        "zypper", "--non-interactive", "--no-refresh", "--xmlout", "list-updates",
+        "--repo", REPOSITORY,
+        env: { "ZYPP_READONLY_HACK" => "1" }, stdout: :capture
+      )
+      update = REXML::Document.new(output).get_elements("//update[@name='#{PACKAGE}']").first
+      @available_version = update&.attributes&.[]("edition")
+    rescue Cheetah::ExecutionFailed, REXML::ParseException => e
+      logger.warn "Could not check the installer updates: #{e.message}"
+      @available_version = nil
+    end
+  end
+end
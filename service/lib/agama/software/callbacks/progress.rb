@@ -29,15 +29,24 @@ module Agama
       # This class represents the installer status
       class Progress
         class << self
-          def setup(pkg_count, progress)
-            new(pkg_count, progress).setup
+          def setup(pkg_count, progress, size: 0, &block)
+            new(pkg_count, progress, size: size, &block).setup
           end
         end
 
-        def initialize(pkg_count, progress)
+        # @param pkg_count [Integer] Number of packages to install
+        # @param progress [Agama::Progress]
+        # @param size [Integer] Size of the packages to install, in bytes
+        # @param block [Proc] Called with the package name, the number of installed packages, the
+        #   total number of packages, the installed bytes and the total bytes when a package
+        #   installation starts
+        def initialize(pkg_count, progress, size: 0, &block)
           @total = pkg_count
           @installed = 0
+          @size = size
+          @installed_size = 0
           @progress = progress
+          @on_package = block
         end
 
         def setup
@@ -55,8 +64,11 @@ module Agama
           Yast::FunRef.new(method, signature)
         end
 
-        def start_package(package, _file, _summary, _size, _other)
+        def start_package(package, _file, _summary, size, _other)
           progress.step("Installing #{package}")
+          @on_package&.call(package, @installed, @total, @installed_size, @size)
+          @installed += 1
+          @installed_size += size.to_i
         end
 
         def msg
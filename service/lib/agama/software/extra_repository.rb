@@ -0,0 +1,100 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require "yast"
+
+Yast.import "Pkg"
+
+module Agama
+  module Software
+    # Additional repository, on top of the product ones
+    #
+    # The repository is added to the package management when it is set and, if it is persistent,
+    # it is written to the zypp configuration of the installed system.
+    class ExtraRepository
+      # Unique identifier (e.g., "my-tools")
+      #
+      # @return [String]
+      attr_reader :repo_alias
+
+      # @return [String]
+      attr_reader :url
+
+      # Human readable name (empty to use the alias)
+      #
+      # @return [String]
+      attr_reader :name
+
+      # Whether the repository is kept in the installed system
+      #
+      # @return [Boolean]
+      attr_reader :persistent
+
+      # @param repo_alias [String]
+      # @param url [String]
+      # @param name [String]
+      # @param persistent [Boolean]
+      def initialize(repo_alias, url, name, persistent)
+        @repo_alias = repo_alias
+        @url = url
+        @name = name
+        @persistent = persistent
+      end
+
+      # Adds the repository to the package management
+      #
+      # @return [Boolean] false if the repository could not be added
+      def add
+        @repo_id = Yast::Pkg.RepositoryAdd(
+          "alias"       => repo_alias,
+          "name"        => name.empty? ? repo_alias : name,
+          "base_urls"   => [url],
+          "enabled"     => true,
+          "autorefresh" => true
+        )
+        !@repo_id.nil?
+      end
+
+      # Removes the repository from the package management, if it was added
+      def delete
+        return unless @repo_id
+
+        Yast::Pkg.SourceDelete(@repo_id)
+        @repo_id = nil
+      end
+
+      # Path of the repository file, relative to the root of the system
+      #
+      # @return [String]
+      def repo_file_path
+        "/etc/zypp/repos.d/#{repo_alias}.repo"
+      end
+
+      # Contents of the zypp repository file
+      #
+      # @return [String]
+      def repo_file
+        "[#{repo_alias}]\nname=#{name.empty? ? repo_alias : name}\nenabled=1\nautorefresh=1\n" \
+          "baseurl=#{url}\n"
+      end
+    end
+  end
+end
@@ -24,11 +24,13 @@ require "json"
 require "yast"
 require "y2packager/product"
 require "y2packager/resolvable"
+require "yast2/execute"
 require "agama/config"
 require "agama/helpers"
 require "agama/issue"
 require "agama/registration"
 require "agama/software/callbacks"
+require "agama/software/extra_repository"
 require "agama/software/product"
 require "agama/software/product_builder"
 require "agama/software/proposal"
@@ -37,6 +39,7 @@ require "agama/with_locale"
 require "agama/with_progress"
 require "agama/with_issues"
 
+Yast.import "Arch"
 Yast.import "Language"
 Yast.import "Package"
 Yast.import "Packages"
@@ -80,6 +83,14 @@ module Agama
       TARGET_DIR = "/run/agama/zypp"
       private_constant :TARGET_DIR
 
+      # Actions which can be applied to the systemd units
+      SYSTEMD_ACTIONS = ["enable", "disable", "mask"].freeze
+      private_constant :SYSTEMD_ACTIONS
+
+      # Systemd targets which can be set as default
+      SYSTEMD_TARGETS = ["graphical.target", "multi-user.target"].freeze
+      private_constant :SYSTEMD_TARGETS
+
       attr_accessor :languages
 
       # Available products for installation.
@@ -103,7 +114,10 @@ module Agama
         @repositories = RepositoriesManager.new
         # patterns selected by user
         @user_patterns = []
+        @systemd_units = []
+        @extra_repositories = []
         @selected_patterns_change_callbacks = []
+        @package_progress_callbacks = []
         on_progress_change { logger.info(progress.to_s) }
         initialize_target
       end
@@ -174,6 +188,116 @@ module Agama
         result
       end
 
+      # Systemd units to enable, disable or mask in the installed system
+      #
+      # @return [Array<Array(String, String)>] Unit names and the action to apply ("enable",
+      #   "disable" or "mask")
+      attr_reader :systemd_units
+
+      # Sets the systemd units to enable, disable or mask in the installed system
+      #
+      # @param units [Array<Array(String, String)>] Unit names and their actions
+      # @raise [ArgumentError] if some action is unknown
+      def systemd_units=(units)
+        wrong = units.reject { |_, action| SYSTEMD_ACTIONS.include?(action) }
+        raise ArgumentError, "Unknown systemd actions: #{wrong.inspect}" unless wrong.empty?
+
+        @systemd_units = units
+      end
+
+      # Additional repositories, on top of the product ones
+      #
+      # @return [Array<ExtraRepository>]
+      attr_reader :extra_repositories
+
+      # Replaces the additional repositories
+      #
+      # If a product is selected, the metadata of the repositories is loaded and the proposal is
+      # recalculated.
+      #
+      # @param repos [Array<ExtraRepository>]
+      # @raise [ArgumentError] if some alias is duplicated or some repository cannot be added
+      def extra_repositories=(repos)
+        aliases = repos.map(&:repo_alias)
+        duplicated = aliases.select { |a| aliases.count(a) > 1 }.uniq
+        unless duplicated.empty?
+          raise ArgumentError, "Duplicated repository aliases: #{duplicated.join(", ")}"
+        end
+
+        @extra_repositories.each(&:delete)
+        @extra_repositories = repos
+        failed = repos.reject(&:add)
+        unless failed.empty?
+          failed_aliases = failed.map(&:repo_alias).join(", ")
+          @extra_repositories -= failed
+          raise ArgumentError, "Could not add the repositories: #{failed_aliases}"
+        end
+        return unless product
+
+        Yast::Pkg.SourceLoad
+        propose
+      end
+
+      # Systemd default target of the installed system
+      #
+      # @return [String, nil] nil if the default of the product is used
+      attr_reader :default_target
+
+      # Sets the systemd default target of the installed system
+      #
+      # @param target [String, nil] "graphical.target" or "multi-user.target" (nil or empty to use
+      #   the default of the product)
+      # @raise [ArgumentError] if the target is unknown
+      def default_target=(target)
+        target = nil if target&.empty?
+        unless target.nil? || SYSTEMD_TARGETS.include?(target)
+          raise ArgumentError, "Unknown systemd target: #{target}"
+        end
+
+        @default_target = target
+      end
+
+      # Whether only the required packages are selected (no recommended ones)
+      #
+      # @return [Boolean]
+      def only_required
+        proposal.only_required
+      end
+
+      # Enables or disables the installation of recommended packages
+      #
+      # The proposal is recalculated.
+      #
+      # @param value [Boolean] true to select only the required packages
+      def only_required=(value)
+        proposal.only_required = value
+        propose
+      end
+
+      # Architecture of the system to install
+      #
+      # @return [String] e.g., "x86_64"
+      def architecture
+        Yast::Arch.rpm_arch
+      end
+
+      # Whether multilib (e.g., 32-bit on 64-bit) packages can be selected
+      #
+      # @return [Boolean]
+      def multilib
+        proposal.multilib
+      end
+
+      # Allows or forbids the selection of multilib packages
+      #
+      # The proposal is recalculated.
+      #
+      # @param value [Boolean]
+      def multilib=(value)
+        proposal.multilib = value
+        propose
+      end
+
       # Installs the packages to the target system
       def install
         # move the target from the Live ISO to the installed system (/mnt)
@@ -183,7 +307,9 @@ module Agama
 
         steps = proposal.packages_count
         start_progress_with_size(steps)
-        Callbacks::Progress.setup(steps, progress)
+        Callbacks::Progress.setup(steps, progress, size: proposal.packages_size) do |*args|
+          @package_progress_callbacks.each { |c| c.call(*args) }
+        end
 
         # TODO: error handling
         commit_result = Yast::Pkg.Commit({})
@@ -201,11 +327,16 @@ module Agama
 
       # Writes the repositories information to the installed system
       def finish
+        # the extra repositories are written on their own, only the persistent ones
+        extra_repositories.each(&:delete)
         Yast::Pkg.SourceSaveAll
         Yast::Pkg.TargetFinish
         # copy the libzypp caches to the target
         copy_zypp_to_target
         registration.finish
+        write_extra_repositories
+        write_systemd_units
+        write_default_target
       end
 
       # Determine whether the given tag is provided by the selected packages
@@ -303,6 +434,14 @@ module Agama
         @selected_patterns_change_callbacks << block
       end
 
+      # Registers a callback to be called when the installation of a package starts
+      #
+      # @param block [Proc] Called with the package name, the number of installed packages, the
+      #   total number of packages, the installed bytes and the total bytes
+      def on_package_progress(&block)
+        @package_progress_callbacks << block
+      end
+
       # Determines whether a package is installed in the target system.
       #
       # @param name [String] Package name
@@ -320,6 +459,23 @@ module Agama
         on_local { !!Yast::Package.Available(name) }
       end
 
+      # Packages whose version changes compared to the installed system
+      #
+      # On a fresh installation, all the selected packages are reported as new ones.
+      #
+      # @return [Array<Array(String, String, String)>] Name, installed version (empty if the
+      #   package is not installed) and selected version (empty if the package is removed)
+      def package_changes
+        packages = Yast::Pkg.ResolvableProperties("", :package, "") || []
+        packages.group_by { |p| p["name"] }.each_with_object([]) do |(name, versions), changes|
+          installed = versions.find { |p| [:installed, :removed].include?(p["status"]) }
+          selected = versions.find { |p| p["status"] == :selected }
+          next unless package_changed?(installed, selected)
+
+          changes << [name, installed&.fetch("version") || "", selected&.fetch("version") || ""]
+        end
+      end
+
       # Counts how much disk space installation will use.
       # @return [String]
       # @note Reimplementation of Yast::Package.CountSizeToBeInstalled
@@ -561,6 +717,7 @@ module Agama
       def repos_issues
         repositories.disabled.map do |repo|
           Issue.new(_("Could not read repository \"%s\"") % repo.name,
+            kind:     :unreadable_repository,
             source:   Issue::Source::SYSTEM,
             severity: Issue::Severity::ERROR)
         end
@@ -571,6 +728,7 @@ module Agama
       # @return [Agama::Issue]
       def missing_product_issue
         Issue.new(_("Product not selected yet"),
+          kind:     :missing_product,
           source:   Issue::Source::CONFIG,
           severity: Issue::Severity::ERROR)
       end
@@ -580,6 +738,7 @@ module Agama
       # @return [Agama::Issue]
       def missing_registration_issue
         Issue.new(_("Product must be registered"),
+          kind:     :missing_registration,
           source:   Issue::Source::SYSTEM,
           severity: Issue::Severity::ERROR)
       end
@@ -596,6 +755,18 @@ module Agama
         !Y2Packager::Resolvable.find(kind: :pattern, name: pattern_name).empty?
       end
 
+      # Whether the version of a package changes
+      #
+      # @param installed [Hash, nil] Installed package, if any
+      # @param selected [Hash, nil] Package selected for installation, if any
+      # @return [Boolean]
+      def package_changed?(installed, selected)
+        return installed&.fetch("status") == :removed if selected.nil?
+        return true if installed.nil? || installed["status"] == :removed
+
+        installed["version"] != selected["version"]
+      end
+
       # this reimplements the Pkg.SourceCacheCopyTo call which works correctly
       # only from the inst-sys (it copies the data from "/" where is actually
       # the Live system package manager)
@@ -636,6 +807,40 @@ module Agama
         FileUtils.copy(glob_credentials, target_dir)
       end
 
+      # Writes the persistent extra repositories to the zypp configuration of the installed system
+      def write_extra_repositories
+        extra_repositories.select(&:persistent).each do |repo|
+          file = File.join(Yast::Installation.destdir, repo.repo_file_path)
+          logger.info "Writing the repository #{repo.repo_alias} to #{file}"
+          FileUtils.mkdir_p(File.dirname(file))
+          File.write(file, repo.repo_file)
+        end
+      end
+
+      # Enables, disables or masks the systemd units in the installed system
+      #
+      # A failure does not abort the installation, it is only logged.
+      def write_systemd_units
+        systemd_units.each do |unit, action|
+          logger.info "Running 'systemctl #{action} #{unit}' in the installed system"
+          Yast::Execute.on_target!("systemctl", action, unit)
+        rescue Cheetah::ExecutionFailed => e
+          logger.error "Could not #{action} the unit #{unit}: #{e.message}"
+        end
+      end
+
+      # Sets the systemd default target of the installed system, if any
+      #
+      # A failure does not abort the installation, it is only logged.
+      def write_default_target
+        return unless default_target
+
+        logger.info "Setting the default target to #{default_target} in the installed system"
+        Yast::Execute.on_target!("systemctl", "set-default", default_target)
+      rescue Cheetah::ExecutionFailed => e
+        logger.error "Could not set the default target #{default_target}: #{e.message}"
+      end
+
       # Is any local repository (CD/DVD, disk) currently used?
       # @return [Boolean] true if any local repository is used
       def local_repo?
@@ -103,6 +103,23 @@ module Agama
       # @return [Hash<String, Hash<String, String>>]
       attr_accessor :translations
 
+      # Location of the release notes, indexed by language.
+      #
+      # @example
+      #   product.release_notes #=> { "en" => "https://example.net/release-notes.txt" }
+      #
+      # @return [Hash<String, String>]
+      attr_accessor :release_notes
+
+      # Hardware requirements ("min_memory", "recommended_memory", "min_disk" and
+      # "recommended_disk", in bytes, and "archs")
+      #
+      # @example
+      #   product.requirements #=> { "min_memory" => 1073741824, "archs" => ["x86_64"] }
+      #
+      # @return [Hash<String, Object>]
+      attr_accessor :requirements
+
       # @param id [string] Product id.
       def initialize(id)
         @id = id
@@ -116,6 +133,8 @@ module Agama
         # nil = display all visible patterns, [] = display no patterns
         @user_patterns = nil
         @translations = {}
+        @release_notes = {}
+        @requirements = {}
       end
 
       # Localized product description.
@@ -65,6 +65,8 @@ module Agama
           product.name = data[:name]
           product.version = data[:version]
           product.icon = attrs["icon"] if attrs["icon"]
+          product.release_notes = attrs["release_notes"] || {}
+          product.requirements = attrs["requirements"] || {}
         end
       end
 
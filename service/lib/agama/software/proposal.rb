@@ -59,6 +59,12 @@ module Agama
       # @return [Array<String>] List of languages to install
       attr_reader :languages
 
+      # @return [Boolean] Whether only the required packages are selected (no recommended ones)
+      attr_accessor :only_required
+
+      # @return [Boolean] Whether multilib (e.g., 32-bit on 64-bit) packages can be selected
+      attr_accessor :multilib
+
       # Constructor
       #
       # @param logger [Logger]
@@ -67,6 +73,8 @@ module Agama
 
         @logger = logger || Logger.new($stdout)
         @base_product = nil
+        @only_required = false
+        @multilib = true
       end
 
       # Adds the given list of resolvables to the proposal
@@ -88,6 +96,7 @@ module Agama
         initialize_target
         select_base_product
         @proposal = Yast::Packages.Proposal(force_reset = true, reinit = false, _simple = true)
+        forbid_multilib unless multilib
         solve_dependencies
 
         valid?
@@ -157,7 +166,24 @@ module Agama
         Yast::Pkg.SetPackageLocale(preferred || "")
         Yast::Pkg.SetAdditionalLocales(additional)
 
-        Yast::Pkg.SetSolverFlags("ignoreAlreadyRecommended" => false, "onlyRequires" => false)
+        Yast::Pkg.SetSolverFlags(
+          "ignoreAlreadyRecommended" => false, "onlyRequires" => only_required
+        )
+      end
+
+      # Suffix of the multilib packages (e.g., "libgcc_s1-32bit")
+      MULTILIB_SUFFIX = "-32bit"
+      private_constant :MULTILIB_SUFFIX
+
+      # Prevents the solver from selecting the multilib packages
+      #
+      # The packages are marked as taboo, so they are not pulled in as dependencies or recommended
+      # packages. The proposal reset clears the taboos.
+      def forbid_multilib
+        names = Yast::Pkg.Resolvables({ kind: :package }, [:name]).map { |r| r["name"] }
+        multilib = names.uniq.select { |n| n.end_with?(MULTILIB_SUFFIX) }
+        logger.info "Forbidding #{multilib.size} multilib packages"
+        multilib.each { |n| Yast::Pkg.PkgTaboo(n) }
       end
 
       # Selects the base product
@@ -186,11 +212,12 @@ module Agama
       # @return [Array<Agama::Issue>]
       def update_issues
         msgs = []
-        msgs.concat(warning_messages(proposal)) if proposal
-        msgs.concat(solver_messages)
+        msgs.concat(warning_messages(proposal).map { |m| [:proposal, m] }) if proposal
+        msgs.concat(solver_messages.map { |m| [:solver, m] })
 
-        issues = msgs.map do |msg|
+        issues = msgs.map do |kind, msg|
           Issue.new(msg,
+            kind:     kind,
             source:   Issue::Source::CONFIG,
             severity: Issue::Severity::ERROR)
         end
@@ -0,0 +1,293 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require "fileutils"
+require "json"
+require "yast"
+require "yast2/execute"
+require "bootloader/bootloader_factory"
+require "agama/sysctl"
+require "y2storage/arch"
+
+Yast.import "Installation"
+Yast.import "PackagesProposal"
+
+module Agama
+  module Storage
+    # Bootloader settings for the installed system
+    #
+    # The settings are received as a JSON document (see the "bootloader" section of the profile)
+    # and they are applied on top of the YaST bootloader proposal.
+    class Bootloader
+      # Bootloaders to use when the product does not define them
+      DEFAULT_BOOTLOADERS = ["grub2"].freeze
+      private_constant :DEFAULT_BOOTLOADERS
+
+      PROPOSAL_ID = "agama-bootloader"
+      private_constant :PROPOSAL_ID
+
+      MODULES_LOAD_FILE = "/etc/modules-load.d/agama.conf"
+      private_constant :MODULES_LOAD_FILE
+
+      MODPROBE_FILE = "/etc/modprobe.d/50-agama.conf"
+      private_constant :MODPROBE_FILE
+
+      # GRUB script which sets the superuser and the password
+      GRUB_PASSWORD_FILE = "/etc/grub.d/43_agama_password"
+      private_constant :GRUB_PASSWORD_FILE
+
+      # GRUB script which generates the boot entries
+      GRUB_LINUX_FILE = "/etc/grub.d/10_linux"
+      private_constant :GRUB_LINUX_FILE
+
+      # Packages needed to save the kernel crash dumps
+      KDUMP_PACKAGES = ["kdump"].freeze
+      private_constant :KDUMP_PACKAGES
+
+      # @return [Hash] Settings, as described in the JSON document
+      attr_reader :settings
+
+      # @param config [Agama::Config] Product configuration
+      # @param logger [Logger]
+      def initialize(config, logger)
+        @config = config
+        @logger = logger
+        @settings = {}
+      end
+
+      # Loads the settings from a JSON document
+      #
+      # @param json [String]
+      # @return [Boolean] false if the document is not a valid JSON object
+      def load_json(json)
+        settings = JSON.parse(json)
+        return false unless settings.is_a?(Hash)
+
+        @settings = settings
+        true
+      rescue JSON::ParserError
+        false
+      end
+
+      # Serializes the settings
+      #
+      # @return [String]
+      def to_json(*args)
+        settings.to_json(*args)
+      end
+
+      # Bootloaders supported by the product
+      #
+      # @return [Array<String>]
+      def supported
+        config.data.fetch("bootloader", {}).fetch("supported", DEFAULT_BOOTLOADERS)
+      end
+
+      # Whether the system boots using EFI
+      #
+      # @return [Boolean]
+      def efi?
+        Y2Storage::Arch.new.efiboot?
+      end
+
+      # Whether kdump is enabled
+      #
+      # The crashkernel parameter is already included in the extra kernel parameters.
+      #
+      # @return [Boolean]
+      def kdump?
+        settings.fetch("kdump", {})["enabled"] == true
+      end
+
+      # Applies the settings to the bootloader proposal
+      #
+      # The proposal must be already calculated. The packages needed by the bootloader (and by
+      # kdump, if it is enabled) are selected for installation.
+      def apply_proposal
+        select_bootloader
+        bootloader = ::Bootloader::BootloaderFactory.current
+        apply_menu(bootloader)
+        apply_kernel_params(bootloader)
+        apply_secure_boot(bootloader)
+        packages = bootloader.packages
+        packages += KDUMP_PACKAGES if kdump?
+        Yast::PackagesProposal.SetResolvables(PROPOSAL_ID, :package, packages)
+      end
+
+      # Writes the configuration files of the target system which depend on the settings
+      #
+      # It must be called before writing the bootloader configuration, so the initrd includes
+      # the kernel modules settings. The kdump service is enabled if requested.
+      def write
+        write_kernel_modules
+        write_grub_password
+        Agama::Sysctl.new(logger, settings.fetch("sysctl", {})).write
+        enable_kdump if kdump?
+      end
+
+    private
+
+      # @return [Agama::Config]
+      attr_reader :config
+
+      # @return [Logger]
+      attr_reader :logger
+
+      # Selects the bootloader, if it is set
+      def select_bootloader
+        name = case settings["bootloader"]
+        when "grub2"
+          efi? ? "grub2-efi" : "grub2"
+        when "systemd-boot"
+          "systemd-boot"
+        end
+        return if name.nil? || ::Bootloader::BootloaderFactory.current_name == name
+
+        logger.info "Selecting the bootloader #{name}"
+        ::Bootloader::BootloaderFactory.current_name = name
+        ::Bootloader::BootloaderFactory.current.propose
+      end
+
+      # Sets the menu timeout and whether the menu is shown
+      #
+      # @param bootloader [::Bootloader::BootloaderBase]
+      def apply_menu(bootloader)
+        timeout = settings["timeout"]
+        show_menu = settings["showMenu"]
+
+        if bootloader.respond_to?(:grub_default)
+          bootloader.grub_default.timeout = timeout.to_s if timeout
+          unless show_menu.nil?
+            style = show_menu ? "menu" : "hidden"
+            bootloader.grub_default.generic_set("GRUB_TIMEOUT_STYLE", style)
+          end
+        elsif bootloader.respond_to?(:menu_timeout=)
+          # systemd-boot only shows the menu if the timeout is not 0
+          timeout = 0 if show_menu == false
+          bootloader.menu_timeout = timeout if timeout
+        end
+      end
+
+      # Appends the extra kernel parameters to the ones proposed by YaST
+      #
+      # The parameters which are already set are not added again.
+      #
+      # @param bootloader [::Bootloader::BootloaderBase]
+      def apply_kernel_params(bootloader)
+        params = settings.fetch("extraKernelParams", [])
+        kernel_params = if bootloader.respond_to?(:grub_default)
+          bootloader.grub_default.kernel_params
+        elsif bootloader.respond_to?(:kernel_params)
+          bootloader.kernel_params
+        end
+        return if params.empty? || kernel_params.nil?
+
+        params.each do |param|
+          key, value = param.split("=", 2)
+          value ||= true
+          next if Array(kernel_params.parameter(key)).include?(value)
+
+          logger.info "Adding the kernel parameter #{key}"
+          kernel_params.add_parameter(key, value)
+        end
+      end
+
+      # Installs a Secure Boot capable setup (shim and signed kernel) if requested
+      #
+      # @param bootloader [::Bootloader::BootloaderBase]
+      def apply_secure_boot(bootloader)
+        secure_boot = settings["secureBoot"]
+        return if secure_boot.nil? || !bootloader.respond_to?(:secure_boot=)
+
+        logger.info "Setting Secure Boot support to #{secure_boot}"
+        bootloader.secure_boot = secure_boot
+      end
+
+      # Writes the kernel modules to load (modules-load.d) and their options and the blacklisted
+      # ones (modprobe.d)
+      def write_kernel_modules
+        kernel_modules = settings.fetch("kernelModules", {})
+        load = kernel_modules.fetch("load", [])
+        blacklist = kernel_modules.fetch("blacklist", [])
+
+        write_target_file(MODULES_LOAD_FILE, load.map { |m| m["name"] }) unless load.empty?
+
+        options = load.select { |m| m["options"] }
+        lines = options.map { |m| "options #{m["name"]} #{m["options"]}" } +
+          blacklist.map { |n| "blacklist #{n}" }
+        write_target_file(MODPROBE_FILE, lines) unless lines.empty?
+      end
+
+      # Protects the GRUB boot entries with the superuser password
+      #
+      # The entries can still be booted without the password, but editing them or using the
+      # GRUB shell requires it.
+      def write_grub_password
+        password = settings["password"]
+        return if password.nil? || password["hashedPassword"].nil?
+
+        user = password.fetch("user", "root")
+        file = write_target_file(
+          GRUB_PASSWORD_FILE,
+          [
+            "exec tail -n +4 $0",
+            "set superusers=\"#{user}\"",
+            "password_pbkdf2 #{user} #{password["hashedPassword"]}"
+          ],
+          header: "#!/bin/sh"
+        )
+        File.chmod(0o700, file)
+        unrestrict_grub_entries
+      end
+
+      # Allows booting the GRUB entries without the password
+      def unrestrict_grub_entries
+        file = File.join(Yast::Installation.destdir, GRUB_LINUX_FILE)
+        return unless File.exist?(file)
+
+        content = File.read(file).sub(/^CLASS="((?:(?!--unrestricted)[^"])*)"/) do
+          "CLASS=\"#{Regexp.last_match(1)} --unrestricted\""
+        end
+        File.write(file, content)
+      end
+
+      # Enables the kdump service in the target system
+      def enable_kdump
+        logger.info "Running systemctl enable kdump"
+        Yast::Execute.on_target!("systemctl", "enable", "kdump")
+      end
+
+      # Writes a file in the target system
+      #
+      # @param path [String] Path of the file in the target system
+      # @param lines [Array<String>]
+      # @param header [String, nil] Line to write before the "Written by Agama" comment
+      # @return [String] Path of the written file
+      def write_target_file(path, lines, header: nil)
+        file = File.join(Yast::Installation.destdir, path)
+        logger.info "Writing #{file}"
+        FileUtils.mkdir_p(File.dirname(file))
+        File.write(file, ([header, "# Written by Agama"].compact + lines).join("\n") + "\n")
+        file
+      end
+    end
+  end
+end
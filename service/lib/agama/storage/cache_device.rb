@@ -0,0 +1,143 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require "yast"
+require "y2storage"
+
+module Agama
+  module Storage
+    # Fast device (e.g., NVMe) caching a slower backing device (e.g., HDD)
+    #
+    # The cached device is created in the devicegraph after calculating the proposal, using both
+    # devices entirely. Y2Storage cannot create LVM caches, so the LVM method creates a logical
+    # volume on the backing device and the cache is attached to it at the end of the installation,
+    # see {Caches}.
+    class CacheDevice
+      include Yast::I18n
+
+      # Kernel module implementing each method
+      KERNEL_MODULES = { bcache: "bcache", lvm: "dm-cache" }.freeze
+      private_constant :KERNEL_MODULES
+
+      # Name of the logical volume created by the LVM method
+      LVM_LV_NAME = "cached"
+      private_constant :LVM_LV_NAME
+
+      # Name of the cache device (e.g., "/dev/nvme0n1")
+      #
+      # @return [String]
+      attr_accessor :cache_device
+
+      # Name of the backing device (e.g., "/dev/sda")
+      #
+      # @return [String]
+      attr_accessor :backing_device
+
+      # Caching technology
+      #
+      # @return [Symbol] :bcache or :lvm
+      attr_accessor :cache_method
+
+      # When the writes reach the backing device
+      #
+      # @return [Symbol] :writethrough or :writeback
+      attr_accessor :mode
+
+      # @param cache_device [String]
+      # @param backing_device [String]
+      # @param cache_method [Symbol]
+      def initialize(cache_device, backing_device, cache_method)
+        textdomain "agama"
+
+        @cache_device = cache_device
+        @backing_device = backing_device
+        @cache_method = cache_method
+        @mode = :writethrough
+      end
+
+      # Kernel module required to use the cached device
+      #
+      # @return [String]
+      def kernel_module
+        KERNEL_MODULES[cache_method]
+      end
+
+      # Creates the cached device in the given devicegraph
+      #
+      # The previous contents of both devices are removed, so they must not be in use. The devices
+      # used by the proposal are not touched.
+      #
+      # @param devicegraph [Y2Storage::Devicegraph]
+      # @return [String, nil] Problem which prevents creating the cached device, if any
+      def create(devicegraph)
+        devices = [cache_device, backing_device].map { |n| [n, devicegraph.find_by_name(n)] }
+        unknown = devices.find { |_, d| d.nil? }
+        return format(_("Unknown device %s"), unknown.first) if unknown
+
+        used = devices.find { |_, d| d.descendants.any? { |c| !c.exists_in_probed? } }
+        return format(_("%s is used by the installation"), used.first) if used
+
+        cache, backing = devices.map(&:last)
+        cache.remove_descendants
+        backing.remove_descendants
+        if cache_method == :lvm
+          create_lvm_lv(devicegraph, backing)
+        else
+          create_bcache(cache, backing)
+        end
+        nil
+      end
+
+      # Logical volume created by the LVM method in the given devicegraph
+      #
+      # @param devicegraph [Y2Storage::Devicegraph]
+      # @return [Y2Storage::LvmLv, nil]
+      def lvm_lv(devicegraph)
+        devicegraph.find_by_name(backing_device)&.lvm_pv&.lvm_vg&.lvm_lvs&.first
+      end
+
+    private
+
+      # @param cache [Y2Storage::BlkDevice]
+      # @param backing [Y2Storage::BlkDevice]
+      def create_bcache(cache, backing)
+        bcache = backing.create_bcache(Y2Storage::Bcache.find_free_name(backing.devicegraph))
+        bcache.cache_mode = Y2Storage::CacheMode.find(mode)
+        bcache.add_bcache_cset(cache.create_bcache_cset)
+      end
+
+      # @param devicegraph [Y2Storage::Devicegraph]
+      # @param backing [Y2Storage::BlkDevice]
+      def create_lvm_lv(devicegraph, backing)
+        vg = Y2Storage::LvmVg.create(devicegraph, free_vg_name(devicegraph))
+        vg.add_lvm_pv(backing)
+        vg.create_lvm_lv(LVM_LV_NAME, Y2Storage::LvType::NORMAL, vg.available_space)
+      end
+
+      # @param devicegraph [Y2Storage::Devicegraph]
+      # @return [String]
+      def free_vg_name(devicegraph)
+        names = devicegraph.lvm_vgs.map(&:vg_name)
+        (0..).map { |i| "cache#{i}" }.find { |n| !names.include?(n) }
+      end
+    end
+  end
+end
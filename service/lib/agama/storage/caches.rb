@@ -0,0 +1,110 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require "fileutils"
+require "yast"
+require "yast2/execute"
+require "y2storage"
+
+Yast.import "Installation"
+
+module Agama
+  module Storage
+    # Finishes the cached devices in the target system
+    #
+    # The LVM caches are attached to their logical volumes (see {CacheDevice}) and the kernel
+    # modules implementing the caches are loaded on boot.
+    class Caches
+      MODULES_FILE = "/etc/modules-load.d/agama-cache.conf"
+      private_constant :MODULES_FILE
+
+      # @param caches [Array<CacheDevice>] Cached devices of the proposal
+      # @param logger [Logger]
+      def initialize(caches, logger)
+        @caches = caches
+        @logger = logger
+      end
+
+      # Whether there is any cached device
+      #
+      # @return [Boolean]
+      def any?
+        caches.any?
+      end
+
+      # Attaches the LVM caches and writes the modules-load.d file
+      #
+      # The devices must be committed. A cache which cannot be attached is logged and the rest of
+      # them are still attached.
+      def write
+        caches.select { |c| c.cache_method == :lvm }.each { |c| attach_lvm_cache(c) }
+        write_modules
+      end
+
+    private
+
+      # @return [Array<CacheDevice>]
+      attr_reader :caches
+
+      # @return [Logger]
+      attr_reader :logger
+
+      # Adds the cache device to the volume group and converts the logical volume into a cached one
+      #
+      # @param cache [CacheDevice]
+      def attach_lvm_cache(cache)
+        lv = cache.lvm_lv(Y2Storage::StorageManager.instance.staging)
+        unless lv
+          logger.error "No logical volume found for the cache of #{cache.backing_device}"
+          return
+        end
+
+        vg = lv.lvm_vg.vg_name
+        return unless run("vgextend", vg, cache.cache_device)
+
+        run("lvcreate", "--type", "cache", "--cachemode", cache.mode.to_s, "--extents", "100%PVS",
+          "--name", "#{lv.lv_name}_cache", "#{vg}/#{lv.lv_name}", cache.cache_device)
+      end
+
+      # Loads the kernel modules of the caches on boot
+      def write_modules
+        modules = caches.map(&:kernel_module).uniq.sort
+        file = File.join(Yast::Installation.destdir, MODULES_FILE)
+        logger.info "Writing #{file}"
+        FileUtils.mkdir_p(File.dirname(file))
+        File.write(file, modules.map { |m| "#{m}\n" }.join)
+      end
+
+      # Runs a command in the installation system
+      #
+      # @param args [Array<String>]
+      # @return [Boolean] Whether the command succeeded
+      def run(*args)
+        logger.info "Running #{args.join(" ")}"
+        Yast::Execute.locally!(*args)
+        true
+      rescue Cheetah::ExecutionFailed => e
+        logger.error "Could not attach the cache: #{e.message}"
+        false
+      end
+    end
+  end
+end
@@ -19,6 +19,7 @@
 # To contact SUSE LLC about this file by physical or electronic mail, you may
 # find current contact information at www.suse.com.
 
+require "yast"
 require "agama/question"
 
 module Agama
@@ -26,11 +27,15 @@ module Agama
     module Callbacks
       # Callbacks for multipath activation
       class ActivateMultipath
+        include Yast::I18n
+
         # Constructor
         #
         # @param questions_client [Agama::DBus::Clients::Questions]
         # @param logger [Logger]
         def initialize(questions_client, logger)
+          textdomain "agama"
+
           @questions_client = questions_client
           @logger = logger
         end
@@ -61,8 +66,8 @@ module Agama
         #
         # @return [Question]
         def question
-          text = "The system seems to have multipath hardware. " \
-                 "Do you want to activate multipath?"
+          text = _("The system seems to have multipath hardware. " \
+                   "Do you want to activate multipath?")
 
           Question.new(
             qclass:         "storage.activate_multipath",
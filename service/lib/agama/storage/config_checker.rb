@@ -84,14 +84,14 @@ module Agama
 
         if config.is_a?(Agama::Storage::Configs::Drive)
           if config.search.skip_device?
-            warning(_("No device found for an optional drive"))
+            warning(:drive_not_found, _("No device found for an optional drive"))
           else
-            error(_("No device found for a mandatory drive"))
+            error(:drive_not_found, _("No device found for a mandatory drive"))
           end
         elsif config.search.skip_device?
-          warning(_("No device found for an optional partition"))
+          warning(:partition_not_found, _("No device found for an optional partition"))
         else
-          error(_("No device found for a mandatory partition"))
+          error(:partition_not_found, _("No device found for a mandatory partition"))
         end
       end
 
@@ -118,6 +118,7 @@ module Agama
         return if config.type&.fs_type
 
         error(
+          :missing_filesystem,
           format(
             # TRANSLATORS: %s is the replaced by a mount path (e.g., "/home").
             _("Missing file system type for '%s'"),
@@ -144,6 +145,7 @@ module Agama
         return if types.empty?
 
         error(
+          :invalid_filesystem,
           format(
             # TRANSLATORS: %{filesystem} is replaced by a file system type (e.g., "Btrfs") and
             #   %{path} is replaced by a mount path (e.g., "/home").
@@ -177,6 +179,7 @@ module Agama
         return unless config.missing_password?
 
         error(
+          :missing_encryption_password,
           format(
             # TRANSLATORS: 'crypt_method' is the identifier of the method to encrypt the device
             #   (e.g., 'luks1', 'random_swap').
@@ -195,6 +198,7 @@ module Agama
         return if !method || method.available?
 
         error(
+          :unavailable_encryption_method,
           format(
             # TRANSLATORS: 'crypt_method' is the identifier of the method to encrypt the device
             #   (e.g., 'luks1', 'random_swap').
@@ -214,6 +218,7 @@ module Agama
         return if config.filesystem&.path == Y2Storage::MountPoint::SWAP_PATH.to_s
 
         error(
+          :wrong_encryption_method,
           format(
             # TRANSLATORS: 'crypt_method' is the identifier of the method to encrypt the device
             #   (e.g., 'luks1', 'random_swap').
@@ -265,6 +270,7 @@ module Agama
 
         overused.map do |device|
           error(
+            :overused_physical_volumes_device,
             format(
               # TRANSLATORS: %s is the replaced by a device alias (e.g., "disk1").
               _("The device '%s' is used several times as target device for physical volumes"),
@@ -339,6 +345,7 @@ module Agama
         return if pool
 
         error(
+          :missing_thin_pool,
           format(
             # TRANSLATORS: %s is the replaced by a device alias (e.g., "pv1").
             _("There is no LVM thin pool volume with alias '%s'"),
@@ -364,6 +371,7 @@ module Agama
         return if configs.any? { |c| c.alias == pv_alias }
 
         error(
+          :missing_physical_volume,
           format(
             # TRANSLATORS: %s is the replaced by a device alias (e.g., "pv1").
             _("There is no LVM physical volume with alias '%s'"),
@@ -390,6 +398,7 @@ module Agama
         return if config.drives.any? { |d| d.alias == device_alias }
 
         error(
+          :missing_physical_volumes_device,
           format(
             # TRANSLATORS: %s is the replaced by a device alias (e.g., "disk1").
             _("There is no target device for LVM physical volumes with alias '%s'"),
@@ -422,6 +431,7 @@ module Agama
         return if method.nil? || valid_physical_volumes_encryption_method?(method)
 
         error(
+          :wrong_physical_volumes_encryption_method,
           format(
             # TRANSLATORS: 'crypt_method' is the identifier of the method to encrypt the device
             #   (e.g., 'luks1').
@@ -461,11 +471,13 @@ module Agama
 
       # Creates a warning issue.
       #
+      # @param kind [Symbol]
       # @param message [String]
       # @return [Issue]
-      def warning(message)
+      def warning(kind, message)
         Agama::Issue.new(
           message,
+          kind:     kind,
           source:   Agama::Issue::Source::CONFIG,
           severity: Agama::Issue::Severity::WARN
         )
@@ -473,11 +485,13 @@ module Agama
 
       # Creates an error issue.
       #
+      # @param kind [Symbol]
       # @param message [String]
       # @return [Issue]
-      def error(message)
+      def error(kind, message)
         Agama::Issue.new(
           message,
+          kind:     kind,
           source:   Agama::Issue::Source::CONFIG,
           severity: Agama::Issue::Severity::ERROR
         )
@@ -19,6 +19,8 @@
 # To contact SUSE LLC about this file by physical or electronic mail, you may
 # find current contact information at www.suse.com.
 
+require "agama/storage/esp_settings"
+
 module Agama
   module Storage
     module Configs
@@ -36,9 +38,15 @@ module Agama
         #   device for allocating root.
         attr_accessor :device
 
+        # How to get the EFI System Partition for UEFI systems.
+        #
+        # @return [EspSettings]
+        attr_accessor :esp
+
         # Constructor
         def initialize
           @configure = true
+          @esp = EspSettings.new
         end
       end
     end
@@ -0,0 +1,114 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+
+require "fileutils"
+require "yast"
+require "yast2/execute"
+
+Yast.import "Installation"
+
+module Agama
+  module Storage
+    # Key file to unlock the encrypted devices at boot instead of typing the passphrase
+    #
+    # Once the devices created by the proposal are committed, the key is added to them (the
+    # passphrase is kept as a fallback) and it is referenced in the crypttab of the target system.
+    class EncryptionKeyFile
+      CRYPTTAB_FILE = "/etc/crypttab"
+      private_constant :CRYPTTAB_FILE
+
+      # Path of the key file in the target system or in the key device (e.g., "/luks.key")
+      #
+      # @return [String]
+      attr_accessor :path
+
+      # Device containing the key file (e.g., "LABEL=USBKEY")
+      #
+      # @return [String, nil] nil if the key file is in the root file system
+      attr_accessor :device
+
+      # Key file reference written to the crypttab (e.g., "/luks.key:LABEL=USBKEY")
+      #
+      # @return [String]
+      attr_writer :crypttab
+
+      # File of the installation system holding the key material
+      #
+      # @return [String, nil]
+      attr_accessor :source
+
+      # @param path [String]
+      def initialize(path)
+        @path = path
+      end
+
+      # @return [String]
+      def crypttab
+        @crypttab || [path, device].compact.join(":")
+      end
+
+      # Adds the key to the given encryption devices and references it in the crypttab
+      #
+      # The file holding the key material is removed afterwards, as it is not needed anymore.
+      #
+      # @param encryptions [Array<Y2Storage::Encryption>] Devices created by the proposal
+      # @param password [String] Current passphrase of the devices
+      # @param logger [Logger]
+      def write(encryptions, password, logger)
+        if source.nil? || !File.exist?(source)
+          logger.warn "The encryption key file #{source} is not available"
+          return
+        end
+
+        encryptions.each do |encryption|
+          logger.info "Adding the key file to #{encryption.blk_device.name}"
+          Yast::Execute.locally!(
+            "cryptsetup", "luksAddKey", "--key-file=-", encryption.blk_device.name, source,
+            stdin: password
+          )
+        end
+        update_crypttab(encryptions.map(&:dm_table_name))
+      ensure
+        FileUtils.rm_f(source) if source
+      end
+
+    private
+
+      # Sets the key file of the given devices in the crypttab of the target system
+      #
+      # @param names [Array<String>] Names of the devices in the crypttab
+      def update_crypttab(names)
+        file = File.join(Yast::Installation.destdir, CRYPTTAB_FILE)
+        return unless File.exist?(file)
+
+        lines = File.readlines(file).map do |line|
+          fields = line.split
+          next line unless names.include?(fields.first)
+
+          fields[2] = crypttab
+          fields.join(" ") + "\n"
+        end
+        File.write(file, lines.join)
+      end
+    end
+  end
+end
@@ -21,6 +21,7 @@
 
 require "y2storage/secret_attributes"
 require "y2storage/encryption_method"
+require "agama/storage/encryption_key_file"
 
 module Agama
   module Storage
@@ -40,6 +41,11 @@ module Agama
       #   @return [String, nil] nil if undetermined
       secret_attr :password
 
+      # @!attribute volume_passwords
+      #   Passwords overriding {#password} for some volumes, indexed by mount path
+      #   @return [Hash<String, String>, nil]
+      secret_attr :volume_passwords
+
       # @return [Y2Storage::EncryptionMethod::Base]
       attr_accessor :method
 
@@ -48,6 +54,11 @@ module Agama
       # @return [Y2Storage::PbkdFunction, nil] Can be nil if using LUKS1.
       attr_accessor :pbkd_function
 
+      # Key file to unlock the encrypted devices, besides the password
+      #
+      # @return [EncryptionKeyFile, nil]
+      attr_accessor :key_file
+
       # All known encryption methods
       #
       # This includes all the potentially accepted methods, no matter whether they are
@@ -74,6 +85,14 @@ module Agama
         @pbkd_function = Y2Storage::PbkdFunction::PBKDF2
       end
 
+      # Password to use for the given volume
+      #
+      # @param mount_path [String]
+      # @return [String, nil] nil if the volume uses the default password
+      def volume_password(mount_path)
+        volume_passwords&.[](mount_path)
+      end
+
       # Whether the proposal must create encrypted devices
       #
       # @return [Boolean]
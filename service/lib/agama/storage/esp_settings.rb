@@ -0,0 +1,197 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require "yast"
+require "y2storage"
+
+module Agama
+  module Storage
+    # Settings to get the EFI System Partition (ESP) for UEFI systems
+    #
+    # The proposal always plans the ESP mounted at {DEFAULT_MOUNT_PATH}. Then, these settings are
+    # applied on top of the result: an existing ESP is mounted instead of the proposed one or a new
+    # ESP is created, if the proposal reused an existing one.
+    class EspSettings
+      include Yast::I18n
+
+      MODES = [:auto, :reuse, :create].freeze
+      private_constant :MODES
+
+      # Mount point of the ESP planned by the proposal
+      DEFAULT_MOUNT_PATH = "/boot/efi"
+
+      # Size of the new ESP if no size is given
+      DEFAULT_SIZE = Y2Storage::DiskSize.MiB(512).to_i
+      private_constant :DEFAULT_SIZE
+
+      # @return [Symbol] :auto (let the proposal decide), :reuse or :create
+      attr_accessor :mode
+
+      # Name of the partition to reuse, used only for :reuse
+      #
+      # @return [String, nil]
+      attr_accessor :device
+
+      # Size in bytes of the new ESP, used only for :create
+      #
+      # @return [Integer, nil] nil for the default size (512 MiB)
+      attr_accessor :size
+
+      # Mount point of the ESP
+      #
+      # @return [String]
+      attr_accessor :mount_path
+
+      # All possible modes
+      #
+      # @return [Array<Symbol>]
+      def self.modes
+        MODES
+      end
+
+      def initialize
+        textdomain "agama"
+
+        @mode = :auto
+        @mount_path = DEFAULT_MOUNT_PATH
+      end
+
+      # Whether the proposal decides how to get the ESP
+      #
+      # @return [Boolean]
+      def auto?
+        mode == :auto
+      end
+
+      # Applies the settings to the ESP of the given devicegraph
+      #
+      # @param devicegraph [Y2Storage::Devicegraph]
+      # @return [String, nil] Problem which prevents applying the settings, if any
+      def apply(devicegraph)
+        return if auto?
+
+        esp = devicegraph.filesystems.find { |f| f.mount_path == DEFAULT_MOUNT_PATH }
+        return _("The proposal does not include an EFI System Partition") unless esp
+
+        (mode == :reuse) ? reuse(devicegraph, esp) : create(esp)
+      end
+
+    private
+
+      # Mounts the ESP to reuse instead of the proposed one
+      #
+      # @param devicegraph [Y2Storage::Devicegraph]
+      # @param esp [Y2Storage::Filesystems::BlkFilesystem] Proposed ESP
+      # @return [String, nil]
+      def reuse(devicegraph, esp)
+        partition = devicegraph.find_by_name(device.to_s)
+        return format(_("Unknown device %s"), device) unless partition.is_a?(Y2Storage::Partition)
+
+        filesystem = partition.filesystem
+        unless filesystem&.type&.is?(:vfat)
+          return format(_("%s does not contain a FAT file system"), device)
+        end
+
+        if filesystem != esp
+          unless filesystem.exists_in_probed? && filesystem.mount_point.nil?
+            return format(_("%s is used by the installation"), device)
+          end
+
+          release(esp)
+        end
+        mount(filesystem)
+        nil
+      end
+
+      # Creates a new ESP if the proposal reused an existing one
+      #
+      # The new ESP is created in the biggest free space of the same disk. The proposal takes the
+      # size of the ESPs it creates from the ESP volume, see
+      # {ProposalSettingsConversions::ToY2Storage}.
+      #
+      # @param esp [Y2Storage::Filesystems::BlkFilesystem] Proposed ESP
+      # @return [String, nil]
+      def create(esp)
+        if esp.exists_in_probed?
+          partition = esp.blk_devices.first
+          unless partition.is_a?(Y2Storage::Partition)
+            return format(_("%s is not a partition"), partition.name)
+          end
+
+          filesystem = create_partition(partition.partition_table)
+          unless filesystem
+            return format(_("There is no space for a new EFI System Partition in %s"),
+              partition.partitionable.name)
+          end
+
+          esp.remove_mount_point
+          esp = filesystem
+        end
+        mount(esp)
+        nil
+      end
+
+      # Removes the proposed ESP
+      #
+      # @param esp [Y2Storage::Filesystems::BlkFilesystem]
+      def release(esp)
+        partition = esp.blk_devices.first
+        if partition.is_a?(Y2Storage::Partition) && !partition.exists_in_probed?
+          partition.partition_table.delete_partition(partition)
+        else
+          esp.remove_mount_point
+        end
+      end
+
+      # Mounts the file system at the requested mount point
+      #
+      # @param filesystem [Y2Storage::Filesystems::BlkFilesystem]
+      def mount(filesystem)
+        if filesystem.mount_point
+          filesystem.mount_point.path = mount_path
+        else
+          filesystem.create_mount_point(mount_path)
+        end
+      end
+
+      # Creates a partition for the ESP in the biggest free space of the partition table
+      #
+      # @param ptable [Y2Storage::PartitionTables::Base]
+      # @return [Y2Storage::Filesystems::BlkFilesystem, nil] nil if there is no space
+      def create_partition(ptable)
+        primary = Y2Storage::PartitionType::PRIMARY
+        bytes = size || DEFAULT_SIZE
+        esp_size = Y2Storage::DiskSize.new(bytes)
+        slot = ptable.unused_partition_slots
+          .select { |s| s.region.size >= esp_size && s.possible?(primary) }
+          .max_by { |s| s.region.size }
+        return unless slot
+
+        block_size = slot.region.block_size.to_i
+        blocks = (bytes + block_size - 1) / block_size
+        region = Y2Storage::Region.create(slot.region.start, blocks, slot.region.block_size)
+        partition = ptable.create_partition(slot.name, region, primary)
+        partition.id = Y2Storage::PartitionId::ESP
+        partition.create_filesystem(Y2Storage::Filesystems::Type::VFAT)
+      end
+    end
+  end
+end
@@ -0,0 +1,116 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require "yast"
+require "y2storage"
+
+module Agama
+  module Storage
+    # Partition created at an explicit position of a disk
+    #
+    # The partition is created as given (even if it is not aligned) in the free space left by the
+    # proposal.
+    class ExplicitPartition
+      include Yast::I18n
+
+      # Name of the disk (e.g., "/dev/sda")
+      #
+      # @return [String]
+      attr_accessor :device
+
+      # First sector of the partition
+      #
+      # @return [Integer]
+      attr_accessor :start
+
+      # Last sector of the partition (included)
+      #
+      # @return [Integer]
+      attr_accessor :end_sector
+
+      # File system to create in the partition (e.g., "ext4")
+      #
+      # @return [String, nil]
+      attr_accessor :fs_type
+
+      # Mount point of the file system
+      #
+      # @return [String, nil]
+      attr_accessor :mount_path
+
+      # @param device [String]
+      # @param start [Integer]
+      # @param end_sector [Integer]
+      def initialize(device, start, end_sector)
+        textdomain "agama"
+
+        @device = device
+        @start = start
+        @end_sector = end_sector
+      end
+
+      # Creates the partition in the given devicegraph
+      #
+      # @param devicegraph [Y2Storage::Devicegraph]
+      # @return [String, nil] Problem which prevents creating the partition, if any
+      def create(devicegraph)
+        ptable = devicegraph.find_by_name(device)&.partition_table
+        return format(_("%{device} has no partition table"), device: device) unless ptable
+
+        slot = ptable.unused_partition_slots.find do |s|
+          s.region.start <= start && end_sector <= s.region.end
+        end
+        unless slot
+          return format(_("The sectors %{start}-%{end} of %{device} are not free"),
+            start: start, end: end_sector, device: device)
+        end
+
+        region = Y2Storage::Region.create(start, end_sector - start + 1, slot.region.block_size)
+        partition = ptable.create_partition(slot.name, region, partition_type(slot))
+        create_filesystem(partition)
+        nil
+      end
+
+    private
+
+      # Type of partition to create in the given slot
+      #
+      # @param slot [Y2Storage::PartitionTables::PartitionSlot]
+      # @return [Y2Storage::PartitionType]
+      def partition_type(slot)
+        if slot.possible?(Y2Storage::PartitionType::PRIMARY)
+          Y2Storage::PartitionType::PRIMARY
+        else
+          Y2Storage::PartitionType::LOGICAL
+        end
+      end
+
+      # @param partition [Y2Storage::Partition]
+      def create_filesystem(partition)
+        type = fs_type && Y2Storage::Filesystems::Type.find(fs_type.downcase.to_sym)
+        return unless type
+
+        filesystem = partition.create_filesystem(type)
+        filesystem.mount_path = mount_path if mount_path
+      end
+    end
+  end
+end
@@ -41,10 +41,20 @@ module Agama
       # @param logger [Logger]
       # @param config [Config]
       # @param security [Security]
-      def initialize(logger, config, security)
+      # @param bootloader [Bootloader]
+      # @param nvmeof [NVMeoF::Manager]
+      # @param quotas [Quotas]
+      # @param root_overlay [RootOverlay]
+      # @param caches [Caches]
+      def initialize(logger, config, security, bootloader, nvmeof, quotas, root_overlay, caches)
         @logger = logger
         @config = config
         @security = security
+        @bootloader = bootloader
+        @nvmeof = nvmeof
+        @quotas = quotas
+        @root_overlay = root_overlay
+        @caches = caches
       end
 
       # Execute the final storage actions, reporting the progress
@@ -70,13 +80,33 @@ module Agama
       # @return [Security]
       attr_reader :security
 
+      # @return [Bootloader]
+      attr_reader :bootloader
+
+      # @return [NVMeoF::Manager]
+      attr_reader :nvmeof
+
+      # @return [Quotas]
+      attr_reader :quotas
+
+      # @return [RootOverlay]
+      attr_reader :root_overlay
+
+      # @return [Caches]
+      attr_reader :caches
+
       # All possible steps, that may or not need to be executed
       def possible_steps
         [
           SecurityStep.new(logger, security),
           CopyFilesStep.new(logger),
+          NVMeoFStep.new(logger, nvmeof),
           StorageStep.new(logger),
-          BootloaderStep.new(logger),
+          CachesStep.new(logger, caches),
+          FstabEntriesStep.new(logger),
+          QuotasStep.new(logger, quotas),
+          RootOverlayStep.new(logger, root_overlay),
+          BootloaderStep.new(logger, bootloader),
           IguanaStep.new(logger),
           SnapshotsStep.new(logger),
           CopyLogsStep.new(logger),
@@ -150,6 +180,27 @@ module Agama
         end
       end
 
+      # Step to restore the NVMe-oF connections on boot
+      class NVMeoFStep < Step
+        # Constructor
+        def initialize(logger, nvmeof)
+          super(logger)
+          @nvmeof = nvmeof
+        end
+
+        def label
+          "Writing NVMe over Fabrics configuration"
+        end
+
+        def run?
+          @nvmeof.controllers.any?
+        end
+
+        def run
+          @nvmeof.write
+        end
+      end
+
       # Step to write the security settings
       class SecurityStep < Step
         # Constructor
@@ -169,11 +220,18 @@ module Agama
 
       # Step to write the bootloader configuration
       class BootloaderStep < Step
+        # Constructor
+        def initialize(logger, bootloader)
+          super(logger)
+          @bootloader = bootloader
+        end
+
         def label
           "Installing bootloader"
         end
 
         def run
+          @bootloader.write
           cio_ignore_finish if Yast::Arch.s390
           ::Bootloader::FinishClient.new.write
         end
@@ -195,6 +253,103 @@ module Agama
         end
       end
 
+      # Step to append the additional fstab entries (e.g., NFS shares)
+      #
+      # The entries are set through the HTTP API, which writes them to {ENTRIES_FILE}.
+      class FstabEntriesStep < Step
+        ENTRIES_FILE = "/run/agama/storage/fstab"
+        FSTAB_FILE = "/etc/fstab"
+
+        def label
+          "Adding the remote file systems to the fstab"
+        end
+
+        def run?
+          File.exist?(entries_file)
+        end
+
+        def run
+          fstab = File.join(Yast::Installation.destdir, FSTAB_FILE)
+          content = File.exist?(fstab) ? File.read(fstab) : ""
+          lines = content.lines.reject { |l| l.lstrip.start_with?("#") }
+          mount_points = lines.map { |l| l.split[1] }.compact
+          entries = File.readlines(entries_file).reject { |l| mount_points.include?(l.split[1]) }
+          return if entries.empty?
+
+          content += "\n" unless content.empty? || content.end_with?("\n")
+          File.write(fstab, content + entries.join)
+        end
+
+      private
+
+        def entries_file
+          ENTRIES_FILE
+        end
+      end
+
+      # Step to attach the LVM caches and load the cache kernel modules on boot
+      class CachesStep < Step
+        # Constructor
+        def initialize(logger, caches)
+          super(logger)
+          @caches = caches
+        end
+
+        def label
+          "Configuring the cached devices"
+        end
+
+        def run?
+          @caches.any?
+        end
+
+        def run
+          @caches.write
+        end
+      end
+
+      # Step to enable the file system quotas and set their limits
+      class QuotasStep < Step
+        # Constructor
+        def initialize(logger, quotas)
+          super(logger)
+          @quotas = quotas
+        end
+
+        def label
+          "Enabling file system quotas"
+        end
+
+        def run?
+          @quotas.any?
+        end
+
+        def run
+          @quotas.write
+        end
+      end
+
+      # Step to mount the root file system read-only, with a writable overlay
+      class RootOverlayStep < Step
+        # Constructor
+        def initialize(logger, root_overlay)
+          super(logger)
+          @root_overlay = root_overlay
+        end
+
+        def label
+          "Configuring the read-only root file system"
+        end
+
+        def run?
+          @root_overlay.enabled?
+        end
+
+        def run
+          @root_overlay.write
+        end
+      end
+
       # Step to configure the file-system snapshots
       class SnapshotsStep < Step
         def label
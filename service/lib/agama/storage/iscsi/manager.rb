@@ -20,7 +20,9 @@
 # find current contact information at www.suse.com.
 
 require "yast"
+require "yast2/execute"
 require "agama/storage/iscsi/node"
+require "agama/storage/iscsi/session"
 require "agama/storage/iscsi/initiator"
 
 Yast.import "IscsiClientLib"
@@ -32,6 +34,36 @@ module Agama
       class Manager
         STARTUP_OPTIONS = ["onboot", "manual", "automatic"].freeze
 
+        # Node settings changed by YaST when logging in with some credentials
+        AUTH_SETTINGS = [
+          "node.session.auth.authmethod",
+          "node.session.auth.username",
+          "node.session.auth.password",
+          "node.session.auth.username_in",
+          "node.session.auth.password_in"
+        ].freeze
+        private_constant :AUTH_SETTINGS
+
+        # Login results for the iscsiadm exit codes (see iscsi_err.h)
+        LOGIN_ERRORS = {
+          4  => :network_unreachable,  # ISCSI_ERR_TRANS
+          8  => :network_unreachable,  # ISCSI_ERR_TRANS_TIMEOUT
+          11 => :network_unreachable,  # ISCSI_ERR_PDU_TIMEOUT
+          15 => :already_logged_in,    # ISCSI_ERR_SESS_EXISTS
+          21 => :target_not_found,     # ISCSI_ERR_NO_OBJS_FOUND
+          24 => :authentication_failed # ISCSI_ERR_LOGIN_AUTH_FAILED
+        }.freeze
+        private_constant :LOGIN_ERRORS
+
+        # iSCSI interface (iscsiadm iface)
+        #
+        # Empty values are nil (e.g., the hardware address of a software interface).
+        Interface = Struct.new(:name, :transport, :hwaddress, :ipaddress, keyword_init: true)
+
+        # Name of the interface used when the discovery or the login are not bound to any
+        DEFAULT_INTERFACE = "default"
+        private_constant :DEFAULT_INTERFACE
+
         # iSCSI initiator
         #
         # @return [Initiator]
@@ -42,6 +74,11 @@ module Agama
         # @return [Array<Node>]
         attr_reader :nodes
 
+        # Details of the last failed login (e.g., the iscsiadm output)
+        #
+        # @return [String, nil] nil if the last login succeeded
+        attr_reader :login_error
+
         # Constructor
         #
         # @param logger [Logger, nil]
@@ -78,7 +115,8 @@ module Agama
           logger.info "Probing iSCSI"
 
           Yast::IscsiClientLib.readSessions
-          @nodes = Yast::IscsiClientLib.getDiscovered.map { |t| node_from(t.split) }
+          sessions = self.sessions
+          @nodes = Yast::IscsiClientLib.getDiscovered.map { |t| node_from(t.split, sessions) }
 
           @on_probe_callbacks.each(&:call)
         end
@@ -93,16 +131,56 @@ module Agama
         # @param host [String] IP address
         # @param port [Integer]
         # @param authentication [Y2IscsiClient::Authentication]
+        # @param interface [String, nil] iSCSI interface to bind the discovery to (the default one
+        #   if nil)
         #
         # @return [Boolean] Whether the action successes
-        def discover_send_targets(host, port, authentication)
+        def discover_send_targets(host, port, authentication, interface: nil)
           ensure_activated
 
           probe_after do
-            Yast::IscsiClientLib.discover(host, port, authentication, silent: true)
+            if interface.nil? || interface == DEFAULT_INTERFACE
+              Yast::IscsiClientLib.discover(host, port, authentication, silent: true)
+            else
+              discover_through(interface, host, port, authentication)
+            end
           end
         end
 
+        # Whether the iSCSI initiator tools (iscsiadm) are available
+        #
+        # @return [Boolean]
+        def supported?
+          ENV.fetch("PATH", "").split(File::PATH_SEPARATOR).any? do |dir|
+            File.executable?(File.join(dir, "iscsiadm"))
+          end
+        end
+
+        # Names of the iSCSI interfaces (iscsiadm ifaces), including the offload engines
+        #
+        # @return [Array<String>]
+        def interfaces
+          ([DEFAULT_INTERFACE] + interfaces_details.map(&:name)).uniq
+        end
+
+        # Details of the iSCSI interfaces (iscsiadm ifaces)
+        #
+        # @return [Array<Interface>]
+        def interfaces_details
+          output = Yast::Execute.locally!("iscsiadm", "-m", "iface", stdout: :capture)
+          output.to_s.lines.map { |l| interface_from(l) }.compact
+        rescue Cheetah::ExecutionFailed => e
+          logger.warn "Could not read the iSCSI interfaces: #{e.message}"
+          []
+        end
+
+        # Active iSCSI sessions, including the ones not established by Agama (e.g., iBFT)
+        #
+        # @return [Array<Session>]
+        def sessions
+          ISCSI::Session.all
+        end
+
         # Creates a new iSCSI session
         #
         # @note iSCSI nodes are probed again, see {#probe_after}.
@@ -110,17 +188,21 @@ module Agama
         # @param node [Node]
         # @param authentication [Y2IscsiClient::Authentication]
         # @param startup [String, nil] Startup status
+        # @param interface [String, nil] iSCSI interface to bind the session to (the one of the
+        #   node if nil). The node must have been discovered through that interface.
         #
-        # @return [Boolean] Whether the action successes
-        def login(node, authentication, startup: nil)
+        # @return [Symbol] :success, :authentication_failed, :target_not_found,
+        #   :network_unreachable, :already_logged_in or :failed. See {#login_error} for the
+        #   details of the failure.
+        def login(node, authentication, startup: nil, interface: nil)
           startup ||= Yast::IscsiClientLib.default_startup_status
 
           ensure_activated
 
           result = probe_after do
-            Yast::IscsiClientLib.currentRecord = record_from(node)
-            Yast::IscsiClientLib.login_into_current(authentication, silent: true) &&
-              Yast::IscsiClientLib.setStartupStatus(startup)
+            record = record_from(node)
+            record[2] = interface if interface
+            login_record(record, authentication, startup)
           end
 
           run_on_sessions_change_callbacks
@@ -146,6 +228,38 @@ module Agama
           result
         end
 
+        # Checks whether the target accepts the given credentials, without keeping the session
+        #
+        # The session is closed right after logging in and the authentication settings stored in
+        # the node are restored, so the check does not change the node.
+        #
+        # @note iSCSI nodes are probed again, see {#probe_after}.
+        #
+        # @param node [Node]
+        # @param authentication [Y2IscsiClient::Authentication]
+        #
+        # @return [Symbol] :success, :login_failed or :logout_failed
+        def test_auth(node, authentication)
+          ensure_activated
+
+          previous = auth_settings(node)
+          result = probe_after do
+            Yast::IscsiClientLib.currentRecord = record_from(node)
+            if !Yast::IscsiClientLib.login_into_current(authentication, silent: true)
+              :login_failed
+            elsif Yast::IscsiClientLib.deleteRecord
+              :success
+            else
+              :logout_failed
+            end
+          ensure
+            restore_auth_settings(node, previous)
+          end
+
+          run_on_sessions_change_callbacks
+          result
+        end
+
         # Deletes an iSCSI node from the database
         #
         # @note iSCSI nodes are probed again, see {#probe_after}.
@@ -215,8 +329,9 @@ module Agama
         # Creates a node from the record provided by YaST
         #
         # @param record [Array] Contains portal, target and interface of the iSCSI node.
+        # @param sessions [Array<Session>] Active sessions
         # @return [Node]
-        def node_from(record)
+        def node_from(record, sessions)
           ISCSI::Node.new.tap do |node|
             node.portal = record[0]
             node.target = record[1]
@@ -234,10 +349,26 @@ module Agama
               # (ie. calling iscsiadm)
               Yast::IscsiClientLib.currentRecord = session_record
               node.startup = Yast::IscsiClientLib.getStartupStatus
+              node.session = sessions.find { |s| s.node?(node) }
             end
           end
         end
 
+        # Creates an interface from a line of the iscsiadm output
+        #
+        # @param line [String] "name transport,hwaddress,ipaddress,net_ifacename,initiatorname"
+        # @return [Interface, nil] nil if the line cannot be parsed
+        def interface_from(line)
+          name, fields = line.split
+          return unless fields
+
+          transport, hwaddress, ipaddress = fields.split(",").map do |value|
+            (value == "<empty>") ? nil : value
+          end
+          Interface.new(name: name, transport: transport, hwaddress: hwaddress,
+            ipaddress: ipaddress)
+        end
+
         # Generates a YaST record from a node
         #
         # @param node [Node]
@@ -246,6 +377,146 @@ module Agama
           [node.portal, node.target, node.interface]
         end
 
+        # Authentication settings stored in the node
+        #
+        # @param node [Node]
+        # @return [Hash<String, String>]
+        def auth_settings(node)
+          # --show includes the passwords, which are masked otherwise
+          output = Yast::Execute.locally!(*iscsiadm_node(node), "--show", stdout: :capture)
+
+          output.to_s.lines.each_with_object({}) do |line, settings|
+            name, value = line.split("=", 2).map(&:strip)
+            next unless AUTH_SETTINGS.include?(name)
+
+            settings[name] = (value == "<empty>") ? "" : value.to_s
+          end
+        rescue Cheetah::ExecutionFailed => e
+          logger.warn "Could not read the authentication of the iSCSI node: #{e.message}"
+          {}
+        end
+
+        # Writes back the given authentication settings to the node
+        #
+        # @param node [Node]
+        # @param settings [Hash<String, String>]
+        def restore_auth_settings(node, settings)
+          settings.each do |name, value|
+            Yast::Execute.locally!(
+              *iscsiadm_node(node), "--op=update", "--name=#{name}", "--value=#{value}"
+            )
+          end
+        rescue Cheetah::ExecutionFailed => e
+          logger.warn "Could not restore the authentication of the iSCSI node: #{e.message}"
+        end
+
+        # iscsiadm command to manage the given node
+        #
+        # @param node [Node]
+        # @return [Array<String>]
+        def iscsiadm_node(node)
+          iscsiadm_record(record_from(node))
+        end
+
+        # iscsiadm command to manage the node of the given record
+        #
+        # @param record [Array] Contains portal, target and interface of the iSCSI node.
+        # @return [Array<String>]
+        def iscsiadm_record(record)
+          ["iscsiadm", "-m", "node", "-I", record[2] || "default", "-T", record[1],
+           "-p", record[0]]
+        end
+
+        # Logs into the node of the given record and sets its startup status
+        #
+        # iscsiadm is called directly, instead of through YaST, to find out why the login fails.
+        #
+        # @param record [Array] Contains portal, target and interface of the iSCSI node.
+        # @param authentication [Y2IscsiClient::Authentication]
+        # @param startup [String]
+        # @return [Symbol] See {#login}
+        def login_record(record, authentication, startup)
+          @login_error = nil
+          command = iscsiadm_record(record)
+          node_auth_settings(authentication).each do |name, value|
+            Yast::Execute.locally!(*command, "--op=update", "--name=#{name}", "--value=#{value}")
+          end
+          Yast::Execute.locally!(*command, "--login", stderr: :capture)
+
+          Yast::IscsiClientLib.currentRecord = record
+          return :success if Yast::IscsiClientLib.setStartupStatus(startup)
+
+          @login_error = "Could not set the startup status to #{startup}"
+          :failed
+        rescue Cheetah::ExecutionFailed => e
+          logger.error "iSCSI login failed: #{e.message}"
+          @login_error = e.stderr.to_s.strip
+          @login_error = e.message if @login_error.empty?
+          LOGIN_ERRORS.fetch(e.status&.exitstatus, :failed)
+        end
+
+        # Node authentication settings for the given credentials
+        #
+        # @param authentication [Y2IscsiClient::Authentication]
+        # @return [Hash<String, String>]
+        def node_auth_settings(authentication)
+          prefix = "node.session.auth"
+          return { "#{prefix}.authmethod" => "None" } if authentication.username.to_s.empty?
+
+          {
+            "#{prefix}.authmethod"  => "CHAP",
+            "#{prefix}.username"    => authentication.username,
+            "#{prefix}.password"    => authentication.password.to_s,
+            "#{prefix}.username_in" => authentication.username_in.to_s,
+            "#{prefix}.password_in" => authentication.password_in.to_s
+          }
+        end
+
+        # Performs a send targets discovery bound to the given iSCSI interface
+        #
+        # YaST only binds the discovery to the selected offload card, so iscsiadm is used
+        # directly, following the same steps (creating the discovery record, setting the
+        # authentication and discovering).
+        #
+        # @param interface [String]
+        # @param host [String]
+        # @param port [Integer]
+        # @param authentication [Y2IscsiClient::Authentication]
+        # @return [Boolean]
+        def discover_through(interface, host, port, authentication)
+          command = ["iscsiadm", "-m", "discoverydb", "-t", "st", "-I", interface,
+                     "-p", "#{host}:#{port}"]
+          Yast::Execute.locally!(*command, "-o", "new")
+          discovery_auth_settings(authentication).each do |name, value|
+            Yast::Execute.locally!(*command, "-o", "update", "-n", name, "-v", value)
+          end
+          Yast::Execute.locally!(*command, "--discover")
+          true
+        rescue Cheetah::ExecutionFailed => e
+          logger.error "iSCSI discovery through #{interface} failed: #{e.message}"
+          false
+        end
+
+        # Discovery authentication settings for the given credentials
+        #
+        # @param authentication [Y2IscsiClient::Authentication]
+        # @return [Hash<String, String>]
+        def discovery_auth_settings(authentication)
+          prefix = "discovery.sendtargets.auth"
+          return { "#{prefix}.authmethod" => "None" } if authentication.username.to_s.empty?
+
+          settings = {
+            "#{prefix}.authmethod" => "CHAP",
+            "#{prefix}.username"   => authentication.username,
+            "#{prefix}.password"   => authentication.password.to_s
+          }
+          unless authentication.username_in.to_s.empty?
+            settings["#{prefix}.username_in"] = authentication.username_in
+            settings["#{prefix}.password_in"] = authentication.password_in.to_s
+          end
+          settings
+        end
+
         # Finds a session for the given iSCSI record
         #
         # @param record [Array] Contains portal, target and interface of the iSCSI node.
@@ -63,6 +63,11 @@ module Agama
         # @return [String]
         attr_accessor :startup
 
+        # Session of the node
+        #
+        # @return [Session, nil] nil if the node is not connected
+        attr_accessor :session
+
         def ibft?
           !!ibtf
         end
@@ -0,0 +1,216 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+module Agama
+  module Storage
+    module ISCSI
+      # Live iSCSI session held by the system
+      #
+      # The sessions are read from sysfs, so they include the ones which were not established by
+      # Agama (e.g., the iBFT ones logged in at boot time).
+      class Session
+        SESSIONS_DIR = "/sys/class/iscsi_session"
+        private_constant :SESSIONS_DIR
+
+        CONNECTIONS_DIR = "/sys/class/iscsi_connection"
+        private_constant :CONNECTIONS_DIR
+
+        IBFT_DIR = "/sys/firmware/ibft"
+        private_constant :IBFT_DIR
+
+        # Negotiated parameters of the session, named as iscsiadm does
+        SESSION_PARAMETERS = {
+          "first_burst_len"     => "FirstBurstLength",
+          "max_burst_len"       => "MaxBurstLength",
+          "immediate_data"      => "ImmediateData",
+          "initial_r2t"         => "InitialR2T",
+          "max_outstanding_r2t" => "MaxOutstandingR2T"
+        }.freeze
+        private_constant :SESSION_PARAMETERS
+
+        # Negotiated parameters of the connections
+        CONNECTION_PARAMETERS = {
+          "max_recv_dlength" => "MaxRecvDataSegmentLength",
+          "max_xmit_dlength" => "MaxXmitDataSegmentLength"
+        }.freeze
+        private_constant :CONNECTION_PARAMETERS
+
+        # Boolean parameters, which sysfs reports as 0 or 1
+        BOOLEAN_PARAMETERS = ["ImmediateData", "InitialR2T"].freeze
+        private_constant :BOOLEAN_PARAMETERS
+
+        # Target name
+        #
+        # @return [String]
+        attr_accessor :target
+
+        # Portal address
+        #
+        # @return [String]
+        attr_accessor :address
+
+        # Portal port
+        #
+        # @return [Integer]
+        attr_accessor :port
+
+        # iSCSI interface used by the session
+        #
+        # @return [String]
+        attr_accessor :interface
+
+        # Session state (e.g., "LOGGED_IN" or "FAILED")
+        #
+        # @return [String]
+        attr_accessor :state
+
+        # Number of connections
+        #
+        # @return [Integer]
+        attr_accessor :connections
+
+        # Negotiated header digest (e.g., "None" or "CRC32C")
+        #
+        # @return [String]
+        attr_accessor :header_digest
+
+        # Negotiated data digest (e.g., "None" or "CRC32C")
+        #
+        # @return [String]
+        attr_accessor :data_digest
+
+        # Other negotiated parameters (e.g., "MaxRecvDataSegmentLength")
+        #
+        # @return [Hash<String, String>]
+        attr_accessor :parameters
+
+        # Whether the session was established by the firmware (iBFT)
+        #
+        # @return [Boolean]
+        attr_accessor :ibft
+
+        # Reads all the sessions
+        #
+        # The sessions without connections (e.g., being torn down) are skipped.
+        #
+        # @param root [String] Root directory of sysfs, for testing purposes
+        # @return [Array<Session>]
+        def self.all(root: "/")
+          ibft_targets = Dir.glob(File.join(root, IBFT_DIR, "target*", "target-name"))
+            .map { |f| File.read(f).strip }
+
+          Dir.glob(File.join(root, SESSIONS_DIR, "session*"))
+            .sort_by { |d| d[/\d+\z/].to_i }
+            .map { |d| SysfsReader.new(d, root).session }
+            .compact
+            .each { |s| s.ibft = ibft_targets.include?(s.target) }
+        end
+
+        def ibft?
+          !!ibft
+        end
+
+        # Whether the session belongs to the given node
+        #
+        # @param node [Node]
+        # @return [Boolean]
+        def node?(node)
+          node.target == target && node.address == address && node.port == port &&
+            (node.interface || "default") == interface
+        end
+
+        # Reader of a session from its sysfs directory
+        class SysfsReader
+          # @param session_dir [String]
+          # @param root [String]
+          def initialize(session_dir, root)
+            @session_dir = session_dir
+            id = File.basename(session_dir).delete_prefix("session")
+            @connection_dirs = Dir.glob(File.join(root, CONNECTIONS_DIR, "connection#{id}:*")).sort
+          end
+
+          # @return [Session, nil] nil if the session has no connections
+          def session
+            return if connection_dirs.empty?
+
+            Session.new.tap do |session|
+              session.target = attribute(session_dir, "targetname")
+              session.state = attribute(session_dir, "state")
+              session.interface = attribute(session_dir, "ifacename") || "default"
+              session.address = connection_attribute("persistent_address", "address")
+              session.port = connection_attribute("persistent_port", "port").to_i
+              session.connections = connection_dirs.size
+              session.header_digest = connection_attribute("header_digest") || "None"
+              session.data_digest = connection_attribute("data_digest") || "None"
+              session.parameters = parameters(session_dir, SESSION_PARAMETERS)
+                .merge(parameters(connection_dirs.first, CONNECTION_PARAMETERS))
+            end
+          end
+
+        private
+
+          # @return [String]
+          attr_reader :session_dir
+
+          # @return [Array<String>]
+          attr_reader :connection_dirs
+
+          # Value of the first existing attribute of the first connection
+          #
+          # @param names [Array<String>]
+          # @return [String, nil]
+          def connection_attribute(*names)
+            names.lazy.map { |n| attribute(connection_dirs.first, n) }.find(&:itself)
+          end
+
+          # @param dir [String]
+          # @param names [Hash<String, String>] sysfs attributes and parameter names
+          # @return [Hash<String, String>]
+          def parameters(dir, names)
+            names.each_with_object({}) do |(attribute_name, name), parameters|
+              value = attribute(dir, attribute_name)
+              next unless value
+
+              value = (value == "1") ? "Yes" : "No" if BOOLEAN_PARAMETERS.include?(name)
+              parameters[name] = value
+            end
+          end
+
+          # Value of a sysfs attribute
+          #
+          # @param dir [String]
+          # @param name [String]
+          # @return [String, nil] nil if the attribute does not exist or it is empty
+          def attribute(dir, name)
+            path = File.join(dir, name)
+            return unless File.file?(path)
+
+            value = File.read(path).strip
+            (value.empty? || value == "(null)") ? nil : value
+          rescue SystemCallError
+            nil
+          end
+        end
+        private_constant :SysfsReader
+      end
+    end
+  end
+end
@@ -24,11 +24,16 @@ require "bootloader/proposal_client"
 require "y2storage/storage_manager"
 require "y2storage/clients/inst_prepdisk"
 require "agama/storage/actions_generator"
+require "agama/storage/bootloader"
 require "agama/storage/proposal"
 require "agama/storage/proposal_settings"
 require "agama/storage/callbacks"
 require "agama/storage/iscsi/manager"
+require "agama/storage/nvmeof/manager"
+require "agama/storage/caches"
 require "agama/storage/finisher"
+require "agama/storage/quotas"
+require "agama/storage/root_overlay"
 require "agama/storage/proposal_settings_reader"
 require "agama/issue"
 require "agama/with_locale"
@@ -37,6 +42,7 @@ require "agama/with_progress"
 require "agama/security"
 require "agama/dbus/clients/questions"
 require "agama/dbus/clients/software"
+require "yast2/execute"
 
 Yast.import "PackagesProposal"
 
@@ -49,6 +55,12 @@ module Agama
       include WithProgress
       include Yast::I18n
 
+      # Result codes of {#unlock_device}, as defined by the D-Bus API
+      UNLOCK_SUCCESS = 0
+      UNLOCK_DEVICE_NOT_FOUND = 1
+      UNLOCK_WRONG_PASSPHRASE = 2
+      UNLOCK_FAILED = 3
+
       # @return [Config]
       attr_reader :config
 
@@ -120,6 +132,30 @@ module Agama
         @on_probe_callbacks&.each(&:call)
       end
 
+      # Unlocks an existing encrypted (LUKS) device and probes the system again
+      #
+      # The device is opened with cryptsetup, so the probing includes it and its content. The
+      # passphrase is only passed through the standard input, so it is not logged.
+      #
+      # @param device [String] Name of the encrypted device (e.g., "/dev/sda2")
+      # @param passphrase [String]
+      # @return [Integer] One of the UNLOCK_* result codes
+      def unlock_device(device, passphrase)
+        return UNLOCK_DEVICE_NOT_FOUND unless luks?(device)
+
+        logger.info "Unlocking the encrypted device #{device}"
+        Yast::Execute.locally!(
+          "cryptsetup", "open", "--type", "luks", "--key-file=-", device, dm_name(device),
+          stdin: passphrase
+        )
+        probe
+        UNLOCK_SUCCESS
+      rescue Cheetah::ExecutionFailed => e
+        logger.error "Could not unlock #{device}: #{e.message}"
+        # cryptsetup exits with 2 when no key slot can be opened with the passphrase
+        (e.status&.exitstatus == 2) ? UNLOCK_WRONG_PASSPHRASE : UNLOCK_FAILED
+      end
+
       # Prepares the partitioning to install the system
       def install
         start_progress_with_size(4)
@@ -127,6 +163,8 @@ module Agama
           # first make bootloader proposal to be sure that required packages are installed
           proposal = ::Bootloader::ProposalClient.new.make_proposal({})
           logger.debug "Bootloader proposal #{proposal.inspect}"
+          # then apply the user settings on top of it
+          bootloader.apply_proposal
         end
         progress.step(_("Adding storage-related packages")) { add_packages }
         progress.step(_("Preparing the storage devices")) { perform_storage_actions }
@@ -139,7 +177,13 @@ module Agama
 
       # Performs the final steps on the target file system(s)
       def finish
-        Finisher.new(logger, config, security).run
+        settings = proposal.guided_settings
+        quotas = Quotas.new(settings&.volumes || [], logger)
+        root_overlay = RootOverlay.new(settings, logger)
+        caches = Caches.new(settings&.caches || [], logger)
+        Finisher.new(
+          logger, config, security, bootloader, nvmeof, quotas, root_overlay, caches
+        ).run
       end
 
       # Storage proposal manager
@@ -149,6 +193,13 @@ module Agama
         @proposal ||= Proposal.new(config, logger: logger)
       end
 
+      # Bootloader settings
+      #
+      # @return [Storage::Bootloader]
+      def bootloader
+        @bootloader ||= Bootloader.new(config, logger)
+      end
+
       # iSCSI manager
       #
       # @return [Storage::ISCSI::Manager]
@@ -156,6 +207,13 @@ module Agama
         @iscsi ||= ISCSI::Manager.new(logger: logger)
       end
 
+      # NVMe-oF manager
+      #
+      # @return [Storage::NVMeoF::Manager]
+      def nvmeof
+        @nvmeof ||= NVMeoF::Manager.new(logger: logger)
+      end
+
       # Returns the client to ask the software service
       #
       # @return [Agama::DBus::Clients::Software]
@@ -203,6 +261,26 @@ module Agama
         Y2Storage::StorageManager.instance.activate(callbacks)
       end
 
+      # Whether the given device exists and contains a LUKS header
+      #
+      # @param device [String]
+      # @return [Boolean]
+      def luks?(device)
+        Yast::Execute.locally!("cryptsetup", "isLuks", device)
+        true
+      rescue Cheetah::ExecutionFailed
+        false
+      end
+
+      # Name of the device mapper device for the given encrypted device, following the YaST
+      # convention (e.g., "cr_sda2")
+      #
+      # @param device [String]
+      # @return [String]
+      def dm_name(device)
+        "cr_#{File.basename(device)}"
+      end
+
       # Probes the devices
       def probe_devices
         callbacks = Y2Storage::Callbacks::UserProbe.new
@@ -237,7 +315,19 @@ module Agama
         callbacks = Callbacks::Commit.new(questions_client, logger: logger)
 
         client = Y2Storage::Clients::InstPrepdisk.new(commit_callbacks: callbacks)
-        client.run == :next
+        success = client.run == :next
+        write_encryption_key_file if success
+        success
+      end
+
+      # Adds the key file to the encrypted devices created by the proposal, if any
+      def write_encryption_key_file
+        encryption = proposal.guided_settings&.encryption
+        return unless encryption&.key_file && encryption.encrypt?
+
+        staging = Y2Storage::StorageManager.instance.staging
+        created = staging.encryptions.reject(&:exists_in_probed?)
+        encryption.key_file.write(created, encryption.password, logger)
       end
 
       # Recalculates the list of issues
@@ -265,6 +355,7 @@ module Agama
 
         y2storage_issues.map do |y2storage_issue|
           Issue.new(y2storage_issue.message,
+            kind:     :probing,
             details:  y2storage_issue.details,
             source:   Issue::Source::SYSTEM,
             severity: Issue::Severity::WARN)
@@ -278,6 +369,7 @@ module Agama
         return unless deprecated_system?
 
         Issue.new("The system devices have changed",
+          kind:     :deprecated_system,
           source:   Issue::Source::SYSTEM,
           severity: Issue::Severity::ERROR)
       end
@@ -289,6 +381,7 @@ module Agama
         return if proposal.available_devices.any?
 
         Issue.new("There is no suitable device for installation",
+          kind:     :no_available_devices,
           source:   Issue::Source::SYSTEM,
           severity: Issue::Severity::ERROR)
       end
@@ -0,0 +1,220 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+require "fileutils"
+require "json"
+require "yast"
+require "yast2/execute"
+
+Yast.import "Installation"
+
+module Agama
+  module Storage
+    module NVMeoF
+      # NVMe-oF target (a subsystem exported through a transport address)
+      Target = Struct.new(:transport, :traddr, :trsvcid, :subnqn)
+
+      # Controller connected to a target
+      Controller = Struct.new(:name, :target, :state)
+
+      # Manager for NVMe over Fabrics, based on nvme-cli
+      #
+      # The result codes are the ones of the D-Bus API. The generic failures include the nvme-cli
+      # output as details.
+      class Manager
+        SUCCESS = 0
+        FAILED = 1
+        ALREADY_CONNECTED = 4
+        TRANSPORT_UNAVAILABLE = 5
+        UNKNOWN_CONTROLLER = 6
+
+        # Directory of the NVMe controllers in sysfs
+        SYSFS_DIR = "/sys/class/nvme"
+        private_constant :SYSFS_DIR
+
+        # Directory of the RDMA devices in sysfs
+        RDMA_DIR = "/sys/class/infiniband"
+        private_constant :RDMA_DIR
+
+        TRANSPORTS = ["tcp", "rdma"].freeze
+        private_constant :TRANSPORTS
+
+        # Files identifying the host, copied to the installed system
+        HOST_FILES = ["/etc/nvme/hostnqn", "/etc/nvme/hostid"].freeze
+        private_constant :HOST_FILES
+
+        CONFIG_FILE = "/etc/nvme/config.json"
+        private_constant :CONFIG_FILE
+
+        # @param logger [Logger, nil]
+        def initialize(logger: nil)
+          @logger = logger || ::Logger.new($stdout)
+          @on_connections_change_callbacks = []
+        end
+
+        # Asks a discovery controller for the subsystems it exports
+        #
+        # @param target [Target] Address of the discovery controller
+        # @return [Array(Integer, String, Array<Target>)] Result code, details of the failure
+        #   and discovered targets
+        def discover(target)
+          return [TRANSPORT_UNAVAILABLE, "", []] unless transport_available?(target.transport)
+
+          output = nvme("discover", *address_args(target), "--output-format=json")
+          records = JSON.parse(output).fetch("records", [])
+            .select { |r| r["subtype"] == "nvme subsystem" }
+            .map { |r| Target.new(r["trtype"], r["traddr"], r["trsvcid"], r["subnqn"]) }
+          [SUCCESS, "", records]
+        rescue Cheetah::ExecutionFailed => e
+          [FAILED, e.stderr.to_s, []]
+        rescue JSON::ParserError => e
+          [FAILED, e.message, []]
+        end
+
+        # Connects to a subsystem
+        #
+        # @param target [Target]
+        # @return [Array(Integer, String)] Result code and details of the failure
+        def connect(target)
+          return [TRANSPORT_UNAVAILABLE, ""] unless transport_available?(target.transport)
+          return [ALREADY_CONNECTED, ""] if controllers.any? { |c| c.target == target }
+
+          nvme("connect", *address_args(target), "--nqn=#{target.subnqn}")
+          connections_changed
+          [SUCCESS, ""]
+        rescue Cheetah::ExecutionFailed => e
+          [FAILED, e.stderr.to_s]
+        end
+
+        # Disconnects a controller
+        #
+        # @param name [String] Controller name (e.g., "nvme1")
+        # @return [Array(Integer, String)] Result code and details of the failure
+        def disconnect(name)
+          return [UNKNOWN_CONTROLLER, ""] unless controllers.any? { |c| c.name == name }
+
+          nvme("disconnect", "--device=#{name}")
+          connections_changed
+          [SUCCESS, ""]
+        rescue Cheetah::ExecutionFailed => e
+          [FAILED, e.stderr.to_s]
+        end
+
+        # Controllers connected through a fabric (the PCIe ones are not included)
+        #
+        # @return [Array<Controller>]
+        def controllers
+          Dir.glob(File.join(SYSFS_DIR, "nvme*")).sort.filter_map do |dir|
+            transport = read_sysfs(dir, "transport")
+            next unless TRANSPORTS.include?(transport)
+
+            address = read_sysfs(dir, "address").split(",").to_h { |a| a.split("=", 2) }
+            target = Target.new(transport, address["traddr"], address["trsvcid"],
+              read_sysfs(dir, "subsysnqn"))
+            Controller.new(File.basename(dir), target, read_sysfs(dir, "state"))
+          end
+        end
+
+        # Writes the connections to the installed system, so they are restored on boot
+        #
+        # The host identifiers are copied and the nvmf-autoconnect service is enabled.
+        def write
+          connected = controllers
+          return if connected.empty?
+
+          HOST_FILES.select { |f| File.exist?(f) }.each { |f| copy_to_target(f) }
+          write_target_config(connected)
+          Yast::Execute.on_target!("systemctl", "enable", "nvmf-autoconnect.service")
+        end
+
+        # Registers a callback to be called when a controller is connected or disconnected
+        #
+        # @param block [Proc]
+        def on_connections_change(&block)
+          @on_connections_change_callbacks << block
+        end
+
+      private
+
+        # @return [Logger]
+        attr_reader :logger
+
+        def connections_changed
+          @on_connections_change_callbacks.each(&:call)
+        end
+
+        # @param transport [String]
+        # @return [Boolean]
+        def transport_available?(transport)
+          return false unless TRANSPORTS.include?(transport)
+          return true if transport == "tcp"
+
+          Dir.exist?(RDMA_DIR) && !Dir.empty?(RDMA_DIR)
+        end
+
+        # @param target [Target]
+        # @return [Array<String>]
+        def address_args(target)
+          args = ["--transport=#{target.transport}", "--traddr=#{target.traddr}"]
+          args << "--trsvcid=#{target.trsvcid}" if target.trsvcid
+          args
+        end
+
+        # @return [String] Output of the command
+        def nvme(*args)
+          logger.info "Running nvme #{args.join(" ")}"
+          Yast::Execute.locally!("nvme", *args, stdout: :capture)
+        end
+
+        def read_sysfs(dir, name)
+          path = File.join(dir, name)
+          File.exist?(path) ? File.read(path).strip : ""
+        end
+
+        # Writes the libnvme configuration with the connected subsystems
+        #
+        # @param connected [Array<Controller>]
+        def write_target_config(connected)
+          subsystems = connected.group_by { |c| c.target.subnqn }.map do |nqn, ctrls|
+            ports = ctrls.map do |c|
+              { "transport" => c.target.transport, "traddr" => c.target.traddr,
+                "trsvcid" => c.target.trsvcid }
+            end
+            { "nqn" => nqn, "ports" => ports }
+          end
+          host = { "subsystems" => subsystems }
+          hostnqn = HOST_FILES.first
+          host["hostnqn"] = File.read(hostnqn).strip if File.exist?(hostnqn)
+
+          file = File.join(Yast::Installation.destdir, CONFIG_FILE)
+          FileUtils.mkdir_p(File.dirname(file))
+          File.write(file, JSON.pretty_generate([host]))
+        end
+
+        # @param path [String]
+        def copy_to_target(path)
+          target = File.join(Yast::Installation.destdir, path)
+          FileUtils.mkdir_p(File.dirname(target))
+          FileUtils.cp(path, target)
+        end
+      end
+    end
+  end
+end
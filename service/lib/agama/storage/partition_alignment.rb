@@ -0,0 +1,140 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require "yast"
+require "y2storage"
+
+module Agama
+  module Storage
+    # Alignment of the partitions created by the proposal
+    #
+    # Y2Storage always aligns the new partitions to the optimal I/O size of the device. Any other
+    # alignment is applied on top of the result, see {#apply}.
+    class PartitionAlignment
+      include Yast::I18n
+
+      MODES = [:optimal, :cylinder, :explicit].freeze
+      private_constant :MODES
+
+      # Grain of the classic cylinder boundaries (255 heads, 63 sectors of 512 bytes)
+      CYLINDER_GRAIN = 255 * 63 * 512
+      private_constant :CYLINDER_GRAIN
+
+      # Grain of the optimal alignment
+      OPTIMAL_GRAIN = Y2Storage::DiskSize.MiB(1).to_i
+      private_constant :OPTIMAL_GRAIN
+
+      # @return [Symbol] :optimal, :cylinder or :explicit
+      attr_accessor :mode
+
+      # Grain in bytes, used only for :explicit
+      #
+      # @return [Integer, nil]
+      attr_accessor :explicit_grain
+
+      # All possible modes
+      #
+      # @return [Array<Symbol>]
+      def self.modes
+        MODES
+      end
+
+      def initialize
+        textdomain "agama"
+
+        @mode = :optimal
+      end
+
+      # Whether the partitions are aligned as Y2Storage does
+      #
+      # @return [Boolean]
+      def optimal?
+        mode == :optimal
+      end
+
+      # Grain in bytes
+      #
+      # @return [Integer]
+      def grain
+        case mode
+        when :cylinder
+          CYLINDER_GRAIN
+        when :explicit
+          explicit_grain || OPTIMAL_GRAIN
+        else
+          OPTIMAL_GRAIN
+        end
+      end
+
+      # Rounds up the given size to the grain, adding an extra grain
+      #
+      # A region of that size always contains an aligned region which is at least as big as the
+      # given size, no matter where the region starts.
+      #
+      # @param size [Y2Storage::DiskSize, nil]
+      # @return [Y2Storage::DiskSize, nil]
+      def padded_size(size)
+        return size if size.nil? || optimal? || size.unlimited?
+
+        Y2Storage::DiskSize.new((((size.to_i + grain - 1) / grain) + 1) * grain)
+      end
+
+      # Aligns the start and the end of the new partitions of the given devicegraph
+      #
+      # The partitions are shrunk to the biggest aligned region they contain. Extended partitions
+      # are kept as they are because they must contain their logical partitions.
+      #
+      # @param devicegraph [Y2Storage::Devicegraph]
+      # @return [String, nil] Problem which prevents aligning the partitions, if any
+      def apply(devicegraph)
+        return if optimal?
+
+        partitions = devicegraph.partitions.reject do |partition|
+          partition.exists_in_probed? || partition.type.is?(:extended)
+        end
+        partitions.each do |partition|
+          next if align(partition)
+
+          return format(_("%{device} cannot be aligned to %{grain} bytes"),
+            device: partition.name, grain: grain)
+        end
+        nil
+      end
+
+    private
+
+      # @param partition [Y2Storage::Partition]
+      # @return [Boolean] false if the partition contains no aligned region
+      def align(partition)
+        region = partition.region
+        block_size = region.block_size.to_i
+        blocks = (grain + block_size - 1) / block_size
+
+        first = ((region.start + blocks - 1) / blocks) * blocks
+        last = ((region.end + 1) / blocks) * blocks
+        return false if last <= first
+
+        partition.region = Y2Storage::Region.create(first, last - first, region.block_size)
+        true
+      end
+    end
+  end
+end
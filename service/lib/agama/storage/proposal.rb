@@ -285,6 +285,7 @@ module Agama
       def failed_issue
         Issue.new(
           _("Cannot accommodate the required file systems for installation"),
+          kind:     :proposal_failed,
           source:   Issue::Source::CONFIG,
           severity: Issue::Severity::ERROR
         )
@@ -296,6 +297,7 @@ module Agama
       def exception_issue(error)
         Issue.new(
           _("A problem ocurred while calculating the storage setup"),
+          kind:     :proposal_exception,
           details:  error.message,
           source:   Issue::Source::CONFIG,
           severity: Issue::Severity::ERROR
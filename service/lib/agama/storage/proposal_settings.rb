@@ -19,9 +19,12 @@
 # To contact SUSE LLC about this file by physical or electronic mail, you may
 # find current contact information at www.suse.com.
 
+require "agama/storage/cache_device"
 require "agama/storage/configs/boot"
 require "agama/storage/device_settings"
 require "agama/storage/encryption_settings"
+require "agama/storage/explicit_partition"
+require "agama/storage/partition_alignment"
 require "agama/storage/proposal_settings_conversions"
 require "agama/storage/space_settings"
 
@@ -54,12 +57,64 @@ module Agama
       # @return [Array<Volume>]
       attr_accessor :volumes
 
+      # Whether removable devices (e.g., USB sticks) can be automatically chosen as target.
+      #
+      # @return [Boolean]
+      attr_accessor :include_removable
+
+      # Removable devices which were not considered when choosing the target device.
+      #
+      # @return [Array<String>]
+      attr_accessor :excluded_devices
+
+      # Partitions to create at explicit positions, after calculating the proposal.
+      #
+      # @return [Array<ExplicitPartition>]
+      attr_accessor :partitions
+
+      # How the file systems are referenced in the fstab, unless their volume sets it.
+      #
+      # @return [Y2Storage::Filesystems::MountByType, nil] nil to use the default of Y2Storage
+      attr_accessor :mount_by
+
+      # Whether the root file system is mounted read-only, with a writable overlay.
+      #
+      # @return [Boolean]
+      attr_accessor :read_only_root
+
+      # Directory keeping the changes done on top of the read-only root file system.
+      #
+      # @return [String, nil]
+      attr_accessor :root_overlay
+
+      # Partition table types requested for some disks, indexed by disk name.
+      #
+      # @return [Hash<String, Y2Storage::PartitionTables::Type>]
+      attr_accessor :partition_tables
+
+      # Alignment of the new partitions.
+      #
+      # @return [PartitionAlignment]
+      attr_accessor :alignment
+
+      # Cached devices to create, after calculating the proposal.
+      #
+      # @return [Array<CacheDevice>]
+      attr_accessor :caches
+
       def initialize
         @device = DeviceSettings::Disk.new
         @boot = Configs::Boot.new
         @encryption = EncryptionSettings.new
         @space = SpaceSettings.new
         @volumes = []
+        @include_removable = false
+        @excluded_devices = []
+        @partitions = []
+        @read_only_root = false
+        @caches = []
+        @partition_tables = {}
+        @alignment = PartitionAlignment.new
       end
 
       # All devices involved in the installation.
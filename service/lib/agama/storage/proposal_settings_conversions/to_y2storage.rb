@@ -22,6 +22,7 @@
 require "y2storage"
 require "agama/storage/device_settings"
 require "agama/storage/device_shrinking"
+require "agama/storage/esp_settings"
 require "agama/storage/volume_templates_builder"
 
 module Agama
@@ -49,6 +50,7 @@ module Agama
             encryption_conversion(target)
             space_policy_conversion(target)
             volumes_conversion(target)
+            alignment_conversion(target)
           end
         end
 
@@ -147,11 +149,25 @@ module Agama
             when :delete
               Y2Storage::SpaceActions::Delete.new(device)
             when :resize
-              Y2Storage::SpaceActions::Resize.new(device)
+              resize_action(device)
             end
           end
         end
 
+        # Resize action for the given device
+        #
+        # If a target size is set, the device is resized exactly to that size.
+        #
+        # @param device [String]
+        # @return [Y2Storage::SpaceActions::Resize]
+        def resize_action(device)
+          size = settings.space.resize_sizes[device]
+          return Y2Storage::SpaceActions::Resize.new(device) unless size
+
+          disk_size = Y2Storage::DiskSize.new(size)
+          Y2Storage::SpaceActions::Resize.new(device, min_size: disk_size, max_size: disk_size)
+        end
+
         # @param target [Y2Storage::ProposalSettings]
         def volumes_conversion(target)
           target.swap_reuse = :none
@@ -164,10 +180,54 @@ module Agama
 
           target.volumes = volumes + disabled_volumes
 
+          esp_volume_conversion(target)
           volume_device_conversion(target)
           fallbacks_conversion(target)
         end
 
+        # Pads the sizes of the volumes, so the partitions keep them once aligned
+        #
+        # @see PartitionAlignment#apply
+        #
+        # @param target [Y2Storage::ProposalSettings]
+        def alignment_conversion(target)
+          alignment = settings.alignment
+          return if alignment.optimal?
+
+          target.volumes.each do |volume|
+            volume.min_size = alignment.padded_size(volume.min_size)
+            volume.desired_size = alignment.padded_size(volume.desired_size)
+            volume.max_size = alignment.padded_size(volume.max_size)
+            volume.max_size_lvm = alignment.padded_size(volume.max_size_lvm)
+          end
+        end
+
+        # Sets the size of the ESP to create, if requested
+        #
+        # The boot requirements of the proposal take the size of the ESP from the volume for its
+        # mount point. That volume is not proposed as a regular one.
+        #
+        # @param target [Y2Storage::ProposalSettings]
+        def esp_volume_conversion(target)
+          esp = settings.boot.esp
+          return unless esp.mode == :create && esp.size
+
+          mount_path = EspSettings::DEFAULT_MOUNT_PATH
+          return if target.volumes.any? { |v| v.mount_point == mount_path && v.proposed? }
+
+          size = Y2Storage::DiskSize.new(esp.size)
+          volume = Y2Storage::VolumeSpecification.new({}).tap do |spec|
+            spec.mount_point = mount_path
+            spec.fs_type = Y2Storage::Filesystems::Type::VFAT
+            spec.min_size = size
+            spec.desired_size = size
+            spec.max_size = size
+            spec.proposed = false
+            spec.proposed_configurable = false
+          end
+          target.volumes = target.volumes.reject { |v| v.mount_point == mount_path } + [volume]
+        end
+
         # @return [Array<Agama::Storage::Volume>]
         def missing_volumes
           mount_paths = settings.volumes.map(&:mount_path)
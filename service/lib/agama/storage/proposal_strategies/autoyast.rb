@@ -84,6 +84,7 @@ module Agama
         def agama_issue(ay_issue)
           Issue.new(
             ay_issue.message,
+            kind:     :autoyast,
             source:   Issue::Source::CONFIG,
             severity: ay_issue.warn? ? Issue::Severity::WARN : Issue::Severity::ERROR
           )
@@ -53,6 +53,16 @@ module Agama
           select_target_device(input_settings) if missing_target_device?(input_settings)
           proposal = guided_proposal(input_settings)
           proposal.propose
+          @partition_issues = []
+          unless proposal.failed?
+            encrypt_volume_passwords(proposal)
+            encrypt_random_key_swap(proposal)
+            align_partitions(proposal)
+            create_explicit_partitions(proposal)
+            create_caches(proposal)
+            adjust_esp(proposal)
+            adjust_filesystems(proposal)
+          end
         ensure
           storage_manager.proposal = proposal
           @settings = ProposalSettingsConversions::FromY2Storage
@@ -62,7 +72,7 @@ module Agama
 
         # @see Base#issues
         def issues
-          return [] unless storage_manager.proposal.failed?
+          return @partition_issues || [] unless storage_manager.proposal.failed?
 
           [target_device_issue, missing_devices_issue].compact
         end
@@ -75,9 +85,17 @@ module Agama
 
         # Selects the first available device as target device for installation.
         #
+        # Removable devices (e.g., USB sticks) are skipped unless they are explicitly included.
+        #
         # @param settings [ProposalSettings]
         def select_target_device(settings)
-          device = available_devices.first&.name
+          candidates = available_devices
+          unless settings.include_removable
+            excluded = candidates.select { |d| removable?(d) }
+            settings.excluded_devices = excluded.map(&:name)
+            candidates -= excluded
+          end
+          device = candidates.first&.name
           return unless device
 
           case settings.device
@@ -90,6 +108,16 @@ module Agama
           end
         end
 
+        # Whether the given device is removable (e.g., a USB stick or an SD card)
+        #
+        # @param device [Y2Storage::BlkDevice]
+        # @return [Boolean]
+        def removable?(device)
+          usb = device.respond_to?(:transport) && device.transport&.is?(:usb)
+          sd_card = device.respond_to?(:sd_card?) && device.sd_card?
+          !!(usb || sd_card)
+        end
+
         # Whether the given settings has no target device for the installation.
         #
         # @param settings [ProposalSettings]
@@ -103,6 +131,160 @@ module Agama
           end
         end
 
+        # Sets the passwords of the volumes which do not use the default one
+        #
+        # The devices created by the proposal for those volumes are encrypted, if they are not yet
+        # (e.g., there is no default password), with the global encryption method. The logical
+        # volumes of an encrypted volume group share the passphrase of its physical volumes.
+        #
+        # @param proposal [Y2Storage::MinGuidedProposal]
+        def encrypt_volume_passwords(proposal)
+          encryption = input_settings.encryption
+
+          proposal.devices.filesystems.each do |filesystem|
+            password = encryption.volume_password(filesystem.mount_path.to_s)
+            next unless password
+
+            device = filesystem.blk_devices.first
+            next if device.exists_in_probed?
+
+            if device.is?(:encryption)
+              device.password = password
+            elsif device.ancestors.any? { |d| d.is?(:encryption) }
+              logger.warn "#{filesystem.mount_path} is in an encrypted volume group, so it " \
+                          "cannot use its own passphrase"
+            else
+              logger.info "Encrypting #{device.name} with its own passphrase"
+              device.encrypt(method: encryption.method, password: password, **encryption_args)
+            end
+          end
+        end
+
+        # Additional arguments to encrypt devices with the global encryption method
+        #
+        # @return [Hash]
+        def encryption_args
+          encryption = input_settings.encryption
+          return {} unless encryption.method.is?(:luks2) && encryption.pbkd_function
+
+          { pbkdf: encryption.pbkd_function }
+        end
+
+        # Encrypts the swap with a random key regenerated on each boot, if requested
+        #
+        # The swap devices created by the proposal are encrypted again (or for the first time) using
+        # the random swap method, so they do not need a passphrase.
+        #
+        # @param proposal [Y2Storage::MinGuidedProposal]
+        def encrypt_random_key_swap(proposal)
+          return unless input_settings.volumes.any?(&:random_key_swap?)
+
+          proposal.devices.filesystems.select { |f| f.mount_path == "swap" }.each do |swap|
+            device = swap.blk_devices.first
+            next if device.is?(:encryption) && device.method.is?(:random_swap)
+
+            plain = device.is?(:encryption) ? device.blk_device : device
+            next if plain.exists_in_probed?
+
+            logger.info "Encrypting #{plain.name} with a random key"
+            plain.remove_descendants
+            filesystem = plain
+              .encrypt(method: Y2Storage::EncryptionMethod::RANDOM_SWAP)
+              .create_filesystem(Y2Storage::Filesystems::Type::SWAP)
+            filesystem.mount_path = "swap"
+          end
+        end
+
+        # Creates the partitions requested at explicit positions
+        #
+        # They are created in the free space left by the proposal. The partitions which cannot be
+        # created are reported as issues.
+        #
+        # @param proposal [Y2Storage::MinGuidedProposal]
+        def create_explicit_partitions(proposal)
+          input_settings.partitions.each do |partition|
+            error = partition.create(proposal.devices)
+            next unless error
+
+            logger.warn "Cannot create the partition: #{error}"
+            @partition_issues << Issue.new(error,
+              kind:     :invalid_partition,
+              source:   Issue::Source::CONFIG,
+              severity: Issue::Severity::ERROR)
+          end
+        end
+
+        # Creates the requested cached devices
+        #
+        # The cached devices which cannot be created are reported as issues.
+        #
+        # @param proposal [Y2Storage::MinGuidedProposal]
+        def create_caches(proposal)
+          input_settings.caches.each do |cache|
+            error = cache.create(proposal.devices)
+            next unless error
+
+            logger.warn "Cannot create the cached device: #{error}"
+            @partition_issues << Issue.new(error,
+              kind:     :invalid_cache,
+              source:   Issue::Source::CONFIG,
+              severity: Issue::Severity::ERROR)
+          end
+        end
+
+        # Aligns the new partitions, if an alignment other than the default one is requested
+        #
+        # @param proposal [Y2Storage::MinGuidedProposal]
+        def align_partitions(proposal)
+          error = input_settings.alignment.apply(proposal.devices)
+          return unless error
+
+          logger.warn "Cannot align the partitions: #{error}"
+          @partition_issues << Issue.new(error,
+            kind:     :invalid_alignment,
+            source:   Issue::Source::CONFIG,
+            severity: Issue::Severity::ERROR)
+        end
+
+        # Reuses or creates the EFI System Partition, if requested
+        #
+        # The settings are ignored if the system does not boot using UEFI.
+        #
+        # @param proposal [Y2Storage::MinGuidedProposal]
+        def adjust_esp(proposal)
+          esp = input_settings.boot.esp
+          return if esp.auto? || !input_settings.boot.configure? || !storage_manager.arch.efiboot?
+
+          error = esp.apply(proposal.devices)
+          return unless error
+
+          logger.warn "Cannot set the EFI System Partition: #{error}"
+          @partition_issues << Issue.new(error,
+            kind:     :invalid_esp,
+            source:   Issue::Source::CONFIG,
+            severity: Issue::Severity::ERROR)
+        end
+
+        # Sets the labels of the new file systems and how the file systems are referenced in the
+        # fstab
+        #
+        # The settings of a volume take precedence over the global ones. The swap encrypted with a
+        # random key is always referenced by its device name.
+        #
+        # @param proposal [Y2Storage::MinGuidedProposal]
+        def adjust_filesystems(proposal)
+          proposal.devices.filesystems.each do |filesystem|
+            next unless filesystem.mount_point
+
+            volume = input_settings.volumes.find { |v| v.mount_path == filesystem.mount_path }
+            next if volume&.random_key_swap?
+
+            filesystem.label = volume.label if volume&.label && !filesystem.exists_in_probed?
+            mount_by = volume&.mount_by || input_settings.mount_by
+            filesystem.mount_point.mount_by = mount_by if mount_by
+          end
+        end
+
         # Instance of the Y2Storage proposal to be used to run the calculation.
         #
         # @param settings [Y2Storage::ProposalSettings]
@@ -110,11 +292,34 @@ module Agama
         def guided_proposal(settings)
           Y2Storage::MinGuidedProposal.new(
             settings:      settings.to_y2storage(config: config),
-            devicegraph:   probed_devicegraph,
+            devicegraph:   initial_devicegraph(settings),
             disk_analyzer: disk_analyzer
           )
         end
 
+        # Devicegraph to start the proposal from
+        #
+        # The disks requested to use another type of partition table get a new empty one, so the
+        # proposal creates the partitions on it.
+        #
+        # @param settings [ProposalSettings]
+        # @return [Y2Storage::Devicegraph, nil]
+        def initial_devicegraph(settings)
+          return probed_devicegraph if settings.partition_tables.empty? || !probed_devicegraph
+
+          probed_devicegraph.dup.tap do |devicegraph|
+            settings.partition_tables.each do |name, type|
+              disk = devicegraph.find_by_name(name)
+              next if disk.nil? || !disk.respond_to?(:partition_table)
+              next if disk.partition_table&.type == type
+
+              logger.info "Creating a new #{type} partition table on #{name}"
+              disk.remove_descendants
+              disk.create_partition_table(type)
+            end
+          end
+        end
+
         # Returns an issue if there is no target device.
         #
         # @return [Issue, nil]
@@ -122,6 +327,7 @@ module Agama
           return unless missing_target_device?(settings)
 
           Issue.new(_("No device selected for installation"),
+            kind:     :no_target_device,
             source:   Issue::Source::CONFIG,
             severity: Issue::Severity::ERROR)
         end
@@ -144,6 +350,7 @@ module Agama
               ),
               devices: missing.join(", ")
             ),
+            kind:     :missing_devices,
             source:   Issue::Source::CONFIG,
             severity: Issue::Severity::ERROR
           )
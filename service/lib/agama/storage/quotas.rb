@@ -0,0 +1,148 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+
+require "fileutils"
+require "yast"
+require "yast2/execute"
+
+Yast.import "Installation"
+
+module Agama
+  module Storage
+    # Enables the quotas of the volumes in the target system and sets their limits
+    #
+    # The XFS projects are numbered in order, starting at 1, and named after their full path (e.g.,
+    # "home_alice" for "alice" in "/home").
+    class Quotas
+      # File system supporting each type of quotas
+      FILESYSTEMS = { qgroups: :btrfs, project: :xfs }.freeze
+      private_constant :FILESYSTEMS
+
+      PROJECTS_FILE = "/etc/projects"
+      private_constant :PROJECTS_FILE
+
+      PROJID_FILE = "/etc/projid"
+      private_constant :PROJID_FILE
+
+      # @param volumes [Array<Volume>] Volumes of the proposal, only the ones with quotas are used
+      # @param logger [Logger]
+      def initialize(volumes, logger)
+        @logger = logger
+        @volumes = volumes.select { |v| v.quota && supported?(v) }
+      end
+
+      # Whether any volume uses quotas
+      #
+      # @return [Boolean]
+      def any?
+        volumes.any?
+      end
+
+      # Enables the quotas and sets the limits
+      #
+      # The file systems must be mounted in the target system. A limit which cannot be set is
+      # logged and the rest of them are still set.
+      def write
+        volumes.select { |v| v.quota.type == :qgroups }.each { |v| write_qgroups(v) }
+        write_projects(volumes.select { |v| v.quota.type == :project })
+      end
+
+    private
+
+      # @return [Array<Volume>]
+      attr_reader :volumes
+
+      # @return [Logger]
+      attr_reader :logger
+
+      # Whether the file system of the volume supports its type of quotas
+      #
+      # @param volume [Volume]
+      # @return [Boolean]
+      def supported?(volume)
+        return true if volume.fs_type&.is?(FILESYSTEMS[volume.quota.type])
+
+        logger.warn "The file system of #{volume.mount_path} does not support " \
+                    "#{volume.quota.type} quotas"
+        false
+      end
+
+      # Enables the Btrfs quota groups and limits the subvolumes
+      #
+      # @param volume [Volume]
+      def write_qgroups(volume)
+        run("btrfs", "quota", "enable", volume.mount_path)
+        volume.quota.limits.each do |path, size|
+          run("btrfs", "qgroup", "limit", size.to_i.to_s, full_path(volume.mount_path, path))
+        end
+      end
+
+      # Defines the XFS projects and limits them
+      #
+      # The file systems must be mounted with the "prjquota" option.
+      #
+      # @param volumes [Array<Volume>]
+      def write_projects(volumes)
+        projects = volumes.flat_map do |volume|
+          volume.quota.limits.map do |path, size|
+            [volume.mount_path, full_path(volume.mount_path, path), size]
+          end
+        end
+        return if projects.empty?
+
+        names = projects.map { |_, path, _| path.delete_prefix("/").tr("/", "_") }
+        write_target_file(PROJECTS_FILE, projects.map.with_index(1) { |(_, p, _), i| "#{i}:#{p}" })
+        write_target_file(PROJID_FILE, names.map.with_index(1) { |name, i| "#{name}:#{i}" })
+        projects.zip(names).each do |(mount_path, _, size), name|
+          run("xfs_quota", "-x", "-c", "project -s #{name}", mount_path)
+          run("xfs_quota", "-x", "-c", "limit -p bhard=#{size.to_i} #{name}", mount_path)
+        end
+      end
+
+      # @param mount_path [String]
+      # @param path [String] Path relative to the mount path
+      # @return [String]
+      def full_path(mount_path, path)
+        "#{mount_path.chomp("/")}/#{path.gsub(%r{\A/+|/+\z}, "")}"
+      end
+
+      # Runs a command in the target system
+      #
+      # @param args [Array<String>]
+      def run(*args)
+        logger.info "Running #{args.join(" ")}"
+        Yast::Execute.on_target!(*args)
+      rescue Cheetah::ExecutionFailed => e
+        logger.error "Could not set the quotas: #{e.message}"
+      end
+
+      # @param path [String] Path in the target system
+      # @param lines [Array<String>]
+      def write_target_file(path, lines)
+        file = File.join(Yast::Installation.destdir, path)
+        logger.info "Writing #{file}"
+        FileUtils.mkdir_p(File.dirname(file))
+        File.write(file, lines.map { |l| "#{l}\n" }.join)
+      end
+    end
+  end
+end
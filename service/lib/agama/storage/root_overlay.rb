@@ -0,0 +1,104 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require "fileutils"
+require "yast"
+
+Yast.import "Installation"
+
+module Agama
+  module Storage
+    # Read-only root file system with a writable overlay
+    #
+    # The root file system is installed as usual and, at the end of the installation, the fstab of
+    # the target system is adapted: the root file system is mounted with the "ro" option and an
+    # overlay, keeping the changes in the overlay directory, is mounted on top of it.
+    class RootOverlay
+      FSTAB_FILE = "/etc/fstab"
+      private_constant :FSTAB_FILE
+
+      # @param settings [ProposalSettings, nil] Settings of the proposal, if any
+      # @param logger [Logger]
+      def initialize(settings, logger)
+        @overlay = settings&.root_overlay if settings&.read_only_root
+        @logger = logger
+      end
+
+      # Whether the root file system is read-only
+      #
+      # @return [Boolean]
+      def enabled?
+        !overlay.nil?
+      end
+
+      # Creates the overlay directories and adapts the fstab of the target system
+      #
+      # The file systems must be mounted in the target system.
+      def write
+        directory = overlay.chomp("/")
+        ["upper", "work"].each do |dir|
+          FileUtils.mkdir_p(File.join(Yast::Installation.destdir, directory, dir))
+        end
+
+        fstab = File.join(Yast::Installation.destdir, FSTAB_FILE)
+        lines = File.readlines(fstab).map { |l| read_only_root(l) }
+        lines << overlay_entry(directory)
+        logger.info "Mounting the root file system read-only with an overlay on #{directory}"
+        File.write(fstab, lines.join)
+      end
+
+    private
+
+      # @return [String, nil] Overlay directory (nil if the root file system is writable)
+      attr_reader :overlay
+
+      # @return [Logger]
+      attr_reader :logger
+
+      # Sets the "ro" option if the line is the entry of the root file system
+      #
+      # @param line [String]
+      # @return [String]
+      def read_only_root(line)
+        fields = line.split
+        return line if line.lstrip.start_with?("#") || fields[1] != "/" || fields.size < 4
+
+        options = fields[3].split(",") - ["defaults", "rw"]
+        fields[3] = (["ro"] + options).uniq.join(",")
+        "#{fields.join("  ")}\n"
+      end
+
+      # fstab entry mounting the overlay
+      #
+      # It is mounted after the file system containing the overlay directory.
+      #
+      # @param directory [String]
+      # @return [String]
+      def overlay_entry(directory)
+        options = [
+          "lowerdir=/", "upperdir=#{directory}/upper", "workdir=#{directory}/work",
+          "x-systemd.requires-mounts-for=#{directory}"
+        ]
+        "overlay  /  overlay  #{options.join(",")}  0  0\n"
+      end
+    end
+  end
+end
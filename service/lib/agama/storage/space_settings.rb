@@ -32,6 +32,13 @@ module Agama
       # @return [Hash] used only for :custom
       attr_accessor :actions
 
+      # Target size (in bytes) of the devices to resize, used only for :custom
+      #
+      # The devices with a :resize action but without a size are shrunk as much as needed.
+      #
+      # @return [Hash<String, Integer>]
+      attr_accessor :resize_sizes
+
       # All possible policies
       #
       # @return [Array<Symbol>]
@@ -41,6 +48,7 @@ module Agama
 
       def initialize
         @actions = {}
+        @resize_sizes = {}
         @policy = :keep
       end
     end
@@ -26,6 +26,7 @@ require "agama/storage/configs/btrfs"
 require "agama/storage/volume_conversions"
 require "agama/storage/volume_location"
 require "agama/storage/volume_outline"
+require "agama/storage/volume_quota"
 
 module Agama
   module Storage
@@ -84,6 +85,40 @@ module Agama
       attr_accessor :auto_size
       alias_method :auto_size?, :auto_size
 
+      # Relative share of the extra space the volume gets on top of {#min_size}, up to {#max_size}
+      #
+      # @return [Integer, nil] nil to use the default weight of the proposal
+      attr_accessor :weight
+
+      # How the volume is encrypted, if it is not according to the global encryption settings
+      #
+      # Only :random_key is relevant for the proposal, and only for swap.
+      #
+      # @return [Symbol, nil] :passphrase or :random_key
+      attr_accessor :encryption
+
+      # Free-text description of the volume, only informative
+      #
+      # @return [String, nil]
+      attr_accessor :description
+
+      # Label of the file system
+      #
+      # @return [String, nil]
+      attr_accessor :label
+
+      # How the file system is referenced in the fstab
+      #
+      # @return [Y2Storage::Filesystems::MountByType, nil] nil to use the global setting
+      attr_accessor :mount_by
+
+      # Quotas of the file system, if they are enabled
+      #
+      # Only :qgroups (Btrfs) and :project (XFS) quotas are supported.
+      #
+      # @return [VolumeQuota, nil]
+      attr_accessor :quota
+
       # @param mount_path [String]
       def initialize(mount_path)
         @mount_path = mount_path
@@ -111,6 +146,13 @@ module Agama
         outline.adaptive_sizes?
       end
 
+      # Whether the volume is swap encrypted with a random key regenerated on each boot
+      #
+      # @return [Boolean]
+      def random_key_swap?
+        mount_path == "swap" && encryption == :random_key
+      end
+
       # Creates a new volume object from a JSON hash according to schema.
       #
       # @param volume_json [Hash]
@@ -27,6 +27,10 @@ module Agama
     module VolumeConversions
       # Volume conversion to Y2Storage.
       class ToY2Storage
+        # Weight of the volumes which do not set it
+        DEFAULT_WEIGHT = 100
+        private_constant :DEFAULT_WEIGHT
+
         # @param volume [Agama::Storage::Volume]
         def initialize(volume)
           @volume = volume
@@ -43,7 +47,7 @@ module Agama
             target.proposed_configurable = !volume.outline.required?
             target.fs_types = volume.outline.filesystems
             target.fs_type = volume.fs_type if volume.fs_type
-            target.weight = 100
+            target.weight = volume.weight || DEFAULT_WEIGHT
             target.adjust_by_ram = volume.outline.adjust_by_ram?
 
             sizes_conversion(target)
@@ -0,0 +1,61 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+
+module Agama
+  module Storage
+    # Quotas of the file system of a volume
+    class VolumeQuota
+      # @see .types
+      TYPES = [:qgroups, :project].freeze
+      private_constant :TYPES
+
+      # Type of the quotas
+      #
+      # @return [Symbol] see {.types}
+      attr_reader :type
+
+      # Limits of the subvolumes (Btrfs) or directory trees (XFS), indexed by their path relative
+      # to the mount point of the volume
+      #
+      # The quotas are enabled even if there are no limits, so the space usage can be checked.
+      #
+      # @return [Hash<String, Y2Storage::DiskSize>]
+      attr_accessor :limits
+
+      # All possible values for #type:
+      #
+      #   - :qgroups Btrfs quota groups, one per subvolume
+      #   - :project XFS project quotas, one project per directory tree
+      #
+      # @return [Array<Symbol>]
+      def self.types
+        TYPES
+      end
+
+      # @param type [Symbol] see {.types}
+      def initialize(type)
+        @type = type
+        @limits = {}
+      end
+    end
+  end
+end
@@ -0,0 +1,57 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+
+require "fileutils"
+require "yast"
+
+Yast.import "Installation"
+
+module Agama
+  # Writes the kernel tunables (sysctl) of the installed system
+  class Sysctl
+    # File to write the tunables to, in the target system
+    SYSCTL_FILE = "/etc/sysctl.d/70-agama.conf"
+
+    # @return [Hash<String, String>] Tunables (e.g., "vm.swappiness" => "10")
+    attr_reader :tunables
+
+    # @param logger [Logger]
+    # @param tunables [Hash<String, String>]
+    def initialize(logger, tunables)
+      @logger = logger
+      @tunables = tunables
+    end
+
+    # Writes the tunables to the target system
+    #
+    # Nothing is written if there are no tunables.
+    def write
+      return if tunables.empty?
+
+      file = File.join(Yast::Installation.destdir, SYSCTL_FILE)
+      @logger.info "Writing the sysctl tunables to #{file}"
+      FileUtils.mkdir_p(File.dirname(file))
+      lines = tunables.map { |key, value| "#{key} = #{value}" }
+      File.write(file, (["# Written by Agama"] + lines).join("\n") + "\n")
+    end
+  end
+end
@@ -19,6 +19,7 @@
 # To contact SUSE LLC about this file by physical or electronic mail, you may
 # find current contact information at www.suse.com.
 
+require "json"
 require "yast"
 require "y2users"
 require "y2users/linux" # FIXME: linux is not in y2users file
@@ -39,9 +40,43 @@ module Agama
     def initialize(logger)
       textdomain "agama"
       @logger = logger
+      @groups = []
+      @first_user_expiration = {}
       update_issues
     end
 
+    # Groups to create in the target system
+    #
+    # Each group is described by a hash with the "name", the optional "gid" and the "members"
+    # (names of the users belonging to the group).
+    #
+    # @return [Array<Hash>]
+    attr_reader :groups
+
+    # Password and account expiration policy of the first user
+    #
+    # It contains the "PasswordExpireDays", "AccountExpireDate" and "Locked" values which are set.
+    #
+    # @return [Hash]
+    attr_reader :first_user_expiration
+
+    # Sets the groups to create from a JSON document, replacing the previous ones
+    #
+    # @param json [String] JSON array of groups, see {#groups}
+    # @return [Boolean] false if the document is not a valid list of groups
+    def load_groups_json(json)
+      groups = JSON.parse(json)
+      return false unless groups.is_a?(Array) && groups.all? { |g| valid_group?(g) }
+
+      @groups = groups.map do |group|
+        { "name" => group["name"], "gid" => group["gid"], "members" => group.fetch("members", []) }
+          .compact
+      end
+      true
+    rescue JSON::ParserError
+      false
+    end
+
     def root_ssh_key
       root_user.authorized_keys.first || ""
     end
@@ -87,6 +122,21 @@ module Agama
       config.users.reject(&:root?).first
     end
 
+    # Login shell for the users without an explicit one
+    #
+    # @return [String] Path to the shell or an empty string if it is not set
+    def default_shell
+      config.useradd&.shell || ""
+    end
+
+    # Sets the login shell for the users without an explicit one
+    #
+    # @param value [String] Path to the shell (an empty string unsets it)
+    def default_shell=(value)
+      config.useradd ||= Y2Users::UseraddConfig.new
+      config.useradd.shell = value.empty? ? nil : value
+    end
+
     # Clears the root password
     def remove_root_password
       root_user.password = nil
@@ -100,13 +150,20 @@ module Agama
     # @param user_name [String]
     # @param password [String]
     # @param auto_login [Boolean]
-    # @param _data [Hash]
+    # @param data [Hash] additional data ("Shell", "UID", "GID", "Home", "PasswordExpireDays",
+    #   "AccountExpireDate" and "Locked")
     # @return [Array] the list of fatal issues found
-    def assign_first_user(full_name, user_name, password, auto_login, _data)
+    def assign_first_user(full_name, user_name, password, auto_login, data)
       remove_first_user
 
       user = Y2Users::User.new(user_name)
       user.gecos = [full_name]
+      shell = data.fetch("Shell", "")
+      user.shell = shell unless shell.empty?
+      user.uid = data["UID"].to_s if data["UID"]
+      user.gid = data["GID"].to_s if data["GID"]
+      home = data.fetch("Home", "")
+      user.home = Y2Users::Home.new(home) unless home.empty?
       user.password = Y2Users::Password.create_plain(password)
       fatal_issues = user.issues.map.select(&:error?)
       return fatal_issues.map(&:message) unless fatal_issues.empty?
@@ -114,6 +171,7 @@ module Agama
       config.attach(user)
       config.login ||= Y2Users::LoginConfig.new
       config.login.autologin_user = auto_login ? user : nil
+      @first_user_expiration = data.slice(*EXPIRATION_KEYS)
       update_issues
       []
     end
@@ -122,6 +180,7 @@ module Agama
     def remove_first_user
       old_users = config.users.reject(&:root?)
       config.detach(old_users) unless old_users.empty?
+      @first_user_expiration = {}
       update_issues
     end
 
@@ -131,10 +190,13 @@ module Agama
           system_config = Y2Users::ConfigManager.instance.system(force_read: true)
           target_config = system_config.copy
           Y2Users::ConfigMerger.new(target_config, config).merge
+          add_groups(target_config)
+          check_first_user_ids(target_config)
 
           writer = Y2Users::Linux::Writer.new(target_config, system_config)
           issues = writer.write
           logger.error(issues.inspect) unless issues.empty?
+          write_first_user_expiration
         end
       end
     end
@@ -146,6 +208,7 @@ module Agama
       unless root_password? || root_ssh_key? || first_user?
         new_issues << Issue.new(
           _("Defining a user, setting the root password or a SSH public key is required"),
+          kind:     :missing_user,
           source:   Issue::Source::CONFIG,
           severity: Issue::Severity::ERROR
         )
@@ -156,6 +219,10 @@ module Agama
 
   private
 
+    # Keys of the additional data of the first user with the expiration policy
+    EXPIRATION_KEYS = ["PasswordExpireDays", "AccountExpireDate", "Locked"].freeze
+    private_constant :EXPIRATION_KEYS
+
     attr_reader :logger
 
     # Determines whether a first user is defined or not
@@ -165,6 +232,84 @@ module Agama
       config.users.reject(&:root?).any?
     end
 
+    # Whether the given object describes a group, see {#groups}
+    #
+    # @param group [Object]
+    # @return [Boolean]
+    def valid_group?(group)
+      group.is_a?(Hash) && group["name"].is_a?(String) &&
+        (group["gid"].nil? || group["gid"].is_a?(Integer)) &&
+        group.fetch("members", []).is_a?(Array)
+    end
+
+    # Adds the groups to create to the given config
+    #
+    # The members are added to the groups which already exist.
+    #
+    # @param target_config [Y2Users::Config]
+    def add_groups(target_config)
+      groups.each do |settings|
+        group = target_config.groups.by_name(settings["name"])
+        if group.nil?
+          logger.info "Creating the group #{settings["name"]}"
+          group = Y2Users::Group.new(settings["name"])
+          group.gid = settings["gid"].to_s if settings["gid"]
+          target_config.attach(group)
+        end
+        group.users_name = (Array(group.users_name) + settings["members"]).uniq
+      end
+    end
+
+    # Checks the IDs of the first user against the accounts of the target system
+    #
+    # A UID which is already used by another user is dropped, so the system assigns a new one.
+    # If no group uses the GID, a group named after the user is created with it.
+    #
+    # @param target_config [Y2Users::Config]
+    def check_first_user_ids(target_config)
+      user = first_user && target_config.users.by_name(first_user.name)
+      return unless user
+
+      if user.uid && target_config.users.any? { |u| u.uid == user.uid && u.name != user.name }
+        logger.warn "The UID #{user.uid} is already used, the system assigns another one"
+        user.uid = nil
+      end
+      return if user.gid.nil? || target_config.groups.any? { |g| g.gid == user.gid }
+
+      if target_config.groups.by_name(user.name)
+        logger.warn "The group #{user.name} already exists, the GID #{user.gid} is not used"
+        user.gid = nil
+        return
+      end
+
+      logger.info "Creating the group #{user.name}"
+      group = Y2Users::Group.new(user.name)
+      group.gid = user.gid
+      target_config.attach(group)
+    end
+
+    # Applies the expiration policy of the first user in the target system
+    def write_first_user_expiration
+      user = first_user
+      return if user.nil? || first_user_expiration.empty?
+
+      days = first_user_expiration["PasswordExpireDays"]
+      date = first_user_expiration["AccountExpireDate"]
+      args = []
+      args.push("--maxdays", days.to_s) if days
+      args.push("--expiredate", date) if date
+      run_on_target("chage", *args, user.name) unless args.empty?
+      run_on_target("usermod", "--lock", user.name) if first_user_expiration["Locked"]
+    end
+
+    # @param args [Array<String>]
+    def run_on_target(*args)
+      logger.info "Running #{args.join(" ")}"
+      Yast::Execute.on_target!(*args)
+    rescue Cheetah::ExecutionFailed => e
+      logger.error "Could not set the expiration policy: #{e.message}"
+    end
+
     def without_run_mount(&block)
       Yast::Execute.locally!("/usr/bin/umount", "/mnt/run")
       block.call
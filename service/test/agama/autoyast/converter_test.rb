@@ -102,6 +102,17 @@ describe Agama::AutoYaST::Converter do
         subject.to_agama(workdir)
         expect(result["product"]).to include("id" => "Tumbleweed")
       end
+
+      context "and the scripts are disabled" do
+        subject do
+          described_class.new("file://#{profile}", run_scripts: false)
+        end
+
+        it "does not run the script" do
+          expect(Y2Autoinstall::ScriptRunner).to_not receive(:new)
+          subject.to_agama(workdir)
+        end
+      end
     end
 
     context "when the profile contains some ERB" do
@@ -38,6 +38,7 @@ class DBusObjectWithIssuesInterface < ::DBus::Object
         source:   Agama::Issue::Source::SYSTEM,
         severity: Agama::Issue::Severity::WARN),
       Agama::Issue.new("Issue 2",
+        kind:     :missing_product,
         details:  "Details 2",
         source:   Agama::Issue::Source::CONFIG,
         severity: Agama::Issue::Severity::ERROR)
@@ -65,6 +66,17 @@ describe DBusObjectWithIssuesInterface do
     end
   end
 
+  describe "#dbus_issues_with_kind" do
+    it "returns the info of all issues, including their kind" do
+      result = subject.dbus_issues_with_kind
+
+      expect(result).to contain_exactly(
+        ["Issue 1", "Details 1", 1, 0, "generic"],
+        ["Issue 2", "Details 2", 2, 1, "missing_product"]
+      )
+    end
+  end
+
   describe "#issues_properties_changed" do
     it "emits a properties changed signal for issues" do
       expect(subject).to receive(:dbus_properties_changed)
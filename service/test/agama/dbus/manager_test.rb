@@ -22,6 +22,10 @@
 require_relative "../../test_helper"
 require "agama/dbus/manager"
 require "agama/dbus/service_status"
+require "agama/firewall"
+require "agama/machine_id"
+require "agama/scripts"
+require "agama/self_update"
 require "agama/installation_phase"
 require "agama/service_status_recorder"
 
@@ -37,9 +41,23 @@ describe Agama::DBus::Manager do
       software:                  software_client,
       on_services_status_change: nil,
       valid?:                    true,
-      service_status:            service_status)
+      service_status:            service_status,
+      firewall:                  firewall,
+      scripts:                   scripts,
+      machine_id:                machine_id,
+      self_update:               self_update)
   end
 
+  let(:self_update) do
+    instance_double(Agama::SelfUpdate, available?: true, applied?: false, skipped?: false,
+      from_version: "10-1.1", to_version: "11-1.1", skip: true)
+  end
+
+  let(:machine_id) { Agama::MachineId.new(logger) }
+
+  let(:firewall) { Agama::Firewall.new(logger) }
+  let(:scripts) { Agama::Scripts.new(logger) }
+
   let(:installation_phase) { Agama::InstallationPhase.new }
   let(:software_client) do
     instance_double(Agama::DBus::Clients::Software, on_product_selected: nil)
@@ -218,6 +236,75 @@ describe Agama::DBus::Manager do
     end
   end
 
+  describe "#assign_machine_id_policy" do
+    before do
+      allow(subject).to receive(:dbus_properties_changed)
+    end
+
+    it "sets the machine-id policy" do
+      result = subject.assign_machine_id_policy("explicit", "0123456789ABCDEF0123456789abcdef")
+      expect(result).to eq(0)
+      expect(subject.machine_id_policy)
+        .to eq("Policy" => "explicit", "Id" => "0123456789abcdef0123456789abcdef")
+    end
+
+    it "returns 1 if the policy is not valid" do
+      expect(subject.assign_machine_id_policy("explicit", "1234")).to eq(1)
+      expect(subject.assign_machine_id_policy("random", "")).to eq(1)
+      expect(subject.machine_id_policy).to eq("Policy" => "generate")
+    end
+  end
+
+  describe "#self_update" do
+    it "returns the state of the self-update" do
+      expect(subject.self_update).to eq(
+        "Available" => true, "Applied" => false, "Skipped" => false,
+        "FromVersion" => "10-1.1", "ToVersion" => "11-1.1"
+      )
+    end
+
+    context "when there is no update" do
+      before do
+        allow(self_update).to receive(:available?).and_return(false)
+        allow(self_update).to receive(:to_version).and_return(nil)
+      end
+
+      it "does not include the target version" do
+        expect(subject.self_update).to_not have_key("ToVersion")
+      end
+    end
+  end
+
+  describe "#skip_self_update" do
+    it "opts out of the self-update" do
+      expect(self_update).to receive(:skip).and_return(true)
+      expect(subject).to receive(:dbus_properties_changed)
+      subject.skip_self_update
+    end
+
+    context "when the update was already applied" do
+      before do
+        allow(self_update).to receive(:skip).and_return(false)
+      end
+
+      it "raises an error" do
+        expect { subject.skip_self_update }.to raise_error(::DBus::Error, /already applied/)
+      end
+    end
+  end
+
+  describe "#load_firewall_config" do
+    it "sets the firewall settings" do
+      result = subject.load_firewall_config('{ "enabled": true, "defaultZone": "public" }')
+      expect(result).to eq(0)
+      expect(firewall.settings).to eq("enabled" => true, "defaultZone" => "public")
+    end
+
+    it "returns 1 if the settings are not valid" do
+      expect(subject.load_firewall_config("[]")).to eq(1)
+    end
+  end
+
   describe "#can_install?" do
     before do
       allow(backend).to receive(:valid?).and_return(valid?)
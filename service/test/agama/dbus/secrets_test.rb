@@ -0,0 +1,49 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2026] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require_relative "../../test_helper"
+require "agama/dbus/secrets"
+
+describe Agama::DBus::Secrets do
+  describe ".redact" do
+    it "replaces the secrets of nested hashes and arrays" do
+      value = {
+        "EncryptionPassword"        => "notsecret",
+        "VolumeEncryptionPasswords" => { "/home" => "notsecret" },
+        "Volumes"                   => [{ "MountPath" => "/", "EncryptionPassword" => "s3cr3t" }],
+        storage:                       { encryption: { password: "s3cr3t" } }
+      }
+
+      expect(described_class.redact(value)).to eq(
+        "EncryptionPassword"        => "[FILTERED]",
+        "VolumeEncryptionPasswords" => "[FILTERED]",
+        "Volumes"                   => [{ "MountPath" => "/", "EncryptionPassword" => "[FILTERED]" }],
+        storage:                       { encryption: { password: "[FILTERED]" } }
+      )
+    end
+
+    it "does not modify the given value" do
+      value = { "EncryptionPassword" => "s3cr3t" }
+      described_class.redact(value)
+      expect(value["EncryptionPassword"]).to eq("s3cr3t")
+    end
+  end
+end
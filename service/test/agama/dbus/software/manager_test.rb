@@ -107,6 +107,34 @@ describe Agama::DBus::Software::Manager do
     end
   end
 
+  describe "#only_required=" do
+    before do
+      allow(subject).to receive(:dbus_properties_changed)
+    end
+
+    it "sets the option, setting the service as busy meanwhile" do
+      expect(subject.service_status).to receive(:busy)
+      expect(backend).to receive(:only_required=).with(true)
+      expect(subject.service_status).to receive(:idle)
+
+      subject.only_required = true
+    end
+  end
+
+  describe "#multilib=" do
+    before do
+      allow(subject).to receive(:dbus_properties_changed)
+    end
+
+    it "sets the option, setting the service as busy meanwhile" do
+      expect(subject.service_status).to receive(:busy)
+      expect(backend).to receive(:multilib=).with(false)
+      expect(subject.service_status).to receive(:idle)
+
+      subject.multilib = false
+    end
+  end
+
   describe "#install" do
     it "installs the software, setting the service as busy meanwhile" do
       expect(subject.service_status).to receive(:busy)
@@ -127,6 +155,31 @@ describe Agama::DBus::Software::Manager do
     end
   end
 
+  describe "#extra_repositories=" do
+    it "replaces the extra repositories of the backend" do
+      expect(backend).to receive(:extra_repositories=) do |repositories|
+        expect(repositories.first).to have_attributes(
+          repo_alias: "tools", url: "https://example.net/tools", name: "", persistent: true
+        )
+      end
+      allow(backend).to receive(:extra_repositories).and_return([])
+
+      subject.extra_repositories = [["tools", "https://example.net/tools", "", true]]
+    end
+
+    it "raises a D-Bus error if the repositories cannot be set" do
+      allow(backend).to receive(:extra_repositories=).and_raise(ArgumentError, "Duplicated")
+
+      expect { subject.extra_repositories = [] }.to raise_error(::DBus::Error, /Duplicated/)
+    end
+  end
+
+  describe "#default_target=" do
+    it "raises a D-Bus error if the target is unknown" do
+      expect { subject.default_target = "rescue.target" }.to raise_error(::DBus::Error)
+    end
+  end
+
   describe "D-Bus IsPackageInstalled" do
     it "returns whether the package is installed or not" do
       expect(backend).to receive(:package_installed?).with("NetworkManager").and_return(true)
@@ -182,6 +182,48 @@ describe Agama::DBus::Software::Product do
     end
   end
 
+  describe "#subscription" do
+    before do
+      allow(backend.registration).to receive(:subscription).and_return(subscription)
+    end
+
+    context "if the product is not registered" do
+      let(:subscription) { nil }
+
+      it "returns an empty hash" do
+        expect(subject.subscription).to eq({})
+      end
+    end
+
+    context "if the product is registered" do
+      let(:subscription) do
+        Agama::Registration::Subscription.new(
+          product: "SLES/16.0/x86_64", expires_at: "2027-01-31T00:00:00.000Z"
+        )
+      end
+
+      it "returns the subscription details" do
+        expect(subject.subscription).to eq(
+          "Product" => "SLES/16.0/x86_64", "ExpiresAt" => "2027-01-31T00:00:00.000Z"
+        )
+      end
+    end
+  end
+
+  describe "#extensions" do
+    before do
+      allow(backend.registration).to receive(:extensions).and_return(
+        [Agama::Registration::Extension.new(id: "sle-module-basesystem", version: "16.0")]
+      )
+    end
+
+    it "returns the activated extensions" do
+      expect(subject.extensions).to eq(
+        [{ "Id" => "sle-module-basesystem", "Version" => "16.0" }]
+      )
+    end
+  end
+
   describe "#register" do
     before do
       allow(backend.registration).to receive(:reg_code).and_return(nil)
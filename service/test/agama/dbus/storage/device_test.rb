@@ -22,6 +22,7 @@
 require_relative "../../../test_helper"
 require_relative "../../storage/storage_helpers"
 require_relative "./interfaces/device/block_examples"
+require_relative "./interfaces/device/cache_examples"
 require_relative "./interfaces/device/component_examples"
 require_relative "./interfaces/device/device_examples"
 require_relative "./interfaces/device/drive_examples"
@@ -238,6 +239,8 @@ describe Agama::DBus::Storage::Device do
 
   include_examples "Block interface"
 
+  include_examples "Cache interface"
+
   include_examples "LVM.VolumeGroup interface"
 
   include_examples "LVM.LogicalVolume interface"
@@ -0,0 +1,66 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require_relative "../../../../../test_helper"
+require "agama/storage/cache_device"
+
+shared_examples "Cache interface" do
+  describe "Cache D-Bus interface" do
+    let(:scenario) { "nvme-hdd.yaml" }
+
+    let(:devicegraph) { Y2Storage::StorageManager.instance.staging }
+
+    let(:device) do
+      cache = Agama::Storage::CacheDevice.new("/dev/nvme0n1", "/dev/sdb", :bcache)
+      cache.mode = :writeback
+      cache.create(devicegraph)
+      devicegraph.bcaches.first
+    end
+
+    describe "#cache_method" do
+      it "returns the caching technology" do
+        expect(subject.cache_method).to eq("bcache")
+      end
+    end
+
+    describe "#cache_mode" do
+      it "returns the cache mode" do
+        expect(subject.cache_mode).to eq("writeback")
+      end
+    end
+
+    describe "#cache_device" do
+      it "returns the path of the cache device" do
+        nvme = devicegraph.find_by_name("/dev/nvme0n1")
+
+        expect(subject.cache_device).to eq(tree.path_for(nvme))
+      end
+    end
+
+    describe "#cache_backing_device" do
+      it "returns the path of the backing device" do
+        sdb = devicegraph.find_by_name("/dev/sdb")
+
+        expect(subject.cache_backing_device).to eq(tree.path_for(sdb))
+      end
+    end
+  end
+end
@@ -23,6 +23,7 @@ require_relative "../../../test_helper"
 require "agama/dbus/storage/iscsi_node"
 require "agama/storage/iscsi/manager"
 require "agama/storage/iscsi/node"
+require "agama/storage/iscsi/session"
 
 describe Agama::DBus::Storage::ISCSINode do
   subject { described_class.new(iscsi_manager, iscsi_node, path, logger: logger) }
@@ -61,6 +62,36 @@ describe Agama::DBus::Storage::ISCSINode do
     end
   end
 
+  describe "#session" do
+    context "if the node is not connected" do
+      it "returns an empty hash" do
+        expect(subject.session).to eq({})
+      end
+    end
+
+    context "if the node is connected" do
+      before do
+        iscsi_node.connected = true
+        iscsi_node.session = Agama::Storage::ISCSI::Session.new.tap do |session|
+          session.state = "LOGGED_IN"
+          session.connections = 1
+          session.header_digest = "CRC32C"
+          session.data_digest = "None"
+          session.parameters = {}
+        end
+      end
+
+      it "returns the session details" do
+        session = subject.session
+
+        expect(session).to include(
+          "State" => "LOGGED_IN", "HeaderDigest" => "CRC32C", "DataDigest" => "None"
+        )
+        expect(session["Connections"].value).to eq(1)
+      end
+    end
+  end
+
   describe "#iscsi_node=" do
     it "sets the iSCSI node value" do
       node = Agama::Storage::ISCSI::Node.new
@@ -79,21 +110,30 @@ describe Agama::DBus::Storage::ISCSINode do
 
   describe "#login" do
     it "creates an iSCSI session" do
-      expect(iscsi_manager).to receive(:login) do |node, auth, startup:|
+      expect(iscsi_manager).to receive(:login) do |node, auth, startup:, interface:|
         expect(node).to eq(iscsi_node)
         expect(auth).to be_a(Y2IscsiClient::Authentication)
         expect(startup).to be_nil
+        expect(interface).to be_nil
       end
 
       subject.login
     end
 
     it "uses the given startup status" do
-      expect(iscsi_manager).to receive(:login).with(anything, anything, startup: "automatic")
+      expect(iscsi_manager).to receive(:login)
+        .with(anything, anything, startup: "automatic", interface: nil)
 
       subject.login({ "Startup" => "automatic" })
     end
 
+    it "uses the given interface" do
+      expect(iscsi_manager).to receive(:login)
+        .with(anything, anything, startup: nil, interface: "iser")
+
+      subject.login({ "Interface" => "iser" })
+    end
+
     context "when no authentication options are given" do
       it "uses an empty authentication" do
         expect(iscsi_manager).to receive(:login) do |_, auth|
@@ -129,11 +169,12 @@ describe Agama::DBus::Storage::ISCSINode do
 
     context "when the action successes" do
       before do
-        allow(iscsi_manager).to receive(:login).and_return(true)
+        allow(iscsi_manager).to receive(:login).and_return(:success)
       end
 
       it "returns 0" do
         expect(subject.login).to eq(0)
+        expect(subject.login_error).to eq("")
       end
     end
 
@@ -142,18 +183,67 @@ describe Agama::DBus::Storage::ISCSINode do
 
       it "returns 1" do
         expect(subject.login({ "Startup" => startup })).to eq(1)
+        expect(subject.login_error).to eq("Invalid startup value: invalid")
       end
     end
 
     context "when the action fails" do
       before do
-        allow(iscsi_manager).to receive(:login).and_return(false)
+        allow(iscsi_manager).to receive(:login).and_return(:failed)
       end
 
       it "returns 2" do
         expect(subject.login).to eq(2)
       end
     end
+
+    context "when the target rejects the credentials" do
+      before do
+        allow(iscsi_manager).to receive(:login).and_return(:authentication_failed)
+        allow(iscsi_manager).to receive(:login_error)
+          .and_return("iscsiadm: Login failed to authenticate")
+      end
+
+      it "returns 3 and reports the details" do
+        expect(subject).to receive(:dbus_properties_changed)
+          .with(anything, { "LoginError" => "iscsiadm: Login failed to authenticate" }, [])
+
+        expect(subject.login).to eq(3)
+        expect(subject.login_error).to eq("iscsiadm: Login failed to authenticate")
+      end
+    end
+  end
+
+  describe "#test_auth" do
+    it "checks the given credentials" do
+      expect(iscsi_manager).to receive(:test_auth) do |node, auth|
+        expect(node).to eq(iscsi_node)
+        expect(auth.username).to eq("testi")
+        expect(auth.password).to eq("testi")
+      end.and_return(:success)
+
+      expect(subject.test_auth({ "Username" => "testi", "Password" => "testi" })).to eq(0)
+    end
+
+    context "when the target rejects the credentials" do
+      before do
+        allow(iscsi_manager).to receive(:test_auth).and_return(:login_failed)
+      end
+
+      it "returns 2" do
+        expect(subject.test_auth).to eq(2)
+      end
+    end
+
+    context "when the session cannot be closed" do
+      before do
+        allow(iscsi_manager).to receive(:test_auth).and_return(:logout_failed)
+      end
+
+      it "raises a D-Bus error" do
+        expect { subject.test_auth }.to raise_error(::DBus::Error)
+      end
+    end
   end
 
   describe "#logout" do
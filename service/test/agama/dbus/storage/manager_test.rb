@@ -30,6 +30,8 @@ require "agama/storage/proposal"
 require "agama/storage/proposal_settings"
 require "agama/storage/volume"
 require "agama/storage/iscsi/manager"
+require "agama/storage/iscsi/session"
+require "agama/storage/nvmeof/manager"
 require "agama/storage/dasd/manager"
 require "agama/dbus/storage/dasds_tree"
 require "agama/dbus/clients/software"
@@ -49,6 +51,8 @@ describe Agama::DBus::Storage::Manager do
       proposal:                    proposal,
       iscsi:                       iscsi,
       software:                    software,
+      bootloader:                  bootloader,
+      nvmeof:                      nvmeof,
       config:                      config,
       on_probe:                    nil,
       on_progress_change:          nil,
@@ -73,6 +77,12 @@ describe Agama::DBus::Storage::Manager do
     instance_double(Agama::DBus::Clients::Software, on_probe_finished: nil)
   end
 
+  let(:bootloader) { Agama::Storage::Bootloader.new(config, logger) }
+
+  let(:nvmeof) do
+    instance_double(Agama::Storage::NVMeoF::Manager, on_connections_change: nil, controllers: [])
+  end
+
   before do
     # Speed up tests by avoding real check of TPM presence.
     allow(Y2Storage::EncryptionMethod::TPM_FDE).to receive(:possible?).and_return(true)
@@ -684,6 +694,106 @@ describe Agama::DBus::Storage::Manager do
     end
   end
 
+  describe "#unlock_device" do
+    it "unlocks the device and returns the result" do
+      expect(backend).to receive(:unlock_device).with("/dev/sda2", "n0ts3cr3t").and_return(2)
+      expect(subject.unlock_device("/dev/sda2", "n0ts3cr3t")).to eq(2)
+    end
+  end
+
+  describe "#install" do
+    it "prepares the storage devices" do
+      expect(backend).to receive(:install)
+      subject.install
+    end
+
+    it "refuses a commit while another one is in progress" do
+      expect(backend).to receive(:install).once do
+        expect { subject.install }.to raise_error(::DBus::Error)
+      end
+      subject.install
+    end
+
+    it "allows a new commit after a failed one" do
+      expect(backend).to receive(:install).and_raise(RuntimeError).ordered
+      expect(backend).to receive(:install).ordered
+      expect { subject.install }.to raise_error(RuntimeError)
+      expect { subject.install }.to_not raise_error
+    end
+  end
+
+  describe "#nvmeof_discover" do
+    let(:target) do
+      Agama::Storage::NVMeoF::Target.new("tcp", "192.168.1.10", "4420", "nqn.2024-01.com.example")
+    end
+
+    it "returns the discovered targets" do
+      expect(nvmeof).to receive(:discover) do |discovery|
+        expect(discovery.traddr).to eq("192.168.1.10")
+        expect(discovery.trsvcid).to eq("8009")
+        [0, "", [target]]
+      end
+
+      result = subject.nvmeof_discover(
+        "Transport" => "tcp", "TrAddr" => "192.168.1.10", "TrSvcId" => "8009"
+      )
+      expect(result).to eq(
+        [0, "", [{ "Transport" => "tcp", "TrAddr" => "192.168.1.10", "TrSvcId" => "4420",
+                   "SubNQN" => "nqn.2024-01.com.example" }]]
+      )
+    end
+  end
+
+  describe "#nvmeof_controllers" do
+    before do
+      target = Agama::Storage::NVMeoF::Target.new("tcp", "192.168.1.10", "4420", "nqn.2024-01")
+      controller = Agama::Storage::NVMeoF::Controller.new("nvme1", target, "live")
+      allow(nvmeof).to receive(:controllers).and_return([controller])
+    end
+
+    it "returns the name, state and target of the controllers" do
+      expect(subject.nvmeof_controllers).to eq(
+        [{ "Transport" => "tcp", "TrAddr" => "192.168.1.10", "TrSvcId" => "4420",
+           "SubNQN" => "nqn.2024-01", "Name" => "nvme1", "State" => "live" }]
+      )
+    end
+  end
+
+  describe "#load_bootloader_config" do
+    it "sets the bootloader settings" do
+      result = subject.load_bootloader_config('{ "timeout": 5, "secureBoot": true }')
+      expect(result).to eq(0)
+      expect(bootloader.settings).to eq("timeout" => 5, "secureBoot" => true)
+    end
+
+    context "if the settings are not a valid JSON object" do
+      it "returns 1 and keeps the previous settings" do
+        subject.load_bootloader_config('{ "timeout": 5 }')
+        expect(subject.load_bootloader_config("[1, 2]")).to eq(1)
+        expect(subject.load_bootloader_config("{")).to eq(1)
+        expect(bootloader.settings).to eq("timeout" => 5)
+      end
+    end
+  end
+
+  describe "#supported_bootloaders" do
+    context "if the product does not define the bootloaders" do
+      it "returns GRUB 2" do
+        expect(subject.supported_bootloaders).to eq(["grub2"])
+      end
+    end
+
+    context "if the product defines the bootloaders" do
+      let(:config_data) do
+        { "bootloader" => { "supported" => ["grub2", "systemd-boot"] } }
+      end
+
+      it "returns the product bootloaders" do
+        expect(subject.supported_bootloaders).to eq(["grub2", "systemd-boot"])
+      end
+    end
+  end
+
   describe "#iscsi_discover" do
     it "performs an iSCSI discovery" do
       expect(iscsi).to receive(:discover_send_targets) do |address, port, auth|
@@ -695,6 +805,13 @@ describe Agama::DBus::Storage::Manager do
       subject.iscsi_discover("192.168.100.90", 3260, {})
     end
 
+    it "binds the discovery to the given interface" do
+      expect(iscsi).to receive(:discover_send_targets)
+        .with("192.168.100.90", 3260, anything, interface: "iser")
+
+      subject.iscsi_discover("192.168.100.90", 3260, { "Interface" => "iser" })
+    end
+
     context "when no authentication options are given" do
       let(:auth_options) { {} }
 
@@ -755,6 +872,81 @@ describe Agama::DBus::Storage::Manager do
     end
   end
 
+  describe "#iscsi_interfaces_details" do
+    before do
+      allow(iscsi).to receive(:interfaces_details).and_return([interface])
+    end
+
+    let(:interface) do
+      Agama::Storage::ISCSI::Manager::Interface.new(
+        name: "bnx2i.00:11:22:33:44:55", transport: "bnx2i", hwaddress: "00:11:22:33:44:55"
+      )
+    end
+
+    it "returns the D-Bus representation of the interfaces" do
+      expect(subject.iscsi_interfaces_details).to eq(
+        [
+          {
+            "Name"      => "bnx2i.00:11:22:33:44:55",
+            "Transport" => "bnx2i",
+            "HWAddress" => "00:11:22:33:44:55",
+            "IPAddress" => ""
+          }
+        ]
+      )
+    end
+  end
+
+  describe "#iscsi_sessions" do
+    before do
+      allow(iscsi).to receive(:sessions).and_return([session])
+    end
+
+    let(:session) do
+      Agama::Storage::ISCSI::Session.new.tap do |session|
+        session.target = "iqn.2023-01.com.example:12ac588"
+        session.address = "192.168.100.101"
+        session.port = 3260
+        session.interface = "default"
+        session.ibft = true
+        session.state = "LOGGED_IN"
+        session.connections = 1
+        session.header_digest = "None"
+        session.data_digest = "None"
+        session.parameters = { "MaxBurstLength" => "262144" }
+      end
+    end
+
+    it "returns the D-Bus representation of the active sessions" do
+      sessions = subject.iscsi_sessions
+
+      expect(sessions.size).to eq(1)
+      expect(sessions.first).to include(
+        "Target"       => "iqn.2023-01.com.example:12ac588",
+        "Address"      => "192.168.100.101",
+        "Interface"    => "default",
+        "IBFT"         => true,
+        "State"        => "LOGGED_IN",
+        "HeaderDigest" => "None",
+        "DataDigest"   => "None"
+      )
+    end
+
+    it "exports the port and the number of connections as unsigned integers" do
+      session = subject.iscsi_sessions.first
+
+      expect(session["Port"]).to be_a(DBus::Data::UInt32)
+      expect(session["Port"].value).to eq(3260)
+      expect(session["Connections"].value).to eq(1)
+    end
+
+    it "exports the negotiated parameters as a dictionary of strings" do
+      parameters = subject.iscsi_sessions.first["Parameters"]
+
+      expect(parameters.type.to_s).to eq("a{ss}")
+    end
+  end
+
   describe "#iscsi_delete" do
     before do
       allow(Agama::DBus::Storage::ISCSINodesTree)
@@ -90,12 +90,17 @@ describe Agama::DBus::Storage::ProposalSettingsConversion::FromDBus do
           },
           {
             "Device" => "/dev/sdb1",
-            "Action" => "resize"
+            "Action" => "resize",
+            "Size"   => 1024
           }
         ],
         "Volumes"                => [
           { "MountPath" => "/" },
           { "MountPath" => "/test" }
+        ],
+        "IncludeRemovable"       => true,
+        "Partitions"             => [
+          { "Device" => "/dev/sda", "Start" => 2048, "End" => 4095, "FsType" => "ext4" }
         ]
       }
     end
@@ -114,10 +119,17 @@ describe Agama::DBus::Storage::ProposalSettingsConversion::FromDBus do
       expect(settings.space.actions).to eq({
         "/dev/sda" => :force_delete, "/dev/sdb1" => :resize
       })
+      expect(settings.space.resize_sizes).to eq("/dev/sdb1" => 1024)
       expect(settings.volumes).to contain_exactly(
         an_object_having_attributes(mount_path: "/"),
         an_object_having_attributes(mount_path: "/test")
       )
+      expect(settings.include_removable).to eq(true)
+      expect(settings.partitions).to contain_exactly(
+        an_object_having_attributes(
+          device: "/dev/sda", start: 2048, end_sector: 4095, fs_type: "ext4", mount_path: nil
+        )
+      )
     end
 
     context "when some values are not provided from D-Bus" do
@@ -141,6 +153,151 @@ describe Agama::DBus::Storage::ProposalSettingsConversion::FromDBus do
       end
     end
 
+    context "when a key file is provided from D-Bus" do
+      let(:dbus_settings) do
+        {
+          "EncryptionKeyFile"     => "/luks.key",
+          "EncryptionKeyDevice"   => "LABEL=USBKEY",
+          "EncryptionKeyCrypttab" => "/luks.key:LABEL=USBKEY",
+          "EncryptionKeySource"   => "/run/agama/storage/luks.key"
+        }
+      end
+
+      it "sets the key file of the encryption settings" do
+        key_file = subject.convert.encryption.key_file
+
+        expect(key_file.path).to eq("/luks.key")
+        expect(key_file.device).to eq("LABEL=USBKEY")
+        expect(key_file.crypttab).to eq("/luks.key:LABEL=USBKEY")
+        expect(key_file.source).to eq("/run/agama/storage/luks.key")
+      end
+    end
+
+    context "when MountBy is provided from D-Bus" do
+      let(:dbus_settings) { { "MountBy" => "path" } }
+
+      it "sets how the file systems are mounted" do
+        expect(subject.convert.mount_by).to eq(Y2Storage::Filesystems::MountByType::PATH)
+      end
+    end
+
+    context "when a read-only root file system is requested from D-Bus" do
+      let(:dbus_settings) { { "ReadOnlyRoot" => true, "RootOverlay" => "/var/lib/overlay" } }
+
+      it "sets the read-only root and its overlay directory" do
+        settings = subject.convert
+        expect(settings.read_only_root).to eq(true)
+        expect(settings.root_overlay).to eq("/var/lib/overlay")
+      end
+    end
+
+    context "when the passwords of some volumes are provided from D-Bus" do
+      let(:dbus_settings) do
+        { "VolumeEncryptionPasswords" => { "/home" => "notsecret", "/srv" => "" } }
+      end
+
+      it "sets the passwords of the volumes, ignoring the empty ones" do
+        encryption = subject.convert.encryption
+        expect(encryption.volume_passwords).to eq("/home" => "notsecret")
+        expect(encryption.volume_password("/home")).to eq("notsecret")
+        expect(encryption.volume_password("/")).to be_nil
+      end
+    end
+
+    context "when the partition tables are provided from D-Bus" do
+      let(:dbus_settings) do
+        {
+          "PartitionTables" => { "/dev/sda" => "gpt", "/dev/sdb" => "msdos", "/dev/sdc" => "dasd" }
+        }
+      end
+
+      it "sets the known partition table types" do
+        expect(subject.convert.partition_tables).to eq(
+          "/dev/sda" => Y2Storage::PartitionTables::Type::GPT,
+          "/dev/sdb" => Y2Storage::PartitionTables::Type::MSDOS
+        )
+      end
+    end
+
+    context "when the partition alignment is provided from D-Bus" do
+      let(:dbus_settings) { { "Alignment" => "explicit", "AlignmentGrain" => 4_194_304 } }
+
+      it "sets the partition alignment" do
+        alignment = subject.convert.alignment
+        expect(alignment.mode).to eq(:explicit)
+        expect(alignment.grain).to eq(4_194_304)
+      end
+
+      context "and the alignment is unknown" do
+        let(:dbus_settings) { { "Alignment" => "foo" } }
+
+        it "keeps the optimal alignment" do
+          expect(subject.convert.alignment.mode).to eq(:optimal)
+        end
+      end
+    end
+
+    context "when the ESP settings are provided from D-Bus" do
+      let(:dbus_settings) do
+        {
+          "EspMode"      => "reuse",
+          "EspDevice"    => "/dev/sda1",
+          "EspSize"      => 536_870_912,
+          "EspMountPath" => "/efi"
+        }
+      end
+
+      it "sets the ESP settings" do
+        esp = subject.convert.boot.esp
+        expect(esp.mode).to eq(:reuse)
+        expect(esp.device).to eq("/dev/sda1")
+        expect(esp.size).to eq(536_870_912)
+        expect(esp.mount_path).to eq("/efi")
+      end
+
+      context "and the mode is unknown" do
+        let(:dbus_settings) { { "EspMode" => "foo" } }
+
+        it "keeps the default mode" do
+          expect(subject.convert.boot.esp.mode).to eq(:auto)
+        end
+      end
+    end
+
+    context "when cached devices are provided from D-Bus" do
+      let(:dbus_settings) do
+        {
+          "Caches" => [
+            {
+              "CacheDevice" => "/dev/nvme0n1", "BackingDevice" => "/dev/sda",
+              "Method" => "lvm", "Mode" => "writeback"
+            },
+            { "CacheDevice" => "/dev/nvme1n1", "BackingDevice" => "/dev/sdb", "Method" => "bcache" }
+          ]
+        }
+      end
+
+      it "sets the cached devices" do
+        lvm, bcache = subject.convert.caches
+        expect(lvm).to have_attributes(
+          cache_device: "/dev/nvme0n1", backing_device: "/dev/sda", cache_method: :lvm,
+          mode: :writeback
+        )
+        expect(bcache).to have_attributes(
+          cache_device: "/dev/nvme1n1", backing_device: "/dev/sdb", cache_method: :bcache,
+          mode: :writethrough
+        )
+      end
+    end
+
+    context "when an empty key file is provided from D-Bus" do
+      let(:dbus_settings) { { "EncryptionKeyFile" => "", "EncryptionKeyDevice" => "" } }
+
+      it "does not use a key file" do
+        expect(subject.convert.encryption.key_file).to be_nil
+      end
+    end
+
     context "when 'Target' is not provided from D-Bus" do
       let(:dbus_settings) { {} }
 
@@ -39,7 +39,9 @@ describe Agama::DBus::Storage::ProposalSettingsConversion::ToDBus do
       settings.encryption.pbkd_function = Y2Storage::PbkdFunction::ARGON2ID
       settings.space.policy = :custom
       settings.space.actions = { "/dev/sda" => :force_delete, "/dev/sdb1" => "resize" }
+      settings.space.resize_sizes = { "/dev/sdb1" => 1024 }
       settings.volumes = [Agama::Storage::Volume.new("/test")]
+      settings.partitions = [Agama::Storage::ExplicitPartition.new("/dev/sda", 2048, 4095)]
     end
   end
 
@@ -56,7 +58,10 @@ describe Agama::DBus::Storage::ProposalSettingsConversion::ToDBus do
         "EncryptionPBKDFunction" => "pbkdf2",
         "SpacePolicy"            => "keep",
         "SpaceActions"           => [],
-        "Volumes"                => []
+        "Volumes"                => [],
+        "IncludeRemovable"       => false,
+        "ExcludedDevices"        => [],
+        "Partitions"             => []
       )
 
       expect(described_class.new(custom_settings).convert).to eq(
@@ -76,7 +81,8 @@ describe Agama::DBus::Storage::ProposalSettingsConversion::ToDBus do
           },
           {
             "Device" => "/dev/sdb1",
-            "Action" => "resize"
+            "Action" => "resize",
+            "Size"   => 1024
           }
         ],
         "Volumes"                => [
@@ -100,6 +106,95 @@ describe Agama::DBus::Storage::ProposalSettingsConversion::ToDBus do
               "SizeRelevantVolumes"   => []
             }
           }
+        ],
+        "IncludeRemovable"       => false,
+        "ExcludedDevices"        => [],
+        "Partitions"             => [
+          { "Device" => "/dev/sda", "Start" => 2048, "End" => 4095 }
+        ]
+      )
+    end
+
+    it "includes the key file without the key material" do
+      default_settings.encryption.key_file = Agama::Storage::EncryptionKeyFile.new("/luks.key")
+      default_settings.encryption.key_file.source = "/run/agama/storage/luks.key"
+
+      dbus_settings = described_class.new(default_settings).convert
+      expect(dbus_settings).to include(
+        "EncryptionKeyFile"   => "/luks.key",
+        "EncryptionKeyDevice" => ""
+      )
+      expect(dbus_settings).to_not include("EncryptionKeySource")
+    end
+
+    it "includes how the file systems are mounted if it is set" do
+      expect(described_class.new(default_settings).convert).to_not have_key("MountBy")
+      default_settings.mount_by = Y2Storage::Filesystems::MountByType::LABEL
+
+      expect(described_class.new(default_settings).convert).to include("MountBy" => "label")
+    end
+
+    it "includes the overlay directory if the root file system is read-only" do
+      expect(described_class.new(default_settings).convert).to_not have_key("ReadOnlyRoot")
+      default_settings.read_only_root = true
+      default_settings.root_overlay = "/var/lib/overlay"
+
+      expect(described_class.new(default_settings).convert)
+        .to include("ReadOnlyRoot" => true, "RootOverlay" => "/var/lib/overlay")
+    end
+
+    it "includes the passwords of the volumes if there is any" do
+      expect(described_class.new(default_settings).convert)
+        .to_not have_key("VolumeEncryptionPasswords")
+      default_settings.encryption.volume_passwords = { "/home" => "notsecret" }
+
+      expect(described_class.new(default_settings).convert)
+        .to include("VolumeEncryptionPasswords" => { "/home" => "notsecret" })
+    end
+
+    it "includes the partition tables if there is any" do
+      expect(described_class.new(default_settings).convert).to_not have_key("PartitionTables")
+      default_settings.partition_tables = { "/dev/sda" => Y2Storage::PartitionTables::Type::GPT }
+
+      expect(described_class.new(default_settings).convert)
+        .to include("PartitionTables" => { "/dev/sda" => "gpt" })
+    end
+
+    it "includes the partition alignment if it is not optimal" do
+      expect(described_class.new(default_settings).convert).to_not have_key("Alignment")
+      default_settings.alignment.mode = :explicit
+      default_settings.alignment.explicit_grain = 4_194_304
+
+      expect(described_class.new(default_settings).convert)
+        .to include("Alignment" => "explicit", "AlignmentGrain" => 4_194_304)
+    end
+
+    it "includes the ESP settings if the mode is not auto" do
+      expect(described_class.new(default_settings).convert).to_not have_key("EspMode")
+      default_settings.boot.esp.tap do |esp|
+        esp.mode = :create
+        esp.size = 536_870_912
+      end
+
+      expect(described_class.new(default_settings).convert).to include(
+        "EspMode"      => "create",
+        "EspSize"      => 536_870_912,
+        "EspMountPath" => "/boot/efi"
+      )
+    end
+
+    it "includes the cached devices if there is any" do
+      expect(described_class.new(default_settings).convert).to_not have_key("Caches")
+      default_settings.caches = [
+        Agama::Storage::CacheDevice.new("/dev/nvme0n1", "/dev/sda", :bcache)
+      ]
+
+      expect(described_class.new(default_settings).convert).to include(
+        "Caches" => [
+          {
+            "CacheDevice" => "/dev/nvme0n1", "BackingDevice" => "/dev/sda",
+            "Method" => "bcache", "Mode" => "writethrough"
+          }
         ]
       )
     end
@@ -260,5 +260,114 @@ describe Agama::DBus::Storage::VolumeConversion::FromDBus do
         expect(volume.location.target).to eq :default
       end
     end
+
+    context "when the D-Bus settings provide an Encryption value" do
+      let(:dbus_volume) do
+        {
+          "MountPath"  => "swap",
+          "Encryption" => "random_key"
+        }
+      end
+
+      it "sets the Encryption value provided from D-Bus" do
+        volume = subject.convert
+
+        expect(volume.encryption).to eq(:random_key)
+        expect(volume.random_key_swap?).to eq(true)
+      end
+    end
+
+    context "when the D-Bus settings provide an unknown Encryption value" do
+      let(:dbus_volume) do
+        {
+          "MountPath"  => "swap",
+          "Encryption" => "foo"
+        }
+      end
+
+      it "ignores the Encryption value provided from D-Bus" do
+        volume = subject.convert
+
+        expect(volume.encryption).to be_nil
+      end
+    end
+
+    context "when the D-Bus settings provide a Weight value" do
+      let(:dbus_volume) do
+        {
+          "MountPath" => "/home",
+          "Weight"    => 300
+        }
+      end
+
+      it "sets the Weight value provided from D-Bus" do
+        volume = subject.convert
+
+        expect(volume.weight).to eq(300)
+      end
+    end
+
+    context "when the D-Bus settings provide a Description value" do
+      let(:dbus_volume) do
+        {
+          "MountPath"   => "/home",
+          "Description" => "Users data"
+        }
+      end
+
+      it "sets the Description value provided from D-Bus" do
+        volume = subject.convert
+
+        expect(volume.description).to eq("Users data")
+      end
+    end
+
+    context "when the D-Bus settings provide Label and MountBy values" do
+      let(:dbus_volume) do
+        {
+          "MountPath" => "/home",
+          "Label"     => "home",
+          "MountBy"   => "label"
+        }
+      end
+
+      it "sets the Label and MountBy values provided from D-Bus" do
+        volume = subject.convert
+
+        expect(volume.label).to eq("home")
+        expect(volume.mount_by).to eq(Y2Storage::Filesystems::MountByType::LABEL)
+      end
+    end
+
+    context "when the D-Bus settings provide a Quota value" do
+      let(:dbus_volume) do
+        {
+          "MountPath" => "/home",
+          "Quota"     => { "Type" => "project", "Limits" => [["alice", 1024], ["bob", 2048]] }
+        }
+      end
+
+      it "sets the quota type and limits provided from D-Bus" do
+        quota = subject.convert.quota
+
+        expect(quota.type).to eq(:project)
+        expect(quota.limits).to eq(
+          "alice" => Y2Storage::DiskSize.new(1024), "bob" => Y2Storage::DiskSize.new(2048)
+        )
+      end
+    end
+
+    context "when the D-Bus settings provide an unknown Quota type" do
+      let(:dbus_volume) do
+        {
+          "MountPath" => "/home",
+          "Quota"     => { "Type" => "foo" }
+        }
+      end
+
+      it "ignores the Quota value provided from D-Bus" do
+        expect(subject.convert.quota).to be_nil
+      end
+    end
   end
 end
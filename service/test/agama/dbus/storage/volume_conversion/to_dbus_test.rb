@@ -100,5 +100,44 @@ describe Agama::DBus::Storage::VolumeConversion::ToDBus do
         }
       )
     end
+
+    it "includes the encryption if it is set" do
+      default_volume.encryption = :random_key
+
+      expect(described_class.new(default_volume).convert).to include("Encryption" => "random_key")
+    end
+
+    it "includes the weight if it is set" do
+      expect(described_class.new(default_volume).convert).to_not have_key("Weight")
+      default_volume.weight = 300
+
+      expect(described_class.new(default_volume).convert).to include("Weight" => 300)
+    end
+
+    it "includes the description if it is set" do
+      expect(described_class.new(default_volume).convert).to_not have_key("Description")
+      default_volume.description = "Users data"
+
+      expect(described_class.new(default_volume).convert).to include("Description" => "Users data")
+    end
+
+    it "includes the label and how the file system is mounted if they are set" do
+      default_volume.label = "home"
+      default_volume.mount_by = Y2Storage::Filesystems::MountByType::LABEL
+
+      expect(described_class.new(default_volume).convert)
+        .to include("Label" => "home", "MountBy" => "label")
+    end
+
+    it "includes the quota if it is set" do
+      expect(described_class.new(default_volume).convert).to_not have_key("Quota")
+      default_volume.quota = Agama::Storage::VolumeQuota.new(:qgroups).tap do |quota|
+        quota.limits["var/log"] = Y2Storage::DiskSize.new(1024)
+      end
+
+      expect(described_class.new(default_volume).convert).to include(
+        "Quota" => { "Type" => "qgroups", "Limits" => [["var/log", 1024]] }
+      )
+    end
   end
 end
@@ -78,16 +78,78 @@ describe Agama::DBus::Users do
         instance_double(Y2Users::User,
           full_name:        "Test user",
           name:             "test",
-          password_content: "12345")
+          password_content: "12345",
+          shell:            nil,
+          uid:              nil,
+          gid:              nil,
+          home:             nil)
       end
 
+      let(:expiration) { {} }
+
       before do
         allow(backend).to receive(:autologin?).with(user).and_return(true)
+        allow(backend).to receive(:first_user_expiration).and_return(expiration)
       end
 
       it "returns the first user data" do
         expect(subject.first_user).to eq(["Test user", "test", "12345", true, {}])
       end
+
+      context "and the user has a shell" do
+        before do
+          allow(user).to receive(:shell).and_return("/usr/bin/zsh")
+        end
+
+        it "includes the shell in the data" do
+          expect(subject.first_user)
+            .to eq(["Test user", "test", "12345", true, { "Shell" => "/usr/bin/zsh" }])
+        end
+      end
+
+      context "and the user has IDs, home and expiration policy" do
+        let(:expiration) { { "PasswordExpireDays" => 90, "Locked" => true } }
+
+        before do
+          allow(user).to receive(:uid).and_return("1001")
+          allow(user).to receive(:gid).and_return("2000")
+          allow(user).to receive(:home).and_return(Y2Users::Home.new("/srv/test"))
+        end
+
+        it "includes them in the data" do
+          expect(subject.first_user.last).to eq(
+            "UID" => 1001, "GID" => 2000, "Home" => "/srv/test",
+            "PasswordExpireDays" => 90, "Locked" => true
+          )
+        end
+      end
+    end
+  end
+
+  describe "#load_groups" do
+    let(:json) { '[{ "name": "developers", "members": [] }]' }
+
+    it "sets the groups in the backend" do
+      expect(backend).to receive(:load_groups_json).with(json).and_return(true)
+      expect(subject.load_groups(json)).to eq(0)
+    end
+
+    context "if the groups are not valid" do
+      it "returns 1" do
+        allow(backend).to receive(:load_groups_json).and_return(false)
+        expect(subject.load_groups(json)).to eq(1)
+      end
+    end
+  end
+
+  describe "#default_shell=" do
+    before do
+      allow(subject).to receive(:dbus_properties_changed)
+    end
+
+    it "sets the default shell in the backend" do
+      expect(backend).to receive(:default_shell=).with("/usr/bin/zsh")
+      subject.default_shell = "/usr/bin/zsh"
     end
   end
 end
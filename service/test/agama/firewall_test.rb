@@ -0,0 +1,118 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require_relative "../test_helper"
+require "agama/firewall"
+
+describe Agama::Firewall do
+  subject { described_class.new(logger) }
+
+  let(:logger) { Logger.new($stdout, level: :warn) }
+
+  before do
+    allow(Yast::Execute).to receive(:on_target!)
+  end
+
+  describe "#load_json" do
+    it "loads the settings from a JSON object" do
+      expect(subject.load_json('{ "enabled": true }')).to eq(true)
+      expect(subject.settings).to eq("enabled" => true)
+    end
+
+    it "rejects the documents which are not JSON objects" do
+      expect(subject.load_json("[]")).to eq(false)
+      expect(subject.load_json("{")).to eq(false)
+      expect(subject.settings).to eq({})
+    end
+  end
+
+  describe "#known_services" do
+    before do
+      allow(Dir).to receive(:glob).and_return([])
+      allow(Dir).to receive(:glob).with("/usr/lib/firewalld/services/*.xml")
+        .and_return(["/usr/lib/firewalld/services/ssh.xml", "/usr/lib/firewalld/services/http.xml"])
+      allow(Dir).to receive(:glob).with("/etc/firewalld/services/*.xml")
+        .and_return(["/etc/firewalld/services/ssh.xml"])
+    end
+
+    it "returns the names of the firewalld services" do
+      expect(subject.known_services).to eq(["http", "ssh"])
+    end
+  end
+
+  describe "#propose" do
+    it "selects firewalld if the firewall is configured" do
+      subject.load_json('{ "zones": [{ "name": "public", "services": ["ssh"] }] }')
+      expect(Yast::PackagesProposal).to receive(:SetResolvables)
+        .with("agama-firewall", :package, ["firewalld"])
+      subject.propose
+    end
+
+    it "does not select firewalld if the firewall is disabled" do
+      subject.load_json('{ "enabled": false, "defaultZone": "public" }')
+      expect(Yast::PackagesProposal).to receive(:SetResolvables)
+        .with("agama-firewall", :package, [])
+      subject.propose
+    end
+  end
+
+  describe "#write" do
+    it "opens the services and ports and sets the default zone" do
+      subject.load_json(
+        '{ "enabled": true, "defaultZone": "servers", "zones": [' \
+        '{ "name": "servers", "services": ["ssh"], "ports": ["8080/tcp"] }] }'
+      )
+      expect(Yast::Execute).to receive(:on_target!)
+        .with("firewall-offline-cmd", "--new-zone=servers").ordered
+      expect(Yast::Execute).to receive(:on_target!)
+        .with("firewall-offline-cmd", "--zone=servers", "--add-service=ssh").ordered
+      expect(Yast::Execute).to receive(:on_target!)
+        .with("firewall-offline-cmd", "--zone=servers", "--add-port=8080/tcp").ordered
+      expect(Yast::Execute).to receive(:on_target!)
+        .with("firewall-offline-cmd", "--set-default-zone=servers").ordered
+      expect(Yast::Execute).to receive(:on_target!)
+        .with("systemctl", "enable", "firewalld").ordered
+      subject.write
+    end
+
+    it "does not create the predefined zones" do
+      subject.load_json('{ "zones": [{ "name": "public", "services": ["ssh"] }] }')
+      expect(Yast::Execute).to_not receive(:on_target!)
+        .with("firewall-offline-cmd", "--new-zone=public")
+      expect(Yast::Execute).to_not receive(:on_target!).with("systemctl", any_args)
+      subject.write
+    end
+
+    it "only disables firewalld if the firewall is disabled" do
+      subject.load_json('{ "enabled": false, "zones": [{ "name": "public" }] }')
+      expect(Yast::Execute).to receive(:on_target!).with("systemctl", "disable", "firewalld")
+      expect(Yast::Execute).to_not receive(:on_target!).with("firewall-offline-cmd", any_args)
+      subject.write
+    end
+
+    it "logs the errors instead of raising them" do
+      subject.load_json('{ "enabled": true }')
+      allow(Yast::Execute).to receive(:on_target!)
+        .and_raise(Cheetah::ExecutionFailed.new([], "", nil, nil))
+      expect { subject.write }.to_not raise_error
+    end
+  end
+end
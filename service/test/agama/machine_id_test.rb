@@ -0,0 +1,82 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+
+require_relative "../test_helper"
+require "agama/machine_id"
+require "tmpdir"
+
+describe Agama::MachineId do
+  subject { described_class.new(logger) }
+
+  let(:logger) { Logger.new($stdout, level: :warn) }
+  let(:destdir) { Dir.mktmpdir }
+  let(:machine_id_file) { File.join(destdir, "etc/machine-id") }
+
+  before do
+    allow(Yast::Installation).to receive(:destdir).and_return(destdir)
+  end
+
+  after do
+    FileUtils.remove_entry(destdir)
+  end
+
+  describe "#assign" do
+    it "rejects the unknown policies and the invalid machine-ids" do
+      expect(subject.assign("random", "")).to eq(false)
+      expect(subject.assign("explicit", "0123456789abcdef")).to eq(false)
+      expect(subject.assign("explicit", "0" * 32)).to eq(false)
+      expect(subject.policy).to eq("generate")
+    end
+  end
+
+  describe "#write" do
+    it "leaves an empty machine-id by default" do
+      subject.write
+      expect(File.read(machine_id_file)).to eq("")
+    end
+
+    context "when an explicit machine-id is set" do
+      before do
+        subject.assign("explicit", "0123456789ABCDEF0123456789abcdef")
+      end
+
+      it "writes the machine-id in lowercase" do
+        subject.write
+        expect(File.read(machine_id_file)).to eq("0123456789abcdef0123456789abcdef\n")
+      end
+    end
+
+    context "when the machine-id of the installation media is kept" do
+      before do
+        subject.assign("keep", "")
+        allow(File).to receive(:read).and_call_original
+        allow(File).to receive(:read).with("/etc/machine-id")
+          .and_return("fedcba9876543210fedcba9876543210\n")
+      end
+
+      it "copies it to the target system" do
+        subject.write
+        expect(File.read(machine_id_file)).to eq("fedcba9876543210fedcba9876543210\n")
+      end
+    end
+  end
+end
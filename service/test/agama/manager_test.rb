@@ -54,6 +54,9 @@ describe Agama::Manager do
   end
   let(:locale) { instance_double(Agama::DBus::Clients::Locale, finish: nil) }
   let(:network) { instance_double(Agama::Network, install: nil) }
+  let(:firewall) { instance_double(Agama::Firewall, propose: nil, write: nil) }
+  let(:scripts) { instance_double(Agama::Scripts, write: nil) }
+  let(:machine_id) { instance_double(Agama::MachineId, write: nil) }
   let(:storage) do
     instance_double(
       Agama::DBus::Clients::Storage, probe: nil, install: nil, finish: nil,
@@ -65,6 +68,9 @@ describe Agama::Manager do
 
   before do
     allow(Agama::Network).to receive(:new).and_return(network)
+    allow(Agama::Firewall).to receive(:new).and_return(firewall)
+    allow(Agama::Scripts).to receive(:new).and_return(scripts)
+    allow(Agama::MachineId).to receive(:new).and_return(machine_id)
     allow(Agama::ProxySetup).to receive(:instance).and_return(proxy)
     allow(Agama::DBus::Clients::Locale).to receive(:instance).and_return(locale)
     allow(Agama::DBus::Clients::Software).to receive(:new).and_return(software)
@@ -120,8 +126,9 @@ describe Agama::Manager do
       expect(subject.installation_phase.install?).to eq(true)
     end
 
-    it "calls #propose on proxy and software modules" do
+    it "calls #propose on proxy, firewall and software modules" do
       expect(proxy).to receive(:propose)
+      expect(firewall).to receive(:propose)
       expect(software).to receive(:propose)
       subject.install_phase
     end
@@ -134,8 +141,27 @@ describe Agama::Manager do
       expect(storage).to receive(:install)
       expect(storage).to receive(:finish)
       expect(users).to receive(:write)
+      expect(firewall).to receive(:write)
+      expect(scripts).to receive(:write)
+      expect(machine_id).to receive(:write)
       subject.install_phase
     end
+
+    it "reports the installation as successful" do
+      subject.install_phase
+      expect(subject.installation_failed?).to eq(false)
+    end
+
+    context "when a module fails" do
+      before do
+        allow(software).to receive(:install).and_raise(StandardError, "Cannot install")
+      end
+
+      it "reports the installation as failed" do
+        subject.install_phase
+        expect(subject.installation_failed?).to eq(true)
+      end
+    end
   end
 
   let(:idle) { Agama::DBus::ServiceStatus::IDLE }
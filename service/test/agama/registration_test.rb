@@ -341,6 +341,96 @@ describe Agama::Registration do
     end
   end
 
+  describe "#subscription" do
+    let(:product) { Agama::Software::Product.new("SLES").tap { |p| p.version = "16.0" } }
+
+    context "if the product is not registered" do
+      it "returns nil" do
+        expect(subject.subscription).to be_nil
+      end
+    end
+
+    context "if the product is registered" do
+      before do
+        allow(SUSE::Connect::YaST).to receive(:status).and_return(status)
+      end
+
+      let(:status) { OpenStruct.new(activations: [activation]) }
+
+      let(:activation) do
+        product = OpenStruct.new(identifier: "SLES", version: "16.0")
+        OpenStruct.new(
+          service: OpenStruct.new(product: product), expires_at: "2027-01-31T00:00:00.000Z"
+        )
+      end
+
+      it "returns the registered product and its expiration date" do
+        subject.register("11112222", email: "test@test.com")
+
+        expect(subject.subscription).to have_attributes(
+          product: "SLES/16.0/x86_64", expires_at: "2027-01-31T00:00:00.000Z"
+        )
+      end
+
+      context "and the subscription details cannot be read" do
+        before do
+          allow(SUSE::Connect::YaST).to receive(:status).and_raise(Timeout::Error)
+        end
+
+        it "returns the registered product without expiration date" do
+          subject.register("11112222", email: "test@test.com")
+
+          expect(subject.subscription).to have_attributes(
+            product: "SLES/16.0/x86_64", expires_at: nil
+          )
+        end
+      end
+    end
+  end
+
+  describe "#extensions" do
+    let(:product) { Agama::Software::Product.new("SLES").tap { |p| p.version = "16.0" } }
+
+    before do
+      allow(SUSE::Connect::YaST).to receive(:status).and_return(status)
+    end
+
+    let(:status) { OpenStruct.new(activations: activations) }
+
+    let(:activations) do
+      [
+        activation("SLES", "16.0", "2027-01-31T00:00:00.000Z"),
+        activation("sle-module-basesystem", "16.0", "2027-01-31T00:00:00.000Z"),
+        activation("sle-ha", "16.0", "2026-12-31T00:00:00.000Z")
+      ]
+    end
+
+    def activation(identifier, version, expires_at)
+      product = OpenStruct.new(identifier: identifier, version: version)
+      OpenStruct.new(service: OpenStruct.new(product: product), expires_at: expires_at)
+    end
+
+    context "if the product is not registered" do
+      it "returns an empty list" do
+        expect(subject.extensions).to eq([])
+      end
+    end
+
+    context "if the product is registered" do
+      before do
+        subject.register("11112222", email: "test@test.com")
+      end
+
+      it "returns the activated extensions, except the product" do
+        expect(subject.extensions.map(&:id)).to eq(["sle-module-basesystem", "sle-ha"])
+      end
+
+      it "only includes the expiration dates which differ from the product one" do
+        expect(subject.extensions.map(&:expires_at)).to eq([nil, "2026-12-31T00:00:00.000Z"])
+      end
+    end
+  end
+
   describe "#requirement" do
     context "if there is not product selected yet" do
       let(:product) { nil }
@@ -0,0 +1,97 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require_relative "../test_helper"
+require "agama/scripts"
+require "tmpdir"
+
+describe Agama::Scripts do
+  subject { described_class.new(logger) }
+
+  let(:logger) { Logger.new($stdout, level: :warn) }
+  let(:destdir) { Dir.mktmpdir }
+  let(:scripts_dir) { File.join(destdir, "var/lib/agama/scripts") }
+  let(:units_dir) { File.join(destdir, "etc/systemd/system") }
+
+  before do
+    allow(Yast::Installation).to receive(:destdir).and_return(destdir)
+    allow(Yast::Execute).to receive(:on_target!)
+  end
+
+  after do
+    FileUtils.remove_entry(destdir)
+  end
+
+  describe "#load_json" do
+    it "rejects the documents which are not JSON objects" do
+      expect(subject.load_json("[]")).to eq(false)
+      expect(subject.load_json("{")).to eq(false)
+      expect(subject.settings).to eq({})
+    end
+  end
+
+  describe "#write" do
+    before do
+      subject.load_json(
+        '{ "scripts": [{ "name": "setup", "body": "echo $MODE", "shell": "/bin/bash", ' \
+        '"env": { "MODE": "ser\\"ver" }, "locale": "de_DE.UTF-8" }, ' \
+        '{ "name": "check", "body": "true", "trigger": "everyBoot" }] }'
+      )
+    end
+
+    it "copies the scripts and their environment to the target system" do
+      subject.write
+
+      script = File.join(scripts_dir, "setup")
+      expect(File.read(script)).to eq("echo $MODE")
+      expect(File.stat(script).mode & 0o777).to eq(0o700)
+      env = File.read(File.join(scripts_dir, "setup.env")).lines.map(&:chomp)
+      expect(env).to contain_exactly("MODE=\"ser\\\"ver\"", "LANG=\"de_DE.UTF-8\"")
+    end
+
+    it "writes a service which runs the first boot scripts only once" do
+      subject.write
+
+      unit = File.read(File.join(units_dir, "agama-script-setup.service")).lines.map(&:chomp)
+      expect(unit).to include(
+        "ConditionPathExists=!/var/lib/agama/scripts/setup.done",
+        "ExecStart=/bin/bash /var/lib/agama/scripts/setup",
+        "ExecStartPost=/usr/bin/touch /var/lib/agama/scripts/setup.done"
+      )
+    end
+
+    it "writes a service which runs the every boot scripts unconditionally" do
+      subject.write
+
+      unit = File.read(File.join(units_dir, "agama-script-check.service"))
+      expect(unit).to include("ExecStart=/bin/sh /var/lib/agama/scripts/check")
+      expect(unit).to_not include("ConditionPathExists")
+    end
+
+    it "enables the services" do
+      expect(Yast::Execute).to receive(:on_target!)
+        .with("systemctl", "enable", "agama-script-setup.service")
+      expect(Yast::Execute).to receive(:on_target!)
+        .with("systemctl", "enable", "agama-script-check.service")
+      subject.write
+    end
+  end
+end
@@ -0,0 +1,114 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require_relative "../test_helper"
+require "agama/self_update"
+require "tmpdir"
+
+describe Agama::SelfUpdate do
+  subject { described_class.new(logger) }
+
+  let(:logger) { Logger.new($stdout, level: :warn) }
+  let(:tmpdir) { Dir.mktmpdir }
+  let(:state_file) { File.join(tmpdir, "self_update") }
+  let(:skip_file) { File.join(tmpdir, "self_update_skip") }
+
+  let(:updates) do
+    <<~XML
+      <?xml version='1.0'?>
+      <stream>
+        <update-status version="0.6">
+          <update-list>
+            <update kind="package" name="agama" edition="11-1.1" arch="x86_64"/>
+          </update-list>
+        </update-status>
+      </stream>
+    XML
+  end
+
+  before do
+    stub_const("Agama::SelfUpdate::STATE_FILE", state_file)
+    stub_const("Agama::SelfUpdate::SKIP_FILE", skip_file)
+    allow(Yast::Execute).to receive(:locally!)
+      .with("rpm", any_args).and_return("10-1.1")
+    allow(Yast::Execute).to receive(:locally!)
+      .with("zypper", any_args).and_return(updates)
+  end
+
+  after do
+    FileUtils.remove_entry(tmpdir)
+  end
+
+  context "when an update is available" do
+    it "reports the version jump" do
+      expect(subject.available?).to eq(true)
+      expect(subject.applied?).to eq(false)
+      expect(subject.from_version).to eq("10-1.1")
+      expect(subject.to_version).to eq("11-1.1")
+    end
+  end
+
+  context "when there is no update" do
+    let(:updates) { "<?xml version='1.0'?><stream><update-status/></stream>" }
+
+    it "reports no update" do
+      expect(subject.available?).to eq(false)
+      expect(subject.to_version).to be_nil
+    end
+  end
+
+  context "when the updates cannot be checked" do
+    before do
+      allow(Yast::Execute).to receive(:locally!).with("zypper", any_args)
+        .and_raise(Cheetah::ExecutionFailed.new([], 1, "", "repository not found"))
+    end
+
+    it "reports no update" do
+      expect(subject.available?).to eq(false)
+    end
+  end
+
+  context "when the update was applied" do
+    before do
+      File.write(state_file, "9-1.1 10-1.1\n")
+    end
+
+    it "reports the recorded versions" do
+      expect(subject.applied?).to eq(true)
+      expect(subject.available?).to eq(false)
+      expect(subject.from_version).to eq("9-1.1")
+      expect(subject.to_version).to eq("10-1.1")
+    end
+
+    it "does not allow skipping it" do
+      expect(subject.skip).to eq(false)
+      expect(File).to_not exist(skip_file)
+    end
+  end
+
+  describe "#skip" do
+    it "disables the self-update for the rest of the session" do
+      expect(subject.skip).to eq(true)
+      expect(subject.skipped?).to eq(true)
+      expect(File).to exist(skip_file)
+    end
+  end
+end
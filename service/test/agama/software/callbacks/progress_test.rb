@@ -0,0 +1,47 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require_relative "../../../test_helper"
+require "agama/progress"
+require "agama/software/callbacks/progress"
+
+describe Agama::Software::Callbacks::Progress do
+  subject do
+    described_class.new(2, progress, size: 300) { |*args| reported << args }
+  end
+
+  let(:progress) { instance_double(Agama::Progress, step: nil) }
+  let(:reported) { [] }
+
+  describe "#start_package" do
+    it "reports the installed packages and bytes" do
+      subject.send(:start_package, "bash", "", "", 100, false)
+      subject.send(:start_package, "vim", "", "", 200, false)
+
+      expect(reported).to eq([["bash", 0, 2, 0, 300], ["vim", 1, 2, 100, 300]])
+    end
+
+    it "updates the progress" do
+      expect(progress).to receive(:step).with("Installing bash")
+      subject.send(:start_package, "bash", "", "", 100, false)
+    end
+  end
+end
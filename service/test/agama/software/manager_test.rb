@@ -26,6 +26,7 @@ require_relative File.join(SRC_PATH, "agama/dbus/y2dir/software/modules/PackageC
 require "agama/config"
 require "agama/issue"
 require "agama/registration"
+require "agama/software/extra_repository"
 require "agama/software/manager"
 require "agama/software/product"
 require "agama/software/proposal"
@@ -339,6 +340,96 @@ describe Agama::Software::Manager do
     end
   end
 
+  describe "#systemd_units=" do
+    it "raises an error if some action is unknown" do
+      expect { subject.systemd_units = [["sshd.service", "restart"]] }
+        .to raise_error(ArgumentError)
+    end
+  end
+
+  describe "#extra_repositories=" do
+    let(:tools) do
+      Agama::Software::ExtraRepository.new("tools", "https://example.net/tools", "", true)
+    end
+    let(:extra) do
+      Agama::Software::ExtraRepository.new("extra", "dir:///srv/extra", "Extra", false)
+    end
+
+    before do
+      allow(Yast::Pkg).to receive(:RepositoryAdd).and_return(100, 101)
+      allow(Yast::Pkg).to receive(:SourceDelete)
+      allow(Yast::Pkg).to receive(:SourceLoad)
+    end
+
+    it "adds the repositories to the package management" do
+      expect(Yast::Pkg).to receive(:RepositoryAdd).with(
+        "alias" => "tools", "name" => "tools", "base_urls" => ["https://example.net/tools"],
+        "enabled" => true, "autorefresh" => true
+      )
+      expect(Yast::Pkg).to receive(:RepositoryAdd).with(hash_including("name" => "Extra"))
+
+      subject.extra_repositories = [tools, extra]
+      expect(subject.extra_repositories).to eq([tools, extra])
+    end
+
+    it "removes the previous repositories" do
+      subject.extra_repositories = [tools]
+      expect(Yast::Pkg).to receive(:SourceDelete).with(100)
+
+      subject.extra_repositories = [extra]
+    end
+
+    it "raises an error if some alias is duplicated" do
+      expect { subject.extra_repositories = [tools, tools] }.to raise_error(ArgumentError)
+    end
+
+    it "raises an error if some repository cannot be added" do
+      allow(Yast::Pkg).to receive(:RepositoryAdd).and_return(100, nil)
+
+      expect { subject.extra_repositories = [tools, extra] }
+        .to raise_error(ArgumentError, /extra/)
+      expect(subject.extra_repositories).to eq([tools])
+    end
+  end
+
+  describe "#default_target=" do
+    it "raises an error if the target is unknown" do
+      expect { subject.default_target = "rescue.target" }.to raise_error(ArgumentError)
+    end
+
+    it "uses the default of the product if the target is empty" do
+      subject.default_target = "multi-user.target"
+      subject.default_target = ""
+      expect(subject.default_target).to be_nil
+    end
+  end
+
+  describe "#only_required=" do
+    before do
+      subject.select_product("Tumbleweed")
+      allow(proposal).to receive(:only_required=)
+    end
+
+    it "sets the option in the proposal and recalculates it" do
+      expect(proposal).to receive(:only_required=).with(true)
+      expect(proposal).to receive(:calculate)
+      subject.only_required = true
+    end
+  end
+
+  describe "#multilib=" do
+    before do
+      subject.select_product("Tumbleweed")
+      allow(proposal).to receive(:multilib=)
+    end
+
+    it "sets the option in the proposal and recalculates it" do
+      expect(proposal).to receive(:multilib=).with(false)
+      expect(proposal).to receive(:calculate)
+      subject.multilib = false
+    end
+  end
+
   describe "#install" do
     let(:commit_result) { [250, [], [], [], []] }
 
@@ -382,6 +473,92 @@ describe Agama::Software::Manager do
       subject.finish
     end
 
+    context "when some systemd units are configured" do
+      before do
+        allow(subject).to receive(:copy_zypp_to_target)
+        allow(Yast::Pkg).to receive(:SourceSaveAll)
+        allow(Yast::Pkg).to receive(:TargetFinish)
+        subject.systemd_units = [["sshd.service", "enable"], ["cups.socket", "mask"]]
+      end
+
+      it "applies the action to each unit in the installed system" do
+        expect(Yast::Execute).to receive(:on_target!)
+          .with("systemctl", "enable", "sshd.service")
+        expect(Yast::Execute).to receive(:on_target!)
+          .with("systemctl", "mask", "cups.socket")
+
+        subject.finish
+      end
+
+      it "does not abort when an action fails" do
+        allow(Yast::Execute).to receive(:on_target!)
+          .with("systemctl", "enable", "sshd.service")
+          .and_raise(Cheetah::ExecutionFailed.new([], "", nil, nil))
+        expect(Yast::Execute).to receive(:on_target!)
+          .with("systemctl", "mask", "cups.socket")
+
+        subject.finish
+      end
+    end
+
+    context "when there are extra repositories" do
+      let(:destdir) { Dir.mktmpdir }
+
+      let(:tools) do
+        Agama::Software::ExtraRepository.new("tools", "https://example.net/tools", "", true)
+      end
+      let(:extra) { Agama::Software::ExtraRepository.new("extra", "dir:///srv/extra", "", false) }
+
+      before do
+        allow(subject).to receive(:copy_zypp_to_target)
+        allow(Yast::Pkg).to receive(:SourceSaveAll)
+        allow(Yast::Pkg).to receive(:TargetFinish)
+        allow(Yast::Pkg).to receive(:RepositoryAdd).and_return(100, 101)
+        allow(Yast::Pkg).to receive(:SourceLoad)
+        subject.extra_repositories = [tools, extra]
+      end
+
+      after do
+        FileUtils.remove_entry(destdir)
+      end
+
+      it "does not save them along with the product repositories" do
+        expect(Yast::Pkg).to receive(:SourceDelete).with(100).ordered
+        expect(Yast::Pkg).to receive(:SourceDelete).with(101).ordered
+        expect(Yast::Pkg).to receive(:SourceSaveAll).ordered
+
+        subject.finish
+      end
+
+      it "writes the persistent ones to the zypp configuration of the installed system" do
+        allow(Yast::Pkg).to receive(:SourceDelete)
+
+        subject.finish
+
+        repos_dir = File.join(destdir, "etc", "zypp", "repos.d")
+        expect(Dir.children(repos_dir)).to eq(["tools.repo"])
+        expect(File.read(File.join(repos_dir, "tools.repo"))).to eq(
+          "[tools]\nname=tools\nenabled=1\nautorefresh=1\nbaseurl=https://example.net/tools\n"
+        )
+      end
+    end
+
+    context "when the default target is set" do
+      before do
+        allow(subject).to receive(:copy_zypp_to_target)
+        allow(Yast::Pkg).to receive(:SourceSaveAll)
+        allow(Yast::Pkg).to receive(:TargetFinish)
+        subject.default_target = "multi-user.target"
+      end
+
+      it "sets the default target in the installed system" do
+        expect(Yast::Execute).to receive(:on_target!)
+          .with("systemctl", "set-default", "multi-user.target")
+
+        subject.finish
+      end
+    end
+
     it "copies the libzypp cache and credentials to the target system" do
       allow(Dir).to receive(:exist?).and_call_original
       allow(Dir).to receive(:entries).and_call_original
@@ -462,6 +639,29 @@ describe Agama::Software::Manager do
     end
   end
 
+  describe "#package_changes" do
+    before do
+      allow(Yast::Pkg).to receive(:ResolvableProperties).with("", :package, "").and_return(
+        [
+          { "name" => "bash", "version" => "5.2-1.1", "status" => :installed },
+          { "name" => "kernel-default", "version" => "6.4-1.1", "status" => :removed },
+          { "name" => "kernel-default", "version" => "6.8-1.1", "status" => :selected },
+          { "name" => "vim", "version" => "9.1-1.1", "status" => :selected },
+          { "name" => "nano", "version" => "7.2-1.1", "status" => :removed },
+          { "name" => "emacs", "version" => "29.1-1.1", "status" => :available }
+        ]
+      )
+    end
+
+    it "returns the packages to upgrade, install or remove" do
+      expect(subject.package_changes).to contain_exactly(
+        ["kernel-default", "6.4-1.1", "6.8-1.1"],
+        ["vim", "", "9.1-1.1"],
+        ["nano", "7.2-1.1", ""]
+      )
+    end
+  end
+
   describe "#package_available?" do
     before do
       allow(Yast::Package).to receive(:Available).with(package).and_return(available)
@@ -38,16 +38,18 @@ describe Agama::Software::ProductBuilder do
   let(:products) do
     [
       {
-        "id"           => "Test1",
-        "name"         => "Product Test 1",
-        "description"  => "This is a test product named Test 1",
-        "translations" => {
+        "id"            => "Test1",
+        "name"          => "Product Test 1",
+        "description"   => "This is a test product named Test 1",
+        "release_notes" => { "en" => "https://repos/test1/release-notes.txt" },
+        "requirements"  => { "min_memory" => 1_073_741_824, "archs" => ["x86_64"] },
+        "translations"  => {
           "description" => {
             "cs" => "Czech",
             "es" => "Spanish"
           }
         },
-        "software"     => {
+        "software"      => {
           "installation_repositories" => [
             {
               "url"   => "https://repos/test1/x86_64/product/",
@@ -148,7 +150,9 @@ describe Agama::Software::ProductBuilder do
             optional_patterns:  ["pattern1-3"],
             mandatory_packages: ["package1-1", "package1-2", "package1-3"],
             optional_packages:  ["package1-5"],
-            translations:       { "description" => { "cs" => "Czech", "es" => "Spanish" } }
+            translations:       { "description" => { "cs" => "Czech", "es" => "Spanish" } },
+            release_notes:      { "en" => "https://repos/test1/release-notes.txt" },
+            requirements:       { "min_memory" => 1_073_741_824, "archs" => ["x86_64"] }
           ),
           an_object_having_attributes(
             id:                 "Test2",
@@ -161,7 +165,9 @@ describe Agama::Software::ProductBuilder do
             optional_patterns:  [],
             mandatory_packages: [],
             optional_packages:  [],
-            translations:       {}
+            translations:       {},
+            release_notes:      {},
+            requirements:       {}
           )
         )
       end
@@ -62,6 +62,33 @@ describe Agama::Software::Proposal do
       expect(subject.calculate).to eq(true)
     end
 
+    context "when only the required packages are selected" do
+      before do
+        subject.only_required = true
+      end
+
+      it "tells the solver to ignore the recommended packages" do
+        expect(Yast::Pkg).to receive(:SetSolverFlags)
+          .with("ignoreAlreadyRecommended" => false, "onlyRequires" => true)
+        subject.calculate
+      end
+    end
+
+    context "when the multilib packages are forbidden" do
+      before do
+        subject.multilib = false
+        allow(Yast::Pkg).to receive(:Resolvables).with({ kind: :package }, [:name])
+          .and_return([{ "name" => "glibc" }, { "name" => "glibc-32bit" },
+                       { "name" => "glibc-32bit" }])
+      end
+
+      it "marks the multilib packages as taboo before solving the dependencies" do
+        expect(Yast::Pkg).to receive(:PkgTaboo).with("glibc-32bit").ordered.once
+        expect(Yast::Pkg).to receive(:PkgSolve).ordered
+        subject.calculate
+      end
+    end
+
     context "when a proposal is not possible or contain errors" do
       let(:solve_errors) { 1 }
 
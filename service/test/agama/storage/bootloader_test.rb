@@ -0,0 +1,219 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require_relative "../../test_helper"
+require "agama/config"
+require "agama/storage/bootloader"
+require "bootloader/grub2efi"
+require "tmpdir"
+
+describe Agama::Storage::Bootloader do
+  subject { described_class.new(config, logger) }
+
+  let(:config) { Agama::Config.new({}) }
+  let(:logger) { Logger.new($stdout, level: :warn) }
+
+  let(:grub) do
+    instance_double(::Bootloader::Grub2EFI,
+      grub_default: grub_default, packages: ["grub2-x86_64-efi", "shim"])
+  end
+  let(:grub_default) do
+    double("grub_default", :timeout= => nil, generic_set: nil, kernel_params: kernel_params)
+  end
+  let(:kernel_params) { double("kernel_params", add_parameter: nil) }
+
+  before do
+    allow(::Bootloader::BootloaderFactory).to receive(:current).and_return(grub)
+    allow(::Bootloader::BootloaderFactory).to receive(:current_name).and_return("grub2-efi")
+    allow(grub).to receive(:secure_boot=)
+    allow(kernel_params).to receive(:parameter).and_return(false)
+    allow(Y2Storage::Arch).to receive(:new)
+      .and_return(instance_double(Y2Storage::Arch, efiboot?: true))
+  end
+
+  describe "#load_json" do
+    it "loads the settings from a JSON object" do
+      expect(subject.load_json('{ "timeout": 10 }')).to eq(true)
+      expect(subject.settings).to eq("timeout" => 10)
+    end
+
+    it "rejects the documents which are not JSON objects" do
+      expect(subject.load_json("10")).to eq(false)
+      expect(subject.load_json("{")).to eq(false)
+      expect(subject.settings).to eq({})
+    end
+  end
+
+  describe "#apply_proposal" do
+    it "sets the menu timeout and style" do
+      subject.load_json('{ "timeout": 3, "showMenu": false }')
+      expect(grub_default).to receive(:timeout=).with("3")
+      expect(grub_default).to receive(:generic_set).with("GRUB_TIMEOUT_STYLE", "hidden")
+      subject.apply_proposal
+    end
+
+    it "selects the packages needed by the bootloader" do
+      expect(Yast::PackagesProposal).to receive(:SetResolvables)
+        .with("agama-bootloader", :package, ["grub2-x86_64-efi", "shim"])
+      subject.apply_proposal
+    end
+
+    context "when kdump is enabled" do
+      it "selects the kdump package too" do
+        subject.load_json('{ "kdump": { "enabled": true, "reservedMemory": 256 } }')
+        expect(Yast::PackagesProposal).to receive(:SetResolvables)
+          .with("agama-bootloader", :package, ["grub2-x86_64-efi", "shim", "kdump"])
+        subject.apply_proposal
+      end
+    end
+
+    it "appends the extra kernel parameters" do
+      subject.load_json('{ "extraKernelParams": ["console=ttyS0", "quiet"] }')
+      expect(kernel_params).to receive(:add_parameter).with("console", "ttyS0")
+      expect(kernel_params).to receive(:add_parameter).with("quiet", true)
+      subject.apply_proposal
+    end
+
+    it "does not repeat the kernel parameters which are already set" do
+      subject.load_json('{ "extraKernelParams": ["quiet"] }')
+      allow(kernel_params).to receive(:parameter).with("quiet").and_return(true)
+      expect(kernel_params).to_not receive(:add_parameter)
+      subject.apply_proposal
+    end
+
+    context "when Secure Boot is requested" do
+      it "enables the Secure Boot support" do
+        subject.load_json('{ "secureBoot": true }')
+        expect(grub).to receive(:secure_boot=).with(true)
+        subject.apply_proposal
+      end
+    end
+
+    context "when Secure Boot is not set" do
+      it "keeps the proposed Secure Boot support" do
+        expect(grub).to_not receive(:secure_boot=)
+        subject.apply_proposal
+      end
+    end
+
+    context "when another bootloader is selected" do
+      before do
+        allow(::Bootloader::BootloaderFactory).to receive(:current_name=)
+        allow(grub).to receive(:propose)
+        subject.load_json('{ "bootloader": "systemd-boot" }')
+      end
+
+      it "selects and proposes it" do
+        expect(::Bootloader::BootloaderFactory).to receive(:current_name=).with("systemd-boot")
+        expect(grub).to receive(:propose)
+        subject.apply_proposal
+      end
+    end
+  end
+
+  describe "#write" do
+    let(:destdir) { Dir.mktmpdir }
+
+    before do
+      allow(Yast::Installation).to receive(:destdir).and_return(destdir)
+      allow(Yast::Execute).to receive(:on_target!)
+    end
+
+    after do
+      FileUtils.remove_entry(destdir)
+    end
+
+    it "writes the kernel modules to load and their options" do
+      subject.load_json(
+        '{ "kernelModules": { "load": [{ "name": "br_netfilter" }, ' \
+        '{ "name": "nf_conntrack", "options": "hashsize=1024" }] } }'
+      )
+      subject.write
+
+      modules_load = File.read(File.join(destdir, "etc/modules-load.d/agama.conf"))
+      expect(modules_load.lines.map(&:chomp)).to include("br_netfilter", "nf_conntrack")
+      modprobe = File.read(File.join(destdir, "etc/modprobe.d/50-agama.conf"))
+      expect(modprobe).to include("options nf_conntrack hashsize=1024")
+    end
+
+    it "writes the blacklisted kernel modules" do
+      subject.load_json('{ "kernelModules": { "blacklist": ["nouveau"] } }')
+      subject.write
+
+      modprobe = File.read(File.join(destdir, "etc/modprobe.d/50-agama.conf"))
+      expect(modprobe).to include("blacklist nouveau")
+      expect(File).to_not exist(File.join(destdir, "etc/modules-load.d/agama.conf"))
+    end
+
+    it "writes the sysctl tunables" do
+      subject.load_json('{ "sysctl": { "vm.swappiness": "10" } }')
+      subject.write
+
+      sysctl = File.read(File.join(destdir, "etc/sysctl.d/70-agama.conf"))
+      expect(sysctl).to include("vm.swappiness = 10")
+    end
+
+    context "when a bootloader password is set" do
+      let(:grub_linux) { File.join(destdir, "etc/grub.d/10_linux") }
+      let(:hash) { "grub.pbkdf2.sha512.10000.AB12.CD34" }
+
+      before do
+        FileUtils.mkdir_p(File.dirname(grub_linux))
+        File.write(grub_linux, "CLASS=\"--class gnu-linux --class gnu --class os\"\n")
+        subject.load_json(%({ "password": { "user": "admin", "hashedPassword": "#{hash}" } }))
+      end
+
+      it "writes a GRUB script which sets the superuser and the password" do
+        subject.write
+
+        file = File.join(destdir, "etc/grub.d/43_agama_password")
+        lines = File.read(file).lines.map(&:chomp)
+        expect(lines).to include("set superusers=\"admin\"", "password_pbkdf2 admin #{hash}")
+        expect(File.stat(file).mode & 0o777).to eq(0o700)
+      end
+
+      it "allows booting the entries without the password" do
+        subject.write
+        subject.write
+
+        expect(File.read(grub_linux))
+          .to eq("CLASS=\"--class gnu-linux --class gnu --class os --unrestricted\"\n")
+      end
+    end
+
+    it "enables the kdump service if kdump is enabled" do
+      subject.load_json('{ "kdump": { "enabled": true } }')
+      expect(Yast::Execute).to receive(:on_target!).with("systemctl", "enable", "kdump")
+      subject.write
+    end
+
+    it "does not enable the kdump service if kdump is disabled" do
+      subject.load_json('{ "kdump": { "enabled": false } }')
+      expect(Yast::Execute).to_not receive(:on_target!)
+      subject.write
+    end
+
+    it "does not write any file if there are no kernel modules or sysctl settings" do
+      subject.write
+      expect(Dir.children(destdir)).to be_empty
+    end
+  end
+end
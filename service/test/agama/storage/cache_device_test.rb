@@ -0,0 +1,98 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require_relative "../../test_helper"
+require_relative "storage_helpers"
+require "agama/storage/cache_device"
+require "y2storage"
+
+describe Agama::Storage::CacheDevice do
+  include Agama::RSpec::StorageHelpers
+
+  subject { described_class.new("/dev/nvme0n1", backing_device, cache_method) }
+
+  let(:devicegraph) { Y2Storage::StorageManager.instance.staging }
+
+  let(:backing_device) { "/dev/sda" }
+  let(:cache_method) { :bcache }
+
+  before do
+    mock_storage(devicegraph: "nvme-hdd.yaml")
+  end
+
+  describe "#kernel_module" do
+    it "returns the kernel module of the method" do
+      expect(subject.kernel_module).to eq("bcache")
+      subject.cache_method = :lvm
+      expect(subject.kernel_module).to eq("dm-cache")
+    end
+  end
+
+  describe "#create" do
+    it "creates a bcache device using both devices" do
+      subject.mode = :writeback
+
+      expect(subject.create(devicegraph)).to be_nil
+
+      bcache = devicegraph.bcaches.first
+      expect(bcache.backing_device.name).to eq("/dev/sda")
+      expect(bcache.bcache_cset.blk_devices.map(&:name)).to eq(["/dev/nvme0n1"])
+      expect(bcache.cache_mode).to eq(Y2Storage::CacheMode::WRITEBACK)
+      expect(devicegraph.find_by_name("/dev/sda").filesystem).to be_nil
+    end
+
+    context "when the method is LVM" do
+      let(:cache_method) { :lvm }
+
+      it "creates a logical volume on the backing device" do
+        expect(subject.create(devicegraph)).to be_nil
+
+        vg = devicegraph.find_by_name("/dev/sda").lvm_pv.lvm_vg
+        expect(vg.vg_name).to eq("cache0")
+        expect(vg.lvm_pvs.map { |p| p.blk_device.name }).to eq(["/dev/sda"])
+        expect(subject.lvm_lv(devicegraph).lv_name).to eq("cached")
+        expect(devicegraph.find_by_name("/dev/nvme0n1").descendants).to be_empty
+      end
+    end
+
+    context "when a device is unknown" do
+      let(:backing_device) { "/dev/sdc" }
+
+      it "returns an error" do
+        expect(subject.create(devicegraph)).to match(%r{Unknown device /dev/sdc})
+        expect(devicegraph.bcaches).to be_empty
+      end
+    end
+
+    context "when a device is used by the installation" do
+      let(:backing_device) { "/dev/sdb" }
+
+      before do
+        devicegraph.find_by_name("/dev/sdb").create_filesystem(Y2Storage::Filesystems::Type::XFS)
+      end
+
+      it "returns an error" do
+        expect(subject.create(devicegraph)).to match(%r{/dev/sdb is used by the installation})
+        expect(devicegraph.bcaches).to be_empty
+      end
+    end
+  end
+end
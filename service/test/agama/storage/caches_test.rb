@@ -0,0 +1,96 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require_relative "../../test_helper"
+require_relative "storage_helpers"
+require "agama/storage/caches"
+require "agama/storage/cache_device"
+require "tmpdir"
+require "y2storage"
+
+describe Agama::Storage::Caches do
+  include Agama::RSpec::StorageHelpers
+
+  subject { described_class.new(caches, logger) }
+
+  let(:logger) { Logger.new($stdout, level: :warn) }
+  let(:destdir) { Dir.mktmpdir }
+  let(:devicegraph) { Y2Storage::StorageManager.instance.staging }
+
+  let(:bcache) { Agama::Storage::CacheDevice.new("/dev/nvme0n1", "/dev/sdb", :bcache) }
+  let(:lvm) do
+    Agama::Storage::CacheDevice.new("/dev/nvme0n1", "/dev/sda", :lvm).tap do |cache|
+      cache.mode = :writeback
+    end
+  end
+  let(:caches) { [lvm] }
+
+  before do
+    mock_storage(devicegraph: "nvme-hdd.yaml")
+    lvm.create(devicegraph)
+    allow(Yast::Installation).to receive(:destdir).and_return(destdir)
+    allow(Yast::Execute).to receive(:locally!)
+  end
+
+  after do
+    FileUtils.remove_entry(destdir)
+  end
+
+  describe "#any?" do
+    it "returns whether there is any cached device" do
+      expect(subject.any?).to eq(true)
+      expect(described_class.new([], logger).any?).to eq(false)
+    end
+  end
+
+  describe "#write" do
+    it "attaches the LVM caches to their logical volumes" do
+      expect(Yast::Execute).to receive(:locally!).with("vgextend", "cache0", "/dev/nvme0n1")
+      expect(Yast::Execute).to receive(:locally!).with(
+        "lvcreate", "--type", "cache", "--cachemode", "writeback", "--extents", "100%PVS",
+        "--name", "cached_cache", "cache0/cached", "/dev/nvme0n1"
+      )
+      subject.write
+    end
+
+    it "does not attach the cache if the volume group cannot be extended" do
+      allow(Yast::Execute).to receive(:locally!).with("vgextend", any_args)
+        .and_raise(Cheetah::ExecutionFailed.new([], "", "", ""))
+      expect(Yast::Execute).to_not receive(:locally!).with("lvcreate", any_args)
+      subject.write
+    end
+
+    context "when there are bcache devices" do
+      let(:caches) { [bcache, lvm] }
+
+      it "does not run any command for them" do
+        expect(Yast::Execute).to receive(:locally!).with("vgextend", any_args).once
+        subject.write
+      end
+    end
+
+    it "loads the kernel modules of the caches on boot" do
+      described_class.new([bcache, lvm, lvm], logger).write
+      file = File.join(destdir, "etc", "modules-load.d", "agama-cache.conf")
+      expect(File.read(file)).to eq("bcache\ndm-cache\n")
+    end
+  end
+end
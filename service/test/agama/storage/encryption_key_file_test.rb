@@ -0,0 +1,96 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+
+require_relative "../../test_helper"
+require "agama/storage/encryption_key_file"
+require "y2storage"
+require "tmpdir"
+
+describe Agama::Storage::EncryptionKeyFile do
+  subject do
+    described_class.new("/luks.key").tap do |key_file|
+      key_file.device = "LABEL=USBKEY"
+      key_file.source = source
+    end
+  end
+
+  let(:logger) { Logger.new($stdout, level: :warn) }
+  let(:destdir) { Dir.mktmpdir }
+  let(:source) { File.join(destdir, "luks.key") }
+  let(:crypttab) { File.join(destdir, "etc", "crypttab") }
+  let(:blk_device) { instance_double(Y2Storage::Partition, name: "/dev/sda2") }
+  let(:encryption) do
+    instance_double(Y2Storage::Encryption, blk_device: blk_device, dm_table_name: "cr_root")
+  end
+
+  before do
+    allow(Yast::Installation).to receive(:destdir).and_return(destdir)
+    allow(Yast::Execute).to receive(:locally!)
+    File.write(source, "secret-key")
+    FileUtils.mkdir_p(File.dirname(crypttab))
+    File.write(crypttab, "cr_root UUID=1234 none x-initrd.attach\ncr_home UUID=5678 none\n")
+  end
+
+  after do
+    FileUtils.remove_entry(destdir)
+  end
+
+  describe "#crypttab" do
+    it "returns the key file path and its device" do
+      expect(subject.crypttab).to eq("/luks.key:LABEL=USBKEY")
+    end
+  end
+
+  describe "#write" do
+    it "adds the key to the devices using their passphrase" do
+      expect(Yast::Execute).to receive(:locally!)
+        .with("cryptsetup", "luksAddKey", "--key-file=-", "/dev/sda2", source, stdin: "n0ts3cr3t")
+      subject.write([encryption], "n0ts3cr3t", logger)
+    end
+
+    it "references the key file in the crypttab of the given devices" do
+      subject.write([encryption], "n0ts3cr3t", logger)
+
+      expect(File.read(crypttab).lines).to eq(
+        [
+          "cr_root UUID=1234 /luks.key:LABEL=USBKEY x-initrd.attach\n",
+          "cr_home UUID=5678 none\n"
+        ]
+      )
+    end
+
+    it "removes the key material" do
+      subject.write([encryption], "n0ts3cr3t", logger)
+      expect(File).to_not exist(source)
+    end
+
+    context "when the key material is not available" do
+      before { FileUtils.rm(source) }
+
+      it "does not modify the devices" do
+        expect(Yast::Execute).to_not receive(:locally!)
+        subject.write([encryption], "n0ts3cr3t", logger)
+        expect(File.read(crypttab)).to include("cr_root UUID=1234 none")
+      end
+    end
+  end
+end
@@ -0,0 +1,129 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require_relative "../../test_helper"
+require_relative "storage_helpers"
+require "agama/storage/esp_settings"
+require "y2storage"
+
+describe Agama::Storage::EspSettings do
+  include Agama::RSpec::StorageHelpers
+
+  let(:devicegraph) { Y2Storage::StorageManager.instance.staging }
+
+  let(:vda) { devicegraph.find_by_name("/dev/vda") }
+
+  let(:vda1) { devicegraph.find_by_name("/dev/vda1") }
+
+  before do
+    mock_storage(devicegraph: "esp.yaml")
+  end
+
+  # Creates a new ESP in the free space of /dev/vda, as the proposal would do
+  def propose_new_esp
+    slot = vda.partition_table.unused_partition_slots.first
+    region = Y2Storage::Region.create(slot.region.start, 1_048_576, slot.region.block_size)
+    partition = vda.partition_table.create_partition(
+      slot.name, region, Y2Storage::PartitionType::PRIMARY
+    )
+    partition.id = Y2Storage::PartitionId::ESP
+    partition.create_filesystem(Y2Storage::Filesystems::Type::VFAT)
+      .create_mount_point("/boot/efi")
+    partition
+  end
+
+  describe "#apply" do
+    context "if the mode is :auto" do
+      it "does not modify the devicegraph" do
+        esp = propose_new_esp
+
+        expect(subject.apply(devicegraph)).to be_nil
+        expect(esp.filesystem.mount_path).to eq("/boot/efi")
+        expect(vda1.filesystem.mount_point).to be_nil
+      end
+    end
+
+    context "if the proposal does not include an ESP" do
+      before { subject.mode = :create }
+
+      it "returns an error" do
+        expect(subject.apply(devicegraph)).to match(/does not include/)
+      end
+    end
+
+    context "if the mode is :reuse" do
+      before do
+        subject.mode = :reuse
+        subject.device = "/dev/vda1"
+      end
+
+      it "mounts the given partition instead of the proposed ESP" do
+        propose_new_esp
+        subject.mount_path = "/efi"
+
+        expect(subject.apply(devicegraph)).to be_nil
+        expect(vda.partitions.map(&:name)).to contain_exactly("/dev/vda1", "/dev/vda2")
+        expect(vda1.filesystem.mount_path).to eq("/efi")
+      end
+
+      context "and the partition does not contain a FAT file system" do
+        before { subject.device = "/dev/vda2" }
+
+        it "returns an error" do
+          propose_new_esp
+          expect(subject.apply(devicegraph)).to match(/FAT/)
+        end
+      end
+    end
+
+    context "if the mode is :create" do
+      before do
+        subject.mode = :create
+        subject.size = Y2Storage::DiskSize.MiB(256).to_i
+      end
+
+      context "and the proposal reuses an existing ESP" do
+        before { vda1.filesystem.create_mount_point("/boot/efi") }
+
+        it "creates a new ESP of the given size" do
+          expect(subject.apply(devicegraph)).to be_nil
+
+          esp = devicegraph.filesystems.find { |f| f.mount_path == "/boot/efi" }
+          partition = esp.blk_devices.first
+          expect(partition.name).to_not eq("/dev/vda1")
+          expect(partition.id).to eq(Y2Storage::PartitionId::ESP)
+          expect(partition.size).to eq(Y2Storage::DiskSize.MiB(256))
+          expect(vda1.filesystem.mount_point).to be_nil
+        end
+      end
+
+      context "and the proposal creates a new ESP" do
+        it "keeps the proposed ESP" do
+          esp = propose_new_esp
+
+          expect(subject.apply(devicegraph)).to be_nil
+          expect(vda.partitions.size).to eq(3)
+          expect(esp.filesystem.mount_path).to eq("/boot/efi")
+        end
+      end
+    end
+  end
+end
@@ -0,0 +1,76 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require_relative "../../test_helper"
+require_relative "storage_helpers"
+require "agama/storage/explicit_partition"
+require "y2storage"
+
+describe Agama::Storage::ExplicitPartition do
+  include Agama::RSpec::StorageHelpers
+
+  subject { described_class.new(device, start, end_sector) }
+
+  let(:devicegraph) { Y2Storage::StorageManager.instance.staging }
+
+  let(:device) { "/dev/vda" }
+
+  # 1 MiB at 40 GiB
+  let(:start) { 83_886_080 }
+  let(:end_sector) { 83_888_127 }
+
+  before do
+    mock_storage(devicegraph: "disks.yaml")
+  end
+
+  describe "#create" do
+    it "creates the partition at the given sectors" do
+      subject.fs_type = "ext4"
+      subject.mount_path = "/data"
+
+      expect(subject.create(devicegraph)).to be_nil
+
+      partition = devicegraph.find_by_name("/dev/vda").partitions.max_by { |p| p.region.start }
+      expect(partition.region.start).to eq(start)
+      expect(partition.region.end).to eq(end_sector)
+      expect(partition.filesystem.type).to eq(Y2Storage::Filesystems::Type::EXT4)
+      expect(partition.filesystem.mount_path).to eq("/data")
+    end
+
+    context "when the sectors are not free" do
+      let(:start) { 4096 }
+      let(:end_sector) { 8191 }
+
+      it "does not create the partition" do
+        expect(subject.create(devicegraph)).to match(/not free/)
+        expect(devicegraph.find_by_name("/dev/vda").partitions.size).to eq(3)
+      end
+    end
+
+    context "when the disk has no partition table" do
+      let(:device) { "/dev/vdb" }
+
+      it "returns an error" do
+        expect(subject.create(devicegraph)).to match(/no partition table/)
+      end
+    end
+  end
+end
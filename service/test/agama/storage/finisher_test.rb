@@ -25,12 +25,21 @@ require_relative "../with_progress_examples"
 require "agama/helpers"
 require "agama/config"
 require "agama/security"
+require "agama/storage/bootloader"
+require "agama/storage/caches"
 require "agama/storage/finisher"
+require "agama/storage/nvmeof/manager"
+require "agama/storage/quotas"
+require "agama/storage/root_overlay"
 
 describe Agama::Storage::Finisher do
   include Agama::RSpec::StorageHelpers
 
-  subject(:storage) { described_class.new(logger, config, security) }
+  subject(:storage) do
+    described_class.new(
+      logger, config, security, bootloader, nvmeof, quotas, root_overlay, caches
+    )
+  end
 
   let(:logger) { Logger.new($stdout, level: :warn) }
   let(:config_path) do
@@ -40,6 +49,11 @@ describe Agama::Storage::Finisher do
   let(:destdir) { File.join(FIXTURES_PATH, "target_dir") }
   let(:config) { Agama::Config.from_file(config_path) }
   let(:security) { instance_double(Agama::Security, probe: nil, write: nil) }
+  let(:bootloader) { instance_double(Agama::Storage::Bootloader, write: nil) }
+  let(:nvmeof) { instance_double(Agama::Storage::NVMeoF::Manager, controllers: [], write: nil) }
+  let(:quotas) { instance_double(Agama::Storage::Quotas, any?: false, write: nil) }
+  let(:root_overlay) { instance_double(Agama::Storage::RootOverlay, enabled?: false, write: nil) }
+  let(:caches) { instance_double(Agama::Storage::Caches, any?: false, write: nil) }
   let(:copy_files) { Agama::Storage::Finisher::CopyFilesStep.new(logger) }
   let(:progress) { instance_double(Agama::Progress, step: nil) }
 
@@ -104,8 +118,56 @@ describe Agama::Storage::Finisher do
     end
   end
 
-  describe described_class::BootloaderStep do
+  describe described_class::FstabEntriesStep do
     subject { described_class.new(logger) }
+
+    let(:entries_file) { File.join(destdir, "run", "fstab") }
+    let(:fstab) { File.join(destdir, "etc", "fstab") }
+
+    before do
+      allow(Yast::Installation).to receive(:destdir).and_return(destdir)
+      allow(subject).to receive(:entries_file).and_return(entries_file)
+      FileUtils.mkdir_p(File.dirname(entries_file))
+      FileUtils.mkdir_p(File.dirname(fstab))
+      File.write(fstab, "/dev/sda2  /  btrfs  defaults  0  0\n")
+    end
+
+    after do
+      FileUtils.rm_rf(destdir)
+    end
+
+    describe "#run?" do
+      it "returns false if there are no additional entries" do
+        expect(subject.run?).to eq(false)
+      end
+    end
+
+    describe "#run" do
+      before do
+        File.write(
+          entries_file,
+          "server:/export  /srv/data  nfs4  defaults  0  0\n" \
+          "//server/share  /srv/share  cifs  ro  0  0\n"
+        )
+      end
+
+      it "appends the entries whose mount points are not in the fstab yet" do
+        subject.run
+        subject.run
+
+        expect(File.read(fstab).lines).to eq(
+          [
+            "/dev/sda2  /  btrfs  defaults  0  0\n",
+            "server:/export  /srv/data  nfs4  defaults  0  0\n",
+            "//server/share  /srv/share  cifs  ro  0  0\n"
+          ]
+        )
+      end
+    end
+  end
+
+  describe described_class::BootloaderStep do
+    subject { described_class.new(logger, bootloader) }
     let(:on_s390) { false }
 
     before do
@@ -133,6 +195,11 @@ describe Agama::Storage::Finisher do
       end
     end
 
+    it "writes the configuration files which depend on the bootloader settings" do
+      expect(bootloader).to receive(:write)
+      subject.run
+    end
+
     it "runs the Bootloader Finish Client" do
       expect_any_instance_of(::Bootloader::FinishClient).to receive(:write)
       subject.run
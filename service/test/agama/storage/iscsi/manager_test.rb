@@ -22,6 +22,8 @@
 require_relative "../../../test_helper"
 require "agama/storage/iscsi/manager"
 require "agama/storage/iscsi/node"
+require "agama/storage/iscsi/session"
+require "tmpdir"
 
 describe Agama::Storage::ISCSI::Manager do
   subject { described_class.new(logger: logger) }
@@ -40,6 +42,7 @@ describe Agama::Storage::ISCSI::Manager do
     allow(Yast::IscsiClientLib).to receive(:iBFT?)
     allow(Yast::IscsiClientLib).to receive(:find_session)
     allow(Yast::IscsiClientLib).to receive(:getStartupStatus)
+    allow(Agama::Storage::ISCSI::Session).to receive(:all).and_return([])
     allow(subject).to receive(:sleep)
   end
 
@@ -74,6 +77,32 @@ describe Agama::Storage::ISCSI::Manager do
       ))
     end
 
+    context "if a node is connected" do
+      before do
+        # Only the first node has a session
+        allow(Yast::IscsiClientLib).to receive(:find_session)
+          .and_return("192.168.100.101:3264 iqn.2023-01.com.example:12ac588 default", nil)
+        allow(Agama::Storage::ISCSI::Session).to receive(:all).and_return([session])
+      end
+
+      let(:session) do
+        Agama::Storage::ISCSI::Session.new.tap do |session|
+          session.target = "iqn.2023-01.com.example:12ac588"
+          session.address = "192.168.100.101"
+          session.port = 3264
+          session.interface = "default"
+        end
+      end
+
+      it "links the node to its session" do
+        subject.probe
+
+        connected, disconnected = subject.nodes
+        expect(connected.session).to eq(session)
+        expect(disconnected.session).to be_nil
+      end
+    end
+
     let(:callback) { proc {} }
 
     it "runs the callbacks" do
@@ -123,36 +152,205 @@ describe Agama::Storage::ISCSI::Manager do
         subject.discover_send_targets("192.168.100.101", 3264, auth)
       end
     end
+
+    context "if an interface is given" do
+      let(:command) do
+        ["iscsiadm", "-m", "discoverydb", "-t", "st", "-I", "bnx2i.00:11:22:33:44:55",
+         "-p", "192.168.100.101:3264"]
+      end
+
+      before do
+        allow(Yast::Execute).to receive(:locally!)
+        auth.username = "testi"
+        auth.password = "secret"
+      end
+
+      it "performs the discovery through the interface" do
+        expect(Yast::IscsiClientLib).to_not receive(:discover)
+        expect(Yast::Execute).to receive(:locally!).with(*command, "-o", "new").ordered
+        expect(Yast::Execute).to receive(:locally!)
+          .with(*command, "-o", "update", "-n", "discovery.sendtargets.auth.authmethod",
+            "-v", "CHAP").ordered
+        expect(Yast::Execute).to receive(:locally!)
+          .with(*command, "-o", "update", "-n", "discovery.sendtargets.auth.username",
+            "-v", "testi").ordered
+        expect(Yast::Execute).to receive(:locally!)
+          .with(*command, "-o", "update", "-n", "discovery.sendtargets.auth.password",
+            "-v", "secret").ordered
+        expect(Yast::Execute).to receive(:locally!).with(*command, "--discover").ordered
+
+        result = subject.discover_send_targets(
+          "192.168.100.101", 3264, auth, interface: "bnx2i.00:11:22:33:44:55"
+        )
+        expect(result).to eq(true)
+      end
+
+      context "and the discovery fails" do
+        before do
+          allow(Yast::Execute).to receive(:locally!).with(*command, "--discover")
+            .and_raise(Cheetah::ExecutionFailed.new([], "", nil, nil))
+        end
+
+        it "returns false" do
+          result = subject.discover_send_targets(
+            "192.168.100.101", 3264, auth, interface: "bnx2i.00:11:22:33:44:55"
+          )
+          expect(result).to eq(false)
+        end
+      end
+    end
+
+    context "if the default interface is given" do
+      it "performs the discovery through YaST" do
+        expect(Yast::IscsiClientLib).to receive(:discover)
+
+        subject.discover_send_targets("192.168.100.101", 3264, auth, interface: "default")
+      end
+    end
   end
 
-  describe "#login" do
-    let(:node) { Agama::Storage::ISCSI::Node.new }
+  describe "#supported?" do
+    let(:bindir) { Dir.mktmpdir }
 
-    let(:auth) { Y2IscsiClient::Authentication.new }
+    before do
+      allow(ENV).to receive(:fetch).with("PATH", "").and_return(bindir)
+    end
 
+    after do
+      FileUtils.remove_entry(bindir)
+    end
+
+    it "returns true if iscsiadm is in the PATH" do
+      iscsiadm = File.join(bindir, "iscsiadm")
+      File.write(iscsiadm, "")
+      File.chmod(0o755, iscsiadm)
+      expect(subject.supported?).to eq(true)
+    end
+
+    it "returns false if iscsiadm is not in the PATH" do
+      expect(subject.supported?).to eq(false)
+    end
+  end
+
+  describe "#interfaces" do
     before do
-      allow(Yast::IscsiClientLib).to receive(:default_startup_status).and_return("onboot")
-      allow(Yast::IscsiClientLib).to receive(:login_into_current)
-      allow(Yast::IscsiClientLib).to receive(:setStartupStatus)
+      allow(Yast::Execute).to receive(:locally!).with("iscsiadm", "-m", "iface", stdout: :capture)
+        .and_return(
+          "default tcp,<empty>,<empty>,<empty>,<empty>\n" \
+          "iser iser,<empty>,<empty>,<empty>,<empty>\n" \
+          "bnx2i.00:11:22:33:44:55 bnx2i,00:11:22:33:44:55,<empty>,<empty>,<empty>\n"
+        )
+    end
+
+    it "returns the names of the iSCSI interfaces" do
+      expect(subject.interfaces).to eq(["default", "iser", "bnx2i.00:11:22:33:44:55"])
+    end
+
+    context "if the interfaces cannot be read" do
+      before do
+        allow(Yast::Execute).to receive(:locally!)
+          .and_raise(Cheetah::ExecutionFailed.new([], "", nil, nil))
+      end
+
+      it "returns only the default interface" do
+        expect(subject.interfaces).to eq(["default"])
+      end
+    end
+  end
+
+  describe "#interfaces_details" do
+    before do
+      allow(Yast::Execute).to receive(:locally!).with("iscsiadm", "-m", "iface", stdout: :capture)
+        .and_return(
+          "default tcp,<empty>,<empty>,<empty>,<empty>\n" \
+          "bnx2i.00:11:22:33:44:55 bnx2i,00:11:22:33:44:55,192.168.100.5,<empty>,<empty>\n"
+        )
+    end
+
+    it "returns the details of the iSCSI interfaces" do
+      default, bnx2i = subject.interfaces_details
+
+      expect(default).to have_attributes(
+        name: "default", transport: "tcp", hwaddress: nil, ipaddress: nil
+      )
+      expect(bnx2i).to have_attributes(
+        name:      "bnx2i.00:11:22:33:44:55",
+        transport: "bnx2i",
+        hwaddress: "00:11:22:33:44:55",
+        ipaddress: "192.168.100.5"
+      )
     end
 
+    context "if the interfaces cannot be read" do
+      before do
+        allow(Yast::Execute).to receive(:locally!)
+          .and_raise(Cheetah::ExecutionFailed.new([], "", nil, nil))
+      end
+
+      it "returns an empty list" do
+        expect(subject.interfaces_details).to eq([])
+      end
+    end
+  end
+
+  describe "#login" do
+    let(:node) do
+      Agama::Storage::ISCSI::Node.new.tap do |node|
+        node.portal = "192.168.100.101:3264"
+        node.target = "iqn.2023-01.com.example:12ac588"
+        node.interface = "default"
+      end
+    end
+
+    let(:auth) { Y2IscsiClient::Authentication.new }
+
     let(:startup) { "automatic" }
 
+    let(:iscsiadm_node) do
+      ["iscsiadm", "-m", "node", "-I", "default", "-T", node.target, "-p", node.portal]
+    end
+
+    let(:startup_success) { true }
+
     before do
-      allow(Yast::IscsiClientLib).to receive(:login_into_current).and_return(login_success)
+      allow(Yast::IscsiClientLib).to receive(:default_startup_status).and_return("onboot")
       allow(Yast::IscsiClientLib).to receive(:setStartupStatus).and_return(startup_success)
+      allow(Yast::Execute).to receive(:locally!)
     end
 
-    let(:login_success) { nil }
+    it "logs into the node" do
+      expect(Yast::Execute).to receive(:locally!).with(*iscsiadm_node, "--login", stderr: :capture)
 
-    let(:startup_success) { nil }
+      subject.login(node, auth, startup: startup)
+    end
 
-    it "tries to login" do
-      expect(Yast::IscsiClientLib).to receive(:login_into_current)
+    it "sets the given authentication" do
+      auth.username = "jane"
+      auth.password = "secret"
+      expect(Yast::Execute).to receive(:locally!).with(
+        *iscsiadm_node, "--op=update", "--name=node.session.auth.authmethod", "--value=CHAP"
+      )
+      expect(Yast::Execute).to receive(:locally!).with(
+        *iscsiadm_node, "--op=update", "--name=node.session.auth.username", "--value=jane"
+      )
+      expect(Yast::Execute).to receive(:locally!).with(
+        *iscsiadm_node, "--op=update", "--name=node.session.auth.password", "--value=secret"
+      )
 
       subject.login(node, auth, startup: startup)
     end
 
+    context "if an interface is given" do
+      it "logs into the node record of that interface" do
+        command = ["iscsiadm", "-m", "node", "-I", "iser", "-T", node.target, "-p", node.portal]
+        expect(Yast::Execute).to receive(:locally!).with(*command, "--login", stderr: :capture)
+        expect(Yast::IscsiClientLib).to receive(:currentRecord=)
+          .with(["192.168.100.101:3264", "iqn.2023-01.com.example:12ac588", "iser"])
+
+        subject.login(node, auth, startup: startup, interface: "iser")
+      end
+    end
+
     context "if iSCSI activation is not performed yet" do
       it "activates iSCSI" do
         expect(subject).to receive(:activate)
@@ -174,21 +372,18 @@ describe Agama::Storage::ISCSI::Manager do
     end
 
     context "and the session is created" do
-      let(:login_success) { true }
-
       context "and the startup status is correctly set" do
-        let(:startup_success) { true }
-
         it "probes iSCSI" do
           expect(subject).to receive(:probe)
 
           subject.login(node, auth, startup: startup)
         end
 
-        it "returns true" do
+        it "returns :success" do
           result = subject.login(node, auth, startup: startup)
 
-          expect(result).to eq(true)
+          expect(result).to eq(:success)
+          expect(subject.login_error).to be_nil
         end
       end
 
@@ -201,10 +396,45 @@ describe Agama::Storage::ISCSI::Manager do
           subject.login(node, auth, startup: startup)
         end
 
-        it "returns false" do
+        it "returns :failed" do
           result = subject.login(node, auth, startup: startup)
 
-          expect(result).to eq(false)
+          expect(result).to eq(:failed)
+          expect(subject.login_error).to eq("Could not set the startup status to automatic")
+        end
+      end
+    end
+
+    context "and the session cannot be created" do
+      let(:status) { double("status", exitstatus: exit_code) }
+
+      let(:exit_code) { 24 }
+
+      before do
+        allow(Yast::Execute).to receive(:locally!).with(*iscsiadm_node, "--login", stderr: :capture)
+          .and_raise(
+            Cheetah::ExecutionFailed.new([], status, nil, "iscsiadm: Login failed to authenticate")
+          )
+      end
+
+      it "probes iSCSI" do
+        expect(subject).to receive(:probe)
+
+        subject.login(node, auth, startup: startup)
+      end
+
+      it "returns the reason reported by iscsiadm" do
+        result = subject.login(node, auth, startup: startup)
+
+        expect(result).to eq(:authentication_failed)
+        expect(subject.login_error).to eq("iscsiadm: Login failed to authenticate")
+      end
+
+      context "because of an unknown reason" do
+        let(:exit_code) { 19 }
+
+        it "returns :failed" do
+          expect(subject.login(node, auth, startup: startup)).to eq(:failed)
         end
       end
     end
@@ -250,6 +480,87 @@ describe Agama::Storage::ISCSI::Manager do
     end
   end
 
+  describe "#test_auth" do
+    let(:node) do
+      Agama::Storage::ISCSI::Node.new.tap do |node|
+        node.portal = "192.168.100.101:3264"
+        node.target = "iqn.2023-01.com.example:12ac588"
+        node.interface = "default"
+      end
+    end
+
+    let(:auth) { Y2IscsiClient::Authentication.new }
+
+    let(:iscsiadm_node) do
+      ["iscsiadm", "-m", "node", "-I", "default", "-T", node.target, "-p", node.portal]
+    end
+
+    let(:node_output) do
+      "node.name = #{node.target}\n" \
+        "node.session.auth.authmethod = CHAP\n" \
+        "node.session.auth.username = previous\n" \
+        "node.session.auth.password = secret\n" \
+        "node.session.auth.username_in = <empty>\n" \
+        "node.session.auth.password_in = <empty>\n"
+    end
+
+    let(:login_success) { true }
+
+    let(:logout_success) { true }
+
+    before do
+      allow(Yast::Execute).to receive(:locally!)
+      allow(Yast::Execute).to receive(:locally!)
+        .with(*iscsiadm_node, "--show", stdout: :capture).and_return(node_output)
+      allow(Yast::IscsiClientLib).to receive(:login_into_current).and_return(login_success)
+      allow(Yast::IscsiClientLib).to receive(:deleteRecord).and_return(logout_success)
+    end
+
+    it "logs in and closes the session right away" do
+      expect(Yast::IscsiClientLib).to receive(:login_into_current).with(auth, silent: true)
+      expect(Yast::IscsiClientLib).to receive(:deleteRecord)
+
+      expect(subject.test_auth(node, auth)).to eq(:success)
+    end
+
+    it "restores the previous authentication of the node" do
+      expect(Yast::Execute).to receive(:locally!).with(
+        *iscsiadm_node, "--op=update", "--name=node.session.auth.username", "--value=previous"
+      )
+      expect(Yast::Execute).to receive(:locally!).with(
+        *iscsiadm_node, "--op=update", "--name=node.session.auth.username_in", "--value="
+      )
+
+      subject.test_auth(node, auth)
+    end
+
+    context "when the login fails" do
+      let(:login_success) { false }
+
+      it "does not try to close the session" do
+        expect(Yast::IscsiClientLib).to_not receive(:deleteRecord)
+
+        expect(subject.test_auth(node, auth)).to eq(:login_failed)
+      end
+
+      it "restores the previous authentication of the node" do
+        expect(Yast::Execute).to receive(:locally!).with(
+          *iscsiadm_node, "--op=update", "--name=node.session.auth.password", "--value=secret"
+        )
+
+        subject.test_auth(node, auth)
+      end
+    end
+
+    context "when the session cannot be closed" do
+      let(:logout_success) { false }
+
+      it "returns :logout_failed" do
+        expect(subject.test_auth(node, auth)).to eq(:logout_failed)
+      end
+    end
+  end
+
   describe "#delete" do
     before do
       allow(Yast::IscsiClientLib).to receive(:removeRecord)
@@ -0,0 +1,132 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require_relative "../../../test_helper"
+require "agama/storage/iscsi/session"
+require "agama/storage/iscsi/node"
+require "fileutils"
+require "tmpdir"
+
+describe Agama::Storage::ISCSI::Session do
+  let(:root) { Dir.mktmpdir }
+
+  after { FileUtils.remove_entry(root) }
+
+  def write_attributes(dir, attributes)
+    path = File.join(root, dir)
+    FileUtils.mkdir_p(path)
+    attributes.each { |name, value| File.write(File.join(path, name), "#{value}\n") }
+  end
+
+  let(:target) { "iqn.2023-01.com.example:12ac588" }
+
+  before do
+    write_attributes("sys/class/iscsi_session/session2",
+      "targetname" => target, "state" => "LOGGED_IN", "ifacename" => "default",
+      "first_burst_len" => "262144", "immediate_data" => "1", "initial_r2t" => "0")
+    write_attributes("sys/class/iscsi_connection/connection2:0",
+      "persistent_address" => "192.168.100.101", "persistent_port" => "3260",
+      "header_digest" => "CRC32C", "data_digest" => "", "max_recv_dlength" => "262144")
+    # Session without connections
+    write_attributes("sys/class/iscsi_session/session3",
+      "targetname" => "iqn.2023-01.com.example:4fb2e1", "state" => "FREE")
+  end
+
+  describe ".all" do
+    it "reads the sessions with connections" do
+      sessions = described_class.all(root: root)
+
+      expect(sessions.size).to eq(1)
+      session = sessions.first
+      expect(session.target).to eq(target)
+      expect(session.address).to eq("192.168.100.101")
+      expect(session.port).to eq(3260)
+      expect(session.interface).to eq("default")
+      expect(session.state).to eq("LOGGED_IN")
+      expect(session.connections).to eq(1)
+      expect(session.header_digest).to eq("CRC32C")
+      expect(session.data_digest).to eq("None")
+    end
+
+    it "reads the negotiated parameters" do
+      session = described_class.all(root: root).first
+
+      expect(session.parameters).to eq(
+        "FirstBurstLength"         => "262144",
+        "ImmediateData"            => "Yes",
+        "InitialR2T"               => "No",
+        "MaxRecvDataSegmentLength" => "262144"
+      )
+    end
+
+    context "if the target was configured by iBFT" do
+      before do
+        write_attributes("sys/firmware/ibft/target0", "target-name" => target)
+      end
+
+      it "marks the session as iBFT" do
+        expect(described_class.all(root: root).first.ibft?).to eq(true)
+      end
+    end
+
+    context "if the target was not configured by iBFT" do
+      it "does not mark the session as iBFT" do
+        expect(described_class.all(root: root).first.ibft?).to eq(false)
+      end
+    end
+
+    context "if there are no sessions" do
+      before { FileUtils.rm_rf(File.join(root, "sys")) }
+
+      it "returns an empty list" do
+        expect(described_class.all(root: root)).to eq([])
+      end
+    end
+  end
+
+  describe "#node?" do
+    subject { described_class.all(root: root).first }
+
+    let(:node) do
+      Agama::Storage::ISCSI::Node.new.tap do |node|
+        node.target = target
+        node.portal = "192.168.100.101:3260"
+        node.interface = interface
+      end
+    end
+
+    context "if the node has the same target, portal and interface" do
+      let(:interface) { "default" }
+
+      it "returns true" do
+        expect(subject.node?(node)).to eq(true)
+      end
+    end
+
+    context "if the node uses another interface" do
+      let(:interface) { "iser" }
+
+      it "returns false" do
+        expect(subject.node?(node)).to eq(false)
+      end
+    end
+  end
+end
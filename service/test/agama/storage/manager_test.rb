@@ -252,6 +252,58 @@ describe Agama::Storage::Manager do
     end
   end
 
+  describe "#unlock_device" do
+    before do
+      allow(Yast::Execute).to receive(:locally!)
+      allow(storage).to receive(:probe)
+    end
+
+    it "opens the device with the given passphrase and probes the system again" do
+      expect(Yast::Execute).to receive(:locally!).with(
+        "cryptsetup", "open", "--type", "luks", "--key-file=-", "/dev/sda2", "cr_sda2",
+        stdin: "n0ts3cr3t"
+      )
+      expect(storage).to receive(:probe)
+      expect(storage.unlock_device("/dev/sda2", "n0ts3cr3t")).to eq(0)
+    end
+
+    context "when the device is not a LUKS device" do
+      before do
+        allow(Yast::Execute).to receive(:locally!).with("cryptsetup", "isLuks", "/dev/sda2")
+          .and_raise(Cheetah::ExecutionFailed.new([], "", nil, nil))
+      end
+
+      it "returns 1 and does not probe the system" do
+        expect(storage).to_not receive(:probe)
+        expect(storage.unlock_device("/dev/sda2", "n0ts3cr3t")).to eq(1)
+      end
+    end
+
+    context "when the passphrase is wrong" do
+      before do
+        status = instance_double(Process::Status, exitstatus: 2)
+        allow(Yast::Execute).to receive(:locally!).with("cryptsetup", "open", any_args)
+          .and_raise(Cheetah::ExecutionFailed.new([], status, nil, nil))
+      end
+
+      it "returns 2" do
+        expect(storage.unlock_device("/dev/sda2", "wrong")).to eq(2)
+      end
+    end
+
+    context "when cryptsetup fails for another reason" do
+      before do
+        status = instance_double(Process::Status, exitstatus: 5)
+        allow(Yast::Execute).to receive(:locally!).with("cryptsetup", "open", any_args)
+          .and_raise(Cheetah::ExecutionFailed.new([], status, nil, nil))
+      end
+
+      it "returns 3" do
+        expect(storage.unlock_device("/dev/sda2", "n0ts3cr3t")).to eq(3)
+      end
+    end
+  end
+
   describe "#install" do
     before do
       allow(Y2Storage::StorageManager).to receive(:instance).and_return(y2storage_manager)
@@ -263,6 +315,7 @@ describe Agama::Storage::Manager do
       allow(Bootloader::ProposalClient).to receive(:new)
         .and_return(bootloader_proposal)
       allow(Y2Storage::Clients::InstPrepdisk).to receive(:new).and_return(client)
+      allow(storage.bootloader).to receive(:apply_proposal)
     end
 
     let(:proposed_devicegraph) do
@@ -277,6 +330,13 @@ describe Agama::Storage::Manager do
 
     let(:client) { instance_double(Y2Storage::Clients::InstPrepdisk, run: nil) }
 
+    it "applies the bootloader settings to the bootloader proposal" do
+      expect(bootloader_proposal).to receive(:make_proposal).ordered
+      expect(storage.bootloader).to receive(:apply_proposal).ordered
+
+      storage.install
+    end
+
     it "adds storage software to install" do
       expect(Yast::PackagesProposal).to receive(:SetResolvables) do |_, _, packages|
         expect(packages).to contain_exactly("btrfsprogs", "snapper")
@@ -307,6 +367,7 @@ describe Agama::Storage::Manager do
       mock_storage(devicegraph: devicegraph)
       allow(File).to receive(:directory?).with("/iguana").and_return iguana
       allow(copy_files_class).to receive(:new).and_return(copy_files)
+      allow(storage.nvmeof).to receive(:controllers).and_return([])
     end
     let(:copy_files_class) { Agama::Storage::Finisher::CopyFilesStep }
     let(:copy_files) { instance_double(copy_files_class, run?: true, run: true, label: "Copy") }
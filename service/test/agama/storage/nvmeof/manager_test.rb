@@ -0,0 +1,155 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require_relative "../../../test_helper"
+require "agama/storage/nvmeof/manager"
+require "tmpdir"
+
+describe Agama::Storage::NVMeoF::Manager do
+  subject { described_class.new(logger: logger) }
+
+  let(:logger) { Logger.new($stdout, level: :warn) }
+  let(:sysfs_dir) { Dir.mktmpdir }
+  let(:destdir) { Dir.mktmpdir }
+  let(:subnqn) { "nqn.2014-08.org.nvmexpress:uuid:1b4e28ba-2fa1-11d2-883f-0016d3cca427" }
+  let(:target) { Agama::Storage::NVMeoF::Target.new("tcp", "192.168.1.10", "4420", subnqn) }
+
+  before do
+    stub_const("Agama::Storage::NVMeoF::Manager::SYSFS_DIR", sysfs_dir)
+    stub_const("Agama::Storage::NVMeoF::Manager::HOST_FILES", [])
+    allow(Yast::Installation).to receive(:destdir).and_return(destdir)
+    allow(Yast::Execute).to receive(:locally!)
+    allow(Yast::Execute).to receive(:on_target!)
+  end
+
+  after do
+    FileUtils.remove_entry(sysfs_dir)
+    FileUtils.remove_entry(destdir)
+  end
+
+  def add_controller(name, transport, address, nqn)
+    dir = File.join(sysfs_dir, name)
+    FileUtils.mkdir_p(dir)
+    { "transport" => transport, "address" => address, "subsysnqn" => nqn, "state" => "live" }
+      .each { |f, v| File.write(File.join(dir, f), "#{v}\n") }
+  end
+
+  describe "#controllers" do
+    before do
+      add_controller("nvme0", "pcie", "0000:01:00.0", "nqn.local")
+      add_controller("nvme1", "tcp", "traddr=192.168.1.10,trsvcid=4420,src_addr=10.0.0.1", subnqn)
+    end
+
+    it "returns the controllers connected through a fabric" do
+      controllers = subject.controllers
+      expect(controllers.map(&:name)).to eq(["nvme1"])
+      expect(controllers.first.target).to eq(target)
+      expect(controllers.first.state).to eq("live")
+    end
+  end
+
+  describe "#discover" do
+    let(:output) do
+      {
+        "records" => [
+          { "trtype" => "tcp", "traddr" => "192.168.1.10", "trsvcid" => "8009",
+            "subtype" => "discovery subsystem",
+            "subnqn" => "nqn.2014-08.org.nvmexpress.discovery" },
+          { "trtype" => "tcp", "traddr" => "192.168.1.10", "trsvcid" => "4420",
+            "subtype" => "nvme subsystem", "subnqn" => subnqn }
+        ]
+      }.to_json
+    end
+
+    it "returns the exported subsystems" do
+      expect(Yast::Execute).to receive(:locally!)
+        .with("nvme", "discover", "--transport=tcp", "--traddr=192.168.1.10", "--trsvcid=8009",
+          "--output-format=json", stdout: :capture)
+        .and_return(output)
+
+      discovery = Agama::Storage::NVMeoF::Target.new("tcp", "192.168.1.10", "8009", "")
+      expect(subject.discover(discovery)).to eq([described_class::SUCCESS, "", [target]])
+    end
+
+    it "reports the failures with the nvme-cli output" do
+      allow(Yast::Execute).to receive(:locally!)
+        .and_raise(Cheetah::ExecutionFailed.new([], "", nil, "Connection refused"))
+      expect(subject.discover(target)).to eq([described_class::FAILED, "Connection refused", []])
+    end
+
+    it "reports the RDMA transport as unavailable if there are no RDMA devices" do
+      stub_const("Agama::Storage::NVMeoF::Manager::RDMA_DIR", File.join(sysfs_dir, "none"))
+      rdma = Agama::Storage::NVMeoF::Target.new("rdma", "192.168.1.10", "8009", "")
+      expect(subject.discover(rdma).first).to eq(described_class::TRANSPORT_UNAVAILABLE)
+    end
+  end
+
+  describe "#connect" do
+    it "connects to the subsystem and runs the callbacks" do
+      callback = proc {}
+      subject.on_connections_change(&callback)
+      expect(Yast::Execute).to receive(:locally!)
+        .with("nvme", "connect", "--transport=tcp", "--traddr=192.168.1.10", "--trsvcid=4420",
+          "--nqn=#{subnqn}", stdout: :capture)
+      expect(callback).to receive(:call)
+      expect(subject.connect(target)).to eq([described_class::SUCCESS, ""])
+    end
+
+    it "does not connect again to a connected subsystem" do
+      add_controller("nvme1", "tcp", "traddr=192.168.1.10,trsvcid=4420", subnqn)
+      expect(Yast::Execute).to_not receive(:locally!)
+      expect(subject.connect(target)).to eq([described_class::ALREADY_CONNECTED, ""])
+    end
+  end
+
+  describe "#disconnect" do
+    it "returns an error if the controller does not exist" do
+      expect(subject.disconnect("nvme1")).to eq([described_class::UNKNOWN_CONTROLLER, ""])
+    end
+
+    it "disconnects the controller" do
+      add_controller("nvme1", "tcp", "traddr=192.168.1.10,trsvcid=4420", subnqn)
+      expect(Yast::Execute).to receive(:locally!)
+        .with("nvme", "disconnect", "--device=nvme1", stdout: :capture)
+      expect(subject.disconnect("nvme1")).to eq([described_class::SUCCESS, ""])
+    end
+  end
+
+  describe "#write" do
+    it "writes the connected subsystems and enables the autoconnect service" do
+      add_controller("nvme1", "tcp", "traddr=192.168.1.10,trsvcid=4420", subnqn)
+      expect(Yast::Execute).to receive(:on_target!)
+        .with("systemctl", "enable", "nvmf-autoconnect.service")
+      subject.write
+
+      config = JSON.parse(File.read(File.join(destdir, "etc/nvme/config.json")))
+      expect(config.first["subsystems"]).to eq(
+        [{ "nqn" => subnqn,
+           "ports" => [{ "transport" => "tcp", "traddr" => "192.168.1.10", "trsvcid" => "4420" }] }]
+      )
+    end
+
+    it "does nothing if there are no connections" do
+      expect(Yast::Execute).to_not receive(:on_target!)
+      subject.write
+    end
+  end
+end
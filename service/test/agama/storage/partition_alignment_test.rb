@@ -0,0 +1,123 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require_relative "../../test_helper"
+require_relative "storage_helpers"
+require "agama/storage/partition_alignment"
+require "y2storage"
+
+describe Agama::Storage::PartitionAlignment do
+  include Agama::RSpec::StorageHelpers
+
+  let(:devicegraph) { Y2Storage::StorageManager.instance.staging }
+
+  let(:vda) { devicegraph.find_by_name("/dev/vda") }
+
+  before do
+    mock_storage(devicegraph: "disks.yaml")
+  end
+
+  # Creates a partition of the given amount of 512 bytes sectors, after the existing ones
+  def create_partition(offset, blocks)
+    slot = vda.partition_table.unused_partition_slots.first
+    region = Y2Storage::Region.create(
+      slot.region.start + offset, blocks, slot.region.block_size
+    )
+    vda.partition_table.create_partition(slot.name, region, Y2Storage::PartitionType::PRIMARY)
+  end
+
+  describe "#grain" do
+    it "returns 1 MiB for the optimal alignment" do
+      expect(subject.grain).to eq(1_048_576)
+    end
+
+    it "returns the size of a cylinder for the cylinder alignment" do
+      subject.mode = :cylinder
+      expect(subject.grain).to eq(8_225_280)
+    end
+
+    it "returns the given grain for the explicit alignment" do
+      subject.mode = :explicit
+      subject.explicit_grain = 4_194_304
+      expect(subject.grain).to eq(4_194_304)
+    end
+  end
+
+  describe "#padded_size" do
+    before do
+      subject.mode = :explicit
+      subject.explicit_grain = 4_194_304
+    end
+
+    it "rounds up the size to the grain and adds one grain" do
+      size = subject.padded_size(Y2Storage::DiskSize.MiB(5))
+      expect(size).to eq(Y2Storage::DiskSize.MiB(12))
+    end
+
+    it "keeps the unlimited sizes" do
+      size = subject.padded_size(Y2Storage::DiskSize.unlimited)
+      expect(size).to be_unlimited
+    end
+  end
+
+  describe "#apply" do
+    before do
+      subject.mode = :explicit
+      subject.explicit_grain = 4_194_304
+    end
+
+    it "shrinks the new partitions to aligned regions" do
+      # 12 MiB, starting 1.5 MiB after the existing partitions
+      partition = create_partition(3072, 24_576)
+
+      expect(subject.apply(devicegraph)).to be_nil
+      expect(partition.region.start % 8192).to eq(0)
+      expect((partition.region.end + 1) % 8192).to eq(0)
+      expect(partition.size).to be >= Y2Storage::DiskSize.MiB(8)
+    end
+
+    it "does not modify the existing partitions" do
+      regions = vda.partitions.map(&:region)
+
+      subject.apply(devicegraph)
+      expect(vda.partitions.map(&:region)).to eq(regions)
+    end
+
+    context "if a new partition does not contain an aligned region" do
+      it "returns an error" do
+        create_partition(2048, 4096)
+        expect(subject.apply(devicegraph)).to match(/cannot be aligned/)
+      end
+    end
+
+    context "if the alignment is optimal" do
+      before { subject.mode = :optimal }
+
+      it "does not modify the new partitions" do
+        partition = create_partition(2048, 24_576)
+        region = partition.region
+
+        expect(subject.apply(devicegraph)).to be_nil
+        expect(partition.region).to eq(region)
+      end
+    end
+  end
+end
@@ -319,6 +319,28 @@ describe Agama::Storage::ProposalSettingsConversions::ToY2Storage do
         end
       end
 
+      context "when the space policy is set to :custom" do
+        before do
+          settings.space.policy = :custom
+          settings.space.actions = { "/dev/sda2" => :resize, "/dev/sda3" => :resize }
+          settings.space.resize_sizes = { "/dev/sda2" => 2 * 1024**3 }
+
+          allow(Agama::Storage::DeviceShrinking).to receive(:new)
+            .and_return(instance_double(Agama::Storage::DeviceShrinking, supported?: true))
+        end
+
+        it "generates resize actions limited to the target sizes" do
+          y2storage_settings = subject.convert
+
+          size = Y2Storage::DiskSize.GiB(2)
+          unlimited = Y2Storage::DiskSize.unlimited
+          expect(y2storage_settings.space_settings.actions).to contain_exactly(
+            an_object_having_attributes(device: "/dev/sda2", min_size: size, max_size: size),
+            an_object_having_attributes(device: "/dev/sda3", max_size: unlimited)
+          )
+        end
+      end
+
       context "when the space policy is set to :keep" do
         before do
           settings.space.policy = :keep
@@ -410,6 +432,48 @@ describe Agama::Storage::ProposalSettingsConversions::ToY2Storage do
           )
         )
       end
+
+      context "if the partitions are not aligned to the optimal grain" do
+        before do
+          settings.alignment.mode = :explicit
+          settings.alignment.explicit_grain = 4_194_304
+          settings.volumes.first.min_size = Y2Storage::DiskSize.MiB(5)
+          settings.volumes.first.max_size = Y2Storage::DiskSize.unlimited
+        end
+
+        it "pads the sizes of the volumes to the grain" do
+          y2storage_settings = subject.convert
+
+          expect(y2storage_settings.volumes).to include(
+            an_object_having_attributes(
+              mount_point: "/test",
+              min_size:    Y2Storage::DiskSize.MiB(12),
+              max_size:    Y2Storage::DiskSize.unlimited
+            )
+          )
+        end
+      end
+
+      context "if a new ESP of a given size is requested" do
+        before do
+          settings.boot.esp.mode = :create
+          settings.boot.esp.size = Y2Storage::DiskSize.MiB(256).to_i
+        end
+
+        it "includes a not proposed ESP volume with the requested size" do
+          y2storage_settings = subject.convert
+
+          expect(y2storage_settings.volumes).to include(
+            an_object_having_attributes(
+              mount_point:  "/boot/efi",
+              proposed:     false,
+              min_size:     Y2Storage::DiskSize.MiB(256),
+              desired_size: Y2Storage::DiskSize.MiB(256),
+              max_size:     Y2Storage::DiskSize.MiB(256)
+            )
+          )
+        end
+      end
     end
   end
 end
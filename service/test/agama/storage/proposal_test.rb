@@ -365,6 +365,151 @@ describe Agama::Storage::Proposal do
             an_object_having_attributes(mount_point: "/", device: "/dev/sda")
           )
         end
+
+        context "and the first available device is removable" do
+          before do
+            allow_any_instance_of(Y2Storage::Disk).to receive(:transport) do |disk|
+              double("transport", is?: disk.name == "/dev/sda")
+            end
+          end
+
+          it "skips it unless removable devices are included" do
+            subject.calculate_guided(achivable_settings)
+            y2storage_settings = Y2Storage::StorageManager.instance.proposal.settings
+
+            expect(y2storage_settings.volumes).to contain_exactly(
+              an_object_having_attributes(mount_point: "/", device: "/dev/sdb")
+            )
+            expect(subject.guided_settings.excluded_devices).to eq(["/dev/sda"])
+          end
+
+          it "uses it if removable devices are included" do
+            achivable_settings.include_removable = true
+            subject.calculate_guided(achivable_settings)
+            y2storage_settings = Y2Storage::StorageManager.instance.proposal.settings
+
+            expect(y2storage_settings.volumes).to contain_exactly(
+              an_object_having_attributes(mount_point: "/", device: "/dev/sda")
+            )
+            expect(subject.guided_settings.excluded_devices).to be_empty
+          end
+        end
+      end
+    end
+
+    context "if the swap is encrypted with a random key" do
+      before do
+        swap = Agama::Storage::Volume.new("swap").tap do |volume|
+          volume.fs_type = Y2Storage::Filesystems::Type::SWAP
+          volume.min_size = Y2Storage::DiskSize.GiB(1)
+          volume.max_size = Y2Storage::DiskSize.GiB(2)
+          volume.encryption = :random_key
+        end
+        achivable_settings.volumes << swap
+      end
+
+      it "encrypts the new swap with the random swap method" do
+        subject.calculate_guided(achivable_settings)
+        devices = Y2Storage::StorageManager.instance.proposal.devices
+
+        swap = devices.filesystems.find { |f| f.mount_path == "swap" }
+        encryption = swap.blk_devices.first
+        expect(encryption.is?(:encryption)).to eq(true)
+        expect(encryption.method.is?(:random_swap)).to eq(true)
+      end
+    end
+
+    context "if some volumes use their own encryption passphrase" do
+      before do
+        achivable_settings.volumes << Agama::Storage::Volume.new("/home").tap do |volume|
+          volume.fs_type = Y2Storage::Filesystems::Type::XFS
+          volume.min_size = Y2Storage::DiskSize.GiB(1)
+          volume.max_size = Y2Storage::DiskSize.GiB(2)
+        end
+        achivable_settings.encryption.password = password
+        achivable_settings.encryption.volume_passwords = { "/home" => "home-secret" }
+      end
+
+      def encryption_of(mount_path)
+        devices = Y2Storage::StorageManager.instance.proposal.devices
+        filesystem = devices.filesystems.find { |f| f.mount_path == mount_path }
+        device = filesystem.blk_devices.first
+        device if device.is?(:encryption)
+      end
+
+      context "and there is a default passphrase" do
+        let(:password) { "secret" }
+
+        it "uses the passphrase of each volume" do
+          subject.calculate_guided(achivable_settings)
+
+          expect(encryption_of("/").password).to eq("secret")
+          expect(encryption_of("/home").password).to eq("home-secret")
+        end
+      end
+
+      context "and there is no default passphrase" do
+        let(:password) { nil }
+
+        it "only encrypts the volumes with their own passphrase" do
+          subject.calculate_guided(achivable_settings)
+
+          expect(encryption_of("/")).to be_nil
+          expect(encryption_of("/home").password).to eq("home-secret")
+          expect(encryption_of("/home").method.is?(:luks2)).to eq(true)
+        end
+      end
+    end
+
+    context "if the type of partition table is requested for some disks" do
+      before do
+        achivable_settings.partition_tables = {
+          "/dev/sda" => Y2Storage::PartitionTables::Type::GPT,
+          "/dev/sdb" => Y2Storage::PartitionTables::Type::MSDOS
+        }
+      end
+
+      it "creates the requested partition tables" do
+        subject.calculate_guided(achivable_settings)
+        devices = Y2Storage::StorageManager.instance.proposal.devices
+
+        sda = devices.find_by_name("/dev/sda")
+        expect(sda.partition_table.type).to eq(Y2Storage::PartitionTables::Type::GPT)
+        sdb = devices.find_by_name("/dev/sdb")
+        expect(sdb.partition_table.type).to eq(Y2Storage::PartitionTables::Type::MSDOS)
+      end
+
+      it "does not modify the probed devicegraph" do
+        subject.calculate_guided(achivable_settings)
+
+        sda = Y2Storage::StorageManager.instance.probed.find_by_name("/dev/sda")
+        expect(sda.partition_table.type).to eq(Y2Storage::PartitionTables::Type::MSDOS)
+      end
+    end
+
+    context "if the volumes set the labels and how the file systems are mounted" do
+      before do
+        achivable_settings.mount_by = Y2Storage::Filesystems::MountByType::PATH
+        achivable_settings.volumes.first.tap do |volume|
+          volume.label = "root"
+          volume.mount_by = Y2Storage::Filesystems::MountByType::LABEL
+        end
+        achivable_settings.volumes << Agama::Storage::Volume.new("/home").tap do |volume|
+          volume.fs_type = Y2Storage::Filesystems::Type::XFS
+          volume.min_size = Y2Storage::DiskSize.GiB(1)
+          volume.max_size = Y2Storage::DiskSize.GiB(2)
+        end
+      end
+
+      it "applies them to the new file systems" do
+        subject.calculate_guided(achivable_settings)
+        devices = Y2Storage::StorageManager.instance.proposal.devices
+
+        root = devices.filesystems.find { |f| f.mount_path == "/" }
+        expect(root.label).to eq("root")
+        expect(root.mount_point.mount_by).to eq(Y2Storage::Filesystems::MountByType::LABEL)
+        home = devices.filesystems.find { |f| f.mount_path == "/home" }
+        expect(home.mount_point.mount_by).to eq(Y2Storage::Filesystems::MountByType::PATH)
       end
     end
 
@@ -0,0 +1,99 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+
+require_relative "../../test_helper"
+require "agama/storage/quotas"
+require "agama/storage/volume"
+require "tmpdir"
+require "y2storage"
+
+describe Agama::Storage::Quotas do
+  subject { described_class.new(volumes, logger) }
+
+  let(:logger) { Logger.new($stdout, level: :warn) }
+  let(:destdir) { Dir.mktmpdir }
+
+  let(:root) { volume("/", :btrfs, :qgroups, "var/log" => 1024) }
+  let(:home) { volume("/home", :xfs, :project, "alice" => 2048, "/shared/data/" => 4096) }
+  let(:swap) { Agama::Storage::Volume.new("swap") }
+  let(:volumes) { [root, home, swap] }
+
+  def volume(mount_path, fs_type, quota_type, limits)
+    Agama::Storage::Volume.new(mount_path).tap do |volume|
+      volume.fs_type = Y2Storage::Filesystems::Type.find(fs_type)
+      volume.quota = Agama::Storage::VolumeQuota.new(quota_type).tap do |quota|
+        limits.each { |path, size| quota.limits[path] = Y2Storage::DiskSize.new(size) }
+      end
+    end
+  end
+
+  before do
+    allow(Yast::Installation).to receive(:destdir).and_return(destdir)
+    allow(Yast::Execute).to receive(:on_target!)
+  end
+
+  after do
+    FileUtils.remove_entry(destdir)
+  end
+
+  describe "#any?" do
+    it "returns whether any volume uses quotas" do
+      expect(subject.any?).to eq(true)
+      expect(described_class.new([swap], logger).any?).to eq(false)
+    end
+
+    it "ignores the quotas which are not supported by the file system" do
+      root.fs_type = Y2Storage::Filesystems::Type::EXT4
+      expect(described_class.new([root], logger).any?).to eq(false)
+    end
+  end
+
+  describe "#write" do
+    it "enables the Btrfs quota groups and limits the subvolumes" do
+      expect(Yast::Execute).to receive(:on_target!).with("btrfs", "quota", "enable", "/")
+      expect(Yast::Execute).to receive(:on_target!)
+        .with("btrfs", "qgroup", "limit", "1024", "/var/log")
+      subject.write
+    end
+
+    it "defines the XFS projects and limits them" do
+      expect(Yast::Execute).to receive(:on_target!)
+        .with("xfs_quota", "-x", "-c", "project -s home_alice", "/home")
+      expect(Yast::Execute).to receive(:on_target!)
+        .with("xfs_quota", "-x", "-c", "limit -p bhard=4096 home_shared_data", "/home")
+      subject.write
+
+      expect(File.read(File.join(destdir, "etc/projects")))
+        .to eq("1:/home/alice\n2:/home/shared/data\n")
+      expect(File.read(File.join(destdir, "etc/projid")))
+        .to eq("home_alice:1\nhome_shared_data:2\n")
+    end
+
+    it "sets the rest of the limits if one of them cannot be set" do
+      allow(Yast::Execute).to receive(:on_target!).with("btrfs", "quota", "enable", "/")
+        .and_raise(Cheetah::ExecutionFailed.new([], "", nil, nil))
+      expect(Yast::Execute).to receive(:on_target!)
+        .with("btrfs", "qgroup", "limit", "1024", "/var/log")
+      subject.write
+    end
+  end
+end
@@ -0,0 +1,82 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+require_relative "../../test_helper"
+require "agama/storage/proposal_settings"
+require "agama/storage/root_overlay"
+require "tmpdir"
+
+describe Agama::Storage::RootOverlay do
+  subject { described_class.new(settings, logger) }
+
+  let(:logger) { Logger.new($stdout, level: :warn) }
+  let(:destdir) { Dir.mktmpdir }
+  let(:fstab) { File.join(destdir, "etc/fstab") }
+
+  let(:settings) do
+    Agama::Storage::ProposalSettings.new.tap do |settings|
+      settings.read_only_root = true
+      settings.root_overlay = "/var/lib/overlay/"
+    end
+  end
+
+  before do
+    allow(Yast::Installation).to receive(:destdir).and_return(destdir)
+    FileUtils.mkdir_p(File.dirname(fstab))
+    File.write(
+      fstab,
+      "UUID=1234  /  btrfs  defaults  0  0\nUUID=5678  /var  xfs  defaults  0  0\n"
+    )
+  end
+
+  after do
+    FileUtils.remove_entry(destdir)
+  end
+
+  describe "#enabled?" do
+    it "returns whether the root file system is read-only" do
+      expect(subject.enabled?).to eq(true)
+      settings.read_only_root = false
+      expect(described_class.new(settings, logger).enabled?).to eq(false)
+      expect(described_class.new(nil, logger).enabled?).to eq(false)
+    end
+  end
+
+  describe "#write" do
+    it "mounts the root file system read-only with an overlay on top of it" do
+      subject.write
+
+      expect(File.read(fstab)).to eq(
+        "UUID=1234  /  btrfs  ro  0  0\n" \
+        "UUID=5678  /var  xfs  defaults  0  0\n" \
+        "overlay  /  overlay  lowerdir=/,upperdir=/var/lib/overlay/upper," \
+        "workdir=/var/lib/overlay/work,x-systemd.requires-mounts-for=/var/lib/overlay  0  0\n"
+      )
+    end
+
+    it "creates the overlay directories" do
+      subject.write
+
+      expect(File.directory?(File.join(destdir, "var/lib/overlay/upper"))).to eq(true)
+      expect(File.directory?(File.join(destdir, "var/lib/overlay/work"))).to eq(true)
+    end
+  end
+end
@@ -101,6 +101,14 @@ describe Agama::Storage::VolumeConversions::ToY2Storage do
       end
     end
 
+    context "when the volume has a weight" do
+      before { volume.weight = 300 }
+
+      it "sets the weight of the spec" do
+        expect(subject.convert.weight).to eq(300)
+      end
+    end
+
     context "when the default target is used" do
       before { volume.location.target = :default }
 
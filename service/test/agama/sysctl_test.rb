@@ -0,0 +1,62 @@
+# frozen_string_literal: true
+
+# Copyright (c) [2024] SUSE LLC
+#
+# All Rights Reserved.
+#
+# This program is free software; you can redistribute it and/or modify it
+# under the terms of version 2 of the GNU General Public License as published
+# by the Free Software Foundation.
+#
+# This program is distributed in the hope that it will be useful, but WITHOUT
+# ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+# FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for
+# more details.
+#
+# You should have received a copy of the GNU General Public License along
+# with this program; if not, contact SUSE LLC.
+#
+# To contact SUSE LLC about this file by physical or electronic mail, you may
+# find current contact information at www.suse.com.
+
+
+require_relative "../test_helper"
+require "agama/sysctl"
+require "tmpdir"
+
+describe Agama::Sysctl do
+  subject { described_class.new(logger, tunables) }
+
+  let(:logger) { Logger.new($stdout, level: :warn) }
+  let(:destdir) { Dir.mktmpdir }
+  let(:sysctl_file) { File.join(destdir, "etc", "sysctl.d", "70-agama.conf") }
+
+  before do
+    allow(Yast::Installation).to receive(:destdir).and_return(destdir)
+  end
+
+  after do
+    FileUtils.remove_entry(destdir)
+  end
+
+  describe "#write" do
+    context "when there are tunables" do
+      let(:tunables) { { "vm.swappiness" => "10", "net.ipv4.ip_forward" => "1" } }
+
+      it "writes them to the sysctl.d directory of the target system" do
+        subject.write
+        lines = File.read(sysctl_file).lines.map(&:chomp)
+        expect(lines).to include("vm.swappiness = 10", "net.ipv4.ip_forward = 1")
+      end
+    end
+
+    context "when there are no tunables" do
+      let(:tunables) { {} }
+
+      it "does not write the file" do
+        subject.write
+        expect(File).to_not exist(sysctl_file)
+      end
+    end
+  end
+end
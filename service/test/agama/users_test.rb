@@ -107,6 +107,27 @@ describe Agama::Users do
         issues = subject.assign_first_user("Jane Doe", "jane", "12345", false, {})
         expect(issues).to be_empty
       end
+
+      context "when a shell is given" do
+        it "sets the user's shell" do
+          subject.assign_first_user("Jane Doe", "jane", "12345", false,
+            { "Shell" => "/usr/bin/zsh" })
+          user = users_config.users.by_name("jane")
+          expect(user.shell).to eq("/usr/bin/zsh")
+        end
+      end
+
+      context "when the IDs, the home directory and the expiration policy are given" do
+        it "sets them" do
+          subject.assign_first_user("Jane Doe", "jane", "12345", false,
+            { "UID" => 1001, "GID" => 2000, "Home" => "/srv/jane", "Locked" => true })
+          user = users_config.users.by_name("jane")
+          expect(user.uid).to eq("1001")
+          expect(user.gid).to eq("2000")
+          expect(user.home.path).to eq("/srv/jane")
+          expect(subject.first_user_expiration).to eq("Locked" => true)
+        end
+      end
     end
 
     context "when the given arguments presents some critical error" do
@@ -126,6 +147,20 @@ describe Agama::Users do
     end
   end
 
+  describe "#default_shell=" do
+    it "sets the shell for the new users" do
+      subject.default_shell = "/usr/bin/zsh"
+      expect(subject.default_shell).to eq("/usr/bin/zsh")
+      expect(users_config.useradd.shell).to eq("/usr/bin/zsh")
+    end
+
+    it "unsets the shell when an empty string is given" do
+      subject.default_shell = "/usr/bin/zsh"
+      subject.default_shell = ""
+      expect(subject.default_shell).to eq("")
+    end
+  end
+
   describe "#remove_first_user" do
     before do
       subject.assign_first_user("Jane Doe", "jane", "12345", false, {})
@@ -138,6 +173,21 @@ describe Agama::Users do
     end
   end
 
+  describe "#load_groups_json" do
+    it "sets the groups to create" do
+      result = subject.load_groups_json('[{ "name": "developers", "members": ["jane"] }]')
+      expect(result).to eq(true)
+      expect(subject.groups).to eq([{ "name" => "developers", "members" => ["jane"] }])
+    end
+
+    it "rejects the documents which are not lists of groups" do
+      expect(subject.load_groups_json('{ "name": "developers" }')).to eq(false)
+      expect(subject.load_groups_json('[{ "gid": 2000 }]')).to eq(false)
+      expect(subject.load_groups_json("[")).to eq(false)
+      expect(subject.groups).to eq([])
+    end
+  end
+
   describe "#write" do
     let(:writer) { instance_double(Y2Users::Linux::Writer, write: issues) }
     let(:issues) { [] }
@@ -168,6 +218,62 @@ describe Agama::Users do
       subject.write
     end
 
+    context "when groups are defined" do
+      before do
+        system_config.attach(Y2Users::Group.new("wheel"))
+        subject.load_groups_json(
+          '[{ "name": "developers", "gid": 2000, "members": ["jane"] }, ' \
+          '{ "name": "wheel", "members": ["jane"] }]'
+        )
+      end
+
+      it "creates the new groups and adds the members to the existing ones" do
+        expect(Y2Users::Linux::Writer).to receive(:new) do |target_config, _old_config|
+          developers = target_config.groups.by_name("developers")
+          expect(developers.gid).to eq("2000")
+          expect(developers.users_name).to eq(["jane"])
+          expect(target_config.groups.by_name("wheel").users_name).to eq(["jane"])
+          writer
+        end
+
+        subject.write
+      end
+    end
+
+    context "when the first user sets its IDs" do
+      before do
+        system_config.users.by_name("messagebus").uid = "1001"
+        subject.assign_first_user("Jane Doe", "jane", "12345", false,
+          { "UID" => 1001, "GID" => 2000 })
+      end
+
+      it "drops the UID used by another user and creates the primary group" do
+        expect(Y2Users::Linux::Writer).to receive(:new) do |target_config, _old_config|
+          expect(target_config.users.by_name("jane").uid).to be_nil
+          expect(target_config.groups.by_name("jane").gid).to eq("2000")
+          writer
+        end
+
+        subject.write
+      end
+    end
+
+    context "when the first user has an expiration policy" do
+      before do
+        allow(Yast::Execute).to receive(:on_target!)
+        subject.assign_first_user("Jane Doe", "jane", "12345", false,
+          { "PasswordExpireDays" => 90, "AccountExpireDate" => "2030-01-01", "Locked" => true })
+      end
+
+      it "applies it in the target system" do
+        expect(Yast::Execute).to receive(:on_target!)
+          .with("chage", "--maxdays", "90", "--expiredate", "2030-01-01", "jane")
+        expect(Yast::Execute).to receive(:on_target!).with("usermod", "--lock", "jane")
+
+        subject.write
+      end
+    end
+
     context "if some issue occurs" do
       let(:issues) { [double("issue")] }
 
@@ -31,8 +31,10 @@ const startProbing = () => post("/api/manager/probe");
  * Starts the installation process.
  *
  * The progress of the installation process can be tracked through installer signals.
+ *
+ * @param token - token confirming the storage impact (see fetchImpactToken)
  */
-const startInstallation = () => post("/api/manager/install");
+const startInstallation = (token: string) => post("/api/manager/install", { token });
 
 /**
  * Clean-up when installation is done.
@@ -57,4 +57,13 @@ const refresh = async (): Promise<void> => {
   await calculate(settings);
 };
 
-export { fetchStorageJobs, findStorageJob, refresh };
+/**
+ * Returns the token confirming the current impact of the storage proposal
+ *
+ * The token is required to start the installation. It is refused if the
+ * proposal changes afterwards.
+ */
+const fetchImpactToken = (): Promise<string> =>
+  get("/api/storage/impact").then(({ token }) => token);
+
+export { fetchStorageJobs, findStorageJob, refresh, fetchImpactToken };
@@ -90,7 +90,7 @@ const login = async (node: ISCSINode, options: LoginOptions) => {
   } catch (error) {
     const { data: reason } = error.response;
     console.warn("Could not login into the iSCSI node:", error.message, "reason:", reason);
-    return reason === "InvalidStartup" ? 1 : 2;
+    return reason?.code === "InvalidStartup" ? 1 : 2;
   }
 };
 
@@ -189,7 +189,19 @@ export type LoginParams = ISCSIAuth & {
   startup: string;
 };
 
-export type LoginResult = "Success" | "InvalidStartup" | "Failed";
+export type LoginResult =
+  | "Success"
+  | "InvalidStartup"
+  | "Failed"
+  | "AuthenticationFailed"
+  | "TargetNotFound"
+  | "NetworkUnreachable"
+  | "AlreadyLoggedIn";
+
+export type LoginError = {
+  code: LoginResult;
+  message?: string;
+};
 
 export type LvmLv = {
   volumeGroup: DeviceSid;
@@ -256,7 +268,7 @@ export type ProposalSettings = {
   defaultBootDevice: string;
   encryptionMethod: string;
   encryptionPBKDFunction: string;
-  encryptionPassword: string;
+  encryptionPasswordSet: boolean;
   spaceActions: Array<SpaceActionSettings>;
   spacePolicy: string;
   target: ProposalTarget;
@@ -29,7 +29,12 @@ const mockStartInstallationFn = jest.fn();
 
 jest.mock("~/api/manager", () => ({
   ...jest.requireActual("~/api/manager"),
-  startInstallation: () => mockStartInstallationFn(),
+  startInstallation: (token) => mockStartInstallationFn(token),
+}));
+
+jest.mock("~/api/storage", () => ({
+  ...jest.requireActual("~/api/storage"),
+  fetchImpactToken: () => Promise.resolve("s3cr3t"),
 }));
 
 describe("when the button is clicked and there are not errors", () => {
@@ -40,7 +45,7 @@ describe("when the button is clicked and there are not errors", () => {
 
     const continueButton = await screen.findByRole("button", { name: "Continue" });
     await user.click(continueButton);
-    expect(mockStartInstallationFn).toHaveBeenCalled();
+    expect(mockStartInstallationFn).toHaveBeenCalledWith("s3cr3t");
   });
 
   it("does not start the installation if the user cancels", async () => {
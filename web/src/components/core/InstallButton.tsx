@@ -27,6 +27,7 @@ import { Button, Stack } from "@patternfly/react-core";
 import { Popup } from "~/components/core";
 import { _ } from "~/i18n";
 import { startInstallation } from "~/api/manager";
+import { fetchImpactToken } from "~/api/storage";
 
 const InstallConfirmationPopup = ({ onAccept, onClose }) => {
   return (
@@ -69,8 +70,13 @@ according to the provided installation settings.",
  */
 const InstallButton = () => {
   const [isOpen, setIsOpen] = useState(false);
+  const [token, setToken] = useState<string>();
 
-  const open = async () => setIsOpen(true);
+  // the token confirms the storage changes the user is reviewing
+  const open = async () => {
+    setToken(await fetchImpactToken());
+    setIsOpen(true);
+  };
   const close = () => setIsOpen(false);
 
   return (
@@ -80,7 +86,9 @@ const InstallButton = () => {
         {_("Install")}
       </Button>
 
-      {isOpen && <InstallConfirmationPopup onAccept={startInstallation} onClose={close} />}
+      {isOpen && (
+        <InstallConfirmationPopup onAccept={() => startInstallation(token)} onClose={close} />
+      )}
     </>
   );
 };
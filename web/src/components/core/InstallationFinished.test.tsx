@@ -27,7 +27,7 @@ import { plainRender } from "~/test-utils";
 import { EncryptionMethods } from "~/types/storage";
 import InstallationFinished from "./InstallationFinished";
 
-let mockEncryptionPassword: string;
+let mockEncryptionPasswordSet: boolean;
 let mockEncryptionMethod: string;
 
 jest.mock("~/queries/status", () => ({
@@ -40,7 +40,7 @@ jest.mock("~/queries/storage", () => ({
   useProposalResult: () => ({
     settings: {
       encryptionMethod: mockEncryptionMethod,
-      encryptionPassword: mockEncryptionPassword,
+      encryptionPasswordSet: mockEncryptionPasswordSet,
     },
   }),
 }));
@@ -56,7 +56,7 @@ jest.mock("~/components/core/InstallerOptions", () => () => <div>Installer Optio
 
 describe("InstallationFinished", () => {
   beforeEach(() => {
-    mockEncryptionPassword = "n0tS3cr3t";
+    mockEncryptionPasswordSet = true;
     mockEncryptionMethod = EncryptionMethods.LUKS2;
   });
 
@@ -91,7 +91,7 @@ describe("InstallationFinished", () => {
 
     describe("but encryption was not set", () => {
       beforeEach(() => {
-        mockEncryptionPassword = "";
+        mockEncryptionPasswordSet = false;
       });
 
       it("does not show the TPM reminder", async () => {
@@ -78,9 +78,9 @@ const SuccessIcon = () => <Icon name="check_circle" className="icon-xxxl color-s
 function InstallationFinished() {
   const { useIguana } = useInstallerStatus({ suspense: true });
   const {
-    settings: { encryptionPassword, encryptionMethod },
+    settings: { encryptionPasswordSet, encryptionMethod },
   } = useProposalResult();
-  const usingTpm = encryptionPassword?.length > 0 && encryptionMethod === EncryptionMethods.TPM;
+  const usingTpm = encryptionPasswordSet && encryptionMethod === EncryptionMethods.TPM;
 
   return (
     <SimpleLayout showOutlet={false}>
@@ -64,6 +64,8 @@ export type EncryptionConfig = {
 
 export type EncryptionFieldProps = {
   password?: string;
+  /** Whether the encryption is enabled, as the password is not sent back by the server */
+  isEnabled?: boolean;
   method?: string;
   methods?: string[];
   isLoading?: boolean;
@@ -76,13 +78,17 @@ export type EncryptionFieldProps = {
  */
 export default function EncryptionField({
   password = "",
+  isEnabled: initiallyEnabled,
   method = "",
   // FIXME: should be available methods actually a prop?
   methods = [],
   isLoading = false,
   onChange = noop,
 }: EncryptionFieldProps) {
-  const validPassword = useCallback(() => password?.length > 0, [password]);
+  const validPassword = useCallback(
+    () => password?.length > 0 || !!initiallyEnabled,
+    [password, initiallyEnabled],
+  );
   const [isEnabled, setIsEnabled] = useState(validPassword());
   const [isDialogOpen, setIsDialogOpen] = useState(false);
 
@@ -114,7 +114,7 @@ const mockProposalResult: ProposalResult = {
     configureBoot: false,
     bootDevice: "",
     defaultBootDevice: "",
-    encryptionPassword: "",
+    encryptionPasswordSet: false,
     encryptionMethod: "",
     spacePolicy: "",
     spaceActions: [],
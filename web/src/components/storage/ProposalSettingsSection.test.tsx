@@ -38,7 +38,7 @@ beforeEach(() => {
       configureBoot: false,
       bootDevice: "",
       defaultBootDevice: "",
-      encryptionPassword: "",
+      encryptionPasswordSet: false,
       encryptionMethod: "",
       spacePolicy: "delete",
       spaceActions: [],
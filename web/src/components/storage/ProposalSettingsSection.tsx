@@ -112,9 +112,10 @@ export default function ProposalSettingsSection({
       <GridItem sm={12} xl2={6}>
         <EncryptionField
           password={settings.encryptionPassword || ""}
+          isEnabled={settings.encryptionPasswordSet}
           method={settings.encryptionMethod}
           methods={encryptionMethods}
-          isLoading={settings.encryptionPassword === undefined}
+          isLoading={settings.encryptionPasswordSet === undefined}
           onChange={changeEncryption}
         />
       </GridItem>
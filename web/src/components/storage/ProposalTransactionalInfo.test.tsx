@@ -41,7 +41,7 @@ const settings: ProposalSettings = {
   configureBoot: false,
   bootDevice: "",
   defaultBootDevice: "",
-  encryptionPassword: "",
+  encryptionPasswordSet: false,
   encryptionMethod: "",
   spacePolicy: "delete",
   spaceActions: [],
@@ -57,6 +57,8 @@ type SoftwareConfig = {
   product?: string;
   /** An object where the keys are the pattern names and the values whether to install them or not */
   patterns?: { [key: string]: boolean };
+  /** Whether to install only the required packages, skipping the recommended ones */
+  onlyRequired?: boolean;
 };
 
 type Pattern = {
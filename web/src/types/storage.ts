@@ -104,7 +104,8 @@ type ProposalSettings = {
   configureBoot: boolean;
   bootDevice: string;
   defaultBootDevice: string;
-  encryptionPassword: string;
+  encryptionPassword?: string;
+  encryptionPasswordSet: boolean;
   encryptionMethod: string;
   encryptionPBKDFunction?: string;
   spacePolicy: string;